@@ -0,0 +1,103 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+//! Benchmark for the hand-over of records from application threads to the Coaly worker thread.
+//! Measures the throughput of the log macros with an increasing number of threads, all threads
+//! issuing records concurrently. The throughput should grow with the number of threads until
+//! the number of CPU cores is reached, instead of collapsing due to lock contention.
+//! The benchmark has a main function of its own instead of using a benchmark framework,
+//! because Coaly is initialized and shut down only once per process, and the figure of interest
+//! is the scaling over the thread counts rather than the duration of a single iteration.
+//! Run with `cargo bench --bench agent_contention`.
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+use coaly::*;
+
+// number of records issued by every thread per measurement
+const RECORDS_PER_THREAD: usize = 20_000;
+
+// numbers of concurrently logging threads to measure
+const THREAD_COUNTS: [usize; 7] = [1, 2, 4, 8, 16, 32, 64];
+
+// configuration writing all records to a plain file in the system's temporary directory
+const CONFIG: &str = r#"
+[system]
+output_path = "$Env[COALY_BENCH_DIR]"
+[system.mode]
+enabled = [ "all" ]
+buffered = [ ]
+[[resources]]
+kind = "file"
+levels = [ "all" ]
+name = "agent_contention.log"
+"#;
+
+/// Lets the given number of threads issue records concurrently.
+///
+/// # Return values
+/// the time needed by the slowest thread to hand over all its records
+fn measure(thread_count: usize) -> Duration {
+    let barrier = Arc::new(Barrier::new(thread_count));
+    let workers: Vec<thread::JoinHandle<Duration>> = (0 .. thread_count).map(|_| {
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            // register thread with Coaly before measurement starts
+            logdebug!("thread started");
+            barrier.wait();
+            let start = Instant::now();
+            for i in 0 .. RECORDS_PER_THREAD { loginfo!("benchmark record {}", i); }
+            start.elapsed()
+        })
+    }).collect();
+    workers.into_iter().map(|w| w.join().unwrap()).max().unwrap_or_default()
+}
+
+fn main() {
+    let dir = std::env::temp_dir().join("coaly_bench");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var("COALY_BENCH_DIR", &dir);
+    coaly::initialize_from_str(CONFIG, None);
+    // warm up
+    measure(1);
+    println!("{:>8} {:>12} {:>16}", "threads", "time [ms]", "records/s");
+    for thread_count in THREAD_COUNTS {
+        let elapsed = measure(thread_count);
+        let records = (thread_count * RECORDS_PER_THREAD) as f64;
+        println!("{:>8} {:>12.1} {:>16.0}", thread_count, elapsed.as_secs_f64() * 1000.0,
+                 records / elapsed.as_secs_f64());
+    }
+    coaly::shutdown();
+    let _ = std::fs::remove_dir_all(&dir);
+}
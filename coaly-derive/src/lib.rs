@@ -0,0 +1,190 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Derive macros for Coaly.
+//! Derive macro `CoalyObservable` implements the trait of the same name for structures holding
+//! a Coaly observer and generates an associated function creating the observer, so that
+//! application objects can be observed without boilerplate code.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Index, LitStr, Member, Type};
+
+/// Derives trait `CoalyObservable` for a structure.
+/// The structure must contain a field of type `CoalyObserver`, which is returned by the trait
+/// method `coaly_observer`.
+/// Additionally, an associated function `new_coaly_observer` is generated, that creates the
+/// observer using the structure name as observer name. If attribute
+/// `#[coaly(value = "field")]` is specified for the structure, the function expects a reference
+/// to the given field's value, which is used as observer value. The field's type must implement
+/// `Display`.
+///
+/// # Example
+/// ```ignore
+/// #[derive(CoalyObservable)]
+/// #[coaly(value = "id")]
+/// struct Order {
+///     id: String,
+///     obs: CoalyObserver
+/// }
+/// impl Order {
+///     fn new(id: &str) -> Order {
+///         let id = id.to_string();
+///         Order { obs: Order::new_coaly_observer(&id), id }
+///     }
+/// }
+/// ```
+#[proc_macro_derive(CoalyObservable, attributes(coaly))]
+pub fn derive_coaly_observable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_coaly_observable(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into()
+    }
+}
+
+/// Generates the code for derive macro `CoalyObservable`.
+///
+/// # Arguments
+/// * `input` - the parsed structure definition
+///
+/// # Errors
+/// Returns a compile error, if the input is not a structure containing a Coaly observer or
+/// attribute `coaly` is invalid
+fn expand_coaly_observable(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let fields = match &input.data {
+        Data::Struct(s) => &s.fields,
+        _ => return Err(Error::new_spanned(input, ERR_NOT_A_STRUCT))
+    };
+    let obs_field = match observer_field(fields) {
+        Some(f) => f,
+        None => return Err(Error::new_spanned(input, ERR_NO_OBSERVER_FIELD))
+    };
+    let value_field = value_field_name(input)?;
+    let struct_name = &input.ident;
+    let obs_name = struct_name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let new_fn = match value_field {
+        Some(name) => {
+            let value_ty = match field_type(fields, &name.value()) {
+                Some(t) => t,
+                None => return Err(Error::new_spanned(name, ERR_UNKNOWN_VALUE_FIELD))
+            };
+            quote! {
+                /// Creates the Coaly observer for a new instance of this structure.
+                /// Observer name is the structure name, observer value the given field value.
+                pub fn new_coaly_observer(value: &#value_ty) -> ::coaly::CoalyObserver {
+                    let value = value.to_string();
+                    ::coaly::CoalyObserver::for_obj(#obs_name, Some(value.as_str()),
+                                                    ::std::file!(), ::std::line!())
+                }
+            }
+        },
+        None => {
+            quote! {
+                /// Creates the Coaly observer for a new instance of this structure.
+                /// Observer name is the structure name.
+                pub fn new_coaly_observer() -> ::coaly::CoalyObserver {
+                    ::coaly::CoalyObserver::for_obj(#obs_name, None,
+                                                    ::std::file!(), ::std::line!())
+                }
+            }
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::coaly::CoalyObservable for #struct_name #ty_generics #where_clause {
+            fn coaly_observer(&self) -> &::coaly::CoalyObserver { &self.#obs_field }
+        }
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #new_fn
+        }
+    })
+}
+
+/// Returns the member holding the Coaly observer, i.e. the first field of type `CoalyObserver`.
+fn observer_field(fields: &Fields) -> Option<Member> {
+    for (i, f) in fields.iter().enumerate() {
+        if let Type::Path(tp) = &f.ty {
+            if tp.path.segments.last().is_some_and(|s| s.ident == OBSERVER_TYPE_NAME) {
+                return Some(match &f.ident {
+                    Some(id) => Member::Named(id.clone()),
+                    None => Member::Unnamed(Index::from(i))
+                })
+            }
+        }
+    }
+    None
+}
+
+/// Returns the type of the field with the given name.
+/// For tuple structures, the name is the field index.
+fn field_type<'a>(fields: &'a Fields, name: &str) -> Option<&'a Type> {
+    for (i, f) in fields.iter().enumerate() {
+        match &f.ident {
+            Some(id) if id == name => return Some(&f.ty),
+            None if i.to_string() == name => return Some(&f.ty),
+            _ => ()
+        }
+    }
+    None
+}
+
+/// Returns the field name specified in structure attribute `#[coaly(value = "field")]`.
+///
+/// # Errors
+/// Returns a compile error, if the attribute contains anything else
+fn value_field_name(input: &DeriveInput) -> Result<Option<LitStr>, Error> {
+    let mut value_field: Option<LitStr> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident(ATTR_COALY)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(ATTR_VALUE) {
+                value_field = Some(meta.value()?.parse()?);
+                return Ok(())
+            }
+            Err(meta.error(ERR_UNKNOWN_ATTR))
+        })?;
+    }
+    if let Some(ref f) = value_field {
+        if f.value().is_empty() { return Err(Error::new(Span::call_site(), ERR_EMPTY_VALUE)) }
+    }
+    Ok(value_field)
+}
+
+const ATTR_COALY: &str = "coaly";
+const ATTR_VALUE: &str = "value";
+const OBSERVER_TYPE_NAME: &str = "CoalyObserver";
+const ERR_NOT_A_STRUCT: &str = "CoalyObservable can only be derived for structures";
+const ERR_NO_OBSERVER_FIELD: &str = "CoalyObservable requires a field of type CoalyObserver";
+const ERR_UNKNOWN_VALUE_FIELD: &str = "field specified as observer value does not exist";
+const ERR_UNKNOWN_ATTR: &str = "unsupported coaly attribute, expected value = \"field\"";
+const ERR_EMPTY_VALUE: &str = "field name for observer value must not be empty";
@@ -0,0 +1,76 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Tests for derive macro CoalyObservable.
+
+use coaly::*;
+
+#[derive(CoalyObservable)]
+#[coaly(value = "id")]
+struct Order {
+    id: u32,
+    obs: CoalyObserver
+}
+impl Order {
+    fn new(id: u32) -> Order { Order { obs: Order::new_coaly_observer(&id), id } }
+}
+
+#[derive(CoalyObservable)]
+struct Session(String, CoalyObserver);
+impl Session {
+    fn new(user: &str) -> Session { Session(user.to_string(), Session::new_coaly_observer()) }
+}
+
+#[derive(CoalyObservable)]
+#[coaly(value = "0")]
+struct Request<T: std::fmt::Display>(T, CoalyObserver);
+
+#[test]
+fn test_derive_with_value() {
+    let order = Order::new(4711);
+    assert_eq!(4711, order.id);
+    let _ = order.coaly_observer();
+    logobj!(&order, "order created");
+}
+
+#[test]
+fn test_derive_without_value() {
+    let session = Session::new("admin");
+    assert_eq!("admin", session.0);
+    let _ = session.coaly_observer();
+}
+
+#[test]
+fn test_derive_generic_tuple() {
+    let req = Request(42, Request::<i32>::new_coaly_observer(&42));
+    let _ = req.coaly_observer();
+}
@@ -41,8 +41,19 @@ pub fn main() {
     loginfo!("Coaly demo started");
     for i in 121..=124 {
         let my_order = Order::new(&i.to_string());
+        // written for the first order only
+        loginfo_once!("Processing orders starting with {}", i);
         mymod::process(&my_order);
     }
+    // a task token may be moved into a job executed by another thread, records from that
+    // thread are attributed to the task's scope until the token is closed
+    let mut task = newcoalytask!("invoice", "124");
+    let job = std::thread::spawn(move || {
+        task.attach();
+        loginfo!("Invoice created");
+        task.close();
+    });
+    let _ = job.join();
     loginfo!("Coaly demo terminated");
     coaly::shutdown();
 }
@@ -85,5 +96,7 @@ mod mymod {
         // again, function entry/exit and debug message is logged for order '123' only
         logfn!("mymod::calc", order);
         logdebug!("calc");
+        // written for every second order only
+        log_every!(2, RecordLevelId::Info, "calc invoked for {}", order);
     }
 }
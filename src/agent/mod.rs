@@ -31,26 +31,43 @@
 // -----------------------------------------------------------------------------------------------
 
 //! The local agent is the central trace and log management instance within a process.
+//!
+//! Application threads never format records or perform any output themselves.
+//! Functions like [`write()`] merely pass an event to a dedicated worker thread, which is
+//! spawned once per process. The worker thread applies the output formats and writes to the
+//! output resources, hence slow file or network I/O doesn't affect the latency of the
+//! application threads.
 
 extern crate chrono;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::sync::mpsc::{channel, Sender};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::thread;
-use std::time::Instant;
-use crate::{coalyxe, CoalyObservable};
+use std::time::{Duration, Instant};
+use crate::{coalyxe, coalyxw, CoalyObservable};
 use crate::config;
+use crate::config::ConfigSource;
+use crate::counter;
 use crate::errorhandling::*;
 use crate::event::CoalyEvent;
 use crate::observer::ObserverData;
+use crate::output::forecast::RolloverForecast;
 use crate::record::RecordLevelId;
+use crate::record::recorddata::SourceUnit;
 use crate::util;
 
 #[cfg(feature="net")]
 use std::net::SocketAddr;
 
+#[cfg(any(feature="log-compat", feature="tracing"))]
+use std::sync::RwLock;
+
+#[cfg(any(feature="log-compat", feature="tracing"))]
+use crate::config::adapter::{find_adapter_mapping, AdapterMappingDescList};
+
 #[cfg(feature="net")]
 use crate::record::originator::OriginatorInfo;
 
@@ -60,11 +77,20 @@ use crate::record::recorddata::RemoteRecordData;
 mod threadstatus;
 mod worker;
 
+pub use threadstatus::ThreadWriteStats;
+
 lazy_static! {
     /// Singleton instance of local agent
     static ref LOCAL_AGENT: Arc<Mutex<CoalyAgent>> = Arc::new(Mutex::new(CoalyAgent::new()));
 }
 
+#[cfg(any(feature="log-compat", feature="tracing"))]
+lazy_static! {
+    /// Adapter mappings of the active configuration, shared with all application threads
+    static ref ADAPTER_MAPPINGS: RwLock<Arc<AdapterMappingDescList>> =
+        RwLock::new(Arc::new(AdapterMappingDescList::new()));
+}
+
 /// Initializes the local agent.
 /// 
 /// If the function has not been called prior to any message output, the system will assume
@@ -75,7 +101,185 @@ lazy_static! {
 /// # Arguments
 /// * `config_file_name` - the name of the configuration file
 pub fn initialize(config_file_name: &str) {
-    if let Ok(mut agent) = LOCAL_AGENT.try_lock() { agent.configure(config_file_name); }
+    let source = ConfigSource::File(config_file_name.to_string());
+    if let Ok(mut agent) = LOCAL_AGENT.try_lock() { agent.configure(source, None); }
+}
+
+/// Initializes the local agent with a configuration from TOML formatted text.
+///
+/// Calling the function for an already initialized system has no effect.
+///
+/// # Arguments
+/// * `toml_text` - the TOML formatted configuration
+/// * `overlay_file_name` - the name of an optional configuration file overlaying the text
+pub fn initialize_from_str(toml_text: &str, overlay_file_name: Option<&str>) {
+    let source = ConfigSource::Text(toml_text.to_string(), overlay_file_name.map(String::from));
+    if let Ok(mut agent) = LOCAL_AGENT.try_lock() { agent.configure(source, None); }
+}
+
+/// Initializes the local agent and waits until the configuration has been processed.
+///
+/// Calling the function for an already initialized system has no effect.
+///
+/// # Arguments
+/// * `config_file_name` - the name of the configuration file
+///
+/// # Errors
+/// Returns an error structure, if a file resource configured to fail initialization couldn't
+/// be created. The system uses default settings in this case.
+pub fn try_initialize(config_file_name: &str) -> Result<(), CoalyException> {
+    try_configure(ConfigSource::File(config_file_name.to_string()))
+}
+
+/// Initializes the local agent with a configuration from TOML formatted text and waits until
+/// the configuration has been processed.
+///
+/// Calling the function for an already initialized system has no effect.
+///
+/// # Arguments
+/// * `toml_text` - the TOML formatted configuration
+/// * `overlay_file_name` - the name of an optional configuration file overlaying the text
+///
+/// # Errors
+/// Returns an error structure, if a file resource configured to fail initialization couldn't
+/// be created. The system uses default settings in this case.
+pub fn try_initialize_from_str(toml_text: &str,
+                               overlay_file_name: Option<&str>) -> Result<(), CoalyException> {
+    try_configure(ConfigSource::Text(toml_text.to_string(), overlay_file_name.map(String::from)))
+}
+
+/// Sends a configure event to the worker thread and waits for the result.
+///
+/// # Arguments
+/// * `source` - the configuration source
+///
+/// # Errors
+/// Returns an error structure, if a file resource configured to fail initialization couldn't
+/// be created.
+fn try_configure(source: ConfigSource) -> Result<(), CoalyException> {
+    let (sender, receiver) = channel::<Result<(), CoalyException>>();
+    if let Ok(mut agent) = LOCAL_AGENT.lock() {
+        agent.configure(source, Some(sender));
+    } else {
+        return Ok(())
+    }
+    match receiver.recv_timeout(Duration::from_secs(INITIALIZE_TIMEOUT)) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            Err(coalyxe!(E_INTERNAL_EVENT_FAILED, RecvTimeoutError::Timeout.to_string()))
+        },
+        // no descriptor for calling thread, system is shutting down
+        Err(RecvTimeoutError::Disconnected) => Ok(())
+    }
+}
+
+/// Replaces the active configuration of the local agent.
+/// 
+/// # Arguments
+/// * `config_file_name` - the name of the configuration file
+pub fn reconfigure(config_file_name: &str) {
+    let source = ConfigSource::File(config_file_name.to_string());
+    if let Ok(mut agent) = LOCAL_AGENT.lock() { agent.reconfigure(source); }
+}
+
+/// Returns the forecasts for the next rollover of all file based output resources.
+/// Waits until the worker thread has processed all events sent before by the calling thread.
+///
+/// # Return values
+/// the rollover forecasts; an empty array, if the worker thread doesn't answer in time
+pub fn rollover_forecast() -> Vec<RolloverForecast> {
+    if let Some(thread_desc) = app_thread_desc() {
+        let (sender, receiver) = channel::<Vec<RolloverForecast>>();
+        thread_desc.send(CoalyEvent::for_rollover_forecast(sender));
+        return receiver.recv_timeout(Duration::from_secs(FORECAST_TIMEOUT)).unwrap_or_default()
+    }
+    Vec::new()
+}
+
+/// Returns the numbers of records written and suppressed by all application threads.
+/// Waits until the worker thread has processed all events sent before by the calling thread.
+///
+/// # Return values
+/// the statistics ordered by thread ID; an empty array, if the worker thread doesn't answer
+/// in time
+pub fn thread_write_stats() -> Vec<ThreadWriteStats> {
+    if let Some(thread_desc) = app_thread_desc() {
+        let (sender, receiver) = channel::<Vec<ThreadWriteStats>>();
+        thread_desc.send(CoalyEvent::for_thread_write_stats(sender));
+        return receiver.recv_timeout(Duration::from_secs(STATS_TIMEOUT)).unwrap_or_default()
+    }
+    Vec::new()
+}
+
+/// Adds a mode change at runtime.
+/// The specification is a TOML inline table with the same parameters as an entry in group
+/// modes of the configuration file, e.g.
+/// `{ trigger = "object", name = "customer", value = "^4711$", enabled = ["all"] }`.
+/// Mode changes added at runtime take precedence over the ones from the configuration file,
+/// the latest one first, and are retained upon reconfiguration. They affect observers created
+/// after the call only.
+///
+/// # Arguments
+/// * `spec` - the mode change specification
+///
+/// # Errors
+/// Returns an error structure, if the specification can't be parsed or is invalid
+pub fn add_mode_change(spec: &str) -> Result<(), CoalyException> {
+    let mode_changes = config::runtime_mode_change(spec)?;
+    if let Some(thread_desc) = app_thread_desc() {
+        thread_desc.send(CoalyEvent::for_add_mode_change(mode_changes));
+    }
+    Ok(())
+}
+
+/// Removes all mode changes added at runtime.
+/// Observers created before the call keep the mode they activated until they are dropped.
+pub fn clear_mode_changes() {
+    if let Some(thread_desc) = app_thread_desc() {
+        thread_desc.send(CoalyEvent::for_clear_mode_changes());
+    }
+}
+
+/// Opens the file handles and network sockets of all output resources anew.
+/// Waits until the worker thread has processed all events sent before by the calling thread.
+///
+/// # Errors
+/// Returns an array with error structures for all resources that couldn't be opened
+pub fn reopen_resources() -> Result<(), Vec<CoalyException>> {
+    if let Some(thread_desc) = app_thread_desc() {
+        let (sender, receiver) = channel::<Vec<CoalyException>>();
+        thread_desc.send(CoalyEvent::for_reopen_resources(sender));
+        return match receiver.recv_timeout(Duration::from_secs(REOPEN_TIMEOUT)) {
+            Ok(problems) if problems.is_empty() => Ok(()),
+            Ok(problems) => Err(problems),
+            Err(RecvTimeoutError::Timeout) => {
+                Err(vec!(coalyxe!(E_INTERNAL_EVENT_FAILED, RecvTimeoutError::Timeout.to_string())))
+            },
+            // system is shutting down
+            Err(RecvTimeoutError::Disconnected) => Ok(())
+        }
+    }
+    Ok(())
+}
+
+/// Changes the output directory for files created from now on.
+/// The directory is created, if it doesn't exist.
+///
+/// # Arguments
+/// * `path` - the absolute path of the new output directory
+///
+/// # Errors
+/// Returns an error structure, if the path is not absolute, can't be created or is not a
+/// directory
+pub fn set_output_path(path: &str) -> Result<(), CoalyException> {
+    let dir = PathBuf::from(path);
+    if ! dir.is_absolute() || std::fs::create_dir_all(&dir).is_err() || ! dir.is_dir() {
+        return Err(coalyxe!(E_INV_OUTPUT_PATH, path.to_string()))
+    }
+    if let Some(thread_desc) = app_thread_desc() {
+        thread_desc.send(CoalyEvent::for_change_output_path(dir));
+    }
+    Ok(())
 }
 
 /// Terminates the local agent.
@@ -90,33 +294,186 @@ pub fn shutdown() {
 /// * `level` - the record level
 /// * `file_name` - the name of the source code file, where the message was issued
 /// * `line_nr` - the line number in the source code file, where the message was issued
+/// * `unit` - module path and function, where the message was issued
 /// * `msg` - the log or trace message
 pub fn write(level: RecordLevelId,
              file_name: &'static str,
              line_nr: u32,
+             unit: SourceUnit,
              msg: &str) {
+    crate::testing::check_record(level, file_name, line_nr, msg);
     if let Some(thread_desc) = app_thread_desc() {
         let event = CoalyEvent::for_msg(thread_desc.id, &thread_desc.name,
-                                        level, file_name, line_nr, msg);
+                                        level, file_name, line_nr, unit, msg);
         thread_desc.send(event);
     }
 }
 
+/// Processes a log or trace record with a message created by the given closure.
+/// The closure is invoked only, if at least one output resource accepts the record level,
+/// hence neither the message arguments are evaluated nor the message is formatted for
+/// disabled levels. Used by the log macros.
+/// If the closure panics, the panic is caught and a placeholder message noting the failure is
+/// written instead of the record.
+/// Panics can't be caught, if the application is built with panic strategy abort.
+/// 
+/// # Arguments
+/// * `level` - the record level
+/// * `file_name` - the name of the source code file, where the message was issued
+/// * `line_nr` - the line number in the source code file, where the message was issued
+/// * `unit` - module path and function, where the message was issued
+/// * `msg` - the closure creating the log or trace message
+pub fn write_lazy<F: FnOnce() -> String>(level: RecordLevelId,
+                                         file_name: &'static str,
+                                         line_nr: u32,
+                                         unit: SourceUnit,
+                                         msg: F) {
+    if ! must_render(level) {
+        pipetrace::level_not_accepted(level, file_name, line_nr);
+        return
+    }
+    write(level, file_name, line_nr, unit, &render_msg(msg));
+}
+
+/// Indicates whether at least one output resource of the active configuration accepts records
+/// with the given level.
+/// Before the configuration has been processed, all levels are considered accepted.
+/// 
+/// # Arguments
+/// * `level` - the record level
+#[inline]
+pub fn level_accepted(level: RecordLevelId) -> bool {
+    ACCEPTED_LEVELS.load(Ordering::Relaxed) & level as u32 != 0
+}
+
+/// Stores the record levels accepted by at least one output resource of the active
+/// configuration.
+/// 
+/// # Arguments
+/// * `levels` - the bit mask with the accepted record levels
+#[inline]
+pub(crate) fn set_accepted_levels(levels: u32) {
+    ACCEPTED_LEVELS.store(levels, Ordering::Relaxed);
+}
+
+/// Indicates whether a record with given level must be formatted, i.e. whether it may be
+/// written to an output resource or is checked by a testing guard.
+/// 
+/// # Arguments
+/// * `level` - the record level
+#[inline]
+fn must_render(level: RecordLevelId) -> bool {
+    level_accepted(level) || crate::testing::checks_active()
+}
+
+/// Formats the given arguments.
+/// 
+/// # Arguments
+/// * `args` - the format arguments
+/// 
+/// # Return values
+/// the formatted arguments, a placeholder message if the formatting panicked
+#[cfg(feature="log-compat")]
+pub(crate) fn render_args(args: std::fmt::Arguments) -> String {
+    render_msg(|| std::fmt::format(args))
+}
+
+/// Creates a message using the given closure.
+/// 
+/// # Arguments
+/// * `msg` - the closure creating the message
+/// 
+/// # Return values
+/// the message, a placeholder message if the closure panicked
+fn render_msg<F: FnOnce() -> String>(msg: F) -> String {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(msg)) {
+        Ok(msg) => msg,
+        Err(payload) => {
+            let reason = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                                .or_else(|| payload.downcast_ref::<String>().cloned())
+                                .unwrap_or_default();
+            coalyxw!(W_FMT_ARGS_PANICKED, reason).localized_message()
+        }
+    }
+}
+
+/// Increments the event counter with the given name.
+/// No event is sent to the worker thread, the counts of all counters are written periodically
+/// as a single record.
+/// 
+/// # Arguments
+/// * `name` - the counter name
+/// * `n` - the number of events to add
+#[inline]
+pub fn count(name: &'static str, n: u64) { counter::increment(name, n); }
+
+/// Processes a record passed to Coaly by the log crate or tracing bridges.
+/// The first adapter mapping of the active configuration matching the record's target and
+/// foreign level is applied. If the mapping specifies a unit, the record is written within a
+/// module observer with the unit name, hence mode changes configured for the unit apply.
+/// 
+/// # Arguments
+/// * `target` - the record's target, usually the module path of the issuing crate
+/// * `adapter_level` - the bit value of the record's level in the foreign facade
+/// * `level` - the Coaly record level to use, if no mapping specifies a different one
+/// * `file_name` - the name of the source code file, where the message was issued
+/// * `line_nr` - the line number in the source code file, where the message was issued
+/// * `source_unit` - module path and function, where the message was issued
+/// * `msg` - the log or trace message
+#[cfg(any(feature="log-compat", feature="tracing"))]
+pub(crate) fn write_adapted(target: &str,
+                            adapter_level: u32,
+                            level: RecordLevelId,
+                            file_name: &'static str,
+                            line_nr: u32,
+                            source_unit: SourceUnit,
+                            msg: &str) {
+    let mappings = match ADAPTER_MAPPINGS.read() {
+        Ok(m) => m.clone(),
+        Err(_) => return write(level, file_name, line_nr, source_unit, msg)
+    };
+    let Some(mapping) = find_adapter_mapping(&mappings, target, adapter_level) else {
+        return write(level, file_name, line_nr, source_unit, msg)
+    };
+    let level = mapping.level().unwrap_or(level);
+    match mapping.unit() {
+        Some(unit) => {
+            let obs_data = ObserverData::for_mod(unit, file_name);
+            observer_created(&obs_data, line_nr);
+            write(level, file_name, line_nr, source_unit, msg);
+            observer_dropped(&obs_data);
+        },
+        None => write(level, file_name, line_nr, source_unit, msg)
+    }
+}
+
+/// Replaces the adapter mappings used for records from the log crate or tracing bridges.
+/// Called by the worker thread whenever a configuration is activated.
+/// 
+/// # Arguments
+/// * `mappings` - the adapter mappings of the new configuration
+#[cfg(any(feature="log-compat", feature="tracing"))]
+fn set_adapter_mappings(mappings: &AdapterMappingDescList) {
+    if let Ok(mut m) = ADAPTER_MAPPINGS.write() { *m = Arc::new(mappings.clone()); }
+}
+
 /// Processes a log or trace record according to the specified behaviour.
 /// 
 /// # Arguments
 /// * `level` - the record level
 /// * `file_name` - the name of the source code file, where the message was issued
 /// * `line_nr` - the line number in the source code file, where the message was issued
+/// * `unit` - module path and function, where the message was issued
 /// * `msg` - the log or trace message
 pub fn write_obs(observer: &dyn CoalyObservable,
                  file_name: &'static str,
                  line_nr: u32,
+                 unit: SourceUnit,
                  msg: &str) {
     if let Some(thread_desc) = app_thread_desc() {
         let obs_data = &observer.coaly_observer().0;
         let event = CoalyEvent::for_obs_msg(thread_desc.id, &thread_desc.name,
-                                            obs_data, file_name, line_nr, msg);
+                                            obs_data, file_name, line_nr, unit, msg);
         thread_desc.send(event);
     }
 }
@@ -309,10 +666,23 @@ impl CoalyAgent {
     /// Sends a configure event to the worker thread
     /// 
     /// # Arguments
-    /// * `config_file_name` - the name of the configuration file
-    fn configure(&mut self, config_file_name: &str) {
+    /// * `source` - the configuration source
+    /// * `reply_channel` - the channel where the worker thread shall send the result to
+    fn configure(&mut self,
+                 source: ConfigSource,
+                 reply_channel: Option<Sender<Result<(), CoalyException>>>) {
         if let Some(tdata) = self.desc_for(std::thread::current().id()) {
-            tdata.send(CoalyEvent::for_config(config_file_name));
+            tdata.send(CoalyEvent::for_config(source, reply_channel));
+        }
+    }
+
+    /// Sends a reconfigure event to the worker thread
+    /// 
+    /// # Arguments
+    /// * `source` - the configuration source
+    fn reconfigure(&mut self, source: ConfigSource) {
+        if let Some(tdata) = self.desc_for(std::thread::current().id()) {
+            tdata.send(CoalyEvent::for_reconfig(source));
         }
     }
 
@@ -332,18 +702,76 @@ impl CoalyAgent {
         };
         self.threads.get(&thread_id).cloned()
     }
+
+    /// Removes the descriptor for the application thread with given thread ID.
+    /// 
+    /// # Arguments
+    /// * `thread_id` - the (Rust) thread ID
+    /// 
+    /// # Return values
+    /// the removed application thread descriptor structure; None, if the thread is not known
+    fn release(&mut self, thread_id: &thread::ThreadId) -> Option<Arc<AppThreadDesc>> {
+        self.threads.remove(thread_id)
+    }
+}
+
+/// Handle of an application thread known to Coaly.
+/// Caches the thread's descriptor, so that records and observer events are handed over to the
+/// worker thread without locking the global agent. The agent is locked only upon the first
+/// access of the thread and upon its termination.
+/// Dropped when the thread terminates, regardless whether it ends normally or due to a panic.
+/// Informs the worker thread, which flushes and closes the thread-specific resources.
+struct AppThreadHandle {
+    // (Rust) thread ID of the application thread
+    thread_id: thread::ThreadId,
+    // descriptor of the application thread, registered with the agent upon first access
+    desc: RefCell<Option<Arc<AppThreadDesc>>>
+}
+impl AppThreadHandle {
+    /// Returns the descriptor for the application thread.
+    /// Descriptor structure is registered with the agent, if not done yet.
+    ///
+    /// # Return values
+    /// application thread descriptor structure; None, if the Coaly system is shutting down or
+    /// the internal descriptor table can't be locked
+    fn desc(&self) -> Option<Arc<AppThreadDesc>> {
+        if let Some(desc) = self.desc.borrow().as_ref() { return Some(desc.clone()) }
+        let desc = LOCAL_AGENT.lock().ok().and_then(|mut agent| agent.desc_for(self.thread_id))?;
+        self.desc.replace(Some(desc.clone()));
+        Some(desc)
+    }
+}
+impl Drop for AppThreadHandle {
+    fn drop(&mut self) {
+        let desc = LOCAL_AGENT.lock().ok().and_then(|mut agent| agent.release(&self.thread_id));
+        if let Some(thread_desc) = desc {
+            thread_desc.send(CoalyEvent::for_thread_exit(thread_desc.id));
+        }
+    }
+}
+
+thread_local! {
+    // handle of the application thread, created on first access
+    static APP_THREAD_HANDLE: AppThreadHandle = AppThreadHandle {
+                                                    thread_id: thread::current().id(),
+                                                    desc: RefCell::new(None)
+                                                };
 }
 
 /// Returns descriptor for the calling application thread needed to communicate with Coaly worker
 /// thread. Descriptor structure is created, if the calling thread is not yet known to Coaly.
+/// The descriptor is taken from the thread's handle, the global agent is locked only if the
+/// thread is unknown or terminating.
 /// 
 /// # Return values
 /// application thread descriptor structure; None, if the Coaly system is shutting down or
 /// the internal descriptor table can't be locked
 fn app_thread_desc() -> Option<Arc<AppThreadDesc>> {
+    if SHUTDOWN_PENDING.load(Ordering::Relaxed) { return None }
+    // fails only while the thread terminates
+    if let Ok(desc) = APP_THREAD_HANDLE.try_with(AppThreadHandle::desc) { return desc }
     let tid = std::thread::current().id();
-    let app_thread_table = LOCAL_AGENT.clone();
-    if let Ok(mut agent) = app_thread_table.lock() { return agent.desc_for(tid) }
+    if let Ok(mut agent) = LOCAL_AGENT.lock() { return agent.desc_for(tid) }
     None
 }
 
@@ -354,5 +782,152 @@ const INITIAL_SEND_ERRORS_TO_LOG: u64 = 5;
 // in seconds
 const SEND_ERROR_IGNORE_DURATION: u64 = 60;
 
+// maximum time to wait for the worker thread's answer to a rollover forecast request, in seconds
+const FORECAST_TIMEOUT: u64 = 5;
+
+// maximum time to wait for the worker thread's answer to an initialization request, in seconds
+const INITIALIZE_TIMEOUT: u64 = 30;
+
+// maximum time to wait for the worker thread's answer to a reopen resources request, in seconds
+const REOPEN_TIMEOUT: u64 = 30;
+
+// maximum time to wait for the worker thread's answer to a write statistics request, in seconds
+const STATS_TIMEOUT: u64 = 5;
+
 // shutdown indicator
 static SHUTDOWN_PENDING: AtomicBool = AtomicBool::new(false);
+
+// bit mask with the record levels accepted by at least one output resource
+static ACCEPTED_LEVELS: AtomicU32 = AtomicU32::new(u32::MAX);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::{Display, Formatter};
+    use crate::agent;
+    use crate::CoalyObserver;
+
+    struct Faulty;
+    impl Display for Faulty {
+        fn fmt(&self, _f: &mut Formatter<'_>) -> std::fmt::Result { panic!("faulty display") }
+    }
+
+    struct Observed { observer: CoalyObserver }
+    impl CoalyObservable for Observed {
+        fn coaly_observer(&self) -> &CoalyObserver { &self.observer }
+    }
+
+    #[cfg(feature="log-compat")]
+    #[test]
+    /// Tests formatting of record arguments, where a Display implementation panics
+    fn test_render_args() {
+        assert_eq!("value 42", render_args(format_args!("value {}", 42)));
+        let msg = render_args(format_args!("value {}", Faulty));
+        assert!(msg.ends_with("faulty display"));
+    }
+
+    #[test]
+    /// Tests creation of record messages, where a Display implementation panics
+    fn test_render_msg() {
+        assert_eq!("value 42", render_msg(|| format!("value {}", 42)));
+        let msg = render_msg(|| format!("value {}", Faulty));
+        assert!(msg.ends_with("faulty display"));
+    }
+
+    #[test]
+    /// Tests that message arguments are evaluated only for levels accepted by at least one
+    /// output resource
+    fn test_write_lazy() {
+        // the macros don't issue any records, if a max_level feature strips the levels used
+        let used_levels = RecordLevelId::Debug as u32 | RecordLevelId::Info as u32;
+        if crate::STATIC_LEVELS & used_levels != used_levels { return }
+        let _lock = crate::testing::tests::CHECKS_LOCK.lock();
+        let evaluated = AtomicU32::new(0);
+        let eval = || evaluated.fetch_add(1, Ordering::SeqCst);
+        set_accepted_levels(RecordLevelId::All as u32 & ! (RecordLevelId::Debug as u32));
+        crate::logdebug!("not accepted {}", eval());
+        write_lazy(RecordLevelId::Debug, file!(), line!(), SourceUnit::default(),
+                   || format!("not accepted {}", eval()));
+        assert_eq!(0, evaluated.load(Ordering::SeqCst));
+        crate::loginfo!("accepted {}", eval());
+        assert_eq!(1, evaluated.load(Ordering::SeqCst));
+        set_accepted_levels(u32::MAX);
+        crate::logdebug!("accepted {}", eval());
+        assert_eq!(2, evaluated.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    /// Tests that object records are issued only, if level object isn't stripped by a
+    /// max_level feature
+    fn test_logobj() {
+        let observed = Observed {
+            observer: CoalyObserver::for_obj("observed", None, file!(), line!())
+        };
+        let evaluated = AtomicU32::new(0);
+        let obj = || { evaluated.fetch_add(1, Ordering::SeqCst); &observed };
+        crate::logobj!(obj(), "object record");
+        let expected = (crate::STATIC_LEVELS & RecordLevelId::Object as u32 != 0) as u32;
+        assert_eq!(expected, evaluated.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    /// Tests the call site specific latches of the once macros
+    fn test_log_once() {
+        // the macros don't issue any records, if a max_level feature strips the levels used
+        let used_levels = RecordLevelId::Info as u32 | RecordLevelId::Warning as u32;
+        if crate::STATIC_LEVELS & used_levels != used_levels { return }
+        let _lock = crate::testing::tests::CHECKS_LOCK.lock();
+        // a guard forces rendering of all records, regardless of the accepted levels
+        let _guard = crate::testing::deny_above(RecordLevelId::Emergency);
+        let evaluated = AtomicU32::new(0);
+        let eval = || evaluated.fetch_add(1, Ordering::SeqCst);
+        for _ in 0..3 { crate::loginfo_once!("first call site {}", eval()); }
+        assert_eq!(1, evaluated.load(Ordering::SeqCst));
+        // the latch of the first call site doesn't affect other call sites
+        for _ in 0..3 { crate::loginfo_once!("second call site {}", eval()); }
+        for _ in 0..3 { crate::logwarn_once!("third call site {}", eval()); }
+        assert_eq!(3, evaluated.load(Ordering::SeqCst));
+        // the latch belongs to the call site, not to the invocation
+        let issue = || crate::logwarn_once!("fourth call site {}", eval());
+        issue();
+        issue();
+        assert_eq!(4, evaluated.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    /// Tests the intervals of macro log_every
+    fn test_log_every() {
+        // the macro doesn't issue any records, if a max_level feature strips level info
+        if crate::STATIC_LEVELS & RecordLevelId::Info as u32 == 0 { return }
+        let _lock = crate::testing::tests::CHECKS_LOCK.lock();
+        // a guard forces rendering of all records, regardless of the accepted levels
+        let _guard = crate::testing::deny_above(RecordLevelId::Emergency);
+        let mut evaluated = Vec::<u32>::new();
+        for call in 1..=10 {
+            crate::log_every!(4, RecordLevelId::Info, "call {}", { evaluated.push(call); call });
+        }
+        assert_eq!(vec!(1, 5, 9), evaluated);
+        // interval 0 is treated like 1
+        let mut evaluated = Vec::<u32>::new();
+        for call in 1..=3 {
+            crate::log_every!(0, RecordLevelId::Info, "call {}", { evaluated.push(call); call });
+        }
+        assert_eq!(vec!(1, 2, 3), evaluated);
+    }
+
+    #[test]
+    /// Tests the descriptors cached in the application thread handles, every thread must
+    /// reuse its own descriptor and release it upon termination
+    fn test_app_thread_desc() {
+        let desc = app_thread_desc().unwrap();
+        assert!(Arc::ptr_eq(&desc, &app_thread_desc().unwrap()));
+        let (other_desc, other_tid) = thread::spawn(|| {
+            let d = app_thread_desc().unwrap();
+            assert!(Arc::ptr_eq(&d, &app_thread_desc().unwrap()));
+            (d, thread::current().id())
+        }).join().unwrap();
+        assert!(! Arc::ptr_eq(&desc, &other_desc));
+        assert_ne!(desc.id, other_desc.id);
+        assert!(! LOCAL_AGENT.lock().unwrap().threads.contains_key(&other_tid));
+    }
+}
@@ -33,10 +33,13 @@
 //! Status handling for every application thread.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use crate::collections::RecoverableStack;
 use crate::config::Configuration;
+use crate::context::ContextMap;
 use crate::modechange::OverrideModeMap;
 use crate::output::Interface;
+use crate::record::recorddata::RecordDecoration;
 
 /// Holds the data about a client thread as it is needed by the worker thread.
 pub(crate) struct ThreadStatus {
@@ -45,20 +48,139 @@ pub(crate) struct ThreadStatus {
     // Stack for output modes controlled by functions and modules
     unit_mode_stack: RecoverableStack<u32>,
     // List of output resources
-    pub(crate) output_interface: Interface
+    pub(crate) output_interface: Interface,
+    // Thread name, needed to recreate the output interface upon reconfiguration
+    thread_name: String,
+    // Context map from the thread's first record, needed to recreate the output interface
+    // upon reconfiguration
+    context: Option<ContextMap>,
+    // IDs of all active observers, outermost first
+    scope_ids: Vec<u64>,
+    // Names of all active observers, outermost first, shared with the records
+    scope_names: Arc<Vec<String>>,
+    // Decorations of active functions and modules with matching mode changes, outermost first
+    decorations: Vec<(u64, Arc<RecordDecoration>)>,
+    // Number of records written since thread start
+    records_written: u64,
+    // Number of records suppressed by the output mode since thread start
+    records_suppressed: u64
 }
 impl ThreadStatus {
-    pub(crate) fn new(intf: Interface, config: &Configuration) -> ThreadStatus {
+    pub(crate) fn new(intf: Interface,
+                      thread_name: &str,
+                      context: Option<&ContextMap>,
+                      config: &Configuration) -> ThreadStatus {
         let st_size = config.system_properties().change_stack_size();
         let mut unit_mode_stack = RecoverableStack::<u32>::new(st_size, 256);
         unit_mode_stack.push(config.system_properties().initial_output_mode());
         ThreadStatus {
             obj_mode_map: OverrideModeMap::new(32768),
             unit_mode_stack,
-            output_interface: intf
+            output_interface: intf,
+            thread_name: thread_name.to_string(),
+            context: context.cloned(),
+            scope_ids: Vec::new(),
+            scope_names: Arc::new(Vec::new()),
+            decorations: Vec::new(),
+            records_written: 0,
+            records_suppressed: 0
         }
     }
 
+    /// Returns the thread name.
+    #[inline]
+    pub(crate) fn thread_name(&self) -> &str { &self.thread_name }
+
+    /// Returns the context map from the thread's first record.
+    #[inline]
+    pub(crate) fn context(&self) -> Option<&ContextMap> { self.context.as_ref() }
+
+    /// Returns the names of all active observers, outermost first.
+    #[inline]
+    pub(crate) fn scope_stack(&self) -> Arc<Vec<String>> { self.scope_names.clone() }
+
+    /// Returns the numbers of records written and suppressed by the output mode since
+    /// thread start.
+    #[inline]
+    pub(crate) fn write_counts(&self) -> (u64, u64) {
+        (self.records_written, self.records_suppressed)
+    }
+
+    /// Counts a record of the thread.
+    /// 
+    /// # Arguments
+    /// * `written` - **true** if the record is written, **false** if it was suppressed by
+    ///   the output mode
+    #[inline]
+    pub(crate) fn record_processed(&mut self, written: bool) {
+        if written {
+            self.records_written += 1;
+        } else {
+            self.records_suppressed += 1;
+        }
+    }
+
+    /// Returns the write statistics of the thread.
+    /// 
+    /// # Arguments
+    /// * `thread_id` - the thread's ID
+    pub(crate) fn write_stats(&self, thread_id: u64) -> ThreadWriteStats {
+        ThreadWriteStats {
+            thread_id,
+            thread_name: self.thread_name.clone(),
+            records_written: self.records_written,
+            records_suppressed: self.records_suppressed
+        }
+    }
+
+    /// Adds an observer to the active observers.
+    /// 
+    /// # Arguments
+    /// * `observer_id` - the observer's ID
+    /// * `observer_name` - the observer's name
+    pub(crate) fn scope_entered(&mut self, observer_id: u64, observer_name: &str) {
+        self.scope_ids.push(observer_id);
+        Arc::make_mut(&mut self.scope_names).push(observer_name.to_string());
+    }
+
+    /// Removes an observer from the active observers.
+    /// Observers are usually dropped in reverse order of their creation, but custom objects
+    /// may be dropped at any time.
+    /// 
+    /// # Arguments
+    /// * `observer_id` - the observer's ID
+    pub(crate) fn scope_left(&mut self, observer_id: u64) {
+        if let Some(pos) = self.scope_ids.iter().rposition(|id| *id == observer_id) {
+            self.scope_ids.remove(pos);
+            Arc::make_mut(&mut self.scope_names).remove(pos);
+        }
+        if let Some(pos) = self.decorations.iter().rposition(|(id, _)| *id == observer_id) {
+            self.decorations.remove(pos);
+        }
+    }
+
+    /// Adds the decoration for a function or module just entered.
+    /// The decoration is removed, when the observer leaves the active observers.
+    /// 
+    /// # Arguments
+    /// * `observer_id` - the observer's ID
+    /// * `decoration` - the decoration from the matching mode change
+    pub(crate) fn unit_decorated(&mut self, observer_id: u64, decoration: Arc<RecordDecoration>) {
+        self.decorations.push((observer_id, decoration));
+    }
+
+    /// Returns the decoration of the innermost active function or module having one.
+    #[inline]
+    pub(crate) fn decoration(&self) -> Option<Arc<RecordDecoration>> {
+        self.decorations.last().map(|(_, d)| d.clone())
+    }
+
+    /// Adopts the initial output mode from a new configuration.
+    /// Mode changes caused by active functions, modules or objects are retained.
+    pub(crate) fn reconfigure(&mut self, config: &Configuration) {
+        self.unit_mode_stack.replace_bottom(config.system_properties().initial_output_mode());
+    }
+
     /// Returns the active output mode.
     /// Mode changes triggered by custom objects have priority over functions and modules.
     /// 
@@ -78,7 +200,11 @@ impl ThreadStatus {
     }
 
     /// Removes the last mode change from the functions and modules stack.
-    pub(crate) fn unit_left(&mut self) { self.unit_mode_stack.pop(); }
+    /// The initial output mode is never removed, since a reconfiguration may cause
+    /// more units to be left than entered.
+    pub(crate) fn unit_left(&mut self) {
+        if self.unit_mode_stack.len() > 1 { self.unit_mode_stack.pop(); }
+    }
 
     /// Adds a mode change to the custom objects map.
     pub(crate) fn object_created(&mut self, observer_id: u64, mode: u32) -> u32 {
@@ -104,3 +230,34 @@ impl ThreadStatus {
     }
 }
 pub(crate) type ThreadStatusTable = HashMap<u64, ThreadStatus>;
+
+/// Numbers of records written and suppressed by an application thread since its start.
+/// Records are suppressed, if their record level is disabled in the thread's active output mode.
+#[derive(Clone, Debug)]
+pub struct ThreadWriteStats {
+    // Coaly's ID of the thread
+    thread_id: u64,
+    // thread name, defaults to the thread ID if not set by the application
+    thread_name: String,
+    // number of records written
+    records_written: u64,
+    // number of records suppressed by the output mode
+    records_suppressed: u64
+}
+impl ThreadWriteStats {
+    /// Returns the ID of the thread, as shown by placeholder variable $ThreadId.
+    #[inline]
+    pub fn thread_id(&self) -> u64 { self.thread_id }
+
+    /// Returns the name of the thread, as shown by placeholder variable $ThreadName.
+    #[inline]
+    pub fn thread_name(&self) -> &str { &self.thread_name }
+
+    /// Returns the number of records written since thread start.
+    #[inline]
+    pub fn records_written(&self) -> u64 { self.records_written }
+
+    /// Returns the number of records suppressed by the output mode since thread start.
+    #[inline]
+    pub fn records_suppressed(&self) -> u64 { self.records_suppressed }
+}
@@ -33,22 +33,35 @@
 //! Worker thread handling all events in the local Coaly agent.
 
 use chrono::{DateTime, Local};
+use std::path::Path;
 use std::rc::Rc;
-use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use crate::coalyxw;
+use crate::counter;
+use crate::datetime;
+use crate::degradation::{self, DegradationMonitor};
 use crate::errorhandling::*;
+use crate::errorhandling::reporting::set_problem_reporting;
 use crate::event::CoalyEvent;
 use crate::modechange::{ModeChangeDescList, OverrideModeMap};
+use crate::output::capture::CaptureTable;
+use crate::output::forecast::RolloverForecast;
 use crate::output::inventory::Inventory;
 use crate::output::standaloneinventory::StandaloneInventory;
 use crate::record::{RecordLevelId, RecordTrigger};
+use crate::record::backtrace::set_backtrace_levels;
 use crate::record::originator::OriginatorInfo;
+use crate::signal::{self, SIGNAL_ROLLOVER};
+use crate::stats;
 use crate::record::recorddata::{LocalRecordData, RecordData};
 use crate::util;
-use super::threadstatus::{ThreadStatus, ThreadStatusTable};
+use crate::variables::set_record_env_ttl;
+use super::threadstatus::{ThreadStatus, ThreadStatusTable, ThreadWriteStats};
 use super::config;
+use crate::config::ConfigSource;
+use crate::config::systemproperties::DEFAULT_COUNTER_INTERVAL;
 
 #[cfg(feature="net")]
 use std::collections::HashMap;
@@ -61,6 +74,8 @@ use crate::output::Interface;
 
 #[cfg(feature="net")]
 use crate::output::serverinventory::ServerInventory;
+#[cfg(feature="net")]
+use crate::stats::MetricsEndpoint;
 
 #[cfg(feature="net")]
 use crate::record::recorddata::RemoteRecordData;
@@ -100,8 +115,12 @@ pub(crate) fn spawn(rx_channel: Receiver<CoalyEvent>) -> thread::JoinHandle<()>
                                 worker.handle_timer_event(&now);
                             }
                         },
-                        CoalyEvent::Config(cfg_fn) => {
-                            worker.handle_config_event(&cfg_fn);
+                        CoalyEvent::Config((source, reply_channel)) => {
+                            let result = worker.handle_config_event(&source);
+                            if let Some(ch) = reply_channel { let _ = ch.send(result); }
+                        },
+                        CoalyEvent::Reconfig(source) => {
+                            worker.handle_reconfig_event(&source);
                         },
                         #[cfg(feature="net")]
                         CoalyEvent::RemoteClientConnected((addr, orig_info)) => {
@@ -111,6 +130,27 @@ pub(crate) fn spawn(rx_channel: Receiver<CoalyEvent>) -> thread::JoinHandle<()>
                         CoalyEvent::RemoteClientDisconnected(addr) => {
                             worker.handle_client_disconnected_event(addr);
                         },
+                        CoalyEvent::RolloverForecast(reply_channel) => {
+                            worker.handle_rollover_forecast_event(reply_channel);
+                        },
+                        CoalyEvent::ReopenResources(reply_channel) => {
+                            worker.handle_reopen_resources_event(reply_channel);
+                        },
+                        CoalyEvent::ChangeOutputPath(path) => {
+                            worker.handle_change_output_path_event(&path);
+                        },
+                        CoalyEvent::ThreadWriteStats(reply_channel) => {
+                            worker.handle_thread_write_stats_event(reply_channel);
+                        },
+                        CoalyEvent::AddModeChange(mode_changes) => {
+                            worker.handle_add_mode_change_event(mode_changes);
+                        },
+                        CoalyEvent::ClearModeChanges => {
+                            worker.handle_clear_mode_changes_event();
+                        },
+                        CoalyEvent::ThreadExit(thread_id) => {
+                            worker.handle_thread_exit_event(thread_id);
+                        },
                         CoalyEvent::Shutdown => {
                             worker.handle_shutdown_event();
                             break
@@ -143,6 +183,23 @@ struct Worker {
     res_inventory: Option<Box<dyn Inventory>>,
     // map for global output mode
     mode_map: OverrideModeMap,
+    // mode changes added at runtime, retained upon reconfiguration
+    runtime_mode_changes: ModeChangeDescList,
+    // mode changes in effect, runtime mode changes followed by the ones from the configuration
+    mode_changes: ModeChangeDescList,
+    // the configuration file watched for changes, if requested in the configuration
+    config_watch: Option<ConfigWatch>,
+    // point in time when the event counts were written the last time
+    last_counts_written: Instant,
+    // capture files of mode changes triggered by custom observer objects
+    captures: CaptureTable,
+    // runtime state of the degradation profile, if automatic degradation is configured
+    degradation: Option<DegradationMonitor>,
+    // point in time when the pipeline was checked for failures or overload the last time
+    last_degradation_check: Instant,
+    // the endpoint serving pipeline metrics, if requested in the configuration
+    #[cfg(feature="net")]
+    metrics_endpoint: Option<MetricsEndpoint>,
     // information about remote clients
     #[cfg(feature="net")]
     remote_clients: HashMap<SocketAddr, HashMap<u64, Interface>>,
@@ -156,6 +213,15 @@ impl Worker {
             originator: util::originator_info(),
             res_inventory: None,
             mode_map: OverrideModeMap::new(4096),
+            runtime_mode_changes: ModeChangeDescList::new(),
+            mode_changes: ModeChangeDescList::new(),
+            config_watch: None,
+            last_counts_written: Instant::now(),
+            captures: CaptureTable::new(),
+            degradation: None,
+            last_degradation_check: Instant::now(),
+            #[cfg(feature="net")]
+            metrics_endpoint: None,
             #[cfg(feature="net")]
             remote_clients: HashMap::new()
         }
@@ -171,27 +237,94 @@ impl Worker {
     /// 
     /// # Arguments
     /// * `record` - the record data
-    pub fn handle_local_record_event(&mut self, record: LocalRecordData) {
+    pub fn handle_local_record_event(&mut self, mut record: LocalRecordData) {
         if self.configuration.is_none() {
             // no need to update originator info here, since default config doesn't use
             // environment variables
-            self.configuration = Some(config::configuration(&self.originator, None));
+            self.set_configuration(config::configuration(&self.originator, None));
         }
         let cnf = &self.configuration.as_ref().unwrap().clone();
         if self.res_inventory.is_none() {
             self.res_inventory = Some(StandaloneInventory::new(cnf, &self.originator));
         }
+        let degraded_mask = self.degraded_mask();
+        let trigger_suppressed = self.trigger_suppressed(&record);
         let inv = self.res_inventory.as_mut().unwrap();
         let tid = record.thread_id();
         let tname = record.thread_name();
+        let context = record.context();
         let ts =
             self.thread_states.entry(tid)
-                .or_insert_with(|| ThreadStatus::new(inv.local_thread_interface(tid, tname),
-                                                     cnf));
-        let current_mode = determine_mode(&mut self.mode_map, ts, cnf.mode_changes(), &record);
-        if record.level() as u32 & current_mode == 0 { return }
-        let use_buffering = (record.level() as u32) & (current_mode >> 16) != 0;
-        if let Err(m) = ts.output_interface.write(&record, use_buffering) { log_problems(&m); }
+                .or_insert_with(|| ThreadStatus::new(inv.local_thread_interface(tid, tname,
+                                                                                context),
+                                                     tname, context, cnf));
+        if record.trigger() == RecordTrigger::ObserverCreated {
+            let obs_name = record.observer_name().as_deref().unwrap_or_default();
+            ts.scope_entered(record.observer_id(), obs_name);
+            if record.level() != RecordLevelId::Object {
+                let decoration = self.mode_changes
+                                    .local_decoration_for_unit(record.observer_name().as_deref());
+                if let Some(d) = decoration { ts.unit_decorated(record.observer_id(), d); }
+            } else {
+                let obs_value = record.message().as_deref();
+                let capture = self.mode_changes.capture_for_obj(record.observer_id(),
+                                                                record.observer_name().as_deref(),
+                                                                obs_value);
+                if let Some(c) = capture {
+                    if let Err(ex) = self.captures.acquire(c, record.observer_id(), obs_name,
+                                                           obs_value.unwrap_or_default(), tid,
+                                                           tname, context, cnf,
+                                                           &self.originator) {
+                        log_problems(&[ex]);
+                    }
+                }
+            }
+        }
+        record.set_scope_stack(ts.scope_stack());
+        record.set_decoration(ts.decoration());
+        let mode = determine_mode(&mut self.mode_map, ts, &self.mode_changes, &record);
+        let current_mode = mode & degraded_mask;
+        let suppressed = cnf.system_properties().suppresses(&record);
+        let enabled = record.level() as u32 & current_mode != 0 &&
+                      ! suppressed &&
+                      ! trigger_suppressed;
+        if record.traced() && ! enabled {
+            let level = record.level() as u32;
+            let reason = if level & mode == 0 { W_PIPE_LEVEL_DISABLED }
+                         else if level & current_mode == 0 { W_PIPE_DEGRADED }
+                         else if suppressed { W_PIPE_SUPPRESSED }
+                         else { W_PIPE_TRIGGER_SUPPRESSED };
+            pipetrace::decision(&record, reason);
+        }
+        ts.record_processed(enabled);
+        if enabled {
+            stats::record_written(record.level());
+            let (written, suppressed) = ts.write_counts();
+            record.set_thread_write_counts(written, suppressed);
+            let use_buffering = (record.level() as u32) & (current_mode >> 16) != 0;
+            if let Err(m) = ts.output_interface.write(&record, use_buffering) { log_problems(&m); }
+            if let Err(m) = self.captures.write(&record) { log_problems(&m); }
+        }
+        if record.trigger() == RecordTrigger::ObserverDropped {
+            ts.scope_left(record.observer_id());
+            if record.level() == RecordLevelId::Object {
+                self.captures.release(tid, record.observer_id());
+            }
+        }
+    }
+
+    /// Indicates, whether the output of a function or module creation or drop record is
+    /// suppressed by a matching mode change descriptor.
+    /// 
+    /// # Arguments
+    /// * `record` - the record data
+    fn trigger_suppressed(&self, record: &LocalRecordData) -> bool {
+        if record.trigger() == RecordTrigger::Message ||
+           record.level() == RecordLevelId::Object { return false }
+        let suppressed = self.mode_changes
+                             .local_suppressed_triggers_for_unit(record.observer_name()
+                                                                       .as_deref());
+        suppressed & record.trigger() as u32 != 0
     }
 
     /// Handles a record event from a client thread.
@@ -208,6 +341,9 @@ impl Worker {
     pub fn handle_remote_record_event(&mut self,
                                       client_addr: SocketAddr,
                                       record: RemoteRecordData) {
+        if record.level() as u32 & self.degraded_mask() == 0 { return }
+        if self.configuration.as_ref()
+               .is_some_and(|c| c.system_properties().suppresses(&record)) { return }
         if let Some(client_info) = self.remote_clients.get_mut(&client_addr) {
             let tid = record.thread_id();
             let tname = record.thread_name();
@@ -217,75 +353,165 @@ impl Worker {
                                                               .unwrap()
                                                               .remote_thread_interface(&client_addr,
                                                                                        tid, tname));
+            stats::record_written(record.level());
             if let Err(m) = thread_if.write(&record, false) { log_problems(&m); }
         }
         // ignore records from unconnected clients
     }
 
     /// Handles a configuration event from a client thread.
-    /// Parses the specified configuration file and creates the corresponding structures.
+    /// Parses the specified configuration source and creates the corresponding structures.
     /// The caller must make sure that this function is invoked only once.
-    /// Uses default configuration if an error is encountered during configuration file processing.
+    /// Uses default configuration if an error is encountered during configuration processing.
     /// 
     /// # Arguments
-    /// * `config_file_name` - the name of the configuration file
+    /// * `source` - the configuration source
+    ///
+    /// # Errors
+    /// Returns an error structure if a file resource configured to fail initialization couldn't
+    /// be created. Default configuration is used in this case.
     #[cfg(not(feature="net"))]
     pub fn handle_config_event(&mut self,
-                               config_file_name: &str) {
+                               source: &ConfigSource) -> Result<(), CoalyException> {
         if self.res_inventory.is_none() {
-            let cnf = config::configuration(&self.originator, Some(config_file_name));
-            self.originator.set_application_id(cnf.system_properties().application_id());
-            self.originator.set_application_name(cnf.system_properties().application_name());
-            for ev_name in cnf.referenced_env_vars() {
-                if let Ok(ev_val) = std::env::var(&ev_name) {
-                    self.originator.add_env_var(&ev_name, &ev_val);
-                }
+            let cnf = config::source_configuration(&self.originator, source);
+            self.update_originator(&cnf);
+            configure_problem_reporting(&cnf);
+            log_config_issues(&cnf, &source.name());
+            let mut inv = StandaloneInventory::new(&cnf, &self.originator);
+            if let Some(ex) = inv.take_startup_failure() {
+                return self.use_default_config(inv, ex)
             }
-            let msgs = cnf.messages();
-            if ! msgs.is_empty() {
-                let header_msg = coalyxw!(E_CFG_FOUND_ISSUES, config_file_name.to_string());
-                let mut emsgs = msgs.clone();
-                emsgs.insert(0, header_msg);
-                log_problems(&emsgs);
-            }
-            self.res_inventory = Some(StandaloneInventory::new(&cnf, &self.originator));
-            self.configuration = Some(cnf);
+            #[cfg(any(feature="log-compat", feature="tracing"))]
+            super::set_adapter_mappings(cnf.adapter_mappings());
+            self.res_inventory = Some(inv);
+            self.watch_config_file(source, &cnf);
+            self.monitor_degradation(&cnf);
+            install_signal_handlers(&cnf);
+            self.set_configuration(cnf);
         };
+        Ok(())
     }
 
     /// Handles a configuration event from a client thread.
-    /// Parses the specified configuration file and creates the corresponding structures.
+    /// Parses the specified configuration source and creates the corresponding structures.
     /// The caller must make sure that this function is invoked only once.
-    /// Uses default configuration if an error is encountered during configuration file processing.
+    /// Uses default configuration if an error is encountered during configuration processing.
     /// 
     /// # Arguments
-    /// * `config_file_name` - the name of the configuration file
+    /// * `source` - the configuration source
+    ///
+    /// # Errors
+    /// Returns an error structure if a file resource configured to fail initialization couldn't
+    /// be created. Default configuration is used in this case.
     #[cfg(feature="net")]
     pub fn handle_config_event(&mut self,
-                               config_file_name: &str) {
+                               source: &ConfigSource) -> Result<(), CoalyException> {
         if self.res_inventory.is_none() {
-            let cnf = config::configuration(&self.originator, Some(config_file_name));
-            self.originator.set_application_id(cnf.system_properties().application_id());
-            self.originator.set_application_name(cnf.system_properties().application_name());
-            for ev_name in cnf.referenced_env_vars() {
-                if let Ok(ev_val) = std::env::var(&ev_name) {
-                    self.originator.add_env_var(&ev_name, &ev_val);
-                }
-            }
-            let msgs = cnf.messages();
-            if ! msgs.is_empty() {
-                let header_msg = coalyxw!(E_CFG_FOUND_ISSUES, config_file_name.to_string());
-                let mut emsgs = msgs.clone();
-                emsgs.insert(0, header_msg);
-                log_problems(&emsgs);
-            }
+            let cnf = config::source_configuration(&self.originator, source);
+            self.update_originator(&cnf);
+            configure_problem_reporting(&cnf);
+            log_config_issues(&cnf, &source.name());
             if cnf.server_properties().is_none() {
-                self.res_inventory = Some(StandaloneInventory::new(&cnf, &self.originator));
+                let mut inv = StandaloneInventory::new(&cnf, &self.originator);
+                if let Some(ex) = inv.take_startup_failure() {
+                    return self.use_default_config(inv, ex)
+                }
+                self.res_inventory = Some(inv);
             } else {
                 self.res_inventory = Some(ServerInventory::new(&cnf, &self.originator));
             }
-            self.configuration = Some(cnf);
+            #[cfg(any(feature="log-compat", feature="tracing"))]
+            super::set_adapter_mappings(cnf.adapter_mappings());
+            self.watch_config_file(source, &cnf);
+            self.monitor_degradation(&cnf);
+            self.serve_metrics(&cnf);
+            install_signal_handlers(&cnf);
+            self.set_configuration(cnf);
         };
+        Ok(())
+    }
+
+    /// Discards the inventory created from a configuration file, because a file resource
+    /// configured to fail initialization couldn't be created, and uses default configuration
+    /// instead.
+    ///
+    /// # Arguments
+    /// * `inv` - the inventory created from the configuration file
+    /// * `failure` - the error describing the resource creation failure
+    ///
+    /// # Errors
+    /// Always returns the given error structure
+    fn use_default_config(&mut self,
+                          mut inv: Box<StandaloneInventory>,
+                          failure: CoalyException) -> Result<(), CoalyException> {
+        inv.close(None);
+        log_problems(std::slice::from_ref(&failure));
+        let cnf = config::configuration(&self.originator, None);
+        self.res_inventory = Some(StandaloneInventory::new(&cnf, &self.originator));
+        self.set_configuration(cnf);
+        Err(failure)
+    }
+
+    /// Handles a reconfiguration event from a client thread.
+    /// Parses the specified configuration source and replaces the active configuration.
+    /// Output resources are closed and created anew only if settings relevant for output
+    /// have changed.
+    /// The active configuration remains unchanged if the configuration source can't be processed.
+    /// 
+    /// # Arguments
+    /// * `source` - the configuration source
+    pub fn handle_reconfig_event(&mut self,
+                                 source: &ConfigSource) {
+        if self.configuration.is_none() {
+            let _ = self.handle_config_event(source);
+            return
+        }
+        let cnf = match config::reconfiguration(&self.originator, source) {
+            Ok(cnf) => cnf,
+            Err(ex) => {
+                log_problems(&[ex]);
+                return
+            }
+        };
+        configure_problem_reporting(&cnf);
+        log_config_issues(&cnf, &source.name());
+        let prev_cnf = self.configuration.as_ref().unwrap().clone();
+        if cnf.output_settings_differ(&prev_cnf) {
+            #[cfg(feature="net")]
+            if prev_cnf.server_properties().is_some() || cnf.server_properties().is_some() {
+                log_problems(&[coalyxw!(W_CFG_RECONFIG_SERVER_OUTPUT, source.name())]);
+                configure_problem_reporting(&prev_cnf);
+                return
+            }
+            if let Some(ref mut inv) = self.res_inventory.take() { inv.close(None); }
+            self.update_originator(&cnf);
+            let mut inv = StandaloneInventory::new(&cnf, &self.originator);
+            let failure = inv.take_startup_failure();
+            if let Some(ex) = &failure {
+                // keep previous configuration
+                inv.close(None);
+                log_problems(std::slice::from_ref(ex));
+                configure_problem_reporting(&prev_cnf);
+                self.update_originator(&prev_cnf);
+                inv = StandaloneInventory::new(&prev_cnf, &self.originator);
+            }
+            for (tid, ts) in self.thread_states.iter_mut() {
+                ts.output_interface = inv.local_thread_interface(*tid, ts.thread_name(),
+                                                                 ts.context());
+            }
+            self.res_inventory = Some(inv);
+            if failure.is_some() { return }
+        }
+        #[cfg(any(feature="log-compat", feature="tracing"))]
+        super::set_adapter_mappings(cnf.adapter_mappings());
+        for ts in self.thread_states.values_mut() { ts.reconfigure(&cnf); }
+        self.watch_config_file(source, &cnf);
+        self.monitor_degradation(&cnf);
+        #[cfg(feature="net")]
+        self.serve_metrics(&cnf);
+        install_signal_handlers(&cnf);
+        self.set_configuration(cnf);
     }
 
     /// Handles a connect event from a remote client.
@@ -313,20 +539,328 @@ impl Worker {
         let _ = self.remote_clients.remove(&client_addr);
     }
 
+    /// Handles a rollover forecast request from a client thread.
+    /// Sends the forecasts for all file based resources to the requesting thread.
+    ///
+    /// # Arguments
+    /// * `reply_channel` - the channel where to send the forecasts to
+    pub fn handle_rollover_forecast_event(&mut self,
+                                          reply_channel: Sender<Vec<RolloverForecast>>) {
+        let forecasts = self.res_inventory.as_ref()
+                                          .map_or_else(Vec::new, |inv| inv.rollover_forecast());
+        let _ = reply_channel.send(forecasts);
+    }
+
+    /// Handles a write statistics request from a client thread.
+    /// Sends the statistics of all threads having issued records to the requesting thread,
+    /// ordered by thread ID.
+    ///
+    /// # Arguments
+    /// * `reply_channel` - the channel where to send the statistics to
+    pub fn handle_thread_write_stats_event(&mut self,
+                                           reply_channel: Sender<Vec<ThreadWriteStats>>) {
+        let mut stats: Vec<ThreadWriteStats> = self.thread_states.iter()
+                                                   .map(|(tid, ts)| ts.write_stats(*tid))
+                                                   .collect();
+        stats.sort_by_key(|s| s.thread_id());
+        let _ = reply_channel.send(stats);
+    }
+
+    /// Handles a mode change added at runtime by a client thread.
+    /// Mode changes added at runtime take precedence over the ones from the configuration,
+    /// the latest one first. They affect observers created afterwards only.
+    ///
+    /// # Arguments
+    /// * `mode_changes` - the mode change descriptors to add
+    pub fn handle_add_mode_change_event(&mut self, mode_changes: ModeChangeDescList) {
+        self.runtime_mode_changes = mode_changes.followed_by(&self.runtime_mode_changes);
+        self.update_mode_changes();
+    }
+
+    /// Handles a request from a client thread to remove all mode changes added at runtime.
+    pub fn handle_clear_mode_changes_event(&mut self) {
+        self.runtime_mode_changes = ModeChangeDescList::new();
+        self.update_mode_changes();
+    }
+
+    /// Handles the termination of a client thread.
+    /// Removes the thread's runtime state, flushes and closes all thread-specific resources
+    /// no longer used by any other thread. Capture files used by the thread expire after their
+    /// time to live.
+    ///
+    /// # Arguments
+    /// * `thread_id` - the terminated thread's ID
+    pub fn handle_thread_exit_event(&mut self, thread_id: u64) {
+        self.captures.release_thread(thread_id);
+        if self.thread_states.remove(&thread_id).is_none() { return }
+        if let Some(ref mut inv) = self.res_inventory { inv.release_thread_resources(); }
+    }
+
+    /// Handles a request from a client thread to reopen all output resources.
+    /// Sends the errors that occurred to the requesting thread.
+    ///
+    /// # Arguments
+    /// * `reply_channel` - the channel where to send the errors to
+    pub fn handle_reopen_resources_event(&mut self,
+                                         reply_channel: Sender<Vec<CoalyException>>) {
+        let problems = self.res_inventory.as_mut()
+                                         .map_or_else(Vec::new, |inv| inv.reopen_resources());
+        if ! problems.is_empty() { log_problems(&problems); }
+        let _ = reply_channel.send(problems);
+    }
+
+    /// Handles a request from a client thread to change the output directory for files.
+    ///
+    /// # Arguments
+    /// * `path` - the new output directory
+    pub fn handle_change_output_path_event(&mut self, path: &Path) {
+        if let Some(ref mut inv) = self.res_inventory { inv.change_output_dir(path); }
+    }
+
     /// Handles a shutdown event from a client thread.
     /// Executes configured actions upon application exit like buffer flushes, if any.
-    /// Closes all output resources.
+    /// Closes all output resources, resources with higher flush priority first.
+    /// If a shutdown timeout is configured, resources not flushed when the timeout has
+    /// expired are closed without flush.
     pub fn handle_shutdown_event(&mut self) {
-        if let Some(ref mut inv) = self.res_inventory.take() { inv.close(); }
+        let timeout = self.configuration.as_ref()
+                                        .map_or(0, |c| c.system_properties().shutdown_timeout());
+        let deadline = if timeout > 0 { Some(Instant::now() + Duration::from_millis(timeout)) }
+                       else { None };
+        self.write_event_counts();
+        self.captures.close(deadline);
+        if let Some(ref mut inv) = self.res_inventory.take() { inv.close(deadline); }
+        #[cfg(feature="net")]
+        { self.metrics_endpoint = None; }
     }
 
     /// Handles a periodic timer event, issued every second.
     /// Informs all resources in inventory to perform a file rollover if it is due.
+    /// Closes all capture files, whose time to live has expired.
+    /// Writes the event counts, if the counter interval has expired.
+    /// Enters or leaves degraded mode, if required by the degradation profile.
     ///
     /// # Arguments
     /// * `now` - current timestamp
     pub fn handle_timer_event(&mut self, now: &DateTime<Local>) {
-        if let Some(ref mut inv) = self.res_inventory { inv.rollover_if_due(now); }
+        let signal_requests = signal::take_requests();
+        if let Some(ref mut inv) = self.res_inventory {
+            if signal_requests != 0 { inv.handle_signal(signal_requests & SIGNAL_ROLLOVER != 0); }
+            inv.rollover_if_due(now);
+        }
+        self.captures.close_expired(Instant::now());
+        let interval = self.configuration.as_ref()
+                                         .map_or(DEFAULT_COUNTER_INTERVAL as u64,
+                                                 |c| c.system_properties().counter_interval());
+        if self.last_counts_written.elapsed().as_secs() >= interval { self.write_event_counts(); }
+        self.check_degradation();
+        self.reconfigure_if_changed();
+    }
+
+    /// Returns the bit mask restricting the output mode in degraded mode, with all bits set in
+    /// normal mode.
+    fn degraded_mask(&self) -> u32 {
+        if ! degradation::is_degraded() { return u32::MAX }
+        let levels = self.degradation.as_ref()
+                                     .map_or(u32::MAX, |m| m.profile().enabled_levels());
+        (levels << 16) | levels
+    }
+
+    /// Checks the pipeline for output failures or overload and enters or leaves degraded mode,
+    /// if required by the degradation profile.
+    /// A warning record is written when entering degraded mode before the restrictions apply,
+    /// and when leaving degraded mode after the restrictions have been lifted.
+    fn check_degradation(&mut self) {
+        let elapsed = self.last_degradation_check.elapsed();
+        self.last_degradation_check = Instant::now();
+        let Some(ref mut mon) = self.degradation else { return };
+        let Some(degrade) = mon.check(stats::failure_count(), stats::processed_count(), elapsed)
+            else { return };
+        let (msg_id, secs) = if degrade { (W_DEGRADED_ENTERED, mon.profile().degrade_after()) }
+                             else { (W_DEGRADED_LEFT, mon.profile().recover_after()) };
+        let msg = coalyxw!(msg_id, secs.to_string()).localized_message();
+        if ! degrade { degradation::set_degraded(false); }
+        let (tid, tname) = util::thread_info();
+        let record = LocalRecordData::for_write(tid, &tname, RecordLevelId::Warning, file!(),
+                                                line!(), &msg);
+        self.handle_local_record_event(record);
+        if degrade { degradation::set_degraded(true); }
+    }
+
+    /// Installs the runtime state for the degradation profile in the given configuration.
+    /// Degraded mode is left, if it is active.
+    ///
+    /// # Arguments
+    /// * `cnf` - the configuration
+    fn monitor_degradation(&mut self, cnf: &config::Configuration) {
+        degradation::set_degraded(false);
+        self.last_degradation_check = Instant::now();
+        self.degradation = cnf.system_properties().degradation().as_ref()
+                              .map(|p| DegradationMonitor::new(p, stats::failure_count(),
+                                                               stats::processed_count()));
+    }
+
+    /// Writes the counts of all event counters since the last call as a single record with
+    /// level info.
+    /// The record is attributed to the worker thread and processed like a record from an
+    /// application thread. Nothing is written, if no event was counted.
+    fn write_event_counts(&mut self) {
+        self.last_counts_written = Instant::now();
+        let counts = counter::take_counts();
+        if counts.is_empty() { return }
+        let (tid, tname) = util::thread_info();
+        let record = LocalRecordData::for_write(tid, &tname, RecordLevelId::Info, file!(),
+                                                line!(), &counter::counts_message(&counts));
+        self.handle_local_record_event(record);
+    }
+
+    /// Starts or stops watching the configuration file for changes, depending on the watch flag
+    /// in the given configuration.
+    /// For a configuration from text, the overlay file is watched.
+    ///
+    /// # Arguments
+    /// * `source` - the configuration source
+    /// * `cnf` - the configuration read from the source
+    fn watch_config_file(&mut self, source: &ConfigSource, cnf: &config::Configuration) {
+        self.config_watch = if cnf.system_properties().watch() && source.file_name().is_some() {
+                                Some(ConfigWatch::new(source))
+                            } else { None };
+    }
+
+    /// Starts or stops the endpoint serving pipeline metrics, depending on the metrics address
+    /// in the given configuration. A running endpoint is kept, if the address didn't change.
+    ///
+    /// # Arguments
+    /// * `cnf` - the configuration
+    #[cfg(feature="net")]
+    fn serve_metrics(&mut self, cnf: &config::Configuration) {
+        let addr = cnf.system_properties().metrics_address();
+        if self.metrics_endpoint.as_ref().map(|e| e.address()) == addr { return }
+        // stop the running endpoint first, it may listen on the requested port
+        self.metrics_endpoint = None;
+        let Some(addr) = addr else { return };
+        match MetricsEndpoint::start(addr) {
+            Ok(ep) => self.metrics_endpoint = Some(ep),
+            Err(e) => log_problems(&[e])
+        }
+    }
+
+    /// Applies the configuration file anew, if it is watched and has been modified since the
+    /// last check.
+    /// If the modified file can't be processed, the active configuration remains unchanged and
+    /// the file is not processed again until it is modified once more.
+    fn reconfigure_if_changed(&mut self) {
+        let Some(ref mut watch) = self.config_watch else { return };
+        if ! watch.changed() { return }
+        let source = watch.source.clone();
+        self.handle_reconfig_event(&source);
+    }
+
+    /// Replaces the active configuration.
+    ///
+    /// # Arguments
+    /// * `cnf` - the new configuration
+    fn set_configuration(&mut self, cnf: Rc<config::Configuration>) {
+        set_record_env_ttl(cnf.system_properties().env_cache_ttl());
+        datetime::set_record_clock(cnf.system_properties().record_clock());
+        set_backtrace_levels(cnf.system_properties().backtrace_levels());
+        super::set_accepted_levels(cnf.resources().accepted_levels());
+        self.configuration = Some(cnf);
+        self.update_mode_changes();
+    }
+
+    /// Combines the mode changes added at runtime with the ones from the active configuration.
+    fn update_mode_changes(&mut self) {
+        if let Some(cnf) = self.configuration.as_ref() {
+            self.mode_changes = self.runtime_mode_changes.followed_by(cnf.mode_changes());
+        }
+    }
+
+    /// Updates application and environment information from the given configuration.
+    ///
+    /// # Arguments
+    /// * `cnf` - the configuration
+    fn update_originator(&mut self, cnf: &config::Configuration) {
+        self.originator.set_application_id(cnf.system_properties().application_id());
+        self.originator.set_application_name(cnf.system_properties().application_name());
+        for ev_name in cnf.referenced_env_vars() {
+            if let Ok(ev_val) = std::env::var(&ev_name) {
+                self.originator.add_env_var(&ev_name, &ev_val);
+            }
+        }
+    }
+}
+
+/// Modification state of a configuration file watched for changes.
+/// The file is polled upon every timer event, i.e. about once per second. Polling is used
+/// instead of operating system notifications, since editors often replace the file instead of
+/// modifying it, and the file may reside on a network share.
+struct ConfigWatch {
+    // the configuration source, must refer to a file
+    source: ConfigSource,
+    // the file's last modification time seen, None if the file couldn't be accessed
+    modified: Option<SystemTime>
+}
+impl ConfigWatch {
+    /// Creates a watch for the configuration file of the given source.
+    ///
+    /// # Arguments
+    /// * `source` - the configuration source
+    fn new(source: &ConfigSource) -> ConfigWatch {
+        let modified = source.file_name().and_then(last_modified);
+        ConfigWatch { source: source.clone(), modified }
+    }
+
+    /// Indicates whether the configuration file has been modified since the last call.
+    /// A file that can't be accessed, e.g. while being replaced by an editor, is not regarded
+    /// as modified.
+    fn changed(&mut self) -> bool {
+        let modified = self.source.file_name().and_then(last_modified);
+        if modified.is_none() || modified == self.modified { return false }
+        self.modified = modified;
+        true
+    }
+}
+
+/// Returns the last modification time of the given file, None if it can't be determined.
+///
+/// # Arguments
+/// * `file_name` - the name of the file
+fn last_modified(file_name: &str) -> Option<SystemTime> {
+    std::fs::metadata(file_name).and_then(|m| m.modified()).ok()
+}
+
+/// Installs the handlers for flush and rollover signals, if at least one buffer policy in the
+/// given configuration flushes upon signal.
+/// The process' signal dispositions remain untouched otherwise.
+///
+/// # Arguments
+/// * `cnf` - the configuration
+fn install_signal_handlers(cnf: &config::Configuration) {
+    if cnf.flushes_on_signal() { signal::install_handlers(); }
+}
+
+/// Activates the settings for the reporting of problems from the given configuration.
+///
+/// # Arguments
+/// * `cnf` - the configuration
+fn configure_problem_reporting(cnf: &config::Configuration) {
+    let sp = cnf.system_properties();
+    set_problem_reporting(&sp.problem_reporting(), sp.output_path());
+}
+
+/// Logs the issues found while processing a configuration file, if any.
+/// 
+/// # Arguments
+/// * `cnf` - the configuration
+/// * `config_file_name` - the name of the configuration file
+fn log_config_issues(cnf: &config::Configuration, config_file_name: &str) {
+    let msgs = cnf.messages();
+    if ! msgs.is_empty() {
+        let header_msg = coalyxw!(E_CFG_FOUND_ISSUES, config_file_name.to_string());
+        let mut emsgs = msgs.clone();
+        emsgs.insert(0, header_msg);
+        log_problems(&emsgs);
     }
 }
 
@@ -346,21 +880,22 @@ fn determine_mode(glob_mode_map: &mut OverrideModeMap,
     let mut mode = glob_mode_map.active_mode();
     match record.trigger() {
         RecordTrigger::ObserverCreated => {
+            let obs_id = record.observer_id();
             let obs_name = record.observer_name().as_deref();
             if record.level() == RecordLevelId::Object {
                 let obs_value = record.message().as_deref();
-                let glob_mode = mode_change_descs.global_mode_for_obj(obs_name, obs_value);
+                let glob_mode = mode_change_descs.global_mode_for_obj(obs_id, obs_name, obs_value);
                 if glob_mode != u32::MAX {
-                    glob_mode_map.matching_observer_created(record.observer_id(), glob_mode);
+                    glob_mode_map.matching_observer_created(obs_id, glob_mode);
                     mode = glob_mode;
                 }
-                let loc_mode = mode_change_descs.local_mode_for_obj(obs_name, obs_value);
+                let loc_mode = mode_change_descs.local_mode_for_obj(obs_id, obs_name, obs_value);
                 if loc_mode != u32::MAX {
-                    let new_mode = thread_status.object_created(record.observer_id(), loc_mode);
+                    let new_mode = thread_status.object_created(obs_id, loc_mode);
                     if mode == u32::MAX { mode = new_mode; }
                 }
             } else {
-                let loc_mode = mode_change_descs.local_mode_for_unit(obs_name);
+                let loc_mode = mode_change_descs.local_mode_for_unit(obs_id, obs_name);
                 if loc_mode != u32::MAX {
                     let new_mode = thread_status.unit_entered(loc_mode);
                     if mode == u32::MAX { mode = new_mode; }
@@ -369,16 +904,17 @@ fn determine_mode(glob_mode_map: &mut OverrideModeMap,
         },
         RecordTrigger::ObserverDropped => {
             if mode == u32::MAX { mode = thread_status.active_mode(); }
+            let obs_id = record.observer_id();
             let obs_name = record.observer_name().as_deref();
             if record.level() == RecordLevelId::Object {
                 let obs_value = record.message().as_deref();
-                if mode_change_descs.global_mode_for_obj(obs_name, obs_value) != u32::MAX {
-                    glob_mode_map.matching_observer_dropped(record.observer_id());
+                if mode_change_descs.global_mode_for_obj(obs_id, obs_name, obs_value) != u32::MAX {
+                    glob_mode_map.matching_observer_dropped(obs_id);
                 }
-                if mode_change_descs.local_mode_for_obj(obs_name, obs_value) != u32::MAX {
-                    thread_status.object_dropped(record.observer_id());
+                if mode_change_descs.local_mode_for_obj(obs_id, obs_name, obs_value) != u32::MAX {
+                    thread_status.object_dropped(obs_id);
                 }
-            } else if mode_change_descs.local_mode_for_unit(obs_name) != u32::MAX {
+            } else if mode_change_descs.local_mode_for_unit(obs_id, obs_name) != u32::MAX {
                 thread_status.unit_left();
             }
         },
@@ -0,0 +1,149 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+//! Converter for Coaly binary record files.
+//!
+//! Reads plain output files written with file format binary and prints their records to
+//! standard output, formatted according to an output format from a configuration file.
+//! Without configuration file or output format name, the default output format is used.
+//! With a key file or passphrase, encrypted plain and memory mapped output files are decrypted
+//! first, encrypted text files are printed as they are.
+//!
+//! Usage: `coaly-cat [-c <configuration file>] [-f <output format>] [-k <key file>]
+//! [-p <passphrase>] <file>...`
+
+use coaly::errorhandling::{CoalyException, Severity, W_FILE_TORN_FRAMES};
+use coaly::output::binaryfile::read_binary_file;
+#[cfg(feature="encryption")]
+use coaly::output::binaryfile::{is_binary_data, read_binary_data};
+#[cfg(feature="encryption")]
+use coaly::output::encryption::{read_encrypted_file, DecryptionKey};
+use std::io::Write;
+use std::path::Path;
+
+#[cfg(not(feature="encryption"))]
+const USAGE: &str = "Usage: coaly-cat [-c <configuration file>] [-f <output format>] \
+                     <binary file>...";
+#[cfg(feature="encryption")]
+const USAGE: &str = "Usage: coaly-cat [-c <configuration file>] [-f <output format>] \
+                     [-k <key file>] [-p <passphrase>] <file>...";
+
+fn main() {
+    let mut cfg_file_name: Option<String> = None;
+    let mut format_name: Option<String> = None;
+    #[cfg(feature="encryption")]
+    let mut key: Option<DecryptionKey> = None;
+    let mut file_names = Vec::<String>::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" => cfg_file_name = args.next(),
+            "-f" => format_name = args.next(),
+            #[cfg(feature="encryption")]
+            "-k" => {
+                match args.next().map(|f| DecryptionKey::from_key_file(Path::new(&f))) {
+                    Some(Ok(k)) => key = Some(k),
+                    Some(Err(ex)) => {
+                        eprintln!("{}", ex.localized_message());
+                        std::process::exit(1);
+                    },
+                    None => ()
+                }
+            },
+            #[cfg(feature="encryption")]
+            "-p" => key = args.next().map(|p| DecryptionKey::from_passphrase(&p)),
+            _ => file_names.push(arg)
+        }
+    }
+    if file_names.is_empty() {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    }
+    let mut exit_code = 0;
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for file_name in file_names {
+        #[cfg(not(feature="encryption"))]
+        let result = read_file(&file_name, cfg_file_name.as_deref(), format_name.as_deref());
+        #[cfg(feature="encryption")]
+        let result = read_file(&file_name, cfg_file_name.as_deref(), format_name.as_deref(),
+                               key.as_ref());
+        match result {
+            Ok((content, torn_count)) => {
+                if out.write_all(&content).is_err() { std::process::exit(exit_code) }
+                if torn_count > 0 {
+                    let ex = CoalyException::with_args(W_FILE_TORN_FRAMES, Severity::Warning,
+                                                       &[torn_count.to_string(),
+                                                         file_name.to_string()]);
+                    eprintln!("{}", ex.localized_message());
+                }
+            },
+            Err(ex) => {
+                eprintln!("{}", ex.localized_message());
+                exit_code = 1;
+            }
+        }
+    }
+    let _ = out.flush();
+    std::process::exit(exit_code);
+}
+
+/// Reads a file and converts its records to text.
+/// The file is decrypted first, if a key is given.
+///
+/// # Arguments
+/// * `file_name` - the name of the file
+/// * `cfg_file_name` - the optional name of the configuration file defining the output format
+/// * `format_name` - the name of the output format, **None** for the default format
+/// * `key` - the key to decrypt the file, **None** if the file is not encrypted
+///
+/// # Return values
+/// the text to print and the number of corrupted records skipped
+///
+/// # Errors
+/// Returns an error structure if the file could not be read or decrypted
+fn read_file(file_name: &str,
+             cfg_file_name: Option<&str>,
+             format_name: Option<&str>,
+             #[cfg(feature="encryption")]
+             key: Option<&DecryptionKey>) -> Result<(Vec<u8>, usize), CoalyException> {
+    #[cfg(feature="encryption")]
+    if let Some(key) = key {
+        let dec = read_encrypted_file(Path::new(file_name), key)?;
+        if ! is_binary_data(dec.content()) { return Ok((dec.content().to_vec(), dec.torn_count())) }
+        let recs = read_binary_data(dec.content(), file_name, cfg_file_name, format_name)?;
+        return Ok((recs.records().concat().into_bytes(), dec.torn_count() + recs.torn_count()))
+    }
+    let recs = read_binary_file(Path::new(file_name), cfg_file_name, format_name)?;
+    Ok((recs.records().concat().into_bytes(), recs.torn_count()))
+}
@@ -0,0 +1,135 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+//! Crash dump extraction tool for Coaly memory mapped output files.
+//!
+//! Reads memory mapped output files, e.g. left behind by a crashed process, and prints their
+//! intact records to standard output in chronological order.
+//! Record boundaries are reconstructed from the administrative data stored in the file,
+//! records torn by the crash are skipped and reported to standard error.
+//! With a key file or passphrase, encrypted memory mapped files are decrypted first.
+//!
+//! Usage: `coaly-dump [-k <key file>] [-p <passphrase>] <file>...`
+
+use coaly::errorhandling::{CoalyException, Severity, W_FILE_TORN_FRAMES};
+#[cfg(feature="encryption")]
+use coaly::output::encryption::{read_encrypted_file, DecryptionKey};
+use coaly::output::mappedfile::read_mapped_file;
+use std::io::Write;
+use std::path::Path;
+
+#[cfg(not(feature="encryption"))]
+const USAGE: &str = "Usage: coaly-dump <memory mapped file>...";
+#[cfg(feature="encryption")]
+const USAGE: &str = "Usage: coaly-dump [-k <key file>] [-p <passphrase>] <memory mapped file>...";
+
+fn main() {
+    #[cfg(not(feature="encryption"))]
+    let file_names: Vec<String> = std::env::args().skip(1).collect();
+    #[cfg(feature="encryption")]
+    let mut key: Option<DecryptionKey> = None;
+    #[cfg(feature="encryption")]
+    let mut file_names = Vec::<String>::new();
+    #[cfg(feature="encryption")]
+    let mut args = std::env::args().skip(1);
+    #[cfg(feature="encryption")]
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-k" => {
+                match args.next().map(|f| DecryptionKey::from_key_file(Path::new(&f))) {
+                    Some(Ok(k)) => key = Some(k),
+                    Some(Err(ex)) => {
+                        eprintln!("{}", ex.localized_message());
+                        std::process::exit(1);
+                    },
+                    None => ()
+                }
+            },
+            "-p" => key = args.next().map(|p| DecryptionKey::from_passphrase(&p)),
+            _ => file_names.push(arg)
+        }
+    }
+    if file_names.is_empty() {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    }
+    let mut exit_code = 0;
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for file_name in file_names {
+        #[cfg(not(feature="encryption"))]
+        let result = dump_file(&file_name);
+        #[cfg(feature="encryption")]
+        let result = dump_file(&file_name, key.as_ref());
+        match result {
+            Ok((content, torn_count)) => {
+                if out.write_all(&content).is_err() { std::process::exit(exit_code) }
+                if torn_count > 0 {
+                    let ex = CoalyException::with_args(W_FILE_TORN_FRAMES, Severity::Warning,
+                                                       &[torn_count.to_string(),
+                                                         file_name.to_string()]);
+                    eprintln!("{}", ex.localized_message());
+                }
+            },
+            Err(ex) => {
+                eprintln!("{}", ex.localized_message());
+                exit_code = 1;
+            }
+        }
+    }
+    let _ = out.flush();
+    std::process::exit(exit_code);
+}
+
+/// Reads the intact records from a memory mapped file in chronological order.
+/// The file is decrypted first, if a key is given.
+///
+/// # Arguments
+/// * `file_name` - the name of the file
+/// * `key` - the key to decrypt the file, **None** if the file is not encrypted
+///
+/// # Return values
+/// the records to print and the number of corrupted records skipped
+///
+/// # Errors
+/// Returns an error structure if the file could not be read or decrypted
+fn dump_file(file_name: &str,
+             #[cfg(feature="encryption")]
+             key: Option<&DecryptionKey>) -> Result<(Vec<u8>, usize), CoalyException> {
+    #[cfg(feature="encryption")]
+    if let Some(key) = key {
+        let dec = read_encrypted_file(Path::new(file_name), key)?;
+        return Ok((dec.content().to_vec(), dec.torn_count()))
+    }
+    let recs = read_mapped_file(Path::new(file_name))?;
+    Ok((recs.records().concat().into_bytes(), recs.torn_count()))
+}
@@ -0,0 +1,79 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+//! Coaly log server.
+//!
+//! Receives log and trace records from remote Coaly clients and writes them to the output
+//! resources specified in the configuration file. Resources with file name variables like
+//! $HostName or $ProcessId are instantiated per remote originator, resources restricted by
+//! parameter app_ids only receive records from the listed applications.
+//!
+//! Usage: `coaly-server <configuration file>`
+//!
+//! The server terminates upon CTRL-C, SIGTERM or a shutdown command sent to the administrative
+//! network address.
+
+use coaly::errorhandling::{CoalyException, Severity, E_SRV_CFG_FILE_NOT_SPECIFIED};
+use coaly::net::server::TraceServer;
+
+fn main() {
+    let cfg_file_name = match std::env::args().nth(1) {
+        Some(file_name) => file_name,
+        None => {
+            let ex = CoalyException::new(E_SRV_CFG_FILE_NOT_SPECIFIED, Severity::Error);
+            eprintln!("{}", ex.localized_message());
+            eprintln!("Usage: coaly-server <configuration file>");
+            std::process::exit(1);
+        }
+    };
+    let mut server = match TraceServer::from_config_file(&cfg_file_name) {
+        Ok(server) => server,
+        Err(ex) => {
+            eprintln!("{}", ex.localized_message());
+            std::process::exit(1);
+        }
+    };
+    let runtime = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{}", e);
+            coaly::shutdown();
+            std::process::exit(1);
+        }
+    };
+    let result = runtime.block_on(server.run(true, true));
+    coaly::shutdown();
+    if let Err(ex) = result {
+        eprintln!("{}", ex.localized_message());
+        std::process::exit(1);
+    }
+}
@@ -102,6 +102,24 @@ impl<T> RecoverableStack<T> {
     /// **top element** of the stack, **None** if the stack is empty
     #[inline]
     pub(crate) fn last(&self) -> Option<&T> { self.items.last() }
+
+    /// Returns the number of elements in the stack, not counting elements pushed in
+    /// overflow state.
+    #[inline]
+    pub(crate) fn len(&self) -> usize { self.items.len() }
+
+    /// Replaces the bottom element of the stack.
+    /// The value is pushed, if the stack is empty.
+    ///
+    /// # Arguments
+    /// * `value` - the new bottom element
+    pub(crate) fn replace_bottom(&mut self, value: T) {
+        if self.items.is_empty() {
+            self.push(value);
+            return
+        }
+        self.items[0] = value;
+    }
 }
 impl<T> Debug for RecoverableStack<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -139,6 +157,13 @@ impl<T> MapWithDefault<T> {
         &self.default_element
     }
 
+    /// Indicates whether the map contains a custom element with the given name.
+    ///
+    /// # Arguments
+    /// * `name` - the element name
+    #[inline]
+    pub(crate) fn contains(&self, name: &str) -> bool { self.custom_elements.contains_key(name) }
+
     /// Inserts a custom element into the map.
     /// 
     /// # Arguments
@@ -204,6 +229,12 @@ impl<T> VecWithDefault<T> {
     #[inline]
     pub(crate) fn push(&mut self, element: T) { self.custom_elements.push(element) }
 
+    /// Returns a mutable reference to the last custom element, if any.
+    #[inline]
+    pub(crate) fn last_custom_element_mut(&mut self) -> Option<&mut T> {
+        self.custom_elements.last_mut()
+    }
+
     /// Returns the default element.
     #[cfg(test)]
     #[inline]
@@ -0,0 +1,203 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+
+//! Descriptor structure for adapter mappings.
+//! An adapter mapping shapes records passed to Coaly by the log crate or tracing bridges. Records
+//! are selected by their target and the level assigned by the foreign facade, matching records
+//! may be written with a different Coaly record level and within a Coaly module observer (unit).
+
+use std::fmt::{Debug, Formatter};
+use crate::record::RecordLevelId;
+
+/// Foreign record level error, used by both log crate and tracing
+#[cfg(any(test, feature="log-compat", feature="tracing"))]
+pub(crate) const ADAPTER_LEVEL_ERROR: u32 = 0b00001;
+/// Foreign record level warn, used by both log crate and tracing
+#[cfg(any(test, feature="log-compat", feature="tracing"))]
+pub(crate) const ADAPTER_LEVEL_WARN: u32 = 0b00010;
+/// Foreign record level info, used by both log crate and tracing
+#[cfg(any(test, feature="log-compat", feature="tracing"))]
+pub(crate) const ADAPTER_LEVEL_INFO: u32 = 0b00100;
+/// Foreign record level debug, used by both log crate and tracing
+#[cfg(any(test, feature="log-compat", feature="tracing"))]
+pub(crate) const ADAPTER_LEVEL_DEBUG: u32 = 0b01000;
+/// Foreign record level trace, used by both log crate and tracing
+#[cfg(any(test, feature="log-compat", feature="tracing"))]
+pub(crate) const ADAPTER_LEVEL_TRACE: u32 = 0b10000;
+/// All foreign record levels
+pub(crate) const ADAPTER_LEVEL_ALL: u32 = 0b11111;
+
+/// Names of the foreign record levels, in ascending order of their bit values
+pub(crate) const ADAPTER_LEVEL_NAMES: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Names of the Coaly record levels allowed as mapping result
+pub(crate) const ADAPTER_TARGET_LEVEL_NAMES: &[&str] = &["emergency", "alert", "critical",
+                                                         "error", "warning", "notice", "info",
+                                                         "debug"];
+
+/// Returns the bit value of the foreign record level with the given name.
+///
+/// # Arguments
+/// * `name` - the level name as used by log crate and tracing, in lower case
+pub(crate) fn adapter_level_from_str(name: &str) -> Option<u32> {
+    ADAPTER_LEVEL_NAMES.iter().position(|n| *n == name).map(|i| 1 << i)
+}
+
+/// Validates the target specified for an adapter mapping.
+/// A target consists of one or more names separated by `::`, optionally terminated by `::*`.
+/// Contrary to module paths, targets may contain all characters except white space and colons,
+/// since log crate and tracing allow arbitrary target names.
+///
+/// # Return values
+/// the target without trailing wildcard, **None** if the target is invalid
+pub(crate) fn validate_adapter_target(target: &str) -> Option<String> {
+    let t = target.strip_suffix("::*").unwrap_or(target);
+    if t.is_empty() { return None }
+    for seg in t.split("::") {
+        if seg.is_empty() || seg.chars().any(|c| c.is_whitespace() || c == ':') { return None }
+    }
+    Some(t.to_string())
+}
+
+/// Holds a mapping for records from the log crate or tracing bridges.
+#[derive (Clone)]
+pub(crate) struct AdapterMappingDesc {
+    // target prefix of the records affected, matched on `::` boundaries
+    target: String,
+    // bit mask with the foreign levels of the records affected
+    levels: u32,
+    // the Coaly record level to use instead of the default level
+    level: Option<RecordLevelId>,
+    // the name of the Coaly module observer the records shall be written within
+    unit: Option<String>
+}
+impl AdapterMappingDesc {
+    /// Creates an adapter mapping.
+    ///
+    /// # Arguments
+    /// * `target` - the target prefix of the records affected
+    /// * `levels` - the bit mask with the foreign levels of the records affected
+    /// * `level` - the Coaly record level to use instead of the default level
+    /// * `unit` - the name of the module observer the records shall be written within
+    pub(crate) fn new(target: &str,
+                      levels: u32,
+                      level: Option<RecordLevelId>,
+                      unit: Option<String>) -> AdapterMappingDesc {
+        AdapterMappingDesc { target: target.to_string(), levels, level, unit }
+    }
+
+    /// Returns the Coaly record level to use instead of the default level
+    #[inline]
+    #[cfg(any(test, feature="log-compat", feature="tracing"))]
+    pub(crate) fn level(&self) -> Option<RecordLevelId> { self.level }
+
+    /// Returns the name of the module observer the records shall be written within
+    #[inline]
+    #[cfg(any(test, feature="log-compat", feature="tracing"))]
+    pub(crate) fn unit(&self) -> Option<&str> { self.unit.as_deref() }
+
+    /// Indicates whether a record with given target and foreign level is subject to this mapping.
+    ///
+    /// # Arguments
+    /// * `target` - the record's target, usually the module path of the issuing crate
+    /// * `level` - the bit value of the record's foreign level
+    #[cfg(any(test, feature="log-compat", feature="tracing"))]
+    pub(crate) fn matches(&self, target: &str, level: u32) -> bool {
+        if self.levels & level == 0 { return false }
+        target.strip_prefix(self.target.as_str())
+              .is_some_and(|r| r.is_empty() || r.starts_with("::"))
+    }
+}
+impl Debug for AdapterMappingDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lvl = self.level.map_or(String::from("-"), |l| l.to_string());
+        write!(f, "T:{}/AL:{:b}/L:{}/U:{}", self.target, self.levels, lvl,
+               self.unit.as_deref().unwrap_or("-"))
+    }
+}
+
+/// List with adapter mappings, in the order of definition
+pub(crate) type AdapterMappingDescList = Vec<AdapterMappingDesc>;
+
+/// Returns the first mapping in the given list matching a record.
+///
+/// # Arguments
+/// * `mappings` - the adapter mappings
+/// * `target` - the record's target
+/// * `level` - the bit value of the record's foreign level
+#[cfg(any(test, feature="log-compat", feature="tracing"))]
+pub(crate) fn find_adapter_mapping<'a>(mappings: &'a AdapterMappingDescList,
+                                       target: &str,
+                                       level: u32) -> Option<&'a AdapterMappingDesc> {
+    mappings.iter().find(|m| m.matches(target, level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_adapter_target() {
+        assert_eq!(Some(String::from("hyper")), validate_adapter_target("hyper::*"));
+        assert_eq!(Some(String::from("hyper::client")), validate_adapter_target("hyper::client"));
+        assert_eq!(Some(String::from("my-target")), validate_adapter_target("my-target"));
+        assert!(validate_adapter_target("").is_none());
+        assert!(validate_adapter_target("::*").is_none());
+        assert!(validate_adapter_target("hyper::").is_none());
+        assert!(validate_adapter_target("hyper client").is_none());
+    }
+
+    #[test]
+    fn test_find_adapter_mapping() {
+        assert_eq!(Some(ADAPTER_LEVEL_DEBUG), adapter_level_from_str("debug"));
+        assert!(adapter_level_from_str("warning").is_none());
+        let mappings = vec!(
+            AdapterMappingDesc::new("hyper", ADAPTER_LEVEL_DEBUG | ADAPTER_LEVEL_TRACE,
+                                    Some(RecordLevelId::Notice), Some(String::from("http"))),
+            AdapterMappingDesc::new("hyper::client", ADAPTER_LEVEL_ALL, None,
+                                    Some(String::from("client"))),
+            AdapterMappingDesc::new("hyper", ADAPTER_LEVEL_ALL, Some(RecordLevelId::Info), None));
+        let m = find_adapter_mapping(&mappings, "hyper::proto", ADAPTER_LEVEL_DEBUG).unwrap();
+        assert_eq!(Some(RecordLevelId::Notice), m.level());
+        assert_eq!(Some("http"), m.unit());
+        let m = find_adapter_mapping(&mappings, "hyper::client", ADAPTER_LEVEL_WARN).unwrap();
+        assert_eq!(Some("client"), m.unit());
+        let m = find_adapter_mapping(&mappings, "hyper", ADAPTER_LEVEL_ERROR).unwrap();
+        assert_eq!(Some(RecordLevelId::Info), m.level());
+        assert!(m.unit().is_none());
+        assert!(find_adapter_mapping(&mappings, "hyperx", ADAPTER_LEVEL_DEBUG).is_none());
+        assert!(find_adapter_mapping(&mappings, "tokio", ADAPTER_LEVEL_ERROR).is_none());
+        let m = find_adapter_mapping(&mappings, "hyper::proto", ADAPTER_LEVEL_INFO).unwrap();
+        assert_eq!(Some(RecordLevelId::Info), m.level());
+    }
+}
@@ -32,8 +32,11 @@
 
 //! Descriptor structure for date-time types.
 
+use chrono::{DateTime, Local, TimeZone, Timelike, Utc};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
 use crate::collections::MapWithDefault;
 
 /// Validates the specified date format string.
@@ -54,9 +57,71 @@ pub fn validate_timestamp_format(fmt_str: &str) -> Result<(), String> {
     validate_format(fmt_str, TIMESTAMP_FORMAT_VARS)
 }
 
+/// Formats a date time value.
+/// In addition to the specifiers supported by chrono, fractional seconds may be specified with
+/// any precision between 1 and 9 digits, with or without leading dot (e.g. %5f or %.2f).
+/// 
+/// # Arguments
+/// * `dtm` - the date time value
+/// * `fmt_str` - the format string, must have been validated
+pub(crate) fn format_date_time<Tz>(dtm: &DateTime<Tz>, fmt_str: &str) -> String
+    where Tz: TimeZone, Tz::Offset: Display {
+    match expand_fractions(fmt_str, dtm.nanosecond() % 1_000_000_000) {
+        Cow::Borrowed(f) => dtm.format(f).to_string(),
+        Cow::Owned(f) => dtm.format(&f).to_string()
+    }
+}
+
+/// Formats a local date time value in the given time zone.
+/// 
+/// # Arguments
+/// * `dtm` - the date time value
+/// * `fmt_str` - the format string, must have been validated
+/// * `zone` - the time zone for the formatted value
+pub(crate) fn format_in_zone(dtm: &DateTime<Local>, fmt_str: &str, zone: DateTimeZone) -> String {
+    match zone {
+        DateTimeZone::Local => format_date_time(dtm, fmt_str),
+        DateTimeZone::Utc => format_date_time(&dtm.with_timezone(&Utc), fmt_str)
+    }
+}
+
+/// Time zone used for formatted date and time values.
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub(crate) enum DateTimeZone {
+    // local time zone of the host
+    Local,
+    // coordinated universal time
+    Utc
+}
+impl DateTimeZone {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateTimeZone::Local => write!(f, "{}", DTM_ZONE_LOCAL),
+            DateTimeZone::Utc => write!(f, "{}", DTM_ZONE_UTC)
+        }
+    }
+}
+impl Debug for DateTimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for DateTimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for DateTimeZone {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            DTM_ZONE_LOCAL => Ok(DateTimeZone::Local),
+            DTM_ZONE_UTC => Ok(DateTimeZone::Utc),
+            _ => Err(false)
+        }
+    }
+}
+
 /// Holds format strings for date, time and timestamp values.
 /// Needed within record format and file name descriptors to use the desired form of these values.
-#[derive (Clone, Default)]
+#[derive (Clone)]
 pub(crate) struct DateTimeFormatDesc {
     // format name
     name: String,
@@ -65,7 +130,9 @@ pub(crate) struct DateTimeFormatDesc {
     // format string for time values
     time_format: Option<String>,
     // format string for date-time values
-    timestamp_format: Option<String>
+    timestamp_format: Option<String>,
+    // time zone for all values
+    zone: DateTimeZone
 }
 impl DateTimeFormatDesc {
     /// Creates a date time format.
@@ -76,14 +143,21 @@ impl DateTimeFormatDesc {
     /// * `date_format` - the optional format string for date values
     /// * `time_format` - the optional format string for time values
     /// * `timestamp_format` - the optional format string for timestamp (date and time) values
+    /// * `zone` - the time zone for all values
     #[inline]
     pub(crate) fn new(name: &str,
                       date_format: Option<String>,
                       time_format: Option<String>,
-                      timestamp_format: Option<String>) -> DateTimeFormatDesc {
-        DateTimeFormatDesc { name: name.to_string(), date_format, time_format, timestamp_format }
+                      timestamp_format: Option<String>,
+                      zone: DateTimeZone) -> DateTimeFormatDesc {
+        DateTimeFormatDesc { name: name.to_string(), date_format, time_format, timestamp_format,
+                             zone }
     }
 
+    /// Returns the time zone for date, time and timestamp values.
+    #[inline]
+    pub(crate) fn zone(&self) -> DateTimeZone { self.zone }
+
     /// Returns the format string for date values used in output records.
     /// 
     /// # Return values
@@ -121,7 +195,20 @@ impl Debug for DateTimeFormatDesc {
                self.date_format.as_ref().unwrap_or(&String::from("-")),
                self.time_format.as_ref().unwrap_or(&String::from("-")),
                self.timestamp_format.as_ref().unwrap_or(&String::from("-"))
-        )
+        )?;
+        if self.zone != DateTimeZone::Local { write!(f, "/Z:{}", self.zone)?; }
+        Ok(())
+    }
+}
+impl Default for DateTimeFormatDesc {
+    fn default() -> Self {
+        DateTimeFormatDesc {
+            name: String::default(),
+            date_format: None,
+            time_format: None,
+            timestamp_format: None,
+            zone: DateTimeZone::Local
+        }
     }
 }
 
@@ -134,19 +221,29 @@ fn validate_format(fmt_str: &str, var_str: &str) -> Result<(), String> {
     let var_map = var_str_to_map(var_str);
     let mut expect_var = false;
     let mut length_ind: u32 = 99;
+    let mut prefixed_var: Option<char> = None;
     let mut var_buf = String::with_capacity(8);
     for ch in fmt_str.chars() {
         if expect_var {
-            if ch == '%' {
+            if ch == '%' && var_buf.len() == 1 {
                 expect_var = false;
                 continue
             }
             var_buf.push(ch);
+            if ch == '.' || ch == ':' {
+                // dot before fractional seconds or colon before timezone offset, allowed
+                // directly after the percent sign only
+                let var_ch = if ch == '.' { 'f' } else { 'z' };
+                if var_buf.len() > 2 || ! var_map.contains_key(&var_ch) { return Err(var_buf) }
+                prefixed_var = Some(var_ch);
+                continue
+            }
             if ch.is_ascii_digit() {
-                if length_ind < 10 { return Err(var_buf) }
+                if length_ind < 10 || prefixed_var == Some('z') { return Err(var_buf) }
                 length_ind = ch.to_digit(10).unwrap();
                 continue
             }
+            if prefixed_var.is_some_and(|v| v != ch) { return Err(var_buf) }
             if var_map.contains_key(&ch) {
                 if length_ind < 99 {
                     let length_range = var_map.get(&ch).unwrap();
@@ -163,12 +260,55 @@ fn validate_format(fmt_str: &str, var_str: &str) -> Result<(), String> {
             var_buf.clear();
             var_buf.push(ch);
             length_ind = 99;
+            prefixed_var = None;
             expect_var = true;
         }
     }
     Ok(())
 }
 
+/// Replaces all fractional seconds specifiers in a format string, whose precision isn't
+/// supported by chrono, with the digits for the given nanoseconds.
+/// 
+/// # Arguments
+/// * `fmt_str` - the format string
+/// * `nanos` - the nanoseconds of the value to format
+/// 
+/// # Return values
+/// the format string with the digits inserted, unchanged if there are no such specifiers
+fn expand_fractions(fmt_str: &str, nanos: u32) -> Cow<'_, str> {
+    let bytes = fmt_str.as_bytes();
+    let mut result: Option<String> = None;
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' { i += 1; continue }
+        if bytes.get(i + 1) == Some(&b'%') { i += 2; continue }
+        let dot = bytes.get(i + 1) == Some(&b'.');
+        let digit_pos = if dot { i + 2 } else { i + 1 };
+        match (bytes.get(digit_pos), bytes.get(digit_pos + 1)) {
+            (Some(d @ b'1'..=b'9'), Some(b'f')) if ! matches!(d, b'3' | b'6' | b'9') => {
+                let precision = (d - b'0') as usize;
+                let digits = format!("{:09}", nanos);
+                let res = result.get_or_insert_with(|| String::with_capacity(fmt_str.len() + 8));
+                res.push_str(&fmt_str[copied .. i]);
+                if dot { res.push('.'); }
+                res.push_str(&digits[.. precision]);
+                i = digit_pos + 2;
+                copied = i;
+            },
+            _ => i += 1
+        }
+    }
+    match result {
+        Some(mut res) => {
+            res.push_str(&fmt_str[copied ..]);
+            Cow::Owned(res)
+        },
+        None => Cow::Borrowed(fmt_str)
+    }
+}
+
 fn var_str_to_map(var_str: &str) -> HashMap<char, u32> {
     let mut map = HashMap::<char, u32>::with_capacity(var_str.len());
     let mut in_range_var = false;
@@ -205,4 +345,42 @@ const DEFAULT_REC_TIME_FORMAT: &str = "%H:%M:%S%.3f";
 
 const DATE_FORMAT_VARS: &str = "dmyY";
 const TIME_FORMAT_VARS: &str = "\\19fHIMpPS";
-const TIMESTAMP_FORMAT_VARS: &str = "d\\19fHImMpPSyYzZ";
+const TIMESTAMP_FORMAT_VARS: &str = "d\\19fHImMpPSyYzZ+";
+
+// Time zones for date time formats
+const DTM_ZONE_LOCAL: &str = "local";
+const DTM_ZONE_UTC: &str = "utc";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_validate_format() {
+        assert!(validate_time_format("%H:%M:%S%.f").is_ok());
+        assert!(validate_time_format("%H:%M:%S%.5f").is_ok());
+        assert!(validate_time_format("%H:%M:%S.%9f").is_ok());
+        assert_eq!(Err(String::from("%:")), validate_time_format("%H:%M:%S%:z"));
+        assert_eq!(Err(String::from("%.S")), validate_time_format("%H:%M:%.S"));
+        assert!(validate_timestamp_format("%Y-%m-%dT%H:%M:%S%.3f%:z").is_ok());
+        assert!(validate_timestamp_format("%+").is_ok());
+        assert_eq!(Err(String::from("%:3")), validate_timestamp_format("%H%:3z"));
+        assert_eq!(Err(String::from("%.z")), validate_timestamp_format("%H%.z"));
+    }
+
+    #[test]
+    fn test_format_date_time() {
+        let offset = FixedOffset::east_opt(7200).unwrap();
+        let dtm = offset.with_ymd_and_hms(2024, 5, 17, 14, 30, 45).unwrap()
+                        .with_nanosecond(123_456_789).unwrap();
+        assert_eq!("14:30:45.123", format_date_time(&dtm, "%H:%M:%S%.3f"));
+        assert_eq!("14:30:45.12345", format_date_time(&dtm, "%H:%M:%S%.5f"));
+        assert_eq!("14:30:45.1|1234567", format_date_time(&dtm, "%H:%M:%S.%1f|%7f"));
+        assert_eq!("%5f 123456", format_date_time(&dtm, "%%5f %6f"));
+        assert_eq!("2024-05-17T14:30:45.123456+02:00",
+                   format_date_time(&dtm, "%Y-%m-%dT%H:%M:%S%.6f%:z"));
+        assert_eq!("2024-05-17T12:30:45.12Z",
+                   format_date_time(&dtm.with_timezone(&Utc), "%Y-%m-%dT%H:%M:%S%.2fZ"));
+    }
+}
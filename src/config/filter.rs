@@ -0,0 +1,282 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Descriptor structure for record filters.
+//! A record filter includes or excludes log and trace records based on the source file they
+//! were issued from. Source files can be specified by path name prefix or by Rust module path
+//! prefix, the module path is derived from the source file name.
+//! Suppression rules discard records issued from a source code location or within a function
+//! or module regardless of record level, independent of the resources.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+/// Validates the specified module path prefix.
+/// A prefix consists of module names separated by `::`, optionally terminated by `::*`.
+///
+/// # Return values
+/// the module path prefix without trailing wildcard, **None** if the prefix is invalid
+pub(crate) fn validate_module_prefix(prefix: &str) -> Option<String> {
+    let p = prefix.strip_suffix("::*").unwrap_or(prefix);
+    if p.is_empty() { return None }
+    for seg in p.split("::") {
+        if seg.is_empty() || ! seg.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return None
+        }
+    }
+    Some(p.to_string())
+}
+
+/// Derives the Rust module path from a source file name.
+/// All path components up to and including the last `src` directory are removed, as well as
+/// the file names `mod.rs`, `lib.rs` and `main.rs`.
+/// Source file `src/net/tcp.rs` yields module path `net::tcp`, `src/net/mod.rs` yields `net`.
+///
+/// # Arguments
+/// * `source_fn` - the source file name, as returned by macro `file!()`
+pub(crate) fn module_path_of(source_fn: &str) -> String {
+    let norm = source_fn.replace('\\', "/");
+    let stem = norm.strip_suffix(".rs").unwrap_or(&norm);
+    let comps: Vec<&str> = stem.split('/').filter(|c| ! c.is_empty() && *c != ".").collect();
+    let start = comps.iter().rposition(|c| *c == "src").map_or(0, |p| p + 1);
+    let mut mods = &comps[start..];
+    if let Some(&"mod" | &"lib" | &"main") = mods.last() { mods = &mods[..mods.len() - 1]; }
+    mods.join("::")
+}
+
+/// Holds the criteria of a record filter.
+/// A record passes the filter, if its source file matches at least one of the include criteria
+/// (or no include criteria are defined) and none of the exclude criteria.
+#[derive (Clone, Default)]
+pub(crate) struct RecordFilterDesc {
+    // filter name
+    name: String,
+    // module path prefixes of records to include
+    include_modules: Vec<String>,
+    // module path prefixes of records to exclude
+    exclude_modules: Vec<String>,
+    // source file path prefixes of records to include
+    include_files: Vec<String>,
+    // source file path prefixes of records to exclude
+    exclude_files: Vec<String>
+}
+impl RecordFilterDesc {
+    /// Creates a record filter.
+    /// Used for a filter defined in the filters section of the custom configuration file.
+    ///
+    /// # Arguments
+    /// * `name` - the filter name
+    /// * `include_modules` - the module path prefixes of records to include
+    /// * `exclude_modules` - the module path prefixes of records to exclude
+    /// * `include_files` - the source file path prefixes of records to include
+    /// * `exclude_files` - the source file path prefixes of records to exclude
+    pub(crate) fn new(name: &str,
+                      include_modules: Vec<String>,
+                      exclude_modules: Vec<String>,
+                      include_files: Vec<String>,
+                      exclude_files: Vec<String>) -> RecordFilterDesc {
+        let norm = |v: Vec<String>| v.iter().map(|f| f.replace('\\', "/")).collect();
+        RecordFilterDesc { name: name.to_string(), include_modules, exclude_modules,
+                           include_files: norm(include_files), exclude_files: norm(exclude_files) }
+    }
+
+    /// Indicates whether a record issued from the given source file passes this filter.
+    ///
+    /// # Arguments
+    /// * `source_fn` - the name of the source file, where the record was issued
+    pub(crate) fn passes(&self, source_fn: &str) -> bool {
+        let file_name = source_fn.replace('\\', "/");
+        let module_path = if self.include_modules.is_empty() && self.exclude_modules.is_empty() {
+                              String::default()
+                          } else { module_path_of(&file_name) };
+        let file_matches = |prefixes: &Vec<String>| {
+            prefixes.iter().any(|p| file_name.starts_with(p.as_str()))
+        };
+        let module_matches = |prefixes: &Vec<String>| {
+            prefixes.iter().any(|p| module_path.strip_prefix(p.as_str())
+                                               .is_some_and(|r| r.is_empty() || r.starts_with("::")))
+        };
+        if file_matches(&self.exclude_files) || module_matches(&self.exclude_modules) {
+            return false
+        }
+        if self.include_files.is_empty() && self.include_modules.is_empty() { return true }
+        file_matches(&self.include_files) || module_matches(&self.include_modules)
+    }
+}
+impl Debug for RecordFilterDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "N:{}/IM:{:?}/EM:{:?}/IF:{:?}/EF:{:?}",
+               self.name, self.include_modules, self.exclude_modules,
+               self.include_files, self.exclude_files)
+    }
+}
+
+/// Map with record filters
+pub(crate) type RecordFilterDescMap = HashMap<String, RecordFilterDesc>;
+
+/// A rule suppressing all records issued from a source code location or within a unit.
+#[derive (Clone, Eq, PartialEq)]
+pub(crate) enum SuppressionRule {
+    // source file or directory path, optionally restricted to a single line number
+    Location(String, Option<u32>),
+    // name of a function or module
+    Unit(String)
+}
+impl SuppressionRule {
+    /// Creates a suppression rule from its specification in the custom configuration file.
+    /// Specifications starting with `unit:` denote a function or module name, all others a
+    /// source file or directory path, optionally followed by a colon and a line number.
+    ///
+    /// # Arguments
+    /// * `spec` - the rule specification
+    ///
+    /// # Return values
+    /// the suppression rule, **None** if the specification is invalid
+    pub(crate) fn from_spec(spec: &str) -> Option<SuppressionRule> {
+        if let Some(unit) = spec.strip_prefix(UNIT_PREFIX) {
+            if unit.is_empty() { return None }
+            return Some(SuppressionRule::Unit(unit.to_string()))
+        }
+        let norm = spec.replace('\\', "/");
+        let (path, line_nr) = match norm.rsplit_once(':') {
+            Some((p, l)) if ! l.is_empty() && l.chars().all(|c| c.is_ascii_digit()) => {
+                (p.to_string(), Some(l.parse::<u32>().ok()?))
+            },
+            _ => (norm, None)
+        };
+        let path = path.trim_matches('/');
+        if path.is_empty() { return None }
+        Some(SuppressionRule::Location(path.to_string(), line_nr))
+    }
+
+    /// Indicates whether a record is suppressed by this rule.
+    ///
+    /// # Arguments
+    /// * `source_fn` - the name of the source file, where the record was issued
+    /// * `line_nr` - the line number, where the record was issued
+    /// * `scope_names` - the names of all functions and modules active when the record was issued
+    pub(crate) fn matches(&self,
+                          source_fn: &str,
+                          line_nr: Option<u32>,
+                          scope_names: &[String]) -> bool {
+        match self {
+            SuppressionRule::Location(path, rule_line_nr) => {
+                if rule_line_nr.is_some() && *rule_line_nr != line_nr { return false }
+                let file_name = format!("/{}/", source_fn.replace('\\', "/"));
+                file_name.contains(&format!("/{}/", path))
+            },
+            SuppressionRule::Unit(name) => scope_names.iter().any(|n| n == name)
+        }
+    }
+}
+impl Debug for SuppressionRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuppressionRule::Location(path, Some(line_nr)) => write!(f, "F:{}:{}", path, line_nr),
+            SuppressionRule::Location(path, None) => write!(f, "F:{}", path),
+            SuppressionRule::Unit(name) => write!(f, "U:{}", name)
+        }
+    }
+}
+
+// Prefix for suppression rules denoting a function or module
+const UNIT_PREFIX: &str = "unit:";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_path_of() {
+        assert_eq!("net::tcp", module_path_of("src/net/tcp.rs"));
+        assert_eq!("net", module_path_of("src/net/mod.rs"));
+        assert_eq!("", module_path_of("src/lib.rs"));
+        assert_eq!("worker::queue", module_path_of("examples/demo/src/worker/queue.rs"));
+        assert_eq!("output::file", module_path_of("C:\\work\\app\\src\\output\\file.rs"));
+        assert_eq!("util", module_path_of("util.rs"));
+    }
+
+    #[test]
+    fn test_validate_module_prefix() {
+        assert_eq!(Some(String::from("net")), validate_module_prefix("net::*"));
+        assert_eq!(Some(String::from("net::tcp")), validate_module_prefix("net::tcp"));
+        assert!(validate_module_prefix("").is_none());
+        assert!(validate_module_prefix("::*").is_none());
+        assert!(validate_module_prefix("net::").is_none());
+        assert!(validate_module_prefix("net/tcp").is_none());
+    }
+
+    #[test]
+    fn test_passes() {
+        let strs = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let f = RecordFilterDesc::new("net", strs(&["net"]), strs(&["net::udp"]),
+                                      vec!(), vec!());
+        assert!(f.passes("src/net/mod.rs"));
+        assert!(f.passes("src/net/tcp.rs"));
+        assert!(! f.passes("src/net/udp.rs"));
+        assert!(! f.passes("src/network.rs"));
+        assert!(! f.passes("src/main.rs"));
+        let f = RecordFilterDesc::new("nonet", vec!(), strs(&["net"]), vec!(), vec!());
+        assert!(! f.passes("src/net/tcp.rs"));
+        assert!(f.passes("src/network.rs"));
+        let f = RecordFilterDesc::new("files", vec!(), vec!(), strs(&["src\\output"]),
+                                      strs(&["src/output/file.rs"]));
+        assert!(f.passes("src/output/mod.rs"));
+        assert!(f.passes("src\\output\\pipe.rs"));
+        assert!(! f.passes("src/output/file.rs"));
+        assert!(! f.passes("src/net/tcp.rs"));
+    }
+
+    #[test]
+    fn test_suppression_rules() {
+        let units = vec!(String::from("main"), String::from("heartbeat"));
+        let r = SuppressionRule::from_spec("third_party/noisy.rs").unwrap();
+        assert!(r.matches("src/third_party/noisy.rs", Some(12), &[]));
+        assert!(r.matches("src\\third_party\\noisy.rs", None, &[]));
+        assert!(! r.matches("src/third_party/noisy.rs.bak", Some(12), &[]));
+        assert!(! r.matches("src/my_third_party/noisy.rs", Some(12), &[]));
+        let r = SuppressionRule::from_spec("src/third_party").unwrap();
+        assert!(r.matches("src/third_party/noisy.rs", Some(12), &[]));
+        assert!(! r.matches("src/main.rs", Some(12), &[]));
+        let r = SuppressionRule::from_spec("src/worker.rs:42").unwrap();
+        assert_eq!(SuppressionRule::Location(String::from("src/worker.rs"), Some(42)), r);
+        assert!(r.matches("src/worker.rs", Some(42), &[]));
+        assert!(! r.matches("src/worker.rs", Some(43), &[]));
+        let r = SuppressionRule::from_spec("unit:heartbeat").unwrap();
+        assert!(r.matches("src/main.rs", Some(1), &units));
+        assert!(! r.matches("src/heartbeat.rs", Some(1), &units[..1]));
+        assert!(SuppressionRule::from_spec("").is_none());
+        assert!(SuppressionRule::from_spec("unit:").is_none());
+        assert!(SuppressionRule::from_spec(":12").is_none());
+        assert!(SuppressionRule::from_spec("src/worker.rs:99999999999").is_none());
+    }
+}
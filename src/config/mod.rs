@@ -32,7 +32,6 @@
 
 //! Coaly configuration handling.
 
-use regex::Regex;
 use std::collections::HashSet;
 use std::fmt::{Debug, Formatter};
 use std::fs::create_dir_all;
@@ -40,22 +39,45 @@ use std::path::Path;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::vec::Vec;
-use crate::coalyxw;
+use crate::{coalyxe, coalyxw};
+use crate::datetime::RecordClock;
 use crate::errorhandling::*;
+use crate::errorhandling::reporting::{min_severity_from_str, ProblemReporting, ProblemTarget};
 use crate::modechange::*;
 use crate::observer::ObserverKind;
 use crate::policies::*;
 use crate::record::*;
 use crate::record::originator::OriginatorInfo;
+use crate::record::recorddata::RecordDecoration;
+use crate::util::{edit_distance, is_valid_file_name_char};
 use crate::variables::*;
+use adapter::*;
 use datetimeformat::*;
+use filter::*;
 use output::*;
-use resource::{ResourceDesc, ResourceDescList, ResourceKind};
+use resource::{CharEncoding, ClockGranularity, CreateErrorPolicy,
+               ResourceDescList, resource_kind_names, CLOCK_GRANULARITY_NAMES,
+               CREATE_ERROR_POLICY_NAMES, DEF_CREATE_RETRY_INTERVAL, MAX_CREATE_RETRY_INTERVAL,
+               MIN_CREATE_RETRY_INTERVAL, FileFormat, FILE_FORMAT_NAMES, FileSharing,
+               FILE_SHARING_NAMES};
+#[cfg(feature="mmfile")]
+use resource::{TimestampDomain, TIMESTAMP_DOMAIN_NAMES};
+#[cfg(feature="net")]
+use resource::{TlsSettings, TlsVerification, WebhookPayload, STREAM_COMPRESSION_DEFLATE,
+               STREAM_COMPRESSION_NAMES, STREAM_COMPRESSION_NONE, TLS_VERIFICATION_NAMES,
+               DEF_MAX_RECONNECT_DELAY, DEF_RECONNECT_DELAY, MAX_RECONNECT_DELAY,
+               MIN_RECONNECT_DELAY, BatchFormat, BATCH_FORMAT_NAMES, DEF_BATCH_INTERVAL,
+               DEF_BATCH_SIZE, DEF_MAX_RETRIES, MAX_BATCH_INTERVAL, MAX_BATCH_SIZE,
+               MAX_MAX_RETRIES, MIN_BATCH_INTERVAL, MIN_BATCH_SIZE, MIN_MAX_RETRIES};
 use systemproperties::*;
 use crate::config::toml::document::*;
-use crate::config::toml::parse_file;
+use crate::config::toml::{parse_file, parse_str};
+pub use resource::{ResourceDesc, ResourceKind};
+pub use systemproperties::SystemProperties;
 
+pub(crate) mod adapter;
 pub(crate) mod datetimeformat;
+pub(crate) mod filter;
 pub(crate) mod output;
 pub(crate) mod resource;
 pub(crate) mod systemproperties;
@@ -65,7 +87,10 @@ pub(crate) mod toml;
 use crate::net::serverproperties::*;
 
 #[cfg(feature="net")]
-use crate::net::is_valid_url;
+use crate::net::{is_valid_remote_url, is_valid_url, parse_gelf_url, parse_http_or_https_url,
+                 parse_http_url, parse_url};
+#[cfg(feature="tls")]
+use crate::net::NetworkProtocol;
 
 /// Returns the system's configuration.
 /// If a filename is given, the configuration is read from that file, otherwise the defaults
@@ -79,7 +104,7 @@ use crate::net::is_valid_url;
 /// Coaly system configuration
 pub(crate) fn configuration(orig_info: &OriginatorInfo,
                             config_file_name: Option<&str>) -> Rc<Configuration> {
-    let mut cfg = if config_file_name.is_none() {
+    let cfg = if config_file_name.is_none() {
                       // no configuration file is specified, use default configuration
                       Configuration::default()
                   } else {
@@ -89,6 +114,188 @@ pub(crate) fn configuration(orig_info: &OriginatorInfo,
                           Err(msg) => Configuration::default_because_of_error(msg)
                       }
                   };
+    finalized(cfg, orig_info)
+}
+
+/// Returns the system's configuration from the given source.
+/// The defaults are used, if the source can't be processed. If only an overlay file can't be
+/// processed, the configuration text is used without overlay.
+/// 
+/// # Arguments
+/// * `orig_info` - information about application and local host
+/// * `source` - the configuration source
+/// 
+/// # Return values
+/// Coaly system configuration
+pub(crate) fn source_configuration(orig_info: &OriginatorInfo,
+                                   source: &ConfigSource) -> Rc<Configuration> {
+    let cfg = match source.parse() {
+                  Ok((doc, overlay_error)) => {
+                      let mut cfg = Configuration::from_toml_document(&doc);
+                      if let Some(ex) = overlay_error { cfg.add_message(ex); }
+                      cfg
+                  },
+                  Err(msg) => Configuration::default_because_of_error(msg)
+              };
+    finalized(cfg, orig_info)
+}
+
+/// Returns the configuration for a reconfiguration of the running system.
+/// Contrary to the initial configuration, defaults are not used if the configuration source
+/// can't be processed.
+/// 
+/// # Arguments
+/// * `orig_info` - information about application and local host
+/// * `source` - the configuration source
+/// 
+/// # Return values
+/// the configuration structure
+/// 
+/// # Errors
+/// Returns an error structure if the configuration file or text could not be read or parsed
+pub(crate) fn reconfiguration(orig_info: &OriginatorInfo,
+                              source: &ConfigSource) -> Result<Rc<Configuration>, CoalyException> {
+    let cause = match source.parse() {
+        Ok((doc, None)) => return Ok(finalized(Configuration::from_toml_document(&doc), orig_info)),
+        Ok((_, Some(cause))) => cause,
+        Err(cause) => cause
+    };
+    let mut ex = coalyxe!(E_CFG_RECONFIG_FAILED, source.name());
+    ex.set_cause(cause);
+    Err(ex)
+}
+
+/// Source of a custom configuration.
+#[derive(Clone, Debug)]
+pub(crate) enum ConfigSource {
+    // configuration file name
+    File(String),
+    // TOML formatted configuration text, optionally overlaid by the configuration file with
+    // given name, if it exists
+    Text(String, Option<String>)
+}
+impl ConfigSource {
+    /// Returns the name of the source used in messages.
+    pub(crate) fn name(&self) -> String {
+        match self {
+            ConfigSource::File(file_name) => file_name.clone(),
+            ConfigSource::Text(_, None) => String::from(EMBEDDED_CONFIG_NAME),
+            ConfigSource::Text(_, Some(file_name)) => format!("{}+{}", EMBEDDED_CONFIG_NAME,
+                                                              file_name)
+        }
+    }
+
+    /// Returns the name of the file to watch for changes, if any.
+    pub(crate) fn file_name(&self) -> Option<&str> {
+        match self {
+            ConfigSource::File(file_name) => Some(file_name),
+            ConfigSource::Text(_, file_name) => file_name.as_deref()
+        }
+    }
+
+    /// Parses the configuration source.
+    /// A missing overlay file is silently ignored.
+    ///
+    /// # Return values
+    /// the TOML document and an error structure, if the overlay file couldn't be processed
+    ///
+    /// # Errors
+    /// Returns an error structure if the configuration file or text could not be read or parsed
+    fn parse(&self) -> Result<(TomlDocument, Option<CoalyException>), CoalyException> {
+        match self {
+            ConfigSource::File(file_name) => Ok((parse_file(file_name)?, None)),
+            ConfigSource::Text(text, overlay_file_name) => {
+                let mut doc = parse_str(text)?;
+                let Some(file_name) = overlay_file_name else { return Ok((doc, None)) };
+                if ! Path::new(file_name).exists() { return Ok((doc, None)) }
+                match parse_file(file_name) {
+                    Ok(overlay) => {
+                        doc.overlay(overlay);
+                        Ok((doc, None))
+                    },
+                    Err(ex) => Ok((doc, Some(ex)))
+                }
+            }
+        }
+    }
+}
+
+/// Creates a mode change from a specification handed over at runtime.
+/// The specification is a TOML inline table with the same parameters as an entry in group
+/// modes of the configuration file, e.g.
+/// `{ trigger = "object", value = "^4711$", enabled = ["all"] }`.
+/// 
+/// # Arguments
+/// * `spec` - the mode change specification
+/// 
+/// # Return values
+/// the list containing the mode change
+/// 
+/// # Errors
+/// Returns an error structure if the specification could not be parsed or is invalid
+pub(crate) fn runtime_mode_change(spec: &str) -> Result<ModeChangeDescList, CoalyException> {
+    let mut msgs = Vec::<CoalyException>::new();
+    let mut m_chgs = ModeChangeDescList::new();
+    match parse_str(&format!("{} = {}", TOML_GRP_MODES, spec)) {
+        Ok(doc) => {
+            for (key, mode_spec) in doc.root_items() {
+                if not_table_item(mode_spec, key, None, &mut msgs) { continue }
+                read_mode(mode_spec, &mode_spec.line_nr(), &mut m_chgs, &mut msgs);
+            }
+        },
+        Err(cause) => msgs.push(cause)
+    }
+    if let Some(cause) = msgs.into_iter().next() {
+        let mut ex = coalyxe!(E_CFG_INV_RUNTIME_MODE_CHANGE, spec.to_string());
+        ex.set_cause(cause);
+        return Err(ex)
+    }
+    Ok(m_chgs)
+}
+
+/// Reads the record levels specified in environment variable COALY_LEVELS.
+/// The variable holds a comma separated list of record level names, e.g. `error,warning,debug`,
+/// and overrides the initially enabled levels from the configuration file, similar to RUST_LOG.
+/// Level `none` disables all levels.
+/// 
+/// # Arguments
+/// * `spec` - the value of the environment variable
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// a bit mask with all record levels or'ed, **None** if the variable contains no valid level
+fn read_env_levels(spec: &str, msgs: &mut Vec<CoalyException>) -> Option<u32> {
+    let mut bit_mask: Option<u32> = None;
+    for name in spec.split(',').map(|n| n.trim().to_lowercase()).filter(|n| ! n.is_empty()) {
+        if name == ENV_LEVEL_NONE {
+            bit_mask = Some(bit_mask.unwrap_or(0));
+            continue
+        }
+        if let Ok(lvl_id) = RecordLevelId::from_str(&name) {
+            bit_mask = Some(bit_mask.unwrap_or(0) | lvl_id as u32);
+            continue
+        }
+        let ex = coalyxw!(W_CFG_INV_ENV_LEVEL, name.to_string(), ENV_COALY_LEVELS.to_string());
+        msgs.push(suggest_value(ex, &name, RECORD_LEVEL_NAMES));
+    }
+    bit_mask
+}
+
+/// Completes the given configuration with the output and fallback paths.
+/// 
+/// # Arguments
+/// * `cfg` - the configuration read from file or defaults
+/// * `orig_info` - information about application and local host
+/// 
+/// # Return values
+/// the configuration structure
+fn finalized(mut cfg: Configuration,
+             orig_info: &OriginatorInfo) -> Rc<Configuration> {
+    if let Ok(spec) = std::env::var(ENV_COALY_LEVELS) {
+        if let Some(levels) = read_env_levels(&spec, &mut cfg.messages) {
+            cfg.system_properties_mut().set_initially_enabled_levels(levels);
+        }
+    }
     if cfg.resources().needs_output_path() {
         let mut opath = std::env::temp_dir();
         if let Ok(cwd) = std::env::current_dir() {
@@ -124,13 +331,15 @@ pub(crate) fn configuration(orig_info: &OriginatorInfo,
 
 /// Holds all configuration definitions, either defaults or as specified in configuration file.
 #[cfg(not(feature="net"))]
-pub(crate) struct Configuration {
+pub struct Configuration {
     // basic settings
     system_properties: SystemProperties,
     // date-time format descriptors
     date_time_formats: DateTimeFormatDescMap,
     // output format descriptors
     output_formats: OutputFormatDescMap,
+    // color settings for console resources, if colored output is configured
+    console_colors: Option<ConsoleColorsDesc>,
     // settings when resources operate in buffered mode
     buffer_policies: BufferPolicyMap,
     // rollover behaviours for file based resources
@@ -139,11 +348,13 @@ pub(crate) struct Configuration {
     resources: ResourceDescList,
     // output mode change descriptors
     mode_changes: ModeChangeDescList,
+    // mappings for records from the log crate or tracing bridges
+    adapter_mappings: AdapterMappingDescList,
     // errors or warnings which occurred during configuration file processing
     messages: Vec::<CoalyException>
 }
 #[cfg(feature="net")]
-pub(crate) struct Configuration {
+pub struct Configuration {
     // basic settings
     system_properties: SystemProperties,
     // optional server settings
@@ -152,6 +363,8 @@ pub(crate) struct Configuration {
     date_time_formats: DateTimeFormatDescMap,
     // output format descriptors
     output_formats: OutputFormatDescMap,
+    // color settings for console resources, if colored output is configured
+    console_colors: Option<ConsoleColorsDesc>,
     // settings when resources operate in buffered mode
     buffer_policies: BufferPolicyMap,
     // rollover behaviours for file based resources
@@ -160,13 +373,15 @@ pub(crate) struct Configuration {
     resources: ResourceDescList,
     // output mode change descriptors
     mode_changes: ModeChangeDescList,
+    // mappings for records from the log crate or tracing bridges
+    adapter_mappings: AdapterMappingDescList,
     // errors or warnings which occurred during configuration file processing
     messages: Vec::<CoalyException>
 }
 impl Configuration {
     /// Returns the system properties
     #[inline]
-    pub(crate) fn system_properties(&self) -> &SystemProperties { &self.system_properties }
+    pub fn system_properties(&self) -> &SystemProperties { &self.system_properties }
 
     /// Returns the system properties
     #[inline]
@@ -188,17 +403,41 @@ impl Configuration {
 //    pub(crate) fn output_formats(&self) -> &OutputFormatDescMap { &self.output_formats }
 
     /// Returns the output format descriptor with the given name or default.
-    #[inline]
+    /// Output format pretty is built-in, unless the configuration file defines an output
+    /// format with that name.
     pub(crate) fn output_format(&self, name: &Option<String>) -> &OutputFormatDesc {
+        if let Some(n) = name {
+            if n == PRETTY_FORMAT_NAME && ! self.output_formats.contains(n) {
+                return &PRETTY_OUTPUT_FORMAT
+            }
+        }
         self.output_formats.find(name)
     }
 
+    /// Indicates whether the configuration file defines an output format with the given name.
+    #[inline]
+    pub(crate) fn defines_output_format(&self, name: &str) -> bool {
+        self.output_formats.contains(name)
+    }
+
+    /// Returns the color settings for console resources, **None** if colored output is not
+    /// configured.
+    #[inline]
+    pub(crate) fn console_colors(&self) -> &Option<ConsoleColorsDesc> { &self.console_colors }
+
     /// Returns the buffer policy with the given name or default.
     #[inline]
     pub(crate) fn buffer_policy(&self, name: &Option<String>) -> &BufferPolicy {
         self.buffer_policies.find(name)
     }
 
+    /// Indicates whether at least one buffer policy flushes the buffer upon a signal.
+    pub(crate) fn flushes_on_signal(&self) -> bool {
+        std::iter::once(self.buffer_policies.find(&None))
+            .chain(self.buffer_policies.custom_values())
+            .any(|p| p.flush_conditions() & BufferFlushCondition::Signal as u32 != 0)
+    }
+
     /// Returns the rollover policy with the given name or default.
     #[inline]
     pub(crate) fn rollover_policy(&self, name: &Option<String>) -> &RolloverPolicy {
@@ -209,17 +448,64 @@ impl Configuration {
     #[inline]
     pub(crate) fn resources(&self) -> &ResourceDescList { &self.resources }
 
+    /// Returns the descriptors of all output resources.
+    /// If the configuration doesn't define any resource, the default resource is returned.
+    #[inline]
+    pub fn resource_descs(&self) -> std::slice::Iter<'_, ResourceDesc> { self.resources.elements() }
+
     /// Returns a reference to the mode change descriptors
     #[inline]
     pub(crate) fn mode_changes(&self) -> &ModeChangeDescList { &self.mode_changes }
 
+    /// Returns the number of mode changes defined
+    #[inline]
+    pub fn mode_change_count(&self) -> usize { self.mode_changes.len() }
+
+    /// Returns a reference to the mappings for records from the log crate or tracing bridges
+    #[cfg(any(test, feature="log-compat", feature="tracing"))]
+    #[inline]
+    pub(crate) fn adapter_mappings(&self) -> &AdapterMappingDescList { &self.adapter_mappings }
+
     /// Returns a reference to the list of warnings.
     #[inline]
-    pub(crate) fn messages(&self) -> &Vec<CoalyException> { &self.messages }
+    pub fn messages(&self) -> &Vec<CoalyException> { &self.messages }
+
+    /// Indicates whether the settings relevant for output resources differ from the ones in
+    /// another configuration.
+    /// Mode changes, the initially enabled or buffered record levels and the watch flag for
+    /// the configuration file are not taken into account, they can be changed without affecting
+    /// output resources.
+    /// 
+    /// # Arguments
+    /// * `other` - the configuration to compare with
+    /// 
+    /// # Return values
+    /// **true** if system properties, formats, policies or resources differ
+    pub(crate) fn output_settings_differ(&self, other: &Configuration) -> bool {
+        let initial_mode = self.system_properties.initial_output_mode();
+        let mut other_props = other.system_properties.clone();
+        other_props.set_initially_enabled_levels(initial_mode & 0xffff);
+        other_props.set_initially_buffered_levels(initial_mode >> 16);
+        other_props.set_watch(self.system_properties.watch());
+        #[cfg(feature="net")]
+        if format!("{:?}", self.server_properties) != format!("{:?}", other.server_properties) {
+            return true
+        }
+        format!("{:?}/{:?}/{:?}/{:?}/{:?}/{:?}/{:?}", self.system_properties,
+                self.date_time_formats, self.output_formats, self.console_colors,
+                self.buffer_policies, self.rollover_policies, self.resources)
+        != format!("{:?}/{:?}/{:?}/{:?}/{:?}/{:?}/{:?}", other_props, other.date_time_formats,
+                   other.output_formats, other.console_colors, other.buffer_policies,
+                   other.rollover_policies, other.resources)
+    }
 
     /// Returns the names of all environment variables referenced in output formats or file names
+    /// or configured for record enrichment upon application start
     pub(crate) fn referenced_env_vars(&self) -> HashSet<String> {
         let mut var_names = HashSet::<String>::new();
+        for var_name in self.system_properties.env_snapshot() {
+            var_names.insert(var_name.to_string());
+        }
         for outp_fmt in self.output_formats.custom_values() {
             for rec_fmt in outp_fmt.specific_formats() {
                 merge_env_vars(rec_fmt.items(), &mut var_names);
@@ -230,6 +516,9 @@ impl Configuration {
                 merge_env_vars(file_data.file_name_spec(), &mut var_names);
             }
         }
+        for capture_name in self.mode_changes.capture_names() {
+            merge_env_vars(capture_name, &mut var_names);
+        }
         var_names
     }
 
@@ -244,86 +533,125 @@ impl Configuration {
     /// # Errors
     /// A structure containing error information, if the configuration file can't be read or
     /// contains errors
-    #[cfg(not(feature="net"))]
     fn from_config_file(file_name: &str) -> Result<Configuration, CoalyException> {
+        Ok(Configuration::from_toml_document(&parse_file(file_name)?))
+    }
+
+    /// Returns a custom configuration from the specified TOML document.
+    /// 
+    /// # Arguments
+    /// * `cust_toml` - the parsed TOML formatted configuration
+    /// 
+    /// # Return values
+    /// The custom configuration
+    #[cfg(not(feature="net"))]
+    fn from_toml_document(cust_toml: &TomlDocument) -> Configuration {
         let mut sys_props: Option<SystemProperties> = None;
         let mut dt_fmts: Option<DateTimeFormatDescMap> = None;
         let mut outp_fmts: Option<OutputFormatDescMap> = None;
+        let mut colors: Option<ConsoleColorsDesc> = None;
         let mut buf_pols: Option<BufferPolicyMap> = None;
         let mut rovr_pols: Option<RolloverPolicyMap> = None;
+        let mut thr_pols = ThrottlePolicyMap::new();
+        let mut smp_pols = SamplingPolicyMap::new();
+        let mut filters = RecordFilterDescMap::new();
         let mut res: Option<ResourceDescList> = None;
+        let mut res_item: Option<&TomlValueItem> = None;
         let mut mod_chgs: Option<ModeChangeDescList> = None;
+        let mut adapters = AdapterMappingDescList::new();
         let mut msgs: Vec<CoalyException> = Vec::new();
-        let cust_toml = parse_file(file_name)?;
         for (key, val) in cust_toml.root_items() {
             match key.as_str() {
                 TOML_GRP_SYSTEM => sys_props = read_system_properties(val, &mut msgs),
-                TOML_GRP_POLICIES => read_policies(val, &mut buf_pols, &mut rovr_pols, &mut msgs),
-                TOML_GRP_FORMATS => read_formats(val, &mut dt_fmts, &mut outp_fmts, &mut msgs),
-                TOML_GRP_RESOURCES => res = read_resources(val, &mut msgs),
+                TOML_GRP_POLICIES => read_policies(val, &mut buf_pols, &mut rovr_pols,
+                                                   &mut thr_pols, &mut smp_pols, &mut msgs),
+                TOML_GRP_FORMATS => read_formats(val, &mut dt_fmts, &mut outp_fmts, &mut colors,
+                                                 &mut msgs),
+                TOML_GRP_FILTERS => filters = read_filters(val, &mut msgs),
+                TOML_GRP_RESOURCES => res_item = Some(val),
                 TOML_GRP_MODES => mod_chgs = read_modes(val, &mut msgs),
-                _ => msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, val.line_nr(), key.clone()))
+                TOML_GRP_ADAPTERS => adapters = read_adapters(val, &mut msgs),
+                _ => msgs.push(suggest_key(coalyxw!(W_CFG_UNKNOWN_KEY, val.line_nr(), key.clone()),
+                                           key, ROOT_KEYS, None))
             }
         }
-        let custom_cfg = Configuration {
+        // resources may reference filters, throttle and sampling policies, hence they are read
+        // after all other groups
+        if let Some(val) = res_item {
+            res = read_resources(val, &filters, &thr_pols, &smp_pols, &mut msgs);
+        }
+        Configuration {
             system_properties: sys_props.unwrap_or_default(),
             date_time_formats: dt_fmts.unwrap_or_default(),
             output_formats: outp_fmts.unwrap_or_default(),
+            console_colors: colors,
             buffer_policies: buf_pols.unwrap_or_default(),
             rollover_policies: rovr_pols.unwrap_or_default(),
             resources: res.unwrap_or_default(),
             mode_changes:mod_chgs.unwrap_or_default(),
+            adapter_mappings: adapters,
             messages: msgs
-        };
-        Ok(custom_cfg)
+        }
     }
 
-    /// Returns a custom configuration from the file with the specified name.
+    /// Returns a custom configuration from the specified TOML document.
     /// 
     /// # Arguments
-    /// * `file_name` - the name of TOML formatted configuration file
+    /// * `cust_toml` - the parsed TOML formatted configuration
     /// 
     /// # Return values
     /// The custom configuration
-    /// 
-    /// # Errors
-    /// A structure containing error information, if the configuration file can't be read or
-    /// contains errors
     #[cfg(feature="net")]
-    fn from_config_file(file_name: &str) -> Result<Configuration, CoalyException> {
+    fn from_toml_document(cust_toml: &TomlDocument) -> Configuration {
         let mut sys_props: Option<SystemProperties> = None;
         let mut srv_props: Option<ServerProperties> = None;
         let mut dt_fmts: Option<DateTimeFormatDescMap> = None;
         let mut outp_fmts: Option<OutputFormatDescMap> = None;
+        let mut colors: Option<ConsoleColorsDesc> = None;
         let mut buf_pols: Option<BufferPolicyMap> = None;
         let mut rovr_pols: Option<RolloverPolicyMap> = None;
+        let mut thr_pols = ThrottlePolicyMap::new();
+        let mut smp_pols = SamplingPolicyMap::new();
+        let mut filters = RecordFilterDescMap::new();
         let mut res: Option<ResourceDescList> = None;
+        let mut res_item: Option<&TomlValueItem> = None;
         let mut mod_chgs: Option<ModeChangeDescList> = None;
+        let mut adapters = AdapterMappingDescList::new();
         let mut msgs: Vec<CoalyException> = Vec::new();
-        let cust_toml = parse_file(file_name)?;
         for (key, val) in cust_toml.root_items() {
             match key.as_str() {
                 TOML_GRP_SYSTEM => sys_props = read_system_properties(val, &mut msgs),
                 TOML_GRP_SERVER => srv_props = read_server_properties(val, &mut msgs),
-                TOML_GRP_POLICIES => read_policies(val, &mut buf_pols, &mut rovr_pols, &mut msgs),
-                TOML_GRP_FORMATS => read_formats(val, &mut dt_fmts, &mut outp_fmts, &mut msgs),
-                TOML_GRP_RESOURCES => res = read_resources(val, &mut msgs),
+                TOML_GRP_POLICIES => read_policies(val, &mut buf_pols, &mut rovr_pols,
+                                                   &mut thr_pols, &mut smp_pols, &mut msgs),
+                TOML_GRP_FORMATS => read_formats(val, &mut dt_fmts, &mut outp_fmts, &mut colors,
+                                                 &mut msgs),
+                TOML_GRP_FILTERS => filters = read_filters(val, &mut msgs),
+                TOML_GRP_RESOURCES => res_item = Some(val),
                 TOML_GRP_MODES => mod_chgs = read_modes(val, &mut msgs),
-                _ => msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, val.line_nr(), key.clone()))
+                TOML_GRP_ADAPTERS => adapters = read_adapters(val, &mut msgs),
+                _ => msgs.push(suggest_key(coalyxw!(W_CFG_UNKNOWN_KEY, val.line_nr(), key.clone()),
+                                           key, ROOT_KEYS, None))
             }
         }
-        let custom_cfg = Configuration {
+        // resources may reference filters, throttle and sampling policies, hence they are read
+        // after all other groups
+        if let Some(val) = res_item {
+            res = read_resources(val, &filters, &thr_pols, &smp_pols, &mut msgs);
+        }
+        Configuration {
             system_properties: sys_props.unwrap_or_default(),
             server_properties: srv_props,
             date_time_formats: dt_fmts.unwrap_or_default(),
             output_formats: outp_fmts.unwrap_or_default(),
+            console_colors: colors,
             buffer_policies: buf_pols.unwrap_or_default(),
             rollover_policies: rovr_pols.unwrap_or_default(),
             resources: res.unwrap_or_default(),
             mode_changes:mod_chgs.unwrap_or_default(),
+            adapter_mappings: adapters,
             messages: msgs
-        };
-        Ok(custom_cfg)
+        }
     }
 
     /// Returns default configuration with given error message.
@@ -341,6 +669,25 @@ impl Configuration {
     #[inline]
     fn add_message(&mut self, msg: CoalyException) { self.messages.push(msg) }
 }
+impl FromStr for Configuration {
+    type Err = CoalyException;
+
+    /// Returns a custom configuration from the specified TOML formatted text.
+    /// Neither files nor environment variables are accessed, hence the function is suitable
+    /// for unit tests of configuration snippets.
+    ///
+    /// # Arguments
+    /// * `toml_text` - the TOML formatted configuration
+    ///
+    /// # Return values
+    /// The custom configuration, warnings may be retrieved using function messages
+    ///
+    /// # Errors
+    /// A structure containing error information, if the text is not valid TOML
+    fn from_str(toml_text: &str) -> Result<Self, Self::Err> {
+        Ok(Configuration::from_toml_document(&parse_str(toml_text)?))
+    }
+}
 #[cfg(not(feature="net"))]
 impl Default for Configuration {
     fn default() -> Self {
@@ -348,10 +695,12 @@ impl Default for Configuration {
             system_properties: SystemProperties::default(),
             date_time_formats: DateTimeFormatDescMap::default(),
             output_formats: OutputFormatDescMap::default(),
+            console_colors: None,
             buffer_policies: BufferPolicyMap::default(),
             rollover_policies: RolloverPolicyMap::default(),
             resources: ResourceDescList::default(),
             mode_changes: ModeChangeDescList::new(),
+            adapter_mappings: AdapterMappingDescList::new(),
             messages: Vec::<CoalyException>::new()
         }
     }
@@ -364,10 +713,12 @@ impl Default for Configuration {
             server_properties: None,
             date_time_formats: DateTimeFormatDescMap::default(),
             output_formats: OutputFormatDescMap::default(),
+            console_colors: None,
             buffer_policies: BufferPolicyMap::default(),
             rollover_policies: RolloverPolicyMap::default(),
             resources: ResourceDescList::default(),
             mode_changes: ModeChangeDescList::new(),
+            adapter_mappings: AdapterMappingDescList::new(),
             messages: Vec::<CoalyException>::new()
         }
     }
@@ -379,7 +730,12 @@ impl Debug for Configuration {
                    self.system_properties, self.date_time_formats,
                    self.output_formats, self.buffer_policies, self.rollover_policies,
                    self.resources, self.mode_changes
-              )
+              )?;
+        if let Some(colors) = &self.console_colors { write!(f, "/COL:{:?}", colors)?; }
+        if ! self.adapter_mappings.is_empty() {
+            write!(f, "/ADPT:{:?}", self.adapter_mappings)?;
+        }
+        Ok(())
     }
 }
 #[cfg(feature="net")]
@@ -390,14 +746,19 @@ impl Debug for Configuration {
                    self.system_properties, self.date_time_formats,
                    self.output_formats, self.buffer_policies, self.rollover_policies,
                    self.resources, self.mode_changes
-                  )
+                  )?;
         } else {
             write!(f, "SYSP:{:?}/SRVP:{:?}/DATF:{:?}/OUTF:{:?}/BUFP:{:?}/ROVP:{:?}/RES:{:?}/MODS:{:?}",
                    self.system_properties, self.server_properties.as_ref().unwrap(),
                    self.date_time_formats, self.output_formats, self.buffer_policies,
                    self.rollover_policies, self.resources, self.mode_changes
-                  )
+                  )?;
+        }
+        if let Some(colors) = &self.console_colors { write!(f, "/COL:{:?}", colors)?; }
+        if ! self.adapter_mappings.is_empty() {
+            write!(f, "/ADPT:{:?}", self.adapter_mappings)?;
         }
+        Ok(())
     }
 }
 
@@ -413,6 +774,8 @@ fn read_system_properties(system_item: &TomlValueItem,
                           msgs: &mut Vec<CoalyException>) -> Option<SystemProperties> {
     if not_table_item(system_item, TOML_GRP_SYSTEM, None, msgs) { return None }
     let mut sp = SystemProperties::default();
+    let mut key_file: Option<String> = None;
+    let mut passphrase: Option<(String, String)> = None;
     for (sys_key, sys_val) in system_item.child_items().unwrap() {
         match sys_key.as_str() {
             TOML_PAR_VERSION => (
@@ -429,22 +792,138 @@ fn read_system_properties(system_item: &TomlValueItem,
                     sp.set_application_name(&sys_val.value().as_str().unwrap());
                 }
             },
+            TOML_PAR_BACKTRACE_LEVELS => {
+                if let Some(lvls) = read_levels_array(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    sp.set_backtrace_levels(lvls);
+                }
+            },
+            TOML_PAR_BUFFER_MEMORY_LIMIT => {
+                if let Some(limit) = size_par(sys_val, sys_key, TOML_GRP_SYSTEM,
+                                              MIN_BUFFER_MEMORY_LIMIT, MAX_BUFFER_MEMORY_LIMIT,
+                                              DEFAULT_BUFFER_MEMORY_LIMIT, msgs) {
+                    sp.set_buffer_memory_limit(limit);
+                }
+            },
+            TOML_PAR_SHUTDOWN_TIMEOUT => {
+                if int_par(sys_val, sys_key, TOML_GRP_SYSTEM, MIN_SHUTDOWN_TIMEOUT,
+                           MAX_SHUTDOWN_TIMEOUT, DEFAULT_SHUTDOWN_TIMEOUT, msgs) {
+                    sp.set_shutdown_timeout(sys_val.value().as_integer().unwrap() as u64);
+                }
+            },
+            TOML_PAR_COUNTER_INTERVAL => {
+                if int_par(sys_val, sys_key, TOML_GRP_SYSTEM, MIN_COUNTER_INTERVAL,
+                           MAX_COUNTER_INTERVAL, DEFAULT_COUNTER_INTERVAL, msgs) {
+                    sp.set_counter_interval(sys_val.value().as_integer().unwrap() as u64);
+                }
+            },
+            TOML_PAR_MAX_OPEN_FILES => {
+                if int_par(sys_val, sys_key, TOML_GRP_SYSTEM, MIN_MAX_OPEN_FILES,
+                           MAX_MAX_OPEN_FILES, DEFAULT_MAX_OPEN_FILES, msgs) {
+                    sp.set_max_open_files(sys_val.value().as_integer().unwrap() as usize);
+                }
+            },
+            #[cfg(feature="net")]
+            TOML_PAR_METRICS_ADDRESS => {
+                if str_par(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    let addr = sys_val.value().as_str().unwrap();
+                    match addr.parse::<std::net::SocketAddr>() {
+                        Ok(a) => sp.set_metrics_address(a),
+                        Err(_) => msgs.push(coalyxw!(W_CFG_INV_METRICS_ADDR,
+                                                     sys_val.line_nr(), addr.to_string()))
+                    }
+                }
+            },
+            TOML_PAR_CLOCK => {
+                let mut clock_str = String::from("");
+                if str_par(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    clock_str = sys_val.value().as_str().unwrap();
+                    if let Ok(clock) = RecordClock::from_str(&clock_str) {
+                        sp.set_record_clock(clock);
+                        continue
+                    }
+                }
+                msgs.push(coalyxw!(W_CFG_INV_RECORD_CLOCK, sys_val.line_nr(), clock_str));
+            },
+            TOML_PAR_WATCH => {
+                if bool_par(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    sp.set_watch(sys_val.value().as_bool().unwrap());
+                }
+            },
+            TOML_PAR_ENCRYPTION_KEY_FILE => {
+                if str_par(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    key_file = Some(sys_val.value().as_str().unwrap());
+                }
+            },
+            TOML_PAR_ENCRYPTION_PASSPHRASE => {
+                if str_par(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    passphrase = Some((sys_val.value().as_str().unwrap(), sys_val.line_nr()));
+                }
+            },
             TOML_PAR_CHG_STACK_SIZE => {
                 if int_par(sys_val, sys_key, TOML_GRP_SYSTEM, MIN_CHANGE_STACK_SIZE,
                            MAX_CHANGE_STACK_SIZE, DEFAULT_CHANGE_STACK_SIZE, msgs) {
                     sp.set_change_stack_size(sys_val.value().as_integer().unwrap() as usize);
                 }
             },
+            TOML_PAR_ENV_PER_RECORD => {
+                if let Some(names) = read_str_array(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    sp.set_env_per_record(names);
+                }
+            },
+            TOML_PAR_ENV_SNAPSHOT => {
+                if let Some(names) = read_str_array(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    sp.set_env_snapshot(names);
+                }
+            },
+            TOML_PAR_ENV_CACHE_TTL => {
+                if int_par(sys_val, sys_key, TOML_GRP_SYSTEM, MIN_ENV_CACHE_TTL,
+                           MAX_ENV_CACHE_TTL, DEFAULT_ENV_CACHE_TTL, msgs) {
+                    sp.set_env_cache_ttl(sys_val.value().as_integer().unwrap() as u64);
+                }
+            },
             TOML_PAR_FALLBACK_PATH => {
                 if str_par(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
                     sp.set_fallback_path(&sys_val.value().as_str().unwrap());
                 }
             },
+            TOML_PAR_FN_MAX_VAR_LENGTH => {
+                if int_par(sys_val, sys_key, TOML_GRP_SYSTEM, MIN_FILE_NAME_MAX_VAR_LENGTH,
+                           MAX_FILE_NAME_MAX_VAR_LENGTH, DEFAULT_FILE_NAME_MAX_VAR_LENGTH, msgs) {
+                    sp.set_file_name_max_var_length(sys_val.value().as_integer().unwrap() as usize);
+                }
+            },
+            TOML_PAR_FN_REPLACEMENT_CHAR => {
+                if let Some(ch_str) = sys_val.value().as_str() {
+                    let mut chars = ch_str.chars();
+                    if let (Some(ch), None) = (chars.next(), chars.next()) {
+                        if is_valid_file_name_char(ch) {
+                            sp.set_file_name_replacement_char(ch);
+                            continue
+                        }
+                    }
+                }
+                let full_key = format!("{}.{}", TOML_GRP_SYSTEM, sys_key);
+                msgs.push(coalyxw!(W_CFG_INV_FN_REPLACEMENT_CHAR, sys_val.line_nr(), full_key,
+                                   DEFAULT_FILE_NAME_REPLACEMENT_CHAR.to_string()));
+            },
             TOML_PAR_OUTPUT_PATH => {
                 if str_par(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
                     sp.set_output_path(&sys_val.value().as_str().unwrap());
                 }
             },
+            TOML_PAR_SUPPRESS => {
+                if let Some(specs) = read_str_array(sys_val, sys_key, TOML_GRP_SYSTEM, msgs) {
+                    let mut rules = Vec::<SuppressionRule>::with_capacity(specs.len());
+                    for spec in specs {
+                        match SuppressionRule::from_spec(&spec) {
+                            Some(r) => rules.push(r),
+                            None => msgs.push(coalyxw!(W_CFG_INV_SUPPRESSION_RULE,
+                                                       sys_val.line_nr(), spec))
+                        }
+                    }
+                    sp.set_suppressions(rules);
+                }
+            },
             TOML_GRP_LEVELS => {
                 let cust_lvls = read_levels(sys_val, msgs);
                 sp.set_record_levels(cust_lvls);
@@ -466,39 +945,260 @@ fn read_system_properties(system_item: &TomlValueItem,
                         },
                         _ => {
                             let full_key = format!("{}.{}", m_grp_key, m_key);
-                            msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, m_val.line_nr(), full_key));
+                            let ex = coalyxw!(W_CFG_UNKNOWN_KEY, m_val.line_nr(), full_key);
+                            msgs.push(suggest_key(ex, m_key, SYSTEM_MODE_KEYS, Some(&m_grp_key)));
                         }
                     }
                 }
             },
+            TOML_GRP_DEGRADED => {
+                if let Some(profile) = read_degradation_profile(sys_val, msgs) {
+                    sp.set_degradation(profile);
+                }
+            },
+            TOML_GRP_CLEANUP => {
+                if let Some(cleanup) = read_stale_file_cleanup(sys_val, msgs) {
+                    sp.set_stale_file_cleanup(cleanup);
+                }
+            },
+            TOML_GRP_PROBLEMS => {
+                if let Some(reporting) = read_problem_reporting(sys_val, msgs) {
+                    sp.set_problem_reporting(reporting);
+                }
+            },
             _ => {
                 let full_key = format!("{}.{}", TOML_GRP_SYSTEM, sys_key);
-                msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, sys_val.line_nr(), full_key));
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, sys_val.line_nr(), full_key);
+                msgs.push(suggest_key(ex, sys_key, SYSTEM_KEYS, Some(TOML_GRP_SYSTEM)));
             }
         }
     }
+    match (key_file, passphrase) {
+        (Some(kf), pp) => {
+            if let Some((_, lnr)) = pp { msgs.push(coalyxw!(W_CFG_ENCRYPTION_KEY_AMBIGUOUS, lnr)); }
+            sp.set_encryption_key(EncryptionKeySpec::KeyFile(kf));
+        },
+        (None, Some((pp, _))) => sp.set_encryption_key(EncryptionKeySpec::Passphrase(pp)),
+        (None, None) => ()
+    }
     Some(sp)
 }
 
-/// Reads rollover and buffer policies from custom configuration.
+/// Reads the profile for degraded mode from the custom configuration file.
+/// 
+/// # Arguments
+/// * `degraded_item` - the value item for the system.degraded group in the custom TOML document
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the profile for degraded mode, **None** if the group is not a TOML table
+fn read_degradation_profile(degraded_item: &TomlValueItem,
+                            msgs: &mut Vec<CoalyException>) -> Option<DegradationProfile> {
+    let grp_key = format!("{}.{}", TOML_GRP_SYSTEM, TOML_GRP_DEGRADED);
+    if not_table_item(degraded_item, &grp_key, None, msgs) { return None }
+    let mut profile = DegradationProfile::default();
+    for (d_key, d_val) in degraded_item.child_items().unwrap() {
+        match d_key.as_str() {
+            TOML_PAR_ENABLED => {
+                if let Some(l_mask) = read_levels_array(d_val, d_key, &grp_key, msgs) {
+                    profile.set_enabled_levels(l_mask);
+                }
+            },
+            TOML_GRP_RESOURCES => {
+                if let Some(names) = read_str_array(d_val, d_key, &grp_key, msgs) {
+                    let mut kinds = Vec::<String>::new();
+                    for name in names {
+                        if ResourceKind::from_str(&name).is_ok() {
+                            kinds.push(name);
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_RES_KIND, d_val.line_nr(), name.clone());
+                        msgs.push(suggest_resource_kind(ex, &name));
+                    }
+                    profile.set_resource_kinds(kinds);
+                }
+            },
+            TOML_PAR_DEGRADE_AFTER => {
+                if int_par(d_val, d_key, &grp_key, MIN_DEGRADE_AFTER,
+                           MAX_DEGRADE_AFTER, DEFAULT_DEGRADE_AFTER, msgs) {
+                    profile.set_degrade_after(d_val.value().as_integer().unwrap() as u64);
+                }
+            },
+            TOML_PAR_RECOVER_AFTER => {
+                if int_par(d_val, d_key, &grp_key, MIN_RECOVER_AFTER,
+                           MAX_RECOVER_AFTER, DEFAULT_RECOVER_AFTER, msgs) {
+                    profile.set_recover_after(d_val.value().as_integer().unwrap() as u64);
+                }
+            },
+            TOML_PAR_OVERLOAD_RATE => {
+                if int_par(d_val, d_key, &grp_key, MIN_OVERLOAD_RATE,
+                           MAX_OVERLOAD_RATE, DEFAULT_OVERLOAD_RATE, msgs) {
+                    profile.set_overload_rate(d_val.value().as_integer().unwrap() as u64);
+                }
+            },
+            _ => {
+                let full_key = format!("{}.{}", grp_key, d_key);
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, d_val.line_nr(), full_key);
+                msgs.push(suggest_key(ex, d_key, SYSTEM_DEGRADED_KEYS, Some(&grp_key)));
+            }
+        }
+    }
+    Some(profile)
+}
+
+/// Reads the settings for the cleanup of orphaned output files upon startup from custom
+/// configuration.
+/// 
+/// # Arguments
+/// * `cleanup_item` - the value item for the cleanup settings in the custom TOML document
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the cleanup settings, **None** if the item is not a table
+fn read_stale_file_cleanup(cleanup_item: &TomlValueItem,
+                           msgs: &mut Vec<CoalyException>) -> Option<StaleFileCleanup> {
+    let grp_key = format!("{}.{}", TOML_GRP_SYSTEM, TOML_GRP_CLEANUP);
+    if not_table_item(cleanup_item, &grp_key, None, msgs) { return None }
+    let mut cleanup = StaleFileCleanup::default();
+    for (c_key, c_val) in cleanup_item.child_items().unwrap() {
+        match c_key.as_str() {
+            TOML_PAR_RETENTION => {
+                if int_par(c_val, c_key, &grp_key, MIN_CLEANUP_RETENTION,
+                           MAX_CLEANUP_RETENTION, DEFAULT_CLEANUP_RETENTION, msgs) {
+                    cleanup.set_retention(c_val.value().as_integer().unwrap() as u64);
+                }
+            },
+            TOML_PAR_COMPRESSION => {
+                let mut ca_str = String::from("");
+                if str_par(c_val, c_key, &grp_key, msgs) {
+                    ca_str = c_val.value().as_str().unwrap();
+                    if let Ok(ca) = CompressionAlgorithm::from_str(&ca_str) {
+                        #[cfg(not(feature="compression"))]
+                        if ca != CompressionAlgorithm::None {
+                            msgs.push(coalyxw!(W_CFG_COMPR_NOT_SUPPORTED, c_val.line_nr()));
+                            continue;
+                        }
+                        cleanup.set_compression(ca);
+                        continue
+                    }
+                }
+                msgs.push(coalyxw!(W_CFG_INV_COMPR_ALGO, c_val.line_nr(), ca_str,
+                                   format!("{}", CompressionAlgorithm::default())));
+            },
+            _ => {
+                let full_key = format!("{}.{}", grp_key, c_key);
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, c_val.line_nr(), full_key);
+                msgs.push(suggest_key(ex, c_key, SYSTEM_CLEANUP_KEYS, Some(&grp_key)));
+            }
+        }
+    }
+    Some(cleanup)
+}
+
+/// Reads the settings for the reporting of problems detected by Coaly itself from custom
+/// configuration.
+/// 
+/// # Arguments
+/// * `problems_item` - the value item for the problem settings in the custom TOML document
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the problem reporting settings, **None** if the item is not a table
+fn read_problem_reporting(problems_item: &TomlValueItem,
+                          msgs: &mut Vec<CoalyException>) -> Option<ProblemReporting> {
+    let grp_key = format!("{}.{}", TOML_GRP_SYSTEM, TOML_GRP_PROBLEMS);
+    if not_table_item(problems_item, &grp_key, None, msgs) { return None }
+    let mut reporting = ProblemReporting::default();
+    let mut file_name = String::from(DEFAULT_PROBLEM_FILE_NAME);
+    for (p_key, p_val) in problems_item.child_items().unwrap() {
+        match p_key.as_str() {
+            TOML_PAR_LEVEL => {
+                if str_par(p_val, p_key, &grp_key, msgs) {
+                    let level_name = p_val.value().as_str().unwrap();
+                    if let Ok(sev) = min_severity_from_str(&level_name) {
+                        reporting.set_min_severity(sev);
+                        continue
+                    }
+                    msgs.push(coalyxw!(W_CFG_INV_PROBLEM_LEVEL, p_val.line_nr(), level_name));
+                }
+            },
+            TOML_PAR_TARGET => {
+                if str_par(p_val, p_key, &grp_key, msgs) {
+                    let target_name = p_val.value().as_str().unwrap();
+                    if let Ok(target) = ProblemTarget::from_str(&target_name) {
+                        reporting.set_target(target);
+                        continue
+                    }
+                    msgs.push(coalyxw!(W_CFG_INV_PROBLEM_TARGET, p_val.line_nr(), target_name));
+                }
+            },
+            TOML_PAR_NAME => {
+                if str_par(p_val, p_key, &grp_key, msgs) {
+                    file_name = p_val.value().as_str().unwrap();
+                }
+            },
+            TOML_PAR_RATE_LIMIT => {
+                if int_par(p_val, p_key, &grp_key, MIN_PROBLEM_RATE_LIMIT,
+                           MAX_PROBLEM_RATE_LIMIT, DEFAULT_PROBLEM_RATE_LIMIT, msgs) {
+                    reporting.set_rate_limit(p_val.value().as_integer().unwrap() as u32);
+                }
+            },
+            TOML_PAR_TRACE_PIPELINE => {
+                if int_par(p_val, p_key, &grp_key, MIN_PIPELINE_TRACE,
+                           MAX_PIPELINE_TRACE, DEFAULT_PIPELINE_TRACE, msgs) {
+                    reporting.set_pipeline_trace(p_val.value().as_integer().unwrap() as u64);
+                }
+            },
+            TOML_PAR_LAYOUT => {
+                if str_par(p_val, p_key, &grp_key, msgs) {
+                    let layout_name = p_val.value().as_str().unwrap();
+                    match RecordLayout::from_str(&layout_name) {
+                        Ok(RecordLayout::Plain) => reporting.set_json(false),
+                        Ok(RecordLayout::Json) => reporting.set_json(true),
+                        _ => msgs.push(coalyxw!(W_CFG_INV_PROBLEM_LAYOUT, p_val.line_nr(),
+                                                layout_name))
+                    }
+                }
+            },
+            _ => {
+                let full_key = format!("{}.{}", grp_key, p_key);
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, p_val.line_nr(), full_key);
+                msgs.push(suggest_key(ex, p_key, SYSTEM_PROBLEMS_KEYS, Some(&grp_key)));
+            }
+        }
+    }
+    if let ProblemTarget::File(_) = reporting.target() {
+        reporting.set_target(ProblemTarget::File(file_name));
+    }
+    Some(reporting)
+}
+
+/// Reads rollover, buffer, throttle and sampling policies from custom configuration.
 /// 
 /// # Arguments
 /// * `policies_item` - the value item for the policies in the custom TOML document
 /// * `buffer_policies` - the hash map that shall receive the custom buffer policies
 /// * `rollover_policies` - the hash map that shall receive the custom rollover policies
+/// * `throttle_policies` - the hash map that shall receive the custom throttle policies
+/// * `sampling_policies` - the hash map that shall receive the custom sampling policies
 /// * `msgs` - the array, where error messages shall be stored
 fn read_policies(policies_item: &TomlValueItem,
                  buffer_policies: &mut Option<BufferPolicyMap>,
                  rollover_policies: &mut Option<RolloverPolicyMap>,
+                 throttle_policies: &mut ThrottlePolicyMap,
+                 sampling_policies: &mut SamplingPolicyMap,
                  msgs: &mut Vec<CoalyException>) {
     if not_table_item(policies_item, TOML_GRP_POLICIES, None, msgs) { return }
     for (key, val_item) in policies_item.child_items().unwrap() {
         match key.as_str() {
             TOML_GRP_BUFFER => *buffer_policies = read_buffer_policies(val_item, msgs),
             TOML_GRP_ROLLOVER => *rollover_policies = read_rollover_policies(val_item, msgs),
+            TOML_GRP_THROTTLE => *throttle_policies = read_throttle_policies(val_item, msgs),
+            TOML_GRP_SAMPLING => *sampling_policies = read_sampling_policies(val_item, msgs),
             _ => {
                 let full_key = format!("{}.{}", TOML_GRP_POLICIES, key);
-                msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, val_item.line_nr(), full_key));
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, val_item.line_nr(), full_key);
+                msgs.push(suggest_key(ex, key, POLICIES_KEYS, Some(TOML_GRP_POLICIES)));
             }
         }
     }
@@ -510,10 +1210,12 @@ fn read_policies(policies_item: &TomlValueItem,
 /// * `formats_item` - the value item for the formats in the custom TOML document
 /// * `output_formats` - the hash map that shall receive the custom output formats
 /// * `datetime_formats` - the hash map that shall receive the custom date time formats
+/// * `console_colors` - receives the color settings for console resources
 /// * `msgs` - the array, where error messages shall be stored
 fn read_formats(formats_item: &TomlValueItem,
                 datetime_formats: &mut Option<DateTimeFormatDescMap>,
                 output_formats: &mut Option<OutputFormatDescMap>,
+                console_colors: &mut Option<ConsoleColorsDesc>,
                 msgs: &mut Vec<CoalyException>) {
     if not_table_item(formats_item, TOML_GRP_FORMATS, None, msgs) { return }
     for (key, val_item) in formats_item.child_items().unwrap() {
@@ -522,145 +1224,471 @@ fn read_formats(formats_item: &TomlValueItem,
                 *output_formats = Some(read_output_formats(val_item, formats_item, msgs))
             },
             TOML_GRP_DATETIME => *datetime_formats = Some(read_datetime_formats(val_item, msgs)),
-            _ => msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, val_item.line_nr(),
-                                  format!("{}.{}", TOML_GRP_FORMATS, key)))
+            TOML_GRP_COLORS => *console_colors = read_console_colors(val_item, msgs),
+            _ => {
+                let full_key = format!("{}.{}", TOML_GRP_FORMATS, key);
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, val_item.line_nr(), full_key);
+                msgs.push(suggest_key(ex, key, FORMATS_KEYS, Some(TOML_GRP_FORMATS)));
+            }
         }
     }
 }
 
-/// Reads mode changes from custom configuration.
+/// Reads the color settings for console resources from custom configuration.
 /// 
 /// # Arguments
-/// * `modes_item` - the value item for the modes in the custom TOML document
-/// * `cfg` - the default configuration settings
+/// * `colors_item` - the value item for the colors in the custom TOML document
 /// * `msgs` - the array, where error messages shall be stored
-fn read_modes(modes_item: &TomlValueItem,
-              msgs: &mut Vec<CoalyException>) -> Option<ModeChangeDescList> {
-    if ! modes_item.is_array_of_tables() {
+/// 
+/// # Return values
+/// the color settings, **None** if the colors item is not a TOML table
+fn read_console_colors(colors_item: &TomlValueItem,
+                       msgs: &mut Vec<CoalyException>) -> Option<ConsoleColorsDesc> {
+    if not_table_item(colors_item, TOML_GRP_COLORS, Some(TOML_GRP_FORMATS), msgs) { return None }
+    let grp_key = format!("{}.{}", TOML_GRP_FORMATS, TOML_GRP_COLORS);
+    let mut mode = ColorMode::Auto;
+    let mut level_colors = Vec::<(u32, u8)>::new();
+    for (key, val_item) in colors_item.child_items().unwrap() {
+        if key == TOML_PAR_MODE {
+            if str_par(val_item, key, &grp_key, msgs) {
+                let mode_name = val_item.value().as_str().unwrap();
+                match ColorMode::from_str(&mode_name) {
+                    Ok(m) => mode = m,
+                    Err(_) => msgs.push(coalyxw!(W_CFG_INV_COLOR_MODE, val_item.line_nr(),
+                                                 mode_name))
+                }
+            }
+            continue
+        }
+        let Ok(lvl_id) = RecordLevelId::from_str(key) else {
+            let ex = coalyxw!(W_CFG_INV_LVL_REF, val_item.line_nr(), key.to_string(),
+                              grp_key.clone());
+            msgs.push(suggest_value(ex, key, RECORD_LEVEL_NAMES));
+            continue
+        };
+        if ! str_par(val_item, key, &grp_key, msgs) { continue }
+        let color_name = val_item.value().as_str().unwrap();
+        match color_code(&color_name) {
+            Some(code) => level_colors.push((lvl_id as u32, code)),
+            None => {
+                let ex = coalyxw!(W_CFG_INV_COLOR, val_item.line_nr(), color_name.clone(),
+                                  format!("{}.{}", grp_key, key));
+                msgs.push(suggest_value(ex, &color_name, COLOR_NAMES));
+            }
+        }
+    }
+    Some(ConsoleColorsDesc::new(mode, level_colors))
+}
+
+/// Reads mode changes from custom configuration.
+/// 
+/// # Arguments
+/// * `modes_item` - the value item for the modes in the custom TOML document
+/// * `cfg` - the default configuration settings
+/// * `msgs` - the array, where error messages shall be stored
+fn read_modes(modes_item: &TomlValueItem,
+              msgs: &mut Vec<CoalyException>) -> Option<ModeChangeDescList> {
+    if ! modes_item.is_array_of_tables() {
         msgs.push(coalyxw!(W_CFG_INV_MODES_HDR, modes_item.line_nr()));
         return None
     }
     let mut m_chgs = ModeChangeDescList::new();
     for mode_spec in modes_item.child_values().unwrap() {
-        let mut trg: Option<ObserverKind> = None;
-        let mut name: Option<String> = None;
-        let mut value: Option<String> = None;
-        let mut enabled_levels: u32 = RecordLevelId::no_change_ind();
-        let mut buffered_levels: u32 = RecordLevelId::no_change_ind();
-        let mut scope: Option<ModeChangeScope> = None;
-        for (attr_key, attr_val) in mode_spec.child_items().unwrap() {
-            match attr_key.as_str() {
-                TOML_PAR_TRIGGER => {
-                    if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
-                        let obs_kind_name = attr_val.value().as_str().unwrap();
-                        if let Ok(trg_id) = ObserverKind::from_str(&obs_kind_name) {
-                            trg = Some(trg_id);
-                            continue
-                        }
-                        msgs.push(coalyxw!(W_CFG_INV_MODE_TRIGGER, attr_val.line_nr(),
-                                         obs_kind_name.to_string()));
-                    }
-                },
-                TOML_PAR_NAME => {
-                    if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
-                       name = Some(attr_val.value().as_str().unwrap());
+        read_mode(mode_spec, &modes_item.line_nr(), &mut m_chgs, msgs);
+    }
+    Some(m_chgs)
+}
+
+/// Reads a single mode change from custom configuration.
+/// 
+/// # Arguments
+/// * `mode_spec` - the value item for the mode change table
+/// * `line_nr` - the line number to report in error messages
+/// * `m_chgs` - the list, where the mode change shall be added
+/// * `msgs` - the array, where error messages shall be stored
+fn read_mode(mode_spec: &TomlValueItem,
+             line_nr: &str,
+             m_chgs: &mut ModeChangeDescList,
+             msgs: &mut Vec<CoalyException>) {
+    let mut trg: Option<ObserverKind> = None;
+    let mut name: Option<String> = None;
+    let mut value: Option<String> = None;
+    let mut enabled_levels: u32 = RecordLevelId::no_change_ind();
+    let mut buffered_levels: u32 = RecordLevelId::no_change_ind();
+    let mut scope: Option<ModeChangeScope> = None;
+    let mut sample_rate: Option<f64> = None;
+    let mut prefix: Option<String> = None;
+    let mut suffix: Option<String> = None;
+    let mut fields: Option<Vec<(String, String)>> = None;
+    let mut suppressed_triggers: u32 = 0;
+    let mut capture: Option<CaptureDesc> = None;
+    for (attr_key, attr_val) in mode_spec.child_items().unwrap() {
+        match attr_key.as_str() {
+            TOML_PAR_TRIGGER => {
+                if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                    let obs_kind_name = attr_val.value().as_str().unwrap();
+                    if let Ok(trg_id) = ObserverKind::from_str(&obs_kind_name) {
+                        trg = Some(trg_id);
+                        continue
                     }
-                },
-                TOML_PAR_VALUE => {
-                    if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
-                       value = Some(attr_val.value().as_str().unwrap());
+                    msgs.push(coalyxw!(W_CFG_INV_MODE_TRIGGER, attr_val.line_nr(),
+                                     obs_kind_name.to_string()));
+                }
+            },
+            TOML_PAR_NAME => {
+                if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                   name = Some(attr_val.value().as_str().unwrap());
+                }
+            },
+            TOML_PAR_VALUE => {
+                if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                   value = Some(attr_val.value().as_str().unwrap());
+                }
+            },
+            TOML_PAR_ENABLED => {
+                if let Some(l) = read_levels_array(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                    enabled_levels = l;
+                }
+            },
+            TOML_PAR_BUFFERED => {
+                if let Some(l) = read_levels_array(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                    buffered_levels = l;
+                }
+            },
+            TOML_PAR_SCOPE => {
+                if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                    let scope_name = attr_val.value().as_str().unwrap();
+                    if let Ok(scope_id) = ModeChangeScope::from_str(&scope_name) {
+                        scope = Some(scope_id);
+                        continue
                     }
-                },
-                TOML_PAR_ENABLED => {
-                    if let Some(l) = read_levels_array(attr_val, attr_key, TOML_GRP_MODES, msgs) {
-                        enabled_levels = l;
+                }
+                msgs.push(coalyxw!(W_CFG_INV_SCOPE, attr_val.line_nr(), attr_key.to_string()));
+            },
+            TOML_PAR_SAMPLE_RATE => {
+                if let Some(rate) = attr_val.value().as_float() {
+                    if (0.0..=1.0).contains(&rate) {
+                        sample_rate = Some(rate);
+                        continue
                     }
-                },
-                TOML_PAR_BUFFERED => {
-                    if let Some(l) = read_levels_array(attr_val, attr_key, TOML_GRP_MODES, msgs) {
-                        buffered_levels = l;
+                }
+                msgs.push(coalyxw!(W_CFG_INV_SAMPLE_RATE, attr_val.line_nr(),
+                                   attr_val.value().as_str().unwrap_or_default()));
+            },
+            TOML_PAR_PREFIX => {
+                if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                   prefix = Some(attr_val.value().as_str().unwrap());
+                }
+            },
+            TOML_PAR_SUFFIX => {
+                if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
+                   suffix = Some(attr_val.value().as_str().unwrap());
+                }
+            },
+            TOML_PAR_FIELDS => {
+                if not_table_item(attr_val, attr_key, Some(TOML_GRP_MODES), msgs) { continue }
+                let full_key = format!("{}.{}", TOML_GRP_MODES, attr_key);
+                let mut field_values = Vec::<(String, String)>::new();
+                for (f_name, f_item) in attr_val.child_items().unwrap() {
+                    if str_par(f_item, f_name, &full_key, msgs) {
+                        field_values.push((f_name.to_string(),
+                                           f_item.value().as_str().unwrap()));
                     }
-                },
-                TOML_PAR_SCOPE => {
-                    if str_par(attr_val, attr_key, TOML_GRP_MODES, msgs) {
-                        let scope_name = attr_val.value().as_str().unwrap();
-                        if let Ok(scope_id) = ModeChangeScope::from_str(&scope_name) {
-                            scope = Some(scope_id);
-                            continue
-                        }
+                }
+                fields = Some(field_values);
+            },
+            TOML_PAR_SUPPRESSED_TRIGGERS => {
+                if let Some(trgs) = read_rec_triggers_array(attr_val, attr_key,
+                                                            TOML_GRP_MODES, msgs) {
+                    let obs_trgs = RecordTrigger::ObserverCreated as u32 |
+                                   RecordTrigger::ObserverDropped as u32;
+                    if trgs & ! obs_trgs != 0 {
+                        msgs.push(coalyxw!(W_CFG_INV_SUPPRESSED_TRIGGERS, attr_val.line_nr()));
                     }
-                    msgs.push(coalyxw!(W_CFG_INV_SCOPE, attr_val.line_nr(), attr_key.to_string()));
-                },
-                _ => msgs.push(coalyxw!(W_CFG_INV_MODE_ATTR, attr_val.line_nr(), attr_key.to_string()))
+                    suppressed_triggers = trgs & obs_trgs;
+                }
+            },
+            TOML_PAR_CAPTURE => capture = read_mode_capture(attr_val, msgs),
+            _ => {
+                let ex = coalyxw!(W_CFG_INV_MODE_ATTR, attr_val.line_nr(), attr_key.to_string());
+                msgs.push(suggest_key(ex, attr_key, MODE_KEYS, None));
             }
         }
-        if trg.is_none() ||
-            (RecordLevelId::is_no_change_ind(enabled_levels) &&
-             RecordLevelId::is_no_change_ind(buffered_levels)) ||
-            (name.is_none() && value.is_none()) {
-            msgs.push(coalyxw!(W_CFG_INV_MODE_SPEC, modes_item.line_nr()));
-            continue
-        }
-        match trg.unwrap() {
-            ObserverKind::Object => {
-                let mut name_pattern: Option<Regex> = None;
-                let mut value_pattern: Option<Regex> = None;
-                if name.is_none() && value.is_none() {
-                    msgs.push(coalyxw!(W_CFG_ANONYMOUS_OBSERVER_IGNORED, modes_item.line_nr()));
-                    continue;
-                }
-                if let Some(n) = name {
-                    if let Ok(pattern) = Regex::new(&n) {
-                        name_pattern = Some(pattern);
-                    } else {
-                        msgs.push(coalyxw!(W_CFG_INV_OBSERVER_NAME, n, modes_item.line_nr()));
-                        continue;
-                    }
+    }
+    let decoration = if prefix.is_some() || suffix.is_some() || fields.is_some() {
+        Some(RecordDecoration::new(&prefix.unwrap_or_default(), &suffix.unwrap_or_default(),
+                                   fields.unwrap_or_default()))
+    } else { None };
+    let levels_unchanged = RecordLevelId::is_no_change_ind(enabled_levels) &&
+                           RecordLevelId::is_no_change_ind(buffered_levels);
+    if trg.is_none() ||
+        (levels_unchanged && decoration.is_none() && suppressed_triggers == 0 &&
+         capture.is_none()) ||
+        (name.is_none() && value.is_none()) {
+        msgs.push(coalyxw!(W_CFG_INV_MODE_SPEC, line_nr.to_string()));
+        return
+    }
+    match trg.unwrap() {
+        ObserverKind::Object => {
+            if decoration.is_some() {
+                msgs.push(coalyxw!(W_CFG_MODE_DECORATION_IGNORED, line_nr.to_string()));
+            }
+            if suppressed_triggers != 0 {
+                msgs.push(coalyxw!(W_CFG_MODE_SUPPRESSION_IGNORED, line_nr.to_string()));
+            }
+            if levels_unchanged && capture.is_none() { return }
+            let mut name_pattern: Option<ObserverPattern> = None;
+            let mut value_pattern: Option<ObserverPattern> = None;
+            if name.is_none() && value.is_none() {
+                msgs.push(coalyxw!(W_CFG_ANONYMOUS_OBSERVER_IGNORED, line_nr.to_string()));
+                return;
+            }
+            if let Some(n) = name {
+                if let Ok(pattern) = ObserverPattern::new(&n) {
+                    name_pattern = Some(pattern);
+                } else {
+                    msgs.push(coalyxw!(W_CFG_INV_OBSERVER_NAME, n, line_nr.to_string()));
+                    return;
                 }
-                if let Some(v) = value {
-                    if let Ok(pattern) = Regex::new(&v) {
-                        value_pattern = Some(pattern);
-                    } else {
-                        msgs.push(coalyxw!(W_CFG_INV_OBSERVER_VALUE, v, modes_item.line_nr()));
-                        continue;
-                    }
+            }
+            if let Some(v) = value {
+                if let Ok(pattern) = ObserverPattern::new(&v) {
+                    value_pattern = Some(pattern);
+                } else {
+                    msgs.push(coalyxw!(W_CFG_INV_OBSERVER_VALUE, v, line_nr.to_string()));
+                    return;
                 }
-                m_chgs.push(ModeChangeDesc::for_object(scope.unwrap_or_default(),
+            }
+            let mut m_chg = ModeChangeDesc::for_object(scope.unwrap_or_default(),
                                                        name_pattern, value_pattern,
-                                                       enabled_levels, buffered_levels));
+                                                       enabled_levels, buffered_levels);
+            if let Some(rate) = sample_rate { m_chg.set_sample_rate(rate); }
+            if let Some(c) = capture { m_chg.set_capture(c); }
+            m_chgs.push(m_chg);
+        },
+        _ => {
+            if value.is_some() {
+                msgs.push(coalyxw!(W_CFG_MODE_VALUE_IGNORED, line_nr.to_string()));
+            }
+            if let Some(sc) = scope {
+                if sc == ModeChangeScope::Process {
+                    msgs.push(coalyxw!(W_CFG_MODE_SCOPE_IGNORED, line_nr.to_string()));
+                }
+            }
+            if capture.is_some() {
+                msgs.push(coalyxw!(W_CFG_MODE_CAPTURE_IGNORED, line_nr.to_string()));
+            }
+            if levels_unchanged && decoration.is_none() && suppressed_triggers == 0 { return }
+            if let Some(u_name) = name {
+                if let Ok(pattern) = ObserverPattern::new(&u_name) {
+                    let mut m_chg = ModeChangeDesc::for_unit(trg.unwrap(), Some(pattern),
+                                                             enabled_levels, buffered_levels);
+                    if let Some(rate) = sample_rate { m_chg.set_sample_rate(rate); }
+                    if let Some(d) = decoration { m_chg.set_decoration(d); }
+                    m_chg.set_suppressed_triggers(suppressed_triggers);
+                    m_chgs.push(m_chg);
+                } else {
+                    msgs.push(coalyxw!(W_CFG_INV_OBSERVER_NAME, u_name, line_nr.to_string()));
+                }
+                return
+            }
+            msgs.push(coalyxw!(W_CFG_MISSING_MODE_NAME, line_nr.to_string()));
+        }
+    }
+}
+
+/// Reads the capture file of a mode change from custom configuration.
+/// 
+/// # Arguments
+/// * `capture_item` - the value item for the capture table
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the capture descriptor, **None** if the item is not a table or the file name is missing
+fn read_mode_capture(capture_item: &TomlValueItem,
+                     msgs: &mut Vec<CoalyException>) -> Option<CaptureDesc> {
+    let grp_key = format!("{}.{}", TOML_GRP_MODES, TOML_PAR_CAPTURE);
+    if not_table_item(capture_item, &grp_key, None, msgs) { return None }
+    let mut name: Option<String> = None;
+    let mut ttl = DEFAULT_CAPTURE_TTL as u64;
+    for (c_key, c_val) in capture_item.child_items().unwrap() {
+        match c_key.as_str() {
+            TOML_PAR_NAME => {
+                if str_par(c_val, c_key, &grp_key, msgs) {
+                    name = Some(c_val.value().as_str().unwrap());
+                }
             },
-            _ => {
-                if value.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MODE_VALUE_IGNORED, modes_item.line_nr()));
+            TOML_PAR_TTL => {
+                if int_par(c_val, c_key, &grp_key, MIN_CAPTURE_TTL, MAX_CAPTURE_TTL,
+                           DEFAULT_CAPTURE_TTL, msgs) {
+                    ttl = c_val.value().as_integer().unwrap() as u64;
                 }
-                if let Some(sc) = scope {
-                    if sc == ModeChangeScope::Process {
-                        msgs.push(coalyxw!(W_CFG_MODE_SCOPE_IGNORED, modes_item.line_nr()));
+            },
+            _ => {
+                let full_key = format!("{}.{}", grp_key, c_key);
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, c_val.line_nr(), full_key);
+                msgs.push(suggest_key(ex, c_key, MODE_CAPTURE_KEYS, Some(&grp_key)));
+            }
+        }
+    }
+    match name {
+        Some(n) if ! n.is_empty() => Some(CaptureDesc::new(&n, ttl)),
+        _ => {
+            msgs.push(coalyxw!(W_CFG_CAPTURE_NAME_MISSING, capture_item.line_nr()));
+            None
+        }
+    }
+}
+
+/// Reads record filters from custom configuration.
+/// 
+/// # Arguments
+/// * `filters_item` - the value item for the filters in the custom TOML document
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the custom record filters, indexed by filter name
+fn read_filters(filters_item: &TomlValueItem,
+                msgs: &mut Vec<CoalyException>) -> RecordFilterDescMap {
+    let mut filters = RecordFilterDescMap::new();
+    if not_table_item(filters_item, TOML_GRP_FILTERS, None, msgs) { return filters }
+    for (fk, fi) in filters_item.child_items().unwrap() {
+        if not_table_item(fi, fk, Some(TOML_GRP_FILTERS), msgs) { continue }
+        let gk = format!("{}.{}", TOML_GRP_FILTERS, fk);
+        let mut incl_mods = Vec::<String>::new();
+        let mut excl_mods = Vec::<String>::new();
+        let mut incl_files = Vec::<String>::new();
+        let mut excl_files = Vec::<String>::new();
+        for (attr_key, attr_item) in fi.child_items().unwrap() {
+            if ! FILTER_KEYS.contains(&attr_key.as_str()) {
+                let full_key = format!("{}.{}", gk, attr_key);
+                let ex = coalyxw!(W_CFG_UNKNOWN_KEY, attr_item.line_nr(), full_key);
+                msgs.push(suggest_key(ex, attr_key, FILTER_KEYS, Some(&gk)));
+                continue
+            }
+            let prefixes = read_str_array(attr_item, attr_key, &gk, msgs).unwrap_or_default();
+            match attr_key.as_str() {
+                TOML_PAR_INCLUDE_MODULES | TOML_PAR_EXCLUDE_MODULES => {
+                    let mut mods = Vec::<String>::new();
+                    for prefix in prefixes {
+                        match validate_module_prefix(&prefix) {
+                            Some(p) => mods.push(p),
+                            None => {
+                                let full_key = format!("{}.{}", gk, attr_key);
+                                msgs.push(coalyxw!(W_CFG_INV_FILTER_MODULE, attr_item.line_nr(),
+                                                   prefix, full_key));
+                            }
+                        }
                     }
-                }
-                if let Some(u_name) = name {
-                    if let Ok(pattern) = Regex::new(&u_name) {
-                        m_chgs.push(ModeChangeDesc::for_unit(trg.unwrap(), Some(pattern),
-                                                             enabled_levels, buffered_levels));
-                    } else {
-                        msgs.push(coalyxw!(W_CFG_INV_OBSERVER_NAME, u_name, modes_item.line_nr()));
+                    if attr_key == TOML_PAR_INCLUDE_MODULES { incl_mods = mods; }
+                    else { excl_mods = mods; }
+                },
+                TOML_PAR_INCLUDE_FILES => incl_files = prefixes,
+                _ => excl_files = prefixes
+            }
+        }
+        filters.insert(fk.to_string(),
+                       RecordFilterDesc::new(fk, incl_mods, excl_mods, incl_files, excl_files));
+    }
+    filters
+}
+
+/// Reads the mappings for records from the log crate or tracing bridges.
+/// 
+/// # Arguments
+/// * `adapters_item` - the value item for the adapter mappings in the custom TOML document
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the adapter mappings in the order of definition
+fn read_adapters(adapters_item: &TomlValueItem,
+                 msgs: &mut Vec<CoalyException>) -> AdapterMappingDescList {
+    let mut mappings = AdapterMappingDescList::new();
+    if ! adapters_item.is_array_of_tables() {
+        msgs.push(coalyxw!(W_CFG_INV_ADAPTERS_HDR, adapters_item.line_nr()));
+        return mappings
+    }
+    for adapter_spec in adapters_item.child_values().unwrap() {
+        let mut target: Option<String> = None;
+        let mut levels = ADAPTER_LEVEL_ALL;
+        let mut level: Option<RecordLevelId> = None;
+        let mut unit: Option<String> = None;
+        let mut target_valid = true;
+        for (attr_key, attr_val) in adapter_spec.child_items().unwrap() {
+            let full_key = format!("{}.{}", TOML_GRP_ADAPTERS, attr_key);
+            match attr_key.as_str() {
+                TOML_PAR_TARGET => {
+                    if str_par(attr_val, attr_key, TOML_GRP_ADAPTERS, msgs) {
+                        let t = attr_val.value().as_str().unwrap();
+                        target = validate_adapter_target(&t);
+                        if target.is_none() {
+                            msgs.push(coalyxw!(W_CFG_INV_ADAPTER_TARGET, attr_val.line_nr(), t));
+                            target_valid = false;
+                        }
                     }
-                    continue
+                },
+                TOML_PAR_LEVELS => {
+                    let names = read_str_array(attr_val, attr_key, TOML_GRP_ADAPTERS, msgs);
+                    let Some(names) = names else { continue };
+                    levels = 0;
+                    for name in names {
+                        if let Some(l) = adapter_level_from_str(&name) {
+                            levels |= l;
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_ADAPTER_LEVEL, attr_val.line_nr(),
+                                          name.to_string(), full_key.to_string());
+                        msgs.push(suggest_value(ex, &name, ADAPTER_LEVEL_NAMES));
+                    }
+                },
+                TOML_PAR_LEVEL => {
+                    if str_par(attr_val, attr_key, TOML_GRP_ADAPTERS, msgs) {
+                        let name = attr_val.value().as_str().unwrap();
+                        if ADAPTER_TARGET_LEVEL_NAMES.contains(&name.as_str()) {
+                            level = RecordLevelId::from_str(&name).ok();
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_ADAPTER_LEVEL, attr_val.line_nr(),
+                                          name.to_string(), full_key);
+                        msgs.push(suggest_value(ex, &name, ADAPTER_TARGET_LEVEL_NAMES));
+                    }
+                },
+                TOML_PAR_UNIT => {
+                    if str_par(attr_val, attr_key, TOML_GRP_ADAPTERS, msgs) {
+                        let u = attr_val.value().as_str().unwrap();
+                        if ! u.is_empty() { unit = Some(u); }
+                    }
+                },
+                _ => {
+                    let ex = coalyxw!(W_CFG_UNKNOWN_KEY, attr_val.line_nr(), full_key);
+                    msgs.push(suggest_key(ex, attr_key, ADAPTER_KEYS, Some(TOML_GRP_ADAPTERS)));
                 }
-                msgs.push(coalyxw!(W_CFG_MISSING_MODE_NAME, modes_item.line_nr()));
             }
         }
+        if ! target_valid { continue }
+        if target.is_none() || levels == 0 || (level.is_none() && unit.is_none()) {
+            msgs.push(coalyxw!(W_CFG_INV_ADAPTER_SPEC, adapters_item.line_nr()));
+            continue
+        }
+        mappings.push(AdapterMappingDesc::new(&target.unwrap(), levels, level, unit));
     }
-    Some(m_chgs)
+    mappings
 }
 
 /// Reads mode changes from custom configuration.
 /// 
 /// # Arguments
 /// * `res_item` - the value item for the resources in the custom TOML document
-/// * `cfg` - the default configuration settings
+/// * `filters` - the record filters defined in the custom TOML document
+/// * `throttle_policies` - the throttle policies defined in the custom TOML document
+/// * `sampling_policies` - the sampling policies defined in the custom TOML document
 /// * `msgs` - the array, where error messages shall be stored
+// buffer and notification interval are meaningful for some resource kinds only
+#[cfg_attr(not(feature="os-resources"), allow(unused_variables, unused_assignments))]
 fn read_resources(res_item: &TomlValueItem,
+                  filters: &RecordFilterDescMap,
+                  throttle_policies: &ThrottlePolicyMap,
+                  sampling_policies: &SamplingPolicyMap,
                   msgs: &mut Vec<CoalyException>) -> Option<ResourceDescList> {
     if ! res_item.is_array_of_tables() {
         msgs.push(coalyxw!(W_CFG_INV_RESOURCES_HDR, res_item.line_nr()));
@@ -677,18 +1705,89 @@ fn read_resources(res_item: &TomlValueItem,
         let mut file_size: Option<usize> = None;
         let mut bufp: Option<String> = None;
         let mut outp_format: Option<String> = None;
+        let mut level_formats: Vec<(u32, String)> = Vec::new();
+        let mut filter: Option<RecordFilterDesc> = None;
+        let mut throttle: Option<ThrottlePolicy> = None;
+        let mut sampling: Option<SamplingPolicy> = None;
+        let mut flush_priority = DEF_FLUSH_PRIORITY as u32;
+        let mut flush_deadline = DEF_FLUSH_DEADLINE as u64;
+        let mut clock_granularity = ClockGranularity::Full;
         let mut rovrp: Option<String> = None;
+        let mut encoding: Option<CharEncoding> = None;
         let mut name_lnr: Option<String> = None;
         let mut local_url_lnr: Option<String> = None;
         let mut remote_url_lnr: Option<String> = None;
         let mut file_size_lnr: Option<String> = None;
         let mut bufp_lnr: Option<String> = None;
         let mut rovrp_lnr: Option<String> = None;
+        let mut encoding_lnr: Option<String> = None;
+        let mut create_error_policy = CreateErrorPolicy::Warn;
+        let mut create_error_lnr: Option<String> = None;
+        let mut retry_interval = DEF_CREATE_RETRY_INTERVAL as u64;
+        let mut min_interval: Option<u64> = None;
+        let mut min_interval_lnr: Option<String> = None;
+        let mut time_slice: Option<u32> = None;
+        let mut time_slice_lnr: Option<String> = None;
+        #[cfg(feature="mmfile")]
+        let mut timestamp_domain = TimestampDomain::WallClock;
+        #[cfg(feature="mmfile")]
+        let mut timestamp_domain_lnr: Option<String> = None;
+        let mut file_format = FileFormat::Text;
+        let mut file_format_lnr: Option<String> = None;
+        let mut encrypted = false;
+        let mut encrypted_lnr: Option<String> = None;
+        #[cfg(feature="mmfile")]
+        let mut ring = false;
+        #[cfg(feature="mmfile")]
+        let mut ring_lnr: Option<String> = None;
+        let mut sharing = FileSharing::Exclusive;
+        let mut probe = false;
+        let mut sharing_lnr: Option<String> = None;
         let mut _assigned_levels: u32 = 0;
         #[cfg(feature="net")]
         let mut facility: Option<u32> = None;
         #[cfg(feature="net")]
         let mut outp_fmt_lnr: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut lvl_fmts_lnr: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut payload: Option<WebhookPayload> = None;
+        #[cfg(feature="net")]
+        let mut payload_lnr: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut stream_compr = false;
+        #[cfg(feature="net")]
+        let mut stream_compr_lnr: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut spool: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut spool_lnr: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut spill: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut spill_lnr: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut reconnect_delay = DEF_RECONNECT_DELAY as u64;
+        #[cfg(feature="net")]
+        let mut max_reconnect_delay = DEF_MAX_RECONNECT_DELAY as u64;
+        #[cfg(feature="net")]
+        let mut reconnect_lnr: Option<(String, String)> = None;
+        #[cfg(feature="net")]
+        let mut max_reconnect_lnr: Option<String> = None;
+        #[cfg(feature="net")]
+        let mut tls_settings = TlsSettings::default();
+        #[cfg(feature="net")]
+        let mut tls_lnr: Option<(String, String)> = None;
+        #[cfg(feature="net")]
+        let mut batch_format = BatchFormat::JsonLines;
+        #[cfg(feature="net")]
+        let mut batch_size = DEF_BATCH_SIZE;
+        #[cfg(feature="net")]
+        let mut batch_interval = DEF_BATCH_INTERVAL as u64;
+        #[cfg(feature="net")]
+        let mut max_retries = DEF_MAX_RETRIES as u32;
+        #[cfg(feature="net")]
+        let mut batch_lnr: Option<(String, String)> = None;
         for (attr_key, attr_val) in res_spec.child_items().unwrap() {
             match attr_key.as_str() {
                 TOML_PAR_KIND => {
@@ -698,8 +1797,9 @@ fn read_resources(res_item: &TomlValueItem,
                             kind = Some(kind_id);
                             continue
                         }
-                        msgs.push(coalyxw!(W_CFG_INV_RES_KIND, attr_val.line_nr(),
-                                         res_kind_name.to_string()));
+                        let ex = coalyxw!(W_CFG_INV_RES_KIND, attr_val.line_nr(),
+                                          res_kind_name.to_string());
+                        msgs.push(suggest_resource_kind(ex, &res_kind_name));
                     }
                 },
                 TOML_PAR_APP_IDS => {
@@ -731,12 +1831,30 @@ fn read_resources(res_item: &TomlValueItem,
                         { outp_fmt_lnr = Some(attr_val.line_nr()); }
                     }
                 },
+                TOML_PAR_LEVEL_FORMATS => {
+                    level_formats = read_level_formats(attr_val, attr_key,
+                                                       TOML_GRP_RESOURCES, msgs);
+                    #[cfg(feature="net")]
+                    { lvl_fmts_lnr = Some(attr_val.line_nr()); }
+                },
                 TOML_PAR_ROLLOVER => {
                     if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
                         rovrp = Some(attr_val.value().as_str().unwrap());
                         rovrp_lnr = Some(attr_val.line_nr());
                     }
                 },
+                TOML_PAR_ENCODING => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let enc_name = attr_val.value().as_str().unwrap();
+                        encoding_lnr = Some(attr_val.line_nr());
+                        if let Ok(enc) = CharEncoding::from_str(&enc_name) {
+                            encoding = Some(enc);
+                            continue
+                        }
+                        encoding = Some(CharEncoding::Utf8);
+                        msgs.push(coalyxw!(W_CFG_INV_RES_ENCODING, attr_val.line_nr(), enc_name));
+                    }
+                },
                 TOML_PAR_LOCAL_URL => {
                     if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
                         local_url = Some(attr_val.value().as_str().unwrap());
@@ -755,90 +1873,577 @@ fn read_resources(res_item: &TomlValueItem,
                         bufp_lnr = Some(attr_val.line_nr());
                     }
                 },
+                TOML_PAR_MIN_INTERVAL => {
+                    min_interval_lnr = Some(attr_val.line_nr());
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES,
+                               MIN_NOTIFICATION_INTERVAL, MAX_NOTIFICATION_INTERVAL,
+                               DEF_NOTIFICATION_INTERVAL, msgs) {
+                        min_interval = Some(attr_val.value().as_integer().unwrap() as u64);
+                    }
+                },
                 #[cfg(feature="net")]
                 TOML_PAR_FACILITY => {
                     if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, 0, 23, 1, msgs) {
                         facility = Some(attr_val.value().as_integer().unwrap() as u32);
                     }
                 },
-                _ => msgs.push(coalyxw!(W_CFG_INV_RES_ATTR,attr_val.line_nr(),attr_key.to_string()))
-            }
-        }
-        if kind.is_none() || levels.is_none() || levels.unwrap() == 0 {
-            // kind and at least one record level is mandatory for all resources
-            msgs.push(coalyxw!(W_CFG_INV_RES_SPEC, res_item.line_nr()));
-            continue
-        }
-        match kind.unwrap() {
-            ResourceKind::PlainFile => {
-                if name.is_none() {
-                    msgs.push(coalyxw!(W_CFG_RES_FN_MISSING, res_item.line_nr()));
-                    continue
-                }
-                if file_size.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
-                                     TOML_PAR_SIZE.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                if local_url.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
-                                     TOML_PAR_LOCAL_URL.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                if remote_url.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
-                                     TOML_PAR_REMOTE_URL.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                let r = ResourceDesc::for_plain_file(&scope,
-                                                     levels.unwrap(), bufp.as_ref(),
-                                                     outp_format.as_ref(), &name.unwrap(),
-                                                     rovrp.as_ref());
-                res.push(r);
-            },
-            ResourceKind::MemoryMappedFile => {
-                if name.is_none() {
-                    msgs.push(coalyxw!(W_CFG_RES_FN_MISSING, res_item.line_nr()));
-                    continue
-                }
-                if file_size.is_none() {
-                    msgs.push(coalyxw!(W_CFG_FILE_SIZE_MISSING, res_item.line_nr()));
-                    continue
-                }
-                if bufp.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, bufp_lnr.unwrap(),
-                                     TOML_PAR_BUFFER.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                if local_url.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
-                                     TOML_PAR_LOCAL_URL.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                if remote_url.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
-                                     TOML_PAR_REMOTE_URL.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                let r = ResourceDesc::for_mem_mapped_file(&scope, levels.unwrap(),
-                                                          outp_format.as_ref(),
-                                                          &name.unwrap(), file_size.unwrap(),
-                                                          rovrp.as_ref());
-                res.push(r);
-            },
-            ResourceKind::StdOut | ResourceKind::StdErr => {
-                if name.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, name_lnr.unwrap(),
-                                     TOML_PAR_NAME.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                if file_size.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
-                                     TOML_PAR_SIZE.to_string(),
-                                     kind.unwrap().to_string()));
-                }
-                if rovrp.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                #[cfg(feature="net")]
+                TOML_PAR_PAYLOAD => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let payload_name = attr_val.value().as_str().unwrap();
+                        payload_lnr = Some(attr_val.line_nr());
+                        if let Ok(pl) = WebhookPayload::from_str(&payload_name) {
+                            payload = Some(pl);
+                            continue
+                        }
+                        payload = Some(WebhookPayload::Generic);
+                        msgs.push(coalyxw!(W_CFG_INV_RES_PAYLOAD, attr_val.line_nr(),
+                                         payload_name));
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_COMPRESSION => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let compr_name = attr_val.value().as_str().unwrap();
+                        stream_compr_lnr = Some(attr_val.line_nr());
+                        match compr_name.as_str() {
+                            STREAM_COMPRESSION_NONE => stream_compr = false,
+                            STREAM_COMPRESSION_DEFLATE => {
+                                #[cfg(not(feature="compression"))]
+                                msgs.push(coalyxw!(W_CFG_COMPR_NOT_SUPPORTED,
+                                                 attr_val.line_nr()));
+                                #[cfg(feature="compression")]
+                                { stream_compr = true; }
+                            },
+                            _ => {
+                                let ex = coalyxw!(W_CFG_INV_RES_COMPRESSION, attr_val.line_nr(),
+                                                  compr_name.to_string());
+                                msgs.push(suggest_value(ex, &compr_name,
+                                                        STREAM_COMPRESSION_NAMES));
+                            }
+                        }
+                    }
+                },
+                TOML_PAR_FILTER => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let filter_name = attr_val.value().as_str().unwrap();
+                        match filters.get(&filter_name) {
+                            Some(f) => filter = Some(f.clone()),
+                            None => {
+                                let ex = coalyxw!(W_CFG_UNKNOWN_FILTER, attr_val.line_nr(),
+                                                  filter_name.to_string());
+                                let names: Vec<&str> = filters.keys().map(|k| k.as_str())
+                                                                     .collect();
+                                msgs.push(if names.is_empty() { ex }
+                                          else { suggest_value(ex, &filter_name, &names) });
+                            }
+                        }
+                    }
+                },
+                TOML_PAR_FLUSH_PRIORITY => {
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, MIN_FLUSH_PRIORITY,
+                               MAX_FLUSH_PRIORITY, DEF_FLUSH_PRIORITY, msgs) {
+                        flush_priority = attr_val.value().as_integer().unwrap() as u32;
+                    }
+                },
+                TOML_PAR_FLUSH_DEADLINE => {
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, MIN_FLUSH_DEADLINE,
+                               MAX_FLUSH_DEADLINE, DEF_FLUSH_DEADLINE, msgs) {
+                        flush_deadline = attr_val.value().as_integer().unwrap() as u64;
+                    }
+                },
+                TOML_PAR_ON_CREATE_ERROR => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let pol_name = attr_val.value().as_str().unwrap();
+                        create_error_lnr = Some(attr_val.line_nr());
+                        if let Ok(pol) = CreateErrorPolicy::from_str(&pol_name) {
+                            create_error_policy = pol;
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_RES_CREATE_ERROR, attr_val.line_nr(),
+                                          pol_name.to_string());
+                        msgs.push(suggest_value(ex, &pol_name, CREATE_ERROR_POLICY_NAMES));
+                    }
+                },
+                TOML_PAR_CLOCK_GRANULARITY => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let gran_name = attr_val.value().as_str().unwrap();
+                        if let Ok(g) = ClockGranularity::from_str(&gran_name) {
+                            clock_granularity = g;
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_RES_CLOCK_GRANULARITY, attr_val.line_nr(),
+                                          gran_name.to_string());
+                        msgs.push(suggest_value(ex, &gran_name, CLOCK_GRANULARITY_NAMES));
+                    }
+                },
+                #[cfg(feature="mmfile")]
+                TOML_PAR_TIMESTAMPS => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let domain_name = attr_val.value().as_str().unwrap();
+                        timestamp_domain_lnr = Some(attr_val.line_nr());
+                        if let Ok(d) = TimestampDomain::from_str(&domain_name) {
+                            timestamp_domain = d;
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_RES_TIMESTAMP_DOMAIN, attr_val.line_nr(),
+                                          domain_name.to_string());
+                        msgs.push(suggest_value(ex, &domain_name, TIMESTAMP_DOMAIN_NAMES));
+                    }
+                },
+                TOML_PAR_FILE_FORMAT => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let format_name = attr_val.value().as_str().unwrap();
+                        file_format_lnr = Some(attr_val.line_nr());
+                        match FileFormat::from_str(&format_name) {
+                            Ok(FileFormat::Binary) => {
+                                // records are serialized by the network code
+                                #[cfg(not(feature="net"))]
+                                msgs.push(coalyxw!(W_CFG_BINARY_FORMAT_NOT_SUPPORTED,
+                                                 attr_val.line_nr()));
+                                #[cfg(feature="net")]
+                                { file_format = FileFormat::Binary; }
+                            },
+                            Ok(f) => file_format = f,
+                            Err(_) => {
+                                let ex = coalyxw!(W_CFG_INV_RES_FILE_FORMAT, attr_val.line_nr(),
+                                                  format_name.to_string());
+                                msgs.push(suggest_value(ex, &format_name, FILE_FORMAT_NAMES));
+                            }
+                        }
+                    }
+                },
+                TOML_PAR_ENCRYPTED => {
+                    if bool_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        encrypted = attr_val.value().as_bool().unwrap();
+                        encrypted_lnr = Some(attr_val.line_nr());
+                    }
+                },
+                #[cfg(feature="mmfile")]
+                TOML_PAR_RING => {
+                    if bool_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        ring = attr_val.value().as_bool().unwrap();
+                        ring_lnr = Some(attr_val.line_nr());
+                    }
+                },
+                TOML_PAR_PROBE => {
+                    if bool_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        probe = attr_val.value().as_bool().unwrap();
+                    }
+                },
+                TOML_PAR_SHARING => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let mode_name = attr_val.value().as_str().unwrap();
+                        sharing_lnr = Some(attr_val.line_nr());
+                        match FileSharing::from_str(&mode_name) {
+                            Ok(m) => sharing = m,
+                            Err(_) => {
+                                let ex = coalyxw!(W_CFG_INV_RES_SHARING, attr_val.line_nr(),
+                                                  mode_name.to_string());
+                                msgs.push(suggest_value(ex, &mode_name, FILE_SHARING_NAMES));
+                            }
+                        }
+                    }
+                },
+                TOML_PAR_RETRY_INTERVAL => {
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, MIN_CREATE_RETRY_INTERVAL,
+                               MAX_CREATE_RETRY_INTERVAL, DEF_CREATE_RETRY_INTERVAL, msgs) {
+                        retry_interval = attr_val.value().as_integer().unwrap() as u64;
+                    }
+                },
+                TOML_PAR_INTERVAL => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let interval_spec = attr_val.value().as_str().unwrap();
+                        time_slice_lnr = Some(attr_val.line_nr());
+                        time_slice = parse_time_slice(&interval_spec);
+                        if time_slice.is_none() {
+                            msgs.push(coalyxw!(W_CFG_INV_TIME_SLICE, attr_val.line_nr(),
+                                             interval_spec));
+                            time_slice = Some(DEFAULT_TIME_SLICE_INTERVAL);
+                        }
+                    }
+                },
+                TOML_PAR_THROTTLE => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let pol_name = attr_val.value().as_str().unwrap();
+                        match throttle_policies.get(&pol_name) {
+                            Some(p) => throttle = Some(p.clone()),
+                            None => {
+                                let ex = coalyxw!(W_CFG_UNKNOWN_THROTTLE, attr_val.line_nr(),
+                                                  pol_name.to_string());
+                                let names: Vec<&str> = throttle_policies.keys()
+                                                                        .map(|k| k.as_str())
+                                                                        .collect();
+                                msgs.push(if names.is_empty() { ex }
+                                          else { suggest_value(ex, &pol_name, &names) });
+                            }
+                        }
+                    }
+                },
+                TOML_PAR_SAMPLING => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let pol_name = attr_val.value().as_str().unwrap();
+                        match sampling_policies.get(&pol_name) {
+                            Some(p) => sampling = Some(p.clone()),
+                            None => {
+                                let ex = coalyxw!(W_CFG_UNKNOWN_SAMPLING, attr_val.line_nr(),
+                                                  pol_name.to_string());
+                                let names: Vec<&str> = sampling_policies.keys()
+                                                                        .map(|k| k.as_str())
+                                                                        .collect();
+                                msgs.push(if names.is_empty() { ex }
+                                          else { suggest_value(ex, &pol_name, &names) });
+                            }
+                        }
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_SPOOL => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        // spool file is always located in the output directory
+                        let spool_name = attr_val.value().as_str().unwrap();
+                        spool = Some(spool_name.replace(['/', '\\'], "_"));
+                        spool_lnr = Some(attr_val.line_nr());
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_SPILL => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        // spill file is always located in the output directory
+                        let spill_name = attr_val.value().as_str().unwrap();
+                        spill = Some(spill_name.replace(['/', '\\'], "_"));
+                        spill_lnr = Some(attr_val.line_nr());
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_RECONNECT_DELAY | TOML_PAR_MAX_RECONNECT_DELAY => {
+                    let def_val = if attr_key == TOML_PAR_RECONNECT_DELAY { DEF_RECONNECT_DELAY }
+                                  else { DEF_MAX_RECONNECT_DELAY };
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, MIN_RECONNECT_DELAY,
+                               MAX_RECONNECT_DELAY, def_val, msgs) {
+                        let delay = attr_val.value().as_integer().unwrap() as u64;
+                        if reconnect_lnr.is_none() {
+                            reconnect_lnr = Some((attr_val.line_nr(), attr_key.to_string()));
+                        }
+                        if attr_key == TOML_PAR_RECONNECT_DELAY {
+                            reconnect_delay = delay;
+                        } else {
+                            max_reconnect_delay = delay;
+                            max_reconnect_lnr = Some(attr_val.line_nr());
+                        }
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_TLS_CA_FILE | TOML_PAR_TLS_CERT_FILE | TOML_PAR_TLS_KEY_FILE |
+                TOML_PAR_TLS_SERVER_NAME => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let par_val = attr_val.value().as_str().unwrap();
+                        if tls_lnr.is_none() {
+                            tls_lnr = Some((attr_val.line_nr(), attr_key.to_string()));
+                        }
+                        match attr_key.as_str() {
+                            TOML_PAR_TLS_CA_FILE => tls_settings.set_ca_file(&par_val),
+                            TOML_PAR_TLS_CERT_FILE => tls_settings.set_cert_file(&par_val),
+                            TOML_PAR_TLS_KEY_FILE => tls_settings.set_key_file(&par_val),
+                            _ => tls_settings.set_server_name(&par_val)
+                        }
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_TLS_VERIFY => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let verify_name = attr_val.value().as_str().unwrap();
+                        if tls_lnr.is_none() {
+                            tls_lnr = Some((attr_val.line_nr(), attr_key.to_string()));
+                        }
+                        if let Ok(v) = TlsVerification::from_str(&verify_name) {
+                            tls_settings.set_verification(v);
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_RES_TLS_VERIFY, attr_val.line_nr(),
+                                          verify_name.to_string());
+                        msgs.push(suggest_value(ex, &verify_name, TLS_VERIFICATION_NAMES));
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_BATCH_FORMAT => {
+                    if str_par(attr_val, attr_key, TOML_GRP_RESOURCES, msgs) {
+                        let format_name = attr_val.value().as_str().unwrap();
+                        if batch_lnr.is_none() {
+                            batch_lnr = Some((attr_val.line_nr(), attr_key.to_string()));
+                        }
+                        if let Ok(bf) = BatchFormat::from_str(&format_name) {
+                            batch_format = bf;
+                            continue
+                        }
+                        let ex = coalyxw!(W_CFG_INV_RES_BATCH_FORMAT, attr_val.line_nr(),
+                                          format_name.to_string());
+                        msgs.push(suggest_value(ex, &format_name, BATCH_FORMAT_NAMES));
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_BATCH_SIZE => {
+                    if batch_lnr.is_none() {
+                        batch_lnr = Some((attr_val.line_nr(), attr_key.to_string()));
+                    }
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, MIN_BATCH_SIZE,
+                               MAX_BATCH_SIZE, DEF_BATCH_SIZE, msgs) {
+                        batch_size = attr_val.value().as_integer().unwrap() as usize;
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_BATCH_INTERVAL => {
+                    if batch_lnr.is_none() {
+                        batch_lnr = Some((attr_val.line_nr(), attr_key.to_string()));
+                    }
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, MIN_BATCH_INTERVAL,
+                               MAX_BATCH_INTERVAL, DEF_BATCH_INTERVAL, msgs) {
+                        batch_interval = attr_val.value().as_integer().unwrap() as u64;
+                    }
+                },
+                #[cfg(feature="net")]
+                TOML_PAR_MAX_RETRIES => {
+                    if batch_lnr.is_none() {
+                        batch_lnr = Some((attr_val.line_nr(), attr_key.to_string()));
+                    }
+                    if int_par(attr_val, attr_key, TOML_GRP_RESOURCES, MIN_MAX_RETRIES,
+                               MAX_MAX_RETRIES, DEF_MAX_RETRIES, msgs) {
+                        max_retries = attr_val.value().as_integer().unwrap() as u32;
+                    }
+                },
+                _ => {
+                    let ex = coalyxw!(W_CFG_INV_RES_ATTR, attr_val.line_nr(), attr_key.to_string());
+                    msgs.push(suggest_key(ex, attr_key, RESOURCE_KEYS, None));
+                }
+            }
+        }
+        if kind.is_none() || levels.is_none() || levels.unwrap() == 0 {
+            // kind and at least one record level is mandatory for all resources
+            msgs.push(coalyxw!(W_CFG_INV_RES_SPEC, res_item.line_nr()));
+            continue
+        }
+        if let Some(lnr) = create_error_lnr {
+            if ! kind.unwrap().is_file() {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr,
+                                 TOML_PAR_ON_CREATE_ERROR.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        if let Some(lnr) = time_slice_lnr.as_ref() {
+            if ! kind.unwrap().is_file() {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 TOML_PAR_INTERVAL.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="mmfile")]
+        if let Some(lnr) = timestamp_domain_lnr {
+            if ! matches!(kind.unwrap(), ResourceKind::MemoryMappedFile) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr,
+                                 TOML_PAR_TIMESTAMPS.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="mmfile")]
+        if let Some(lnr) = ring_lnr {
+            if ! matches!(kind.unwrap(), ResourceKind::MemoryMappedFile) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr,
+                                 TOML_PAR_RING.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        if let Some(lnr) = sharing_lnr.as_ref() {
+            if ! matches!(kind.unwrap(), ResourceKind::PlainFile) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 TOML_PAR_SHARING.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        if let Some(lnr) = file_format_lnr {
+            if ! matches!(kind.unwrap(), ResourceKind::PlainFile) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr,
+                                 TOML_PAR_FILE_FORMAT.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        if let Some(lnr) = encrypted_lnr.as_ref() {
+            if ! kind.unwrap().is_file() {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 TOML_PAR_ENCRYPTED.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        // records must not be written unencrypted
+        #[cfg(not(feature="encryption"))]
+        if encrypted && kind.unwrap().is_file() {
+            msgs.push(coalyxw!(W_CFG_ENCRYPTION_NOT_SUPPORTED, encrypted_lnr.unwrap_or_default()));
+            continue
+        }
+        if let Some(lnr) = min_interval_lnr {
+            if ! kind.unwrap().is_rate_limited() {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr,
+                                 TOML_PAR_MIN_INTERVAL.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="net")]
+        if let Some(lnr) = payload_lnr {
+            if ! matches!(kind.unwrap(), ResourceKind::Webhook) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr,
+                                 TOML_PAR_PAYLOAD.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="net")]
+        if let Some(lnr) = stream_compr_lnr.as_ref() {
+            if ! matches!(kind.unwrap(), ResourceKind::Network) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 TOML_PAR_COMPRESSION.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="net")]
+        if let Some(lnr) = spool_lnr.as_ref() {
+            if ! matches!(kind.unwrap(), ResourceKind::Network) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 TOML_PAR_SPOOL.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="net")]
+        if let Some((lnr, key)) = tls_lnr.as_ref() {
+            if ! matches!(kind.unwrap(), ResourceKind::Network | ResourceKind::Http) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 key.to_string(), kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="net")]
+        if let Some((lnr, key)) = batch_lnr.as_ref() {
+            if ! matches!(kind.unwrap(), ResourceKind::Http) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 key.to_string(), kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="net")]
+        if let Some(lnr) = spill_lnr.as_ref() {
+            if ! matches!(kind.unwrap(), ResourceKind::Network) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 TOML_PAR_SPILL.to_string(),
+                                 kind.unwrap().to_string()));
+            }
+        }
+        #[cfg(feature="net")]
+        if let Some((lnr, key)) = reconnect_lnr.as_ref() {
+            if ! matches!(kind.unwrap(), ResourceKind::Network) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr.to_string(),
+                                 key.to_string(), kind.unwrap().to_string()));
+            }
+        }
+        match kind.unwrap() {
+            ResourceKind::PlainFile => {
+                if name.is_none() {
+                    msgs.push(coalyxw!(W_CFG_RES_FN_MISSING, res_item.line_nr()));
+                    continue
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if remote_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
+                                     TOML_PAR_REMOTE_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let mut r = ResourceDesc::for_plain_file(&scope,
+                                                         levels.unwrap(), bufp.as_ref(),
+                                                         outp_format.as_ref(), &name.unwrap(),
+                                                         rovrp.as_ref(),
+                                                         encoding.unwrap_or(CharEncoding::Utf8));
+                // file headers of binary and encrypted files are specific for a process
+                if sharing.is_shared() && file_format == FileFormat::Binary {
+                    msgs.push(coalyxw!(W_CFG_SHARING_IGNORED, sharing_lnr.clone().unwrap_or_default(),
+                                     TOML_PAR_FILE_FORMAT.to_string()));
+                    sharing = FileSharing::Exclusive;
+                }
+                if sharing.is_shared() && encrypted {
+                    msgs.push(coalyxw!(W_CFG_SHARING_IGNORED, sharing_lnr.clone().unwrap_or_default(),
+                                     TOML_PAR_ENCRYPTED.to_string()));
+                    sharing = FileSharing::Exclusive;
+                }
+                if let Some(max_size) = file_size { r.set_file_size(max_size); }
+                r.set_create_error_policy(create_error_policy, retry_interval);
+                if let Some(interval) = time_slice { r.set_time_slice(interval); }
+                r.set_file_format(file_format);
+                r.set_encrypted(encrypted);
+                r.set_sharing(sharing);
+                res.push(r);
+            },
+            #[cfg(feature="mmfile")]
+            ResourceKind::MemoryMappedFile => {
+                if name.is_none() {
+                    msgs.push(coalyxw!(W_CFG_RES_FN_MISSING, res_item.line_nr()));
+                    continue
+                }
+                if file_size.is_none() {
+                    msgs.push(coalyxw!(W_CFG_FILE_SIZE_MISSING, res_item.line_nr()));
+                    continue
+                }
+                if bufp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, bufp_lnr.unwrap(),
+                                     TOML_PAR_BUFFER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if remote_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
+                                     TOML_PAR_REMOTE_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                // files in ring mode wrap around instead of rolling over
+                if ring && rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_RING_ROLLOVER, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string()));
+                    rovrp = None;
+                }
+                if ring && time_slice.is_some() {
+                    msgs.push(coalyxw!(W_CFG_RING_ROLLOVER, time_slice_lnr.unwrap(),
+                                     TOML_PAR_INTERVAL.to_string()));
+                    time_slice = None;
+                }
+                let mut r = ResourceDesc::for_mem_mapped_file(&scope, levels.unwrap(),
+                                                              outp_format.as_ref(),
+                                                              &name.unwrap(), file_size.unwrap(),
+                                                              rovrp.as_ref());
+                r.set_create_error_policy(create_error_policy, retry_interval);
+                if let Some(interval) = time_slice { r.set_time_slice(interval); }
+                r.set_timestamp_domain(timestamp_domain);
+                r.set_encrypted(encrypted);
+                r.set_ring(ring);
+                res.push(r);
+            },
+            ResourceKind::StdOut | ResourceKind::StdErr => {
+                if name.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, name_lnr.unwrap(),
+                                     TOML_PAR_NAME.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
                                      TOML_PAR_ROLLOVER.to_string(),
                                      kind.unwrap().to_string()));
                 }
@@ -852,14 +2457,166 @@ fn read_resources(res_item: &TomlValueItem,
                                      TOML_PAR_REMOTE_URL.to_string(),
                                      kind.unwrap().to_string()));
                 }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
                 let r = ResourceDesc::for_console(&scope, kind.unwrap(), levels.unwrap(),
                                                   bufp.as_ref(), outp_format.as_ref());
                 res.push(r);
             },
+            #[cfg(feature="os-resources")]
+            ResourceKind::Pipe => {
+                if name.is_none() {
+                    msgs.push(coalyxw!(W_CFG_RES_PIPE_NAME_MISSING, res_item.line_nr()));
+                    continue
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if remote_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
+                                     TOML_PAR_REMOTE_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let r = ResourceDesc::for_pipe(&scope, levels.unwrap(), bufp.as_ref(),
+                                               outp_format.as_ref(), &name.unwrap());
+                res.push(r);
+            },
+            #[cfg(feature="os-resources")]
+            ResourceKind::Notification => {
+                if bufp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, bufp_lnr.unwrap(),
+                                     TOML_PAR_BUFFER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if remote_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
+                                     TOML_PAR_REMOTE_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let app_name = name.unwrap_or_else(|| DEFAULT_NOTIFICATION_APP_NAME.to_string());
+                let r = ResourceDesc::for_notification(&scope, levels.unwrap(),
+                                                       outp_format.as_ref(), &app_name,
+                                                       min_interval.unwrap_or(
+                                                           DEF_NOTIFICATION_INTERVAL as u64));
+                res.push(r);
+            },
+            #[cfg(all(feature="os-resources", windows))]
+            ResourceKind::WindowsEventLog => {
+                if bufp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, bufp_lnr.unwrap(),
+                                     TOML_PAR_BUFFER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if remote_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
+                                     TOML_PAR_REMOTE_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let source_name = name.unwrap_or_else(|| DEFAULT_EVENTLOG_SOURCE_NAME.to_string());
+                let r = ResourceDesc::for_eventlog(&scope, levels.unwrap(),
+                                                   outp_format.as_ref(), &source_name);
+                res.push(r);
+            },
+            #[cfg(all(feature="os-resources", target_os="linux"))]
+            ResourceKind::Journald => {
+                if bufp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, bufp_lnr.unwrap(),
+                                     TOML_PAR_BUFFER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if remote_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, remote_url_lnr.unwrap(),
+                                     TOML_PAR_REMOTE_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let r = ResourceDesc::for_journal(&scope, levels.unwrap(),
+                                                  outp_format.as_ref(), name.as_ref());
+                res.push(r);
+            },
             #[cfg(feature="net")]
             ResourceKind::Syslog => {
                 if let Some(ref u) = remote_url {
-                    if ! is_valid_url(u) {
+                    if ! is_valid_remote_url(u) {
                         msgs.push(coalyxw!(W_CFG_INV_RES_URL, res_item.line_nr()));
                         remote_url = Some(DEFAULT_SYSLOG_URL.to_string());
                     }
@@ -885,23 +2642,194 @@ fn read_resources(res_item: &TomlValueItem,
                                      TOML_PAR_ROLLOVER.to_string(),
                                      kind.unwrap().to_string()));
                 }
-                let r = ResourceDesc::for_syslog(&scope, levels.unwrap(), bufp.as_ref(),
-                                                 facility.unwrap_or(1),
-                                                 &remote_url.unwrap_or(String::from(DEFAULT_SYSLOG_URL)),
-                                                 local_url.as_ref());
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let r = ResourceDesc::for_syslog(&scope, levels.unwrap(), bufp.as_ref(),
+                                                 facility.unwrap_or(1),
+                                                 &remote_url.unwrap_or(String::from(DEFAULT_SYSLOG_URL)),
+                                                 local_url.as_ref());
+                res.push(r);
+            },
+            #[cfg(feature="net")]
+            ResourceKind::Network => {
+                if remote_url.is_none() || ! is_valid_remote_url(&remote_url.clone().unwrap()) {
+                    msgs.push(coalyxw!(W_CFG_INV_RES_URL, res_item.line_nr()));
+                    continue
+                }
+                if let Some(ref u) = local_url {
+                    if ! is_valid_url(u) {
+                        msgs.push(coalyxw!(W_CFG_INV_RES_URL, res_item.line_nr()));
+                        continue
+                    }
+                }
+                if name.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, name_lnr.unwrap(),
+                                     TOML_PAR_NAME.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if outp_format.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, outp_fmt_lnr.unwrap(),
+                                     TOML_PAR_OUTPUT_FORMAT.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if let Some(lnr) = lvl_fmts_lnr.take() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, lnr,
+                                     TOML_PAR_LEVEL_FORMATS.to_string(),
+                                     kind.unwrap().to_string()));
+                    level_formats.clear();
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let remote_url = remote_url.unwrap();
+                if stream_compr && ! matches!(parse_url(&remote_url), Ok(a) if a.is_tcp_stream()) {
+                    msgs.push(coalyxw!(W_CFG_STREAM_COMPR_TCP_ONLY,
+                                     stream_compr_lnr.unwrap()));
+                    stream_compr = false;
+                }
+                if spool.is_some() && ! matches!(parse_url(&remote_url),
+                                                 Ok(a) if a.is_tcp_stream()) {
+                    msgs.push(coalyxw!(W_CFG_SPOOL_TCP_ONLY, spool_lnr.unwrap()));
+                    spool = None;
+                }
+                if spool.is_some() && spill.is_some() {
+                    msgs.push(coalyxw!(W_CFG_SPILL_WITH_SPOOL, spill_lnr.unwrap()));
+                    spill = None;
+                }
+                if max_reconnect_delay < reconnect_delay {
+                    msgs.push(coalyxw!(W_CFG_MAX_RECONNECT_DELAY,
+                                     max_reconnect_lnr.unwrap_or_default(),
+                                     max_reconnect_delay.to_string(),
+                                     reconnect_delay.to_string()));
+                    max_reconnect_delay = reconnect_delay;
+                }
+                if let Some((lnr, _)) = tls_lnr {
+                    #[cfg(not(feature="tls"))]
+                    {
+                        msgs.push(coalyxw!(W_CFG_TLS_NOT_SUPPORTED, lnr));
+                        tls_settings = TlsSettings::default();
+                    }
+                    #[cfg(feature="tls")]
+                    if ! matches!(parse_url(&remote_url),
+                                  Ok(a) if *a.protocol() == NetworkProtocol::Tls) {
+                        msgs.push(coalyxw!(W_CFG_TLS_PARS_TLS_ONLY, lnr));
+                        tls_settings = TlsSettings::default();
+                    }
+                }
+                let mut r = ResourceDesc::for_network(&scope, levels.unwrap(), bufp.as_ref(),
+                                                      &remote_url, local_url.as_ref(),
+                                                      stream_compr);
+                if let Some(spool_name) = spool { r.set_spool_file_name(&spool_name); }
+                if let Some(spill_name) = spill { r.set_spill_file_name(&spill_name); }
+                r.set_reconnect_delays(reconnect_delay, max_reconnect_delay);
+                r.set_tls_settings(tls_settings);
+                res.push(r);
+            },
+            #[cfg(feature="net")]
+            ResourceKind::Webhook => {
+                if remote_url.is_none() || parse_http_url(remote_url.as_ref().unwrap()).is_none() {
+                    msgs.push(coalyxw!(W_CFG_INV_RES_URL, res_item.line_nr()));
+                    continue
+                }
+                if name.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, name_lnr.unwrap(),
+                                     TOML_PAR_NAME.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let r = ResourceDesc::for_webhook(&scope, levels.unwrap(), bufp.as_ref(),
+                                                  outp_format.as_ref(), &remote_url.unwrap(),
+                                                  payload.unwrap_or(WebhookPayload::Generic),
+                                                  min_interval.unwrap_or(
+                                                      DEF_NOTIFICATION_INTERVAL as u64));
+                res.push(r);
+            },
+            #[cfg(feature="net")]
+            ResourceKind::Gelf => {
+                if remote_url.is_none() || parse_gelf_url(remote_url.as_ref().unwrap()).is_none() {
+                    msgs.push(coalyxw!(W_CFG_INV_RES_URL, res_item.line_nr()));
+                    continue
+                }
+                if bufp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, bufp_lnr.unwrap(),
+                                     TOML_PAR_BUFFER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if name.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, name_lnr.unwrap(),
+                                     TOML_PAR_NAME.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if file_size.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, file_size_lnr.unwrap(),
+                                     TOML_PAR_SIZE.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if rovrp.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
+                                     TOML_PAR_ROLLOVER.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                let r = ResourceDesc::for_gelf(&scope, levels.unwrap(), outp_format.as_ref(),
+                                               &remote_url.unwrap());
                 res.push(r);
             },
             #[cfg(feature="net")]
-            ResourceKind::Network => {
-                if remote_url.is_none() || ! is_valid_url(&remote_url.clone().unwrap()) {
+            ResourceKind::Http => {
+                let parsed_url = remote_url.as_ref().and_then(|u| parse_http_or_https_url(u));
+                if parsed_url.is_none() {
                     msgs.push(coalyxw!(W_CFG_INV_RES_URL, res_item.line_nr()));
                     continue
                 }
-                if let Some(ref u) = local_url {
-                    if ! is_valid_url(u) {
-                        msgs.push(coalyxw!(W_CFG_INV_RES_URL, res_item.line_nr()));
-                        continue
-                    }
+                let (secure, _, _, _) = parsed_url.unwrap();
+                #[cfg(not(feature="tls"))]
+                if secure {
+                    msgs.push(coalyxw!(W_CFG_HTTPS_NOT_SUPPORTED, remote_url_lnr.unwrap()));
+                    continue
                 }
                 if name.is_some() {
                     msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, name_lnr.unwrap(),
@@ -913,21 +2841,54 @@ fn read_resources(res_item: &TomlValueItem,
                                      TOML_PAR_SIZE.to_string(),
                                      kind.unwrap().to_string()));
                 }
-                if outp_format.is_some() {
-                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, outp_fmt_lnr.unwrap(),
-                                     TOML_PAR_OUTPUT_FORMAT.to_string(),
-                                     kind.unwrap().to_string()));
-                }
                 if rovrp.is_some() {
                     msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, rovrp_lnr.unwrap(),
                                      TOML_PAR_ROLLOVER.to_string(),
                                      kind.unwrap().to_string()));
                 }
-                let r = ResourceDesc::for_network(&scope, levels.unwrap(), bufp.as_ref(),
-                                                  &remote_url.unwrap(), local_url.as_ref());
+                if local_url.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, local_url_lnr.unwrap(),
+                                     TOML_PAR_LOCAL_URL.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if encoding.is_some() {
+                    msgs.push(coalyxw!(W_CFG_MEANINGLESS_RES_PAR, encoding_lnr.unwrap(),
+                                     TOML_PAR_ENCODING.to_string(),
+                                     kind.unwrap().to_string()));
+                }
+                if let Some((lnr, _)) = tls_lnr {
+                    if ! secure {
+                        msgs.push(coalyxw!(W_CFG_TLS_PARS_TLS_ONLY, lnr));
+                        tls_settings = TlsSettings::default();
+                    }
+                }
+                let mut r = ResourceDesc::for_http(&scope, levels.unwrap(), bufp.as_ref(),
+                                                   outp_format.as_ref(), &remote_url.unwrap(),
+                                                   batch_format, batch_size, batch_interval);
+                r.set_retry_policy(max_retries, retry_interval);
+                r.set_tls_settings(tls_settings);
                 res.push(r);
             }
         }
+        if ! level_formats.is_empty() {
+            if let Some(r) = res.last_custom_element_mut() {
+                r.set_level_format_names(level_formats);
+            }
+        }
+        if let Some(f) = filter {
+            if let Some(r) = res.last_custom_element_mut() { r.set_filter(f); }
+        }
+        if let Some(p) = throttle {
+            if let Some(r) = res.last_custom_element_mut() { r.set_throttle_policy(p); }
+        }
+        if let Some(p) = sampling {
+            if let Some(r) = res.last_custom_element_mut() { r.set_sampling_policy(p); }
+        }
+        if let Some(r) = res.last_custom_element_mut() {
+            r.set_flush_settings(flush_priority, flush_deadline);
+            r.set_clock_granularity(clock_granularity);
+            r.set_probe(probe);
+        }
     }
     Some(res)
 }
@@ -1023,6 +2984,7 @@ fn read_output_formats(parent_item: &TomlValueItem, formats_item: &TomlValueItem
             let mut trgs: Option<u32> = None;
             let mut dtm_fmt_name: Option<String> = None;
             let mut items: Option<String> = None;
+            let mut layout = RecordLayout::Plain;
             for (spk, spi) in rfi.child_items().unwrap() {
                 match spk.as_str() {
                     TOML_PAR_LEVELS => lvls = read_levels_array(spi, spk, &gk, msgs),
@@ -1037,9 +2999,20 @@ fn read_output_formats(parent_item: &TomlValueItem, formats_item: &TomlValueItem
                            items = Some(spi.value().as_str().unwrap());
                         }
                     },
+                    TOML_PAR_LAYOUT if str_par(spi, spk, &gk, msgs) => {
+                        let layout_name = spi.value().as_str().unwrap();
+                        if let Ok(l) = RecordLayout::from_str(&layout_name) {
+                            layout = l;
+                            continue
+                        }
+                        msgs.push(coalyxw!(W_CFG_INV_RECFMT_LAYOUT, spi.line_nr(),
+                                           layout_name, fk.to_string()));
+                    },
                     _ => ()
                 }
             }
+            // items are not needed for JSON and pretty records
+            if items.is_none() && layout != RecordLayout::Plain { items = Some(String::from("")); }
             if lvls.is_none() || trgs.is_none() || items.is_none() {
                 msgs.push(coalyxw!(W_CFG_INV_RECFMT_SPEC, fi.line_nr(), fk.to_string()));
                 continue
@@ -1054,7 +3027,7 @@ fn read_output_formats(parent_item: &TomlValueItem, formats_item: &TomlValueItem
                 msgs.push(coalyxw!(W_CFG_OUTFMT_LEVELS_EMPTY, fi.line_nr(), fk.to_string()));
                 continue
             }
-            let rfmt = RecordFormatDesc::new(lvls, trgs, &items.unwrap(), dtm_fmt_name);
+            let rfmt = RecordFormatDesc::new(lvls, trgs, layout, &items.unwrap(), dtm_fmt_name);
             specific_fmts.push(rfmt);
         }
         if ! specific_fmts.is_empty() {
@@ -1092,6 +3065,7 @@ fn read_datetime_formats(parent_item: &TomlValueItem,
         let mut tstamp: Option<String> = None;
         let mut time: Option<String> = None;
         let mut date: Option<String> = None;
+        let mut zone = DateTimeZone::Local;
         for (dk, di) in fi.child_items().unwrap() {
             let full_dk = format!("{}.{}", gk, dk);
             match dk.as_str() {
@@ -1128,15 +3102,131 @@ fn read_datetime_formats(parent_item: &TomlValueItem,
                         tstamp = Some(fmt_str);
                     }
                 },
+                TOML_PAR_ZONE => {
+                    if str_par(di, dk, &gk, msgs) {
+                        let zone_str = di.value().as_str().unwrap();
+                        match DateTimeZone::from_str(&zone_str) {
+                            Ok(z) => zone = z,
+                            Err(_) => msgs.push(coalyxw!(W_CFG_INV_DTFMT_ZONE, di.line_nr(),
+                                                         zone_str, full_dk.to_string()))
+                        }
+                    }
+                },
                 _ => msgs.push(coalyxw!(W_CFG_INV_DFMT_ATTR, di.line_nr(),
                                       dk.to_string(), fk.to_string()))
             }
         }
-        res_table.insert(fk, DateTimeFormatDesc::new(fk, date, time, tstamp));
+        res_table.insert(fk, DateTimeFormatDesc::new(fk, date, time, tstamp, zone));
     }
     res_table
 }
 
+/// Reads an array of strings.
+/// Array items not holding a string value are ignored, duplicate values are skipped.
+/// 
+/// # Arguments
+/// * `arr_item` - the TOML array containing the strings, or a single string item
+/// * `key` - key of the array or string item, for error messages only
+/// * `parent_key` - the full TOML key of the parent item, for error messages only
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// a vector with all strings in the order of definition
+fn read_str_array(arr_item: &TomlValueItem, key: &str, parent_key: &str,
+                  msgs: &mut Vec<CoalyException>)  -> Option<Vec<String>> {
+    match arr_item.value() {
+        TomlValue::String(s) => Some(vec!(s.to_string())),
+        TomlValue::Array(_) => {
+            let mut strs = Vec::<String>::new();
+            for item in arr_item.child_values().unwrap() {
+                if ! str_par(item, key, parent_key, msgs) { continue }
+                let s = item.value().as_str().unwrap();
+                if ! strs.contains(&s) { strs.push(s); }
+            }
+            Some(strs)
+        },
+        _ => {
+            let full_name = format!("{}.{}", parent_key, key);
+            msgs.push(coalyxw!(W_CFG_KEY_NOT_AN_ARRAY, arr_item.line_nr(), full_name));
+            None
+        }
+    }
+}
+
+/// Returns the candidate most similar to the given key or value.
+/// 
+/// # Arguments
+/// * `s` - the unknown key or invalid value
+/// * `candidates` - all valid keys or values
+/// 
+/// # Return values
+/// the most similar candidate, **None** if no candidate is similar enough
+fn nearest_candidate<'a>(s: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_dist = (s.chars().count() / 3).max(1);
+    candidates.iter()
+              .map(|c| (edit_distance(s, c), *c))
+              .filter(|(dist, _)| *dist <= max_dist)
+              .min_by_key(|(dist, _)| *dist)
+              .map(|(_, c)| c)
+}
+
+/// Adds a hint with the most similar valid key to a warning about an unknown key.
+/// 
+/// # Arguments
+/// * `ex` - the warning about the unknown key
+/// * `key` - the unknown key, without parent key
+/// * `valid_keys` - all keys allowed at the position of the unknown key
+/// * `parent_key` - the full TOML key of the parent item, if any
+/// 
+/// # Return values
+/// the warning, including the hint if a similar key was found
+fn suggest_key(mut ex: CoalyException, key: &str, valid_keys: &[&str],
+               parent_key: Option<&str>) -> CoalyException {
+    if let Some(k) = nearest_candidate(key, valid_keys) {
+        let full_key = match parent_key {
+            Some(pk) => format!("{}.{}", pk, k),
+            None => k.to_string()
+        };
+        ex.set_hint(coalyxw!(W_CFG_DID_YOU_MEAN, full_key));
+    }
+    ex
+}
+
+/// Adds a hint with the most similar valid value to a warning about an invalid value.
+/// If no valid value is similar enough, the hint lists all valid values.
+/// 
+/// # Arguments
+/// * `ex` - the warning about the invalid value
+/// * `value` - the invalid value
+/// * `valid_values` - all valid values
+/// 
+/// # Return values
+/// the warning, including the hint
+fn suggest_value(mut ex: CoalyException, value: &str, valid_values: &[&str]) -> CoalyException {
+    match nearest_candidate(value, valid_values) {
+        Some(v) => ex.set_hint(coalyxw!(W_CFG_DID_YOU_MEAN, v.to_string())),
+        None => ex.set_hint(coalyxw!(W_CFG_VALID_VALUES, valid_values.join(", ")))
+    }
+    ex
+}
+
+/// Adds a hint with the most similar resource kind to a warning about an invalid kind.
+/// Unlike other values, the resource kinds are not listed if no kind is similar enough,
+/// since the available kinds depend on the platform and the enabled features.
+/// 
+/// # Arguments
+/// * `ex` - the warning about the invalid resource kind
+/// * `kind_name` - the invalid resource kind
+/// 
+/// # Return values
+/// the warning, including the hint if a similar resource kind exists
+fn suggest_resource_kind(mut ex: CoalyException, kind_name: &str) -> CoalyException {
+    if let Some(k) = nearest_candidate(kind_name, &resource_kind_names()) {
+        ex.set_hint(coalyxw!(W_CFG_DID_YOU_MEAN, k.to_string()));
+    }
+    ex
+}
+
 /// Reads record levels.
 /// 
 /// # Arguments
@@ -1152,8 +3242,9 @@ fn read_levels_array(lvls_item: &TomlValueItem, key: &str, parent_key: &str,
     match lvls_item.value() {
         TomlValue::String(s) => {
             if let Ok(lvl_id) = RecordLevelId::from_str(s) { return Some(lvl_id as u32) }
-            msgs.push(coalyxw!(W_CFG_INV_LVL_REF, lvls_item.line_nr(),
-                             s.to_string(), format!("{}.{}", parent_key, key)));
+            let ex = coalyxw!(W_CFG_INV_LVL_REF, lvls_item.line_nr(),
+                              s.to_string(), format!("{}.{}", parent_key, key));
+            msgs.push(suggest_value(ex, s, RECORD_LEVEL_NAMES));
             None
         },
         TomlValue::Array(_) => {
@@ -1172,8 +3263,9 @@ fn read_levels_array(lvls_item: &TomlValueItem, key: &str, parent_key: &str,
                     defined_lvls.insert(lvl_id);
                     continue
                 }
-                msgs.push(coalyxw!(W_CFG_INV_LVL_REF, item.line_nr(),
-                                 lvl_name, format!("{}.{}", parent_key, key)));
+                let ex = coalyxw!(W_CFG_INV_LVL_REF, item.line_nr(),
+                                  lvl_name.to_string(), format!("{}.{}", parent_key, key));
+                msgs.push(suggest_value(ex, &lvl_name, RECORD_LEVEL_NAMES));
             }
             Some(bit_mask)
         },
@@ -1185,6 +3277,38 @@ fn read_levels_array(lvls_item: &TomlValueItem, key: &str, parent_key: &str,
     }
 }
 
+/// Reads the mapping of record levels to output format names for a resource.
+/// 
+/// # Arguments
+/// * `fmts_item` - the TOML table containing the mapping
+/// * `key` - key of the table item, for error messages only
+/// * `parent_key` - the full TOML key of the parent item, for error messages only
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// array with bit masks containing the record levels and the associated output format names
+fn read_level_formats(fmts_item: &TomlValueItem, key: &str, parent_key: &str,
+                      msgs: &mut Vec<CoalyException>) -> Vec<(u32, String)> {
+    let mut level_formats = Vec::<(u32, String)>::new();
+    if not_table_item(fmts_item, key, Some(parent_key), msgs) { return level_formats }
+    let full_key = format!("{}.{}", parent_key, key);
+    for (lvl_name, fmt_item) in fmts_item.child_items().unwrap() {
+        if ! str_par(fmt_item, lvl_name, &full_key, msgs) { continue }
+        match RecordLevelId::from_str(lvl_name) {
+            Ok(lvl_id) => {
+                let fmt_name = fmt_item.value().as_str().unwrap();
+                level_formats.push((lvl_id as u32, fmt_name));
+            },
+            Err(_) => {
+                let ex = coalyxw!(W_CFG_INV_LVL_REF, fmt_item.line_nr(),
+                                  lvl_name.to_string(), full_key.clone());
+                msgs.push(suggest_value(ex, lvl_name, RECORD_LEVEL_NAMES));
+            }
+        }
+    }
+    level_formats
+}
+
 /// Reads a TOML array containing record triggers.
 /// 
 /// # Arguments
@@ -1297,6 +3421,10 @@ fn read_buffer_policies(buffers_item: &TomlValueItem,
     for (key, pol_item) in buffers_item.child_items().unwrap() {
         if not_table_item(pol_item, key, Some(&bpkey), msgs) { continue }
         let polkey = format!("{}.{}", bpkey, key);
+        let mut max_cont_size: Option<usize> = None;
+        let mut shrink_after: Option<u64> = None;
+        let mut duplicate_window: Option<u64> = None;
+        let mut adaptive_latency: Option<u64> = None;
         for (attr_key, attr_item) in pol_item.child_items().unwrap() {
             match attr_key.as_str() {
                 TOML_PAR_FLUSH => {
@@ -1311,6 +3439,31 @@ fn read_buffer_policies(buffers_item: &TomlValueItem,
                     }
                     cont_size = Some(DEF_BUFFER_CONT_SIZE);
                 },
+                TOML_PAR_MAX_CONTENT_SIZE => {
+                    if let Some(cs) = size_par(attr_item, attr_key, &polkey,
+                                               MIN_BUFFER_CONT_SIZE, MAX_BUFFER_CONT_SIZE,
+                                               DEF_BUFFER_CONT_SIZE, msgs) {
+                        max_cont_size = Some(cs);
+                    }
+                },
+                TOML_PAR_SHRINK_AFTER => {
+                    if int_par(attr_item, attr_key, &polkey, MIN_BUFFER_SHRINK_AFTER,
+                               MAX_BUFFER_SHRINK_AFTER, DEF_BUFFER_SHRINK_AFTER, msgs) {
+                        shrink_after = Some(attr_item.value().as_integer().unwrap() as u64);
+                    }
+                },
+                TOML_PAR_DUPLICATE_WINDOW => {
+                    if int_par(attr_item, attr_key, &polkey, MIN_BUFFER_DUPLICATE_WINDOW,
+                               MAX_BUFFER_DUPLICATE_WINDOW, DEF_BUFFER_DUPLICATE_WINDOW, msgs) {
+                        duplicate_window = Some(attr_item.value().as_integer().unwrap() as u64);
+                    }
+                },
+                TOML_PAR_ADAPTIVE_LATENCY => {
+                    if int_par(attr_item, attr_key, &polkey, MIN_BUFFER_ADAPTIVE_LATENCY,
+                               MAX_BUFFER_ADAPTIVE_LATENCY, DEF_BUFFER_ADAPTIVE_LATENCY, msgs) {
+                        adaptive_latency = Some(attr_item.value().as_integer().unwrap() as u64);
+                    }
+                },
                 TOML_PAR_INDEX_SIZE => {
                     if let Some(is) = size_par(attr_item, attr_key, &polkey,
                                                MIN_BUFFER_INDEX_SIZE, MAX_BUFFER_INDEX_SIZE,
@@ -1357,8 +3510,18 @@ fn read_buffer_policies(buffers_item: &TomlValueItem,
         } else {
             max_rec_len = Some(DEF_MAX_REC_LEN as usize);
         }
-        let pol_spec = BufferPolicy::new(key, cont_size.unwrap(), index_size.unwrap(),
-                                         flush_events, max_rec_len.unwrap());
+        let mut max_cs = max_cont_size.unwrap_or(cont_size.unwrap());
+        if max_cs < cont_size.unwrap() {
+            max_cs = cont_size.unwrap();
+            msgs.push(coalyxw!(W_CFG_MAX_SIZE_BELOW_SIZE, pol_item.line_nr(),
+                             key.to_string(), max_cs.to_string()));
+        }
+        let mut pol_spec = BufferPolicy::new(key, cont_size.unwrap(), max_cs,
+                                             shrink_after.unwrap_or(DEF_BUFFER_SHRINK_AFTER as u64),
+                                             index_size.unwrap(), flush_events,
+                                             max_rec_len.unwrap());
+        if let Some(window) = duplicate_window { pol_spec.set_duplicate_window(window); }
+        if let Some(latency) = adaptive_latency { pol_spec.set_adaptive_latency(latency); }
         bpols.insert(key, pol_spec);
    }
     Some(bpols)
@@ -1379,6 +3542,8 @@ fn read_rollover_policies(rollover_item: &TomlValueItem,
         let polkey = format!("{}.{}", rpkey, key);
         let mut compr_algo: Option<CompressionAlgorithm> = None;
         let mut keep_count: Option<u32> = None;
+        let mut checksums: Option<bool> = None;
+        let mut max_total_size: Option<usize> = None;
         let mut cond: Option<RolloverCondition> = None;
         let mut cond_specified = false;
         for (attr_key, attr_item) in pol_item.child_items().unwrap() {
@@ -1409,6 +3574,20 @@ fn read_rollover_policies(rollover_item: &TomlValueItem,
                     }
                     keep_count = Some(DEFAULT_KEEP_COUNT as u32);
                 },
+                TOML_PAR_CHECKSUMS => {
+                    if bool_par(attr_item, attr_key, &polkey, msgs) {
+                        #[cfg(not(feature="checksums"))]
+                        if attr_item.value().as_bool().unwrap() {
+                            msgs.push(coalyxw!(W_CFG_CHECKSUMS_NOT_SUPPORTED, attr_item.line_nr()));
+                            continue;
+                        }
+                        checksums = Some(attr_item.value().as_bool().unwrap());
+                    }
+                },
+                TOML_PAR_MAX_TOTAL_SIZE => {
+                    max_total_size = size_par(attr_item, attr_key, &polkey, MIN_MAX_TOTAL_SIZE,
+                                              MAX_MAX_TOTAL_SIZE, DEF_MAX_TOTAL_SIZE, msgs);
+                },
                 TOML_PAR_CONDITION => {
                     cond_specified = true;
                     if str_par(attr_item, attr_key, &polkey, msgs) {
@@ -1439,11 +3618,14 @@ fn read_rollover_policies(rollover_item: &TomlValueItem,
         let cond = cond.unwrap();
         match cond {
             RolloverCondition::Never => {
-                if compr_algo.is_some() || keep_count.is_some() {
+                if compr_algo.is_some() || keep_count.is_some() || checksums.is_some() ||
+                   max_total_size.is_some() {
                     msgs.push(coalyxw!(W_CFG_MEANINGLESS_ROVR_ATTR, pol_item.line_nr()));
                 }
                 compr_algo = Some(CompressionAlgorithm::default());
                 keep_count = Some(0);
+                checksums = None;
+                max_total_size = None;
             },
             _ => {
                 if compr_algo.is_none() { compr_algo = Some(CompressionAlgorithm::default()); }
@@ -1454,13 +3636,199 @@ fn read_rollover_policies(rollover_item: &TomlValueItem,
                 }
             }
         }
-        let pol_spec = RolloverPolicy::new(key, cond,
-                                           keep_count.unwrap(), compr_algo.unwrap());
+        let mut pol_spec = RolloverPolicy::new(key, cond,
+                                               keep_count.unwrap(), compr_algo.unwrap());
+        pol_spec.set_checksums(checksums.unwrap_or(false));
+        pol_spec.set_max_total_size(max_total_size.unwrap_or(DEF_MAX_TOTAL_SIZE));
         rpols.insert(key, pol_spec);
     }
     Some(rpols)
 }
 
+/// Reads throttle policies from custom configuration.
+/// 
+/// # Arguments
+/// * `throttle_item` - the value item for the policies in the custom TOML document
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the hash map with all valid throttle policies found
+fn read_throttle_policies(throttle_item: &TomlValueItem,
+                          msgs: &mut Vec<CoalyException>) -> ThrottlePolicyMap {
+    let mut tpols = ThrottlePolicyMap::new();
+    if not_table_item(throttle_item, TOML_GRP_THROTTLE, Some(TOML_GRP_POLICIES), msgs) {
+        return tpols
+    }
+    let tpkey = format!("{}.{}", TOML_GRP_POLICIES, TOML_GRP_THROTTLE);
+    for (key, pol_item) in throttle_item.child_items().unwrap() {
+        if not_table_item(pol_item, key, Some(&tpkey), msgs) { continue }
+        let polkey = format!("{}.{}", tpkey, key);
+        let mut max_records = DEF_THROTTLE_MAX_RECORDS as u32;
+        let mut interval = DEF_THROTTLE_INTERVAL as u64;
+        let mut level_limits = Vec::<(u32, u32)>::new();
+        for (attr_key, attr_item) in pol_item.child_items().unwrap() {
+            match attr_key.as_str() {
+                TOML_PAR_MAX_RECORDS => {
+                    if int_par(attr_item, attr_key, &polkey, MIN_THROTTLE_MAX_RECORDS,
+                               MAX_THROTTLE_MAX_RECORDS, DEF_THROTTLE_MAX_RECORDS, msgs) {
+                        max_records = attr_item.value().as_integer().unwrap() as u32;
+                    }
+                },
+                TOML_PAR_INTERVAL => {
+                    if int_par(attr_item, attr_key, &polkey, MIN_THROTTLE_INTERVAL,
+                               MAX_THROTTLE_INTERVAL, DEF_THROTTLE_INTERVAL, msgs) {
+                        interval = attr_item.value().as_integer().unwrap() as u64;
+                    }
+                },
+                TOML_PAR_LEVEL_LIMITS => {
+                    level_limits = read_level_limits(attr_item, attr_key, &polkey, msgs);
+                },
+                _ => {
+                    msgs.push(coalyxw!(W_CFG_INV_THROTTLE_ATTR, attr_item.line_nr(),
+                                     attr_key.to_string(), key.to_string()));
+                }
+            }
+        }
+        tpols.insert(key.to_string(), ThrottlePolicy::new(key, max_records, interval,
+                                                          level_limits));
+    }
+    tpols
+}
+
+/// Reads the record level specific limits of a throttle policy.
+/// 
+/// # Arguments
+/// * `limits_item` - the TOML table containing the mapping from record level to limit
+/// * `key` - key of the table item, for error messages only
+/// * `parent_key` - the full TOML key of the parent item, for error messages only
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// array with the record level IDs and the associated maximum number of records per interval
+fn read_level_limits(limits_item: &TomlValueItem, key: &str, parent_key: &str,
+                     msgs: &mut Vec<CoalyException>) -> Vec<(u32, u32)> {
+    let mut level_limits = Vec::<(u32, u32)>::new();
+    if not_table_item(limits_item, key, Some(parent_key), msgs) { return level_limits }
+    let full_key = format!("{}.{}", parent_key, key);
+    for (lvl_name, limit_item) in limits_item.child_items().unwrap() {
+        match RecordLevelId::from_str(lvl_name) {
+            Ok(lvl_id) => {
+                if int_par(limit_item, lvl_name, &full_key, MIN_THROTTLE_MAX_RECORDS,
+                           MAX_THROTTLE_MAX_RECORDS, DEF_THROTTLE_MAX_RECORDS, msgs) {
+                    let limit = limit_item.value().as_integer().unwrap() as u32;
+                    level_limits.push((lvl_id as u32, limit));
+                }
+            },
+            Err(_) => {
+                let ex = coalyxw!(W_CFG_INV_LVL_REF, limit_item.line_nr(),
+                                  lvl_name.to_string(), full_key.clone());
+                msgs.push(suggest_value(ex, lvl_name, RECORD_LEVEL_NAMES));
+            }
+        }
+    }
+    level_limits
+}
+
+/// Reads sampling policies from custom configuration.
+/// 
+/// # Arguments
+/// * `sampling_item` - the value item for the policies in the custom TOML document
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the hash map with all valid sampling policies found
+fn read_sampling_policies(sampling_item: &TomlValueItem,
+                          msgs: &mut Vec<CoalyException>) -> SamplingPolicyMap {
+    let mut spols = SamplingPolicyMap::new();
+    if not_table_item(sampling_item, TOML_GRP_SAMPLING, Some(TOML_GRP_POLICIES), msgs) {
+        return spols
+    }
+    let spkey = format!("{}.{}", TOML_GRP_POLICIES, TOML_GRP_SAMPLING);
+    for (key, pol_item) in sampling_item.child_items().unwrap() {
+        if not_table_item(pol_item, key, Some(&spkey), msgs) { continue }
+        let polkey = format!("{}.{}", spkey, key);
+        let mut sample_rate = DEF_SAMPLE_RATE;
+        let mut level_rates = Vec::<(u32, f64)>::new();
+        for (attr_key, attr_item) in pol_item.child_items().unwrap() {
+            match attr_key.as_str() {
+                TOML_PAR_SAMPLE_RATE => {
+                    if let Some(rate) = rate_par(attr_item, attr_key, &polkey, msgs) {
+                        sample_rate = rate;
+                    }
+                },
+                TOML_PAR_LEVEL_RATES => {
+                    level_rates = read_level_rates(attr_item, attr_key, &polkey, msgs);
+                },
+                _ => {
+                    msgs.push(coalyxw!(W_CFG_INV_SAMPLING_ATTR, attr_item.line_nr(),
+                                     attr_key.to_string(), key.to_string()));
+                }
+            }
+        }
+        spols.insert(key.to_string(), SamplingPolicy::new(key, sample_rate, level_rates));
+    }
+    spols
+}
+
+/// Reads the record level specific rates of a sampling policy.
+/// 
+/// # Arguments
+/// * `rates_item` - the TOML table containing the mapping from record level to rate
+/// * `key` - key of the table item, for error messages only
+/// * `parent_key` - the full TOML key of the parent item, for error messages only
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// array with the record level IDs and the associated fraction of records written
+fn read_level_rates(rates_item: &TomlValueItem, key: &str, parent_key: &str,
+                    msgs: &mut Vec<CoalyException>) -> Vec<(u32, f64)> {
+    let mut level_rates = Vec::<(u32, f64)>::new();
+    if not_table_item(rates_item, key, Some(parent_key), msgs) { return level_rates }
+    let full_key = format!("{}.{}", parent_key, key);
+    for (lvl_name, rate_item) in rates_item.child_items().unwrap() {
+        match RecordLevelId::from_str(lvl_name) {
+            Ok(lvl_id) => {
+                if let Some(rate) = rate_par(rate_item, lvl_name, &full_key, msgs) {
+                    level_rates.push((lvl_id as u32, rate));
+                }
+            },
+            Err(_) => {
+                let ex = coalyxw!(W_CFG_INV_LVL_REF, rate_item.line_nr(),
+                                  lvl_name.to_string(), full_key.clone());
+                msgs.push(suggest_value(ex, lvl_name, RECORD_LEVEL_NAMES));
+            }
+        }
+    }
+    level_rates
+}
+
+/// Returns the sampling rate held by the specified TOML value item.
+/// Appends an exception to the given exception array, if the item doesn't hold a number
+/// between 0.0 and 1.0.
+/// 
+/// # Arguments
+/// * `item` - the TOML value item
+/// * `key` - the pure name of the value item
+/// * `parent_key` - the full key of the item's parent
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the sampling rate, **None** if the item holds an invalid value
+fn rate_par(item: &TomlValueItem, key: &str, parent_key: &str,
+            msgs: &mut Vec<CoalyException>) -> Option<f64> {
+    let rate = item.value().as_float();
+    if let Some(r) = rate {
+        if (0.0..=1.0).contains(&r) { return Some(r) }
+    }
+    let val = match rate {
+        Some(r) => r.to_string(),
+        None => item.value().as_str().unwrap_or_default()
+    };
+    msgs.push(coalyxw!(W_CFG_INV_SAMPLING_RATE, item.line_nr(), val,
+                       format!("{}.{}", parent_key, key)));
+    None
+}
+
 /// Reads all application IDs from a TOML array.
 /// 
 /// # Arguments
@@ -1508,6 +3876,26 @@ pub(crate) fn str_par(item: &TomlValueItem, key: &str,
     false
 }
 
+/// Checks whether the specified TOML value item holds a boolean value.
+/// Appends an exception to the given exception array, if not.
+/// 
+/// # Arguments
+/// * `item` - the TOML value item
+/// * `key` - the pure name of the value item
+/// * `parent_key` - the full key of the item's parent
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// **true** if the value item holds a boolean value; otherwise **false**
+pub(crate) fn bool_par(item: &TomlValueItem, key: &str,
+                       parent_key: &str,
+                       msgs: &mut Vec<CoalyException>) -> bool {
+    if matches!(item.value(), TomlValue::Boolean(_)) { return true }
+    let full_name = format!("{}.{}", parent_key, key);
+    msgs.push(coalyxw!(W_CFG_KEY_NOT_A_BOOL, item.line_nr(), full_name));
+    false
+}
+
 /// Checks whether the specified TOML value item holds a number value.
 /// Appends an exception to the given exception array, if not.
 /// 
@@ -1553,8 +3941,8 @@ pub(crate) fn size_par(item: &TomlValueItem, key: &str, parent_key: &str,
                        msgs: &mut Vec<CoalyException>) -> Option<usize> {
     let full_key = format!("{}.{}", parent_key, key);
     if let Some(str_item) = item.value().as_str() {
-        let num_pat = Regex::new("^[0-9]+[kKmMgG]{0,1}$").unwrap();
-        if ! num_pat.is_match(&str_item) {
+        let num_part = str_item.strip_suffix(['k', 'K', 'm', 'M', 'g', 'G']).unwrap_or(&str_item);
+        if num_part.is_empty() || ! num_part.bytes().all(|b| b.is_ascii_digit()) {
             msgs.push(coalyxw!(W_CFG_INV_SIZE_SPEC, item.line_nr(), str_item,
                              full_key, default_val.to_string()));
             return None
@@ -1588,6 +3976,29 @@ pub(crate) fn size_par(item: &TomlValueItem, key: &str, parent_key: &str,
     None
 }
 
+/// Parses a time slice interval specification.
+/// The specification consists of a number followed by one of the units m (minutes),
+/// h (hours) or d (days), the interval must divide a day evenly.
+/// 
+/// # Arguments
+/// * `spec` - the interval specification
+/// 
+/// # Return values
+/// The interval in minutes; **None** if the specification is invalid
+fn parse_time_slice(spec: &str) -> Option<u32> {
+    let spec = spec.trim();
+    let num_spec = spec.strip_suffix(TIME_SLICE_UNITS)?;
+    if num_spec.is_empty() || ! num_spec.bytes().all(|b| b.is_ascii_digit()) { return None }
+    let num = u32::from_str(num_spec).ok()?;
+    let minutes = match spec.chars().last()? {
+        'm' => num,
+        'h' => num.checked_mul(60)?,
+        _ => num.checked_mul(MINUTES_PER_DAY)?
+    };
+    if minutes == 0 || ! MINUTES_PER_DAY.is_multiple_of(minutes) { return None }
+    Some(minutes)
+}
+
 /// Checks whether the specified TOML value item holds a table value.
 /// Appends an exception to the given exception array, if not.
 /// 
@@ -1610,9 +4021,23 @@ pub(crate) fn not_table_item(item: &TomlValueItem, key: &str, parent_key: Option
 
 /// Returns all environment variable names in the given format string.
 fn merge_env_vars(fmt_str: &str, result: &mut HashSet<String>) {
-    for var_name in Regex::new(ENV_VAR_PATTERN).unwrap().captures_iter(fmt_str) {
-        result.insert(var_name.get(1).unwrap().as_str().to_string());
+    for var_name in env_var_refs(fmt_str) {
+        result.insert(var_name.to_string());
+    }
+}
+
+/// Returns the names of all environment variables referenced by *$Env[name]* in the given
+/// string, in order of their occurrence.
+fn env_var_refs(s: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = s;
+    while let Some(pos) = rest.find(ENV_VAR_PREFIX) {
+        let arg = &rest[pos + ENV_VAR_PREFIX.len()..];
+        let Some(end) = arg.find(']') else { break };
+        names.push(&arg[..end]);
+        rest = &arg[end + 1..];
     }
+    names
 }
 
 /// Replaces all placeholder variables in a path.
@@ -1633,9 +4058,7 @@ fn prepare_path(path_spec: &str,
     path_name = path_name.replace(&var_proc_name, orig_info.process_name());
     let var_env = format!("${}[", VAR_NAME_ENV);
     if path_name.contains(&var_env) {
-        let env_pat = Regex::new(ENV_VAR_PATTERN).unwrap();
-        for enva in env_pat.captures_iter(&path_name.clone()) {
-            let enva_name = enva.get(1).unwrap().as_str();
+        for enva_name in env_var_refs(&path_name.clone()) {
             if let Ok(enva_val) = std::env::var(enva_name) {
                 path_name = path_name.replace(enva_name, &enva_val);
             } else {
@@ -1669,10 +4092,19 @@ fn prepare_path(path_spec: &str,
     Ok(path_name)
 }
 
+// name of a configuration from text used in messages
+const EMBEDDED_CONFIG_NAME: &str = "<embedded>";
+
 // TOML keys for logical groups in the custom configuration file.
 // Logical groups are formed by TOML tables or arrays of tables.
+const TOML_GRP_ADAPTERS: &str = "adapters";
 const TOML_GRP_BUFFER: &str = "buffer";
+const TOML_GRP_COLORS: &str = "colors";
 const TOML_GRP_DATETIME: &str = "datetime";
+const TOML_GRP_CLEANUP: &str = "cleanup";
+const TOML_GRP_PROBLEMS: &str = "problems";
+const TOML_GRP_DEGRADED: &str = "degraded";
+const TOML_GRP_FILTERS: &str = "filters";
 const TOML_GRP_FORMATS: &str = "formats";
 const TOML_GRP_LEVELS: &str = "levels";
 const TOML_GRP_MODE: &str = "mode";
@@ -1681,6 +4113,8 @@ const TOML_GRP_OUTPUT: &str = "output";
 const TOML_GRP_POLICIES: &str = "policies";
 const TOML_GRP_RESOURCES: &str = "resources";
 const TOML_GRP_ROLLOVER: &str = "rollover";
+const TOML_GRP_SAMPLING: &str = "sampling";
+const TOML_GRP_THROTTLE: &str = "throttle";
 const TOML_GRP_SYSTEM: &str = "system";
 #[cfg(feature="net")]
 const TOML_GRP_SERVER: &str = "server";
@@ -1689,45 +4123,243 @@ const TOML_GRP_SERVER: &str = "server";
 const TOML_PAR_APP_ID: &str = "app_id";
 const TOML_PAR_APP_IDS: &str = "app_ids";
 const TOML_PAR_APP_NAME: &str = "app_name";
+const TOML_PAR_BACKTRACE_LEVELS: &str = "backtrace_levels";
 const TOML_PAR_BUFFER: &str = "buffer";
 const TOML_PAR_BUFFERED: &str = "buffered";
+const TOML_PAR_BUFFER_MEMORY_LIMIT: &str = "buffer_memory_limit";
+const TOML_PAR_CAPTURE: &str = "capture";
+const TOML_PAR_CHECKSUMS: &str = "checksums";
 const TOML_PAR_CHG_STACK_SIZE: &str = "change_stack_size";
+const TOML_PAR_CLOCK: &str = "clock";
+const TOML_PAR_CLOCK_GRANULARITY: &str = "clock_granularity";
 const TOML_PAR_COMPRESSION: &str = "compression";
 const TOML_PAR_CONDITION: &str = "condition";
 const TOML_PAR_CONTENT_SIZE: &str = "content_size";
 const TOML_PAR_DATE: &str = "date";
 const TOML_PAR_DATETIME_FORMAT: &str = "datetime_format";
 const TOML_PAR_ENABLED: &str = "enabled";
+const TOML_PAR_ENCODING: &str = "encoding";
+const TOML_PAR_ENV_CACHE_TTL: &str = "env_cache_ttl";
+const TOML_PAR_ENV_PER_RECORD: &str = "env_per_record";
+const TOML_PAR_ENV_SNAPSHOT: &str = "env_snapshot";
+const TOML_PAR_EXCLUDE_FILES: &str = "exclude_files";
+const TOML_PAR_EXCLUDE_MODULES: &str = "exclude_modules";
 const TOML_PAR_FALLBACK_PATH: &str = "fallback_path";
+const TOML_PAR_FIELDS: &str = "fields";
+const TOML_PAR_FN_MAX_VAR_LENGTH: &str = "file_name_max_var_length";
+const TOML_PAR_FN_REPLACEMENT_CHAR: &str = "file_name_replacement_char";
+const TOML_PAR_FILTER: &str = "filter";
 const TOML_PAR_FLUSH: &str = "flush";
+const TOML_PAR_FLUSH_DEADLINE: &str = "flush_deadline";
+const TOML_PAR_FLUSH_PRIORITY: &str = "flush_priority";
 const TOML_PAR_ID: &str = "id";
+const TOML_PAR_INCLUDE_FILES: &str = "include_files";
+const TOML_PAR_INCLUDE_MODULES: &str = "include_modules";
 const TOML_PAR_INDEX_SIZE: &str = "index_size";
+const TOML_PAR_INTERVAL: &str = "interval";
 const TOML_PAR_ITEMS: &str = "items";
 const TOML_PAR_KEEP: &str = "keep";
 const TOML_PAR_KIND: &str = "kind";
+const TOML_PAR_LAYOUT: &str = "layout";
+const TOML_PAR_LEVEL: &str = "level";
+const TOML_PAR_LEVEL_FORMATS: &str = "level_formats";
+const TOML_PAR_LEVEL_LIMITS: &str = "level_limits";
+const TOML_PAR_LEVEL_RATES: &str = "level_rates";
 const TOML_PAR_LEVELS: &str = "levels";
 const TOML_PAR_LOCAL_URL: &str = "local_url";
+const TOML_PAR_MAX_CONTENT_SIZE: &str = "max_content_size";
 const TOML_PAR_MAX_REC_LEN: &str = "max_record_length";
+const TOML_PAR_MAX_RECORDS: &str = "max_records";
+const TOML_PAR_MAX_TOTAL_SIZE: &str = "max_total_size";
+const TOML_PAR_MIN_INTERVAL: &str = "min_interval";
+const TOML_PAR_MODE: &str = "mode";
 const TOML_PAR_NAME: &str = "name";
+const TOML_PAR_ON_CREATE_ERROR: &str = "on_create_error";
 const TOML_PAR_OUTPUT_FORMAT: &str = "output_format";
 const TOML_PAR_OUTPUT_PATH: &str = "output_path";
+const TOML_PAR_PREFIX: &str = "prefix";
 const TOML_PAR_REMOTE_URL: &str = "remote_url";
+const TOML_PAR_RETRY_INTERVAL: &str = "retry_interval";
 const TOML_PAR_ROLLOVER: &str = "rollover";
+const TOML_PAR_SAMPLE_RATE: &str = "sample_rate";
+const TOML_PAR_SAMPLING: &str = "sampling";
 const TOML_PAR_SCOPE: &str = "scope";
+const TOML_PAR_SHRINK_AFTER: &str = "shrink_after";
+const TOML_PAR_DUPLICATE_WINDOW: &str = "duplicate_window";
+const TOML_PAR_ADAPTIVE_LATENCY: &str = "adaptive_latency";
+const TOML_PAR_SHUTDOWN_TIMEOUT: &str = "shutdown_timeout";
+const TOML_PAR_COUNTER_INTERVAL: &str = "counter_interval";
+const TOML_PAR_DEGRADE_AFTER: &str = "degrade_after";
+const TOML_PAR_RECOVER_AFTER: &str = "recover_after";
+const TOML_PAR_RETENTION: &str = "retention";
+const TOML_PAR_RATE_LIMIT: &str = "rate_limit";
+const TOML_PAR_OVERLOAD_RATE: &str = "overload_rate";
+const TOML_PAR_MAX_OPEN_FILES: &str = "max_open_files";
 const TOML_PAR_SIZE: &str = "size";
+const TOML_PAR_SUFFIX: &str = "suffix";
+const TOML_PAR_SUPPRESS: &str = "suppress";
+const TOML_PAR_SUPPRESSED_TRIGGERS: &str = "suppressed_triggers";
+const TOML_PAR_TARGET: &str = "target";
+const TOML_PAR_THROTTLE: &str = "throttle";
 const TOML_PAR_TIME: &str = "time";
 const TOML_PAR_TIMESTAMP: &str = "timestamp";
+const TOML_PAR_TIMESTAMPS: &str = "timestamps";
+const TOML_PAR_TRACE_PIPELINE: &str = "trace_pipeline";
+const TOML_PAR_FILE_FORMAT: &str = "file_format";
+const TOML_PAR_ENCRYPTED: &str = "encrypted";
+const TOML_PAR_RING: &str = "ring";
+const TOML_PAR_SHARING: &str = "sharing";
+const TOML_PAR_PROBE: &str = "probe";
+const TOML_PAR_ENCRYPTION_KEY_FILE: &str = "encryption_key_file";
+const TOML_PAR_ENCRYPTION_PASSPHRASE: &str = "encryption_passphrase";
 const TOML_PAR_TRIGGER: &str = "trigger";
 const TOML_PAR_TRIGGERS: &str = "triggers";
+const TOML_PAR_TTL: &str = "ttl";
+const TOML_PAR_UNIT: &str = "unit";
 const TOML_PAR_VALUE: &str = "value";
 const TOML_PAR_VERSION: &str = "version";
+const TOML_PAR_WATCH: &str = "watch";
+const TOML_PAR_ZONE: &str = "zone";
 #[cfg(feature="net")]
 const TOML_PAR_FACILITY: &str = "facility";
+#[cfg(feature="net")]
+const TOML_PAR_PAYLOAD: &str = "payload";
+#[cfg(feature="net")]
+const TOML_PAR_SPOOL: &str = "spool";
+#[cfg(feature="net")]
+const TOML_PAR_SPILL: &str = "spill";
+#[cfg(feature="net")]
+const TOML_PAR_RECONNECT_DELAY: &str = "reconnect_delay";
+#[cfg(feature="net")]
+const TOML_PAR_MAX_RECONNECT_DELAY: &str = "max_reconnect_delay";
+#[cfg(feature="net")]
+const TOML_PAR_TLS_CA_FILE: &str = "tls_ca_file";
+#[cfg(feature="net")]
+const TOML_PAR_TLS_CERT_FILE: &str = "tls_cert_file";
+#[cfg(feature="net")]
+const TOML_PAR_TLS_KEY_FILE: &str = "tls_key_file";
+#[cfg(feature="net")]
+const TOML_PAR_TLS_SERVER_NAME: &str = "tls_server_name";
+#[cfg(feature="net")]
+const TOML_PAR_TLS_VERIFY: &str = "tls_verify";
+#[cfg(feature="net")]
+const TOML_PAR_BATCH_FORMAT: &str = "batch_format";
+#[cfg(feature="net")]
+const TOML_PAR_BATCH_SIZE: &str = "batch_size";
+#[cfg(feature="net")]
+const TOML_PAR_BATCH_INTERVAL: &str = "batch_interval";
+#[cfg(feature="net")]
+const TOML_PAR_MAX_RETRIES: &str = "max_retries";
+#[cfg(feature="net")]
+const TOML_PAR_METRICS_ADDRESS: &str = "metrics_address";
+
+// Valid keys per TOML table, used for hints in configuration warnings
+#[cfg(not(feature="net"))]
+const ROOT_KEYS: &[&str] = &[TOML_GRP_SYSTEM, TOML_GRP_POLICIES, TOML_GRP_FORMATS,
+                             TOML_GRP_FILTERS, TOML_GRP_RESOURCES, TOML_GRP_MODES,
+                             TOML_GRP_ADAPTERS];
+#[cfg(feature="net")]
+const ROOT_KEYS: &[&str] = &[TOML_GRP_SYSTEM, TOML_GRP_SERVER, TOML_GRP_POLICIES,
+                             TOML_GRP_FORMATS, TOML_GRP_FILTERS, TOML_GRP_RESOURCES,
+                             TOML_GRP_MODES, TOML_GRP_ADAPTERS];
+#[cfg(not(feature="net"))]
+const SYSTEM_KEYS: &[&str] = &[TOML_PAR_VERSION, TOML_PAR_APP_ID, TOML_PAR_APP_NAME,
+                               TOML_PAR_BACKTRACE_LEVELS, TOML_PAR_BUFFER_MEMORY_LIMIT,
+                               TOML_PAR_CHG_STACK_SIZE, TOML_PAR_CLOCK, TOML_PAR_COUNTER_INTERVAL,
+                               TOML_PAR_ENCRYPTION_KEY_FILE,
+                               TOML_PAR_ENCRYPTION_PASSPHRASE, TOML_PAR_ENV_CACHE_TTL,
+                               TOML_PAR_ENV_PER_RECORD, TOML_PAR_ENV_SNAPSHOT,
+                               TOML_PAR_FALLBACK_PATH, TOML_PAR_FN_MAX_VAR_LENGTH,
+                               TOML_PAR_FN_REPLACEMENT_CHAR, TOML_PAR_MAX_OPEN_FILES,
+                               TOML_PAR_OUTPUT_PATH, TOML_PAR_SHUTDOWN_TIMEOUT, TOML_PAR_SUPPRESS,
+                               TOML_PAR_WATCH, TOML_GRP_CLEANUP, TOML_GRP_LEVELS, TOML_GRP_MODE,
+                               TOML_GRP_DEGRADED, TOML_GRP_PROBLEMS];
+#[cfg(feature="net")]
+const SYSTEM_KEYS: &[&str] = &[TOML_PAR_VERSION, TOML_PAR_APP_ID, TOML_PAR_APP_NAME,
+                               TOML_PAR_BACKTRACE_LEVELS, TOML_PAR_BUFFER_MEMORY_LIMIT,
+                               TOML_PAR_CHG_STACK_SIZE, TOML_PAR_CLOCK, TOML_PAR_COUNTER_INTERVAL,
+                               TOML_PAR_ENCRYPTION_KEY_FILE,
+                               TOML_PAR_ENCRYPTION_PASSPHRASE, TOML_PAR_ENV_CACHE_TTL,
+                               TOML_PAR_ENV_PER_RECORD, TOML_PAR_ENV_SNAPSHOT,
+                               TOML_PAR_FALLBACK_PATH, TOML_PAR_FN_MAX_VAR_LENGTH,
+                               TOML_PAR_FN_REPLACEMENT_CHAR, TOML_PAR_MAX_OPEN_FILES,
+                               TOML_PAR_METRICS_ADDRESS, TOML_PAR_OUTPUT_PATH, TOML_PAR_SHUTDOWN_TIMEOUT,
+                               TOML_PAR_SUPPRESS, TOML_PAR_WATCH, TOML_GRP_CLEANUP,
+                               TOML_GRP_LEVELS, TOML_GRP_MODE, TOML_GRP_DEGRADED,
+                               TOML_GRP_PROBLEMS];
+const SYSTEM_MODE_KEYS: &[&str] = &[TOML_PAR_BUFFERED, TOML_PAR_ENABLED];
+const SYSTEM_DEGRADED_KEYS: &[&str] = &[TOML_PAR_ENABLED, TOML_GRP_RESOURCES,
+                                        TOML_PAR_DEGRADE_AFTER, TOML_PAR_RECOVER_AFTER,
+                                        TOML_PAR_OVERLOAD_RATE];
+const SYSTEM_CLEANUP_KEYS: &[&str] = &[TOML_PAR_COMPRESSION, TOML_PAR_RETENTION];
+const SYSTEM_PROBLEMS_KEYS: &[&str] = &[TOML_PAR_LAYOUT, TOML_PAR_LEVEL, TOML_PAR_NAME,
+                                        TOML_PAR_RATE_LIMIT, TOML_PAR_TARGET,
+                                        TOML_PAR_TRACE_PIPELINE];
+const POLICIES_KEYS: &[&str] = &[TOML_GRP_BUFFER, TOML_GRP_ROLLOVER, TOML_GRP_THROTTLE,
+                                 TOML_GRP_SAMPLING];
+const FORMATS_KEYS: &[&str] = &[TOML_GRP_OUTPUT, TOML_GRP_DATETIME, TOML_GRP_COLORS];
+const FILTER_KEYS: &[&str] = &[TOML_PAR_INCLUDE_MODULES, TOML_PAR_EXCLUDE_MODULES,
+                               TOML_PAR_INCLUDE_FILES, TOML_PAR_EXCLUDE_FILES];
+const MODE_KEYS: &[&str] = &[TOML_PAR_TRIGGER, TOML_PAR_NAME, TOML_PAR_VALUE, TOML_PAR_ENABLED,
+                             TOML_PAR_BUFFERED, TOML_PAR_SCOPE, TOML_PAR_SAMPLE_RATE,
+                             TOML_PAR_PREFIX, TOML_PAR_SUFFIX, TOML_PAR_FIELDS,
+                             TOML_PAR_SUPPRESSED_TRIGGERS, TOML_PAR_CAPTURE];
+const MODE_CAPTURE_KEYS: &[&str] = &[TOML_PAR_NAME, TOML_PAR_TTL];
+const ADAPTER_KEYS: &[&str] = &[TOML_PAR_TARGET, TOML_PAR_LEVELS, TOML_PAR_LEVEL, TOML_PAR_UNIT];
+#[cfg(not(feature="net"))]
+const RESOURCE_KEYS: &[&str] = &[TOML_PAR_KIND, TOML_PAR_APP_IDS, TOML_PAR_NAME, TOML_PAR_SIZE,
+                                 TOML_PAR_LEVELS, TOML_PAR_OUTPUT_FORMAT, TOML_PAR_ROLLOVER,
+                                 TOML_PAR_ENCODING, TOML_PAR_LOCAL_URL, TOML_PAR_REMOTE_URL,
+                                 TOML_PAR_BUFFER, TOML_PAR_MIN_INTERVAL, TOML_PAR_LEVEL_FORMATS,
+                                 TOML_PAR_FILTER, TOML_PAR_THROTTLE, TOML_PAR_SAMPLING,
+                                 TOML_PAR_FLUSH_PRIORITY,
+                                 TOML_PAR_FLUSH_DEADLINE, TOML_PAR_ON_CREATE_ERROR,
+                                 TOML_PAR_RETRY_INTERVAL, TOML_PAR_INTERVAL,
+                                 TOML_PAR_CLOCK_GRANULARITY, TOML_PAR_TIMESTAMPS,
+                                 TOML_PAR_FILE_FORMAT, TOML_PAR_ENCRYPTED, TOML_PAR_RING,
+                                 TOML_PAR_SHARING, TOML_PAR_PROBE];
+#[cfg(feature="net")]
+const RESOURCE_KEYS: &[&str] = &[TOML_PAR_KIND, TOML_PAR_APP_IDS, TOML_PAR_NAME, TOML_PAR_SIZE,
+                                 TOML_PAR_LEVELS, TOML_PAR_OUTPUT_FORMAT, TOML_PAR_ROLLOVER,
+                                 TOML_PAR_ENCODING, TOML_PAR_LOCAL_URL, TOML_PAR_REMOTE_URL,
+                                 TOML_PAR_BUFFER, TOML_PAR_MIN_INTERVAL, TOML_PAR_FACILITY,
+                                 TOML_PAR_PAYLOAD, TOML_PAR_COMPRESSION,
+                                 TOML_PAR_LEVEL_FORMATS, TOML_PAR_SPOOL, TOML_PAR_FILTER,
+                                 TOML_PAR_THROTTLE, TOML_PAR_SAMPLING, TOML_PAR_FLUSH_PRIORITY,
+                                 TOML_PAR_FLUSH_DEADLINE, TOML_PAR_ON_CREATE_ERROR,
+                                 TOML_PAR_RETRY_INTERVAL, TOML_PAR_TLS_CA_FILE,
+                                 TOML_PAR_TLS_CERT_FILE, TOML_PAR_TLS_KEY_FILE,
+                                 TOML_PAR_TLS_SERVER_NAME, TOML_PAR_TLS_VERIFY, TOML_PAR_SPILL,
+                                 TOML_PAR_RECONNECT_DELAY, TOML_PAR_MAX_RECONNECT_DELAY,
+                                 TOML_PAR_INTERVAL, TOML_PAR_BATCH_FORMAT, TOML_PAR_BATCH_SIZE,
+                                 TOML_PAR_BATCH_INTERVAL, TOML_PAR_MAX_RETRIES,
+                                 TOML_PAR_CLOCK_GRANULARITY, TOML_PAR_TIMESTAMPS,
+                                 TOML_PAR_FILE_FORMAT, TOML_PAR_ENCRYPTED, TOML_PAR_RING,
+                                 TOML_PAR_SHARING, TOML_PAR_PROBE];
+
+// Default application name for desktop notifications
+#[cfg(feature="os-resources")]
+const DEFAULT_NOTIFICATION_APP_NAME: &str = "Coaly";
 
-const ENV_VAR_PATTERN: &str = r"\$Env\[(.*?)\]";
+// Default event source name for the Windows event log
+#[cfg(all(feature="os-resources", windows))]
+const DEFAULT_EVENTLOG_SOURCE_NAME: &str = "Coaly";
+
+// Prefix of environment variable references in path and format specifications
+const ENV_VAR_PREFIX: &str = "$Env[";
+
+// Units for time slice intervals of file based resources
+const TIME_SLICE_UNITS: [char; 3] = ['m', 'h', 'd'];
+const MINUTES_PER_DAY: u32 = 1440;
+
+// Environment variable overriding the initially enabled record levels
+const ENV_COALY_LEVELS: &str = "COALY_LEVELS";
+
+// Pseudo level name in environment variable COALY_LEVELS disabling all levels
+const ENV_LEVEL_NONE: &str = "none";
 
 #[cfg(feature="net")]
-const DEFAULT_SYSLOG_URL: &str = "file:/dev/log";
+pub(crate) const DEFAULT_SYSLOG_URL: &str = "file:/dev/log";
 
 #[cfg(test)]
 mod test {
@@ -1736,7 +4368,11 @@ mod test {
     use crate::util::tests::run_unit_tests;
     use std::env;
     use std::fs::read_to_string;
-    use super::configuration;
+    use std::str::FromStr;
+    use super::{configuration, read_env_levels, runtime_mode_change, source_configuration,
+                Configuration, ConfigSource};
+    use crate::errorhandling::E_CFG_INV_RUNTIME_MODE_CHANGE;
+    use crate::record::RecordLevelId;
 
     /// Unit test function for Coaly configuration tests.
     fn run_config_test(success_expected: bool,
@@ -1745,13 +4381,21 @@ mod test {
                        ref_fn: &str) -> Option<String> {
         let test_name = &input_fn[input_fn.rfind('/').unwrap()+1 ..];
         let test_name = &test_name[0 .. test_name.find('.').unwrap()];
+        let block_index = test_name.find(|c: char| c.is_ascii_digit()).unwrap();
+        // characters before test type denote the features or platform required by the test
+        let prefix = &test_name[.. block_index - 1];
         #[cfg(not(feature="net"))]
-        if test_name.starts_with('n') { return None }
+        if prefix.contains('n') { return None }
+        #[cfg(not(feature="tls"))]
+        if prefix.contains('t') { return None }
         #[cfg(not(feature="compression"))]
-        if test_name.starts_with('x') { return None }
+        if prefix.contains('x') { return None }
         #[cfg(feature="compression")]
-        if test_name.starts_with('c') { return None }
-        let block_index = if test_name.starts_with('s') || test_name.starts_with('f') {1} else {2};
+        if prefix.contains('c') { return None }
+        #[cfg(not(target_os="linux"))]
+        if prefix.contains('l') { return None }
+        #[cfg(not(feature="encryption"))]
+        if prefix.contains('e') { return None }
         let oinfo = originator_info();
         match read_to_string(ref_fn) {
             Ok(expected_result) => {
@@ -1767,11 +4411,15 @@ mod test {
                 let mut actual_result = match test_name.chars().nth(block_index).unwrap() {
                     '1' => format!("{:?}", config.system_properties()),
                     '2' => format!("{:?}", config.date_time_formats()),
-                    '3' => format!("{:?}", config.output_formats),
+                    '3' => match config.console_colors() {
+                               Some(c) => format!("{:?}/COL:{:?}", config.output_formats, c),
+                               None => format!("{:?}", config.output_formats)
+                           },
                     '4' => format!("{:?}", config.buffer_policies),
                     '5' => format!("{:?}", config.rollover_policies),
                     '6' => format!("{:?}", config.resources()),
                     '7' => format!("{:?}", config.mode_changes()),
+                    '9' => format!("{:?}", config.adapter_mappings()),
                     #[cfg(feature="net")]
                     '8' => if config.server_properties().is_none() { String::from("-") }
                            else { format!("{:?}", config.server_properties().as_ref().unwrap()) },
@@ -1802,6 +4450,8 @@ mod test {
 
     #[test]
     fn config_tests() {
+        // reference results assume the resource kinds and parameters of the core feature set
+        if cfg!(not(feature="core")) { return }
         let test_lang = "en";
         let proj_root = env::var("COALY_PROJ_ROOT").unwrap();
         // Success tests
@@ -1815,4 +4465,101 @@ mod test {
             panic!("Coaly failure tests failed: {}", &err_msg)
         }
     }
+    #[test]
+    fn config_from_source() {
+        let oinfo = originator_info();
+        let text = "[system]\napp_id = 4711\napp_name = \"embedded\"\n".to_string();
+        let dir = format!("{}/config_from_source", env::var("COALY_TESTING_ROOT").unwrap());
+        let _ = std::fs::create_dir_all(&dir);
+        let overlay_fn = format!("{}/overlay.toml", dir);
+        let _ = std::fs::remove_file(&overlay_fn);
+        // missing overlay file is ignored
+        let source = ConfigSource::Text(text.clone(), Some(overlay_fn.clone()));
+        let cfg = source_configuration(&oinfo, &source);
+        assert!(cfg.messages().is_empty());
+        assert_eq!(4711, cfg.system_properties().application_id());
+        // overlay file values take precedence, tables are merged
+        std::fs::write(&overlay_fn, "[system]\napp_id = 815\n").unwrap();
+        let cfg = source_configuration(&oinfo, &source);
+        assert!(cfg.messages().is_empty());
+        assert_eq!(815, cfg.system_properties().application_id());
+        assert_eq!("embedded", cfg.system_properties().application_name());
+        // invalid overlay file is reported, text is used alone
+        std::fs::write(&overlay_fn, "[system\n").unwrap();
+        let cfg = source_configuration(&oinfo, &source);
+        assert_eq!(1, cfg.messages().len());
+        assert_eq!(4711, cfg.system_properties().application_id());
+        let _ = std::fs::remove_file(&overlay_fn);
+    }
+    #[test]
+    fn config_from_str() {
+        let cfg = Configuration::from_str("[system]\napp_id = 4711\n").unwrap();
+        assert!(cfg.messages().is_empty());
+        assert_eq!(4711, cfg.system_properties().application_id());
+        assert_eq!(1, cfg.resource_descs().count());
+        assert_eq!(0, cfg.mode_change_count());
+        let toml_text = "[[resources]]\nkind = \"file\"\nlevels = [ \"all\" ]\nname = \"app.log\"\n\
+                         [[resources]]\nkind = \"stdout\"\nlevels = [ \"logs\" ]\n\
+                         [[modes]]\ntrigger = \"module\"\nname = \"stable\"\n\
+                         enabled = [ \"error\" ]\n\
+                         [[modes]]\ntrigger = \"object\"\nvalue = \"CLY.*\"\n\
+                         enabled = [ \"all\" ]\nscope = \"process\"\n";
+        let cfg = Configuration::from_str(toml_text).unwrap();
+        assert!(cfg.messages().is_empty());
+        let kinds: Vec<String> = cfg.resource_descs().map(|r| r.kind().to_string()).collect();
+        assert_eq!(vec!("file", "stdout"), kinds);
+        assert_eq!(2, cfg.mode_change_count());
+        let cfg = Configuration::from_str("[system]\nunknown_key = 1\n").unwrap();
+        assert_eq!(1, cfg.messages().len());
+        assert!(Configuration::from_str("[system\n").is_err());
+    }
+    #[test]
+    fn config_env_levels() {
+        let mut msgs = Vec::new();
+        let expected = RecordLevelId::Error as u32 | RecordLevelId::Warning as u32 |
+                       RecordLevelId::Debug as u32;
+        assert_eq!(Some(expected), read_env_levels("error, Warning,debug", &mut msgs));
+        assert_eq!(Some(RecordLevelId::All as u32), read_env_levels("all", &mut msgs));
+        assert_eq!(Some(0), read_env_levels("none", &mut msgs));
+        assert!(msgs.is_empty());
+        assert_eq!(None, read_env_levels(" , ", &mut msgs));
+        assert_eq!(Some(RecordLevelId::Info as u32), read_env_levels("info,verbose", &mut msgs));
+        assert_eq!(1, msgs.len());
+    }
+    #[test]
+    fn config_output_settings_differ() {
+        let res = "[[resources]]\nkind = \"file\"\nlevels = [ \"all\" ]\nname = \"a.log\"\n";
+        let cfg = Configuration::from_str(res).unwrap();
+        assert!(! cfg.output_settings_differ(&Configuration::from_str(res).unwrap()));
+        let mode_cfg = format!("[system.mode]\nenabled = [ \"all\" ]\n\
+                                [[modes]]\ntrigger = \"module\"\nname = \"m\"\n\
+                                enabled = [ \"error\" ]\n{}", res);
+        let mode_cfg = Configuration::from_str(&mode_cfg).unwrap();
+        assert!(mode_cfg.messages().is_empty());
+        assert!(! cfg.output_settings_differ(&mode_cfg));
+        let res_cfg = Configuration::from_str(&res.replace("a.log", "b.log")).unwrap();
+        assert!(cfg.output_settings_differ(&res_cfg));
+        let sys_cfg = format!("[system]\napp_id = 4711\n{}", res);
+        let sys_cfg = Configuration::from_str(&sys_cfg).unwrap();
+        assert!(cfg.output_settings_differ(&sys_cfg));
+    }
+    #[test]
+    fn config_runtime_mode_change() {
+        // observer value is a regular expression
+        if cfg!(not(feature="regex")) { return }
+        let spec = "{ trigger = \"object\", name = \"customer\", value = \"^4711$\", \
+                    enabled = [ \"all\" ] }";
+        let rt_chgs = runtime_mode_change(spec).unwrap();
+        let cfg = Configuration::from_str("[[modes]]\ntrigger = \"object\"\nname = \"customer\"\n\
+                                           enabled = [ \"error\" ]\n").unwrap();
+        let m_chgs = rt_chgs.followed_by(cfg.mode_changes());
+        let mode = m_chgs.local_mode_for_obj(1, Some("customer"), Some("4711"));
+        assert_eq!(RecordLevelId::All as u32 & 0xffff, mode & 0xffff);
+        let mode = m_chgs.local_mode_for_obj(2, Some("customer"), Some("4712"));
+        assert_eq!(RecordLevelId::Error as u32, mode & 0xffff);
+        for invalid in ["{ trigger = \"object\", name = \"customer\" }", "[ 1 ]", "{ trigger ="] {
+            let ex = runtime_mode_change(invalid).unwrap_err();
+            assert_eq!(E_CFG_INV_RUNTIME_MODE_CHANGE, ex.id());
+        }
+    }
 }
@@ -32,10 +32,21 @@
 
 //! Output format descriptors.
 
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
 use crate::collections::MapWithDefault;
 use crate::record::{RecordLevelId, RecordTrigger};
 
+lazy_static! {
+    /// Built-in output format with layout pretty, used if a resource references an output format
+    /// named pretty, that is not defined in the configuration file
+    pub(crate) static ref PRETTY_OUTPUT_FORMAT: OutputFormatDesc =
+        OutputFormatDesc::new(PRETTY_FORMAT_NAME,
+                              vec!(RecordFormatDesc::new(RecordLevelId::All as u32,
+                                                         RecordTrigger::All as u32,
+                                                         RecordLayout::Pretty, "", None)));
+}
+
 /// An output format descriptor describes how log or trace records are formatted for a resource.
 /// An output format contains of a list of record format descriptors, since different
 /// formats can be used depending on the record level and/or the cause,
@@ -120,6 +131,45 @@ impl Debug for OutputFormatDesc {
     }
 }
 
+/// Layouts for log or trace records
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum RecordLayout {
+    // record consists of the items specified in the format
+    Plain,
+    // record is a JSON object with timestamp, level, thread, source location and message
+    Json,
+    // record consists of a line with timestamp, level and message followed by an indented
+    // block with thread, source location, observer value and scope, aligned by field name
+    Pretty
+}
+impl RecordLayout {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordLayout::Plain => write!(f, "{}", LAYOUT_PLAIN),
+            RecordLayout::Json => write!(f, "{}", LAYOUT_JSON),
+            RecordLayout::Pretty => write!(f, "{}", LAYOUT_PRETTY)
+        }
+    }
+}
+impl Debug for RecordLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for RecordLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for RecordLayout {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            LAYOUT_PLAIN => Ok(RecordLayout::Plain),
+            LAYOUT_JSON => Ok(RecordLayout::Json),
+            LAYOUT_PRETTY => Ok(RecordLayout::Pretty),
+            _ => Err(false)
+        }
+    }
+}
+
 /// A record format descriptor specifies the fields of a log or trace message in the output.
 /// The components of a log or trace record are converted to a string according to this format.
 #[derive(Clone)]
@@ -128,6 +178,8 @@ pub struct RecordFormatDesc {
     levels: u32,
     // bit mask of all record triggers, for which the format is defined
     triggers: u32,
+    // record layout
+    layout: RecordLayout,
     // name of custom date time format
     date_time_format_name: Option<String>,
     // list of fields that form the record format
@@ -139,13 +191,16 @@ impl RecordFormatDesc {
     /// # Arguments
     /// * `levels` - the bit mask of all record levels valid for the format
     /// * `triggers` - the bit mask of all record triggers valid for the format
+    /// * `layout` - the record layout
     /// * `items` - the format string with the specification of all fields in the format
+    ///   (ignored for layouts JSON and pretty)
     /// * `date_time_format_name` - the optional name of the date-time format to use
-    pub fn new(levels: u32, triggers: u32, items: &str,
+    pub fn new(levels: u32, triggers: u32, layout: RecordLayout, items: &str,
                date_time_format_name: Option<String>) -> RecordFormatDesc {
         RecordFormatDesc {
             levels,
             triggers,
+            layout,
             items: items.to_string(),
             date_time_format_name
         }
@@ -156,6 +211,7 @@ impl RecordFormatDesc {
         RecordFormatDesc {
             levels: RecordLevelId::All as u32,
             triggers: RecordTrigger::Message as u32,
+            layout: RecordLayout::Plain,
             items: DEFAULT_ITEMS_MESSAGE.to_string(),
             date_time_format_name: None
         }
@@ -166,6 +222,7 @@ impl RecordFormatDesc {
         RecordFormatDesc {
             levels: RecordLevelId::Object as u32,
             triggers: RecordTrigger::ObserverCreated as u32,
+            layout: RecordLayout::Plain,
             items: DEFAULT_ITEMS_OBJ_CREATED.to_string(),
             date_time_format_name: None
         }
@@ -176,6 +233,7 @@ impl RecordFormatDesc {
         RecordFormatDesc {
             levels: RecordLevelId::Object as u32,
             triggers: RecordTrigger::ObserverDropped as u32,
+            layout: RecordLayout::Plain,
             items: DEFAULT_ITEMS_OBJ_DROPPED.to_string(),
             date_time_format_name: None
         }
@@ -186,6 +244,7 @@ impl RecordFormatDesc {
         RecordFormatDesc {
             levels: RecordLevelId::Units as u32,
             triggers: RecordTrigger::ObserverCreated as u32,
+            layout: RecordLayout::Plain,
             items: DEFAULT_ITEMS_UNIT_ENTERED.to_string(),
             date_time_format_name: None
         }
@@ -196,6 +255,7 @@ impl RecordFormatDesc {
         RecordFormatDesc {
             levels: RecordLevelId::Units as u32,
             triggers: RecordTrigger::ObserverDropped as u32,
+            layout: RecordLayout::Plain,
             items: DEFAULT_ITEMS_UNIT_LEFT.to_string(),
             date_time_format_name: None
         }
@@ -209,6 +269,10 @@ impl RecordFormatDesc {
     #[inline]
     pub fn triggers(&self) -> u32 { self.triggers }
 
+    /// Returns the record layout
+    #[inline]
+    pub fn layout(&self) -> RecordLayout { self.layout }
+
     /// Returns the format string with the specification of all fields in the format
     #[inline]
     pub fn items(&self) -> &String { &self.items }
@@ -228,6 +292,7 @@ impl Default for RecordFormatDesc {
         Self {
             levels: RecordLevelId::All as u32,
             triggers: RecordTrigger::All as u32,
+            layout: RecordLayout::Plain,
             items: DEFAULT_ITEMS.to_string(),
             date_time_format_name: None
         }
@@ -236,22 +301,155 @@ impl Default for RecordFormatDesc {
 impl Debug for RecordFormatDesc {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if self.date_time_format_name.is_none() {
-            return write!(f, "L:{:b}/T:{:b}/I:{}/DF:-", self.levels, self.triggers, self.items)
+            return write!(f, "L:{:b}/T:{:b}/LA:{}/I:{}/DF:-", self.levels, self.triggers,
+                          self.layout, self.items)
         }
-        write!(f, "L:{:b}/T:{:b}/I:{}/DF:{}", self.levels, self.triggers, self.items,
-               self.date_time_format_name.as_ref().unwrap())
+        write!(f, "L:{:b}/T:{:b}/LA:{}/I:{}/DF:{}", self.levels, self.triggers, self.layout,
+               self.items, self.date_time_format_name.as_ref().unwrap())
     }
 }
 
 /// Map with output format descriptors
 pub(crate) type OutputFormatDescMap = MapWithDefault<OutputFormatDesc>;
 
+/// Modes for colored output to console resources
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum ColorMode {
+    // colors are used, if the console is a terminal and environment variable NO_COLOR is not set
+    Auto,
+    // colors are always used, even if output is redirected to a file or pipe
+    Always,
+    // colors are never used
+    Never
+}
+impl ColorMode {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorMode::Auto => write!(f, "{}", COLOR_MODE_AUTO),
+            ColorMode::Always => write!(f, "{}", COLOR_MODE_ALWAYS),
+            ColorMode::Never => write!(f, "{}", COLOR_MODE_NEVER)
+        }
+    }
+}
+impl Debug for ColorMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for ColorMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for ColorMode {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            COLOR_MODE_AUTO => Ok(ColorMode::Auto),
+            COLOR_MODE_ALWAYS => Ok(ColorMode::Always),
+            COLOR_MODE_NEVER => Ok(ColorMode::Never),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Color settings for console resources stdout and stderr, specified under TOML table
+/// formats.colors in the custom configuration file.
+#[derive (Clone)]
+pub(crate) struct ConsoleColorsDesc {
+    // the color mode
+    mode: ColorMode,
+    // record levels and associated ANSI color codes, most specific levels first
+    level_colors: Vec<(u32, u8)>
+}
+impl ConsoleColorsDesc {
+    /// Creates a color descriptor.
+    ///
+    /// # Arguments
+    /// * `mode` - the color mode
+    /// * `level_colors` - record level bit masks and ANSI color codes, empty for built-in scheme
+    pub(crate) fn new(mode: ColorMode, mut level_colors: Vec<(u32, u8)>) -> ConsoleColorsDesc {
+        level_colors.sort_by_key(|(lvls, _)| lvls.count_ones());
+        ConsoleColorsDesc { mode, level_colors }
+    }
+
+    /// Returns the record levels and associated ANSI color codes to use for a console resource,
+    /// **None** if the records shall not be colored.
+    ///
+    /// # Arguments
+    /// * `is_terminal` - indicates whether the console is a terminal
+    pub(crate) fn level_colors(&self, is_terminal: bool) -> Option<Vec<(u32, u8)>> {
+        let use_colors = match self.mode {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                is_terminal && std::env::var_os(NO_COLOR_ENV_VAR).is_none_or(|v| v.is_empty())
+            }
+        };
+        if ! use_colors { return None }
+        if self.level_colors.is_empty() { return Some(DEFAULT_LEVEL_COLORS.to_vec()) }
+        Some(self.level_colors.clone())
+    }
+}
+impl Debug for ConsoleColorsDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "M:{:?}", self.mode)?;
+        for (lvls, code) in &self.level_colors { write!(f, "/{:b}:{}", lvls, code)?; }
+        Ok(())
+    }
+}
+
+/// Returns the ANSI color code for the specified color name.
+///
+/// # Arguments
+/// * `name` - the color name
+///
+/// # Return values
+/// the ANSI code for the foreground color, **None** if the name is unknown
+pub(crate) fn color_code(name: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+    let index = COLOR_NAMES.iter().position(|c| *c == name)? as u8;
+    // basic colors use codes 30 to 37, bright colors 90 to 97
+    if index < 8 { Some(30 + index) } else { Some(82 + index) }
+}
+
 /// List with specific output format descriptors
 pub(crate) type RecordFormatDescList = Vec<RecordFormatDesc>;
 
+// Record layout names
+const LAYOUT_PLAIN: &str = "plain";
+const LAYOUT_JSON: &str = "json";
+const LAYOUT_PRETTY: &str = "pretty";
+
+// Color mode names
+const COLOR_MODE_AUTO: &str = "auto";
+const COLOR_MODE_ALWAYS: &str = "always";
+const COLOR_MODE_NEVER: &str = "never";
+
+// Names of the console colors, basic colors followed by the bright variants, each ordered by
+// ANSI color code
+pub(crate) const COLOR_NAMES: &[&str] = &["black", "red", "green", "yellow",
+                                          "blue", "magenta", "cyan", "white",
+                                          "bright_black", "bright_red", "bright_green",
+                                          "bright_yellow", "bright_blue", "bright_magenta",
+                                          "bright_cyan", "bright_white"];
+
+// Environment variable disabling colors in color mode auto, if set to a non-empty value
+const NO_COLOR_ENV_VAR: &str = "NO_COLOR";
+
+// Built-in color scheme, used if no level specific colors are configured
+const DEFAULT_LEVEL_COLORS: &[(u32, u8)] = &[(RecordLevelId::Emergency as u32, 91),
+                                             (RecordLevelId::Alert as u32, 91),
+                                             (RecordLevelId::Critical as u32, 91),
+                                             (RecordLevelId::Error as u32, 31),
+                                             (RecordLevelId::Warning as u32, 33),
+                                             (RecordLevelId::Notice as u32, 36),
+                                             (RecordLevelId::Debug as u32, 90),
+                                             (RecordLevelId::Traces as u32, 34)];
+
 // System default name for output formats
 const DEFAULT_FORMAT_NAME: &str = "default";
 
+// Name of built-in output format with layout pretty
+pub(crate) const PRETTY_FORMAT_NAME: &str = "pretty";
+
 // Default record format string for "plain" trace and log messages
 const DEFAULT_ITEMS_MESSAGE: &str = "$TimeStamp|$LevelId|$SourceFileName:$SourceLineNr|$Message";
 
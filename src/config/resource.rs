@@ -35,6 +35,8 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
 use crate::collections::VecWithDefault;
+use crate::config::filter::RecordFilterDesc;
+use crate::policies::{SamplingPolicy, ThrottlePolicy};
 use crate::record::RecordLevelId;
 
 /// Default output file name
@@ -46,29 +48,87 @@ pub enum ResourceKind {
     // normal file
     PlainFile,
     // memory mapped file
+    #[cfg(feature="mmfile")]
     MemoryMappedFile,
     // standard output device (usually console)
     StdOut,
     // standard error device (usually console)
     StdErr,
+    // named pipe (POSIX FIFO or Windows named pipe)
+    #[cfg(feature="os-resources")]
+    Pipe,
+    // desktop notification
+    #[cfg(feature="os-resources")]
+    Notification,
+    // Windows event log
+    #[cfg(all(feature="os-resources", windows))]
+    WindowsEventLog,
+    // systemd journal
+    #[cfg(all(feature="os-resources", target_os="linux"))]
+    Journald,
     // syslog (Unix) or Event Logger (Windows)
     #[cfg(feature="net")]
     Syslog,
     // connection to remote trace server
     #[cfg(feature="net")]
-    Network
+    Network,
+    // webhook receiving alerts by HTTP POST
+    #[cfg(feature="net")]
+    Webhook,
+    // Graylog server receiving records in GELF format
+    #[cfg(feature="net")]
+    Gelf,
+    // HTTP endpoint receiving batches of records
+    #[cfg(feature="net")]
+    Http
 }
 impl ResourceKind {
+    /// Indicates whether resources of this kind are plain or memory mapped files
+    pub fn is_file(&self) -> bool {
+        match self {
+            ResourceKind::PlainFile => true,
+            #[cfg(feature="mmfile")]
+            ResourceKind::MemoryMappedFile => true,
+            _ => false
+        }
+    }
+
+    /// Indicates whether resources of this kind support a minimum interval between two outputs
+    pub fn is_rate_limited(&self) -> bool {
+        match self {
+            #[cfg(feature="os-resources")]
+            ResourceKind::Notification => true,
+            #[cfg(feature="net")]
+            ResourceKind::Webhook => true,
+            _ => false
+        }
+    }
+
     fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ResourceKind::PlainFile => write!(f, "{}", RES_KIND_FILE),
+            #[cfg(feature="mmfile")]
             ResourceKind::MemoryMappedFile => write!(f, "{}", RES_KIND_MM_FILE),
             ResourceKind::StdOut => write!(f, "{}", RES_KIND_STDOUT),
             ResourceKind::StdErr => write!(f, "{}", RES_KIND_STDERR),
+            #[cfg(feature="os-resources")]
+            ResourceKind::Pipe => write!(f, "{}", RES_KIND_PIPE),
+            #[cfg(feature="os-resources")]
+            ResourceKind::Notification => write!(f, "{}", RES_KIND_NOTIFICATION),
+            #[cfg(all(feature="os-resources", windows))]
+            ResourceKind::WindowsEventLog => write!(f, "{}", RES_KIND_EVENTLOG),
+            #[cfg(all(feature="os-resources", target_os="linux"))]
+            ResourceKind::Journald => write!(f, "{}", RES_KIND_JOURNAL),
             #[cfg(feature="net")]
             ResourceKind::Syslog => write!(f, "{}", RES_KIND_SYSLOG),
             #[cfg(feature="net")]
-            ResourceKind::Network => write!(f, "{}", RES_KIND_NETWORK)
+            ResourceKind::Network => write!(f, "{}", RES_KIND_NETWORK),
+            #[cfg(feature="net")]
+            ResourceKind::Webhook => write!(f, "{}", RES_KIND_WEBHOOK),
+            #[cfg(feature="net")]
+            ResourceKind::Gelf => write!(f, "{}", RES_KIND_GELF),
+            #[cfg(feature="net")]
+            ResourceKind::Http => write!(f, "{}", RES_KIND_HTTP)
         }
     }
 }
@@ -84,13 +144,468 @@ impl FromStr for ResourceKind {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             RES_KIND_FILE => Ok(ResourceKind::PlainFile),
+            #[cfg(feature="mmfile")]
             RES_KIND_MM_FILE => Ok(ResourceKind::MemoryMappedFile),
             RES_KIND_STDOUT => Ok(ResourceKind::StdOut),
             RES_KIND_STDERR => Ok(ResourceKind::StdErr),
+            #[cfg(feature="os-resources")]
+            RES_KIND_PIPE => Ok(ResourceKind::Pipe),
+            #[cfg(feature="os-resources")]
+            RES_KIND_NOTIFICATION => Ok(ResourceKind::Notification),
+            #[cfg(all(feature="os-resources", windows))]
+            RES_KIND_EVENTLOG => Ok(ResourceKind::WindowsEventLog),
+            #[cfg(all(feature="os-resources", target_os="linux"))]
+            RES_KIND_JOURNAL => Ok(ResourceKind::Journald),
             #[cfg(feature="net")]
             RES_KIND_SYSLOG => Ok(ResourceKind::Syslog),
             #[cfg(feature="net")]
             RES_KIND_NETWORK => Ok(ResourceKind::Network),
+            #[cfg(feature="net")]
+            RES_KIND_WEBHOOK => Ok(ResourceKind::Webhook),
+            #[cfg(feature="net")]
+            RES_KIND_GELF => Ok(ResourceKind::Gelf),
+            #[cfg(feature="net")]
+            RES_KIND_HTTP => Ok(ResourceKind::Http),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Character encodings for output files
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum CharEncoding {
+    // UTF-8, no transcoding needed
+    Utf8,
+    // ISO-8859-1, characters not representable are replaced by question marks
+    Latin1,
+    // UTF-16 little endian, with byte order mark at the start of the file
+    Utf16Le
+}
+impl CharEncoding {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CharEncoding::Utf8 => write!(f, "{}", ENCODING_UTF8),
+            CharEncoding::Latin1 => write!(f, "{}", ENCODING_LATIN1),
+            CharEncoding::Utf16Le => write!(f, "{}", ENCODING_UTF16LE)
+        }
+    }
+}
+impl Debug for CharEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for CharEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for CharEncoding {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            ENCODING_UTF8 | "utf8" => Ok(CharEncoding::Utf8),
+            ENCODING_LATIN1 | "latin1" | "iso-8859-1" => Ok(CharEncoding::Latin1),
+            ENCODING_UTF16LE | "utf16le" => Ok(CharEncoding::Utf16Le),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Payload formats for webhook resources
+#[cfg(feature="net")]
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum WebhookPayload {
+    // generic JSON object with record and context
+    Generic,
+    // Slack compatible JSON object with text only
+    Slack
+}
+#[cfg(feature="net")]
+impl WebhookPayload {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookPayload::Generic => write!(f, "{}", PAYLOAD_GENERIC),
+            WebhookPayload::Slack => write!(f, "{}", PAYLOAD_SLACK)
+        }
+    }
+}
+#[cfg(feature="net")]
+impl Debug for WebhookPayload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+#[cfg(feature="net")]
+impl Display for WebhookPayload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+#[cfg(feature="net")]
+impl FromStr for WebhookPayload {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            PAYLOAD_GENERIC => Ok(WebhookPayload::Generic),
+            PAYLOAD_SLACK => Ok(WebhookPayload::Slack),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Payload formats for HTTP batch resources
+#[cfg(feature="net")]
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum BatchFormat {
+    // formatted records separated by newlines (JSON lines, if the output format uses layout json)
+    JsonLines,
+    // JSON object for the Loki push API
+    Loki
+}
+#[cfg(feature="net")]
+impl BatchFormat {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchFormat::JsonLines => write!(f, "{}", BATCH_FORMAT_JSONL),
+            BatchFormat::Loki => write!(f, "{}", BATCH_FORMAT_LOKI)
+        }
+    }
+}
+#[cfg(feature="net")]
+impl Debug for BatchFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+#[cfg(feature="net")]
+impl Display for BatchFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+#[cfg(feature="net")]
+impl FromStr for BatchFormat {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            BATCH_FORMAT_JSONL => Ok(BatchFormat::JsonLines),
+            BATCH_FORMAT_LOKI => Ok(BatchFormat::Loki),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Verification of the server certificate by network resources using TLS
+#[cfg(feature="net")]
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum TlsVerification {
+    // certificate chain and host name are verified
+    Full,
+    // certificate chain is verified, host name is not checked
+    Chain,
+    // any server certificate is accepted, for testing purposes only
+    None
+}
+#[cfg(feature="net")]
+impl TlsVerification {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsVerification::Full => write!(f, "{}", TLS_VERIFY_FULL),
+            TlsVerification::Chain => write!(f, "{}", TLS_VERIFY_CHAIN),
+            TlsVerification::None => write!(f, "{}", TLS_VERIFY_NONE)
+        }
+    }
+}
+#[cfg(feature="net")]
+impl Debug for TlsVerification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+#[cfg(feature="net")]
+impl Display for TlsVerification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+#[cfg(feature="net")]
+impl FromStr for TlsVerification {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            TLS_VERIFY_FULL => Ok(TlsVerification::Full),
+            TLS_VERIFY_CHAIN => Ok(TlsVerification::Chain),
+            TLS_VERIFY_NONE => Ok(TlsVerification::None),
+            _ => Err(false)
+        }
+    }
+}
+
+/// TLS settings of a network resource.
+#[cfg(feature="net")]
+#[derive (Clone)]
+pub struct TlsSettings {
+    // optional PEM file with the certificates of trusted CAs, system certificates if omitted
+    ca_file: Option<String>,
+    // optional PEM file with the client certificate chain for mutual authentication
+    cert_file: Option<String>,
+    // optional PEM file with the client's private key for mutual authentication
+    key_file: Option<String>,
+    // optional name to verify the server certificate against, host from URL if omitted
+    server_name: Option<String>,
+    // verification of the server certificate
+    verification: TlsVerification
+}
+#[cfg(feature="net")]
+impl TlsSettings {
+    /// Returns the optional name of the file with the certificates of trusted CAs
+    pub fn ca_file(&self) -> &Option<String> { &self.ca_file }
+
+    /// Sets the name of the file with the certificates of trusted CAs
+    pub fn set_ca_file(&mut self, file_name: &str) { self.ca_file = Some(file_name.to_string()) }
+
+    /// Returns the optional name of the file with the client certificate chain
+    pub fn cert_file(&self) -> &Option<String> { &self.cert_file }
+
+    /// Sets the name of the file with the client certificate chain
+    pub fn set_cert_file(&mut self, file_name: &str) {
+        self.cert_file = Some(file_name.to_string())
+    }
+
+    /// Returns the optional name of the file with the client's private key
+    pub fn key_file(&self) -> &Option<String> { &self.key_file }
+
+    /// Sets the name of the file with the client's private key
+    pub fn set_key_file(&mut self, file_name: &str) { self.key_file = Some(file_name.to_string()) }
+
+    /// Returns the optional name to verify the server certificate against
+    pub fn server_name(&self) -> &Option<String> { &self.server_name }
+
+    /// Sets the name to verify the server certificate against
+    pub fn set_server_name(&mut self, name: &str) { self.server_name = Some(name.to_string()) }
+
+    /// Returns the verification of the server certificate
+    pub fn verification(&self) -> TlsVerification { self.verification }
+
+    /// Sets the verification of the server certificate
+    pub fn set_verification(&mut self, verification: TlsVerification) {
+        self.verification = verification
+    }
+
+    /// Indicates whether all settings have their default values
+    pub fn is_default(&self) -> bool {
+        self.ca_file.is_none() && self.cert_file.is_none() && self.key_file.is_none() &&
+        self.server_name.is_none() && self.verification == TlsVerification::Full
+    }
+}
+#[cfg(feature="net")]
+impl Default for TlsSettings {
+    fn default() -> Self {
+        TlsSettings {
+            ca_file: None,
+            cert_file: None,
+            key_file: None,
+            server_name: None,
+            verification: TlsVerification::Full
+        }
+    }
+}
+#[cfg(feature="net")]
+impl Debug for TlsSettings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let dash = String::from("-");
+        write!(f, "CA:{}/CRT:{}/KEY:{}/SN:{}/V:{}", self.ca_file.as_ref().unwrap_or(&dash),
+               self.cert_file.as_ref().unwrap_or(&dash), self.key_file.as_ref().unwrap_or(&dash),
+               self.server_name.as_ref().unwrap_or(&dash), self.verification)
+    }
+}
+
+/// Behaviour if the output file of a resource can't be created upon application start
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum CreateErrorPolicy {
+    // report a warning and ignore the resource, plain files are created upon first write
+    Warn,
+    // reject the configuration and use the default configuration instead
+    Fail,
+    // discard records and try again periodically
+    Retry,
+    // create the file in the fallback directory
+    Fallback
+}
+impl CreateErrorPolicy {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CreateErrorPolicy::Warn => write!(f, "{}", CREATE_ERROR_WARN),
+            CreateErrorPolicy::Fail => write!(f, "{}", CREATE_ERROR_FAIL),
+            CreateErrorPolicy::Retry => write!(f, "{}", CREATE_ERROR_RETRY),
+            CreateErrorPolicy::Fallback => write!(f, "{}", CREATE_ERROR_FALLBACK)
+        }
+    }
+}
+impl Debug for CreateErrorPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for CreateErrorPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for CreateErrorPolicy {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            CREATE_ERROR_WARN => Ok(CreateErrorPolicy::Warn),
+            CREATE_ERROR_FAIL => Ok(CreateErrorPolicy::Fail),
+            CREATE_ERROR_RETRY => Ok(CreateErrorPolicy::Retry),
+            CREATE_ERROR_FALLBACK => Ok(CreateErrorPolicy::Fallback),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Precision of the timestamps written to a resource.
+/// With a granularity coarser than the record timestamps, formatted timestamp values are
+/// truncated and reused for all records within the same second resp. millisecond.
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum ClockGranularity {
+    // whole seconds
+    Second,
+    // milliseconds
+    Millisecond,
+    // full precision of the record timestamps
+    Full
+}
+impl ClockGranularity {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClockGranularity::Second => write!(f, "{}", CLOCK_GRANULARITY_SECOND),
+            ClockGranularity::Millisecond => write!(f, "{}", CLOCK_GRANULARITY_MILLISECOND),
+            ClockGranularity::Full => write!(f, "{}", CLOCK_GRANULARITY_FULL)
+        }
+    }
+}
+impl Debug for ClockGranularity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for ClockGranularity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for ClockGranularity {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            CLOCK_GRANULARITY_SECOND => Ok(ClockGranularity::Second),
+            CLOCK_GRANULARITY_MILLISECOND => Ok(ClockGranularity::Millisecond),
+            CLOCK_GRANULARITY_FULL => Ok(ClockGranularity::Full),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Domain of the timestamps written to a memory mapped file.
+/// With domain ticks, records hold a monotonic clock reading, i.e. the offset to a calibration
+/// timestamp stored in the file header, instead of formatted date and time values. The offsets are converted to wall clock
+/// time when the file is read.
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum TimestampDomain {
+    // formatted wall clock date and time
+    WallClock,
+    // nanoseconds relative to the calibration timestamp in the file header
+    Ticks
+}
+impl TimestampDomain {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimestampDomain::WallClock => write!(f, "{}", TIMESTAMP_DOMAIN_WALLCLOCK),
+            TimestampDomain::Ticks => write!(f, "{}", TIMESTAMP_DOMAIN_TICKS)
+        }
+    }
+}
+impl Debug for TimestampDomain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for TimestampDomain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for TimestampDomain {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            TIMESTAMP_DOMAIN_WALLCLOCK => Ok(TimestampDomain::WallClock),
+            TIMESTAMP_DOMAIN_TICKS => Ok(TimestampDomain::Ticks),
+            _ => Err(false)
+        }
+    }
+}
+
+/// On-disk format of plain output files.
+/// With format binary, records are stored as length-prefixed serialized record data, the same
+/// representation used to transfer records to a log server. Such files are converted to text by
+/// tool coaly-cat.
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum FileFormat {
+    // records formatted according to the resource's output format
+    Text,
+    // length-prefixed serialized records
+    Binary
+}
+impl FileFormat {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileFormat::Text => write!(f, "{}", FILE_FORMAT_TEXT),
+            FileFormat::Binary => write!(f, "{}", FILE_FORMAT_BINARY)
+        }
+    }
+}
+impl Debug for FileFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for FileFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for FileFormat {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            FILE_FORMAT_TEXT => Ok(FileFormat::Text),
+            FILE_FORMAT_BINARY => Ok(FileFormat::Binary),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Coordination of several processes writing to the same plain output file.
+/// With modes append and lock, the file is opened in append mode and never truncated, records
+/// are written with a single write operation. Rollover is coordinated through a lock file, the
+/// first process detecting the rollover condition archives the file, all other processes
+/// continue with the new file.
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub enum FileSharing {
+    // file is written by a single process
+    Exclusive,
+    // records are appended atomically by the operating system
+    Append,
+    // every write is additionally protected by an advisory lock
+    Lock
+}
+impl FileSharing {
+    /// Indicates whether the file may be written by several processes.
+    #[inline]
+    pub fn is_shared(&self) -> bool { *self != FileSharing::Exclusive }
+
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileSharing::Exclusive => write!(f, "{}", FILE_SHARING_EXCLUSIVE),
+            FileSharing::Append => write!(f, "{}", FILE_SHARING_APPEND),
+            FileSharing::Lock => write!(f, "{}", FILE_SHARING_LOCK)
+        }
+    }
+}
+impl Debug for FileSharing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl Display for FileSharing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for FileSharing {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            FILE_SHARING_EXCLUSIVE => Ok(FileSharing::Exclusive),
+            FILE_SHARING_APPEND => Ok(FileSharing::Append),
+            FILE_SHARING_LOCK => Ok(FileSharing::Lock),
             _ => Err(false)
         }
     }
@@ -101,10 +616,28 @@ impl FromStr for ResourceKind {
 pub struct FileResourceDesc {
     // name of file or memory mapped file
     file_name_spec: String,
-    // file size in bytes, relevant for memory mapped file only
+    // file size in bytes, fix size for memory mapped files, maximum size for plain files
     file_size: usize,
     // optional rollover policy
-    rollover_policy_name: Option<String>
+    rollover_policy_name: Option<String>,
+    // character encoding, relevant for plain file only
+    encoding: CharEncoding,
+    // behaviour if the file can't be created upon application start
+    create_error_policy: CreateErrorPolicy,
+    // interval in seconds between attempts to create the file, for policy retry only
+    retry_interval: u64,
+    // optional interval in minutes for time slices in the file name
+    time_slice: Option<u32>,
+    // domain of the record timestamps, relevant for memory mapped files only
+    timestamp_domain: TimestampDomain,
+    // on-disk format, relevant for plain file only
+    file_format: FileFormat,
+    // indicates whether the file content is encrypted
+    encrypted: bool,
+    // indicates whether a memory mapped file wraps around instead of rolling over
+    ring: bool,
+    // coordination of several processes writing to the file, relevant for plain file only
+    sharing: FileSharing
 }
 impl FileResourceDesc {
     /// Creates a descriptor for the specific data of a file based output resource.
@@ -113,36 +646,162 @@ impl FileResourceDesc {
     ///
     /// # Arguments
     /// * `file_name_spec` - the file name specification, may contain variables
-    /// * `file_size` - file size in bytes, relevant for memory mapped file only
+    /// * `file_size` - fix size for memory mapped files, maximum size for plain files (0 for unlimited)
     /// * `rollover_policy_name` - the optional name of the rollover policy
+    /// * `encoding` - the character encoding, relevant for plain file only
     pub fn new(file_name_spec: &str, file_size: usize,
-               rollover_policy_name: Option<&String>) -> FileResourceDesc {
+               rollover_policy_name: Option<&String>,
+               encoding: CharEncoding) -> FileResourceDesc {
         FileResourceDesc {
             file_name_spec: file_name_spec.to_string(),
             file_size,
-            rollover_policy_name: rollover_policy_name.map(|n| n.to_string())
+            rollover_policy_name: rollover_policy_name.map(|n| n.to_string()),
+            encoding,
+            create_error_policy: CreateErrorPolicy::Warn,
+            retry_interval: DEF_CREATE_RETRY_INTERVAL as u64,
+            time_slice: None,
+            timestamp_domain: TimestampDomain::WallClock,
+            file_format: FileFormat::Text,
+            encrypted: false,
+            ring: false,
+            sharing: FileSharing::Exclusive
         }
     }
 
-    /// Returns the file name specification
+    /// Returns the file name specification
+    #[inline]
+    pub fn file_name_spec(&self) -> &String { &self.file_name_spec }
+
+    /// Returns the file size
+    #[inline]
+    pub fn file_size(&self) -> usize { self.file_size }
+
+    /// Sets the file size
+    ///
+    /// # Arguments
+    /// * `file_size` - the file size in bytes
+    #[inline]
+    pub fn set_file_size(&mut self, file_size: usize) { self.file_size = file_size }
+
+    /// Returns the optional rollover policy name
+    #[inline]
+    pub fn rollover_policy_name(&self) -> &Option<String> { &self.rollover_policy_name }
+
+    /// Returns the character encoding
+    #[inline]
+    pub fn encoding(&self) -> CharEncoding { self.encoding }
+
+    /// Returns the behaviour if the file can't be created upon application start
+    #[inline]
+    pub fn create_error_policy(&self) -> CreateErrorPolicy { self.create_error_policy }
+
+    /// Returns the interval in seconds between attempts to create the file
+    #[inline]
+    pub fn retry_interval(&self) -> u64 { self.retry_interval }
+
+    /// Sets the behaviour if the file can't be created upon application start
+    ///
+    /// # Arguments
+    /// * `policy` - the policy
+    /// * `retry_interval` - the interval in seconds between attempts to create the file
+    #[inline]
+    pub fn set_create_error_policy(&mut self, policy: CreateErrorPolicy, retry_interval: u64) {
+        self.create_error_policy = policy;
+        self.retry_interval = retry_interval;
+    }
+
+    /// Returns the optional interval in minutes for time slices in the file name
+    #[inline]
+    pub fn time_slice(&self) -> Option<u32> { self.time_slice }
+
+    /// Sets the interval for time slices in the file name
+    ///
+    /// # Arguments
+    /// * `interval` - the interval in minutes
+    #[inline]
+    pub fn set_time_slice(&mut self, interval: u32) { self.time_slice = Some(interval) }
+
+    /// Returns the domain of the record timestamps
+    #[cfg(feature="mmfile")]
+    #[inline]
+    pub fn timestamp_domain(&self) -> TimestampDomain { self.timestamp_domain }
+
+    /// Sets the domain of the record timestamps
+    ///
+    /// # Arguments
+    /// * `domain` - the timestamp domain
+    #[cfg(feature="mmfile")]
+    #[inline]
+    pub fn set_timestamp_domain(&mut self, domain: TimestampDomain) {
+        self.timestamp_domain = domain
+    }
+
+    /// Returns the on-disk format
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn file_format(&self) -> FileFormat { self.file_format }
+
+    /// Sets the on-disk format
+    ///
+    /// # Arguments
+    /// * `format` - the file format
+    #[inline]
+    pub fn set_file_format(&mut self, format: FileFormat) { self.file_format = format }
+
+    /// Indicates whether the file content is encrypted
+    #[cfg(feature="encryption")]
+    #[inline]
+    pub fn encrypted(&self) -> bool { self.encrypted }
+
+    /// Specifies whether the file content is encrypted
+    ///
+    /// # Arguments
+    /// * `encrypted` - **true** to encrypt the file content
+    #[inline]
+    pub fn set_encrypted(&mut self, encrypted: bool) { self.encrypted = encrypted }
+
+    /// Indicates whether a memory mapped file wraps around instead of rolling over
+    #[cfg(feature="mmfile")]
     #[inline]
-    pub fn file_name_spec(&self) -> &String { &self.file_name_spec }
+    pub fn ring(&self) -> bool { self.ring }
 
-    /// Returns the file size
+    /// Specifies whether a memory mapped file wraps around instead of rolling over
+    ///
+    /// # Arguments
+    /// * `ring` - **true** to keep the most recent records in a fixed size file
+    #[cfg(feature="mmfile")]
     #[inline]
-    pub fn file_size(&self) -> usize { self.file_size }
+    pub fn set_ring(&mut self, ring: bool) { self.ring = ring }
 
-    /// Returns the optional rollover policy name
+    /// Returns the coordination mode for several processes writing to the file
     #[inline]
-    pub fn rollover_policy_name(&self) -> &Option<String> { &self.rollover_policy_name }
+    pub fn sharing(&self) -> FileSharing { self.sharing }
+
+    /// Sets the coordination mode for several processes writing to the file
+    ///
+    /// # Arguments
+    /// * `sharing` - the sharing mode
+    #[inline]
+    pub fn set_sharing(&mut self, sharing: FileSharing) { self.sharing = sharing }
 }
 impl Debug for FileResourceDesc {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.rollover_policy_name.is_none() {
-            return write!(f, "N:{}/SZ:{}/RP:-", self.file_name_spec, self.file_size)
+        write!(f, "N:{}/SZ:{}/RP:{}/ENC:{}", self.file_name_spec, self.file_size,
+               self.rollover_policy_name.as_ref().unwrap_or(&String::from("-")), self.encoding)?;
+        if let Some(interval) = self.time_slice { write!(f, "/TS:{}", interval)?; }
+        if self.timestamp_domain != TimestampDomain::WallClock {
+            write!(f, "/TD:{}", self.timestamp_domain)?;
+        }
+        if self.file_format != FileFormat::Text { write!(f, "/FF:{}", self.file_format)?; }
+        if self.encrypted { write!(f, "/ECR")?; }
+        if self.ring { write!(f, "/RING")?; }
+        if self.sharing.is_shared() { write!(f, "/SHR:{}", self.sharing)?; }
+        match self.create_error_policy {
+            CreateErrorPolicy::Warn => Ok(()),
+            CreateErrorPolicy::Retry => write!(f, "/OCE:{}({})", self.create_error_policy,
+                                               self.retry_interval),
+            _ => write!(f, "/OCE:{}", self.create_error_policy)
         }
-        write!(f, "N:{}/SZ:{}/RP:{}", self.file_name_spec, self.file_size,
-               self.rollover_policy_name.as_ref().unwrap())
     }
 }
 
@@ -200,7 +859,19 @@ pub struct NetworkResourceDesc {
     // URL where to send the trace records to
     remote_url: String,
     // optional URL to use to bind local socket
-    local_url: Option<String>
+    local_url: Option<String>,
+    // indicates whether the record stream shall be compressed
+    stream_compression: bool,
+    // optional name of the spool file for guaranteed delivery
+    spool_file_name: Option<String>,
+    // optional name of the spill file for records written while disconnected
+    spill_file_name: Option<String>,
+    // delay before the first reconnect attempt after the connection was lost, in seconds
+    reconnect_delay: u64,
+    // maximum delay between two reconnect attempts, in seconds
+    max_reconnect_delay: u64,
+    // TLS settings, effective for remote URLs with protocol tls only
+    tls_settings: TlsSettings
 }
 #[cfg(feature="net")]
 impl NetworkResourceDesc {
@@ -209,10 +880,19 @@ impl NetworkResourceDesc {
     /// # Arguments
     /// * `remote_url` - the URL where to send the trace records to
     /// * `local_url` - the optional URL to use to bind local socket
-    pub fn new(remote_url: &str, local_url: Option<&String>) -> NetworkResourceDesc {
+    /// * `stream_compression` - indicates whether the record stream shall be compressed
+    pub fn new(remote_url: &str,
+               local_url: Option<&String>,
+               stream_compression: bool) -> NetworkResourceDesc {
         NetworkResourceDesc {
             remote_url: remote_url.to_string(),
-            local_url: local_url.map(|u| u.to_string())
+            local_url: local_url.map(|u| u.to_string()),
+            stream_compression,
+            spool_file_name: None,
+            spill_file_name: None,
+            reconnect_delay: DEF_RECONNECT_DELAY as u64,
+            max_reconnect_delay: DEF_MAX_RECONNECT_DELAY as u64,
+            tls_settings: TlsSettings::default()
         }
     }
 
@@ -221,14 +901,345 @@ impl NetworkResourceDesc {
 
     /// Returns the optional local URL
     pub fn local_url(&self) -> &Option<String> { &self.local_url }
+
+    /// Indicates whether the record stream shall be compressed
+    pub fn stream_compression(&self) -> bool { self.stream_compression }
+
+    /// Returns the optional spool file name
+    pub fn spool_file_name(&self) -> &Option<String> { &self.spool_file_name }
+
+    /// Sets the spool file name
+    ///
+    /// # Arguments
+    /// * `file_name` - the pure spool file name without path
+    pub fn set_spool_file_name(&mut self, file_name: &str) {
+        self.spool_file_name = Some(file_name.to_string())
+    }
+
+    /// Returns the optional spill file name
+    pub fn spill_file_name(&self) -> &Option<String> { &self.spill_file_name }
+
+    /// Sets the spill file name
+    ///
+    /// # Arguments
+    /// * `file_name` - the pure spill file name without path
+    pub fn set_spill_file_name(&mut self, file_name: &str) {
+        self.spill_file_name = Some(file_name.to_string())
+    }
+
+    /// Returns the delay before the first reconnect attempt, in seconds
+    pub fn reconnect_delay(&self) -> u64 { self.reconnect_delay }
+
+    /// Returns the maximum delay between two reconnect attempts, in seconds
+    pub fn max_reconnect_delay(&self) -> u64 { self.max_reconnect_delay }
+
+    /// Sets the delays for reconnect attempts after the connection was lost.
+    /// The delay doubles with every failed attempt, until the maximum is reached.
+    ///
+    /// # Arguments
+    /// * `delay` - the delay before the first attempt, in seconds
+    /// * `max_delay` - the maximum delay between two attempts, in seconds
+    pub fn set_reconnect_delays(&mut self, delay: u64, max_delay: u64) {
+        self.reconnect_delay = delay;
+        self.max_reconnect_delay = max_delay;
+    }
+
+    /// Returns the TLS settings
+    pub fn tls_settings(&self) -> &TlsSettings { &self.tls_settings }
+
+    /// Sets the TLS settings
+    ///
+    /// # Arguments
+    /// * `settings` - the TLS settings
+    pub fn set_tls_settings(&mut self, settings: TlsSettings) { self.tls_settings = settings }
 }
 #[cfg(feature="net")]
 impl Debug for NetworkResourceDesc {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.local_url.is_none() {
-            return write!(f, "R:{}/L:-", self.remote_url)
+        match &self.local_url {
+            Some(u) => write!(f, "R:{}/L:{}", self.remote_url, u)?,
+            None => write!(f, "R:{}/L:-", self.remote_url)?
+        }
+        if self.stream_compression { write!(f, "/C:{}", STREAM_COMPRESSION_DEFLATE)?; }
+        if let Some(n) = &self.spool_file_name { write!(f, "/SP:{}", n)?; }
+        if let Some(n) = &self.spill_file_name { write!(f, "/SL:{}", n)?; }
+        if self.reconnect_delay != DEF_RECONNECT_DELAY as u64 ||
+           self.max_reconnect_delay != DEF_MAX_RECONNECT_DELAY as u64 {
+            write!(f, "/RC:{}-{}", self.reconnect_delay, self.max_reconnect_delay)?;
+        }
+        if ! self.tls_settings.is_default() { write!(f, "/TLS:{{{:?}}}", self.tls_settings)?; }
+        Ok(())
+    }
+}
+
+/// Descriptor for the specific data of a webhook output resource.
+#[cfg(feature="net")]
+#[derive (Clone)]
+pub struct WebhookResourceDesc {
+    // HTTP URL where to post the alerts to
+    url: String,
+    // payload format
+    payload: WebhookPayload,
+    // minimum interval between two alerts, in seconds
+    min_interval: u64
+}
+#[cfg(feature="net")]
+impl WebhookResourceDesc {
+    /// Creates a descriptor for the specific data of a webhook output resource.
+    ///
+    /// # Arguments
+    /// * `url` - the HTTP URL where to post the alerts to
+    /// * `payload` - the payload format
+    /// * `min_interval` - the minimum interval between two alerts, in seconds
+    pub fn new(url: &str, payload: WebhookPayload, min_interval: u64) -> WebhookResourceDesc {
+        WebhookResourceDesc { url: url.to_string(), payload, min_interval }
+    }
+
+    /// Returns the HTTP URL where to post the alerts to
+    pub fn url(&self) -> &String { &self.url }
+
+    /// Returns the payload format
+    pub fn payload(&self) -> WebhookPayload { self.payload }
+
+    /// Returns the minimum interval between two alerts, in seconds
+    pub fn min_interval(&self) -> u64 { self.min_interval }
+}
+#[cfg(feature="net")]
+impl Debug for WebhookResourceDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "U:{}/P:{}/MI:{}", self.url, self.payload, self.min_interval)
+    }
+}
+
+/// Descriptor for the specific data of a GELF output resource.
+#[cfg(feature="net")]
+#[derive (Clone)]
+pub struct GelfResourceDesc {
+    // URL of the Graylog input, gelf+udp://host:port or gelf+tcp://host:port
+    url: String
+}
+#[cfg(feature="net")]
+impl GelfResourceDesc {
+    /// Creates a descriptor for the specific data of a GELF output resource.
+    ///
+    /// # Arguments
+    /// * `url` - the URL of the Graylog input
+    pub fn new(url: &str) -> GelfResourceDesc {
+        GelfResourceDesc { url: url.to_string() }
+    }
+
+    /// Returns the URL of the Graylog input
+    pub fn url(&self) -> &String { &self.url }
+}
+#[cfg(feature="net")]
+impl Debug for GelfResourceDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "U:{}", self.url)
+    }
+}
+
+/// Descriptor for the specific data of an HTTP batch output resource.
+#[cfg(feature="net")]
+#[derive (Clone)]
+pub struct HttpResourceDesc {
+    // HTTP or HTTPS URL where to post the batches to
+    url: String,
+    // payload format
+    format: BatchFormat,
+    // maximum number of records per batch
+    batch_size: usize,
+    // maximum time in seconds a record is held before its batch is posted
+    batch_interval: u64,
+    // maximum number of attempts to post a batch again after a failure
+    max_retries: u32,
+    // interval between two attempts to post a batch, in seconds
+    retry_interval: u64,
+    // TLS settings for HTTPS URLs
+    tls_settings: TlsSettings
+}
+#[cfg(feature="net")]
+impl HttpResourceDesc {
+    /// Creates a descriptor for the specific data of an HTTP batch output resource.
+    ///
+    /// # Arguments
+    /// * `url` - the HTTP or HTTPS URL where to post the batches to
+    /// * `format` - the payload format
+    /// * `batch_size` - the maximum number of records per batch
+    /// * `batch_interval` - the maximum time a record is held before its batch is posted,
+    ///   in seconds
+    pub fn new(url: &str,
+               format: BatchFormat,
+               batch_size: usize,
+               batch_interval: u64) -> HttpResourceDesc {
+        HttpResourceDesc {
+            url: url.to_string(),
+            format,
+            batch_size,
+            batch_interval,
+            max_retries: DEF_MAX_RETRIES as u32,
+            retry_interval: DEF_CREATE_RETRY_INTERVAL as u64,
+            tls_settings: TlsSettings::default()
         }
-        write!(f, "R:{}/L:{}", self.remote_url, self.local_url.as_ref().unwrap())
+    }
+
+    /// Returns the HTTP or HTTPS URL where to post the batches to
+    pub fn url(&self) -> &String { &self.url }
+
+    /// Returns the payload format
+    pub fn format(&self) -> BatchFormat { self.format }
+
+    /// Returns the maximum number of records per batch
+    pub fn batch_size(&self) -> usize { self.batch_size }
+
+    /// Returns the maximum time a record is held before its batch is posted, in seconds
+    pub fn batch_interval(&self) -> u64 { self.batch_interval }
+
+    /// Returns the maximum number of attempts to post a batch again after a failure
+    pub fn max_retries(&self) -> u32 { self.max_retries }
+
+    /// Returns the interval between two attempts to post a batch, in seconds
+    pub fn retry_interval(&self) -> u64 { self.retry_interval }
+
+    /// Sets the retry policy for batches that couldn't be posted.
+    ///
+    /// # Arguments
+    /// * `max_retries` - the maximum number of attempts to post a batch again
+    /// * `retry_interval` - the interval between two attempts, in seconds
+    pub fn set_retry_policy(&mut self, max_retries: u32, retry_interval: u64) {
+        self.max_retries = max_retries;
+        self.retry_interval = retry_interval;
+    }
+
+    /// Returns the TLS settings for HTTPS URLs
+    pub fn tls_settings(&self) -> &TlsSettings { &self.tls_settings }
+
+    /// Sets the TLS settings for HTTPS URLs.
+    ///
+    /// # Arguments
+    /// * `settings` - the TLS settings
+    pub fn set_tls_settings(&mut self, settings: TlsSettings) { self.tls_settings = settings }
+}
+#[cfg(feature="net")]
+impl Debug for HttpResourceDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "U:{}/BF:{}/BS:{}/BI:{}/MR:{}/RI:{}", self.url, self.format, self.batch_size,
+               self.batch_interval, self.max_retries, self.retry_interval)?;
+        if ! self.tls_settings.is_default() { write!(f, "/TLS:{{{:?}}}", self.tls_settings)?; }
+        Ok(())
+    }
+}
+
+/// Descriptor for the specific data of a named pipe output resource.
+#[cfg(feature="os-resources")]
+#[derive (Clone)]
+pub struct PipeResourceDesc {
+    // name of the pipe
+    pipe_name: String
+}
+#[cfg(feature="os-resources")]
+impl PipeResourceDesc {
+    /// Creates a descriptor for the specific data of a named pipe.
+    ///
+    /// # Arguments
+    /// * `pipe_name` - the name of the pipe
+    pub fn new(pipe_name: &str) -> PipeResourceDesc {
+        PipeResourceDesc { pipe_name: pipe_name.to_string() }
+    }
+
+    /// Returns the name of the pipe
+    pub fn pipe_name(&self) -> &String { &self.pipe_name }
+}
+#[cfg(feature="os-resources")]
+impl Debug for PipeResourceDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "N:{}", self.pipe_name)
+    }
+}
+
+/// Descriptor for the specific data of a desktop notification output resource.
+#[cfg(feature="os-resources")]
+#[derive (Clone)]
+pub struct NotificationResourceDesc {
+    // application name shown in the notification
+    app_name: String,
+    // minimum interval between two notifications, in seconds
+    min_interval: u64
+}
+#[cfg(feature="os-resources")]
+impl NotificationResourceDesc {
+    /// Creates a descriptor for the specific data of a desktop notification.
+    ///
+    /// # Arguments
+    /// * `app_name` - the application name shown in the notification
+    /// * `min_interval` - the minimum interval between two notifications, in seconds
+    pub fn new(app_name: &str, min_interval: u64) -> NotificationResourceDesc {
+        NotificationResourceDesc { app_name: app_name.to_string(), min_interval }
+    }
+
+    /// Returns the application name shown in the notification
+    pub fn app_name(&self) -> &String { &self.app_name }
+
+    /// Returns the minimum interval between two notifications, in seconds
+    pub fn min_interval(&self) -> u64 { self.min_interval }
+}
+#[cfg(feature="os-resources")]
+impl Debug for NotificationResourceDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "N:{}/MI:{}", self.app_name, self.min_interval)
+    }
+}
+
+/// Descriptor for the specific data of a Windows event log output resource.
+#[cfg(all(feature="os-resources", windows))]
+#[derive (Clone)]
+pub struct EventLogResourceDesc {
+    // event source name
+    source_name: String
+}
+#[cfg(all(feature="os-resources", windows))]
+impl EventLogResourceDesc {
+    /// Creates a descriptor for the specific data of a Windows event log.
+    ///
+    /// # Arguments
+    /// * `source_name` - the event source name
+    pub fn new(source_name: &str) -> EventLogResourceDesc {
+        EventLogResourceDesc { source_name: source_name.to_string() }
+    }
+
+    /// Returns the event source name
+    pub fn source_name(&self) -> &String { &self.source_name }
+}
+#[cfg(all(feature="os-resources", windows))]
+impl Debug for EventLogResourceDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "S:{}", self.source_name)
+    }
+}
+
+/// Descriptor for the specific data of a systemd journal output resource.
+#[cfg(all(feature="os-resources", target_os="linux"))]
+#[derive (Clone)]
+pub struct JournalResourceDesc {
+    // optional syslog identifier attached to all records
+    identifier: Option<String>
+}
+#[cfg(all(feature="os-resources", target_os="linux"))]
+impl JournalResourceDesc {
+    /// Creates a descriptor for the specific data of a systemd journal.
+    ///
+    /// # Arguments
+    /// * `identifier` - the optional syslog identifier attached to all records
+    pub fn new(identifier: Option<&String>) -> JournalResourceDesc {
+        JournalResourceDesc { identifier: identifier.map(|i| i.to_string()) }
+    }
+
+    /// Returns the optional syslog identifier attached to all records
+    pub fn identifier(&self) -> &Option<String> { &self.identifier }
+}
+#[cfg(all(feature="os-resources", target_os="linux"))]
+impl Debug for JournalResourceDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "I:{}", self.identifier.as_deref().unwrap_or("-"))
     }
 }
 
@@ -239,12 +1250,33 @@ pub enum SpecificResourceDesc {
     File(FileResourceDesc),
     /// StdOut and StdErr don't need specific data
     Console,
+    /// Data specific to named pipes
+    #[cfg(feature="os-resources")]
+    Pipe(PipeResourceDesc),
+    /// Data specific to desktop notifications
+    #[cfg(feature="os-resources")]
+    Notification(NotificationResourceDesc),
+    /// Data specific to Windows event log
+    #[cfg(all(feature="os-resources", windows))]
+    EventLog(EventLogResourceDesc),
+    /// Data specific to systemd journal
+    #[cfg(all(feature="os-resources", target_os="linux"))]
+    Journal(JournalResourceDesc),
     /// Data specific to syslog service
     #[cfg(feature="net")]
     Syslog(SyslogResourceDesc),
     /// Data specific to network resources
     #[cfg(feature="net")]
     Network(NetworkResourceDesc),
+    /// Data specific to webhook resources
+    #[cfg(feature="net")]
+    Webhook(WebhookResourceDesc),
+    /// Data specific to GELF resources
+    #[cfg(feature="net")]
+    Gelf(GelfResourceDesc),
+    /// Data specific to HTTP batch resources
+    #[cfg(feature="net")]
+    Http(HttpResourceDesc),
 }
 impl SpecificResourceDesc {
     /// Returns file specific data, if the resource is a file or memory mapped file.
@@ -255,6 +1287,42 @@ impl SpecificResourceDesc {
         }
     }
 
+    /// Returns pipe specific data, if the resource is a named pipe.
+    #[cfg(feature="os-resources")]
+    fn pipe_data(&self) -> Option<&PipeResourceDesc> {
+        match self {
+            SpecificResourceDesc::Pipe(d) => Some(d),
+            _ => None
+        }
+    }
+
+    /// Returns notification specific data, if the resource is a desktop notification.
+    #[cfg(feature="os-resources")]
+    fn notification_data(&self) -> Option<&NotificationResourceDesc> {
+        match self {
+            SpecificResourceDesc::Notification(d) => Some(d),
+            _ => None
+        }
+    }
+
+    /// Returns event log specific data, if the resource is the Windows event log.
+    #[cfg(all(feature="os-resources", windows))]
+    fn eventlog_data(&self) -> Option<&EventLogResourceDesc> {
+        match self {
+            SpecificResourceDesc::EventLog(d) => Some(d),
+            _ => None
+        }
+    }
+
+    /// Returns journal specific data, if the resource is the systemd journal.
+    #[cfg(all(feature="os-resources", target_os="linux"))]
+    fn journal_data(&self) -> Option<&JournalResourceDesc> {
+        match self {
+            SpecificResourceDesc::Journal(d) => Some(d),
+            _ => None
+        }
+    }
+
     /// Returns syslog specific data, if the resource is syslog service
     #[cfg(feature="net")]
     fn syslog_data(&self) -> Option<&SyslogResourceDesc> {
@@ -272,15 +1340,76 @@ impl SpecificResourceDesc {
             _ => None
         }
     }
+
+    /// Returns webhook specific data, if the resource is a webhook
+    #[cfg(feature="net")]
+    fn webhook_data(&self) -> Option<&WebhookResourceDesc> {
+        match self {
+            SpecificResourceDesc::Webhook(d) => Some(d),
+            _ => None
+        }
+    }
+
+    /// Returns GELF specific data, if the resource is a Graylog input
+    #[cfg(feature="net")]
+    fn gelf_data(&self) -> Option<&GelfResourceDesc> {
+        match self {
+            SpecificResourceDesc::Gelf(d) => Some(d),
+            _ => None
+        }
+    }
+
+    /// Returns HTTP batch specific data, if the resource is an HTTP endpoint
+    #[cfg(feature="net")]
+    fn http_data(&self) -> Option<&HttpResourceDesc> {
+        match self {
+            SpecificResourceDesc::Http(d) => Some(d),
+            _ => None
+        }
+    }
+
+    /// Returns the file name specification, pipe name or URL of the resource, if any
+    fn target(&self) -> Option<&String> {
+        match self {
+            SpecificResourceDesc::File(d) => Some(d.file_name_spec()),
+            #[cfg(feature="os-resources")]
+            SpecificResourceDesc::Pipe(d) => Some(d.pipe_name()),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Syslog(d) => Some(d.remote_url()),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Network(d) => Some(d.remote_url()),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Webhook(d) => Some(d.url()),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Gelf(d) => Some(d.url()),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Http(d) => Some(d.url()),
+            _ => None
+        }
+    }
 }
 impl Debug for SpecificResourceDesc {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             SpecificResourceDesc::File(d) => d.fmt(f),
+            #[cfg(feature="os-resources")]
+            SpecificResourceDesc::Pipe(d) => d.fmt(f),
+            #[cfg(feature="os-resources")]
+            SpecificResourceDesc::Notification(d) => d.fmt(f),
+            #[cfg(all(feature="os-resources", windows))]
+            SpecificResourceDesc::EventLog(d) => d.fmt(f),
+            #[cfg(all(feature="os-resources", target_os="linux"))]
+            SpecificResourceDesc::Journal(d) => d.fmt(f),
             #[cfg(feature="net")]
             SpecificResourceDesc::Syslog(d) => d.fmt(f),
             #[cfg(feature="net")]
             SpecificResourceDesc::Network(d) => d.fmt(f),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Webhook(d) => d.fmt(f),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Gelf(d) => d.fmt(f),
+            #[cfg(feature="net")]
+            SpecificResourceDesc::Http(d) => d.fmt(f),
             _ => Ok(())
         }
     }
@@ -299,6 +1428,23 @@ pub struct ResourceDesc {
     buffer_policy_name: Option<String>,
     // optional output format name
     output_format_name: Option<String>,
+    // output format names for specific record levels, most specific levels first
+    level_format_names: Vec<(u32, String)>,
+    // optional filter for records by source file or module path
+    filter: Option<RecordFilterDesc>,
+    // optional policy limiting the number of records written per interval
+    throttle: Option<ThrottlePolicy>,
+    // optional policy writing only a sample of the records
+    sampling: Option<SamplingPolicy>,
+    // priority for flushing the resource upon shutdown, resources with higher values are
+    // flushed first
+    flush_priority: u32,
+    // maximum time in milliseconds for flushing the resource upon shutdown, 0 if unlimited
+    flush_deadline: u64,
+    // precision of the timestamps written to the resource
+    clock_granularity: ClockGranularity,
+    // indicates whether the resource shall be tested upon startup
+    probe: bool,
     // resource specific data
     specific_data: SpecificResourceDesc
 }
@@ -312,19 +1458,29 @@ impl ResourceDesc {
     /// * `output_format_name` - the optional name of the output format to use
     /// * `file_name_spec` - the file name specification, may contain variables
     /// * `rollover_policy_name` - the optional name of the rollover policy
+    /// * `encoding` - the character encoding
     pub fn for_plain_file(scope: &[u32],
                           levels: u32,
                           buffer_policy_name: Option<&String>,
                           output_format_name: Option<&String>,
                           file_name_spec: &str,
-                          rollover_policy_name: Option<&String>) -> ResourceDesc {
-        let f = FileResourceDesc::new(file_name_spec, 0, rollover_policy_name);
+                          rollover_policy_name: Option<&String>,
+                          encoding: CharEncoding) -> ResourceDesc {
+        let f = FileResourceDesc::new(file_name_spec, 0, rollover_policy_name, encoding);
         ResourceDesc {
             scope: scope.to_vec(),
             kind: ResourceKind::PlainFile,
             levels,
             buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
             output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
             specific_data: SpecificResourceDesc::File(f)
         }
     }
@@ -338,19 +1494,29 @@ impl ResourceDesc {
     /// * `file_name_spec` - the file name specification, may contain variables
     /// * `file_size` - file size in bytes
     /// * `rollover_policy_name` - the optional name of the rollover policy
+    #[cfg(feature="mmfile")]
     pub fn for_mem_mapped_file(scope: &[u32],
                                levels: u32,
                                output_format_name: Option<&String>,
                                file_name_spec: &str,
                                file_size: usize,
                                rollover_policy_name: Option<&String>) -> ResourceDesc {
-        let f = FileResourceDesc::new(file_name_spec, file_size, rollover_policy_name);
+        let f = FileResourceDesc::new(file_name_spec, file_size, rollover_policy_name,
+                                      CharEncoding::Utf8);
         ResourceDesc {
             scope: scope.to_vec(),
             kind: ResourceKind::MemoryMappedFile,
             levels,
             buffer_policy_name: None,
             output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
             specific_data: SpecificResourceDesc::File(f)
         }
     }
@@ -366,15 +1532,148 @@ impl ResourceDesc {
     pub fn for_console(scope: &[u32],
                        kind: ResourceKind,
                        levels: u32,
-                       buffer_policy_name: Option<&String>,
-                       output_format_name: Option<&String>) -> ResourceDesc {
+                       buffer_policy_name: Option<&String>,
+                       output_format_name: Option<&String>) -> ResourceDesc {
+        ResourceDesc {
+            scope: scope.to_vec(),
+            kind,
+            levels,
+            buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
+            output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::Console
+        }
+    }
+
+    /// Creates a resource descriptor for a named pipe.
+    ///
+    /// # Arguments
+    /// * `scope` - the resource scope (application IDs)
+    /// * `levels` - the bit mask with all record levels to be written to the resource
+    /// * `buffer_policy_name` - the optional name of the buffer policy
+    /// * `output_format_name` - the optional name of the output format to use
+    /// * `pipe_name` - the name of the pipe
+    #[cfg(feature="os-resources")]
+    pub fn for_pipe(scope: &[u32],
+                    levels: u32,
+                    buffer_policy_name: Option<&String>,
+                    output_format_name: Option<&String>,
+                    pipe_name: &str) -> ResourceDesc {
+        ResourceDesc {
+            scope: scope.to_vec(),
+            kind: ResourceKind::Pipe,
+            levels,
+            buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
+            output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::Pipe(PipeResourceDesc::new(pipe_name))
+        }
+    }
+
+    /// Creates a resource descriptor for desktop notifications.
+    ///
+    /// # Arguments
+    /// * `scope` - the resource scope (application IDs)
+    /// * `levels` - the bit mask with all record levels to be written to the resource
+    /// * `output_format_name` - the optional name of the output format to use
+    /// * `app_name` - the application name shown in the notification
+    /// * `min_interval` - the minimum interval between two notifications, in seconds
+    #[cfg(feature="os-resources")]
+    pub fn for_notification(scope: &[u32],
+                            levels: u32,
+                            output_format_name: Option<&String>,
+                            app_name: &str,
+                            min_interval: u64) -> ResourceDesc {
+        let ndesc = NotificationResourceDesc::new(app_name, min_interval);
+        ResourceDesc {
+            scope: scope.to_vec(),
+            kind: ResourceKind::Notification,
+            levels,
+            buffer_policy_name: None,
+            output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::Notification(ndesc)
+        }
+    }
+
+    /// Creates a resource descriptor for the Windows event log.
+    ///
+    /// # Arguments
+    /// * `scope` - the resource scope (application IDs)
+    /// * `levels` - the bit mask with all record levels to be written to the resource
+    /// * `output_format_name` - the optional name of the output format to use
+    /// * `source_name` - the event source name
+    #[cfg(all(feature="os-resources", windows))]
+    pub fn for_eventlog(scope: &[u32],
+                        levels: u32,
+                        output_format_name: Option<&String>,
+                        source_name: &str) -> ResourceDesc {
+        ResourceDesc {
+            scope: scope.to_vec(),
+            kind: ResourceKind::WindowsEventLog,
+            levels,
+            buffer_policy_name: None,
+            output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::EventLog(EventLogResourceDesc::new(source_name))
+        }
+    }
+
+    /// Creates a resource descriptor for the systemd journal.
+    ///
+    /// # Arguments
+    /// * `scope` - the resource scope (application IDs)
+    /// * `levels` - the bit mask with all record levels to be written to the resource
+    /// * `output_format_name` - the optional name of the output format to use
+    /// * `identifier` - the optional syslog identifier attached to all records
+    #[cfg(all(feature="os-resources", target_os="linux"))]
+    pub fn for_journal(scope: &[u32],
+                       levels: u32,
+                       output_format_name: Option<&String>,
+                       identifier: Option<&String>) -> ResourceDesc {
         ResourceDesc {
             scope: scope.to_vec(),
-            kind,
+            kind: ResourceKind::Journald,
             levels,
-            buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
+            buffer_policy_name: None,
             output_format_name: output_format_name.map(|n| n.to_string()),
-            specific_data: SpecificResourceDesc::Console
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::Journal(JournalResourceDesc::new(identifier))
         }
     }
 
@@ -400,6 +1699,14 @@ impl ResourceDesc {
             levels,
             buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
             output_format_name: None,
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
             specific_data: SpecificResourceDesc::Syslog(spd)
         }
     }
@@ -412,23 +1719,141 @@ impl ResourceDesc {
     /// * `buffer_policy_name` - the optional name of the buffer policy
     /// * `remote_url` - the URL where to send the trace records to
     /// * `local_url` - the optional URL to use to bind local socket
+    /// * `stream_compression` - indicates whether the record stream shall be compressed
     #[cfg(feature="net")]
     pub fn for_network(scope: &[u32],
                        levels: u32,
                        buffer_policy_name: Option<&String>,
                        remote_url: &str,
-                       local_url: Option<&String>) -> ResourceDesc {
-        let spd = NetworkResourceDesc::new(remote_url, local_url);
+                       local_url: Option<&String>,
+                       stream_compression: bool) -> ResourceDesc {
+        let spd = NetworkResourceDesc::new(remote_url, local_url, stream_compression);
         ResourceDesc {
             scope: scope.to_vec(),
             kind: ResourceKind::Network,
             levels,
             buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
             output_format_name: None,
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
             specific_data: SpecificResourceDesc::Network(spd)
         }
     }
 
+    /// Creates a resource descriptor for a webhook resource.
+    ///
+    /// # Arguments
+    /// * `scope` - the resource scope (application IDs)
+    /// * `levels` - the bit mask with all record levels to be written to the resource
+    /// * `buffer_policy_name` - the optional name of the buffer policy
+    /// * `output_format_name` - the optional name of the output format to use
+    /// * `url` - the HTTP URL where to post the alerts to
+    /// * `payload` - the payload format
+    /// * `min_interval` - the minimum interval between two alerts, in seconds
+    #[cfg(feature="net")]
+    pub fn for_webhook(scope: &[u32],
+                       levels: u32,
+                       buffer_policy_name: Option<&String>,
+                       output_format_name: Option<&String>,
+                       url: &str,
+                       payload: WebhookPayload,
+                       min_interval: u64) -> ResourceDesc {
+        let spd = WebhookResourceDesc::new(url, payload, min_interval);
+        ResourceDesc {
+            scope: scope.to_vec(),
+            kind: ResourceKind::Webhook,
+            levels,
+            buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
+            output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::Webhook(spd)
+        }
+    }
+
+    /// Creates a resource descriptor for a GELF resource.
+    ///
+    /// # Arguments
+    /// * `scope` - the resource scope (application IDs)
+    /// * `levels` - the bit mask with all record levels to be written to the resource
+    /// * `output_format_name` - the optional name of the output format to use
+    /// * `url` - the URL of the Graylog input
+    #[cfg(feature="net")]
+    pub fn for_gelf(scope: &[u32],
+                    levels: u32,
+                    output_format_name: Option<&String>,
+                    url: &str) -> ResourceDesc {
+        ResourceDesc {
+            scope: scope.to_vec(),
+            kind: ResourceKind::Gelf,
+            levels,
+            buffer_policy_name: None,
+            output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::Gelf(GelfResourceDesc::new(url))
+        }
+    }
+
+    /// Creates a resource descriptor for an HTTP batch resource.
+    ///
+    /// # Arguments
+    /// * `scope` - the resource scope (application IDs)
+    /// * `levels` - the bit mask with all record levels to be written to the resource
+    /// * `buffer_policy_name` - the optional name of the buffer policy to use
+    /// * `output_format_name` - the optional name of the output format to use
+    /// * `url` - the HTTP or HTTPS URL where to post the batches to
+    /// * `format` - the payload format
+    /// * `batch_size` - the maximum number of records per batch
+    /// * `batch_interval` - the maximum time a record is held before its batch is posted,
+    ///   in seconds
+    #[cfg(feature="net")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn for_http(scope: &[u32],
+                    levels: u32,
+                    buffer_policy_name: Option<&String>,
+                    output_format_name: Option<&String>,
+                    url: &str,
+                    format: BatchFormat,
+                    batch_size: usize,
+                    batch_interval: u64) -> ResourceDesc {
+        let spd = HttpResourceDesc::new(url, format, batch_size, batch_interval);
+        ResourceDesc {
+            scope: scope.to_vec(),
+            kind: ResourceKind::Http,
+            levels,
+            buffer_policy_name: buffer_policy_name.map(|n| n.to_string()),
+            output_format_name: output_format_name.map(|n| n.to_string()),
+            level_format_names: Vec::new(),
+            filter: None,
+            throttle: None,
+            sampling: None,
+            flush_priority: 0,
+            probe: false,
+            flush_deadline: 0,
+            clock_granularity: ClockGranularity::Full,
+            specific_data: SpecificResourceDesc::Http(spd)
+        }
+    }
+
     /// Returns resource kind of this resource
     #[inline]
     pub fn kind(&self) -> &ResourceKind { &self.kind }
@@ -437,6 +1862,19 @@ impl ResourceDesc {
     #[inline]
     pub fn levels(&self) -> u32 { self.levels }
 
+    /// Returns the application IDs handled by this resource, 0 stands for any application ID
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn scope(&self) -> &[u32] { &self.scope }
+
+    /// Indicates whether this resource handles records from the specified application
+    ///
+    /// # Arguments
+    /// * `app_id` - the application ID
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn applies_to(&self, app_id: u32) -> bool { scope_contains(&self.scope, app_id) }
+
     /// Returns name of the buffer policy to use for this resource
     #[inline]
     pub fn buffer_policy_name(&self) -> &Option<String> { &self.buffer_policy_name }
@@ -445,10 +1883,300 @@ impl ResourceDesc {
     #[inline]
     pub fn output_format_name(&self) -> &Option<String> { &self.output_format_name }
 
+    /// Returns the output format names for specific record levels.
+    /// Every element contains the bit mask with the record levels and the output format name,
+    /// elements with the most specific levels come first.
+    #[inline]
+    pub fn level_format_names(&self) -> &Vec<(u32, String)> { &self.level_format_names }
+
+    /// Sets the output format names for specific record levels.
+    /// Elements are sorted, so that the most specific record levels come first.
+    ///
+    /// # Arguments
+    /// * `level_format_names` - bit masks with record levels and associated output format names
+    pub fn set_level_format_names(&mut self, mut level_format_names: Vec<(u32, String)>) {
+        level_format_names.sort_by_key(|(lvls, _)| (lvls.count_ones(), *lvls));
+        self.level_format_names = level_format_names;
+    }
+
+    /// Returns the filter for records by source file or module path
+    #[inline]
+    pub(crate) fn filter(&self) -> &Option<RecordFilterDesc> { &self.filter }
+
+    /// Sets the filter for records by source file or module path.
+    ///
+    /// # Arguments
+    /// * `filter` - the record filter
+    #[inline]
+    pub(crate) fn set_filter(&mut self, filter: RecordFilterDesc) { self.filter = Some(filter) }
+
+    /// Returns the policy limiting the number of records written per interval
+    #[inline]
+    pub(crate) fn throttle_policy(&self) -> &Option<ThrottlePolicy> { &self.throttle }
+
+    /// Sets the policy limiting the number of records written per interval.
+    ///
+    /// # Arguments
+    /// * `policy` - the throttle policy
+    #[inline]
+    pub(crate) fn set_throttle_policy(&mut self, policy: ThrottlePolicy) {
+        self.throttle = Some(policy)
+    }
+
+    /// Returns the policy writing only a sample of the records
+    #[inline]
+    pub(crate) fn sampling_policy(&self) -> &Option<SamplingPolicy> { &self.sampling }
+
+    /// Sets the policy writing only a sample of the records.
+    ///
+    /// # Arguments
+    /// * `policy` - the sampling policy
+    #[inline]
+    pub(crate) fn set_sampling_policy(&mut self, policy: SamplingPolicy) {
+        self.sampling = Some(policy)
+    }
+
+    /// Returns the priority for flushing the resource upon shutdown
+    #[inline]
+    pub(crate) fn flush_priority(&self) -> u32 { self.flush_priority }
+
+    /// Returns the maximum time in milliseconds for flushing the resource upon shutdown,
+    /// 0 if unlimited
+    #[inline]
+    pub(crate) fn flush_deadline(&self) -> u64 { self.flush_deadline }
+
+    /// Sets the priority and the maximum time for flushing the resource upon shutdown.
+    ///
+    /// # Arguments
+    /// * `priority` - the flush priority, resources with higher values are flushed first
+    /// * `deadline` - the maximum flush time in milliseconds, 0 for unlimited
+    #[inline]
+    pub(crate) fn set_flush_settings(&mut self, priority: u32, deadline: u64) {
+        self.flush_priority = priority;
+        self.flush_deadline = deadline;
+    }
+
+    /// Indicates whether the resource shall be tested upon startup
+    #[inline]
+    pub(crate) fn probe(&self) -> bool { self.probe }
+
+    /// Specifies whether the resource shall be tested upon startup.
+    ///
+    /// # Arguments
+    /// * `probe` - **true** to write and verify a test record resp. check connectivity
+    #[inline]
+    pub(crate) fn set_probe(&mut self, probe: bool) { self.probe = probe }
+
+    /// Returns the label identifying the resource in pipeline statistics.
+    /// The label consists of the resource kind followed by file name specification, pipe name
+    /// or URL, if the resource has one.
+    pub(crate) fn label(&self) -> String {
+        match self.specific_data.target() {
+            Some(t) => format!("{}:{}", self.kind, t),
+            None => self.kind.to_string()
+        }
+    }
+
+    /// Returns the precision of the timestamps written to the resource
+    #[inline]
+    pub(crate) fn clock_granularity(&self) -> ClockGranularity { self.clock_granularity }
+
+    /// Sets the precision of the timestamps written to the resource.
+    ///
+    /// # Arguments
+    /// * `granularity` - the clock granularity
+    #[inline]
+    pub(crate) fn set_clock_granularity(&mut self, granularity: ClockGranularity) {
+        self.clock_granularity = granularity;
+    }
+
     /// Returns file specific data, if the resource is a file or memory mapped file.
     #[inline]
     pub fn file_data(&self) -> Option<&FileResourceDesc> { self.specific_data.file_data() }
 
+    /// Sets the size of a file based resource.
+    /// For plain files, the size is the maximum file size before a rollover takes place.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `file_size` - the file size in bytes
+    pub fn set_file_size(&mut self, file_size: usize) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_file_size(file_size);
+        }
+    }
+
+    /// Sets the behaviour if the file of a file based resource can't be created upon application
+    /// start.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `policy` - the policy
+    /// * `retry_interval` - the interval in seconds between attempts to create the file
+    pub fn set_create_error_policy(&mut self, policy: CreateErrorPolicy, retry_interval: u64) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_create_error_policy(policy, retry_interval);
+        }
+    }
+
+    /// Sets the interval for time slices in the file name of a file based resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `interval` - the interval in minutes
+    pub fn set_time_slice(&mut self, interval: u32) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_time_slice(interval);
+        }
+    }
+
+    /// Sets the domain of the record timestamps of a file based resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `domain` - the timestamp domain
+    #[cfg(feature="mmfile")]
+    pub fn set_timestamp_domain(&mut self, domain: TimestampDomain) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_timestamp_domain(domain);
+        }
+    }
+
+    /// Sets the on-disk format of a file based resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `format` - the file format
+    pub fn set_file_format(&mut self, format: FileFormat) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_file_format(format);
+        }
+    }
+
+    /// Specifies whether the content of a file based resource is encrypted.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `encrypted` - **true** to encrypt the file content
+    pub fn set_encrypted(&mut self, encrypted: bool) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_encrypted(encrypted);
+        }
+    }
+
+    /// Specifies whether a memory mapped file resource wraps around instead of rolling over.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `ring` - **true** to keep the most recent records in a fixed size file
+    #[cfg(feature="mmfile")]
+    pub fn set_ring(&mut self, ring: bool) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_ring(ring);
+        }
+    }
+
+    /// Sets the coordination mode for several processes writing to a plain file resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `sharing` - the sharing mode
+    pub fn set_sharing(&mut self, sharing: FileSharing) {
+        if let SpecificResourceDesc::File(ref mut d) = self.specific_data {
+            d.set_sharing(sharing);
+        }
+    }
+
+    /// Sets the spool file name of a network resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `file_name` - the pure spool file name without path
+    #[cfg(feature="net")]
+    pub fn set_spool_file_name(&mut self, file_name: &str) {
+        if let SpecificResourceDesc::Network(ref mut d) = self.specific_data {
+            d.set_spool_file_name(file_name);
+        }
+    }
+
+    /// Sets the spill file name of a network resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `file_name` - the pure spill file name without path
+    #[cfg(feature="net")]
+    pub fn set_spill_file_name(&mut self, file_name: &str) {
+        if let SpecificResourceDesc::Network(ref mut d) = self.specific_data {
+            d.set_spill_file_name(file_name);
+        }
+    }
+
+    /// Sets the delays for reconnect attempts of a network resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `delay` - the delay before the first attempt, in seconds
+    /// * `max_delay` - the maximum delay between two attempts, in seconds
+    #[cfg(feature="net")]
+    pub fn set_reconnect_delays(&mut self, delay: u64, max_delay: u64) {
+        if let SpecificResourceDesc::Network(ref mut d) = self.specific_data {
+            d.set_reconnect_delays(delay, max_delay);
+        }
+    }
+
+    /// Sets the TLS settings of a network or HTTP batch resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `settings` - the TLS settings
+    #[cfg(feature="net")]
+    pub fn set_tls_settings(&mut self, settings: TlsSettings) {
+        match self.specific_data {
+            SpecificResourceDesc::Network(ref mut d) => d.set_tls_settings(settings),
+            SpecificResourceDesc::Http(ref mut d) => d.set_tls_settings(settings),
+            _ => ()
+        }
+    }
+
+    /// Sets the retry policy of an HTTP batch resource.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `max_retries` - the maximum number of attempts to post a batch again
+    /// * `retry_interval` - the interval between two attempts, in seconds
+    #[cfg(feature="net")]
+    pub fn set_retry_policy(&mut self, max_retries: u32, retry_interval: u64) {
+        if let SpecificResourceDesc::Http(ref mut d) = self.specific_data {
+            d.set_retry_policy(max_retries, retry_interval);
+        }
+    }
+
+    /// Returns pipe specific data, if the resource is a named pipe.
+    #[cfg(feature="os-resources")]
+    #[inline]
+    pub fn pipe_data(&self) -> Option<&PipeResourceDesc> { self.specific_data.pipe_data() }
+
+    /// Returns notification specific data, if the resource is a desktop notification.
+    #[cfg(feature="os-resources")]
+    #[inline]
+    pub fn notification_data(&self) -> Option<&NotificationResourceDesc> {
+        self.specific_data.notification_data()
+    }
+
+    /// Returns event log specific data, if the resource is the Windows event log.
+    #[cfg(all(feature="os-resources", windows))]
+    #[inline]
+    pub fn eventlog_data(&self) -> Option<&EventLogResourceDesc> {
+        self.specific_data.eventlog_data()
+    }
+
+    /// Returns journal specific data, if the resource is the systemd journal.
+    #[cfg(all(feature="os-resources", target_os="linux"))]
+    #[inline]
+    pub fn journal_data(&self) -> Option<&JournalResourceDesc> {
+        self.specific_data.journal_data()
+    }
+
     /// Returns syslog specific data, if the resource is a network interface
     #[cfg(feature="net")]
     #[inline]
@@ -459,10 +2187,27 @@ impl ResourceDesc {
     #[inline]
     pub fn network_data(&self) -> Option<&NetworkResourceDesc> {self.specific_data.network_data()}
 
+    /// Returns webhook specific data, if the resource is a webhook
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn webhook_data(&self) -> Option<&WebhookResourceDesc> {self.specific_data.webhook_data()}
+
+    /// Returns GELF specific data, if the resource is a Graylog input
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn gelf_data(&self) -> Option<&GelfResourceDesc> {self.specific_data.gelf_data()}
+
+    /// Returns HTTP batch specific data, if the resource is an HTTP endpoint
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn http_data(&self) -> Option<&HttpResourceDesc> {self.specific_data.http_data()}
+
     /// Indicates whether this resource requires a fallback path, if there is a temporary problem
     pub fn may_need_fallback_path(&self) -> bool {
         match &self.kind {
-            &ResourceKind::PlainFile | &ResourceKind::MemoryMappedFile => true,
+            &ResourceKind::PlainFile => true,
+            #[cfg(feature="mmfile")]
+            &ResourceKind::MemoryMappedFile => true,
             #[cfg(feature="net")]
             &ResourceKind::Network | &ResourceKind::Syslog => true,
             _ => false
@@ -472,7 +2217,9 @@ impl ResourceDesc {
     /// Indicates whether this resource requires an output path
     pub fn needs_output_path(&self) -> bool {
         match &self.kind {
-            &ResourceKind::PlainFile | &ResourceKind::MemoryMappedFile => true,
+            &ResourceKind::PlainFile => true,
+            #[cfg(feature="mmfile")]
+            &ResourceKind::MemoryMappedFile => true,
             _ => false
         }
     }
@@ -480,7 +2227,7 @@ impl ResourceDesc {
 impl Default for ResourceDesc {
     fn default() -> Self {
         ResourceDesc::for_plain_file(&[0], RecordLevelId::All as u32, None, None,
-                                     DEFAULT_OUTPUT_FILE_NAME, None)
+                                     DEFAULT_OUTPUT_FILE_NAME, None, CharEncoding::Utf8)
     }
 }
 impl Debug for ResourceDesc {
@@ -492,23 +2239,22 @@ impl Debug for ResourceDesc {
             scope_buf.push_str(&aid.to_string());
         }
         scope_buf.push(']');
-        if self.buffer_policy_name.is_none() && self.output_format_name.is_none() {
-            return write!(f, "S:{}/K:{:?}/L:{:b}/BP:-/OF:-/SD:{:?}", scope_buf, self.kind,
-                          self.levels, self.specific_data)
+        let bp = self.buffer_policy_name.as_deref().unwrap_or("-");
+        let of = self.output_format_name.as_deref().unwrap_or("-");
+        write!(f, "S:{}/K:{:?}/L:{:b}/BP:{}/OF:{}", scope_buf, self.kind, self.levels, bp, of)?;
+        for (lvls, fmt_name) in &self.level_format_names {
+            write!(f, "/LF:{:b}={}", lvls, fmt_name)?;
         }
-        if self.buffer_policy_name.is_none() {
-            return write!(f, "S:{}/K:{:?}/L:{:b}/BP:-/OF:{}/SD:{:?}", scope_buf, self.kind,
-                          self.levels, self.output_format_name.as_ref().unwrap(),
-                          self.specific_data)
+        if let Some(flt) = &self.filter { write!(f, "/FLT:{:?}", flt)?; }
+        if let Some(thr) = &self.throttle { write!(f, "/THR:{:?}", thr)?; }
+        if let Some(smp) = &self.sampling { write!(f, "/SMP:{:?}", smp)?; }
+        if self.flush_priority != 0 { write!(f, "/FP:{}", self.flush_priority)?; }
+        if self.flush_deadline != 0 { write!(f, "/FD:{}", self.flush_deadline)?; }
+        if self.clock_granularity != ClockGranularity::Full {
+            write!(f, "/CG:{}", self.clock_granularity)?;
         }
-        if self.output_format_name.is_none() {
-            return write!(f, "S:{}/K:{:?}/L:{:b}/BP:{}/OF:-/SD:{:?}", scope_buf, self.kind,
-                          self.levels, self.buffer_policy_name.as_ref().unwrap(),
-                          self.specific_data)
-        }
-        write!(f, "S:{}/K:{:?}/L:{:b}/BP:{}/OF:{}/SD:{:?}", scope_buf,
-               self.kind, self.levels, self.buffer_policy_name.as_ref().unwrap(),
-               self.output_format_name.as_ref().unwrap(), self.specific_data)
+        if self.probe { write!(f, "/PRB")?; }
+        write!(f, "/SD:{:?}", self.specific_data)
     }
 }
 
@@ -531,16 +2277,186 @@ impl ResourceDescList {
         }
         false
     }
+
+    /// Returns the bit mask with all record levels accepted by at least one of the resources
+    pub(crate) fn accepted_levels(&self) -> u32 {
+        self.elements().fold(0, |levels, rdesc| levels | rdesc.levels())
+    }
 }
 
 // Names for all resource kinds
 const RES_KIND_FILE: &str = "file";
+#[cfg(feature="mmfile")]
 const RES_KIND_MM_FILE: &str = "mmfile";
 const RES_KIND_STDOUT: &str = "stdout";
 const RES_KIND_STDERR: &str = "stderr";
+#[cfg(feature="os-resources")]
+const RES_KIND_PIPE: &str = "pipe";
+#[cfg(feature="os-resources")]
+const RES_KIND_NOTIFICATION: &str = "notification";
+
+#[cfg(all(feature="os-resources", windows))]
+const RES_KIND_EVENTLOG: &str = "eventlog";
+
+#[cfg(all(feature="os-resources", target_os="linux"))]
+const RES_KIND_JOURNAL: &str = "journal";
+
+// Names of resource kinds available on specific platforms only
+#[cfg(all(feature="os-resources", target_os="linux"))]
+const PLATFORM_RES_KINDS: &[&str] = &[RES_KIND_JOURNAL];
+#[cfg(all(feature="os-resources", windows))]
+const PLATFORM_RES_KINDS: &[&str] = &[RES_KIND_EVENTLOG];
+#[cfg(not(all(feature="os-resources", any(target_os="linux", windows))))]
+const PLATFORM_RES_KINDS: &[&str] = &[];
 
 #[cfg(feature="net")]
 const RES_KIND_SYSLOG: &str = "syslog";
 
 #[cfg(feature="net")]
 const RES_KIND_NETWORK: &str = "network";
+
+#[cfg(feature="net")]
+const RES_KIND_WEBHOOK: &str = "webhook";
+
+#[cfg(feature="net")]
+const RES_KIND_GELF: &str = "gelf";
+
+#[cfg(feature="net")]
+const RES_KIND_HTTP: &str = "http";
+
+/// Returns the names of all resource kinds supported on this platform, used for hints in
+/// configuration warnings.
+pub(crate) fn resource_kind_names() -> Vec<&'static str> {
+    let mut names = vec!(RES_KIND_FILE, RES_KIND_STDOUT, RES_KIND_STDERR);
+    #[cfg(feature="mmfile")]
+    names.insert(1, RES_KIND_MM_FILE);
+    #[cfg(feature="os-resources")]
+    names.extend_from_slice(&[RES_KIND_PIPE, RES_KIND_NOTIFICATION]);
+    names.extend_from_slice(PLATFORM_RES_KINDS);
+    #[cfg(feature="net")]
+    names.extend_from_slice(&[RES_KIND_SYSLOG, RES_KIND_NETWORK, RES_KIND_WEBHOOK,
+                               RES_KIND_GELF, RES_KIND_HTTP]);
+    names
+}
+
+/// Indicates whether a resource scope contains the specified application ID.
+/// An empty scope or a scope containing application ID 0 applies to any application.
+///
+/// # Arguments
+/// * `scope` - the resource scope (application IDs)
+/// * `app_id` - the application ID to check
+#[cfg(feature="net")]
+pub(crate) fn scope_contains(scope: &[u32], app_id: u32) -> bool {
+    scope.is_empty() || scope.contains(&0) || scope.contains(&app_id)
+}
+
+// Names for all webhook payload formats
+#[cfg(feature="net")]
+const PAYLOAD_GENERIC: &str = "generic";
+#[cfg(feature="net")]
+const PAYLOAD_SLACK: &str = "slack";
+
+// Names for all payload formats of HTTP batch resources
+#[cfg(feature="net")]
+const BATCH_FORMAT_JSONL: &str = "jsonl";
+#[cfg(feature="net")]
+const BATCH_FORMAT_LOKI: &str = "loki";
+#[cfg(feature="net")]
+pub(crate) const BATCH_FORMAT_NAMES: &[&str] = &[BATCH_FORMAT_JSONL, BATCH_FORMAT_LOKI];
+
+// Default values and ranges for batching and retries of HTTP batch resources
+#[cfg(feature="net")]
+pub(crate) const DEF_BATCH_SIZE: usize = 100;
+#[cfg(feature="net")]
+pub(crate) const MIN_BATCH_SIZE: usize = 1;
+#[cfg(feature="net")]
+pub(crate) const MAX_BATCH_SIZE: usize = 10000;
+#[cfg(feature="net")]
+pub(crate) const DEF_BATCH_INTERVAL: usize = 5;
+#[cfg(feature="net")]
+pub(crate) const MIN_BATCH_INTERVAL: usize = 1;
+#[cfg(feature="net")]
+pub(crate) const MAX_BATCH_INTERVAL: usize = 3600;
+#[cfg(feature="net")]
+pub(crate) const DEF_MAX_RETRIES: usize = 3;
+#[cfg(feature="net")]
+pub(crate) const MIN_MAX_RETRIES: usize = 0;
+#[cfg(feature="net")]
+pub(crate) const MAX_MAX_RETRIES: usize = 100;
+
+// Names for stream compression of network resources
+#[cfg(feature="net")]
+pub(crate) const STREAM_COMPRESSION_NONE: &str = "none";
+#[cfg(feature="net")]
+pub(crate) const STREAM_COMPRESSION_DEFLATE: &str = "deflate";
+#[cfg(feature="net")]
+pub(crate) const STREAM_COMPRESSION_NAMES: &[&str] = &[STREAM_COMPRESSION_NONE,
+                                                       STREAM_COMPRESSION_DEFLATE];
+
+// Names for the verifications of TLS server certificates
+#[cfg(feature="net")]
+const TLS_VERIFY_FULL: &str = "full";
+#[cfg(feature="net")]
+const TLS_VERIFY_CHAIN: &str = "chain";
+#[cfg(feature="net")]
+const TLS_VERIFY_NONE: &str = "none";
+#[cfg(feature="net")]
+pub(crate) const TLS_VERIFICATION_NAMES: &[&str] = &[TLS_VERIFY_FULL, TLS_VERIFY_CHAIN,
+                                                     TLS_VERIFY_NONE];
+
+// Names for all behaviours if a file can't be created upon application start
+const CREATE_ERROR_WARN: &str = "warn";
+const CREATE_ERROR_FAIL: &str = "fail";
+const CREATE_ERROR_RETRY: &str = "retry";
+const CREATE_ERROR_FALLBACK: &str = "fallback";
+pub(crate) const CREATE_ERROR_POLICY_NAMES: &[&str] = &[CREATE_ERROR_WARN, CREATE_ERROR_FAIL,
+                                                        CREATE_ERROR_RETRY,
+                                                        CREATE_ERROR_FALLBACK];
+
+// Names for all clock granularities of resources
+const CLOCK_GRANULARITY_SECOND: &str = "second";
+const CLOCK_GRANULARITY_MILLISECOND: &str = "millisecond";
+const CLOCK_GRANULARITY_FULL: &str = "full";
+pub(crate) const CLOCK_GRANULARITY_NAMES: &[&str] = &[CLOCK_GRANULARITY_SECOND,
+                                                      CLOCK_GRANULARITY_MILLISECOND,
+                                                      CLOCK_GRANULARITY_FULL];
+
+// Names for all timestamp domains of memory mapped files
+const TIMESTAMP_DOMAIN_WALLCLOCK: &str = "wallclock";
+const TIMESTAMP_DOMAIN_TICKS: &str = "ticks";
+#[cfg(feature="mmfile")]
+pub(crate) const TIMESTAMP_DOMAIN_NAMES: &[&str] = &[TIMESTAMP_DOMAIN_WALLCLOCK,
+                                                     TIMESTAMP_DOMAIN_TICKS];
+
+// Names for all on-disk formats of plain files
+const FILE_FORMAT_TEXT: &str = "text";
+const FILE_FORMAT_BINARY: &str = "binary";
+pub(crate) const FILE_FORMAT_NAMES: &[&str] = &[FILE_FORMAT_TEXT, FILE_FORMAT_BINARY];
+
+// Names for all sharing modes of plain files
+const FILE_SHARING_EXCLUSIVE: &str = "exclusive";
+const FILE_SHARING_APPEND: &str = "append";
+const FILE_SHARING_LOCK: &str = "lock";
+pub(crate) const FILE_SHARING_NAMES: &[&str] = &[FILE_SHARING_EXCLUSIVE, FILE_SHARING_APPEND,
+                                                 FILE_SHARING_LOCK];
+
+// Default value and range for the interval between attempts to create a file, in seconds
+pub(crate) const DEF_CREATE_RETRY_INTERVAL: usize = 10;
+pub(crate) const MIN_CREATE_RETRY_INTERVAL: usize = 1;
+pub(crate) const MAX_CREATE_RETRY_INTERVAL: usize = 86400;
+
+// Default values and range for the delays between attempts to reconnect to a logging server,
+// in seconds
+#[cfg(feature="net")]
+pub(crate) const DEF_RECONNECT_DELAY: usize = 1;
+#[cfg(feature="net")]
+pub(crate) const DEF_MAX_RECONNECT_DELAY: usize = 300;
+#[cfg(feature="net")]
+pub(crate) const MIN_RECONNECT_DELAY: usize = 1;
+#[cfg(feature="net")]
+pub(crate) const MAX_RECONNECT_DELAY: usize = 86400;
+
+// Names for all character encodings
+const ENCODING_UTF8: &str = "utf-8";
+const ENCODING_LATIN1: &str = "latin-1";
+const ENCODING_UTF16LE: &str = "utf-16le";
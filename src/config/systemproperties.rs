@@ -33,7 +33,14 @@
 //! Coaly system properties.
 
 use std::fmt::{Debug, Formatter};
+#[cfg(feature="net")]
+use std::net::SocketAddr;
+use crate::datetime::RecordClock;
+use crate::errorhandling::reporting::ProblemReporting;
+use crate::policies::CompressionAlgorithm;
 use crate::record::{RecordLevelId, RecordLevelMap};
+use crate::record::recorddata::RecordData;
+use super::filter::SuppressionRule;
 
 
 // Default value and range for size of mode change stack
@@ -41,6 +48,247 @@ pub(crate) const DEFAULT_CHANGE_STACK_SIZE: usize = 32768;
 pub(crate) const MIN_CHANGE_STACK_SIZE: usize = 16;
 pub(crate) const MAX_CHANGE_STACK_SIZE: usize = 2147483647;
 
+// Default replacement for characters not allowed in file names
+pub(crate) const DEFAULT_FILE_NAME_REPLACEMENT_CHAR: char = '_';
+
+// Default value and range for maximum length of variable values in file names
+pub(crate) const DEFAULT_FILE_NAME_MAX_VAR_LENGTH: usize = 64;
+pub(crate) const MIN_FILE_NAME_MAX_VAR_LENGTH: usize = 1;
+pub(crate) const MAX_FILE_NAME_MAX_VAR_LENGTH: usize = 255;
+
+// Default value and range for maximum memory used by all record buffers, 0 means unlimited
+pub(crate) const DEFAULT_BUFFER_MEMORY_LIMIT: usize = 0;
+pub(crate) const MIN_BUFFER_MEMORY_LIMIT: usize = 65536; // 64 KByte
+pub(crate) const MAX_BUFFER_MEMORY_LIMIT: usize = u32::MAX as usize; // 4 GByte
+
+// Default value and range for the time in milliseconds available for flushing all resources
+// upon shutdown, 0 means unlimited
+pub(crate) const DEFAULT_SHUTDOWN_TIMEOUT: usize = 0;
+pub(crate) const MIN_SHUTDOWN_TIMEOUT: usize = 0;
+pub(crate) const MAX_SHUTDOWN_TIMEOUT: usize = 3600000;
+pub(crate) const DEFAULT_ENV_CACHE_TTL: usize = 0;
+pub(crate) const MIN_ENV_CACHE_TTL: usize = 0;
+pub(crate) const MAX_ENV_CACHE_TTL: usize = 86400000;
+
+// Default value and range for the maximum number of open output files, 0 means unlimited
+pub(crate) const DEFAULT_MAX_OPEN_FILES: usize = 0;
+pub(crate) const MIN_MAX_OPEN_FILES: usize = 0;
+pub(crate) const MAX_MAX_OPEN_FILES: usize = 1048576;
+
+// Default value and range for the interval in seconds between records with event counts
+pub(crate) const DEFAULT_COUNTER_INTERVAL: usize = 60;
+pub(crate) const MIN_COUNTER_INTERVAL: usize = 1;
+pub(crate) const MAX_COUNTER_INTERVAL: usize = 86400;
+
+// Default value and range for the number of consecutive seconds with output failures or overload
+// before switching to degraded mode
+pub(crate) const DEFAULT_DEGRADE_AFTER: usize = 10;
+pub(crate) const MIN_DEGRADE_AFTER: usize = 1;
+pub(crate) const MAX_DEGRADE_AFTER: usize = 3600;
+
+// Default value and range for the number of consecutive seconds without output failures or
+// overload before leaving degraded mode
+pub(crate) const DEFAULT_RECOVER_AFTER: usize = 60;
+pub(crate) const MIN_RECOVER_AFTER: usize = 1;
+pub(crate) const MAX_RECOVER_AFTER: usize = 86400;
+
+// Default value and range for the number of records per second considered as overload,
+// 0 means records are never considered as overload
+pub(crate) const DEFAULT_OVERLOAD_RATE: usize = 0;
+pub(crate) const MIN_OVERLOAD_RATE: usize = 0;
+pub(crate) const MAX_OVERLOAD_RATE: usize = u32::MAX as usize;
+
+// Default value and range for the age in seconds, above which orphaned output files are
+// removed or compressed upon startup
+pub(crate) const DEFAULT_CLEANUP_RETENTION: usize = 604800;
+pub(crate) const MIN_CLEANUP_RETENTION: usize = 60;
+pub(crate) const MAX_CLEANUP_RETENTION: usize = 315360000;
+
+// Default value and range for the maximum number of problem messages per minute,
+// 0 means unlimited
+pub(crate) const DEFAULT_PROBLEM_RATE_LIMIT: usize = 0;
+pub(crate) const MIN_PROBLEM_RATE_LIMIT: usize = 0;
+pub(crate) const MAX_PROBLEM_RATE_LIMIT: usize = u32::MAX as usize;
+
+// Default value and range for the number of records between two records with traced
+// filtering decisions, 0 means pipeline tracing is disabled
+pub(crate) const DEFAULT_PIPELINE_TRACE: usize = 0;
+pub(crate) const MIN_PIPELINE_TRACE: usize = 0;
+pub(crate) const MAX_PIPELINE_TRACE: usize = u32::MAX as usize;
+
+// Default value and range for the seconds a capture file of a mode change is kept open after
+// the last observer using it has been dropped
+pub(crate) const DEFAULT_CAPTURE_TTL: usize = 60;
+pub(crate) const MIN_CAPTURE_TTL: usize = 0;
+pub(crate) const MAX_CAPTURE_TTL: usize = 86400;
+
+// Default name of the file for problem messages, if target file is specified without name
+pub(crate) const DEFAULT_PROBLEM_FILE_NAME: &str = "coaly_problems.log";
+
+
+/// Profile for degraded mode.
+/// The worker thread switches to degraded mode, if output failures or overload persist for a
+/// configured number of seconds. In degraded mode, only the profile's record levels are
+/// processed and only the profile's resource kinds are written. The worker switches back after
+/// a configured number of seconds without failures or overload, the different periods form a
+/// hysteresis preventing frequent switches.
+/// Specified under TOML table system.degraded in the custom configuration file.
+#[derive (Clone)]
+pub(crate) struct DegradationProfile {
+    // bit mask with all record levels processed in degraded mode
+    enabled_levels: u32,
+    // names of the resource kinds written in degraded mode, empty for all kinds
+    resource_kinds: Vec<String>,
+    // number of consecutive seconds with failures or overload before degrading
+    degrade_after: u64,
+    // number of consecutive seconds without failures or overload before recovering
+    recover_after: u64,
+    // number of records per second considered as overload, 0 to disable overload detection
+    overload_rate: u64
+}
+impl DegradationProfile {
+    /// Returns the bit mask with the record levels processed in degraded mode
+    #[inline]
+    pub(crate) fn enabled_levels(&self) -> u32 { self.enabled_levels }
+
+    /// Sets the bit mask with the record levels processed in degraded mode
+    /// 
+    /// # Arguments
+    /// * `levels` - the record levels processed in degraded mode
+    #[inline]
+    pub(crate) fn set_enabled_levels(&mut self, levels: u32) { self.enabled_levels = levels }
+
+    /// Indicates whether resources of the given kind are written in degraded mode.
+    /// 
+    /// # Arguments
+    /// * `kind_name` - the resource kind name
+    #[inline]
+    pub(crate) fn keeps_resource_kind(&self, kind_name: &str) -> bool {
+        self.resource_kinds.is_empty() || self.resource_kinds.iter().any(|k| k == kind_name)
+    }
+
+    /// Sets the names of the resource kinds written in degraded mode.
+    /// 
+    /// # Arguments
+    /// * `kind_names` - the resource kind names, empty to keep all resources
+    #[inline]
+    pub(crate) fn set_resource_kinds(&mut self, kind_names: Vec<String>) {
+        self.resource_kinds = kind_names
+    }
+
+    /// Returns the number of consecutive seconds with failures or overload before degrading
+    #[inline]
+    pub(crate) fn degrade_after(&self) -> u64 { self.degrade_after }
+
+    /// Sets the number of consecutive seconds with failures or overload before degrading.
+    /// 
+    /// # Arguments
+    /// * `secs` - the number of seconds
+    #[inline]
+    pub(crate) fn set_degrade_after(&mut self, secs: u64) { self.degrade_after = secs }
+
+    /// Returns the number of consecutive seconds without failures or overload before recovering
+    #[inline]
+    pub(crate) fn recover_after(&self) -> u64 { self.recover_after }
+
+    /// Sets the number of consecutive seconds without failures or overload before recovering.
+    /// 
+    /// # Arguments
+    /// * `secs` - the number of seconds
+    #[inline]
+    pub(crate) fn set_recover_after(&mut self, secs: u64) { self.recover_after = secs }
+
+    /// Returns the number of records per second considered as overload, 0 if overload detection
+    /// is disabled
+    #[inline]
+    pub(crate) fn overload_rate(&self) -> u64 { self.overload_rate }
+
+    /// Sets the number of records per second considered as overload.
+    /// 
+    /// # Arguments
+    /// * `rate` - the number of records per second, 0 to disable overload detection
+    #[inline]
+    pub(crate) fn set_overload_rate(&mut self, rate: u64) { self.overload_rate = rate }
+}
+impl Default for DegradationProfile {
+    fn default() -> Self {
+        Self {
+            enabled_levels: RecordLevelId::Problems as u32,
+            resource_kinds: Vec::new(),
+            degrade_after: DEFAULT_DEGRADE_AFTER as u64,
+            recover_after: DEFAULT_RECOVER_AFTER as u64,
+            overload_rate: DEFAULT_OVERLOAD_RATE as u64
+        }
+    }
+}
+impl Debug for DegradationProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ENA:{:b}/RES:{:?}/DA:{}/RA:{}/OR:{}", self.enabled_levels, self.resource_kinds,
+               self.degrade_after, self.recover_after, self.overload_rate)
+    }
+}
+
+/// Settings for the cleanup of orphaned output files upon startup.
+/// Output files with thread or process specific names, left behind by earlier runs, are removed
+/// or compressed, if they haven't been modified for the retention period.
+/// Specified under TOML table system.cleanup in the custom configuration file.
+#[derive (Clone)]
+pub(crate) struct StaleFileCleanup {
+    // age in seconds, above which an output file is considered orphaned
+    retention: u64,
+    // algorithm used to compress orphaned files, None to remove them
+    compression: CompressionAlgorithm
+}
+impl StaleFileCleanup {
+    /// Returns the age in seconds, above which an output file is considered orphaned.
+    #[inline]
+    pub(crate) fn retention(&self) -> u64 { self.retention }
+
+    /// Sets the age above which an output file is considered orphaned.
+    ///
+    /// # Arguments
+    /// * `secs` - the age in seconds
+    #[inline]
+    pub(crate) fn set_retention(&mut self, secs: u64) { self.retention = secs }
+
+    /// Returns the algorithm used to compress orphaned files.
+    /// Orphaned files are removed, if the algorithm is **None**.
+    #[inline]
+    pub(crate) fn compression(&self) -> CompressionAlgorithm { self.compression }
+
+    /// Sets the algorithm used to compress orphaned files.
+    ///
+    /// # Arguments
+    /// * `compression` - the compression algorithm, **None** to remove orphaned files
+    #[inline]
+    pub(crate) fn set_compression(&mut self, compression: CompressionAlgorithm) {
+        self.compression = compression
+    }
+}
+impl Default for StaleFileCleanup {
+    fn default() -> Self {
+        Self {
+            retention: DEFAULT_CLEANUP_RETENTION as u64,
+            compression: CompressionAlgorithm::None
+        }
+    }
+}
+impl Debug for StaleFileCleanup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RET:{}/CMP:{:?}", self.retention, self.compression)
+    }
+}
+
+/// Source of the key for the encryption of output files.
+/// The key is used only, if the library was built with feature encryption.
+#[derive (Clone)]
+#[cfg_attr(not(feature="encryption"), allow(dead_code))]
+pub(crate) enum EncryptionKeySpec {
+    // path of a file containing the key
+    KeyFile(String),
+    // passphrase, from which the key is derived
+    Passphrase(String)
+}
 
 /// Coaly system properties.
 /// All properties are specified under TOML table system in the custom configuration file.
@@ -63,7 +311,44 @@ pub struct SystemProperties {
     // bit mask with all buffered record levels upon application start
     buffered_levels: u32,
     // ID character and name for all record levels
-    record_levels: RecordLevelMap
+    record_levels: RecordLevelMap,
+    // names of environment variables captured once upon application start
+    env_snapshot: Vec<String>,
+    // names of environment variables read anew for every record
+    env_per_record: Vec<String>,
+    // time in milliseconds a value of a variable read for every record is reused, 0 for no cache
+    env_cache_ttl: u64,
+    // replacement for characters not allowed in file names
+    file_name_replacement_char: char,
+    // maximum number of characters for a variable value in file names
+    file_name_max_var_length: usize,
+    // maximum number of bytes used by all memory buffers of the process, 0 if unlimited
+    buffer_memory_limit: usize,
+    // time in milliseconds available for flushing all resources upon shutdown, 0 if unlimited
+    shutdown_timeout: u64,
+    // interval in seconds between records with event counts
+    counter_interval: u64,
+    // maximum number of plain output files held open, 0 if unlimited
+    max_open_files: usize,
+    // source for the timestamps of log and trace records
+    record_clock: RecordClock,
+    // record levels, for which a backtrace of the call site is captured
+    backtrace_levels: u32,
+    // socket address of the endpoint serving pipeline metrics, if enabled
+    #[cfg(feature="net")]
+    metrics_address: Option<SocketAddr>,
+    // profile for degraded mode, if automatic degradation is enabled
+    degradation: Option<DegradationProfile>,
+    // settings for the cleanup of orphaned output files upon startup, if enabled
+    stale_file_cleanup: Option<StaleFileCleanup>,
+    // settings for the reporting of problems detected by Coaly itself, if not default
+    problem_reporting: Option<ProblemReporting>,
+    // rules for records suppressed regardless of record level
+    suppressions: Vec<SuppressionRule>,
+    // key file or passphrase for encrypted output files
+    encryption_key: Option<EncryptionKeySpec>,
+    // indicates whether the configuration file shall be watched for changes
+    watch: bool
 }
 impl SystemProperties {
     /// Returns the application ID.
@@ -164,6 +449,251 @@ impl SystemProperties {
     /// * `levels` - the record level ID characters and names
     #[inline]
     pub fn set_record_levels(&mut self, levels: RecordLevelMap) { self.record_levels = levels }
+
+    /// Returns the names of the environment variables captured upon application start
+    #[inline]
+    pub fn env_snapshot(&self) -> &Vec<String> { &self.env_snapshot }
+
+    /// Sets the names of the environment variables captured upon application start
+    /// 
+    /// # Arguments
+    /// * `var_names` - the environment variable names
+    #[inline]
+    pub fn set_env_snapshot(&mut self, var_names: Vec<String>) { self.env_snapshot = var_names }
+
+    /// Returns the names of the environment variables read for every record
+    #[inline]
+    pub fn env_per_record(&self) -> &Vec<String> { &self.env_per_record }
+
+    /// Sets the names of the environment variables read for every record
+    /// 
+    /// # Arguments
+    /// * `var_names` - the environment variable names
+    #[inline]
+    pub fn set_env_per_record(&mut self, var_names: Vec<String>) { self.env_per_record = var_names }
+
+    /// Returns the time in milliseconds the value of an environment variable read for every
+    /// record is reused, 0 if the variable is read anew for every record
+    #[inline]
+    pub fn env_cache_ttl(&self) -> u64 { self.env_cache_ttl }
+
+    /// Sets the time in milliseconds the value of an environment variable read for every
+    /// record is reused.
+    /// 
+    /// # Arguments
+    /// * `ttl` - the time to live for cached values in milliseconds, 0 to disable caching
+    #[inline]
+    pub fn set_env_cache_ttl(&mut self, ttl: u64) { self.env_cache_ttl = ttl }
+
+    /// Returns the replacement for characters not allowed in file names
+    #[inline]
+    pub fn file_name_replacement_char(&self) -> char { self.file_name_replacement_char }
+
+    /// Sets the replacement for characters not allowed in file names
+    /// 
+    /// # Arguments
+    /// * `ch` - the replacement character
+    #[inline]
+    pub fn set_file_name_replacement_char(&mut self, ch: char) {
+        self.file_name_replacement_char = ch
+    }
+
+    /// Returns the maximum number of characters for a variable value in file names
+    #[inline]
+    pub fn file_name_max_var_length(&self) -> usize { self.file_name_max_var_length }
+
+    /// Sets the maximum number of characters for a variable value in file names.
+    /// 
+    /// # Arguments
+    /// * `length` - the maximum length, between 1 and 255
+    #[inline]
+    pub fn set_file_name_max_var_length(&mut self, length: usize) {
+        if (MIN_FILE_NAME_MAX_VAR_LENGTH..=MAX_FILE_NAME_MAX_VAR_LENGTH).contains(&length) {
+            self.file_name_max_var_length = length;
+        }
+    }
+
+    /// Returns the maximum number of bytes used by all memory buffers of the process,
+    /// 0 if unlimited
+    #[inline]
+    pub fn buffer_memory_limit(&self) -> usize { self.buffer_memory_limit }
+
+    /// Sets the maximum number of bytes used by all memory buffers of the process.
+    /// 
+    /// # Arguments
+    /// * `limit` - the memory limit in bytes, between 64 KBytes and 4 GBytes
+    #[inline]
+    pub fn set_buffer_memory_limit(&mut self, limit: usize) {
+        if (MIN_BUFFER_MEMORY_LIMIT..=MAX_BUFFER_MEMORY_LIMIT).contains(&limit) {
+            self.buffer_memory_limit = limit;
+        }
+    }
+
+    /// Returns the time in milliseconds available for flushing all resources upon shutdown,
+    /// 0 if unlimited
+    #[inline]
+    pub fn shutdown_timeout(&self) -> u64 { self.shutdown_timeout }
+
+    /// Sets the time available for flushing all resources upon shutdown.
+    /// 
+    /// # Arguments
+    /// * `timeout` - the timeout in milliseconds, 0 for unlimited
+    #[inline]
+    pub fn set_shutdown_timeout(&mut self, timeout: u64) { self.shutdown_timeout = timeout; }
+
+    /// Returns the interval in seconds between records with event counts.
+    #[inline]
+    pub fn counter_interval(&self) -> u64 { self.counter_interval }
+
+    /// Sets the interval between records with event counts.
+    /// 
+    /// # Arguments
+    /// * `interval` - the interval in seconds, between 1 and 86400
+    #[inline]
+    pub fn set_counter_interval(&mut self, interval: u64) {
+        if (MIN_COUNTER_INTERVAL as u64..=MAX_COUNTER_INTERVAL as u64).contains(&interval) {
+            self.counter_interval = interval;
+        }
+    }
+
+    /// Returns the maximum number of plain output files held open, 0 if unlimited.
+    #[inline]
+    pub fn max_open_files(&self) -> usize { self.max_open_files }
+
+    /// Sets the maximum number of plain output files held open.
+    /// When the limit is reached, files of thread or originator specific resources are not
+    /// created any more.
+    /// 
+    /// # Arguments
+    /// * `limit` - the maximum number of open files, 0 for unlimited
+    #[inline]
+    pub fn set_max_open_files(&mut self, limit: usize) { self.max_open_files = limit; }
+
+    /// Returns the source for the timestamps of log and trace records.
+    #[inline]
+    pub(crate) fn record_clock(&self) -> RecordClock { self.record_clock }
+
+    /// Sets the source for the timestamps of log and trace records.
+    /// 
+    /// # Arguments
+    /// * `clock` - the record clock
+    #[inline]
+    pub(crate) fn set_record_clock(&mut self, clock: RecordClock) { self.record_clock = clock; }
+
+    /// Returns the bit mask with the record levels, for which a backtrace of the call site
+    /// is captured.
+    #[inline]
+    pub fn backtrace_levels(&self) -> u32 { self.backtrace_levels }
+
+    /// Sets the record levels, for which a backtrace of the call site is captured.
+    /// 
+    /// # Arguments
+    /// * `levels` - the bit mask with the record levels
+    #[inline]
+    pub fn set_backtrace_levels(&mut self, levels: u32) { self.backtrace_levels = levels; }
+
+    /// Returns the socket address of the endpoint serving pipeline metrics in Prometheus
+    /// text format, **None** if the endpoint is disabled.
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn metrics_address(&self) -> Option<SocketAddr> { self.metrics_address }
+
+    /// Sets the socket address of the endpoint serving pipeline metrics.
+    /// 
+    /// # Arguments
+    /// * `addr` - the socket address to listen on for scrape requests
+    #[cfg(feature="net")]
+    #[inline]
+    pub fn set_metrics_address(&mut self, addr: SocketAddr) { self.metrics_address = Some(addr); }
+
+    /// Returns the profile for degraded mode, **None** if automatic degradation is disabled.
+    #[inline]
+    pub(crate) fn degradation(&self) -> &Option<DegradationProfile> { &self.degradation }
+
+    /// Sets the profile for degraded mode.
+    /// 
+    /// # Arguments
+    /// * `profile` - the profile for degraded mode
+    #[inline]
+    pub(crate) fn set_degradation(&mut self, profile: DegradationProfile) {
+        self.degradation = Some(profile);
+    }
+
+    /// Returns the settings for the cleanup of orphaned output files upon startup,
+    /// **None** if the cleanup is disabled.
+    #[inline]
+    pub(crate) fn stale_file_cleanup(&self) -> &Option<StaleFileCleanup> {
+        &self.stale_file_cleanup
+    }
+
+    /// Sets the settings for the cleanup of orphaned output files upon startup.
+    /// 
+    /// # Arguments
+    /// * `cleanup` - the cleanup settings
+    #[inline]
+    pub(crate) fn set_stale_file_cleanup(&mut self, cleanup: StaleFileCleanup) {
+        self.stale_file_cleanup = Some(cleanup);
+    }
+
+    /// Returns the settings for the reporting of problems detected by Coaly itself.
+    #[inline]
+    pub(crate) fn problem_reporting(&self) -> ProblemReporting {
+        self.problem_reporting.clone().unwrap_or_default()
+    }
+
+    /// Sets the settings for the reporting of problems detected by Coaly itself.
+    /// 
+    /// # Arguments
+    /// * `reporting` - the problem reporting settings
+    #[inline]
+    pub(crate) fn set_problem_reporting(&mut self, reporting: ProblemReporting) {
+        self.problem_reporting = Some(reporting);
+    }
+
+    /// Sets the rules for records suppressed regardless of record level.
+    /// 
+    /// # Arguments
+    /// * `rules` - the suppression rules
+    #[inline]
+    pub(crate) fn set_suppressions(&mut self, rules: Vec<SuppressionRule>) {
+        self.suppressions = rules;
+    }
+
+    /// Indicates whether the given record is suppressed by one of the suppression rules.
+    /// 
+    /// # Arguments
+    /// * `record` - the record data
+    pub(crate) fn suppresses(&self, record: &dyn RecordData) -> bool {
+        self.suppressions.iter().any(|r| r.matches(record.source_fn(), *record.line_nr(),
+                                                   record.scope_stack()))
+    }
+
+    /// Returns the key file or passphrase for encrypted output files, **None** if not
+    /// specified.
+    #[cfg(feature="encryption")]
+    #[inline]
+    pub(crate) fn encryption_key(&self) -> &Option<EncryptionKeySpec> { &self.encryption_key }
+
+    /// Sets the key file or passphrase for encrypted output files.
+    /// 
+    /// # Arguments
+    /// * `spec` - the key file or passphrase
+    #[inline]
+    pub(crate) fn set_encryption_key(&mut self, spec: EncryptionKeySpec) {
+        self.encryption_key = Some(spec);
+    }
+
+    /// Indicates whether the configuration file shall be watched for changes.
+    /// If so, changes to the file are applied automatically using a reconfiguration.
+    #[inline]
+    pub fn watch(&self) -> bool { self.watch }
+
+    /// Specifies whether the configuration file shall be watched for changes.
+    /// 
+    /// # Arguments
+    /// * `watch` - **true** to apply changes to the configuration file automatically
+    #[inline]
+    pub fn set_watch(&mut self, watch: bool) { self.watch = watch; }
 }
 impl Default for SystemProperties {
     fn default() -> Self {
@@ -177,16 +707,63 @@ impl Default for SystemProperties {
             fallback_path: std::env::temp_dir().to_string_lossy().to_string(),
             enabled_levels: RecordLevelId::Logs as u32,
             buffered_levels: 0,
-            record_levels: RecordLevelMap::default()
+            record_levels: RecordLevelMap::default(),
+            env_snapshot: Vec::new(),
+            env_per_record: Vec::new(),
+            env_cache_ttl: DEFAULT_ENV_CACHE_TTL as u64,
+            file_name_replacement_char: DEFAULT_FILE_NAME_REPLACEMENT_CHAR,
+            file_name_max_var_length: DEFAULT_FILE_NAME_MAX_VAR_LENGTH,
+            buffer_memory_limit: DEFAULT_BUFFER_MEMORY_LIMIT,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT as u64,
+            counter_interval: DEFAULT_COUNTER_INTERVAL as u64,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            record_clock: RecordClock::WallClock,
+            backtrace_levels: 0,
+            #[cfg(feature="net")]
+            metrics_address: None,
+            degradation: None,
+            stale_file_cleanup: None,
+            problem_reporting: None,
+            suppressions: Vec::new(),
+            encryption_key: None,
+            watch: false
         }
     }
 }
 impl Debug for SystemProperties {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f,
-               "AID:{}/APP:{}/CSS:{}/OPP:{}/FBP:{}/ENA:{:b}/BUF:{:b}/LVL:{:?}",
+               "AID:{}/APP:{}/CSS:{}/OPP:{}/FBP:{}/ENA:{:b}/BUF:{:b}/ENVS:{:?}/ENVR:{:?}/\
+                FNR:{}/FNL:{}/BML:{}/LVL:{:?}",
                self.application_id, self.application_name(), self.change_stack_size,
                self.output_path, self.fallback_path,
-               self.enabled_levels,self.buffered_levels,self.record_levels)
+               self.enabled_levels,self.buffered_levels,
+               self.env_snapshot, self.env_per_record,
+               self.file_name_replacement_char, self.file_name_max_var_length,
+               self.buffer_memory_limit, self.record_levels)?;
+        if self.shutdown_timeout != 0 { write!(f, "/SDT:{}", self.shutdown_timeout)?; }
+        if self.env_cache_ttl != 0 { write!(f, "/ENVT:{}", self.env_cache_ttl)?; }
+        if self.counter_interval != DEFAULT_COUNTER_INTERVAL as u64 {
+            write!(f, "/CTI:{}", self.counter_interval)?;
+        }
+        if self.max_open_files != 0 { write!(f, "/MOF:{}", self.max_open_files)?; }
+        if self.record_clock != RecordClock::WallClock {
+            write!(f, "/CLK:{:?}", self.record_clock)?;
+        }
+        if self.backtrace_levels != 0 { write!(f, "/BTL:{:b}", self.backtrace_levels)?; }
+        #[cfg(feature="net")]
+        if let Some(addr) = self.metrics_address { write!(f, "/MTA:{}", addr)?; }
+        if let Some(d) = &self.degradation { write!(f, "/DEG:{{{:?}}}", d)?; }
+        if let Some(c) = &self.stale_file_cleanup { write!(f, "/CLN:{{{:?}}}", c)?; }
+        if let Some(r) = &self.problem_reporting { write!(f, "/PRB:{{{:?}}}", r)?; }
+        if ! self.suppressions.is_empty() { write!(f, "/SUP:{:?}", self.suppressions)?; }
+        // passphrase must never show up in any output
+        match &self.encryption_key {
+            Some(EncryptionKeySpec::KeyFile(path)) => write!(f, "/EKF:{}", path)?,
+            Some(EncryptionKeySpec::Passphrase(_)) => write!(f, "/EPP")?,
+            None => ()
+        }
+        if self.watch { write!(f, "/WCH")?; }
+        Ok(())
     }
 }
@@ -102,6 +102,17 @@ impl TomlDocument {
         self.root.insert(&self.selection, key, value)
     }
 
+    /// Overlays this document with the items of another document.
+    /// Tables present in both documents are merged recursively, all other items of the other
+    /// document replace the items with the same key in this document. Hence arrays of tables
+    /// like resources are replaced as a whole.
+    ///
+    /// # Arguments
+    /// * `other` - the document with the overlay items
+    pub fn overlay(&mut self, other: TomlDocument) {
+        overlay_item(&mut self.root, other.root);
+    }
+
     /// Returns the document's root table.
     /// Return value will always be Some, hence using unwrap without check is safe.
     fn root_table(&self) -> Option<&TomlTable> {
@@ -125,6 +136,27 @@ impl Default for TomlDocument {
     }
 }
 
+/// Overlays a value item with another one.
+/// If both items hold a table, the tables are merged recursively, otherwise the item is
+/// replaced by the overlay item.
+///
+/// # Arguments
+/// * `item` - the value item to overlay
+/// * `overlay` - the overlay item
+fn overlay_item(item: &mut TomlValueItem, overlay: TomlValueItem) {
+    if let (TomlValue::Table(table), TomlValue::Table(_)) = (&mut item.value, &overlay.value) {
+        let TomlValue::Table(overlay_table) = overlay.value else { return };
+        for (key, overlay_child) in overlay_table {
+            match table.get_mut(&key) {
+                Some(child) => overlay_item(child, overlay_child),
+                None => { table.insert(key, overlay_child); }
+            }
+        }
+        return
+    }
+    *item = overlay;
+}
+
 /// Wrapper structure for TOML values within a TOML document.
 /// The pure value is enhanced with an indicator flag allowing distinction between normal and
 /// inline tables as well as between value arrays and arrays of values. 
@@ -371,7 +403,7 @@ impl TomlValue {
     }
 
     /// Returns the boolean value, if the variant is a boolean value.
-    pub fn _as_bool(&self) -> Option<bool> {
+    pub fn as_bool(&self) -> Option<bool> {
         match *self { TomlValue::Boolean(val) => Some(val), _ => None }
     }
 
@@ -387,7 +419,7 @@ impl TomlValue {
 
     /// Returns the float value, if the variant is a number value.
     /// For integer values, default rust conversion is used.
-    pub fn _as_float(&self) -> Option<f64> {
+    pub fn as_float(&self) -> Option<f64> {
         match *self {
             TomlValue::Float(val) => Some(val),
             TomlValue::Integer(val) => Some(val as f64),
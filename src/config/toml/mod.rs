@@ -77,6 +77,28 @@ pub fn parse_file(file_name: &str) -> Result<TomlDocument, CoalyException> {
     }
 }
 
+/// Parses the specified TOML formatted text.
+/// The parsing process quits as soon as the first error is encountered.
+///
+/// # Arguments
+/// * `text` - the TOML formatted text
+///
+/// # Return values
+/// A TOML document structure with all TOML definitions parsed
+///
+/// # Errors
+/// Returns a structure containing error information, if the text can't be parsed
+pub fn parse_str(text: &str) -> Result<TomlDocument, CoalyException> {
+    match TomlParser::new(text).parse() {
+        Ok(doc) => Ok(doc),
+        Err(ex) => {
+            let mut parse_ex = coalyxe!(E_CFG_TOML_STR_PARSE_FAILED);
+            parse_ex.set_cause(ex);
+            Err(parse_ex)
+        }
+    }
+}
+
 /// Encloses a string in double quotes, if it doesn't start already with double quotes.
 /// 
 /// # Arguments
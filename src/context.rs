@@ -0,0 +1,318 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Record context propagation across process boundaries.
+//! Every process using Coaly gets a random run ID upon start. A parent process passes its
+//! trace ID and run ID to a child process in environment variable COALY_PARENT_CONTEXT, the
+//! child adopts the trace ID and remembers the parent's run ID. Hence all records of a process
+//! tree share the trace ID of the root process and can be correlated.
+//!
+//! Within a process, every thread has a context map with application defined entries like
+//! request or session IDs. The entries are attached to all records issued by the thread and
+//! are available as variables `$Context[key]` in output formats and file name specifications.
+//! Asynchronous tasks moving between threads can take a snapshot of the context map and attach
+//! it to the thread currently executing the task.
+//!
+//! ```
+//! let _request = coaly::context::scoped("request_id", "4711");
+//! coaly::context::insert("user", "jdoe");
+//! assert_eq!(Some(String::from("4711")), coaly::context::get("request_id"));
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the environment variable holding the context handed over by a parent process
+pub const PARENT_CONTEXT_ENV_VAR: &str = "COALY_PARENT_CONTEXT";
+
+/// Run context of the process
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct RunContext {
+    // random ID of this process run
+    run_id: String,
+    // run ID of the parent process, empty if the process wasn't started by a Coaly application
+    parent_run_id: String,
+    // ID shared by all processes of a process tree, the run ID of the root process
+    trace_id: String
+}
+impl RunContext {
+    /// Creates the run context from the value handed over by the parent process.
+    /// An invalid value is ignored, the process is regarded as root of a process tree then.
+    ///
+    /// # Arguments
+    /// * `run_id` - the run ID of this process
+    /// * `parent_context` - the value of environment variable COALY_PARENT_CONTEXT, if defined
+    fn new(run_id: String, parent_context: Option<&str>) -> RunContext {
+        if let Some((trace_id, parent_run_id)) = parent_context.and_then(parse_context) {
+            return RunContext { run_id, parent_run_id, trace_id }
+        }
+        RunContext { trace_id: run_id.clone(), run_id, parent_run_id: String::new() }
+    }
+
+    /// Returns the run ID of this process
+    #[inline]
+    pub(crate) fn run_id(&self) -> &str { &self.run_id }
+
+    /// Returns the run ID of the parent process, empty if there is no parent context
+    #[inline]
+    pub(crate) fn parent_run_id(&self) -> &str { &self.parent_run_id }
+
+    /// Returns the trace ID shared by all processes of the process tree
+    #[inline]
+    pub(crate) fn trace_id(&self) -> &str { &self.trace_id }
+
+    /// Returns the value of environment variable COALY_PARENT_CONTEXT for a child process
+    #[inline]
+    pub(crate) fn child_context(&self) -> String {
+        format!("{}{}{}", self.trace_id, CONTEXT_SEPARATOR, self.run_id)
+    }
+}
+
+/// Returns the run context of this process.
+#[inline]
+pub(crate) fn run_context() -> &'static RunContext { &RUN_CONTEXT }
+
+/// Returns the value of environment variable COALY_PARENT_CONTEXT for a child process.
+#[inline]
+pub(crate) fn child_context() -> String { RUN_CONTEXT.child_context() }
+
+/// Passes the run context of this process to a child process.
+///
+/// # Arguments
+/// * `cmd` - the command spawning the child process
+pub(crate) fn propagate_context(cmd: &mut Command) -> &mut Command {
+    cmd.env(PARENT_CONTEXT_ENV_VAR, RUN_CONTEXT.child_context())
+}
+
+/// Context map entries, sorted by key
+pub type ContextMap = BTreeMap<String, String>;
+
+/// Snapshot of the context map of a thread.
+/// Used to hand over the context to another thread, e.g. when an asynchronous task is
+/// executed by a different thread than the one that spawned it.
+#[derive(Clone, Debug, Default)]
+pub struct ContextSnapshot(Option<Arc<ContextMap>>);
+impl ContextSnapshot {
+    /// Replaces the context map of the calling thread with this snapshot.
+    /// The previous context map is restored when the returned guard is dropped.
+    pub fn attach(&self) -> ContextGuard {
+        ContextGuard(THREAD_CONTEXT.with(|c| c.replace(self.0.clone())))
+    }
+
+    /// Returns the value of the entry with the given key, **None** if there is no such entry
+    ///
+    /// # Arguments
+    /// * `key` - the entry key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.as_ref().and_then(|m| m.get(key)).map(|v| v.as_str())
+    }
+}
+
+/// Guard restoring the context map of the calling thread, when it is dropped.
+/// Returned by functions [scoped] and [ContextSnapshot::attach].
+#[must_use]
+pub struct ContextGuard(Option<Arc<ContextMap>>);
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        THREAD_CONTEXT.with(|c| *c.borrow_mut() = previous);
+    }
+}
+
+/// Inserts an entry into the context map of the calling thread.
+/// An existing entry with the same key is replaced.
+///
+/// # Arguments
+/// * `key` - the entry key, referenced as `$Context[key]` in formats
+/// * `value` - the entry value
+pub fn insert(key: &str, value: &str) {
+    THREAD_CONTEXT.with(|c| {
+        let mut ctx = c.borrow_mut();
+        let map = ctx.get_or_insert_with(|| Arc::new(ContextMap::new()));
+        Arc::make_mut(map).insert(key.to_string(), value.to_string());
+    });
+}
+
+/// Removes an entry from the context map of the calling thread.
+///
+/// # Arguments
+/// * `key` - the entry key
+///
+/// # Return values
+/// the value of the removed entry, **None** if there was no such entry
+pub fn remove(key: &str) -> Option<String> {
+    THREAD_CONTEXT.with(|c| {
+        let mut ctx = c.borrow_mut();
+        let map = ctx.as_mut()?;
+        let value = Arc::make_mut(map).remove(key);
+        if map.is_empty() { *ctx = None; }
+        value
+    })
+}
+
+/// Returns the value of an entry in the context map of the calling thread.
+///
+/// # Arguments
+/// * `key` - the entry key
+///
+/// # Return values
+/// the entry value, **None** if there is no entry with the given key
+pub fn get(key: &str) -> Option<String> {
+    THREAD_CONTEXT.with(|c| c.borrow().as_ref().and_then(|m| m.get(key).cloned()))
+}
+
+/// Removes all entries from the context map of the calling thread.
+pub fn clear() { THREAD_CONTEXT.with(|c| *c.borrow_mut() = None); }
+
+/// Inserts an entry into the context map of the calling thread for the lifetime of the
+/// returned guard. The context map is restored to its previous state, when the guard is
+/// dropped.
+///
+/// # Arguments
+/// * `key` - the entry key, referenced as `$Context[key]` in formats
+/// * `value` - the entry value
+pub fn scoped(key: &str, value: &str) -> ContextGuard {
+    let guard = ContextGuard(current());
+    insert(key, value);
+    guard
+}
+
+/// Returns a snapshot of the context map of the calling thread.
+pub fn snapshot() -> ContextSnapshot { ContextSnapshot(current()) }
+
+/// Returns the context map of the calling thread, **None** if the map is empty.
+/// The map is shared with the thread until the thread modifies it.
+#[inline]
+pub(crate) fn current() -> Option<Arc<ContextMap>> {
+    THREAD_CONTEXT.with(|c| c.borrow().clone())
+}
+
+/// Returns a new random run ID consisting of 16 hexadecimal digits.
+fn new_run_id() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    if let Ok(d) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u128(d.as_nanos());
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// Parses the context handed over by a parent process.
+///
+/// # Arguments
+/// * `value` - the context value in the format trace_id:run_id
+///
+/// # Return values
+/// trace ID and parent run ID, **None** if the value is invalid
+fn parse_context(value: &str) -> Option<(String, String)> {
+    let (trace_id, run_id) = value.trim().split_once(CONTEXT_SEPARATOR)?;
+    if ! is_valid_id(trace_id) || ! is_valid_id(run_id) { return None }
+    Some((trace_id.to_string(), run_id.to_string()))
+}
+
+/// Indicates whether the given string is a valid trace or run ID.
+fn is_valid_id(id: &str) -> bool {
+    (1..=MAX_ID_LENGTH).contains(&id.len()) && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// separator between trace ID and run ID in the context value
+const CONTEXT_SEPARATOR: char = ':';
+
+// maximum number of hexadecimal digits in a trace or run ID handed over by a parent
+const MAX_ID_LENGTH: usize = 32;
+
+thread_local! {
+    /// The context map of the calling thread, None if empty
+    static THREAD_CONTEXT: RefCell<Option<Arc<ContextMap>>> = const { RefCell::new(None) };
+}
+
+lazy_static! {
+    /// The run context of this process, determined upon first use
+    static ref RUN_CONTEXT: RunContext =
+        RunContext::new(new_run_id(), std::env::var(PARENT_CONTEXT_ENV_VAR).ok().as_deref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_context() {
+        let id = new_run_id();
+        assert_eq!(16, id.len());
+        assert!(is_valid_id(&id));
+        assert_ne!(id, new_run_id());
+        let root = RunContext::new(String::from("0123abcd"), None);
+        assert_eq!(("0123abcd", "", "0123abcd"),
+                   (root.run_id(), root.parent_run_id(), root.trace_id()));
+        assert_eq!("0123abcd:0123abcd", root.child_context());
+        let child = RunContext::new(String::from("4567ef01"), Some(&root.child_context()));
+        assert_eq!(("4567ef01", "0123abcd", "0123abcd"),
+                   (child.run_id(), child.parent_run_id(), child.trace_id()));
+        assert_eq!("0123abcd:4567ef01", child.child_context());
+        for invalid in ["", "0123abcd", "xyz:0123", ":0123", "0123:", "01:23:45"] {
+            assert_eq!(root, RunContext::new(String::from("0123abcd"), Some(invalid)));
+        }
+    }
+
+    #[test]
+    fn test_context_map() {
+        clear();
+        assert!(current().is_none());
+        insert("request_id", "4711");
+        insert("user", "jdoe");
+        assert_eq!(Some(String::from("4711")), get("request_id"));
+        let snap = snapshot();
+        {
+            let _guard = scoped("request_id", "4712");
+            assert_eq!(Some(String::from("4712")), get("request_id"));
+            assert_eq!(Some("4711"), snap.get("request_id"));
+        }
+        assert_eq!(Some(String::from("4711")), get("request_id"));
+        assert_eq!(Some(String::from("jdoe")), remove("user"));
+        assert_eq!(None, remove("user"));
+        let handle = std::thread::spawn(move || {
+            assert!(get("request_id").is_none());
+            let _guard = snap.attach();
+            (get("request_id"), get("user"))
+        });
+        assert_eq!((Some(String::from("4711")), Some(String::from("jdoe"))),
+                   handle.join().unwrap());
+        assert_eq!(Some(String::from("4711")), remove("request_id"));
+        assert!(current().is_none());
+    }
+}
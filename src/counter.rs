@@ -0,0 +1,112 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Low overhead event counters.
+//! Application threads increment named counters held in a process wide registry, no event is
+//! sent to the worker thread. The worker thread takes the counts periodically and writes them
+//! as a single record, hence high frequency events can be tracked without issuing one record
+//! per occurrence.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+lazy_static! {
+    /// All event counters used by the application since start, keyed by counter name
+    static ref COUNTERS: RwLock<HashMap<&'static str, AtomicU64>> = RwLock::new(HashMap::new());
+}
+
+/// Increments the event counter with the given name.
+/// The counter is created upon first use.
+///
+/// # Arguments
+/// * `name` - the counter name
+/// * `n` - the number of events to add
+pub(crate) fn increment(name: &'static str, n: u64) {
+    if let Ok(counters) = COUNTERS.read() {
+        if let Some(c) = counters.get(name) {
+            c.fetch_add(n, Ordering::Relaxed);
+            return
+        }
+    }
+    if let Ok(mut counters) = COUNTERS.write() {
+        counters.entry(name).or_insert_with(|| AtomicU64::new(0)).fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// Returns the counts of all event counters since the last call and resets them.
+///
+/// # Return values
+/// counter names and counts ordered by name, counters without events are omitted
+pub(crate) fn take_counts() -> Vec<(&'static str, u64)> {
+    let Ok(counters) = COUNTERS.read() else { return Vec::new() };
+    let mut counts: Vec<(&'static str, u64)> =
+        counters.iter()
+                .map(|(name, c)| (*name, c.swap(0, Ordering::Relaxed)))
+                .filter(|(_, n)| *n > 0)
+                .collect();
+    counts.sort_by_key(|(name, _)| *name);
+    counts
+}
+
+/// Returns the message for the record holding the given event counts.
+///
+/// # Arguments
+/// * `counts` - counter names and counts
+///
+/// # Return values
+/// message with the counts as name=count pairs separated by blanks
+pub(crate) fn counts_message(counts: &[(&'static str, u64)]) -> String {
+    let pairs: Vec<String> = counts.iter().map(|(name, n)| format!("{}={}", name, n)).collect();
+    format!("{}{}", COUNTS_MSG_PREFIX, pairs.join(" "))
+}
+
+// prefix for the message of event count records
+const COUNTS_MSG_PREFIX: &str = "Event counts: ";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_counters() {
+        increment("ut_cache_miss", 1);
+        increment("ut_cache_miss", 2);
+        increment("ut_db_retry", 1);
+        let counts: Vec<(&str, u64)> = take_counts().into_iter()
+                                                    .filter(|(n, _)| n.starts_with("ut_"))
+                                                    .collect();
+        assert_eq!(vec!(("ut_cache_miss", 3), ("ut_db_retry", 1)), counts);
+        assert!(take_counts().iter().all(|(n, _)| ! n.starts_with("ut_")));
+        assert_eq!("Event counts: ut_cache_miss=3 ut_db_retry=1", counts_message(&counts));
+    }
+}
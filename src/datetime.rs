@@ -33,10 +33,12 @@
 //! Coaly date-time types.
 
 use chrono::*;
-use regex::Regex;
 use std::cmp::min;
 use std::fmt::{Debug, Formatter};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU8, Ordering};
+use std::thread;
+use std::time::Instant;
 use crate::errorhandling::*;
 use crate::{CoalyResult, coalyxw};
 
@@ -257,10 +259,9 @@ impl TimeStampAnchor {
                 Err(coalyxw!(W_CFG_ANCHOR_MIN_REQ, anchor_str.to_string()))
             },
             TimeSpanUnit::Day => {
-                let pattern = Regex::new(ANCHOR_HOUR_PATTERN).unwrap();
-                if let Some(capts) = pattern.captures(anchor_str) {
-                    let hour = u32::from_str(capts.get(1).unwrap().as_str());
-                    let min = u32::from_str(capts.get(2).unwrap().as_str());
+                if let Some((hour, min)) = anchor_time(anchor_str) {
+                    let hour = u32::from_str(hour);
+                    let min = u32::from_str(min);
                     if hour.is_err() || min.is_err() {
                         return Err(coalyxw!(W_CFG_ANCHOR_HHMM_REQ, anchor_str.to_string()))
                     }
@@ -273,11 +274,10 @@ impl TimeStampAnchor {
                 Err(coalyxw!(W_CFG_ANCHOR_HHMM_REQ, anchor_str.to_string()))
             },
             TimeSpanUnit::Week => {
-                let pattern = Regex::new(ANCHOR_DOW_PATTERN).unwrap();
-                if let Some(capts) = pattern.captures(anchor_str) {
-                    let dow = WeekDay::from_str(capts.get(1).unwrap().as_str());
-                    let hour = u32::from_str(capts.get(2).unwrap().as_str());
-                    let min = u32::from_str(capts.get(3).unwrap().as_str());
+                if let Some((dow_spec, hour, min)) = anchor_day_time(anchor_str) {
+                    let dow = WeekDay::from_str(dow_spec);
+                    let hour = u32::from_str(hour);
+                    let min = u32::from_str(min);
                     if dow.is_err() || hour.is_err() || min.is_err() {
                         return Err(coalyxw!(W_CFG_ANCHOR_DOWHM_REQ, anchor_str.to_string()))
                     }
@@ -291,12 +291,13 @@ impl TimeStampAnchor {
                 Err(coalyxw!(W_CFG_ANCHOR_DOWHM_REQ, anchor_str.to_string()))
             },
             TimeSpanUnit::Month => {
-                let pattern = Regex::new(ANCHOR_DOM_PATTERN).unwrap();
-                if let Some(capts) = pattern.captures(anchor_str) {
-                    let dom_spec = capts.get(1).unwrap().as_str();
-                    let dom = if dom_spec == "ultimo" { Ok(31) } else { u32::from_str(dom_spec) };
-                    let hour = u32::from_str(capts.get(2).unwrap().as_str());
-                    let min = u32::from_str(capts.get(3).unwrap().as_str());
+                if let Some((dom_spec, hour, min)) = anchor_day_time(anchor_str) {
+                    if dom_spec != ANCHOR_ULTIMO && ! is_two_digits(dom_spec) {
+                        return Err(coalyxw!(W_CFG_ANCHOR_DOMHM_REQ, anchor_str.to_string()))
+                    }
+                    let dom = if dom_spec == ANCHOR_ULTIMO { Ok(31) } else { u32::from_str(dom_spec) };
+                    let hour = u32::from_str(hour);
+                    let min = u32::from_str(min);
                     if dom.is_err() || hour.is_err() || min.is_err() {
                         return Err(coalyxw!(W_CFG_ANCHOR_DOMHM_REQ, anchor_str.to_string()))
                     }
@@ -455,6 +456,326 @@ impl Debug for Interval {
     }
 }
 
+/// Time zone used to evaluate a cron schedule
+#[derive (Clone, Copy, PartialEq)]
+pub(crate) enum CronZone {
+    /// local time zone of the host, including daylight saving time changes
+    Local,
+    /// fixed offset to UTC in seconds
+    Fixed(i32)
+}
+
+/// Schedule defined by a cron-like expression.
+/// The expression consists of the five fields minute, hour, day of month, month and day of week,
+/// optionally followed by "utc" or a fixed offset to UTC like "+05:30".
+/// Every field may contain "*", single values, ranges "a-b", steps "*/n" or "a-b/n" and
+/// comma separated lists of them. Months and week days may be specified by their three letter
+/// english names as well. As with cron, a day matches if either the day of month or the day of
+/// week matches, when both fields are restricted.
+#[derive (Clone)]
+pub(crate) struct CronSchedule {
+    // normalized expression, for diagnostic purposes
+    spec: String,
+    // bit mask with matching minutes, bit 0 = minute 0
+    minutes: u64,
+    // bit mask with matching hours, bit 0 = hour 0
+    hours: u32,
+    // bit mask with matching days of month, bit 1 = first day of month
+    days_of_month: u32,
+    // bit mask with matching months, bit 1 = January
+    months: u16,
+    // bit mask with matching days of week, bit 0 = Sunday
+    days_of_week: u8,
+    // indicates whether the day of month field is restricted, i.e. doesn't start with "*"
+    dom_restricted: bool,
+    // indicates whether the day of week field is restricted, i.e. doesn't start with "*"
+    dow_restricted: bool,
+    // time zone used to evaluate the schedule
+    zone: CronZone
+}
+impl CronSchedule {
+    /// Returns the first timestamp later than the specified instant matching this schedule.
+    /// Local times skipped by a daylight saving time change never match.
+    ///
+    /// # Arguments
+    /// * `last_elapsed` - the timestamp when the schedule elapsed last
+    pub(crate) fn next_elapse(&self,
+                              last_elapsed: &DateTime<Local>) -> DateTime<Local> {
+        let zoned = match self.zone {
+            CronZone::Local => last_elapsed.naive_local(),
+            CronZone::Fixed(ofs) => last_elapsed.naive_utc() + Duration::seconds(ofs as i64)
+        };
+        let start = zoned.date().and_hms_opt(zoned.hour(), zoned.minute(), 0).unwrap()
+                    + Duration::minutes(1);
+        for day_offset in 0..CRON_MAX_SEARCH_DAYS {
+            let date = start.date() + Duration::days(day_offset);
+            if ! self.day_matches(&date) { continue }
+            let (first_hour, first_min) = if day_offset == 0 { (start.hour(), start.minute()) }
+                                          else { (0, 0) };
+            for hour in first_hour..24 {
+                if self.hours & (1 << hour) == 0 { continue }
+                let min_start = if hour == first_hour { first_min } else { 0 };
+                for minute in min_start..60 {
+                    if self.minutes & (1 << minute) == 0 { continue }
+                    let candidate = date.and_hms_opt(hour, minute, 0).unwrap();
+                    let result = match self.zone {
+                        CronZone::Local => Local.from_local_datetime(&candidate).earliest(),
+                        CronZone::Fixed(ofs) => {
+                            let utc = candidate - Duration::seconds(ofs as i64);
+                            Some(Local.from_utc_datetime(&utc))
+                        }
+                    };
+                    if let Some(ts) = result { return ts }
+                }
+            }
+        }
+        last_elapsed.checked_add_signed(Duration::seconds(MAX_DURATION)).unwrap()
+    }
+
+    /// Indicates whether the specified date matches the day of month, month and day of week
+    /// fields of this schedule.
+    ///
+    /// # Arguments
+    /// * `date` - the date to check
+    fn day_matches(&self, date: &NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 { return false }
+        let dom_match = self.days_of_month & (1 << date.day()) != 0;
+        let dow_match = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.dom_restricted && self.dow_restricted { return dom_match || dow_match }
+        dom_match && dow_match
+    }
+}
+impl Debug for CronSchedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.spec)
+    }
+}
+impl FromStr for CronSchedule {
+    type Err = CoalyException;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() < 5 || fields.len() > 6 {
+            return Err(coalyxw!(W_CFG_INV_CRON_EXPR, s.trim().to_string()))
+        }
+        let minutes = parse_cron_field(fields[0], 0, 59, &[])?;
+        let hours = parse_cron_field(fields[1], 0, 23, &[])?;
+        let days_of_month = parse_cron_field(fields[2], 1, 31, &[])?;
+        let months = parse_cron_field(fields[3], 1, 12, CRON_MONTH_NAMES)?;
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7, CRON_WEEKDAY_NAMES)?;
+        // 0 and 7 both denote Sunday
+        if days_of_week & 0x80 != 0 { days_of_week = (days_of_week | 1) & 0x7f; }
+        let zone = match fields.get(5) {
+            Some(z) => parse_cron_zone(z).ok_or_else(|| coalyxw!(W_CFG_INV_CRON_EXPR,
+                                                                  s.trim().to_string()))?,
+            None => CronZone::Local
+        };
+        let dom_restricted = ! fields[2].starts_with('*');
+        let dow_restricted = ! fields[4].starts_with('*');
+        if dom_restricted && ! dow_restricted {
+            // reject schedules like "0 0 30 2 *", that never elapse
+            let max_dom = (1..=12u32).filter(|m| months & (1 << m) != 0)
+                                     .map(|m| days_in_month(m, 2000))
+                                     .max().unwrap_or(0);
+            if days_of_month.trailing_zeros() > max_dom {
+                return Err(coalyxw!(W_CFG_INV_CRON_EXPR, s.trim().to_string()))
+            }
+        }
+        Ok(CronSchedule {
+            spec: fields.join(" "),
+            minutes,
+            hours: hours as u32,
+            days_of_month: days_of_month as u32,
+            months: months as u16,
+            days_of_week: days_of_week as u8,
+            dom_restricted,
+            dow_restricted,
+            zone
+        })
+    }
+}
+
+/// Source for the timestamps of log and trace records.
+/// Selected by system property clock in the custom configuration file, the timestamps
+/// are taken when the application issues the record.
+#[derive (Clone, Copy, Eq, PartialEq)]
+pub(crate) enum RecordClock {
+    // system wall clock, read for every record
+    WallClock,
+    // wall clock at the first use of the clock plus the time elapsed on the monotonic system
+    // clock, unaffected by adjustments of the system time
+    Monotonic,
+    // system wall clock, read by a background thread once per millisecond and cached
+    Coarse,
+    // time set by the application with the functions in module testing
+    Mock
+}
+impl RecordClock {
+    fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordClock::WallClock => write!(f, "{}", RECORD_CLOCK_WALLCLOCK),
+            RecordClock::Monotonic => write!(f, "{}", RECORD_CLOCK_MONOTONIC),
+            RecordClock::Coarse => write!(f, "{}", RECORD_CLOCK_COARSE),
+            RecordClock::Mock => write!(f, "{}", RECORD_CLOCK_MOCK)
+        }
+    }
+}
+impl Debug for RecordClock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.dump(f) }
+}
+impl FromStr for RecordClock {
+    type Err = bool;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            RECORD_CLOCK_WALLCLOCK => Ok(RecordClock::WallClock),
+            RECORD_CLOCK_MONOTONIC => Ok(RecordClock::Monotonic),
+            RECORD_CLOCK_COARSE => Ok(RecordClock::Coarse),
+            RECORD_CLOCK_MOCK => Ok(RecordClock::Mock),
+            _ => Err(false)
+        }
+    }
+}
+impl From<u8> for RecordClock {
+    fn from(val: u8) -> Self {
+        match val {
+            1 => RecordClock::Monotonic,
+            2 => RecordClock::Coarse,
+            3 => RecordClock::Mock,
+            _ => RecordClock::WallClock
+        }
+    }
+}
+
+lazy_static! {
+    // monotonic clock reading and corresponding wall clock time in nanoseconds since the epoch,
+    // taken upon first use of the monotonic record clock or the record ticks
+    static ref MONOTONIC_BASE: (Instant, i64) = (Instant::now(), wall_clock_nanos());
+}
+
+// record clock currently selected
+static RECORD_CLOCK: AtomicU8 = AtomicU8::new(RecordClock::WallClock as u8);
+
+// cached wall clock time in nanoseconds since the epoch for the coarse record clock
+static COARSE_NANOS: AtomicI64 = AtomicI64::new(0);
+
+// indicates whether the background thread updating the coarse record clock is running
+static COARSE_TICKER_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// time in nanoseconds since the epoch for the mock record clock
+static MOCK_NANOS: AtomicI64 = AtomicI64::new(0);
+
+/// Returns the record clock currently selected.
+#[inline]
+pub(crate) fn record_clock() -> RecordClock {
+    RecordClock::from(RECORD_CLOCK.load(Ordering::Relaxed))
+}
+
+/// Selects the source for the timestamps of log and trace records.
+/// The background thread for the coarse clock is started upon demand and terminates when
+/// another clock is selected. The mock clock starts at the current wall clock time, unless
+/// it has been set before.
+/// 
+/// # Arguments
+/// * `clock` - the record clock to use
+pub(crate) fn set_record_clock(clock: RecordClock) {
+    match clock {
+        RecordClock::Monotonic => lazy_static::initialize(&MONOTONIC_BASE),
+        RecordClock::Coarse => COARSE_NANOS.store(wall_clock_nanos(), Ordering::Relaxed),
+        RecordClock::Mock => {
+            let _ = MOCK_NANOS.compare_exchange(0, wall_clock_nanos(),
+                                                Ordering::SeqCst, Ordering::SeqCst);
+        },
+        RecordClock::WallClock => ()
+    }
+    RECORD_CLOCK.store(clock as u8, Ordering::SeqCst);
+    if clock == RecordClock::Coarse && ! COARSE_TICKER_ACTIVE.swap(true, Ordering::SeqCst) {
+        let ticker = thread::Builder::new().name(COARSE_TICKER_THREAD_NAME.to_string())
+                                           .spawn(run_coarse_ticker);
+        if ticker.is_err() {
+            // cached time would never change, fall back to reading the clock for every record
+            COARSE_TICKER_ACTIVE.store(false, Ordering::SeqCst);
+            RECORD_CLOCK.store(RecordClock::WallClock as u8, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Returns the timestamp for a log or trace record from the selected record clock.
+/// 
+/// # Return values
+/// the seconds since the epoch and the nanoseconds within the second
+#[inline]
+pub(crate) fn record_timestamp() -> (i64, u32) {
+    let nanos = match record_clock() {
+        RecordClock::WallClock => {
+            let now = Utc::now();
+            return (now.timestamp(), now.timestamp_subsec_nanos())
+        },
+        RecordClock::Monotonic => {
+            let (base_instant, base_nanos) = *MONOTONIC_BASE;
+            base_nanos + base_instant.elapsed().as_nanos() as i64
+        },
+        RecordClock::Coarse => COARSE_NANOS.load(Ordering::Relaxed),
+        RecordClock::Mock => MOCK_NANOS.load(Ordering::Relaxed)
+    };
+    (nanos.div_euclid(NANOS_PER_SEC), nanos.rem_euclid(NANOS_PER_SEC) as u32)
+}
+
+/// Returns the monotonic clock reading for a log or trace record.
+/// The value is independent of the selected record clock and never decreases, even if the
+/// wall clock is adjusted.
+/// 
+/// # Return values
+/// the nanoseconds elapsed since the tick base
+#[inline]
+pub(crate) fn record_ticks() -> u64 { MONOTONIC_BASE.0.elapsed().as_nanos() as u64 }
+
+/// Returns the wall clock time corresponding to tick value 0.
+/// 
+/// # Return values
+/// the seconds since the epoch and the nanoseconds within the second
+#[cfg(feature="mmfile")]
+pub(crate) fn tick_base() -> (i64, u32) {
+    let base_nanos = MONOTONIC_BASE.1;
+    (base_nanos.div_euclid(NANOS_PER_SEC), base_nanos.rem_euclid(NANOS_PER_SEC) as u32)
+}
+
+/// Sets the time of the mock record clock.
+/// 
+/// # Arguments
+/// * `nanos` - the time in nanoseconds since the epoch
+#[inline]
+pub(crate) fn set_mock_nanos(nanos: i64) { MOCK_NANOS.store(nanos, Ordering::SeqCst); }
+
+/// Advances the time of the mock record clock.
+/// 
+/// # Arguments
+/// * `nanos` - the number of nanoseconds to add
+#[inline]
+pub(crate) fn advance_mock_nanos(nanos: i64) { MOCK_NANOS.fetch_add(nanos, Ordering::SeqCst); }
+
+/// Returns the current wall clock time in nanoseconds since the epoch.
+fn wall_clock_nanos() -> i64 {
+    let now = Utc::now();
+    now.timestamp() * NANOS_PER_SEC + now.timestamp_subsec_nanos() as i64
+}
+
+/// Body of the background thread updating the cached time for the coarse record clock.
+/// Terminates as soon as another record clock is selected.
+fn run_coarse_ticker() {
+    loop {
+        while record_clock() == RecordClock::Coarse {
+            COARSE_NANOS.store(wall_clock_nanos(), Ordering::Relaxed);
+            thread::sleep(COARSE_TICK);
+        }
+        COARSE_TICKER_ACTIVE.store(false, Ordering::SeqCst);
+        // coarse clock may have been selected again before the flag was reset, in this case
+        // either this thread or a newly started one continues
+        if record_clock() != RecordClock::Coarse ||
+           COARSE_TICKER_ACTIVE.swap(true, Ordering::SeqCst) { return }
+    }
+}
+
 // Names for all weekdays
 const WEEKDAY_MONDAY: &str = "monday";
 const WEEKDAY_TUESDAY: &str = "tuesday";
@@ -478,10 +799,59 @@ const TS_UNIT_DAYS: &str = "days";
 const TS_UNIT_WEEKS: &str = "weeks";
 const TS_UNIT_MONTHS: &str = "months";
 
-// Regular expression patterns to parse date/time specifications
-const ANCHOR_HOUR_PATTERN: &str = "^([0-9]{2}):([0-9]{2})$";
-const ANCHOR_DOW_PATTERN: &str = "^([a-z]+)\\s+([0-9]{2}):([0-9]{2})$";
-const ANCHOR_DOM_PATTERN: &str = "^([0-9]{2}|ultimo)\\s+([0-9]{2}):([0-9]{2})$";
+// Keyword for the last day of a month in anchor specifications
+const ANCHOR_ULTIMO: &str = "ultimo";
+
+// Names allowed in cron expressions
+const CRON_MONTH_NAMES: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun",
+                                    "jul", "aug", "sep", "oct", "nov", "dec"];
+const CRON_WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const CRON_ZONE_UTC: &str = "utc";
+
+// Maximum number of days searched for the next match of a cron schedule, covers schedules
+// matching February 29th only
+const CRON_MAX_SEARCH_DAYS: i64 = 366*8;
+
+// Names of the record clocks
+const RECORD_CLOCK_WALLCLOCK: &str = "wallclock";
+const RECORD_CLOCK_MONOTONIC: &str = "monotonic";
+const RECORD_CLOCK_COARSE: &str = "coarse";
+const RECORD_CLOCK_MOCK: &str = "mock";
+
+// Update interval and thread name for the coarse record clock
+const COARSE_TICK: std::time::Duration = std::time::Duration::from_millis(1);
+const COARSE_TICKER_THREAD_NAME: &str = "coaly-clock";
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Splits an anchor time specification in the form *HH:MM* into hour and minute.
+/// 
+/// # Return values
+/// Hour and minute strings with two digits each; **None** if the specification is invalid
+fn anchor_time(spec: &str) -> Option<(&str, &str)> {
+    let (hour, min) = spec.split_once(':')?;
+    if ! is_two_digits(hour) || ! is_two_digits(min) { return None }
+    Some((hour, min))
+}
+
+/// Splits an anchor specification in the form *day HH:MM* into day, hour and minute.
+/// The day part must consist of lowercase letters or digits.
+/// 
+/// # Return values
+/// Day, hour and minute strings; **None** if the specification is invalid
+fn anchor_day_time(spec: &str) -> Option<(&str, &str, &str)> {
+    let (day, time) = spec.split_once(char::is_whitespace)?;
+    if day.is_empty() || ! day.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit()) {
+        return None
+    }
+    let (hour, min) = anchor_time(time.trim_start())?;
+    Some((day, hour, min))
+}
+
+/// Indicates whether the given string consists of exactly two decimal digits.
+fn is_two_digits(s: &str) -> bool {
+    s.len() == 2 && s.bytes().all(|b| b.is_ascii_digit())
+}
 
 /// Returns the number of days in the specified month/year combination.
 ///
@@ -500,6 +870,65 @@ fn days_in_month(month: u32, year: i32) -> u32 {
     }
 }
 
+/// Parses a single field of a cron expression.
+///
+/// # Arguments
+/// * `spec` - the field specification
+/// * `min_val` - the minimum value allowed for the field
+/// * `max_val` - the maximum value allowed for the field
+/// * `names` - the names allowed instead of numeric values, the first name denotes `min_val`
+///
+/// # Return values
+/// the bit mask with all values matching the field, bit n is set for value n
+///
+/// # Errors
+/// Returns an error structure if the field specification is invalid
+fn parse_cron_field(spec: &str,
+                    min_val: u32,
+                    max_val: u32,
+                    names: &[&str]) -> CoalyResult<u64> {
+    let inv_field = || coalyxw!(W_CFG_INV_CRON_FIELD, spec.to_string(),
+                                min_val.to_string(), max_val.to_string());
+    let value_of = |s: &str| -> Option<u32> {
+        if let Ok(v) = u32::from_str(s) { return Some(v) }
+        names.iter().position(|n| *n == s).map(|i| i as u32 + min_val)
+    };
+    let mut mask: u64 = 0;
+    for part in spec.split(',') {
+        let (range_spec, step) = match part.split_once('/') {
+            Some((r, s)) => (r, u32::from_str(s).ok().filter(|s| *s > 0).ok_or_else(inv_field)?),
+            None => (part, 1)
+        };
+        let (from, to) = if range_spec == "*" {
+            (min_val, max_val)
+        } else if let Some((f, t)) = range_spec.split_once('-') {
+            (value_of(f).ok_or_else(inv_field)?, value_of(t).ok_or_else(inv_field)?)
+        } else {
+            let v = value_of(range_spec).ok_or_else(inv_field)?;
+            (v, if part.contains('/') { max_val } else { v })
+        };
+        if from < min_val || to > max_val || from > to { return Err(inv_field()) }
+        for v in (from..=to).step_by(step as usize) { mask |= 1 << v; }
+    }
+    Ok(mask)
+}
+
+/// Parses the optional time zone of a cron expression.
+///
+/// # Arguments
+/// * `spec` - the time zone specification, either "utc" or an offset like "+02:00"
+///
+/// # Return values
+/// the time zone, **None** if the specification is invalid
+fn parse_cron_zone(spec: &str) -> Option<CronZone> {
+    if spec == CRON_ZONE_UTC { return Some(CronZone::Fixed(0)) }
+    let sign = match spec.chars().next()? { '+' => 1, '-' => -1, _ => return None };
+    let (hours, minutes) = spec[1..].split_once(':')?;
+    let hours = i32::from_str(hours).ok().filter(|h| *h <= 14)?;
+    let minutes = i32::from_str(minutes).ok().filter(|m| *m <= 59)?;
+    Some(CronZone::Fixed(sign * (hours * 3600 + minutes * 60)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -568,6 +997,41 @@ mod tests {
             ("monthly feb29", "2020-02-29 10:00", "2020-03-31 22:00")
          ];
 
+    // Cron expressions
+    const NEXT_ELAPSES_CRON: &[(&str, &str, &str)] =
+        &[
+            ("0 0 * * *", "2021-06-15 15:55", "2021-06-16 00:00"),
+            ("0 0 * * *", "2021-06-30 23:59", "2021-07-01 00:00"),
+            ("*/15 * * * *", "2021-06-15 15:55", "2021-06-15 16:00"),
+            ("*/15 * * * *", "2021-06-15 16:00", "2021-06-15 16:15"),
+            ("30 2 * * 1-5", "2021-06-18 03:00", "2021-06-21 02:30"),
+            ("30 2 * * mon-fri", "2021-06-18 02:29", "2021-06-18 02:30"),
+            ("0 6,18 1 * *", "2021-06-01 18:00", "2021-07-01 06:00"),
+            ("0 0 29 feb *", "2021-03-01 00:00", "2024-02-29 00:00"),
+            ("0 0 13 * 5", "2021-06-15 12:00", "2021-06-18 00:00"),
+            ("0 0 * * 7", "2021-06-15 12:00", "2021-06-20 00:00"),
+            ("5-10/5 8 * jun *", "2021-06-15 08:05", "2021-06-15 08:10"),
+            ("0 0 1 1 *", "2021-06-15 12:00", "2022-01-01 00:00")
+         ];
+
+    // Invalid cron expressions
+    const INVALID_CRON_EXPRESSIONS: &[&str] =
+        &[
+            "0 0 * *",
+            "0 0 * * * * *",
+            "60 0 * * *",
+            "0 24 * * *",
+            "0 0 0 * *",
+            "0 0 * 13 *",
+            "0 0 * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 * foo *",
+            "0 0 30 feb *",
+            "0 0 * * * cet",
+            "0 0 * * * +25:00"
+         ];
+
     fn test_interval_elapse(interval: &Interval, test_data: &[(&str, &str, &str)]) {
         for (name, start, exp_next) in test_data {
             let dstart = Local.datetime_from_str(start, "%Y-%m-%d %H:%M").unwrap();
@@ -646,4 +1110,58 @@ mod tests {
         let interval = Interval::anchored(span_months_1, anchor_ult_2200);
         test_interval_elapse(&interval, NEXT_ELAPSES_MONTHLY);
     }
+
+    #[test]
+    fn test_cron_next_elapse() {
+        for (expr, start, exp_next) in NEXT_ELAPSES_CRON {
+            let sched = CronSchedule::from_str(expr).unwrap();
+            let dstart = Local.datetime_from_str(start, "%Y-%m-%d %H:%M").unwrap();
+            let dexp_next = Local.datetime_from_str(exp_next, "%Y-%m-%d %H:%M").unwrap();
+            assert_eq!(dexp_next, sched.next_elapse(&dstart), "{}", expr);
+        }
+        // fixed offset, 12:00 at UTC+02:00 is 10:00 UTC
+        let sched = CronSchedule::from_str("0 12 * * * +02:00").unwrap();
+        let dstart = Utc.with_ymd_and_hms(2021, 6, 15, 10, 30, 0).unwrap().with_timezone(&Local);
+        let dexp_next = Utc.with_ymd_and_hms(2021, 6, 16, 10, 0, 0).unwrap();
+        assert_eq!(dexp_next, sched.next_elapse(&dstart));
+        let sched = CronSchedule::from_str("0 0 * * * utc").unwrap();
+        let dexp_next = Utc.with_ymd_and_hms(2021, 6, 16, 0, 0, 0).unwrap();
+        assert_eq!(dexp_next, sched.next_elapse(&dstart));
+    }
+
+    #[test]
+    fn test_cron_parse_errors() {
+        for expr in INVALID_CRON_EXPRESSIONS {
+            assert!(CronSchedule::from_str(expr).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_record_clock() {
+        assert!(RecordClock::from_str("Monotonic") == Ok(RecordClock::Monotonic));
+        assert!(RecordClock::from_str("coarse") == Ok(RecordClock::Coarse));
+        assert!(RecordClock::from_str("sundial").is_err());
+        let wall_secs = Utc::now().timestamp();
+        // mock clock stands still until set or advanced
+        set_record_clock(RecordClock::Mock);
+        set_mock_nanos(wall_secs * NANOS_PER_SEC + 500);
+        assert_eq!((wall_secs, 500), record_timestamp());
+        advance_mock_nanos(NANOS_PER_SEC);
+        assert_eq!((wall_secs + 1, 500), record_timestamp());
+        // monotonic clock never goes back and stays close to the wall clock
+        set_record_clock(RecordClock::Monotonic);
+        let ts1 = record_timestamp();
+        let ts2 = record_timestamp();
+        assert!(ts2 >= ts1);
+        assert!((ts2.0 - Utc::now().timestamp()).abs() <= 1);
+        // coarse clock is updated by the background thread
+        set_record_clock(RecordClock::Coarse);
+        let ts1 = record_timestamp();
+        thread::sleep(std::time::Duration::from_millis(20));
+        let ts2 = record_timestamp();
+        assert!(ts2 > ts1);
+        assert!((ts2.0 - Utc::now().timestamp()).abs() <= 1);
+        set_record_clock(RecordClock::WallClock);
+        assert!((record_timestamp().0 - Utc::now().timestamp()).abs() <= 1);
+    }
 }
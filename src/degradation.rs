@@ -0,0 +1,156 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Automatic degradation of the logging pipeline.
+//! The worker thread checks the pipeline counters about once per second. If output failures
+//! or overload persist for the number of seconds given in the degradation profile, the worker
+//! switches to degraded mode, processing only the profile's record levels and writing only the
+//! profile's resource kinds. Degraded mode is left after the number of seconds without failures
+//! or overload given in the profile.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crate::config::systemproperties::DegradationProfile;
+
+/// Runtime state of the degradation profile.
+pub(crate) struct DegradationMonitor {
+    // the degradation profile
+    profile: DegradationProfile,
+    // number of consecutive seconds with failures or overload in normal mode, resp. without
+    // failures or overload in degraded mode
+    streak: u64,
+    // failure count upon last check
+    last_failures: u64,
+    // processed record count upon last check
+    last_records: u64,
+    // time elapsed since last check, not yet accounted for
+    pending: Duration
+}
+impl DegradationMonitor {
+    /// Creates the runtime state for the given degradation profile.
+    ///
+    /// # Arguments
+    /// * `profile` - the degradation profile
+    /// * `failures` - the current failure count
+    /// * `records` - the current processed record count
+    pub(crate) fn new(profile: &DegradationProfile, failures: u64, records: u64) -> Self {
+        DegradationMonitor {
+            profile: profile.clone(),
+            streak: 0,
+            last_failures: failures,
+            last_records: records,
+            pending: Duration::ZERO
+        }
+    }
+
+    /// Returns the degradation profile.
+    #[inline]
+    pub(crate) fn profile(&self) -> &DegradationProfile { &self.profile }
+
+    /// Checks the pipeline counters for failures or overload.
+    /// Counters are evaluated only, if at least one second has elapsed since the last
+    /// evaluation.
+    ///
+    /// # Arguments
+    /// * `failures` - the current failure count
+    /// * `records` - the current processed record count
+    /// * `elapsed` - the time elapsed since the last check
+    ///
+    /// # Return values
+    /// **Some(true)** if degraded mode shall be entered, **Some(false)** if degraded mode shall
+    /// be left, **None** if the mode remains unchanged
+    pub(crate) fn check(&mut self,
+                        failures: u64,
+                        records: u64,
+                        elapsed: Duration) -> Option<bool> {
+        self.pending += elapsed;
+        let secs = self.pending.as_secs();
+        if secs == 0 { return None }
+        self.pending -= Duration::from_secs(secs);
+        let failed = failures > self.last_failures;
+        let rate = self.profile.overload_rate();
+        let overloaded = rate > 0 && records - self.last_records > rate * secs;
+        self.last_failures = failures;
+        self.last_records = records;
+        let degraded = is_degraded();
+        // in normal mode count seconds with trouble, in degraded mode seconds without
+        if (failed || overloaded) != degraded {
+            self.streak += secs;
+        } else {
+            self.streak = 0;
+        }
+        let limit = if degraded { self.profile.recover_after() }
+                    else { self.profile.degrade_after() };
+        if self.streak < limit { return None }
+        self.streak = 0;
+        Some(! degraded)
+    }
+}
+
+/// Indicates whether the pipeline currently runs in degraded mode.
+#[inline]
+pub(crate) fn is_degraded() -> bool { DEGRADED.load(Ordering::Relaxed) }
+
+/// Enters or leaves degraded mode.
+///
+/// # Arguments
+/// * `degraded` - **true** to enter degraded mode, **false** to leave it
+#[inline]
+pub(crate) fn set_degraded(degraded: bool) { DEGRADED.store(degraded, Ordering::Relaxed) }
+
+// process wide degradation state, read by output resources
+static DEGRADED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degradation_monitor() {
+        let mut profile = DegradationProfile::default();
+        profile.set_degrade_after(3);
+        profile.set_recover_after(2);
+        profile.set_overload_rate(100);
+        let sec = Duration::from_secs(1);
+        let mut mon = DegradationMonitor::new(&profile, 0, 0);
+        assert!(mon.check(1, 10, Duration::from_millis(500)).is_none());
+        assert!(mon.check(1, 10, Duration::from_millis(500)).is_none());
+        assert!(mon.check(2, 20, sec).is_none());
+        assert_eq!(Some(true), mon.check(2, 500, sec));
+        set_degraded(true);
+        assert!(mon.check(4, 520, sec).is_none());
+        assert!(mon.check(4, 530, sec).is_none());
+        assert_eq!(Some(false), mon.check(4, 540, sec));
+        set_degraded(false);
+        assert!(mon.check(4, 540, sec).is_none());
+    }
+}
@@ -32,10 +32,14 @@
 
 //! Common exceptions for all parts of Coaly.
 
-use regex::Regex;
 use std::collections::HashMap;
 use std::env;
-use std::io::{self, Write};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+pub(crate) mod pipetrace;
+pub(crate) mod reporting;
 
 /// Raise an exception with severity error
 #[macro_export]
@@ -64,14 +68,32 @@ macro_rules! coalyxw {
 // General errors
 pub const E_FILE_NOT_FOUND: &str = "E-FileNotFound";
 pub const E_FILE_READ_ERR: &str = "E-FileReadError";
+pub const E_FILE_INV_BINARY_FORMAT: &str = "E-FileInvalidBinaryFormat";
+pub const W_FILE_TORN_FRAMES: &str = "W-FileTornFrames";
+pub const E_ENC_INV_KEY_FILE: &str = "E-Enc-InvalidKeyFile";
+pub const E_ENC_KEY_MISSING: &str = "E-Enc-KeyMissing";
+pub const E_ENC_INV_FILE: &str = "E-Enc-InvalidFile";
+pub const E_ENC_DECRYPTION_FAILED: &str = "E-Enc-DecryptionFailed";
+pub const E_ENC_ENCRYPTION_FAILED: &str = "E-Enc-EncryptionFailed";
 pub const E_FILE_WRITE_ERR: &str = "E-FileWriteError";
 pub const E_FILE_CRE_ERR: &str = "E-FileCreationError";
+pub const E_FILE_LOCK_ERR: &str = "E-FileLockError";
+pub const E_INV_OUTPUT_PATH: &str = "E-InvalidOutputPath";
+pub const E_IO_ERR: &str = "E-IoError";
+pub const E_EVENTLOG_WRITE_ERR: &str = "E-EventLogWriteError";
+pub const E_INJECTED_WRITE_ERR: &str = "E-InjectedWriteError";
+pub const E_INJECTED_ROVR_ERR: &str = "E-InjectedRolloverError";
+pub const E_TEST_RECORDS_ABOVE_LEVEL: &str = "E-Test-RecordsAboveLevel";
 pub const E_INTERNAL_INV_TEMPLATE: &str = "E-Int-InvalidResourceTemplate";
 pub const E_INTERNAL_NOT_YET_IMPLEMENTED: &str = "E-Int-NotYetImplemented";
 pub const E_INTERNAL_EVENT_FAILED: &str = "E-Int-EventFailed";
 pub const E_INTERNAL_EVENTS_FAILED: &str = "E-Int-EventsFailed";
 pub const E_INVALID_URL: &str = "E-Net-InvalidUrl";
 pub const E_SOCKET_CRE_ERR: &str = "E-Net-SocketCreationError";
+pub const E_TLS_CONFIG_ERR: &str = "E-Net-TlsConfigError";
+pub const E_TLS_FILE_ERR: &str = "E-Net-TlsFileError";
+pub const E_HOST_RESOLVE_ERR: &str = "E-Net-HostResolutionError";
+pub const E_DECOMPRESSION_ERR: &str = "E-Net-DecompressionError";
 pub const E_SOCKET_READ_ERR: &str = "E-Net-SocketReadError";
 pub const E_SOCKET_WRITE_ERR: &str = "E-Net-SocketWriteError";
 pub const E_DESER_ERR: &str = "E-DeserializationError";
@@ -83,6 +105,11 @@ pub const E_INVALID_ADDR_PATTERN: &str = "E-Net-InvalidAddressPattern";
 pub const E_IP4_OCTET_TOO_LARGE: &str = "E-Net-IP4OctetTooLarge";
 pub const E_IP_PORT_TOO_LARGE: &str = "E-Net-IPPortTooLarge";
 pub const E_ALREADY_CONNECTED: &str = "E-Net-AlreadyConnected";
+pub const E_WEBHOOK_REJECTED: &str = "E-Net-WebhookRejected";
+pub const E_GELF_MSG_TOO_LARGE: &str = "E-Net-GelfMessageTooLarge";
+pub const E_HTTP_BATCH_REJECTED: &str = "E-Net-HttpBatchRejected";
+pub const E_HTTP_BATCH_DISCARDED: &str = "E-Net-HttpBatchDiscarded";
+pub const E_METRICS_BIND_ERR: &str = "E-Net-MetricsBindError";
 
 // TOML scanner related errors
 pub const E_CFG_TOML_2DIGIT_DAY_REQUIRED: &str = "E-Cfg-Toml-TwoDigitDayRequired";
@@ -147,7 +174,10 @@ pub const E_CFG_INV_NW_PROTOCOL: &str = "E-Cfg-InvalidNetworkProtocol";
 pub const E_CFG_NW_PROT_MISMATCH: &str = "E-Cfg-NetworkProtocolMismatch";
 
 pub const E_CFG_TOML_PARSE_FAILED: &str = "E-Cfg-Toml-ParseFailed";
+pub const E_CFG_TOML_STR_PARSE_FAILED: &str = "E-Cfg-Toml-StrParseFailed";
 pub const E_CFG_FOUND_ISSUES: &str = "E-Cfg-FoundIssues";
+pub const E_CFG_RECONFIG_FAILED: &str = "E-Cfg-ReconfigurationFailed";
+pub const E_CFG_INV_RUNTIME_MODE_CHANGE: &str = "E-Cfg-InvalidRuntimeModeChange";
 
 // Rollover related errors
 pub const E_ROVR_FAILED: &str = "E-Rovr-Failed";
@@ -160,6 +190,40 @@ pub const W_ROVR_COMPRESS_FAILED: &str = "W-Rovr-CompressFailed";
 pub const W_ROVR_GENERIC_FAILURE: &str = "W-Rovr-GenericFailure";
 pub const W_ROVR_GENERIC_FILE_FAILURE: &str = "W-Rovr-GenericFileFailure";
 pub const W_ROVR_USING_OLD: &str = "W-Rovr-UsingOldOutputFile";
+pub const W_ROVR_CHECKSUM_FAILED: &str = "W-Rovr-ChecksumFailed";
+pub const W_CLEANUP_FAILED: &str = "W-Cleanup-Failed";
+pub const W_THROTTLE_SUPPRESSED: &str = "W-Throttle-RecordsSuppressed";
+pub const W_BUFFER_DUPLICATES_SUPPRESSED: &str = "W-Buffer-DuplicatesSuppressed";
+pub const W_DEGRADED_ENTERED: &str = "W-Degraded-ModeEntered";
+pub const W_DEGRADED_LEFT: &str = "W-Degraded-ModeLeft";
+pub const W_FMT_ARGS_PANICKED: &str = "W-Fmt-ArgumentsPanicked";
+pub const W_PROBLEMS_SUPPRESSED: &str = "W-Problems-Suppressed";
+
+// Pipeline trace messages
+pub const W_PIPE_LEVEL_NOT_ACCEPTED: &str = "W-Pipe-LevelNotAccepted";
+pub const W_PIPE_LEVEL_DISABLED: &str = "W-Pipe-LevelDisabled";
+pub const W_PIPE_DEGRADED: &str = "W-Pipe-Degraded";
+pub const W_PIPE_SUPPRESSED: &str = "W-Pipe-Suppressed";
+pub const W_PIPE_TRIGGER_SUPPRESSED: &str = "W-Pipe-TriggerSuppressed";
+pub const W_PIPE_RES_LEVEL: &str = "W-Pipe-Res-LevelNotAssociated";
+pub const W_PIPE_RES_FILTERED: &str = "W-Pipe-Res-Filtered";
+pub const W_PIPE_RES_NOT_SAMPLED: &str = "W-Pipe-Res-NotSampled";
+pub const W_PIPE_RES_UNAVAILABLE: &str = "W-Pipe-Res-Unavailable";
+pub const W_PIPE_RES_THROTTLED: &str = "W-Pipe-Res-Throttled";
+pub const W_PIPE_RES_DUPLICATE: &str = "W-Pipe-Res-Duplicate";
+pub const W_PIPE_RES_BUFFER_FULL: &str = "W-Pipe-Res-BufferFull";
+pub const W_PIPE_RES_WRITTEN: &str = "W-Pipe-Res-Written";
+
+// Resource creation errors
+pub const E_RES_CREATE_FAILED: &str = "E-Res-CreateFailed";
+pub const W_RES_CREATE_RETRY: &str = "W-Res-CreateRetry";
+pub const W_RES_OPEN_FILE_LIMIT: &str = "W-Res-OpenFileLimitReached";
+pub const W_RES_USING_FALLBACK: &str = "W-Res-UsingFallbackPath";
+pub const W_RES_CONNECTION_LOST: &str = "W-Res-ConnectionLost";
+pub const W_RES_PROBE_WRITE_FAILED: &str = "W-Res-ProbeWriteFailed";
+pub const W_RES_PROBE_VERIFY_FAILED: &str = "W-Res-ProbeVerifyFailed";
+pub const W_RES_PROBE_LOW_DISK_SPACE: &str = "W-Res-ProbeLowDiskSpace";
+pub const W_RES_PROBE_UNREACHABLE: &str = "W-Res-ProbeUnreachable";
 
 // Server errors
 pub const E_SRV_CFG_FILE_NOT_SPECIFIED: &str = "E-Srv-CfgFileNotSpecified";
@@ -170,10 +234,15 @@ pub const E_SRV_ACCESS_DENIED: &str = "E-Srv-AccessDenied";
 pub const E_SRV_CLIENT_LIMIT_EXCEEDED: &str = "E-Srv-ClientLimitExceeded";
 pub const E_SRV_INTERNAL_ERROR: &str = "E-Srv-InternalError";
 pub const E_SRV_ACC_CXN_FAILED: &str = "E-Srv-AcceptConnectionFailed";
+pub const E_SRV_BIND_FAILED: &str = "E-Srv-BindFailed";
+pub const E_SRV_TLS_CERT_MISSING: &str = "E-Srv-TlsCertificateMissing";
 
 // Coaly configuration related errors
 pub const W_CFG_UNKNOWN_KEY: &str = "W-Cfg-UnknownKey";
+pub const W_CFG_DID_YOU_MEAN: &str = "W-Cfg-DidYouMean";
+pub const W_CFG_VALID_VALUES: &str = "W-Cfg-ValidValues";
 pub const W_CFG_KEY_NOT_A_STRING: &str = "W-Cfg-KeyIsNotAString";
+pub const W_CFG_KEY_NOT_A_BOOL: &str = "W-Cfg-KeyIsNotABoolean";
 pub const W_CFG_KEY_NOT_A_TABLE: &str = "W-Cfg-KeyIsNotATable";
 pub const W_CFG_KEY_NOT_AN_ARRAY: &str = "W-Cfg-KeyIsNotAnArray";
 pub const W_CFG_NUM_REQUIRED: &str = "W-Cfg-NumberRequired";
@@ -194,6 +263,10 @@ pub const W_CFG_INV_ROVER_COND_PATTERN: &str = "W-Cfg-InvalidRolloverCondPattern
 pub const W_CFG_MISSING_ROVR_COND: &str = "W-Cfg-MissingRolloverCondition";
 pub const W_CFG_INV_ROLLOVER_COND: &str = "W-Cfg-InvalidRolloverCondition";
 pub const W_CFG_COMPR_NOT_SUPPORTED: &str = "W-Cfg-CompressionNotSupported";
+pub const W_CFG_BINARY_FORMAT_NOT_SUPPORTED: &str = "W-Cfg-BinaryFormatNotSupported";
+pub const W_CFG_ENCRYPTION_NOT_SUPPORTED: &str = "W-Cfg-EncryptionNotSupported";
+pub const W_CFG_CHECKSUMS_NOT_SUPPORTED: &str = "W-Cfg-ChecksumsNotSupported";
+pub const W_CFG_ENCRYPTION_KEY_AMBIGUOUS: &str = "W-Cfg-EncryptionKeyAmbiguous";
 pub const W_CFG_UNKNOWN_COMPR_ALGO: &str = "W-Cfg-UnknownCompressionAlgorithm";
 pub const W_CFG_INV_COMPR_ALGO: &str = "W-Cfg-InvalidCompressionAlgorithm";
 pub const W_CFG_INV_KEEP_COUNT: &str = "W-Cfg-InvalidKeepCount";
@@ -207,12 +280,15 @@ pub const W_CFG_UNKNOWN_BUF_FLUSH_CONDITION: &str = "W-Cfg-UnknownBufferFlushCon
 pub const W_CFG_INV_BUF_FLUSH_CONDITION: &str = "W-Cfg-InvalidBufferFlushCondition";
 pub const W_CFG_DUP_BUF_FLUSH_CONDITION: &str = "W-Cfg-DuplicateBufferFlushCondition";
 pub const W_CFG_RECLEN_EXCEEDS_SIZE: &str = "W-Cfg-RecLenExceedsSize";
+pub const W_CFG_MAX_SIZE_BELOW_SIZE: &str = "W-Cfg-MaxContentSizeBelowContentSize";
 pub const W_CFG_INV_NUM_IN_INTVL: &str = "W-Cfg-InvalidNumberInInterval";
 pub const W_CFG_INV_UNIT_IN_INTVL: &str = "W-Cfg-InvalidUnitInInterval";
 pub const W_CFG_INV_RECFMT_HDR: &str = "W-Cfg-InvalidRecordFormatHeader";
 pub const W_CFG_INV_RECFMT_SPEC: &str = "W-Cfg-InvalidRecordFormatSpecification";
+pub const W_CFG_INV_RECFMT_LAYOUT: &str = "W-Cfg-InvalidRecordFormatLayout";
 pub const W_CFG_INV_DFMT_ATTR: &str = "W-Cfg-InvalidDateTimeFormatAttribute";
 pub const W_CFG_INV_DTFMT_SPEC: &str = "W-Cfg-InvalidDateTimeFormatSpecifier";
+pub const W_CFG_INV_DTFMT_ZONE: &str = "W-Cfg-InvalidDateTimeFormatZone";
 pub const W_CFG_OUTFMT_TRIGGERS_EMPTY: &str = "W-Cfg-OutputFormatTriggersEmpty";
 pub const W_CFG_OUTFMT_LEVELS_EMPTY: &str = "W-Cfg-OutputFormatLevelsEmpty";
 pub const W_CFG_INV_MODES_HDR: &str = "W-Cfg-InvalidModesHeader";
@@ -223,13 +299,58 @@ pub const W_CFG_INV_MODE_TRIGGER: &str = "W-Cfg-InvalidModeTrigger";
 pub const W_CFG_MISSING_MODE_NAME: &str = "W-Cfg-MissingModeName";
 pub const W_CFG_MODE_VALUE_IGNORED: &str = "W-Cfg-ModeValueIgnored";
 pub const W_CFG_MODE_SCOPE_IGNORED: &str = "W-Cfg-ModeScopeIgnored";
+pub const W_CFG_INV_SAMPLE_RATE: &str = "W-Cfg-InvalidSampleRate";
+pub const W_CFG_MODE_DECORATION_IGNORED: &str = "W-Cfg-ModeDecorationIgnored";
+pub const W_CFG_MODE_SUPPRESSION_IGNORED: &str = "W-Cfg-ModeSuppressionIgnored";
+pub const W_CFG_MODE_CAPTURE_IGNORED: &str = "W-Cfg-ModeCaptureIgnored";
+pub const W_CFG_CAPTURE_NAME_MISSING: &str = "W-Cfg-CaptureNameMissing";
+pub const W_CFG_INV_SUPPRESSED_TRIGGERS: &str = "W-Cfg-InvalidSuppressedTriggers";
+pub const W_CFG_INV_FN_REPLACEMENT_CHAR: &str = "W-Cfg-InvalidFileNameReplacementChar";
 pub const W_CFG_INV_RESOURCES_HDR: &str = "W-Cfg-InvalidResourcesHeader";
 pub const W_CFG_INV_RES_ATTR: &str = "W-Cfg-InvalidResourceAttribute";
 pub const W_CFG_INV_RES_KIND: &str = "W-Cfg-InvalidResourceKind";
+pub const W_CFG_INV_RES_ENCODING: &str = "W-Cfg-InvalidResourceEncoding";
+pub const W_CFG_INV_RES_PAYLOAD: &str = "W-Cfg-InvalidResourcePayload";
+pub const W_CFG_INV_RES_BATCH_FORMAT: &str = "W-Cfg-InvalidResourceBatchFormat";
+pub const W_CFG_INV_RES_CREATE_ERROR: &str = "W-Cfg-InvalidResourceCreateErrorPolicy";
+pub const W_CFG_INV_RES_CLOCK_GRANULARITY: &str = "W-Cfg-InvalidResourceClockGranularity";
+pub const W_CFG_INV_RES_TIMESTAMP_DOMAIN: &str = "W-Cfg-InvalidResourceTimestampDomain";
+pub const W_CFG_INV_RES_FILE_FORMAT: &str = "W-Cfg-InvalidResourceFileFormat";
+pub const W_CFG_INV_METRICS_ADDR: &str = "W-Cfg-InvalidMetricsAddress";
+pub const W_CFG_INV_RECORD_CLOCK: &str = "W-Cfg-InvalidRecordClock";
+pub const W_CFG_INV_SUPPRESSION_RULE: &str = "W-Cfg-InvalidSuppressionRule";
+pub const W_CFG_INV_TIME_SLICE: &str = "W-Cfg-InvalidTimeSlice";
+pub const W_CFG_INV_RES_COMPRESSION: &str = "W-Cfg-InvalidResourceCompression";
+pub const W_CFG_STREAM_COMPR_TCP_ONLY: &str = "W-Cfg-StreamCompressionTcpOnly";
+pub const W_CFG_SPOOL_TCP_ONLY: &str = "W-Cfg-SpoolTcpOnly";
+pub const W_CFG_INV_RES_TLS_VERIFY: &str = "W-Cfg-InvalidResourceTlsVerification";
+pub const W_CFG_TLS_PARS_TLS_ONLY: &str = "W-Cfg-TlsParametersTlsOnly";
+pub const W_CFG_TLS_NOT_SUPPORTED: &str = "W-Cfg-TlsNotSupported";
+pub const W_CFG_HTTPS_NOT_SUPPORTED: &str = "W-Cfg-HttpsNotSupported";
+pub const W_CFG_SPILL_WITH_SPOOL: &str = "W-Cfg-SpillIgnoredWithSpool";
+pub const W_CFG_MAX_RECONNECT_DELAY: &str = "W-Cfg-MaxReconnectDelayTooSmall";
+pub const W_CFG_UNKNOWN_FILTER: &str = "W-Cfg-UnknownFilter";
+pub const W_CFG_INV_FILTER_MODULE: &str = "W-Cfg-InvalidFilterModule";
+pub const W_CFG_UNKNOWN_THROTTLE: &str = "W-Cfg-UnknownThrottlePolicy";
+pub const W_CFG_INV_THROTTLE_ATTR: &str = "W-Cfg-InvalidThrottleAttribute";
+pub const W_CFG_UNKNOWN_SAMPLING: &str = "W-Cfg-UnknownSamplingPolicy";
+pub const W_CFG_INV_SAMPLING_ATTR: &str = "W-Cfg-InvalidSamplingAttribute";
+pub const W_CFG_INV_SAMPLING_RATE: &str = "W-Cfg-InvalidSamplingRate";
+pub const W_CFG_INV_COLOR: &str = "W-Cfg-InvalidColor";
+pub const W_CFG_INV_COLOR_MODE: &str = "W-Cfg-InvalidColorMode";
+pub const W_CFG_INV_ADAPTERS_HDR: &str = "W-Cfg-InvalidAdaptersHeader";
+pub const W_CFG_INV_ADAPTER_SPEC: &str = "W-Cfg-InvalidAdapterSpecification";
+pub const W_CFG_INV_ADAPTER_TARGET: &str = "W-Cfg-InvalidAdapterTarget";
+pub const W_CFG_INV_ADAPTER_LEVEL: &str = "W-Cfg-InvalidAdapterLevel";
+pub const W_CFG_INV_ENV_LEVEL: &str = "W-Cfg-InvalidEnvLevel";
+pub const W_CFG_INV_STORAGE_KIND: &str = "W-Cfg-InvalidStorageKind";
+pub const W_CFG_INV_STORAGE_URL: &str = "W-Cfg-InvalidStorageUrl";
+pub const W_CFG_MEANINGLESS_STORAGE_PAR: &str = "W-Cfg-MeaninglessStoragePar";
 pub const W_CFG_INV_RES_SCOPE: &str = "W-Cfg-InvalidResourceScope";
 pub const W_CFG_INV_RES_SPEC: &str = "W-Cfg-InvalidResourceSpecification";
 pub const W_CFG_INV_RES_URL: &str = "W-Cfg-InvalidResourceUrl";
 pub const W_CFG_RES_FN_MISSING: &str = "W-Cfg-ResourceFileNameMissing";
+pub const W_CFG_RES_PIPE_NAME_MISSING: &str = "W-Cfg-ResourcePipeNameMissing";
 pub const W_CFG_FILE_SIZE_MISSING: &str = "W-Cfg-FileSizeMissing";
 pub const W_CFG_RECFMT_INCOMPLETE: &str = "W-Cfg-RecordFormatIncomplete";
 pub const W_CFG_ANCHOR_MIN_REQ: &str = "W-Cfg-AnchorMinuteRequired";
@@ -237,6 +358,8 @@ pub const W_CFG_ANCHOR_HHMM_REQ: &str = "W-Cfg-AnchorHourMinRequired";
 pub const W_CFG_ANCHOR_DOWHM_REQ: &str = "W-Cfg-AnchorDowHourMinRequired";
 pub const W_CFG_ANCHOR_DOMHM_REQ: &str = "W-Cfg-AnchorDomHourMinRequired";
 pub const W_CFG_ANCHOR_NOT_ALLOWED: &str = "W-Cfg-AnchorNotAllowed";
+pub const W_CFG_INV_CRON_EXPR: &str = "W-Cfg-InvalidCronExpression";
+pub const W_CFG_INV_CRON_FIELD: &str = "W-Cfg-InvalidCronField";
 pub const W_CFG_MEANINGLESS_RES_PAR: &str = "W-Cfg-MeaninglessResourcePar";
 pub const W_CFG_MEANINGLESS_ROVR_ATTR: &str = "W-Cfg-MeaninglessRolloverAttr";
 pub const W_CFG_ANONYMOUS_OBSERVER_IGNORED: &str = "W-Cfg-AnonymousObserverIgnored";
@@ -244,6 +367,13 @@ pub const W_CFG_INV_OBSERVER_NAME: &str = "W-Cfg-InvalidObserverName";
 pub const W_CFG_INV_OBSERVER_VALUE: &str = "W-Cfg-InvalidObserverValue";
 pub const W_CFG_INV_FALLBACK_PATH: &str = "W-Cfg-InvalidFallbackPath";
 pub const W_CFG_INV_OUTPUT_PATH: &str = "W-Cfg-InvalidOutputPath";
+pub const W_CFG_RECONFIG_SERVER_OUTPUT: &str = "W-Cfg-ReconfigurationServerOutput";
+pub const W_CFG_RING_ROLLOVER: &str = "W-Cfg-RingRollover";
+pub const W_CFG_INV_PROBLEM_LEVEL: &str = "W-Cfg-InvalidProblemLevel";
+pub const W_CFG_INV_PROBLEM_TARGET: &str = "W-Cfg-InvalidProblemTarget";
+pub const W_CFG_INV_PROBLEM_LAYOUT: &str = "W-Cfg-InvalidProblemLayout";
+pub const W_CFG_INV_RES_SHARING: &str = "W-Cfg-InvalidResourceSharing";
+pub const W_CFG_SHARING_IGNORED: &str = "W-Cfg-SharingIgnored";
 
 lazy_static! {
     /// Singleton instance of hash table with language dependent resources
@@ -281,7 +411,9 @@ pub struct CoalyException {
     // Argument values in case the message contains placeholders
     args: Option<Vec<String>>,
     // optional root cause
-    cause: Option<Box<CoalyException>>
+    cause: Option<Box<CoalyException>>,
+    // optional hint how to solve the problem, appended to the message
+    hint: Option<Box<CoalyException>>
 }
 impl CoalyException {
     /// Creates an exception without arguments.
@@ -291,7 +423,7 @@ impl CoalyException {
     /// * `severity' - the exception severity
     #[inline]
     pub fn new (id: &'static str, severity: Severity) -> CoalyException {
-        CoalyException { id, severity, args: None, cause: None }
+        CoalyException { id, severity, args: None, cause: None, hint: None }
     }
 
     /// Creates an exception with an arbitrary number of arguments.
@@ -303,7 +435,7 @@ impl CoalyException {
     pub fn with_args (id: &'static str, severity: Severity, args: &[String]) -> CoalyException {
         let mut v = Vec::<String>::new();
         v.extend(args.iter().map(|e| { (*e).to_string() }));
-        CoalyException { id, severity, args: Some(v), cause: None }
+        CoalyException { id, severity, args: Some(v), cause: None, hint: None }
     }
 
     /// Sets the root cause for this exception.
@@ -313,6 +445,13 @@ impl CoalyException {
     #[inline]
     pub fn set_cause(&mut self, cause: CoalyException) { self.cause = Some(Box::new(cause)); }
 
+    /// Sets a hint how to solve the problem, the hint is appended to the exception message.
+    /// 
+    /// # Arguments
+    /// * `hint' - the exception describing the hint
+    #[inline]
+    pub fn set_hint(&mut self, hint: CoalyException) { self.hint = Some(Box::new(hint)); }
+
     /// Returns the exception ID.
     /// Severity prefix E_ for errors, W_ for warnings.
     /// Prefix is followed by a component indicator, if component specific.
@@ -356,6 +495,20 @@ impl CoalyException {
     /// # Arguments
     /// * `localized_texts' - the hash map with the language dependent resources
     pub fn evaluate(&self, localized_texts: &HashMap<String, String>) -> String {
+        let mut res = self.evaluate_msg(localized_texts);
+        if let Some(hint) = &self.hint {
+            res.push(' ');
+            res.push_str(&hint.evaluate(localized_texts));
+        }
+        res
+    }
+
+    /// Localizes the exception without hint and substitutes placeholder variables with their
+    /// values.
+    /// 
+    /// # Arguments
+    /// * `localized_texts' - the hash map with the language dependent resources
+    fn evaluate_msg(&self, localized_texts: &HashMap<String, String>) -> String {
         let mut res = String::with_capacity(160);
         let eid = &self.id.to_string();
         let msg = localized_texts.get(self.id).unwrap_or(eid);
@@ -389,15 +542,36 @@ impl CoalyException {
     }
 }
 
-/// Logs the specified problems to an emergency resource.
-pub fn log_problems(probs: &[CoalyException]) {
-    // TODO try file/syslog first
-    let stderr = io::stderr();
-    let mut handle = stderr.lock();
-    for p in probs {
-        let _ = handle.write_all(p.localized_message().as_bytes());
+impl Display for CoalyException {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localized_message())
     }
 }
+impl Error for CoalyException {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_deref().map(|c| c as &(dyn Error + 'static))
+    }
+}
+impl From<io::Error> for CoalyException {
+    /// Converts an I/O error to an exception with severity error.
+    /// I/O errors created from an exception are converted back to the original exception.
+    fn from(err: io::Error) -> Self {
+        if let Some(ex) = err.get_ref().and_then(|e| e.downcast_ref::<CoalyException>()) {
+            return ex.clone()
+        }
+        CoalyException::with_args(E_IO_ERR, Severity::Error, &[err.to_string()])
+    }
+}
+impl From<CoalyException> for io::Error {
+    /// Converts an exception to an I/O error of kind other, holding the exception as inner
+    /// error.
+    fn from(ex: CoalyException) -> Self { io::Error::other(ex) }
+}
+
+/// Logs the specified problems.
+/// Problems are written to stderr, unless the configuration specifies other settings under
+/// TOML table system.problems.
+pub fn log_problems(probs: &[CoalyException]) { reporting::report_problems(probs); }
 
 #[cfg(unix)]
 fn locale() -> String {
@@ -422,17 +596,16 @@ fn locale() -> String {
 /// * `lang_id` - the language ID
 fn parse_resource(contents: &str) -> HashMap<String, String> {
     let mut t = HashMap::<String, String>::new();
-    let ignore_pattern = Regex::new(r"^\s*#.*").unwrap();
-    let def_pattern = Regex::new(r"^([\w\d_\-]+)\s+(.*)$").unwrap();
     for line in contents.split('\n') {
         let line = line.trim();
-        if line.is_empty() || ignore_pattern.is_match(line) {
+        if line.is_empty() || line.starts_with('#') {
             continue;
         }
-        if let Some(groups) = def_pattern.captures(line) {
-            let id = groups.get(1).unwrap().as_str();
-            let text = groups.get(2).unwrap().as_str();
-            t.insert(id.to_string(), text.to_string());
+        // resource definition is an ID followed by the text, separated by white space
+        if let Some((id, text)) = line.split_once(char::is_whitespace) {
+            if id.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+                t.insert(id.to_string(), text.trim_start().to_string());
+            }
         }
     }
     t
@@ -544,4 +717,27 @@ mod tests {
         let x = coalyxw!(ID_P3, ARG_P3_1.to_string());
         verify(&x, ID_P3, Severity::Warning, &Some(&[ARG_P3_1]), LOC_TEXT_P3_LINE_ONLY);
     }
+
+    // Make sure, exceptions can be used with the question mark operator alongside other errors
+    #[test]
+    fn std_error_conversions() {
+        fn read_missing() -> Result<Vec<u8>, CoalyException> {
+            Ok(std::fs::read("/nonexistent/coaly/file")?)
+        }
+        let x = read_missing().unwrap_err();
+        assert_eq!(x.id(), E_IO_ERR);
+        assert_eq!(x.severity(), Severity::Error);
+        assert_eq!(x.to_string(), x.localized_message());
+        let mut outer = coalyxe!(E_FILE_READ_ERR, String::from("f"));
+        outer.set_cause(x);
+        assert_eq!(outer.source().map(|c| c.to_string()),
+                   Some(outer.cause.as_ref().unwrap().localized_message()));
+        // round trip through an I/O error keeps the exception
+        let boxed: Box<dyn Error> = Box::new(coalyxe!(ID_P1, ARG_P1.to_string()));
+        assert!(boxed.downcast_ref::<CoalyException>().is_some());
+        let io_err = io::Error::from(coalyxw!(ID_P1, ARG_P1.to_string()));
+        assert_eq!(io_err.kind(), io::ErrorKind::Other);
+        let x = CoalyException::from(io_err);
+        verify(&x, ID_P1, Severity::Warning, &Some(&[ARG_P1]), LOC_TEXT_P1);
+    }
 }
\ No newline at end of file
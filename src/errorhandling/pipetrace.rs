@@ -0,0 +1,116 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+//! Tracing of the filtering decisions made for log and trace records.
+//! Intended to diagnose, why expected records never appear in output. If enabled through
+//! parameter trace_pipeline in TOML table system.problems, every n-th record is sampled, and
+//! all decisions about passing or dropping the sampled record are reported as problems.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::coalyxw;
+use crate::record::RecordLevelId;
+use crate::record::recorddata::RecordData;
+use super::*;
+
+/// Indicates whether the next record shall be traced.
+/// Invoked once for every record created by the application.
+///
+/// # Return values
+/// **true** if pipeline tracing is enabled and the record is within the sample
+pub(crate) fn sampled() -> bool {
+    let interval = SAMPLING_INTERVAL.load(Ordering::Relaxed);
+    if interval == 0 { return false }
+    RECORD_COUNT.fetch_add(1, Ordering::Relaxed).is_multiple_of(interval)
+}
+
+/// Sets the sampling interval for pipeline tracing.
+///
+/// # Arguments
+/// * `interval` - the interval, 1 traces every record, 0 disables pipeline tracing
+pub(crate) fn set_sampling_interval(interval: u64) {
+    SAMPLING_INTERVAL.store(interval, Ordering::Relaxed);
+    RECORD_COUNT.store(0, Ordering::Relaxed);
+}
+
+/// Reports a record dropped in the application thread, because its level is not accepted by
+/// any output resource, if the record is within the sample.
+///
+/// # Arguments
+/// * `level` - the record level
+/// * `file_name` - the name of the source code file, where the record was issued
+/// * `line_nr` - the line number in the source code file, where the record was issued
+pub(crate) fn level_not_accepted(level: RecordLevelId, file_name: &str, line_nr: u32) {
+    if ! sampled() { return }
+    reporting::report_problems(&[coalyxw!(W_PIPE_LEVEL_NOT_ACCEPTED, level.to_string(),
+                                          file_name.to_string(), line_nr.to_string())]);
+}
+
+/// Reports a decision of the worker thread about a traced record.
+///
+/// # Arguments
+/// * `record` - the record, must have been sampled for tracing
+/// * `msg_id` - the ID of the message describing the decision
+pub(crate) fn decision(record: &dyn RecordData, msg_id: &'static str) {
+    let (level, file_name, line_nr) = record_args(record);
+    reporting::report_problems(&[coalyxw!(msg_id, level, file_name, line_nr)]);
+}
+
+/// Reports a decision of an output resource about a traced record.
+///
+/// # Arguments
+/// * `record` - the record, must have been sampled for tracing
+/// * `msg_id` - the ID of the message describing the decision
+/// * `resource` - the label of the output resource
+pub(crate) fn resource_decision(record: &dyn RecordData, msg_id: &'static str,
+                                 resource: &str) {
+    let (level, file_name, line_nr) = record_args(record);
+    reporting::report_problems(&[coalyxw!(msg_id, level, file_name, line_nr,
+                                          resource.to_string())]);
+}
+
+/// Returns the message arguments identifying a record.
+///
+/// # Arguments
+/// * `record` - the record
+///
+/// # Return values
+/// record level, source file name and line number
+fn record_args(record: &dyn RecordData) -> (String, String, String) {
+    let line_nr = record.line_nr().map_or(String::from("-"), |n| n.to_string());
+    (record.level().to_string(), record.source_fn().to_string(), line_nr)
+}
+
+// number of records between two traced records, 0 if pipeline tracing is disabled
+static SAMPLING_INTERVAL: AtomicU64 = AtomicU64::new(0);
+
+// number of records created since pipeline tracing was enabled
+static RECORD_COUNT: AtomicU64 = AtomicU64::new(0);
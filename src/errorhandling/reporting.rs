@@ -0,0 +1,359 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+//! Reporting of problems detected by Coaly itself.
+//! Problems are written to stderr by default. The custom configuration may restrict them to a
+//! minimum severity, redirect them to stdout or a file, limit their rate or request JSON
+//! objects instead of plain text lines.
+
+use chrono::Local;
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::coalyxw;
+use crate::util::{json_escaped_str, shared_open_options};
+use super::*;
+
+/// Targets for problem messages
+#[derive (Clone, Debug, PartialEq)]
+pub(crate) enum ProblemTarget {
+    // messages are written to stderr
+    StdErr,
+    // messages are written to stdout
+    StdOut,
+    // messages are appended to the file with the given name
+    File(String),
+    // messages are discarded
+    Discard
+}
+impl Display for ProblemTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProblemTarget::StdErr => write!(f, "{}", PROBLEM_TARGET_STDERR),
+            ProblemTarget::StdOut => write!(f, "{}", PROBLEM_TARGET_STDOUT),
+            ProblemTarget::File(name) => write!(f, "{}:{}", PROBLEM_TARGET_FILE, name),
+            ProblemTarget::Discard => write!(f, "{}", PROBLEM_TARGET_NONE)
+        }
+    }
+}
+
+/// Settings for the reporting of problems detected by Coaly itself.
+/// Specified under TOML table system.problems in the custom configuration file.
+#[derive (Clone)]
+pub(crate) struct ProblemReporting {
+    // minimum severity of reported problems, None if no problems are reported at all
+    min_severity: Option<Severity>,
+    // target for problem messages
+    target: ProblemTarget,
+    // maximum number of messages per minute, 0 if unlimited
+    rate_limit: u32,
+    // indicates whether messages are written as JSON objects
+    json: bool,
+    // number of records between two records with traced filtering decisions, 0 if disabled
+    pipeline_trace: u64
+}
+impl ProblemReporting {
+    /// Sets the minimum severity of reported problems.
+    ///
+    /// # Arguments
+    /// * `severity` - the minimum severity, **None** to report no problems at all
+    #[inline]
+    pub(crate) fn set_min_severity(&mut self, severity: Option<Severity>) {
+        self.min_severity = severity
+    }
+
+    /// Returns the target for problem messages.
+    #[inline]
+    pub(crate) fn target(&self) -> &ProblemTarget { &self.target }
+
+    /// Sets the target for problem messages.
+    ///
+    /// # Arguments
+    /// * `target` - the target
+    #[inline]
+    pub(crate) fn set_target(&mut self, target: ProblemTarget) { self.target = target }
+
+    /// Sets the maximum number of problem messages per minute.
+    ///
+    /// # Arguments
+    /// * `limit` - the maximum number of messages per minute, 0 for unlimited
+    #[inline]
+    pub(crate) fn set_rate_limit(&mut self, limit: u32) { self.rate_limit = limit }
+
+    /// Specifies whether problem messages are written as JSON objects.
+    ///
+    /// # Arguments
+    /// * `json` - **true** for JSON objects, **false** for plain text lines
+    #[inline]
+    pub(crate) fn set_json(&mut self, json: bool) { self.json = json }
+
+    /// Sets the sampling interval for the tracing of filtering decisions.
+    ///
+    /// # Arguments
+    /// * `interval` - the number of records between two traced records, 0 to disable tracing
+    #[inline]
+    pub(crate) fn set_pipeline_trace(&mut self, interval: u64) { self.pipeline_trace = interval }
+
+    /// Indicates whether a problem with the given severity is reported.
+    ///
+    /// # Arguments
+    /// * `severity` - the problem's severity
+    fn reports(&self, severity: Severity) -> bool {
+        match self.min_severity {
+            Some(Severity::Warning) => true,
+            Some(Severity::Error) => severity == Severity::Error,
+            None => false
+        }
+    }
+
+    /// Returns the message for a problem, terminated by a line feed.
+    ///
+    /// # Arguments
+    /// * `problem` - the problem
+    fn message(&self, problem: &CoalyException) -> String {
+        if ! self.json { return format!("{}\n", problem.localized_message()) }
+        let severity = match problem.severity() {
+            Severity::Error => SEVERITY_ERROR,
+            Severity::Warning => SEVERITY_WARNING
+        };
+        format!("{{\"timestamp\":\"{}\",\"severity\":\"{}\",\"id\":\"{}\",\"message\":\"{}\"}}\n",
+                Local::now().to_rfc3339(), severity, problem.id(),
+                json_escaped_str(&problem.localized_message()))
+    }
+}
+impl Default for ProblemReporting {
+    fn default() -> Self {
+        Self {
+            min_severity: Some(Severity::Warning),
+            target: ProblemTarget::StdErr,
+            rate_limit: 0,
+            json: false,
+            pipeline_trace: 0
+        }
+    }
+}
+impl Debug for ProblemReporting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.min_severity {
+            Some(Severity::Error) => SEVERITY_ERROR,
+            Some(Severity::Warning) => SEVERITY_WARNING,
+            None => SEVERITY_NONE
+        };
+        write!(f, "SEV:{}/TGT:{}/RL:{}/JSON:{}", severity, self.target, self.rate_limit, self.json)?;
+        if self.pipeline_trace != 0 { write!(f, "/TRC:{}", self.pipeline_trace)?; }
+        Ok(())
+    }
+}
+
+/// Parses the minimum severity of reported problems from configuration.
+///
+/// # Arguments
+/// * `s` - the severity name, either warning, error or none
+///
+/// # Return values
+/// the minimum severity, **None** if no problems shall be reported
+///
+/// # Errors
+/// Returns **false**, if the name is invalid
+pub(crate) fn min_severity_from_str(s: &str) -> Result<Option<Severity>, bool> {
+    match s.to_lowercase().as_str() {
+        SEVERITY_WARNING => Ok(Some(Severity::Warning)),
+        SEVERITY_ERROR => Ok(Some(Severity::Error)),
+        SEVERITY_NONE => Ok(None),
+        _ => Err(false)
+    }
+}
+impl FromStr for ProblemTarget {
+    type Err = bool;
+
+    /// Parses a target from configuration.
+    /// Value file is returned with an empty file name, the name is specified by a separate
+    /// parameter.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            PROBLEM_TARGET_STDERR => Ok(ProblemTarget::StdErr),
+            PROBLEM_TARGET_STDOUT => Ok(ProblemTarget::StdOut),
+            PROBLEM_TARGET_FILE => Ok(ProblemTarget::File(String::from(""))),
+            PROBLEM_TARGET_NONE => Ok(ProblemTarget::Discard),
+            _ => Err(false)
+        }
+    }
+}
+
+/// Activates the given problem reporting settings.
+///
+/// # Arguments
+/// * `settings` - the settings from the configuration
+/// * `output_path` - the output directory, relative problem file names are resolved against
+pub(crate) fn set_problem_reporting(settings: &ProblemReporting, output_path: &str) {
+    let mut settings = settings.clone();
+    if let ProblemTarget::File(name) = &settings.target {
+        let path = Path::new(output_path).join(name);
+        settings.target = ProblemTarget::File(path.to_string_lossy().to_string());
+    }
+    pipetrace::set_sampling_interval(settings.pipeline_trace);
+    if let Ok(mut state) = REPORTING_STATE.lock() { state.settings = settings; }
+}
+
+/// Reports the given problems according to the active settings.
+///
+/// # Arguments
+/// * `probs` - the problems to report
+pub(crate) fn report_problems(probs: &[CoalyException]) {
+    let mut state = match REPORTING_STATE.lock() {
+        Ok(s) => s,
+        Err(_) => return
+    };
+    let mut buf = String::new();
+    if let Some(ex) = state.start_interval_if_due() {
+        if state.settings.reports(ex.severity()) { buf.push_str(&state.settings.message(&ex)); }
+    }
+    for p in probs {
+        if ! state.settings.reports(p.severity()) { continue }
+        if ! state.admit() { continue }
+        buf.push_str(&state.settings.message(p));
+    }
+    if buf.is_empty() { return }
+    match &state.settings.target {
+        ProblemTarget::StdErr => { let _ = io::stderr().lock().write_all(buf.as_bytes()); },
+        ProblemTarget::StdOut => { let _ = io::stdout().lock().write_all(buf.as_bytes()); },
+        ProblemTarget::File(path) => {
+            // resort to stderr, problems must not get lost silently
+            let written = shared_open_options().append(true).create(true).open(path)
+                                               .and_then(|mut f| f.write_all(buf.as_bytes()));
+            if written.is_err() { let _ = io::stderr().lock().write_all(buf.as_bytes()); }
+        },
+        ProblemTarget::Discard => ()
+    }
+}
+
+/// Problem reporting settings and rate limit state.
+struct ReportingState {
+    // the active settings
+    settings: ProblemReporting,
+    // start of the current rate limit interval
+    interval_start: Instant,
+    // number of messages reported in the current rate limit interval
+    reported: u32,
+    // number of messages suppressed in the current rate limit interval
+    suppressed: u64
+}
+impl ReportingState {
+    /// Starts a new rate limit interval, if the current one has elapsed.
+    ///
+    /// # Return values
+    /// the problem describing the number of messages suppressed in the elapsed interval,
+    /// **None** if no messages were suppressed or the interval hasn't elapsed yet
+    fn start_interval_if_due(&mut self) -> Option<CoalyException> {
+        if self.interval_start.elapsed() < RATE_LIMIT_INTERVAL { return None }
+        let suppressed = self.suppressed;
+        self.interval_start = Instant::now();
+        self.reported = 0;
+        self.suppressed = 0;
+        if suppressed == 0 { return None }
+        Some(coalyxw!(W_PROBLEMS_SUPPRESSED, suppressed.to_string()))
+    }
+
+    /// Indicates whether another message may be reported within the rate limit.
+    fn admit(&mut self) -> bool {
+        if self.settings.rate_limit == 0 { return true }
+        if self.reported < self.settings.rate_limit {
+            self.reported += 1;
+            return true
+        }
+        self.suppressed += 1;
+        false
+    }
+}
+
+lazy_static! {
+    // the active problem reporting settings and rate limit state
+    static ref REPORTING_STATE: Mutex<ReportingState> = Mutex::new(ReportingState {
+        settings: ProblemReporting::default(),
+        interval_start: Instant::now(),
+        reported: 0,
+        suppressed: 0
+    });
+}
+
+// interval for the rate limit of problem messages
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_secs(60);
+
+const PROBLEM_TARGET_STDERR: &str = "stderr";
+const PROBLEM_TARGET_STDOUT: &str = "stdout";
+const PROBLEM_TARGET_FILE: &str = "file";
+const PROBLEM_TARGET_NONE: &str = "none";
+const SEVERITY_WARNING: &str = "warning";
+const SEVERITY_ERROR: &str = "error";
+const SEVERITY_NONE: &str = "none";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Tests severity threshold, JSON layout and rate limit of problem reporting
+    fn test_problem_reporting() {
+        let warning = coalyxw!(W_PROBLEMS_SUPPRESSED, String::from("1"));
+        let mut settings = ProblemReporting::default();
+        assert!(settings.reports(Severity::Warning));
+        settings.set_min_severity(min_severity_from_str("error").unwrap());
+        assert!(! settings.reports(Severity::Warning));
+        assert!(settings.reports(Severity::Error));
+        settings.set_min_severity(min_severity_from_str("none").unwrap());
+        assert!(! settings.reports(Severity::Error));
+        assert!(min_severity_from_str("fatal").is_err());
+        assert_eq!(format!("{}\n", warning.localized_message()), settings.message(&warning));
+        settings.set_json(true);
+        let msg = settings.message(&warning);
+        assert!(msg.starts_with("{\"timestamp\":\""));
+        assert!(msg.ends_with(&format!("\"severity\":\"warning\",\"id\":\"{}\",\"message\":\"{}\"}}\n",
+                                       W_PROBLEMS_SUPPRESSED, warning.localized_message())));
+        settings.set_rate_limit(2);
+        let mut state = ReportingState { settings, interval_start: Instant::now(),
+                                         reported: 0, suppressed: 0 };
+        assert!(state.admit());
+        assert!(state.admit());
+        assert!(! state.admit());
+        assert!(! state.admit());
+        assert!(state.start_interval_if_due().is_none());
+        state.interval_start -= RATE_LIMIT_INTERVAL;
+        let summary = state.start_interval_if_due().unwrap();
+        assert_eq!(W_PROBLEMS_SUPPRESSED, summary.id());
+        assert!(summary.localized_message().starts_with("2 "));
+        assert!(state.admit());
+    }
+}
@@ -33,9 +33,16 @@
 //! Event structure used to carry information in the communication between application threads4
 //! and Coaly's worker thread.
 
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use crate::config::ConfigSource;
+use crate::errorhandling::CoalyException;
 use crate::observer::{ObserverData};
+use crate::agent::ThreadWriteStats;
+use crate::modechange::ModeChangeDescList;
+use crate::output::forecast::RolloverForecast;
 use crate::record::RecordLevelId;
-use crate::record::recorddata::LocalRecordData;
+use crate::record::recorddata::{LocalRecordData, SourceUnit};
 
 #[cfg(feature="net")]
 use std::net::SocketAddr;
@@ -54,14 +61,30 @@ pub(crate) enum CoalyEvent {
     // Log or trace record from remote client
     #[cfg(feature="net")]
     RemoteRecord((SocketAddr, RemoteRecordData)),
-    // Process custom configuration file
-    Config(String),
+    // Process custom configuration, optionally with channel for the result
+    Config((ConfigSource, Option<Sender<Result<(), CoalyException>>>)),
+    // Replace active configuration with a custom configuration
+    Reconfig(ConfigSource),
     // Connect from remote client
     #[cfg(feature="net")]
     RemoteClientConnected((SocketAddr, OriginatorInfo)),
     // Disconnect from remote client
     #[cfg(feature="net")]
     RemoteClientDisconnected(SocketAddr),
+    // Request for the rollover forecasts of all file based resources
+    RolloverForecast(Sender<Vec<RolloverForecast>>),
+    // Request to open the file handles and network sockets of all resources anew
+    ReopenResources(Sender<Vec<CoalyException>>),
+    // Change of the output directory for files created from now on
+    ChangeOutputPath(PathBuf),
+    // Request for the write statistics of all application threads
+    ThreadWriteStats(Sender<Vec<ThreadWriteStats>>),
+    // Mode change added at runtime
+    AddModeChange(ModeChangeDescList),
+    // Request to remove all mode changes added at runtime
+    ClearModeChanges,
+    // Application thread with given ID terminated
+    ThreadExit(u64),
     // Current process terminates
     Shutdown
 }
@@ -75,6 +98,7 @@ impl CoalyEvent {
     /// * `level` - the record level
     /// * `file_name` - the name of the source code file, where the message was issued
     /// * `line_nr` - the line number in the source code file, where the message was issued
+    /// * `unit` - module path and function, where the message was issued
     /// * `msg` - the log or trace message
     #[inline]
     pub(crate) fn for_msg(thread_id: u64,
@@ -82,9 +106,12 @@ impl CoalyEvent {
                           level: RecordLevelId,
                           file_name: &'static str,
                           line_nr: u32,
+                          unit: SourceUnit,
                           msg: &str) -> CoalyEvent {
-        CoalyEvent::LocalRecord(LocalRecordData::for_write(thread_id, thread_name, level,
-                                                         file_name, line_nr, msg))
+        let mut record = LocalRecordData::for_write(thread_id, thread_name, level,
+                                                    file_name, line_nr, msg);
+        record.set_source_unit(unit);
+        CoalyEvent::LocalRecord(record)
     }
 
     /// Creates an event representing a log or trace record for an observer object.
@@ -95,6 +122,7 @@ impl CoalyEvent {
     /// * `observer_data` - the data describing the application object
     /// * `file_name` - the name of the source code file, where the message was issued
     /// * `line_nr` - the line number in the source code file, where the message was issued
+    /// * `unit` - module path and function, where the message was issued
     /// * `msg` - the log or trace message
     #[inline]
     pub(crate) fn for_obs_msg(thread_id: u64,
@@ -102,9 +130,12 @@ impl CoalyEvent {
                               observer_data: &ObserverData,
                               file_name: &'static str,
                               line_nr: u32,
+                              unit: SourceUnit,
                               msg: &str) -> CoalyEvent {
-        CoalyEvent::LocalRecord(LocalRecordData::for_write_obs(thread_id, thread_name, observer_data,
-                                                             file_name, line_nr, msg))
+        let mut record = LocalRecordData::for_write_obs(thread_id, thread_name, observer_data,
+                                                        file_name, line_nr, msg);
+        record.set_source_unit(unit);
+        CoalyEvent::LocalRecord(record)
     }
 
     /// Creates an event representing the entry of a function or module resp.
@@ -141,9 +172,84 @@ impl CoalyEvent {
     /// Creates an event representing a configuration request.
     ///
     /// # Arguments
-    /// * `cfg_fn` - configuration file name
+    /// * `source` - configuration source
+    /// * `reply_channel` - the channel where the worker thread shall send the result to,
+    ///   **None** if the caller doesn't wait for the result
     #[inline]
-    pub(crate) fn for_config(cfg_fn: &str) -> CoalyEvent { CoalyEvent::Config(String::from(cfg_fn)) }
+    pub(crate) fn for_config(source: ConfigSource,
+                             reply_channel: Option<Sender<Result<(), CoalyException>>>)
+                             -> CoalyEvent {
+        CoalyEvent::Config((source, reply_channel))
+    }
+
+    /// Creates an event representing a reconfiguration request.
+    ///
+    /// # Arguments
+    /// * `source` - configuration source
+    #[inline]
+    pub(crate) fn for_reconfig(source: ConfigSource) -> CoalyEvent {
+        CoalyEvent::Reconfig(source)
+    }
+
+    /// Creates an event representing a rollover forecast request.
+    ///
+    /// # Arguments
+    /// * `reply_channel` - the channel where the worker thread shall send the forecasts to
+    #[inline]
+    pub(crate) fn for_rollover_forecast(reply_channel: Sender<Vec<RolloverForecast>>)
+                                        -> CoalyEvent {
+        CoalyEvent::RolloverForecast(reply_channel)
+    }
+
+    /// Creates an event representing a request to reopen all output resources.
+    ///
+    /// # Arguments
+    /// * `reply_channel` - the channel where the worker thread shall send the errors to
+    #[inline]
+    pub(crate) fn for_reopen_resources(reply_channel: Sender<Vec<CoalyException>>) -> CoalyEvent {
+        CoalyEvent::ReopenResources(reply_channel)
+    }
+
+    /// Creates an event representing a change of the output directory for files.
+    ///
+    /// # Arguments
+    /// * `path` - the new output directory
+    #[inline]
+    pub(crate) fn for_change_output_path(path: PathBuf) -> CoalyEvent {
+        CoalyEvent::ChangeOutputPath(path)
+    }
+
+    /// Creates an event representing a thread write statistics request.
+    ///
+    /// # Arguments
+    /// * `reply_channel` - the channel where the worker thread shall send the statistics to
+    #[inline]
+    pub(crate) fn for_thread_write_stats(reply_channel: Sender<Vec<ThreadWriteStats>>)
+                                         -> CoalyEvent {
+        CoalyEvent::ThreadWriteStats(reply_channel)
+    }
+
+    /// Creates an event representing a mode change added at runtime.
+    ///
+    /// # Arguments
+    /// * `mode_changes` - the mode change descriptors to add
+    #[inline]
+    pub(crate) fn for_add_mode_change(mode_changes: ModeChangeDescList) -> CoalyEvent {
+        CoalyEvent::AddModeChange(mode_changes)
+    }
+
+    /// Creates an event representing a request to remove all mode changes added at runtime.
+    #[inline]
+    pub(crate) fn for_clear_mode_changes() -> CoalyEvent { CoalyEvent::ClearModeChanges }
+
+    /// Creates an event representing the termination of an application thread.
+    ///
+    /// # Arguments
+    /// * `thread_id` - the terminated thread's ID
+    #[inline]
+    pub(crate) fn for_thread_exit(thread_id: u64) -> CoalyEvent {
+        CoalyEvent::ThreadExit(thread_id)
+    }
 
     /// Creates an event representing a shutdown request.
     #[inline]
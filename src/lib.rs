@@ -36,8 +36,10 @@
 //! Advanced features are output buffering in main memory and configurable behaviour dependent
 //! on the location in the code. E.g. for one module or function all levels may be switched off,
 //! whereas for another messages of all levels are included in the output.
-//! The behaviour is entirely specified in a configuration file that is read once at application
-//! start and cannot be changed during runtime.
+//! The behaviour is entirely specified in a configuration file that is read at application
+//! start and may be replaced during runtime using function reconfigure.
+//! Formatting and output of records is done by a dedicated background thread, so that
+//! the application threads only have to hand over the record data.
 
 #[macro_use]
 extern crate lazy_static;
@@ -45,28 +47,66 @@ extern crate lazy_static;
 pub mod agent;
 pub mod collections;
 pub mod config;
+pub mod context;
 pub mod errorhandling;
 pub mod observer;
 pub mod output;
+pub mod testing;
 pub mod util;
+mod counter;
 mod datetime;
+mod degradation;
 mod event;
 mod modechange;
 mod policies;
 mod record;
+mod signal;
+mod stats;
 mod variables;
 
 use observer::ObserverData;
+pub use observer::{AttrMap, AttrValue};
+pub use agent::ThreadWriteStats;
+pub use context::PARENT_CONTEXT_ENV_VAR;
 pub use errorhandling::CoalyException;
-pub use record::originator::OriginatorInfo;
-pub use record::RecordLevelId;
+pub use output::forecast::RolloverForecast;
+pub use output::formatter::{RecordFormatter, register_formatter};
+pub use output::probe::{ProbeResult, ProbeStatus};
+pub use record::originator::{AUTH_TOKEN_ENV_VAR, OriginatorInfo};
+pub use record::{RecordLevelId, RecordTrigger};
+pub use record::recorddata::{RecordData, SourceUnit};
+pub use stats::{PipelineStats, ResourceStats};
 
 #[cfg(feature="net")]
 pub mod net;
+#[cfg(feature="net")]
+pub use record::recorddata::RemoteRecordData;
+
+#[cfg(feature="log-compat")]
+pub mod logcompat;
+
+#[cfg(feature="log-compat")]
+pub use logcompat::CoalyLogger;
+
+#[cfg(feature="tracing")]
+pub mod tracingcompat;
+
+#[cfg(feature="tracing")]
+pub use tracingcompat::CoalyLayer;
+
+#[cfg(feature="derive")]
+pub use coaly_derive::CoalyObservable;
 
 /// Result type used throughout the library for error handling
 pub type CoalyResult<T> = Result<T, CoalyException>;
 
+/// Record levels compiled into the application as bit mask.
+/// Determined by features max_level_off, max_level_error, max_level_warn, max_level_info and
+/// max_level_debug resp. their counterparts release_max_level_* for builds without debug
+/// assertions. The log macros for other levels expand to no-ops, neither the message arguments
+/// are evaluated nor a record is handed over. Includes all levels, if no such feature is enabled.
+pub const STATIC_LEVELS: u32 = record::static_levels();
+
 
 /// Initializes the system.
 /// 
@@ -80,10 +120,231 @@ pub type CoalyResult<T> = Result<T, CoalyException>;
 #[inline]
 pub fn initialize(config_file_name: &str) { agent::initialize(config_file_name); }
 
+/// Initializes the system and waits until the configuration file has been processed.
+/// 
+/// In contrast to `initialize`, the function reports an error if a plain or memory mapped file
+/// resource with creation error policy `fail` can't be created. The system uses default settings
+/// in this case.
+/// Calling the function for an already initialized system has no effect.
+/// 
+/// # Arguments
+/// * `config_file_name` - the name of the configuration file
+///
+/// After the function returned, the results of the startup probes are available through
+/// function `startup_probes`.
+/// 
+/// # Errors
+/// Returns an error structure if a file resource configured to fail initialization couldn't be
+/// created
+#[inline]
+pub fn try_initialize(config_file_name: &str) -> CoalyResult<()> {
+    agent::try_initialize(config_file_name)
+}
+
+/// Initializes the system with a configuration from TOML formatted text.
+/// 
+/// Intended for a default configuration embedded in the application binary, e.g. with
+/// `include_str!("coaly.toml")`, so that a missing configuration file doesn't prevent
+/// initialization.
+/// If an overlay file name is given and the file exists, its settings take precedence over the
+/// ones from the text. Tables are merged, all other values including arrays of tables like
+/// resources or modes replace the values from the text. If the overlay file can't be processed,
+/// the text is used alone. If the text can't be processed, the system assumes default settings.
+/// With system property watch, the overlay file is watched for changes.
+/// Calling the function for an already initialized system has no effect.
+/// 
+/// # Arguments
+/// * `toml_text` - the TOML formatted configuration
+/// * `overlay_file_name` - the name of an optional configuration file overlaying the text
+#[inline]
+pub fn initialize_from_str(toml_text: &str, overlay_file_name: Option<&str>) {
+    agent::initialize_from_str(toml_text, overlay_file_name);
+}
+
+/// Initializes the system with a configuration from TOML formatted text and waits until the
+/// configuration has been processed.
+/// 
+/// Behaves like `initialize_from_str`, but reports an error if a plain or memory mapped file
+/// resource with creation error policy `fail` can't be created, like `try_initialize`.
+/// 
+/// # Arguments
+/// * `toml_text` - the TOML formatted configuration
+/// * `overlay_file_name` - the name of an optional configuration file overlaying the text
+///
+/// # Errors
+/// Returns an error structure if a file resource configured to fail initialization couldn't be
+/// created
+#[inline]
+pub fn try_initialize_from_str(toml_text: &str,
+                               overlay_file_name: Option<&str>) -> CoalyResult<()> {
+    agent::try_initialize_from_str(toml_text, overlay_file_name)
+}
+
+/// Replaces the active configuration with the one from the given configuration file.
+/// 
+/// Output resources are closed and opened anew, if system properties, formats, policies or
+/// resources have changed. Otherwise only the mode changes and the initially enabled and
+/// buffered record levels are replaced, output resources remain untouched.
+/// Records issued before the call are processed using the previous configuration, records
+/// issued afterwards using the new one.
+/// The active configuration remains in effect, if the configuration file can't be processed.
+/// If the system has not been initialized yet, the function behaves like `initialize`.
+/// 
+/// # Arguments
+/// * `config_file_name` - the name of the configuration file
+#[inline]
+pub fn reconfigure(config_file_name: &str) { agent::reconfigure(config_file_name); }
+
+/// Adds a mode change at runtime, e.g. from a debug console or an admin endpoint.
+/// 
+/// The specification is a TOML inline table with the same parameters as an entry in group
+/// modes of the configuration file. To enable all record levels while an observer struct for
+/// a specific customer exists, use
+/// `{ trigger = "object", name = "customer", value = "^4711$", enabled = ["all"] }`.
+/// Mode changes added at runtime take precedence over the ones from the configuration file,
+/// the latest one first, and are retained upon reconfiguration. They affect observers created
+/// after the call only.
+/// 
+/// # Arguments
+/// * `spec` - the mode change specification
+///
+/// # Errors
+/// Returns an error structure if the specification can't be parsed or is invalid
+#[inline]
+pub fn add_mode_change(spec: &str) -> CoalyResult<()> { agent::add_mode_change(spec) }
+
+/// Removes all mode changes added at runtime with function `add_mode_change`.
+/// 
+/// Observers created before the call keep the mode they activated until they are dropped.
+#[inline]
+pub fn clear_mode_changes() { agent::clear_mode_changes(); }
+
+/// Returns the number of bytes currently allocated by all record buffers in main memory.
+/// The total size of these buffers may be limited by system property buffer_memory_limit.
+#[inline]
+pub fn buffer_memory_usage() -> usize { output::recordbuffer::buffer_memory_usage() }
+
+/// Returns the forecasts for the next rollover of all file based output resources.
+/// 
+/// For every plain or memory mapped file, the forecast contains the point in time of the next
+/// time based rollover resp. the progress towards the size threshold of a size based rollover.
+/// Additionally it lists the rollover files, that would be removed because of the rollover
+/// policy's keep count (dry-run, no file is touched).
+/// Intended for capacity planning, e.g. to feed dashboards.
+#[inline]
+pub fn rollover_forecast() -> Vec<RolloverForecast> { agent::rollover_forecast() }
+
+/// Returns the results of the startup probes of all output resources with parameter probe set.
+/// 
+/// A probe writes, reads back and removes a test record in the output directory of a file
+/// resource and checks the free disk space, for network resources it verifies that the peer
+/// is reachable. Intended to verify the logging pipeline before serving traffic.
+/// The results are complete after `try_initialize` or `try_initialize_from_str` returned,
+/// an empty array is returned if no resource is probed.
+#[inline]
+pub fn startup_probes() -> Vec<ProbeResult> { output::probe::results() }
+
+/// Returns the numbers of records written and suppressed by every application thread since
+/// its start.
+/// Threads that have terminated are no longer included.
+///
+/// A record is suppressed, if its level is disabled in the thread's active output mode.
+/// Helps to detect threads flooding the logs and to verify that mode changes had the
+/// intended effect. The same numbers are available in record formats through placeholder
+/// variables $ThreadRecordsWritten and $ThreadRecordsSuppressed.
+#[inline]
+pub fn thread_write_stats() -> Vec<ThreadWriteStats> { agent::thread_write_stats() }
+
+/// Returns a snapshot of the logging pipeline counters.
+///
+/// The counters comprise records written per record level and per output resource, records
+/// dropped, record buffer flushes, output file rollovers and failed writes to network resources,
+/// all cumulative since process start. Use PipelineStats::to_prometheus to render them in
+/// Prometheus text format; system property metrics_address enables a built-in scrape endpoint.
+#[inline]
+pub fn stats() -> PipelineStats { stats::snapshot() }
+
+/// Indicates whether Coaly currently runs in degraded mode.
+///
+/// Degraded mode is entered automatically, if a profile is configured in group
+/// system.degraded and output failures or overload persist for the configured time. Only the
+/// profile's record levels are processed and only the profile's resource kinds are written
+/// then, until failures and overload have ceased for the configured recovery time.
+#[inline]
+pub fn is_degraded() -> bool { degradation::is_degraded() }
+
+/// Returns the context to hand over to a child process using Coaly.
+///
+/// The context consists of the trace ID shared by the process tree and the run ID of this
+/// process, separated by a colon. If environment variable COALY_PARENT_CONTEXT holds the
+/// value when the child process starts, the child's records carry the same trace ID and the
+/// run ID of this process as parent run ID, available through placeholder variables $TraceId
+/// and $ParentRunId. Use function propagate_context for processes spawned by std::process.
+#[inline]
+pub fn child_context() -> String { context::child_context() }
+
+/// Sets environment variable COALY_PARENT_CONTEXT for a child process, so that the child's
+/// records can be correlated with the records of this process.
+///
+/// # Arguments
+/// * `cmd` - the command spawning the child process
+///
+/// # Return values
+/// the command, to allow for chained calls
+#[inline]
+pub fn propagate_context(cmd: &mut std::process::Command) -> &mut std::process::Command {
+    context::propagate_context(cmd)
+}
+
+/// Opens the file handles and network sockets of all output resources anew.
+///
+/// Intended for daemons, that drop privileges or change their root directory after
+/// initialization. Calling the function afterwards ensures that no handle opened under the
+/// previous identity remains. Plain files are opened in append mode, memory mapped files are
+/// mapped anew, network resources reconnect to their server.
+///
+/// # Errors
+/// Returns an array with error structures for all resources that couldn't be opened
+#[inline]
+pub fn reopen_resources() -> Result<(), Vec<CoalyException>> { agent::reopen_resources() }
+
+/// Changes the output directory for files created from now on.
+///
+/// Intended for applications migrating their data directory while running, e.g. after a
+/// storage re-mount. Files already open are continued in their current directory, they are
+/// archived there upon their next rollover and the following file is created in the new
+/// directory. Files not created yet, including thread or originator specific files, are created
+/// in the new directory. The directory is created, if it doesn't exist.
+/// A reconfiguration creates the resources anew with the output path from the configuration.
+///
+/// # Arguments
+/// * `path` - the absolute path of the new output directory
+///
+/// # Errors
+/// Returns an error structure, if the path is not absolute, can't be created or is not a
+/// directory
+#[inline]
+pub fn set_output_path(path: &str) -> Result<(), CoalyException> {
+    agent::set_output_path(path)
+}
+
 /// Terminates the system.
 #[inline]
 pub fn shutdown() { agent::shutdown(); }
 
+/// Returns module path and function of the location, where the macro is invoked.
+/// Used by the log macros, the function name is taken from the type name of a nested function
+/// item.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! coaly_source_unit {
+    () => {{
+        fn f() {}
+        fn fn_path<T>(_: T) -> &'static str { std::any::type_name::<T>() }
+        $crate::SourceUnit::new(std::module_path!(), fn_path(f))
+    }}
+}
+
 /// Writes a log message with level alert.
 /// 
 /// # Arguments
@@ -91,10 +352,16 @@ pub fn shutdown() { agent::shutdown(); }
 #[macro_export]
 macro_rules! logalert {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Alert, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Alert as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Alert, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Alert, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Alert as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Alert, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
     }
 }
 
@@ -105,10 +372,16 @@ macro_rules! logalert {
 #[macro_export]
 macro_rules! logcrit {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Critical, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Critical as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Critical, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Critical, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Critical as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Critical, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
     }
 }
 
@@ -119,10 +392,16 @@ macro_rules! logcrit {
 #[macro_export]
 macro_rules! logdebug {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Debug, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Debug as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Debug, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Debug, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Debug as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Debug, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
     }
 }
 
@@ -133,10 +412,16 @@ macro_rules! logdebug {
 #[macro_export]
 macro_rules! logemgcy {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Emergency, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Emergency as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Emergency, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Emergency, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Emergency as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Emergency, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
     }
 }
 
@@ -147,10 +432,16 @@ macro_rules! logemgcy {
 #[macro_export]
 macro_rules! logerror {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Error, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Error as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Error, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Error, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Error as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Error, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
     }
 }
 
@@ -161,10 +452,16 @@ macro_rules! logerror {
 #[macro_export]
 macro_rules! loginfo {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Info, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Info as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Info, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Info, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Info as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Info, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
     }
 }
 
@@ -175,10 +472,16 @@ macro_rules! loginfo {
 #[macro_export]
 macro_rules! lognote {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Notice, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Notice as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Notice, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Notice, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Notice as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Notice, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
     }
 }
 
@@ -189,10 +492,87 @@ macro_rules! lognote {
 #[macro_export]
 macro_rules! logwarn {
     ($msg: literal) => {
-        agent::write(RecordLevelId::Warning, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Warning as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write(RecordLevelId::Warning, std::file!(), std::line!(),
+                         $crate::coaly_source_unit!(), $msg);
+        }
     };
     ($($arg:tt)+) => {
-        agent::write(RecordLevelId::Warning, std::file!(), std::line!(), &std::fmt::format(format_args!($($arg)+)));
+        if RecordLevelId::Warning as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_lazy(RecordLevelId::Warning, std::file!(), std::line!(),
+                              $crate::coaly_source_unit!(), || std::format!($($arg)+));
+        }
+    }
+}
+
+/// Writes a log message with level information upon the first invocation at the call site only.
+/// Subsequent invocations are ignored without evaluating the message arguments.
+/// 
+/// # Arguments
+/// * `msg` - the message
+#[macro_export]
+macro_rules! loginfo_once {
+    ($($arg:tt)+) => {{
+        static LATCH: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+        if ! LATCH.swap(true, std::sync::atomic::Ordering::Relaxed) {
+            $crate::loginfo!($($arg)+);
+        }
+    }}
+}
+
+/// Writes a log message with level warning upon the first invocation at the call site only.
+/// Subsequent invocations are ignored without evaluating the message arguments.
+/// 
+/// # Arguments
+/// * `msg` - the message
+#[macro_export]
+macro_rules! logwarn_once {
+    ($($arg:tt)+) => {{
+        static LATCH: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+        if ! LATCH.swap(true, std::sync::atomic::Ordering::Relaxed) {
+            $crate::logwarn!($($arg)+);
+        }
+    }}
+}
+
+/// Writes a log or trace message upon the first and then every n-th invocation at the call site.
+/// Other invocations are ignored without evaluating the message arguments.
+/// Bounds the output of conditions occurring repeatedly, e.g. inside loops.
+/// 
+/// # Arguments
+/// * `n` - the interval, every n-th invocation writes the message. 0 is treated like 1
+/// * `level` - the record level
+/// * `msg` - the message
+#[macro_export]
+macro_rules! log_every {
+    ($n: expr, $level: expr, $($arg:tt)+) => {{
+        static CALLS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let level = $level;
+        if level as u32 & $crate::STATIC_LEVELS != 0 {
+            let calls = CALLS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            if calls % std::cmp::max($n as u64, 1) == 0 {
+                agent::write_lazy(level, std::file!(), std::line!(), $crate::coaly_source_unit!(),
+                                  || std::format!($($arg)+));
+            }
+        }
+    }}
+}
+
+/// Counts an event.
+/// Counts are aggregated in-process and written periodically as a single record with level
+/// info, the interval is taken from system property counter_interval. Hence high-frequency
+/// events can be tracked without issuing one record per occurrence.
+/// 
+/// # Arguments
+/// * `name` - the counter name
+/// * `n` - optional number of events to add, defaults to 1
+#[macro_export]
+macro_rules! count {
+    ($name: literal) => {
+        agent::count($name, 1);
+    };
+    ($name: literal, $n: expr) => {
+        agent::count($name, $n as u64);
     }
 }
 
@@ -210,16 +590,20 @@ macro_rules! logwarn {
 #[macro_export]
 macro_rules! logfn {
     ($func_name: literal) => {
-        let _cfn = CoalyObserver::for_fn($func_name, None, std::file!(),std::line!());
+        let _cfn = if RecordLevelId::Function as u32 & $crate::STATIC_LEVELS != 0 {
+            Some(CoalyObserver::for_fn($func_name, None, std::file!(),std::line!()))
+        } else { None };
     };
     ($func_name: literal $(,$arg: expr)+) => {
-        let arg_str = String::new();
-        $(
-            let arg_str = if arg_str.len() == 0 { arg_str + &format!("{}", $arg) }
-                          else { arg_str + &format!(",{}", $arg) };  
-        )+
-        let _cfn = CoalyObserver::for_fn($func_name, Option::from(arg_str.as_str()),
-                                         std::file!(),std::line!());
+        let _cfn = if RecordLevelId::Function as u32 & $crate::STATIC_LEVELS != 0 {
+            let arg_str = String::new();
+            $(
+                let arg_str = if arg_str.len() == 0 { arg_str + &format!("{}", $arg) }
+                              else { arg_str + &format!(",{}", $arg) };  
+            )+
+            Some(CoalyObserver::for_fn($func_name, Option::from(arg_str.as_str()),
+                                       std::file!(),std::line!()))
+        } else { None };
     };
 }
 
@@ -234,7 +618,9 @@ macro_rules! logfn {
 #[macro_export]
 macro_rules! logmod {
     ($module_name: literal) => {
-        let _cmod = CoalyObserver::for_mod($module_name, std::file!(), std::line!());
+        let _cmod = if RecordLevelId::Module as u32 & $crate::STATIC_LEVELS != 0 {
+            Some(CoalyObserver::for_mod($module_name, std::file!(), std::line!()))
+        } else { None };
     }
 }
 
@@ -247,7 +633,10 @@ macro_rules! logmod {
 #[macro_export]
 macro_rules! logobj {
     ($obj: expr, $msg: literal) => {
-        agent::write_obs($obj, std::file!(), std::line!(), $msg);
+        if RecordLevelId::Object as u32 & $crate::STATIC_LEVELS != 0 {
+            agent::write_obs($obj, std::file!(), std::line!(),
+                             $crate::coaly_source_unit!(), $msg);
+        }
     }
 }
 
@@ -270,6 +659,24 @@ macro_rules! newcoalyobs {
     };
 }
 
+/// Creates and returns a detached observer token for a task.
+/// The token may be moved into a job submitted to a thread pool. Records issued by the thread
+/// executing the job are attributed to the token's scope from calling function attach until
+/// the token is closed.
+/// 
+/// # Arguments
+/// * `task_name` - the name of the task
+/// * `task_value` - the optional value of the task, e.g. a job ID
+#[macro_export]
+macro_rules! newcoalytask {
+    ($task_name: expr) => {
+        ObserverToken::new($task_name, None, std::file!(), std::line!())
+    };
+    ($task_name: expr ,$task_value: expr) => {
+        ObserverToken::new($task_name, Option::from($task_value), std::file!(), std::line!())
+    };
+}
+
 /// Coaly observer structure.
 /// An observer structure is created upon entry of a function or during instantiation of a logging
 /// relevant user structure.
@@ -324,12 +731,106 @@ impl CoalyObserver {
         agent::observer_created(&data, line_nr);
         CoalyObserver { 0: data }
     }
+
+    /// Sets the given attributes and returns the observer structure.
+    /// Intended to attach request metadata upon scope entry, e.g.
+    /// `newcoalyobs!("request").with_attrs(&[("request_id", 4711.into()), ("user", "jdoe".into())])`.
+    ///
+    /// # Arguments
+    /// * `attrs` - the attributes as key value pairs
+    pub fn with_attrs(self, attrs: &[(&str, AttrValue)]) -> CoalyObserver {
+        for (key, value) in attrs { self.set_attr(key, value.clone()); }
+        self
+    }
+
+    /// Sets an attribute of the observer structure.
+    /// The attribute is inherited by all records subsequently issued by the calling thread until
+    /// the structure is dropped, including the records of nested observers. An attribute of a
+    /// nested observer takes precedence over an attribute of an outer observer with the same key.
+    /// Attributes are available as placeholder variables `$Attr[key]` and `$Attrs` in output
+    /// formats and as object `attrs` in JSON records.
+    /// An existing attribute with the same key is replaced.
+    ///
+    /// # Arguments
+    /// * `key` - the attribute key
+    /// * `value` - the attribute value
+    pub fn set_attr<V: Into<AttrValue>>(&self, key: &str, value: V) {
+        observer::set_attr(self.0.id(), key, value.into());
+    }
 }
 impl Drop for CoalyObserver {
     /// Invoked automatically when the observer structure goes out of scope.
     /// Writes an output record indicating that the structure has been dropped and may also revert
     /// the changes in the system behaviour to the status before the struct was created.
-    fn drop(&mut self) { agent::observer_dropped(&self.0); }
+    fn drop(&mut self) {
+        agent::observer_dropped(&self.0);
+        observer::remove_attrs(self.0.id());
+    }
+}
+
+/// Detached Coaly observer for tasks executed by a thread pool.
+/// Other than a [`CoalyObserver`], the token's scope is not bound to the stack of the thread
+/// that created it. The token is moved into a job, attached to the thread executing the job and
+/// explicitly closed when the job ends. Records issued by the executing thread in between are
+/// attributed to the token's scope, mode changes for user defined observers apply.
+/// A token attached but not closed is closed automatically when it is dropped.
+pub struct ObserverToken {
+    // observer descriptor
+    data: ObserverData,
+    // the line number in the source code file where the token was created
+    line_nr: u32,
+    // the thread the token is attached to
+    attached_to: Option<std::thread::ThreadId>
+}
+impl ObserverToken {
+    /// Creates a detached observer token for a task.
+    /// No record is written until the token is attached to a thread.
+    ///
+    /// # Arguments
+    /// * `name` - the task's name
+    /// * `value` - the optional task's value
+    /// * `file_name` - the name of the source code file where the token was created
+    /// * `line_nr` - the line number in the source code file where the token was created
+    pub fn new(name: &str,
+               value: Option<&str>,
+               file_name: &'static str,
+               line_nr: u32) -> ObserverToken {
+        ObserverToken { data: ObserverData::for_obj(name, value, file_name), line_nr,
+                        attached_to: None }
+    }
+
+    /// Attaches the token to the calling thread.
+    /// Writes an output record indicating the creation of the observer, all further records
+    /// issued by the calling thread are attributed to the token's scope until the token is
+    /// closed. Calling the function for a token already attached has no effect.
+    pub fn attach(&mut self) {
+        if self.attached_to.is_some() { return }
+        self.attached_to = Some(std::thread::current().id());
+        agent::observer_created(&self.data, self.line_nr);
+    }
+
+    /// Indicates whether the token is attached to a thread.
+    #[inline]
+    pub fn is_attached(&self) -> bool { self.attached_to.is_some() }
+
+    /// Closes the token's scope.
+    /// Writes an output record indicating that the observer has been dropped and may also
+    /// revert the changes in the system behaviour to the status before the token was attached.
+    /// Must be called by the thread the token is attached to, the call is ignored otherwise.
+    pub fn close(mut self) { self.detach(); }
+
+    /// Ends the token's scope, if the token is attached to the calling thread.
+    fn detach(&mut self) {
+        if self.attached_to == Some(std::thread::current().id()) {
+            agent::observer_dropped(&self.data);
+        }
+        self.attached_to = None;
+    }
+}
+impl Drop for ObserverToken {
+    /// Invoked automatically when the token goes out of scope.
+    /// Closes the token's scope, if it has been attached and not closed yet.
+    fn drop(&mut self) { self.detach(); }
 }
 
 pub trait CoalyObservable {
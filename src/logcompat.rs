@@ -0,0 +1,143 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+//! Integration with the log crate facade.
+//! Records issued by the macros of the log crate, e.g. from third-party crates, are routed into
+//! Coaly and processed according to the Coaly configuration.
+//! Levels are mapped as follows:
+//! * log::Level::Error - Coaly level error
+//! * log::Level::Warn - Coaly level warning
+//! * log::Level::Info - Coaly level info
+//! * log::Level::Debug - Coaly level debug
+//! * log::Level::Trace - Coaly level debug
+//! 
+//! Levels may be changed per record target using adapter mappings in the configuration file,
+//! records may also be assigned to a Coaly unit that way.
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use crate::agent;
+use crate::config::adapter::*;
+use crate::record::RecordLevelId;
+use crate::record::recorddata::SourceUnit;
+
+/// Logger for the log crate facade, forwarding all records to the Coaly agent.
+pub struct CoalyLogger;
+impl CoalyLogger {
+    /// Installs the Coaly logger as global logger for the log crate facade.
+    /// Records with a level below the specified maximum are discarded by the log macros,
+    /// all other records are subject to the Coaly configuration.
+    /// 
+    /// # Arguments
+    /// * `max_level` - the maximum level of records passed from the log macros to Coaly
+    /// 
+    /// # Errors
+    /// Returns an error structure if a global logger has already been installed
+    pub fn install(max_level: LevelFilter) -> Result<(), SetLoggerError> {
+        log::set_logger(&COALY_LOGGER)?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+impl Log for CoalyLogger {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool { true }
+
+    fn log(&self, record: &Record<'_>) {
+        let file_name = record.file_static()
+                              .or_else(|| record.module_path_static())
+                              .unwrap_or(UNKNOWN_SOURCE);
+        let line_nr = record.line().unwrap_or(0);
+        let unit = SourceUnit::new(record.module_path_static().unwrap_or_default(), "");
+        agent::write_adapted(record.target(), adapter_level(record.level()),
+                             coaly_level(record.level()), file_name, line_nr, unit,
+                             &agent::render_args(*record.args()));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Returns the Coaly record level corresponding to a log crate level.
+/// 
+/// # Arguments
+/// * `level` - the log crate level
+fn coaly_level(level: Level) -> RecordLevelId {
+    match level {
+        Level::Error => RecordLevelId::Error,
+        Level::Warn => RecordLevelId::Warning,
+        Level::Info => RecordLevelId::Info,
+        Level::Debug | Level::Trace => RecordLevelId::Debug
+    }
+}
+
+/// Returns the adapter level bit value corresponding to a log crate level.
+/// 
+/// # Arguments
+/// * `level` - the log crate level
+fn adapter_level(level: Level) -> u32 {
+    match level {
+        Level::Error => ADAPTER_LEVEL_ERROR,
+        Level::Warn => ADAPTER_LEVEL_WARN,
+        Level::Info => ADAPTER_LEVEL_INFO,
+        Level::Debug => ADAPTER_LEVEL_DEBUG,
+        Level::Trace => ADAPTER_LEVEL_TRACE
+    }
+}
+
+// global logger instance
+static COALY_LOGGER: CoalyLogger = CoalyLogger;
+
+// source file name used, if the log crate doesn't provide file name and module path
+const UNKNOWN_SOURCE: &str = "unknown";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Tests level mapping
+    fn test_coaly_level() {
+        assert_eq!(RecordLevelId::Error, coaly_level(Level::Error));
+        assert_eq!(RecordLevelId::Warning, coaly_level(Level::Warn));
+        assert_eq!(RecordLevelId::Info, coaly_level(Level::Info));
+        assert_eq!(RecordLevelId::Debug, coaly_level(Level::Debug));
+        assert_eq!(RecordLevelId::Debug, coaly_level(Level::Trace));
+    }
+
+    #[test]
+    /// Tests adapter level mapping
+    fn test_adapter_level() {
+        assert_eq!(ADAPTER_LEVEL_ERROR, adapter_level(Level::Error));
+        assert_eq!(ADAPTER_LEVEL_WARN, adapter_level(Level::Warn));
+        assert_eq!(ADAPTER_LEVEL_INFO, adapter_level(Level::Info));
+        assert_eq!(ADAPTER_LEVEL_DEBUG, adapter_level(Level::Debug));
+        assert_eq!(ADAPTER_LEVEL_TRACE, adapter_level(Level::Trace));
+    }
+}
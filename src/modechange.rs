@@ -32,11 +32,14 @@
 
 //! Types and descriptor for output mode changes.
 
-use regex::Regex;
 use std::collections::BTreeMap;
+#[cfg(not(feature="regex"))]
+use std::fmt::Display;
 use std::fmt::{Debug, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
 use crate::observer::ObserverKind;
+use crate::record::recorddata::RecordDecoration;
 
 /// Scope being affected by an output mode change
 #[derive (Clone, Copy, PartialEq)]
@@ -69,6 +72,64 @@ impl FromStr for ModeChangeScope {
     }
 }
 
+/// Pattern for the observer name or value of a mode change.
+/// Patterns are regular expressions, if feature regex is enabled.
+#[cfg(feature="regex")]
+pub(crate) type ObserverPattern = regex::Regex;
+
+/// Pattern for the observer name or value of a mode change.
+/// Without feature regex, observer names and values must match the pattern literally.
+#[cfg(not(feature="regex"))]
+#[derive(Clone)]
+pub(crate) struct ObserverPattern(String);
+#[cfg(not(feature="regex"))]
+impl ObserverPattern {
+    /// Creates a literal observer pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - the observer name or value to match
+    pub(crate) fn new(pattern: &str) -> Result<ObserverPattern, bool> {
+        Ok(ObserverPattern(pattern.to_string()))
+    }
+
+    /// Indicates whether the specified observer name or value matches this pattern.
+    #[inline]
+    pub(crate) fn is_match(&self, s: &str) -> bool { self.0 == s }
+}
+#[cfg(not(feature="regex"))]
+impl Display for ObserverPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.0) }
+}
+
+/// Descriptor for the capture of all records issued within the lifetime of a custom observer
+/// object into a dedicated file.
+#[derive(Clone, Debug)]
+pub(crate) struct CaptureDesc {
+    // file name specification, may contain variables ObserverName and ObserverValue
+    name: String,
+    // seconds after the drop of the last observer using the file, until the file is closed
+    ttl: u64
+}
+impl CaptureDesc {
+    /// Creates a capture descriptor.
+    ///
+    /// # Arguments
+    /// * `name` - the file name specification
+    /// * `ttl` - the seconds to keep the file open after the last observer has been dropped
+    #[inline]
+    pub(crate) fn new(name: &str, ttl: u64) -> CaptureDesc {
+        CaptureDesc { name: name.to_string(), ttl }
+    }
+
+    /// Returns the file name specification
+    #[inline]
+    pub(crate) fn name(&self) -> &str { &self.name }
+
+    /// Returns the seconds to keep the file open after the last observer has been dropped
+    #[inline]
+    pub(crate) fn ttl(&self) -> u64 { self.ttl }
+}
+
 /// Descriptor for an output mode change triggered by a Coaly observer structure.
 #[derive(Clone)]
 pub(crate) struct ModeChangeDesc {
@@ -78,14 +139,23 @@ pub(crate) struct ModeChangeDesc {
     observer_kind: ObserverKind,
     // name of the observer responsible for the mode change, mandatory for function or module,
     // optional for object (but then observer value must be specified)
-    observer_name: Option<Regex>,
+    observer_name: Option<ObserverPattern>,
     // value of the observer responsible for the mode change, None for function or module,
     // optional for object (but then observer name must be specified)
-    observer_value: Option<Regex>,
+    observer_value: Option<ObserverPattern>,
     // bit mask with all record levels enabled after the change
     enabled_levels: u32,
     // bit mask with all record levels buffered after the change
-    buffered_levels: u32
+    buffered_levels: u32,
+    // fraction of matching observers activating the change, None means all observers
+    sample_rate: Option<f64>,
+    // prefix, suffix and fields for all records within a matching function or module
+    decoration: Option<Arc<RecordDecoration>>,
+    // bit mask with the triggers (creation, drop) of records suppressed for a matching
+    // function or module
+    suppressed_triggers: u32,
+    // file capturing the records of the thread while a matching custom object exists
+    capture: Option<CaptureDesc>
 }
 impl ModeChangeDesc {
     /// Creates a mode change descriptor for a unit boundary observer structure.
@@ -99,7 +169,7 @@ impl ModeChangeDesc {
     /// * `enabled_levels` - the bit mask with all record levels enabled after the change
     /// * `buffered_levels` - the bit mask with all record levels buffered after the change
     pub(crate) fn for_unit(observer_kind: ObserverKind,
-                           observer_name: Option<Regex>,
+                           observer_name: Option<ObserverPattern>,
                            enabled_levels: u32,
                            buffered_levels: u32) -> ModeChangeDesc {
         ModeChangeDesc {
@@ -108,7 +178,11 @@ impl ModeChangeDesc {
             observer_name,
             observer_value: None,
             enabled_levels,
-            buffered_levels
+            buffered_levels,
+            sample_rate: None,
+            decoration: None,
+            suppressed_triggers: 0,
+            capture: None
         }
     }
 
@@ -122,8 +196,8 @@ impl ModeChangeDesc {
     /// * `enabled_levels` - the bit mask with all record levels enabled after the change
     /// * `buffered_levels` - the bit mask with all record levels buffered after the change
     pub(crate) fn for_object(scope: ModeChangeScope,
-                             observer_name: Option<Regex>,
-                             observer_value: Option<Regex>,
+                             observer_name: Option<ObserverPattern>,
+                             observer_value: Option<ObserverPattern>,
                              enabled_levels: u32,
                              buffered_levels: u32) -> ModeChangeDesc {
         ModeChangeDesc {
@@ -132,7 +206,64 @@ impl ModeChangeDesc {
             observer_name,
             observer_value,
             enabled_levels,
-            buffered_levels
+            buffered_levels,
+            sample_rate: None,
+            decoration: None,
+            suppressed_triggers: 0,
+            capture: None
+        }
+    }
+
+    /// Restricts the mode change to a fraction of all matching observers.
+    /// The decision is made per observer upon creation and remains the same until the observer
+    /// is dropped.
+    ///
+    /// # Arguments
+    /// * `sample_rate` - the fraction of matching observers activating the change (0.0 - 1.0)
+    #[inline]
+    pub(crate) fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = Some(sample_rate);
+    }
+
+    /// Attaches a decoration to all records issued within a matching function or module.
+    /// The decoration is independent of sampling, it applies to all matching units.
+    ///
+    /// # Arguments
+    /// * `decoration` - the prefix, suffix and fields to attach
+    #[inline]
+    pub(crate) fn set_decoration(&mut self, decoration: RecordDecoration) {
+        self.decoration = Some(Arc::new(decoration));
+    }
+
+    /// Suppresses the output of creation and/or drop records for all matching functions or
+    /// modules. Like decorations, suppression is independent of sampling.
+    ///
+    /// # Arguments
+    /// * `triggers` - the bit mask with the record triggers to suppress
+    #[inline]
+    pub(crate) fn set_suppressed_triggers(&mut self, triggers: u32) {
+        self.suppressed_triggers = triggers;
+    }
+
+    /// Captures all records of the thread issued while a matching custom object exists into a
+    /// dedicated file. Like the mode change itself, the capture is subject to sampling.
+    ///
+    /// # Arguments
+    /// * `capture` - the capture descriptor
+    #[inline]
+    pub(crate) fn set_capture(&mut self, capture: CaptureDesc) {
+        self.capture = Some(capture);
+    }
+
+    /// Indicates, whether the observer with specified ID is selected for this mode change.
+    /// Always **true**, if no sample rate is defined.
+    ///
+    /// # Arguments
+    /// * `observer_id` - the observer's ID
+    pub(crate) fn is_sampled(&self, observer_id: u64) -> bool {
+        match self.sample_rate {
+            Some(rate) => sample_value(observer_id) < rate,
+            None => true
         }
     }
 
@@ -164,25 +295,28 @@ impl ModeChangeDesc {
 }
 impl Debug for ModeChangeDesc {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.observer_name.is_none() && self.observer_value.is_none() {
-            return write!(f, "SC:{:?}/K:{:?}/N:-/V:-/ENA:{:b}/BUF:{:b}",
-                          self.scope, self.observer_kind,
-                          self.enabled_levels, self.buffered_levels)
+        let oname = match self.observer_name.as_ref() {
+            Some(n) => n.to_string(),
+            None => String::from("-")
+        };
+        let ovalue = match self.observer_value.as_ref() {
+            Some(v) => v.to_string(),
+            None => String::from("-")
+        };
+        let srate = match self.sample_rate {
+            Some(r) => r.to_string(),
+            None => String::from("-")
+        };
+        write!(f, "SC:{:?}/K:{:?}/N:{}/V:{}/ENA:{:b}/BUF:{:b}/SR:{}",
+               self.scope, self.observer_kind, oname, ovalue,
+               self.enabled_levels, self.buffered_levels, srate)?;
+        if let Some(d) = self.decoration.as_ref() {
+            write!(f, "/PF:{}/SF:{}", d.prefix(), d.suffix())?;
+            for (name, value) in d.fields() { write!(f, "/F:{}={}", name, value)?; }
         }
-        if self.observer_name.is_none() {
-            return write!(f, "SC:{:?}/K:{:?}/N:-/V:{}/ENA:{:b}/BUF:{:b}",
-                          self.scope, self.observer_kind, self.observer_value.as_ref().unwrap(),
-                          self.enabled_levels, self.buffered_levels)
-        }
-        if self.observer_value.is_none() {
-            return write!(f, "SC:{:?}/K:{:?}/N:{}/V:-/ENA:{:b}/BUF:{:b}",
-                          self.scope, self.observer_kind, self.observer_name.as_ref().unwrap(),
-                          self.enabled_levels, self.buffered_levels)
-        }
-        write!(f, "SC:{:?}/K:{:?}/N:{}/V:{}/ENA:{:b}/BUF:{:b}",
-               self.scope, self.observer_kind,
-               self.observer_name.as_ref().unwrap(), self.observer_value.as_ref().unwrap(),
-               self.enabled_levels, self.buffered_levels)
+        if self.suppressed_triggers != 0 { write!(f, "/SUP:{:b}", self.suppressed_triggers)?; }
+        if let Some(c) = self.capture.as_ref() { write!(f, "/CAP:{}/TTL:{}", c.name, c.ttl)?; }
+        Ok(())
     }
 }
 
@@ -228,10 +362,31 @@ impl ModeChangeDescList {
         }
     }
 
+    /// Returns the number of mode change descriptors in the list.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.global_obj_descs.len() + self.local_obj_descs.len() + self.local_unit_descs.len()
+    }
+
+    /// Returns a list with the descriptors of this list, followed by the descriptors of the
+    /// given list. The descriptors of this list take precedence, if both lists contain
+    /// descriptors matching the same observer.
+    /// 
+    /// # Arguments
+    /// * `other` - the mode change descriptors to append
+    pub(crate) fn followed_by(&self, other: &ModeChangeDescList) -> ModeChangeDescList {
+        let mut result = self.clone();
+        result.global_obj_descs.extend_from_slice(&other.global_obj_descs);
+        result.local_obj_descs.extend_from_slice(&other.local_obj_descs);
+        result.local_unit_descs.extend_from_slice(&other.local_unit_descs);
+        result
+    }
+
     /// Iterates over all process wide mode change descriptors and returns the bit mask
     /// for enabled and buffered record levels specified in the first matching descriptor.
     /// 
     /// # Arguments
+    /// * `observer_id` - the observer's ID
     /// * `observer_name` - the observer's name
     /// * `observer_value` - the observer's value
     ///
@@ -239,9 +394,11 @@ impl ModeChangeDescList {
     /// the bit mask for active/buffered record levels, u32::MAX if no match found
     #[inline]
     pub(crate) fn global_mode_for_obj(&self,
+                                      observer_id: u64,
                                       observer_name: Option<&str>,
                                       observer_value: Option<&str>) -> u32 {
-        ModeChangeDescList::mode_for(&self.global_obj_descs, observer_name, observer_value)
+        ModeChangeDescList::mode_for(&self.global_obj_descs, observer_id,
+                                     observer_name, observer_value)
     }
 
     /// Iterates over all thread specific mode change descriptors for custom objects and returns
@@ -249,6 +406,7 @@ impl ModeChangeDescList {
     /// matching descriptor.
     /// 
     /// # Arguments
+    /// * `observer_id` - the observer's ID
     /// * `observer_name` - the observer's name
     /// * `observer_value` - the observer's value
     ///
@@ -256,9 +414,11 @@ impl ModeChangeDescList {
     /// the bit mask for active/buffered record levels, u32::MAX if no match found
     #[inline]
     pub(crate) fn local_mode_for_obj(&self,
+                                     observer_id: u64,
                                      observer_name: Option<&str>,
                                      observer_value: Option<&str>) -> u32 {
-        ModeChangeDescList::mode_for(&self.local_obj_descs, observer_name, observer_value)
+        ModeChangeDescList::mode_for(&self.local_obj_descs, observer_id,
+                                     observer_name, observer_value)
     }
 
     /// Iterates over all thread specific mode change descriptors for units and returns
@@ -266,29 +426,98 @@ impl ModeChangeDescList {
     /// matching descriptor.
     /// 
     /// # Arguments
+    /// * `observer_id` - the observer's ID
     /// * `observer_name` - the observer's name
     ///
     /// # Return values
     /// the bit mask for active/buffered record levels, u32::MAX if no match found
     #[inline]
-    pub(crate) fn local_mode_for_unit(&self, observer_name: Option<&str>) -> u32 {
-        ModeChangeDescList::mode_for(&self.local_unit_descs, observer_name, None)
+    pub(crate) fn local_mode_for_unit(&self,
+                                      observer_id: u64,
+                                      observer_name: Option<&str>) -> u32 {
+        ModeChangeDescList::mode_for(&self.local_unit_descs, observer_id, observer_name, None)
+    }
+
+    /// Iterates over all thread specific mode change descriptors for units and returns
+    /// the decoration of the first matching descriptor defining one.
+    /// 
+    /// # Arguments
+    /// * `observer_name` - the observer's name
+    ///
+    /// # Return values
+    /// the decoration for all records within the unit, **None** if no match found
+    pub(crate) fn local_decoration_for_unit(&self,
+                                            observer_name: Option<&str>)
+                                            -> Option<Arc<RecordDecoration>> {
+        self.local_unit_descs.iter()
+                             .filter(|d| d.decoration.is_some())
+                             .find(|d| d.applies_to(observer_name, None))
+                             .and_then(|d| d.decoration.clone())
+    }
+
+    /// Iterates over all thread specific mode change descriptors for units and returns
+    /// the suppressed record triggers of the first matching descriptor defining some.
+    /// 
+    /// # Arguments
+    /// * `observer_name` - the observer's name
+    ///
+    /// # Return values
+    /// the bit mask with the suppressed record triggers, 0 if no match found
+    pub(crate) fn local_suppressed_triggers_for_unit(&self, observer_name: Option<&str>) -> u32 {
+        self.local_unit_descs.iter()
+                             .filter(|d| d.suppressed_triggers != 0)
+                             .find(|d| d.applies_to(observer_name, None))
+                             .map_or(0, |d| d.suppressed_triggers)
+    }
+
+    /// Returns the file name specifications of all captures.
+    pub(crate) fn capture_names(&self) -> impl Iterator<Item=&str> {
+        self.global_obj_descs.iter()
+                             .chain(self.local_obj_descs.iter())
+                             .filter_map(|d| d.capture.as_ref().map(|c| c.name()))
+    }
+
+    /// Iterates over all mode change descriptors for custom objects, process wide ones first,
+    /// and returns the capture of the first matching descriptor defining one.
+    /// 
+    /// # Arguments
+    /// * `observer_id` - the observer's ID
+    /// * `observer_name` - the observer's name
+    /// * `observer_value` - the observer's value
+    ///
+    /// # Return values
+    /// the capture descriptor, **None** if no match found or the observer is not sampled
+    pub(crate) fn capture_for_obj(&self,
+                                  observer_id: u64,
+                                  observer_name: Option<&str>,
+                                  observer_value: Option<&str>) -> Option<&CaptureDesc> {
+        self.global_obj_descs.iter()
+                             .chain(self.local_obj_descs.iter())
+                             .filter(|d| d.capture.is_some())
+                             .find(|d| d.applies_to(observer_name, observer_value))
+                             .filter(|d| d.is_sampled(observer_id))
+                             .and_then(|d| d.capture.as_ref())
     }
 
     /// Iterates over all mode change descriptors in the given list and returns the bit mask
     /// for enabled and buffered record levels specified in the first matching descriptor.
+    /// If the matching descriptor is sampled and the observer is not selected, no mode change
+    /// takes place.
     /// 
     /// # Arguments
+    /// * `observer_id` - the observer's ID
     /// * `observer_name` - the observer's name
     /// * `observer_value` - the observer's value
     ///
     /// # Return values
     /// the bit mask for active/buffered record levels, u32::MAX if no match found
     fn mode_for(descs: &[ModeChangeDesc],
+                observer_id: u64,
                 observer_name: Option<&str>,
                 observer_value: Option<&str>) -> u32 {
         for desc in descs.iter() {
             if desc.applies_to(observer_name, observer_value) {
+                if ! desc.is_sampled(observer_id) { return u32::MAX }
                 return (desc.buffered_levels << 16) | (desc.enabled_levels & 0xffff)
             }
         }
@@ -401,6 +630,21 @@ impl OverrideModeMap {
     }
 }
 
+/// Maps an observer ID to a pseudo random value in the range 0.0 (inclusive) to 1.0 (exclusive).
+/// Observer IDs are assigned sequentially, hence the bits are scrambled to get an even
+/// distribution. Since the value depends on the observer ID only, the sampling decision for an
+/// observer is the same upon creation and drop.
+///
+/// # Arguments
+/// * `observer_id` - the observer's ID
+fn sample_value(observer_id: u64) -> f64 {
+    let mut z = observer_id.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
 // Mode change scope names
 const SCOPE_PROCESS: &str = "process";
 const SCOPE_THREAD: &str = "thread";
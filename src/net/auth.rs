@@ -0,0 +1,195 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+//! Hooks for authentication and authorization of clients in a Coaly logging server.
+//! Applications running the server may install an authorizer to validate connecting clients by
+//! application ID, host and the token the client specified in environment variable
+//! COALY_AUTH_TOKEN, and to decide for every single record whether it is written.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use crate::record::originator::OriginatorInfo;
+use crate::record::recorddata::RemoteRecordData;
+use super::clientwhitelist::ClientWhitelist;
+
+/// Trait for application specific authentication and authorization decisions in a Coaly logging
+/// server, e.g. based on LDAP or IAM.
+/// The hooks are invoked from the server's network handlers and must not block for long.
+pub trait ServerAuthorizer: Send + Sync {
+    /// Decides whether a client may connect to the server.
+    /// Invoked for clients permitted by the data client whitelist only.
+    ///
+    /// # Arguments
+    /// * `addr` - the client's socket address
+    /// * `client` - information about the client, including application ID, host name and
+    ///   authentication token
+    ///
+    /// # Return values
+    /// **true** if the client is accepted
+    fn authorize_client(&self, addr: &SocketAddr, client: &OriginatorInfo) -> bool;
+
+    /// Decides whether a record received from an accepted client is written.
+    /// The default implementation accepts all records.
+    ///
+    /// # Arguments
+    /// * `client` - information about the client, the authentication token is not included
+    /// * `record` - the record received
+    ///
+    /// # Return values
+    /// **true** if the record shall be written
+    fn authorize_record(&self, _client: &OriginatorInfo, _record: &RemoteRecordData) -> bool {
+        true
+    }
+}
+
+/// Admission control for clients sending log or trace records, combining the data client
+/// whitelist from the server properties with an optional application defined authorizer.
+#[derive(Clone)]
+pub(super) struct ClientAdmission {
+    // whitelist with allowed client addresses and application IDs
+    whitelist: ClientWhitelist,
+    // application defined authorizer
+    authorizer: Option<Arc<dyn ServerAuthorizer>>
+}
+impl ClientAdmission {
+    /// Creates an admission control structure.
+    ///
+    /// # Arguments
+    /// * `whitelist` - the whitelist with allowed client addresses and application IDs
+    /// * `authorizer` - the optional application defined authorizer
+    pub(super) fn new(whitelist: ClientWhitelist,
+                      authorizer: Option<Arc<dyn ServerAuthorizer>>) -> ClientAdmission {
+        ClientAdmission { whitelist, authorizer }
+    }
+
+    /// Checks, whether a client may connect to the server.
+    ///
+    /// # Arguments
+    /// * `addr` - the client's socket address
+    /// * `client` - information about the client
+    ///
+    /// # Return values
+    /// **true** if both whitelist and authorizer accept the client
+    pub(super) fn admits(&self,
+                         addr: &SocketAddr,
+                         client: &OriginatorInfo) -> bool {
+        if ! self.whitelist.allows_addr_and_appid(addr, client.application_id_value()) {
+            return false
+        }
+        self.authorizer.as_ref().is_none_or(|a| a.authorize_client(addr, client))
+    }
+
+    /// Checks, whether a record received from an accepted client shall be written.
+    ///
+    /// # Arguments
+    /// * `client` - information about the client
+    /// * `record` - the record received
+    #[inline]
+    pub(super) fn permits_record(&self,
+                                 client: &OriginatorInfo,
+                                 record: &RemoteRecordData) -> bool {
+        self.authorizer.as_ref().is_none_or(|a| a.authorize_record(client, record))
+    }
+
+    /// Returns the record authorization for an accepted client, **None** if no authorizer is
+    /// installed.
+    ///
+    /// # Arguments
+    /// * `client` - information about the client
+    pub(super) fn record_authorization(&self,
+                                       client: &OriginatorInfo) -> Option<RecordAuthorization> {
+        self.authorizer.as_ref().map(|a| RecordAuthorization { authorizer: a.clone(),
+                                                               client: client.clone() })
+    }
+}
+
+/// Authorization of the records received over a single client connection.
+pub(super) struct RecordAuthorization {
+    // application defined authorizer
+    authorizer: Arc<dyn ServerAuthorizer>,
+    // information about the client
+    client: OriginatorInfo
+}
+impl RecordAuthorization {
+    /// Checks, whether a record received from the client shall be written.
+    ///
+    /// # Arguments
+    /// * `record` - the record received
+    #[inline]
+    pub(super) fn permits(&self, record: &RemoteRecordData) -> bool {
+        self.authorizer.authorize_record(&self.client, record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::RecordLevelId;
+    use crate::record::recorddata::{LocalRecordData, RecordData};
+
+    struct TokenAuthorizer;
+    impl ServerAuthorizer for TokenAuthorizer {
+        fn authorize_client(&self, _addr: &SocketAddr, client: &OriginatorInfo) -> bool {
+            client.auth_token() == "secret"
+        }
+        fn authorize_record(&self, _client: &OriginatorInfo, record: &RemoteRecordData) -> bool {
+            record.level() != RecordLevelId::Debug
+        }
+    }
+
+    #[test]
+    fn test_client_admission() {
+        let addr: SocketAddr = "1.2.3.4:1111".parse().unwrap();
+        let wl = ClientWhitelist::from_ip_and_app_ids(&[(String::from("1.2.3.4"), vec!(0))]);
+        let mut client = OriginatorInfo::new(1, "p1", "host1", "1.2.3.4");
+        // whitelist only
+        let adm = ClientAdmission::new(wl.clone(), None);
+        assert!(adm.admits(&addr, &client));
+        assert!(adm.record_authorization(&client).is_none());
+        // whitelist and authorizer
+        let adm = ClientAdmission::new(wl, Some(Arc::new(TokenAuthorizer)));
+        assert!(! adm.admits(&addr, &client));
+        client.set_auth_token("secret");
+        assert!(adm.admits(&addr, &client));
+        assert!(! adm.admits(&"5.6.7.8:1111".parse().unwrap(), &client));
+        let rec = RemoteRecordData::from(LocalRecordData::for_write(1, "main",
+                                                                    RecordLevelId::Debug,
+                                                                    "auth.rs", 1, "msg"));
+        assert!(! adm.permits_record(&client, &rec));
+        assert!(! adm.record_authorization(&client).unwrap().permits(&rec));
+        let rec = RemoteRecordData::from(LocalRecordData::for_write(1, "main",
+                                                                    RecordLevelId::Error,
+                                                                    "auth.rs", 1, "msg"));
+        assert!(adm.record_authorization(&client).unwrap().permits(&rec));
+    }
+}
@@ -155,6 +155,22 @@ impl SendBuffer {
         self.buffer.push(SHUTDOWN_RESP_ID);
     }
 
+    /// Stores a record acknowledgement message in the internal buffer.
+    /// Used by the server to confirm the receipt of record notifications, if the client
+    /// requested acknowledgements in its connect request.
+    /// 
+    /// # Arguments
+    /// * `count` - the total number of record notifications received over the connection
+    pub fn store_record_acknowledgement(&mut self, count: u64) {
+        self.buffer.truncate(4);
+        // sequence number
+        0u64.serialize_to(&mut self.buffer);
+        // payload size
+        9u32.serialize_to(&mut self.buffer);
+        self.buffer.push(RECORD_ACK_ID);
+        count.serialize_to(&mut self.buffer);
+    }
+
     /// Returns the payload size, 0 if buffer does not contain a payload.
     fn payload_size(&self) -> u32 {
         if self.buffer.len() < 16 { return 0u32 }
@@ -210,6 +226,23 @@ impl ReceiveBuffer {
     /// socket.
     pub fn as_mut_slice(&mut self) -> &mut [u8] { self.buffer.as_mut_slice() }
 
+    /// Returns the internal byte buffer as slice.
+    pub fn as_slice(&self) -> &[u8] { self.buffer.as_slice() }
+
+    /// Copies a message received in several parts, e.g. reassembled from datagram fragments,
+    /// to the internal buffer.
+    ///
+    /// # Arguments
+    /// * `data` - the serialized message including header
+    ///
+    /// # Return values
+    /// the number of bytes copied, **None** if the message exceeds the buffer size
+    pub fn fill(&mut self, data: &[u8]) -> Option<usize> {
+        if data.len() > self.buffer.len() { return None }
+        self.buffer[..data.len()].copy_from_slice(data);
+        Some(data.len())
+    }
+
     /// Returns the received protocol information, 0 if buffer contains less than 4 bytes
     pub fn protocol_info(&self) -> u32 {
         u32::deserialize_from(&self.buffer).unwrap_or(0u32)
@@ -225,6 +258,9 @@ impl ReceiveBuffer {
         u32::deserialize_from(&self.buffer[12..]).unwrap_or(0u32)
     }
 
+    /// Returns the size of the received message including header
+    pub fn message_size(&self) -> usize { 16 + self.payload_size() as usize }
+
     /// Returns the received message from the internal buffer.
     /// Protocol information is currently ignored.
     /// 
@@ -250,7 +286,7 @@ impl Debug for ReceiveBuffer {
     }
 }
 
-#[cfg(all(net, test))]
+#[cfg(all(feature="net", test))]
 mod tests {
     use super::*;
     use crate::record::RecordLevelId;
@@ -262,15 +298,15 @@ mod tests {
         let mut oinfo = OriginatorInfo::new(1234, "testapp", "clienthost", "1.2.3.4");
         oinfo.set_application_name("superapp");
         send_buf.store_client_notification(&oinfo);
-        assert_eq!("PROT:1/SEQ:0/LEN:97/PSZ:85/PLD:0b",
-                   &format!("{}", &send_buf)[..33]);
+        assert_eq!("PROT:1/SEQ:0/LEN:129/PSZ:113/PLD:0b",
+                   &format!("{:?}", &send_buf)[..35]);
         let rec_txt = LocalRecordData::for_write(1234, "mythread", RecordLevelId::Error, 
                                                  "test.rs", 393, "blabla");
         send_buf.store_record_notification(&rec_txt);
-        assert_eq!("PROT:1/SEQ:1/LEN:102/PSZ:90/PLD:0c",
-                   &format!("{}", &send_buf)[..34]);
+        assert_eq!("PROT:1/SEQ:1/LEN:106/PSZ:90/PLD:0c",
+                   &format!("{:?}", &send_buf)[..34]);
         send_buf.store_disconnect_notification();
-        assert_eq!("PROT:1/SEQ:2/LEN:17/PSZ:1/PLD:0d", format!("{}", &send_buf));
+        assert_eq!("PROT:1/SEQ:2/LEN:17/PSZ:1/PLD:0d", format!("{:?}", &send_buf));
     }
 
     #[test]
@@ -278,11 +314,14 @@ mod tests {
         let mut send_buf = SendBuffer::new(1, 256);
         send_buf.store_shutdown_request("TOPSECRET");
         assert_eq!("PROT:1/SEQ:0/LEN:34/PSZ:18/PLD:15",
-                   &format!("{}", &send_buf)[..33]);
+                   &format!("{:?}", &send_buf)[..33]);
         send_buf.clear();
-        assert_eq!("PROT:1/SEQ:0/LEN:4/PSZ:0/PLD:-", format!("{}", &send_buf));
+        assert_eq!("PROT:1/SEQ:0/LEN:4/PSZ:0/PLD:-", format!("{:?}", &send_buf));
         send_buf.store_shutdown_response();
-        assert_eq!("PROT:1/SEQ:0/LEN:17/PSZ:1/PLD:1f", format!("{}", &send_buf));
+        assert_eq!("PROT:1/SEQ:0/LEN:17/PSZ:1/PLD:1f", format!("{:?}", &send_buf));
+        send_buf.store_record_acknowledgement(258);
+        assert_eq!("PROT:1/SEQ:0/LEN:25/PSZ:9/PLD:200000000000000102",
+                   format!("{:?}", &send_buf));
     }
 
     fn check_recv(buf: &mut ReceiveBuffer,
@@ -290,7 +329,7 @@ mod tests {
                   expected_header: &str,
                   expected_msg: &Message) {
         fill_buf(buf, hex_msg);
-        assert_eq!(expected_header, format!("{}", &buf));
+        assert_eq!(expected_header, format!("{:?}", &buf));
         let hex_data_size = hex_msg.len() >> 1;
         let m = buf.message(hex_data_size);
         assert!(m.is_ok());
@@ -170,6 +170,10 @@ impl ClientConnection {
     #[inline]
     pub(super) fn last_rx_time(&self) -> i64 { self.last_rx_time }
 
+    /// Returns the information about the client
+    #[inline]
+    pub(super) fn client_info(&self) -> &OriginatorInfo { &self.client_info }
+
     /// Re-use the descriptor, eventually with changed client information.
     /// May happen, if we couldn't notice a client disconnected and now the client connects again
     /// using the same socket address.
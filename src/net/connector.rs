@@ -0,0 +1,420 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+
+//! Deferred connection establishment for network and syslog resources.
+//! Host name resolution and TCP connect may take a long time, e.g. if a log server is
+//! blocked by a firewall. Both are therefore done by a short-lived background thread,
+//! data written while the connection is pending is kept in a bounded backlog.
+
+use std::io::{Read, Write};
+use std::net::*;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use crate::coalyxe;
+use crate::errorhandling::*;
+use super::PeerAddr;
+#[cfg(feature="tls")]
+use super::tls::{ClientTlsStream, TlsConnector};
+
+/// Established socket to a remote peer.
+pub(crate) enum Connection {
+    Tcp(TcpConnection),
+    Udp(UdpSocket)
+}
+
+/// Established TCP stream to a remote peer, optionally secured by TLS.
+pub(crate) enum TcpConnection {
+    Plain(TcpStream),
+    #[cfg(feature="tls")]
+    Tls(Box<ClientTlsStream>)
+}
+impl TcpConnection {
+    /// Returns the underlying TCP stream.
+    pub(crate) fn socket(&self) -> &TcpStream {
+        match self {
+            TcpConnection::Plain(s) => s,
+            #[cfg(feature="tls")]
+            TcpConnection::Tls(s) => &s.sock
+        }
+    }
+
+    /// Shuts down the connection.
+    /// A TLS session is closed before, so the peer can tell a regular close from a truncation.
+    pub(crate) fn shutdown(&mut self) {
+        #[cfg(feature="tls")]
+        if let TcpConnection::Tls(s) = self {
+            s.conn.send_close_notify();
+            let _ = s.flush();
+        }
+        let _ = self.socket().shutdown(Shutdown::Both);
+    }
+}
+impl Read for TcpConnection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            TcpConnection::Plain(s) => s.read(buf),
+            #[cfg(feature="tls")]
+            TcpConnection::Tls(s) => s.read(buf)
+        }
+    }
+}
+impl Write for TcpConnection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            TcpConnection::Plain(s) => s.write(buf),
+            #[cfg(feature="tls")]
+            TcpConnection::Tls(s) => s.write(buf)
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            TcpConnection::Plain(s) => s.flush(),
+            #[cfg(feature="tls")]
+            TcpConnection::Tls(s) => s.flush()
+        }
+    }
+}
+
+/// Established socket together with all messages written while the connection was pending.
+pub(crate) type EstablishedConnection = (Connection, Vec<Vec<u8>>);
+
+/// Connection to a remote peer, that is established by a background thread.
+pub(crate) struct DeferredConnection {
+    // remote address, for error messages only
+    remote_addr: String,
+    // channel receiving the outcome of the connection attempt
+    receiver: Receiver<Result<Connection, CoalyException>>,
+    // messages written while the connection is pending
+    backlog: Vec<Vec<u8>>,
+    // total size of all messages in the backlog
+    backlog_size: usize
+}
+impl DeferredConnection {
+    /// Starts the connection attempt to a remote peer in a background thread.
+    ///
+    /// # Arguments
+    /// * `remote_addr` - the TCP or UDP socket address of the remote peer, may contain a
+    ///   host name to be resolved
+    /// * `local_addr` - the optional socket address for a local UDP socket
+    /// * `hello` - the optional message to send immediately after the connection has been
+    ///   established
+    pub(crate) fn start(remote_addr: &PeerAddr,
+                        local_addr: Option<SocketAddr>,
+                        hello: Option<Vec<u8>>) -> DeferredConnection {
+        let addr = remote_addr.clone();
+        DeferredConnection::spawn(remote_addr, move || connect(&addr, local_addr, hello))
+    }
+
+    /// Starts the TLS connection attempt to a remote peer in a background thread.
+    ///
+    /// # Arguments
+    /// * `remote_addr` - the socket address of the remote peer, may contain a host name to
+    ///   be resolved
+    /// * `connector` - the TLS connector performing the handshake
+    /// * `hello` - the optional message to send immediately after the TLS session has been
+    ///   established
+    #[cfg(feature="tls")]
+    pub(crate) fn start_tls(remote_addr: &PeerAddr,
+                            connector: TlsConnector,
+                            hello: Option<Vec<u8>>) -> DeferredConnection {
+        let addr = remote_addr.clone();
+        DeferredConnection::spawn(remote_addr, move || {
+            connect_any(&addr, |a| connect_tls(a, &connector, hello.as_deref()))
+        })
+    }
+
+    /// Runs the given connect function in a background thread.
+    ///
+    /// # Arguments
+    /// * `remote_addr` - the address of the remote peer, for error messages only
+    /// * `connect_fn` - the function establishing the connection
+    fn spawn<F>(remote_addr: &PeerAddr, connect_fn: F) -> DeferredConnection
+        where F: FnOnce() -> Result<Connection, CoalyException> + Send + 'static {
+        let (sender, receiver) = channel();
+        // if the thread can't be spawned, the sender is dropped and the attempt reported
+        // as failed upon the next poll
+        let _ = thread::Builder::new()
+                    .name(String::from(CONNECTOR_THREAD_NAME))
+                    .spawn(move || { let _ = sender.send(connect_fn()); });
+        DeferredConnection {
+            remote_addr: remote_addr.to_string(),
+            receiver,
+            backlog: Vec::new(),
+            backlog_size: 0
+        }
+    }
+
+    /// Stores a message written while the connection is pending.
+    /// The message is discarded, if the backlog is full.
+    ///
+    /// # Arguments
+    /// * `data` - the message
+    pub(crate) fn defer(&mut self, data: &[u8]) {
+        if self.backlog_size + data.len() > MAX_BACKLOG_SIZE { return }
+        self.backlog_size += data.len();
+        self.backlog.push(data.to_vec());
+    }
+
+    /// Checks whether the connection attempt has finished, without blocking.
+    ///
+    /// # Return values
+    /// **None** if the connection attempt is still in progress, otherwise the established
+    /// connection together with all messages written in the meantime
+    ///
+    /// # Errors
+    /// Returns an error structure if host name resolution or connect failed
+    pub(crate) fn poll(&mut self) -> Option<Result<EstablishedConnection, CoalyException>> {
+        match self.receiver.try_recv() {
+            Ok(Ok(conn)) => {
+                self.backlog_size = 0;
+                Some(Ok((conn, std::mem::take(&mut self.backlog))))
+            },
+            Ok(Err(e)) => Some(Err(e)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(coalyxe!(E_SOCKET_CRE_ERR, self.remote_addr.clone(),
+                                  String::from("connection attempt aborted"))))
+            }
+        }
+    }
+}
+
+/// Resolves the socket addresses of a remote peer.
+///
+/// # Arguments
+/// * `remote_addr` - the TCP or UDP socket address of the remote peer
+///
+/// # Errors
+/// Returns an error structure if the host name can't be resolved
+pub(crate) fn socket_addrs(remote_addr: &PeerAddr) -> Result<Vec<SocketAddr>, CoalyException> {
+    match remote_addr {
+        PeerAddr::IpSocket(_, addr) => Ok(vec!(*addr)),
+        PeerAddr::HostSocket(_, host, port) => {
+            match (host.as_str(), *port).to_socket_addrs() {
+                Ok(addrs) => {
+                    let addrs: Vec<SocketAddr> = addrs.collect();
+                    if addrs.is_empty() {
+                        return Err(coalyxe!(E_HOST_RESOLVE_ERR, host.to_string(),
+                                            String::from("no address found")))
+                    }
+                    Ok(addrs)
+                },
+                Err(e) => Err(coalyxe!(E_HOST_RESOLVE_ERR, host.to_string(), e.to_string()))
+            }
+        },
+        #[cfg(unix)]
+        PeerAddr::UnixSocket(_) => Err(coalyxe!(E_INVALID_URL, remote_addr.to_string()))
+    }
+}
+
+/// Resolves the remote peer's address and connects to the first reachable address.
+///
+/// # Arguments
+/// * `remote_addr` - the TCP or UDP socket address of the remote peer
+/// * `local_addr` - the optional socket address for a local UDP socket
+/// * `hello` - the optional message to send after the connection has been established
+fn connect(remote_addr: &PeerAddr,
+           local_addr: Option<SocketAddr>,
+           hello: Option<Vec<u8>>) -> Result<Connection, CoalyException> {
+    connect_any(remote_addr, |addr| {
+        if remote_addr.is_tcp_stream() { connect_tcp(addr, hello.as_deref()) }
+        else { connect_udp(addr, local_addr, hello.as_deref()) }
+    })
+}
+
+/// Resolves the remote peer's address and tries the given connect function on all addresses
+/// until it succeeds.
+///
+/// # Arguments
+/// * `remote_addr` - the TCP or UDP socket address of the remote peer
+/// * `connect_fn` - the function connecting to a single socket address
+fn connect_any<F>(remote_addr: &PeerAddr,
+                  connect_fn: F) -> Result<Connection, CoalyException>
+    where F: Fn(&SocketAddr) -> std::io::Result<Connection> {
+    let addrs = socket_addrs(remote_addr)?;
+    let mut last_err = String::new();
+    for addr in addrs {
+        match connect_fn(&addr) {
+            Ok(conn) => return Ok(conn),
+            Err(e) => last_err = e.to_string()
+        }
+    }
+    Err(coalyxe!(E_SOCKET_CRE_ERR, remote_addr.to_string(), last_err))
+}
+
+/// Connects a TCP stream, giving up after the connect timeout.
+///
+/// # Arguments
+/// * `addr` - the remote socket address
+/// * `hello` - the optional message to send after the connection has been established
+fn connect_tcp(addr: &SocketAddr, hello: Option<&[u8]>) -> std::io::Result<Connection> {
+    let mut s = TcpStream::connect_timeout(addr, CONNECT_TIMEOUT)?;
+    if let Some(data) = hello {
+        if let Err(e) = s.write_all(data) {
+            let _ = s.shutdown(Shutdown::Both);
+            return Err(e)
+        }
+    }
+    Ok(Connection::Tcp(TcpConnection::Plain(s)))
+}
+
+/// Connects a TCP stream and establishes a TLS session on it.
+///
+/// # Arguments
+/// * `addr` - the remote socket address
+/// * `connector` - the TLS connector performing the handshake
+/// * `hello` - the optional message to send after the TLS session has been established
+#[cfg(feature="tls")]
+fn connect_tls(addr: &SocketAddr,
+               connector: &TlsConnector,
+               hello: Option<&[u8]>) -> std::io::Result<Connection> {
+    let s = TcpStream::connect_timeout(addr, CONNECT_TIMEOUT)?;
+    let mut conn = TcpConnection::Tls(Box::new(connector.connect(s)?));
+    if let Some(data) = hello {
+        if let Err(e) = conn.write_all(data) {
+            conn.shutdown();
+            return Err(e)
+        }
+    }
+    Ok(Connection::Tcp(conn))
+}
+
+/// Creates a UDP socket connected to the remote address.
+///
+/// # Arguments
+/// * `addr` - the remote socket address
+/// * `local_addr` - the optional local socket address
+/// * `hello` - the optional message to send after the connection has been established
+fn connect_udp(addr: &SocketAddr,
+               local_addr: Option<SocketAddr>,
+               hello: Option<&[u8]>) -> std::io::Result<Connection> {
+    let laddr = local_addr.unwrap_or_else(|| {
+        if addr.is_ipv4() {
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)
+        } else {
+            SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0)
+        }
+    });
+    let s = UdpSocket::bind(laddr)?;
+    s.connect(addr)?;
+    if let Some(data) = hello { s.send(data)?; }
+    Ok(Connection::Udp(s))
+}
+
+// timeout for TCP connect to a single remote address
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// maximum total size of messages kept while a connection is pending
+const MAX_BACKLOG_SIZE: usize = 65536;
+
+// name of the background threads establishing connections
+const CONNECTOR_THREAD_NAME: &str = "coaly-connector";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{is_valid_remote_url, is_valid_url, parse_url, NetworkProtocol};
+    use std::time::Instant;
+
+    fn wait_for(conn: &mut DeferredConnection) -> Result<EstablishedConnection, CoalyException> {
+        let start = Instant::now();
+        loop {
+            if let Some(res) = conn.poll() { return res }
+            assert!(start.elapsed() < Duration::from_secs(30), "connection attempt hangs");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_deferred_tcp_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let remote_addr = PeerAddr::HostSocket(NetworkProtocol::Tcp, String::from("localhost"),
+                                               port);
+        let mut conn = DeferredConnection::start(&remote_addr, None, Some(b"hello".to_vec()));
+        conn.defer(b"first");
+        conn.defer(&[0u8; MAX_BACKLOG_SIZE]);
+        conn.defer(b"second");
+        let (mut peer, _) = listener.accept().unwrap();
+        let (established, backlog) = wait_for(&mut conn).unwrap();
+        assert!(matches!(established, Connection::Tcp(_)));
+        assert_eq!(vec!(b"first".to_vec(), b"second".to_vec()), backlog);
+        let mut hello = [0u8; 5];
+        peer.read_exact(&mut hello).unwrap();
+        assert_eq!(b"hello", &hello);
+    }
+
+    #[test]
+    fn test_host_url() {
+        assert!(is_valid_remote_url("tcp://logs.example.com:3690"));
+        assert!(is_valid_remote_url("udp://loghost:514"));
+        assert!(is_valid_remote_url("tcp://127.0.0.1:3690"));
+        assert!(! is_valid_remote_url("tcp://logs.example.com"));
+        assert!(! is_valid_remote_url("tcp://loghost:70000"));
+        assert!(! is_valid_url("tcp://loghost:3690"));
+        match parse_url("tcp://logs.example.com:3690") {
+            Ok(PeerAddr::HostSocket(NetworkProtocol::Tcp, host, 3690)) => {
+                assert_eq!("logs.example.com", host);
+            },
+            _ => panic!("host name URL not parsed")
+        }
+        let addrs = socket_addrs(&parse_url("udp://127.0.0.1:514").unwrap()).unwrap();
+        assert_eq!(vec!(SocketAddr::from(([127, 0, 0, 1], 514))), addrs);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_url() {
+        let dir = std::env::temp_dir();
+        let path_name = dir.join("coaly.sock").to_string_lossy().to_string();
+        assert!(is_valid_url(&format!("unix://{}", path_name)));
+        assert!(is_valid_url(&format!("unix:{}", path_name)));
+        assert!(is_valid_remote_url(&format!("unix://{}", path_name)));
+        assert!(! is_valid_url("unix://"));
+        assert!(! is_valid_url("unix:///coaly.invalid/coaly.sock"));
+        match parse_url(&format!("unix://{}", path_name)) {
+            Ok(PeerAddr::UnixSocket(p)) => assert_eq!(path_name, p),
+            _ => panic!("Unix socket URL not parsed")
+        }
+        assert!(parse_url("unix:///coaly.invalid/coaly.sock").is_err());
+    }
+
+    #[test]
+    fn test_failed_resolution() {
+        let remote_addr = PeerAddr::HostSocket(NetworkProtocol::Udp,
+                                               String::from("coaly.invalid"), 514);
+        let mut conn = DeferredConnection::start(&remote_addr, None, None);
+        assert!(wait_for(&mut conn).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+//! Fragmentation of messages too large for a single UDP datagram.
+//!
+//! A message exceeding the maximum datagram size is sent as a series of fragment datagrams.
+//! Every fragment datagram carries a regular message header with flag
+//! PROTOCOL_FLAG_FRAGMENT set in the protocol information and the fragmented message's sequence
+//! number. The payload of a fragment datagram is structured as follows:
+//! Bytes 0..3 - offset of the fragment within the serialized message
+//! Bytes 4..7 - total size of the serialized message
+//! Bytes 8.. - fragment data
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use super::PROTOCOL_FLAG_FRAGMENT;
+use super::serializable::Serializable;
+
+/// Maximum size of a datagram sent over UDP, chosen to avoid fragmentation on the IP layer
+/// for the common Ethernet MTU.
+pub(crate) const MAX_DATAGRAM_SIZE: usize = 1400;
+
+/// Returns the datagrams needed to transmit the given serialized message.
+/// A message fitting into a single datagram is returned unchanged.
+///
+/// # Arguments
+/// * `msg` - the serialized message including header
+/// * `max_datagram_size` - the maximum size of a datagram including header
+///
+/// # Return values
+/// the datagrams to send in the order returned
+pub(crate) fn fragments(msg: &[u8], max_datagram_size: usize) -> Vec<Cow<'_, [u8]>> {
+    if msg.len() <= max_datagram_size { return vec!(Cow::Borrowed(msg)) }
+    let protocol_info = u32::deserialize_from(msg).unwrap_or(0) | PROTOCOL_FLAG_FRAGMENT;
+    let sequence_nr = u64::deserialize_from(&msg[4..]).unwrap_or(0);
+    let max_fragment_size = usize::max(max_datagram_size, FRAGMENT_HEADER_SIZE + 1) -
+                            FRAGMENT_HEADER_SIZE;
+    let mut datagrams = Vec::with_capacity(msg.len() / max_fragment_size + 1);
+    for (i, fragment) in msg.chunks(max_fragment_size).enumerate() {
+        let mut dg = Vec::<u8>::with_capacity(FRAGMENT_HEADER_SIZE + fragment.len());
+        protocol_info.serialize_to(&mut dg);
+        sequence_nr.serialize_to(&mut dg);
+        ((fragment.len() + 8) as u32).serialize_to(&mut dg);
+        ((i * max_fragment_size) as u32).serialize_to(&mut dg);
+        (msg.len() as u32).serialize_to(&mut dg);
+        dg.extend_from_slice(fragment);
+        datagrams.push(Cow::Owned(dg));
+    }
+    datagrams
+}
+
+/// Reassembles messages from fragment datagrams.
+/// Only the most recent fragmented message of every client is tracked, fragments of an older
+/// message are discarded as soon as a fragment of a message with another sequence number
+/// arrives.
+pub(crate) struct MessageAssembler {
+    // maximum size of a reassembled message
+    max_msg_size: usize,
+    // partially received messages by client address
+    pending: HashMap<SocketAddr, PartialMessage>
+}
+impl MessageAssembler {
+    /// Creates a message assembler.
+    ///
+    /// # Arguments
+    /// * `max_msg_size` - the maximum size of a reassembled message, fragments of larger
+    ///   messages are discarded
+    pub(crate) fn new(max_msg_size: usize) -> MessageAssembler {
+        MessageAssembler { max_msg_size, pending: HashMap::new() }
+    }
+
+    /// Adds a fragment datagram received from a client.
+    ///
+    /// # Arguments
+    /// * `client_addr` - the client's socket address
+    /// * `datagram` - the fragment datagram received
+    ///
+    /// # Return values
+    /// the complete serialized message, if the fragment was the last one missing;
+    /// otherwise **None**
+    pub(crate) fn add(&mut self,
+                      client_addr: &SocketAddr,
+                      datagram: &[u8]) -> Option<Vec<u8>> {
+        if datagram.len() <= FRAGMENT_HEADER_SIZE { return None }
+        let sequence_nr = u64::deserialize_from(&datagram[4..]).ok()?;
+        let offset = u32::deserialize_from(&datagram[16..]).ok()? as usize;
+        let total_size = u32::deserialize_from(&datagram[20..]).ok()? as usize;
+        let fragment = &datagram[FRAGMENT_HEADER_SIZE..];
+        if total_size > self.max_msg_size || offset + fragment.len() > total_size { return None }
+        let msg = self.pending.entry(*client_addr)
+                              .or_insert_with(|| PartialMessage::new(sequence_nr, total_size));
+        if msg.sequence_nr != sequence_nr || msg.data.len() != total_size {
+            *msg = PartialMessage::new(sequence_nr, total_size);
+        }
+        if ! msg.offsets.insert(offset) { return None }
+        msg.data[offset..offset + fragment.len()].copy_from_slice(fragment);
+        msg.bytes_received += fragment.len();
+        if msg.bytes_received < total_size { return None }
+        self.pending.remove(client_addr).map(|m| m.data)
+    }
+
+    /// Discards the partially received message of a client.
+    ///
+    /// # Arguments
+    /// * `client_addr` - the client's socket address
+    pub(crate) fn remove(&mut self, client_addr: &SocketAddr) {
+        self.pending.remove(client_addr);
+    }
+}
+
+/// Message, for which not all fragments have been received yet.
+struct PartialMessage {
+    // sequence number of the fragmented message
+    sequence_nr: u64,
+    // serialized message, filled as fragments arrive
+    data: Vec<u8>,
+    // offsets of the fragments received
+    offsets: HashSet<usize>,
+    // number of message bytes received
+    bytes_received: usize
+}
+impl PartialMessage {
+    fn new(sequence_nr: u64, total_size: usize) -> PartialMessage {
+        PartialMessage {
+            sequence_nr,
+            data: vec!(0u8; total_size),
+            offsets: HashSet::new(),
+            bytes_received: 0
+        }
+    }
+}
+
+// size of the header of a fragment datagram, message header plus offset and total size
+const FRAGMENT_HEADER_SIZE: usize = 24;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(sequence_nr: u64, size: usize) -> Vec<u8> {
+        let mut msg = Vec::<u8>::with_capacity(size);
+        1u32.serialize_to(&mut msg);
+        sequence_nr.serialize_to(&mut msg);
+        ((size - 16) as u32).serialize_to(&mut msg);
+        msg.extend((16..size).map(|i| (i & 255) as u8));
+        msg
+    }
+
+    #[test]
+    fn test_small_message() {
+        let msg = message(7, 100);
+        let datagrams = fragments(&msg, MAX_DATAGRAM_SIZE);
+        assert_eq!(1, datagrams.len());
+        assert_eq!(msg.as_slice(), datagrams[0].as_ref());
+    }
+
+    #[test]
+    fn test_fragmented_message() {
+        let msg = message(7, 1000);
+        let datagrams = fragments(&msg, 324);
+        assert_eq!(4, datagrams.len());
+        for dg in &datagrams {
+            assert!(dg.len() <= 324);
+            assert_ne!(0, u32::deserialize_from(dg).unwrap() & PROTOCOL_FLAG_FRAGMENT);
+        }
+        let client: SocketAddr = "127.0.0.1:4711".parse().unwrap();
+        let other_client: SocketAddr = "127.0.0.1:4712".parse().unwrap();
+        let mut assembler = MessageAssembler::new(65536);
+        // fragments may arrive in any order, duplicates are ignored
+        assert!(assembler.add(&client, &datagrams[2]).is_none());
+        assert!(assembler.add(&other_client, &datagrams[0]).is_none());
+        assert!(assembler.add(&client, &datagrams[0]).is_none());
+        assert!(assembler.add(&client, &datagrams[0]).is_none());
+        assert!(assembler.add(&client, &datagrams[3]).is_none());
+        assert_eq!(Some(msg.clone()), assembler.add(&client, &datagrams[1]));
+        // a new message discards the fragments of the incomplete previous one
+        let next_msg = message(8, 1000);
+        let next_datagrams = fragments(&next_msg, 324);
+        assert!(assembler.add(&other_client, &next_datagrams[1]).is_none());
+        for dg in next_datagrams.iter().skip(2) {
+            assert!(assembler.add(&other_client, dg).is_none());
+        }
+        assert_eq!(Some(next_msg.clone()), assembler.add(&other_client, &next_datagrams[0]));
+    }
+
+    #[test]
+    fn test_oversized_message() {
+        let msg = message(7, 1000);
+        let client: SocketAddr = "127.0.0.1:4711".parse().unwrap();
+        let mut assembler = MessageAssembler::new(512);
+        for dg in fragments(&msg, 324) { assert!(assembler.add(&client, &dg).is_none()); }
+    }
+}
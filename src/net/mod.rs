@@ -35,6 +35,7 @@
 use regex::Regex;
 use std::fmt::{Debug, Display, Formatter};
 use std::net::*;
+#[cfg(unix)]
 use std::path::Path;
 use std::str::FromStr;
 use crate::{coalyxe, coalyxw};
@@ -43,10 +44,16 @@ use crate::record::originator::OriginatorInfo;
 use crate::record::recorddata::RemoteRecordData;
 use serializable::Serializable;
 
+pub mod auth;
 pub mod buffer;
+pub(crate) mod connector;
+pub(crate) mod fragment;
 pub mod serializable;
 pub mod server;
 pub mod serverproperties;
+#[cfg(feature="compression")]
+pub(crate) mod streamcompression;
+pub(crate) mod tls;
 mod clientconnection;
 mod clientwhitelist;
 mod tcp;
@@ -56,6 +63,36 @@ mod udp;
 /// Current version for message formats
 pub const PROTOCOL_VERSION: u8 = 1;
 
+/// Current version of the record schema, i.e. the layout of serialized originator information
+/// and record data.
+/// Fields are only ever appended to a structure, readers use default values for fields missing
+/// in data from a peer with an older schema and ignore trailing data from a peer with a newer
+/// schema.
+/// Version history:
+/// 1 - initial layout
+/// 2 - run ID, parent run ID and trace ID appended to originator information
+/// 3 - authentication token appended to originator information
+pub const SCHEMA_VERSION: u8 = 3;
+
+/// Oldest record schema version supported
+pub const MIN_SCHEMA_VERSION: u8 = 1;
+
+/// Bit position of the record schema version in the protocol information.
+/// Peers not supporting schema versioning leave these bits 0, denoting schema version 1.
+pub const PROTOCOL_SCHEMA_SHIFT: u32 = 16;
+
+/// Flag in protocol information of the connect request, indicating that all subsequent
+/// messages are sent as compressed stream
+pub const PROTOCOL_FLAG_STREAM_COMPRESSION: u32 = 0x100;
+
+/// Flag in protocol information of the connect request, indicating that the client requires
+/// acknowledgements for all record notifications
+pub const PROTOCOL_FLAG_ACKNOWLEDGE: u32 = 0x200;
+
+/// Flag in protocol information of a datagram, indicating that the datagram contains only a
+/// fragment of a message too large for a single datagram
+pub const PROTOCOL_FLAG_FRAGMENT: u32 = 0x400;
+
 
 #[derive(Clone,PartialEq)]
 #[repr(u32)]
@@ -63,8 +100,43 @@ pub enum NetworkProtocol {
     Tcp,
     Udp,
     #[cfg(unix)]
-    Unix
+    Unix,
+    #[cfg(feature="tls")]
+    Tls
+}
+
+/// Returns the protocol information for messages using the specified record schema version.
+///
+/// # Arguments
+/// * `schema_version` - the record schema version
+#[inline]
+pub fn protocol_info(schema_version: u8) -> u32 {
+    PROTOCOL_VERSION as u32 | (schema_version as u32) << PROTOCOL_SCHEMA_SHIFT
 }
+
+/// Returns the record schema version contained in the specified protocol information.
+///
+/// # Arguments
+/// * `protocol_info` - the protocol information from a message header
+#[inline]
+pub fn schema_version(protocol_info: u32) -> u8 {
+    u8::max(((protocol_info >> PROTOCOL_SCHEMA_SHIFT) & 255) as u8, 1)
+}
+
+/// Negotiates the record schema version to use for a connection.
+/// The negotiated version is the lower of the peer's and our own schema version.
+///
+/// # Arguments
+/// * `protocol_info` - the protocol information from the peer's connect request
+///
+/// # Return values
+/// the negotiated schema version, **None** if the peer's schema version is not supported
+pub fn negotiate_schema_version(protocol_info: u32) -> Option<u8> {
+    let peer_version = schema_version(protocol_info);
+    if peer_version < MIN_SCHEMA_VERSION { return None }
+    Some(u8::min(peer_version, SCHEMA_VERSION))
+}
+
 impl FromStr for NetworkProtocol {
     type Err = CoalyException;
 
@@ -72,7 +144,10 @@ impl FromStr for NetworkProtocol {
         match s {
             NW_PROT_TCP => Ok(NetworkProtocol::Tcp),
             NW_PROT_UDP => Ok(NetworkProtocol::Udp),
+            #[cfg(unix)]
             NW_PROT_UNIX => Ok(NetworkProtocol::Unix),
+            #[cfg(feature="tls")]
+            NW_PROT_TLS => Ok(NetworkProtocol::Tls),
             _ => Err(coalyxw!(E_CFG_INV_NW_PROTOCOL, s.to_string()))
         }
     }
@@ -83,7 +158,9 @@ impl NetworkProtocol {
             NetworkProtocol::Tcp => write!(f, "{}", NW_PROT_TCP),
             NetworkProtocol::Udp => write!(f, "{}", NW_PROT_UDP),
             #[cfg(unix)]
-            NetworkProtocol::Unix => write!(f, "{}", NW_PROT_UNIX)
+            NetworkProtocol::Unix => write!(f, "{}", NW_PROT_UNIX),
+            #[cfg(feature="tls")]
+            NetworkProtocol::Tls => write!(f, "{}", NW_PROT_TLS)
         }
     }
 }
@@ -95,9 +172,12 @@ impl Debug for NetworkProtocol {
 }
 
 /// Address of a remote peer
+#[derive(Clone)]
 pub enum PeerAddr {
     // Address of TCP or UDP socket
     IpSocket(NetworkProtocol, SocketAddr),
+    // Host name and port of TCP or UDP socket, resolved when connecting
+    HostSocket(NetworkProtocol, String, u16),
     // Path to Unix socket
     #[cfg(unix)]
     UnixSocket(String)
@@ -105,6 +185,10 @@ pub enum PeerAddr {
 impl PeerAddr {
     pub(crate) fn can_talk_to(&self, other: &PeerAddr) -> bool {
         if self.protocol() != other.protocol() { return false }
+        // IP version of a host name is not known before resolution
+        if matches!(self, PeerAddr::HostSocket(..)) || matches!(other, PeerAddr::HostSocket(..)) {
+            return true
+        }
         self.protocol_version() == other.protocol_version()
     }
     pub(crate) fn ip_addr(&self) -> Option<&SocketAddr> {
@@ -113,15 +197,37 @@ impl PeerAddr {
             _ => None
         }
     }
+    /// Indicates whether the peer is reached over a TCP stream, either plain or secured by TLS.
+    pub(crate) fn is_tcp_stream(&self) -> bool {
+        match self.protocol() {
+            NetworkProtocol::Tcp => true,
+            #[cfg(feature="tls")]
+            NetworkProtocol::Tls => true,
+            _ => false
+        }
+    }
+    /// Returns the host name or IP address of the peer, **None** for Unix sockets.
+    pub(crate) fn host(&self) -> Option<String> {
+        match self {
+            PeerAddr::IpSocket(_, addr) => Some(addr.ip().to_string()),
+            PeerAddr::HostSocket(_, host, _) => Some(host.to_string()),
+            #[cfg(unix)]
+            PeerAddr::UnixSocket(_) => None
+        }
+    }
     pub(crate) fn protocol(&self) -> &NetworkProtocol {
         match self {
             PeerAddr::IpSocket(prot, _) => prot,
+            PeerAddr::HostSocket(prot, _, _) => prot,
+            #[cfg(unix)]
             PeerAddr::UnixSocket(_) => &NetworkProtocol::Unix
         }
     }
     fn protocol_version(&self) -> u32 {
         match self {
             PeerAddr::IpSocket(_, addr) => if addr.is_ipv4() { 4 } else { 6 },
+            PeerAddr::HostSocket(..) => 0,
+            #[cfg(unix)]
             PeerAddr::UnixSocket(_) => 0
         }
     }
@@ -130,8 +236,11 @@ impl PeerAddr {
             PeerAddr::IpSocket(prot, addr) => {
                 write!(f, "{}:{}", prot, addr)
             },
+            PeerAddr::HostSocket(prot, host, port) => {
+                write!(f, "{}:{}:{}", prot, host, port)
+            },
             #[cfg(unix)]
-            PeerAddr::UnixSocket(path) => { write!(f, "unix:{}", path) }
+            PeerAddr::UnixSocket(path) => { write!(f, "unix://{}", path) }
         }
     }
 }
@@ -155,7 +264,10 @@ pub enum Message {
     // administrative client request to shutdown log/trace server
     ShutdownRequest(String),
     // shutdown confirmation response from log/trace server to administrative client
-    ShutdownResponse
+    ShutdownResponse,
+    // acknowledgement from log/trace server to client, contains the total number of
+    // record notifications received over the connection
+    RecordAcknowledgement(u64)
 }
 impl<'a> Serializable<'a> for Message {
     fn serialized_size(&self) -> usize {
@@ -164,7 +276,8 @@ impl<'a> Serializable<'a> for Message {
             Message::RecordNotification(rec) => 1 + rec.serialized_size(),
             Message::DisconnectNotification => 1,
             Message::ShutdownRequest(key) => 1 + key.serialized_size(),
-            Message::ShutdownResponse => 1
+            Message::ShutdownResponse => 1,
+            Message::RecordAcknowledgement(count) => 1 + count.serialized_size()
         }
     }
     fn serialize_to(&self, buffer: &mut Vec<u8>) -> usize {
@@ -188,6 +301,10 @@ impl<'a> Serializable<'a> for Message {
             Message::ShutdownResponse => {
                 buffer.push(SHUTDOWN_RESP_ID);
                 1
+            },
+            Message::RecordAcknowledgement(count) => {
+                buffer.push(RECORD_ACK_ID);
+                1 + count.serialize_to(buffer)
             }
         }
     }
@@ -206,6 +323,10 @@ impl<'a> Serializable<'a> for Message {
             return Ok(Message::ShutdownRequest(key))
         }
         if msg_type == SHUTDOWN_RESP_ID { return Ok(Message::ShutdownResponse) }
+        if msg_type == RECORD_ACK_ID {
+            let count = u64::deserialize_from(&buffer[1..])?;
+            return Ok(Message::RecordAcknowledgement(count))
+        }
         if msg_type == DISCONNECT_NOTIF_ID { return Ok(Message::DisconnectNotification) }
         Err(coalyxe!(E_DESER_ERR, String::from("Message")))
     }
@@ -213,9 +334,12 @@ impl<'a> Serializable<'a> for Message {
 
 
 /// Checks whether the given string contains a valid URL.
-/// An URL must start with a protocol specification (either tcp or udp), followed by a colon and
+/// An URL must start with a protocol specification (tcp, udp or tls), followed by a colon and
 /// two slashes, then an IP address (either IPv4 or IPv6),
 /// optionally terminated with a colon and a port.
+/// On Unix platforms, the URL may also specify a Unix domain socket with protocol unix,
+/// followed by the path name of the socket. The socket itself needn't exist, but its
+/// directory must.
 pub(crate) fn is_valid_url(url: &str) -> bool {
     let pattern = Regex::new(IP4_PATTERN).unwrap();
     if let Some(caps) = pattern.captures(url) {
@@ -225,16 +349,33 @@ pub(crate) fn is_valid_url(url: &str) -> bool {
     if let Some(caps) = pattern.captures(url) {
         return SocketAddr::from_str(caps.get(2).unwrap().as_str()).is_ok()
     }
-    let pattern = Regex::new(UNIX_PATTERN).unwrap();
+    #[cfg(unix)]
+    if let Some(caps) = Regex::new(UNIX_PATTERN).unwrap().captures(url) {
+        return is_valid_socket_path(caps.get(2).unwrap().as_str())
+    }
+    false
+}
+
+/// Checks whether the given string contains a valid URL for a remote peer.
+/// In addition to the URLs accepted by function `is_valid_url`, a host name may be used
+/// instead of an IP address.
+/// 
+/// # Arguments
+/// * `url` - the URL string
+/// 
+/// # Return values
+/// **true** if the string contains a valid URL for a remote peer
+pub(crate) fn is_valid_remote_url(url: &str) -> bool {
+    if is_valid_url(url) { return true }
+    let pattern = Regex::new(HOST_PATTERN).unwrap();
     if let Some(caps) = pattern.captures(url) {
-        let path_name = caps.get(2).unwrap().as_str();
-        return Path::new(&path_name).is_file()
+        return u16::from_str(caps.get(3).unwrap().as_str()).is_ok()
     }
     false
 }
 
 /// Parse URL string.
-/// URL must start with a protocol identifier (tcp:, udp: or unix:) followed by either an IP network
+/// URL must start with a protocol identifier (tcp:, udp:, tls: or unix:) followed by either an IP network
 /// address or a Unix file name.
 pub(crate) fn parse_url(url: &str) -> Result<PeerAddr, CoalyException> {
     let pattern = Regex::new(IP4_PATTERN).unwrap();
@@ -259,16 +400,106 @@ pub(crate) fn parse_url(url: &str) -> Result<PeerAddr, CoalyException> {
             Err(_) => return Err(coalyxe!(E_INVALID_URL, url.to_string()))
         }
     }
-    let pattern = Regex::new(UNIX_PATTERN).unwrap();
+    let pattern = Regex::new(HOST_PATTERN).unwrap();
     if let Some(capts) = pattern.captures(url) {
-        let path_name = capts.get(2).unwrap().as_str().to_string();
-        let path = Path::new(&path_name);
-        if ! path.is_file() { return Err(coalyxe!(E_INVALID_URL, url.to_string())) }
-        return Ok(PeerAddr::UnixSocket(path_name))
+        let prot = NetworkProtocol::from_str(capts.get(1).unwrap().as_str())?;
+        let host = capts.get(2).unwrap().as_str().to_string();
+        match u16::from_str(capts.get(3).unwrap().as_str()) {
+            Ok(port) => return Ok(PeerAddr::HostSocket(prot, host, port)),
+            Err(_) => return Err(coalyxe!(E_INVALID_URL, url.to_string()))
+        }
+    }
+    #[cfg(unix)]
+    if let Some(capts) = Regex::new(UNIX_PATTERN).unwrap().captures(url) {
+        let path_name = capts.get(2).unwrap().as_str();
+        if ! is_valid_socket_path(path_name) {
+            return Err(coalyxe!(E_INVALID_URL, url.to_string()))
+        }
+        return Ok(PeerAddr::UnixSocket(path_name.to_string()))
     }
     Err(coalyxe!(E_INVALID_URL, url.to_string()))
 }
 
+/// Checks whether the given path name can be used for a Unix domain socket.
+/// The socket file is created by the server, hence only the directory must exist.
+///
+/// # Arguments
+/// * `path_name` - the path name of the socket
+///
+/// # Return values
+/// **true** if the directory of the socket exists
+#[cfg(unix)]
+fn is_valid_socket_path(path_name: &str) -> bool {
+    match Path::new(path_name).parent() {
+        Some(dir) => dir.as_os_str().is_empty() || dir.is_dir(),
+        None => false
+    }
+}
+
+/// Parses an HTTP URL string.
+/// URL must start with http://, followed by a host name or IP address, an optional port
+/// and an optional path.
+/// 
+/// # Arguments
+/// * `url` - the URL string
+/// 
+/// # Return values
+/// host, port and path; **None** if the URL is not a valid HTTP URL
+pub(crate) fn parse_http_url(url: &str) -> Option<(String, u16, String)> {
+    let pattern = Regex::new(HTTP_PATTERN).unwrap();
+    let capts = pattern.captures(url)?;
+    let host = capts.get(1).unwrap().as_str().to_string();
+    let port = match capts.get(2) {
+        Some(p) => u16::from_str(p.as_str()).ok()?,
+        None => DEFAULT_HTTP_PORT
+    };
+    let path = capts.get(3).map_or("/", |p| p.as_str()).to_string();
+    Some((host, port, path))
+}
+
+/// Parses an HTTP or HTTPS URL string.
+/// URL must start with http:// or https://, followed by a host name or IP address, an optional
+/// port and an optional path.
+/// 
+/// # Arguments
+/// * `url` - the URL string
+/// 
+/// # Return values
+/// HTTPS indicator, host, port and path; **None** if the URL is not a valid HTTP or HTTPS URL
+pub(crate) fn parse_http_or_https_url(url: &str) -> Option<(bool, String, u16, String)> {
+    let pattern = Regex::new(HTTPS_PATTERN).unwrap();
+    let capts = pattern.captures(url)?;
+    let secure = capts.get(1).is_some();
+    let host = capts.get(2).unwrap().as_str().to_string();
+    let port = match capts.get(3) {
+        Some(p) => u16::from_str(p.as_str()).ok()?,
+        None => if secure { DEFAULT_HTTPS_PORT } else { DEFAULT_HTTP_PORT }
+    };
+    let path = capts.get(4).map_or("/", |p| p.as_str()).to_string();
+    Some((secure, host, port, path))
+}
+
+/// Parses a GELF URL string.
+/// URL must start with gelf+udp:// or gelf+tcp://, followed by a host name or IP address and
+/// an optional port.
+/// 
+/// # Arguments
+/// * `url` - the URL string
+/// 
+/// # Return values
+/// transport protocol, host and port; **None** if the URL is not a valid GELF URL
+pub(crate) fn parse_gelf_url(url: &str) -> Option<(NetworkProtocol, String, u16)> {
+    let pattern = Regex::new(GELF_PATTERN).unwrap();
+    let capts = pattern.captures(url)?;
+    let protocol = NetworkProtocol::from_str(capts.get(1).unwrap().as_str()).ok()?;
+    let host = capts.get(2).unwrap().as_str().to_string();
+    let port = match capts.get(3) {
+        Some(p) => u16::from_str(p.as_str()).ok()?,
+        None => DEFAULT_GELF_PORT
+    };
+    Some((protocol, host, port))
+}
+
 /// Parses an URL string and returns specified protocol and IP address. 
 //pub(crate) fn parse_url(url: &str) -> Option<(NetworkProtocol, SocketAddr)> {
 //    let url_pattern = Regex::new(URL_PATTERN).unwrap();
@@ -283,10 +514,21 @@ pub(crate) fn parse_url(url: &str) -> Result<PeerAddr, CoalyException> {
 
 const NW_PROT_TCP: &str = "tcp";
 const NW_PROT_UDP: &str = "udp";
+#[cfg(unix)]
 const NW_PROT_UNIX: &str = "unix";
-const IP4_PATTERN: &str = r"^(tcp|udp)://([\d\.]+:\d+)$";
-const IP6_PATTERN: &str = r"^(tcp|udp)://\[(\d\.]+\]:\d+)$";
-const UNIX_PATTERN: &str = r"^(unix):(.*)$";
+#[cfg(feature="tls")]
+const NW_PROT_TLS: &str = "tls";
+const IP4_PATTERN: &str = r"^(tcp|udp|tls)://([\d\.]+:\d+)$";
+const IP6_PATTERN: &str = r"^(tcp|udp|tls)://\[(\d\.]+\]:\d+)$";
+#[cfg(unix)]
+const UNIX_PATTERN: &str = r"^(unix):(?://)?(.+)$";
+const HOST_PATTERN: &str = r"^(tcp|udp|tls)://([A-Za-z][\w\-]*(?:\.[\w\-]+)*):(\d+)$";
+const HTTP_PATTERN: &str = r"^http://([\w\.\-]+|\[[\da-fA-F:]+\])(?::(\d+))?(/\S*)?$";
+const DEFAULT_HTTP_PORT: u16 = 80;
+const HTTPS_PATTERN: &str = r"^http(s)?://([\w\.\-]+|\[[\da-fA-F:]+\])(?::(\d+))?(/\S*)?$";
+const DEFAULT_HTTPS_PORT: u16 = 443;
+const GELF_PATTERN: &str = r"^gelf\+(tcp|udp)://([\w\.\-]+|\[[\da-fA-F:]+\])(?::(\d+))?$";
+const DEFAULT_GELF_PORT: u16 = 12201;
 
 /// Message type ID for new client notification
 const CLIENT_NOTIF_ID: u8 = 11;
@@ -303,14 +545,17 @@ const SHUTDOWN_REQ_ID: u8 = 21;
 /// Message type ID for shutdown response
 const SHUTDOWN_RESP_ID: u8 = 31;
 
+/// Message type ID for record acknowledgement
+const RECORD_ACK_ID: u8 = 32;
+
 //const URL_PATTERN: &str = "^(tcp|udp)://(.*)$";
 
-#[cfg(all(net, test))]
+#[cfg(all(feature="net", test))]
 mod tests {
     use super::*;
     use core::fmt::Debug;
     use crate::record::RecordLevelId;
-    use crate::record::recorddata::LocalRecordData;
+    use crate::record::recorddata::{LocalRecordData, RemoteRecordData};
 
     fn check_serialization<'a, T>(item: &'a T, expected_size: usize, buffer: &'a mut Vec<u8>)
         where T: Serializable<'a> + Debug + Eq {
@@ -331,7 +576,7 @@ mod tests {
         oinfo.add_env_var("COALYROOT", "/var/log/superapp");
         oinfo.add_env_var("LANG", "en");
         let msg = Message::ClientNotification(oinfo);
-        check_serialization::<Message>(&msg, 143, &mut buffer);
+        check_serialization::<Message>(&msg, 177, &mut buffer);
     }
 
     #[test]
@@ -339,7 +584,7 @@ mod tests {
         let mut buffer = Vec::<u8>::with_capacity(256);
         let local_rec = LocalRecordData::for_write(1234, "mythread", RecordLevelId::Error, 
                                                    "test.rs", 393, "blabla");
-        let remote_rec = local_rec.as_remote();
+        let remote_rec = RemoteRecordData::from(local_rec);
         let msg = Message::RecordNotification(remote_rec);
         check_serialization::<Message>(&msg, 90, &mut buffer);
     }
@@ -365,4 +610,47 @@ mod tests {
         let msg = Message::ShutdownResponse;
         check_serialization::<Message>(&msg, 1, &mut buffer);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_serialize_record_acknowledgement() {
+        let mut buffer = Vec::<u8>::with_capacity(256);
+        let msg = Message::RecordAcknowledgement(4711);
+        check_serialization::<Message>(&msg, 9, &mut buffer);
+    }
+
+    #[test]
+    fn test_parse_http_url() {
+        assert_eq!(Some((String::from("hooks.local"), 80, String::from("/"))),
+                   parse_http_url("http://hooks.local"));
+        assert_eq!(Some((String::from("127.0.0.1"), 8080, String::from("/services/T0/B0"))),
+                   parse_http_url("http://127.0.0.1:8080/services/T0/B0"));
+        assert_eq!(Some((String::from("[::1]"), 9000, String::from("/alert"))),
+                   parse_http_url("http://[::1]:9000/alert"));
+        assert!(parse_http_url("https://hooks.local/alert").is_none());
+        assert!(parse_http_url("http://hooks.local:99999/alert").is_none());
+        assert!(parse_http_url("tcp://127.0.0.1:8080").is_none());
+    }
+
+    #[test]
+    fn test_parse_gelf_url() {
+        assert_eq!(Some((NetworkProtocol::Udp, String::from("graylog.local"), 12201)),
+                   parse_gelf_url("gelf+udp://graylog.local"));
+        assert_eq!(Some((NetworkProtocol::Tcp, String::from("127.0.0.1"), 12202)),
+                   parse_gelf_url("gelf+tcp://127.0.0.1:12202"));
+        assert_eq!(Some((NetworkProtocol::Udp, String::from("[::1]"), 12201)),
+                   parse_gelf_url("gelf+udp://[::1]:12201"));
+        assert!(parse_gelf_url("udp://graylog.local:12201").is_none());
+        assert!(parse_gelf_url("gelf+http://graylog.local:12201").is_none());
+        assert!(parse_gelf_url("gelf+udp://graylog.local:99999").is_none());
+        assert!(parse_gelf_url("gelf+tcp://graylog.local:12201/path").is_none());
+    }
+
+    #[test]
+    fn test_negotiate_schema_version() {
+        assert_eq!(1, schema_version(PROTOCOL_VERSION as u32 | PROTOCOL_FLAG_ACKNOWLEDGE));
+        assert_eq!(SCHEMA_VERSION, schema_version(protocol_info(SCHEMA_VERSION)));
+        assert_eq!(Some(1), negotiate_schema_version(PROTOCOL_VERSION as u32));
+        assert_eq!(Some(SCHEMA_VERSION), negotiate_schema_version(protocol_info(SCHEMA_VERSION)));
+        assert_eq!(Some(SCHEMA_VERSION), negotiate_schema_version(protocol_info(200)));
+    }
+}
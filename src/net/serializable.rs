@@ -62,6 +62,21 @@ pub trait Serializable<'a> {
     where Self: std::marker::Sized;
 }
 
+/// Deserializes an element appended to a structure in a later record schema version.
+/// Peers using an older schema don't send the element, hence an exhausted buffer yields the
+/// element's default value.
+///
+/// # Arguments
+/// * `buffer` - the buffer containing the serialized element, may be empty
+///
+/// # Return values
+/// the deserialized element or its default value in case of success; otherwise Error
+pub fn deserialize_or_default<'a, T>(buffer: &'a [u8]) -> Result<T, CoalyException>
+    where T: Serializable<'a> + Default {
+    if buffer.is_empty() { return Ok(T::default()) }
+    T::deserialize_from(buffer)
+}
+
 impl<'a> fmt::Debug for dyn Serializable<'a> {
     fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
         Ok(())
@@ -243,7 +258,7 @@ where K: Serializable<'a> + std::cmp::Ord, V: Serializable<'a> {
     }
 }
 
-#[cfg(all(net, test))]
+#[cfg(all(feature="net", test))]
 mod tests {
     use super::*;
     use core::fmt::Debug;
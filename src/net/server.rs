@@ -33,18 +33,27 @@
 use crate::*;
 use crate::net::serverproperties::ServerProperties;
 use crate::errorhandling::*;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::net::{TcpListener, UdpSocket};
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::sync::broadcast::*;
 
 use super::{NetworkProtocol, parse_url, is_valid_url};
+#[cfg(unix)]
+use super::PeerAddr;
+use super::auth::{ClientAdmission, ServerAuthorizer};
 use super::clientwhitelist::ClientWhitelist;
-use super::tcp::{tcp_admin_listener, tcp_record_listener};
+use super::tcp::{tcp_admin_listener, stream_record_listener, RecordListener};
+#[cfg(feature="tls")]
+use super::tls::server_acceptor;
 use super::udp::{UdpAdminHandler, UdpRecordHandler};
 
 
 pub struct TraceServer {
     properties: ServerProperties,
+    authorizer: Option<Arc<dyn ServerAuthorizer>>,
     shutdown_ch_tx: Sender<bool>,
     shutdown_ch_rx: Receiver<bool>,
     is_running: AtomicBool
@@ -81,6 +90,7 @@ impl TraceServer {
                 initialize(file_name);
                 let (shutdown_ch_tx, shutdown_ch_rx) = channel::<bool>(1);
                 Ok(TraceServer { properties: srv_props.clone(),
+                                 authorizer: None,
                                  shutdown_ch_tx,
                                  shutdown_ch_rx,
                                  is_running: AtomicBool::new(false) } )
@@ -108,11 +118,23 @@ impl TraceServer {
         }
         let (shutdown_ch_tx, shutdown_ch_rx) = channel::<bool>(1);
         Ok(TraceServer { properties: properties.clone(),
+                         authorizer: None,
                          shutdown_ch_tx,
                          shutdown_ch_rx,
                          is_running: AtomicBool::new(false) } )
     }
 
+    /// Installs hooks for application specific authentication of clients and authorization
+    /// of the records they send.
+    /// The hooks are consulted in addition to the data client whitelist from the server
+    /// properties. Must be called before the server is started.
+    ///
+    /// # Arguments
+    /// * `authorizer` - the authorizer
+    pub fn set_authorizer(&mut self, authorizer: Arc<dyn ServerAuthorizer>) {
+        self.authorizer = Some(authorizer);
+    }
+
     /// Runs the log and trace server.
     /// Terminates, if a shutdown message has been sent to the administrative network port, or
     /// method terminate has been called.
@@ -121,13 +143,16 @@ impl TraceServer {
     /// * `handle_ctrlc` - **true**, if a handler to detect CTRL-C shall be installed
     /// * `handle_term` - **true**, if a handler to detect termination from shell command
     ///                   shall be installed
+    ///
+    /// # Errors
+    /// Returns an error structure if the server can't listen on the data address
     pub async fn run(&mut self,
                      handle_ctrlc: bool,
-                     handle_term: bool) {
+                     handle_term: bool) -> Result<(), CoalyException> {
         // if server is already running, there's nothing to do
         if self.is_running.compare_exchange(false, true,
                                             Ordering::Relaxed, Ordering::Relaxed).is_err() {
-            return
+            return Ok(())
         }
         // install termination detection handlers, if specified
         if handle_ctrlc {
@@ -144,15 +169,23 @@ impl TraceServer {
         // server properties
         self.install_admin_handler().await;
         // install handler for log and trace records from the network
-        self.install_data_handler().await;
+        if let Err(ex) = self.install_data_handler().await {
+            // stop handlers already installed
+            let _ = self.shutdown_ch_tx.send(true);
+            self.is_running.store(false, Ordering::Relaxed);
+            return Err(ex)
+        }
         
         // wait for termination event
         let _ = self.shutdown_ch_rx.recv().await;
+        self.is_running.store(false, Ordering::Relaxed);
+        Ok(())
     }
 
     /// Terminates the log and trace server.
     /// Invoke this function, if CTRL-C or a termination signal has been detected.
     pub fn terminate(&mut self) {
+        let _ = self.shutdown_ch_tx.send(true);
     }
 
     /// Installs a handler for administrative commands, if a valid address is specified in the
@@ -186,46 +219,104 @@ impl TraceServer {
                 #[cfg(unix)]
                 NetworkProtocol::Unix => {
                     // TODO
-                }
+                },
+                // administrative commands over TLS are not supported
+                #[cfg(feature="tls")]
+                NetworkProtocol::Tls => ()
             }
         }
     }
 
     /// Installs a handler for log and trace records sent over the network.
-    async fn install_data_handler(&mut self) {
+    ///
+    /// # Errors
+    /// Returns an error structure if the server can't listen on the data address
+    async fn install_data_handler(&mut self) -> Result<(), CoalyException> {
         let listen_addr = parse_url(self.properties.data_listen_address()).unwrap();
         let prot = listen_addr.protocol();
         let max_conns = self.properties.max_connections();
         let max_msg_size = self.properties.max_msg_size();
         let keep_time = self.properties.keep_connection();
         let allowed_ips = self.properties.data_clients();
-        let client_whitelist = ClientWhitelist::from_ip_and_app_ids(allowed_ips);
+        let admission = ClientAdmission::new(ClientWhitelist::from_ip_and_app_ids(allowed_ips),
+                                             self.authorizer.clone());
         let bc_tx = self.shutdown_ch_tx.clone();
         let bc_rx = self.shutdown_ch_tx.subscribe();
         match prot {
             NetworkProtocol::Udp => {
                 let listen_addr = listen_addr.ip_addr().unwrap();
-                if let Ok(sock) = UdpSocket::bind(&listen_addr).await {
-                    let mut rec_handler = UdpRecordHandler::new(sock, client_whitelist,
-                                                                bc_tx, bc_rx, max_msg_size);
-                    tokio::spawn(async move { rec_handler.run(max_conns, keep_time).await; });
-                }
+                let sock = UdpSocket::bind(&listen_addr).await
+                                     .map_err(|e| coalyxe!(E_SRV_BIND_FAILED,
+                                                           listen_addr.to_string(),
+                                                           e.to_string()))?;
+                let mut rec_handler = UdpRecordHandler::new(sock, admission,
+                                                            bc_tx, bc_rx, max_msg_size);
+                tokio::spawn(async move { rec_handler.run(max_conns, keep_time).await; });
             },
             NetworkProtocol::Tcp => {
                 let listen_addr = listen_addr.ip_addr().unwrap();
-                if let Ok(sock) = TcpListener::bind(&listen_addr).await {
-                    tokio::spawn(async move {
-                        tcp_record_listener(sock, max_conns, max_msg_size, &client_whitelist,
-                                            bc_tx, bc_rx).await;
-                    });
-                }
+                let sock = TcpListener::bind(&listen_addr).await
+                                       .map_err(|e| coalyxe!(E_SRV_BIND_FAILED,
+                                                             listen_addr.to_string(),
+                                                             e.to_string()))?;
+                tokio::spawn(async move {
+                    stream_record_listener(RecordListener::Tcp(sock), None, max_conns,
+                                           max_msg_size, &admission, bc_tx, bc_rx).await;
+                });
             },
             #[cfg(unix)]
             NetworkProtocol::Unix => {
-                // TODO
+                let path = match &listen_addr {
+                    PeerAddr::UnixSocket(path) => path.to_string(),
+                    _ => return Err(coalyxe!(E_SRV_INV_DATA_ADDR, listen_addr.to_string()))
+                };
+                let sock = bind_unix_listener(&path)
+                               .map_err(|e| coalyxe!(E_SRV_BIND_FAILED, path.to_string(),
+                                                     e.to_string()))?;
+                tokio::spawn(async move {
+                    stream_record_listener(RecordListener::Unix(sock, 0), None, max_conns,
+                                           max_msg_size, &admission, bc_tx, bc_rx).await;
+                });
+            },
+            #[cfg(feature="tls")]
+            NetworkProtocol::Tls => {
+                let listen_addr = listen_addr.ip_addr().unwrap();
+                if self.properties.tls_cert_file().is_empty() ||
+                   self.properties.tls_key_file().is_empty() {
+                    return Err(coalyxe!(E_SRV_TLS_CERT_MISSING, listen_addr.to_string()))
+                }
+                let acceptor = server_acceptor(self.properties.tls_cert_file(),
+                                               self.properties.tls_key_file(),
+                                               self.properties.tls_client_ca_file())?;
+                let sock = TcpListener::bind(&listen_addr).await
+                                       .map_err(|e| coalyxe!(E_SRV_BIND_FAILED,
+                                                             listen_addr.to_string(),
+                                                             e.to_string()))?;
+                tokio::spawn(async move {
+                    stream_record_listener(RecordListener::Tcp(sock), Some(acceptor), max_conns,
+                                           max_msg_size, &admission, bc_tx, bc_rx).await;
+                });
             }
         }
+        Ok(())
+    }
+}
+
+/// Creates a listener on a Unix domain socket.
+/// A socket file left over from a previous server run is removed before.
+///
+/// # Arguments
+/// * `path` - the path name of the socket
+///
+/// # Errors
+/// Returns an I/O error if the socket can't be created
+#[cfg(unix)]
+fn bind_unix_listener(path: &str) -> std::io::Result<UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if meta.file_type().is_socket() { std::fs::remove_file(path)?; }
     }
+    UnixListener::bind(path)
 }
 
 
@@ -246,6 +337,21 @@ async fn detect_ctrlc(tx_channel: Sender<bool>, mut rx_channel: Receiver<bool>)
     }
 }
 
+/// Handler to detect CTRL-C from terminal.
+/// 
+/// # Arguments
+/// * `tx_channel` - the sender side of the broadcast channel, used to indicate that CTRL-C has
+///                  been detected
+/// * `rx_channel` - the receiver side of the broadcast channel, used to terminate this handler,
+///                  if the server will shutdown because of another event
+#[cfg(not(unix))]
+async fn detect_ctrlc(tx_channel: Sender<bool>, mut rx_channel: Receiver<bool>) {
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => { let _ = tx_channel.send(true); }
+        _ = rx_channel.recv() => {}
+    }
+}
+
 /// Handler to detect SIGTERM signal, usually from kill command.
 /// 
 /// # Arguments
@@ -262,3 +368,16 @@ async fn detect_term(tx_channel: Sender<bool>, mut rx_channel: Receiver<bool>) {
         }
     }
 }
+
+/// Handler to detect termination from shell command.
+/// Termination signals are not available on this platform, the handler just waits for the
+/// server shutdown.
+/// 
+/// # Arguments
+/// * `_tx_channel` - the sender side of the broadcast channel
+/// * `rx_channel` - the receiver side of the broadcast channel, used to terminate this handler,
+///                  if the server will shutdown because of another event
+#[cfg(not(unix))]
+async fn detect_term(_tx_channel: Sender<bool>, mut rx_channel: Receiver<bool>) {
+    let _ = rx_channel.recv().await;
+}
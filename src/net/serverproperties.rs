@@ -34,9 +34,12 @@
 
 use std::fmt::{Debug, Formatter};
 use crate::coalyxw;
-use crate::config::{int_par, not_table_item, read_app_ids, size_par, str_par};
+use crate::config::{int_par, not_table_item, read_app_ids, size_par, str_par, DEFAULT_SYSLOG_URL};
+use crate::config::resource::ResourceDesc;
 use crate::config::toml::document::TomlValueItem;
 use crate::errorhandling::*;
+use crate::record::RecordLevelId;
+use super::{is_valid_remote_url, is_valid_url};
 
 // Default value and range for maximum number of connections
 pub const DEF_MAX_CXNS: usize = 10;
@@ -53,6 +56,27 @@ pub const DEF_MAX_MSG_SIZE: usize = 65536;
 pub const MIN_MAX_MSG_SIZE: usize = 128;
 pub const MAX_MAX_MSG_SIZE: usize = u32::MAX as usize;
 
+/// Storage for log and trace records received from clients.
+#[derive (Clone)]
+pub enum StorageDesc {
+    /// Records are written to the output resources specified in the configuration file
+    File,
+    /// Records are forwarded to a syslog server, the descriptor specifies the syslog resource
+    Syslog(ResourceDesc),
+    /// Records are forwarded to another Coaly log server, the descriptor specifies the
+    /// network resource
+    Relay(ResourceDesc)
+}
+impl Debug for StorageDesc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageDesc::File => write!(f, "{}", STORAGE_KIND_FILE),
+            StorageDesc::Syslog(d) => write!(f, "{}({:?})", STORAGE_KIND_SYSLOG, d),
+            StorageDesc::Relay(d) => write!(f, "{}({:?})", STORAGE_KIND_RELAY, d)
+        }
+    }
+}
+
 /// Coaly server properties.
 /// All properties are specified under TOML table server in the custom configuration file.
 #[derive (Clone)]
@@ -74,7 +98,16 @@ pub struct ServerProperties {
     data_clients: Vec<(String, Vec<u32>)>,
     // list of allowed clients to administer the server (IP address),
     // defaults to any port on local host ["127.0.0.1:0","[::1]:0"]
-    admin_clients: Vec<String>
+    admin_clients: Vec<String>,
+    // storages for records received from clients, defaults to file storage only
+    storages: Vec<StorageDesc>,
+    // file with the server certificate chain in PEM format, needed for TLS listen addresses
+    tls_cert_file: String,
+    // file with the server private key in PEM format, needed for TLS listen addresses
+    tls_key_file: String,
+    // file with CA certificates to verify client certificates, defaults to empty string
+    // meaning clients are not required to authenticate
+    tls_client_ca_file: String
 }
 impl ServerProperties {
     /// Returns the local network address to be used as listen address for
@@ -174,6 +207,50 @@ impl ServerProperties {
     /// Removes all currently allowed admin clients
     #[inline]
     pub fn clear_admin_clients(&mut self) { self.admin_clients.clear(); }
+
+    /// Returns the storages for records received from clients
+    #[inline]
+    pub fn storages(&self) -> &[StorageDesc] { &self.storages }
+
+    /// Sets the storages for records received from clients.
+    /// File storage is used, if the given array is empty.
+    ///
+    /// # Arguments
+    /// * `storages` - the storage descriptors
+    pub fn set_storages(&mut self, storages: Vec<StorageDesc>) {
+        self.storages = if storages.is_empty() { vec!(StorageDesc::File) } else { storages };
+    }
+
+    /// Returns the file containing the server certificate chain for TLS connections
+    #[inline]
+    pub fn tls_cert_file(&self) -> &String { &self.tls_cert_file }
+
+    /// Sets the file containing the server certificate chain for TLS connections
+    #[inline]
+    pub fn set_tls_cert_file(&mut self, file_name: &str) {
+        self.tls_cert_file = file_name.to_string()
+    }
+
+    /// Returns the file containing the server private key for TLS connections
+    #[inline]
+    pub fn tls_key_file(&self) -> &String { &self.tls_key_file }
+
+    /// Sets the file containing the server private key for TLS connections
+    #[inline]
+    pub fn set_tls_key_file(&mut self, file_name: &str) {
+        self.tls_key_file = file_name.to_string()
+    }
+
+    /// Returns the file containing the CA certificates used to verify client certificates.
+    /// An empty string means, that clients don't have to authenticate.
+    #[inline]
+    pub fn tls_client_ca_file(&self) -> &String { &self.tls_client_ca_file }
+
+    /// Sets the file containing the CA certificates used to verify client certificates
+    #[inline]
+    pub fn set_tls_client_ca_file(&mut self, file_name: &str) {
+        self.tls_client_ca_file = file_name.to_string()
+    }
 }
 impl Default for ServerProperties {
     fn default() -> Self {
@@ -191,7 +268,11 @@ impl Default for ServerProperties {
             max_msg_size: DEF_MAX_MSG_SIZE,
             admin_key: String::from(""),
             data_clients: dcls,
-            admin_clients: acls
+            admin_clients: acls,
+            storages: vec!(StorageDesc::File),
+            tls_cert_file: String::from(""),
+            tls_key_file: String::from(""),
+            tls_client_ca_file: String::from("")
         }
     }
 }
@@ -225,7 +306,18 @@ impl Debug for ServerProperties {
         write!(f,
                "DLA:{}/ALA:{}/MCX:{}/KCX:{}/MMS:{}/KEY:{}/DCL:{}/ACL:{}",
                self.data_listen_address, self.admin_listen_address, self.max_connections,
-               self.keep_connection, self.max_msg_size, self.admin_key, dcl_buf, acl_buf)
+               self.keep_connection, self.max_msg_size, self.admin_key, dcl_buf, acl_buf)?;
+        // storages are only shown if they differ from the default
+        if ! matches!(self.storages.as_slice(), [StorageDesc::File]) {
+            write!(f, "/STO:{:?}", self.storages)?;
+        }
+        // TLS settings are only shown if specified
+        if ! self.tls_cert_file.is_empty() || ! self.tls_key_file.is_empty() ||
+           ! self.tls_client_ca_file.is_empty() {
+            write!(f, "/TLS:{{CRT:{}/KEY:{}/CCA:{}}}",
+                   self.tls_cert_file, self.tls_key_file, self.tls_client_ca_file)?;
+        }
+        Ok(())
     }
 }
 
@@ -285,6 +377,25 @@ pub(crate) fn read_server_properties(srv_item: &TomlValueItem,
                 let full_clients_key = format!("{}.{}", TOML_GRP_SERVER, srv_key);
                 read_allowed_admin_clients(srv_val, &full_clients_key, &mut sp, msgs);
             },
+            TOML_GRP_STORAGE => {
+                let full_storage_key = format!("{}.{}", TOML_GRP_SERVER, srv_key);
+                sp.set_storages(read_storages(srv_val, &full_storage_key, msgs));
+            },
+            TOML_PAR_TLS_CERT_FILE => {
+                if str_par(srv_val, srv_key, TOML_GRP_SERVER, msgs) {
+                    sp.set_tls_cert_file(&srv_val.value().as_str().unwrap());
+                }
+            },
+            TOML_PAR_TLS_KEY_FILE => {
+                if str_par(srv_val, srv_key, TOML_GRP_SERVER, msgs) {
+                    sp.set_tls_key_file(&srv_val.value().as_str().unwrap());
+                }
+            },
+            TOML_PAR_TLS_CLIENT_CA_FILE => {
+                if str_par(srv_val, srv_key, TOML_GRP_SERVER, msgs) {
+                    sp.set_tls_client_ca_file(&srv_val.value().as_str().unwrap());
+                }
+            },
             _ => {
                 let full_key = format!("{}.{}", TOML_GRP_SERVER, srv_key);
                 msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, srv_val.line_nr(), full_key));
@@ -363,15 +474,136 @@ fn read_allowed_admin_clients(clients_item: &TomlValueItem,
                      TOML_PAR_ADMIN_CLIENTS.to_string()));
 }
 
+/// Reads the storages for records received from clients from custom configuration.
+/// 
+/// # Arguments
+/// * `storage_item` - the value item for the storages
+/// * `storage_full_key` - the full name of the storage TOML item
+/// * `msgs` - the array, where error messages shall be stored
+/// 
+/// # Return values
+/// the valid storage descriptors
+fn read_storages(storage_item: &TomlValueItem,
+                 storage_full_key: &str,
+                 msgs: &mut Vec<CoalyException>) -> Vec<StorageDesc> {
+    let mut storages = Vec::<StorageDesc>::new();
+    if ! storage_item.is_array_of_tables() {
+        msgs.push(coalyxw!(W_CFG_KEY_NOT_AN_ARRAY, storage_item.line_nr(),
+                           storage_full_key.to_string()));
+        return storages
+    }
+    for storage_spec in storage_item.child_values().unwrap() {
+        let mut kind: Option<String> = None;
+        let mut remote_url: Option<String> = None;
+        let mut local_url: Option<String> = None;
+        let mut facility: Option<u32> = None;
+        let mut pars = Vec::<(&str, String)>::new();
+        for (attr_key, attr_val) in storage_spec.child_items().unwrap() {
+            match attr_key.as_str() {
+                TOML_PAR_KIND => {
+                    if str_par(attr_val, attr_key, storage_full_key, msgs) {
+                        kind = Some(attr_val.value().as_str().unwrap());
+                    }
+                },
+                TOML_PAR_REMOTE_URL => {
+                    if str_par(attr_val, attr_key, storage_full_key, msgs) {
+                        remote_url = Some(attr_val.value().as_str().unwrap());
+                        pars.push((TOML_PAR_REMOTE_URL, attr_val.line_nr()));
+                    }
+                },
+                TOML_PAR_LOCAL_URL => {
+                    if str_par(attr_val, attr_key, storage_full_key, msgs) {
+                        local_url = Some(attr_val.value().as_str().unwrap());
+                        pars.push((TOML_PAR_LOCAL_URL, attr_val.line_nr()));
+                    }
+                },
+                TOML_PAR_FACILITY => {
+                    if int_par(attr_val, attr_key, storage_full_key, 0, 23, 1, msgs) {
+                        facility = Some(attr_val.value().as_integer().unwrap() as u32);
+                        pars.push((TOML_PAR_FACILITY, attr_val.line_nr()));
+                    }
+                },
+                _ => {
+                    let full_key = format!("{}.{}", storage_full_key, attr_key);
+                    msgs.push(coalyxw!(W_CFG_UNKNOWN_KEY, attr_val.line_nr(), full_key));
+                }
+            }
+        }
+        if let Some(ref u) = local_url {
+            if ! is_valid_url(u) {
+                msgs.push(coalyxw!(W_CFG_INV_STORAGE_URL, storage_spec.line_nr(), u.to_string()));
+                continue
+            }
+        }
+        let kind_name = kind.unwrap_or_default();
+        let valid_pars: &[&str] = match kind_name.as_str() {
+            STORAGE_KIND_FILE => {
+                storages.push(StorageDesc::File);
+                &[]
+            },
+            STORAGE_KIND_SYSLOG => {
+                let url = remote_url.unwrap_or(String::from(DEFAULT_SYSLOG_URL));
+                if ! is_valid_remote_url(&url) {
+                    msgs.push(coalyxw!(W_CFG_INV_STORAGE_URL, storage_spec.line_nr(), url));
+                    continue
+                }
+                let desc = ResourceDesc::for_syslog(&[0], RecordLevelId::All as u32, None,
+                                                    facility.unwrap_or(1), &url,
+                                                    local_url.as_ref());
+                storages.push(StorageDesc::Syslog(desc));
+                &[TOML_PAR_REMOTE_URL, TOML_PAR_LOCAL_URL, TOML_PAR_FACILITY]
+            },
+            STORAGE_KIND_RELAY => {
+                let url = remote_url.unwrap_or_default();
+                if ! is_valid_remote_url(&url) {
+                    msgs.push(coalyxw!(W_CFG_INV_STORAGE_URL, storage_spec.line_nr(), url));
+                    continue
+                }
+                let desc = ResourceDesc::for_network(&[0], RecordLevelId::All as u32, None,
+                                                     &url, local_url.as_ref(), false);
+                storages.push(StorageDesc::Relay(desc));
+                &[TOML_PAR_REMOTE_URL, TOML_PAR_LOCAL_URL]
+            },
+            _ => {
+                let mut ex = coalyxw!(W_CFG_INV_STORAGE_KIND, storage_spec.line_nr(), kind_name);
+                ex.set_hint(coalyxw!(W_CFG_VALID_VALUES, STORAGE_KIND_NAMES.join(", ")));
+                msgs.push(ex);
+                continue
+            }
+        };
+        for (par_name, lnr) in pars {
+            if ! valid_pars.contains(&par_name) {
+                msgs.push(coalyxw!(W_CFG_MEANINGLESS_STORAGE_PAR, lnr, par_name.to_string(),
+                                   kind_name.to_string()));
+            }
+        }
+    }
+    storages
+}
+
 const TOML_GRP_DATA_CLIENTS: &str = "data_clients";
 const TOML_GRP_SERVER: &str = "server";
+const TOML_GRP_STORAGE: &str = "storage";
 
 const TOML_PAR_ADMIN_ADDR: &str = "admin_addr";
 const TOML_PAR_ADMIN_CLIENTS: &str = "admin_clients";
 const TOML_PAR_ADMIN_KEY: &str = "admin_key";
 const TOML_PAR_APP_IDS: &str = "app_ids";
 const TOML_PAR_DATA_ADDR: &str = "data_addr";
+const TOML_PAR_FACILITY: &str = "facility";
 const TOML_PAR_KEEP_CONNECTION: &str = "keep_connection";
+const TOML_PAR_KIND: &str = "kind";
+const TOML_PAR_LOCAL_URL: &str = "local_url";
 const TOML_PAR_MAX_CONNECTIONS: &str = "max_connections";
 const TOML_PAR_MAX_MSG_SIZE: &str = "max_msg_size";
+const TOML_PAR_REMOTE_URL: &str = "remote_url";
 const TOML_PAR_SOURCE: &str = "source";
+const TOML_PAR_TLS_CERT_FILE: &str = "tls_cert_file";
+const TOML_PAR_TLS_CLIENT_CA_FILE: &str = "tls_client_ca_file";
+const TOML_PAR_TLS_KEY_FILE: &str = "tls_key_file";
+
+// Names of the storage kinds for records received from clients
+const STORAGE_KIND_FILE: &str = "file";
+const STORAGE_KIND_RELAY: &str = "relay";
+const STORAGE_KIND_SYSLOG: &str = "syslog";
+const STORAGE_KIND_NAMES: &[&str] = &[STORAGE_KIND_FILE, STORAGE_KIND_SYSLOG, STORAGE_KIND_RELAY];
@@ -0,0 +1,206 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Compression of the message stream between client and log server over TCP.
+//! All messages following the connect request are compressed as a single deflate stream, thus
+//! the compression dictionary is reused across records. Every message is terminated with a
+//! sync flush, hence the server can decompress and process a record as soon as it arrives.
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress};
+use crate::coalyxe;
+use crate::errorhandling::*;
+use super::Message;
+use super::serializable::Serializable;
+
+/// Compressor for the client side of a TCP connection.
+pub(crate) struct StreamCompressor {
+    // deflate engine, keeping the dictionary across messages
+    engine: Compress,
+    // buffer for the compressed data of the last message
+    output: Vec<u8>
+}
+impl StreamCompressor {
+    /// Creates a stream compressor.
+    pub(crate) fn new() -> StreamCompressor {
+        StreamCompressor {
+            engine: Compress::new(Compression::fast(), false),
+            output: Vec::<u8>::with_capacity(STREAM_BUFFER_SIZE)
+        }
+    }
+
+    /// Compresses a message.
+    ///
+    /// # Arguments
+    /// * `data` - the serialized message
+    ///
+    /// # Return values
+    /// the compressed message, including all data needed by the receiver to decompress it
+    pub(crate) fn compress(&mut self, data: &[u8]) -> &[u8] {
+        self.output.clear();
+        let mut consumed = 0;
+        loop {
+            if self.output.capacity() - self.output.len() < MIN_SPARE_CAPACITY {
+                self.output.reserve(data.len() - consumed + MIN_SPARE_CAPACITY);
+            }
+            let total_in = self.engine.total_in();
+            let total_out = self.engine.total_out();
+            // deflate fails on invalid engine state only, which can't occur here
+            if self.engine.compress_vec(&data[consumed..], &mut self.output,
+                                        FlushCompress::Sync).is_err() { break }
+            consumed += (self.engine.total_in() - total_in) as usize;
+            if self.engine.total_in() == total_in && self.engine.total_out() == total_out { break }
+            // flush is complete, if the engine left spare capacity in the output buffer
+            if consumed == data.len() && self.output.len() < self.output.capacity() { break }
+        }
+        self.output.as_slice()
+    }
+}
+
+/// Decompressor for the server side of a TCP connection.
+pub(crate) struct StreamDecompressor {
+    // inflate engine, keeping the dictionary across messages
+    engine: Decompress,
+    // buffer for decompressed data not yet processed
+    output: Vec<u8>
+}
+impl StreamDecompressor {
+    /// Creates a stream decompressor.
+    pub(crate) fn new() -> StreamDecompressor {
+        StreamDecompressor {
+            engine: Decompress::new(false),
+            output: Vec::<u8>::with_capacity(STREAM_BUFFER_SIZE)
+        }
+    }
+
+    /// Decompresses data received from the client.
+    /// The decompressed data is appended to the internal buffer, complete messages can be
+    /// fetched with function next_message.
+    ///
+    /// # Arguments
+    /// * `data` - the compressed data received
+    ///
+    /// # Errors
+    /// Returns an error structure if the data is not a valid deflate stream
+    pub(crate) fn decompress(&mut self, data: &[u8]) -> Result<(), CoalyException> {
+        let mut consumed = 0;
+        loop {
+            if self.output.capacity() - self.output.len() < MIN_SPARE_CAPACITY {
+                self.output.reserve((data.len() - consumed) * 4 + MIN_SPARE_CAPACITY);
+            }
+            let total_in = self.engine.total_in();
+            let total_out = self.engine.total_out();
+            if let Err(e) = self.engine.decompress_vec(&data[consumed..], &mut self.output,
+                                                       FlushDecompress::None) {
+                return Err(coalyxe!(E_DECOMPRESSION_ERR, e.to_string()))
+            }
+            consumed += (self.engine.total_in() - total_in) as usize;
+            if consumed == data.len() && self.output.len() < self.output.capacity() {
+                return Ok(())
+            }
+            if self.engine.total_in() == total_in && self.engine.total_out() == total_out {
+                // no progress possible, remaining input is processed with the next call
+                return Ok(())
+            }
+        }
+    }
+
+    /// Returns the next complete message from the decompressed data, if any.
+    ///
+    /// # Return values
+    /// the message, **None** if the decompressed data doesn't hold a complete message
+    ///
+    /// # Errors
+    /// Returns an error structure if the message can't be deserialized
+    pub(crate) fn next_message(&mut self) -> Option<Result<Message, CoalyException>> {
+        if self.output.len() < MSG_HEADER_SIZE { return None }
+        let payload_size = u32::deserialize_from(&self.output[12..]).unwrap_or(0) as usize;
+        let msg_size = MSG_HEADER_SIZE + payload_size;
+        if self.output.len() < msg_size { return None }
+        let msg = Message::deserialize_from(&self.output[MSG_HEADER_SIZE..msg_size]);
+        self.output.drain(..msg_size);
+        Some(msg)
+    }
+}
+
+// size of message header, consisting of protocol information, sequence number and payload size
+const MSG_HEADER_SIZE: usize = 16;
+
+// initial size of compression and decompression buffers
+const STREAM_BUFFER_SIZE: usize = 1024;
+
+// minimum spare capacity of output buffers before calling the deflate engine
+const MIN_SPARE_CAPACITY: usize = 64;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::buffer::SendBuffer;
+    use crate::record::RecordLevelId;
+    use crate::record::recorddata::LocalRecordData;
+
+    #[test]
+    /// Tests compression and decompression of a message stream
+    fn test_stream_compression() {
+        let mut send_buf = SendBuffer::new(1, 256);
+        let mut compr = StreamCompressor::new();
+        let mut decompr = StreamDecompressor::new();
+        let mut compressed_sizes = Vec::<usize>::new();
+        for i in 0..10 {
+            let rec = LocalRecordData::for_write(1234, "mythread", RecordLevelId::Error,
+                                                 "test.rs", 393 + i, "Repetitive trace output");
+            send_buf.store_record_notification(&rec);
+            let data = compr.compress(send_buf.as_slice()).to_vec();
+            compressed_sizes.push(data.len());
+            // every message can be decompressed as soon as it has been received
+            assert!(decompr.decompress(&data).is_ok());
+            let msg = decompr.next_message().unwrap().unwrap();
+            assert!(matches!(msg, Message::RecordNotification(_)));
+            assert!(decompr.next_message().is_none());
+        }
+        // dictionary reuse makes subsequent records smaller than the first one
+        assert!(compressed_sizes[9] < compressed_sizes[0]);
+        assert!(compressed_sizes[9] < send_buf.as_slice().len() / 2);
+        send_buf.store_disconnect_notification();
+        let data = compr.compress(send_buf.as_slice()).to_vec();
+        // data may arrive in arbitrary fragments
+        assert!(decompr.decompress(&data[..1]).is_ok());
+        assert!(decompr.decompress(&data[1..]).is_ok());
+        assert_eq!(Message::DisconnectNotification, decompr.next_message().unwrap().unwrap());
+    }
+
+    #[test]
+    /// Tests decompression of invalid data
+    fn test_invalid_stream() {
+        let mut decompr = StreamDecompressor::new();
+        assert!(decompr.decompress(&[0xffu8; 16]).is_err());
+    }
+}
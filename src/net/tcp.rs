@@ -34,14 +34,75 @@ use crate::*;
 use crate::net::*;
 use crate::net::buffer::{ReceiveBuffer, SendBuffer};
 use super::clientconnection::ClientConnectionTable;
+use super::auth::{ClientAdmission, RecordAuthorization};
 use super::clientwhitelist::ClientWhitelist;
+use super::tls::TlsAcceptor;
+#[cfg(feature="compression")]
+use super::streamcompression::StreamDecompressor;
 
 use std::net::SocketAddr;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::sync::broadcast::*;
 
 
+/// Byte stream connected to a client, either a plain TCP stream, a Unix domain socket stream
+/// or a TLS session.
+pub(super) trait ClientStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> ClientStream for T {}
+
+/// Listen socket for connections from clients sending log or trace messages.
+pub(super) enum RecordListener {
+    Tcp(TcpListener),
+    // Unix domain socket listener, together with the number of the last client accepted
+    #[cfg(unix)]
+    Unix(UnixListener, u16)
+}
+impl RecordListener {
+    /// Accepts an incoming connection.
+    /// Clients connected over a Unix domain socket don't have a network address, they are
+    /// identified by a synthetic address on the IPv4 loopback interface with a unique port.
+    ///
+    /// # Return values
+    /// the stream connected to the client, the client's address and the local address
+    ///
+    /// # Errors
+    /// Returns an I/O error if accept on the listen socket failed
+    async fn accept(&mut self) -> std::io::Result<(Box<dyn ClientStream>, SocketAddr, String)> {
+        match self {
+            RecordListener::Tcp(l) => {
+                let (sock, addr) = l.accept().await?;
+                let local_addr = local_addr_of(&sock);
+                Ok((Box::new(sock), addr, local_addr))
+            },
+            #[cfg(unix)]
+            RecordListener::Unix(l, client_nr) => {
+                let (sock, _) = l.accept().await?;
+                *client_nr = u16::max(client_nr.wrapping_add(1), 1);
+                let addr = SocketAddr::from(([127, 0, 0, 1], *client_nr));
+                Ok((Box::new(sock), addr, self.local_addr()))
+            }
+        }
+    }
+
+    /// Returns the local address of the listen socket.
+    fn local_addr(&self) -> String {
+        match self {
+            RecordListener::Tcp(l) => local_listener_addr_of(l),
+            #[cfg(unix)]
+            RecordListener::Unix(l, _) => {
+                match l.local_addr().ok().and_then(|a| a.as_pathname().map(|p| p.to_path_buf())) {
+                    Some(p) => format!("unix://{}", p.to_string_lossy()),
+                    None => String::from("-unknown-")
+                }
+            }
+        }
+    }
+}
+
+
 /// TCP listener function to handle incoming connections for administrative messages
 /// The handler terminates upon the following events:
 /// - a shutdown message was received
@@ -82,33 +143,78 @@ pub(super) async fn tcp_admin_listener(socket: TcpListener,
     }
 }
 
-/// TCP listener function to handle incoming connections for log or trace messages
+/// Listener function to handle incoming TCP or Unix domain socket connections for log or
+/// trace messages.
+/// If a TLS acceptor is given, a TLS session is established on every incoming connection.
 /// The handler terminates upon the following events:
 /// - a socket I/O error ocurred
 /// - a shutdown was signaled from another part of the server
-pub(super) async fn tcp_record_listener(socket: TcpListener,
+pub(super) async fn stream_record_listener(mut socket: RecordListener,
+                                        tls_acceptor: Option<TlsAcceptor>,
                                         max_conns: usize,
                                         max_msg_size: usize,
-                                        client_whitelist: &ClientWhitelist,
+                                        admission: &ClientAdmission,
                                         shutdown_sender: Sender<bool>,
                                         mut shutdown_listener: Receiver<bool>) {
-    loginfo!("Started TCP record listener waiting for connections on address {}",
-             local_listener_addr_of(&socket));
+    loginfo!("Started record listener waiting for connections on address {}",
+             socket.local_addr());
     let mut conn_table = ClientConnectionTable::new(max_conns, u32::MAX);
-    let mut rx_buf = ReceiveBuffer::new(PROTOCOL_VERSION as u32, 1024);
+    let mut rx_buf = ReceiveBuffer::new(protocol_info(SCHEMA_VERSION), 1024);
     loop {
         tokio::select! {
             accept_res = socket.accept() => {
                 match accept_res {
-                    Ok((mut sock, addr)) => {
+                    Ok((sock, addr, local_addr)) => {
+                        let mut sock: Box<dyn ClientStream> = match tls_acceptor.as_ref() {
+                            #[cfg(feature="tls")]
+                            Some(acceptor) => {
+                                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT,
+                                                           acceptor.accept(sock)).await {
+                                    Ok(Ok(s)) => Box::new(s),
+                                    Ok(Err(e)) => {
+                                        loginfo!("TLS handshake with client {} failed: {}", addr, e);
+                                        continue
+                                    },
+                                    Err(_) => {
+                                        loginfo!("TLS handshake with client {} timed out", addr);
+                                        continue
+                                    }
+                                }
+                            },
+                            #[cfg(not(feature="tls"))]
+                            Some(acceptor) => match *acceptor {},
+                            None => sock
+                        };
                         tokio::select! {
                             maybe_msg = sock.read(rx_buf.as_mut_slice()) => {
                                 match maybe_msg {
                                     Ok(n) => {
-                                        match rx_buf.message(n) {
-                                            Ok(Message::ClientNotification(client)) => {
+                                        // with stream compression, compressed messages may
+                                        // follow the connect request immediately
+                                        let compressed = n >= 4 && rx_buf.protocol_info() &
+                                                         PROTOCOL_FLAG_STREAM_COMPRESSION != 0;
+                                        let msg_len = if compressed {
+                                            usize::min(n, rx_buf.message_size())
+                                        } else { n };
+                                        #[cfg(not(feature="compression"))]
+                                        if compressed {
+                                            loginfo!("Client {} requested unsupported stream compression", addr);
+                                            drop(sock);
+                                            continue;
+                                        }
+                                        match rx_buf.message(msg_len) {
+                                            Ok(Message::ClientNotification(mut client)) => {
+                                                let schema = match negotiate_schema_version(rx_buf.protocol_info()) {
+                                                    Some(v) => v,
+                                                    None => {
+                                                        loginfo!("Client {} uses unsupported record schema version {}",
+                                                                 addr, schema_version(rx_buf.protocol_info()));
+                                                        drop(sock);
+                                                        continue;
+                                                    }
+                                                };
                                                 let app_id = client.application_id_value();
-                                                if ! client_whitelist.allows_addr_and_appid(&addr, app_id) {
+                                                if ! admission.admits(&addr, &client) {
                                                     loginfo!("Access for client {} with app ID {} denied", addr, app_id);
                                                     drop(sock);
                                                     continue;
@@ -118,11 +224,25 @@ pub(super) async fn tcp_record_listener(socket: TcpListener,
                                                     drop(sock);
                                                     continue;
                                                 }
-                                                loginfo!("Client {} with app ID {} accepted", addr, app_id);
+                                                loginfo!("Client {} with app ID {} accepted, record schema version {}",
+                                                         addr, app_id, schema);
+                                                // token is not needed any more after authentication
+                                                client.set_auth_token("");
+                                                let mut handler = TcpRecordHandler::new(max_msg_size, schema);
+                                                if let Some(a) = admission.record_authorization(&client) {
+                                                    handler.enable_authorization(a);
+                                                }
                                                 agent::remote_client_connected(&addr, client);
-                                                let mut handler = TcpRecordHandler::new(max_msg_size);
+                                                if rx_buf.protocol_info() & PROTOCOL_FLAG_ACKNOWLEDGE != 0 {
+                                                    handler.enable_acknowledgements();
+                                                }
+                                                #[cfg(feature="compression")]
+                                                if compressed {
+                                                    handler.enable_decompression(&rx_buf.as_slice()[msg_len..n]);
+                                                }
                                                 tokio::spawn(async move {
-                                                    handler.run(sock, addr, shutdown_sender.subscribe()).await;
+                                                    handler.run(sock, addr, &local_addr,
+                                                                shutdown_sender.subscribe()).await;
                                                     conn_table.remove(&addr);
                                                 });
                                             },
@@ -176,8 +296,8 @@ impl TcpAdminHandler {
     pub(super) fn new(socket: TcpStream) -> TcpAdminHandler  {
         TcpAdminHandler {
             socket,
-            rx_buf: ReceiveBuffer::new(PROTOCOL_VERSION as u32, 128),
-            tx_buf: SendBuffer::new(PROTOCOL_VERSION as u32, 128)
+            rx_buf: ReceiveBuffer::new(protocol_info(SCHEMA_VERSION), 128),
+            tx_buf: SendBuffer::new(protocol_info(SCHEMA_VERSION), 128)
         }
     }
 
@@ -230,14 +350,86 @@ impl TcpAdminHandler {
 /// Handler for log and trace messages sent to Coaly logging server via TCP.
 pub(super) struct TcpRecordHandler {
     // receive buffer for incoming messages
-    rx_buf: ReceiveBuffer
+    rx_buf: ReceiveBuffer,
+    // send buffer for acknowledgements
+    tx_buf: SendBuffer,
+    // indicates whether the client requires acknowledgements for record notifications
+    acknowledge: bool,
+    // number of record notifications received
+    record_count: u64,
+    // application defined authorization of the records received
+    authorization: Option<RecordAuthorization>,
+    // decompressor for the message stream, if the client uses stream compression
+    #[cfg(feature="compression")]
+    decompressor: Option<StreamDecompressor>,
+    // compressed data received together with the connect request
+    #[cfg(feature="compression")]
+    initial_data: Vec<u8>
 }
 impl TcpRecordHandler {
-    /// Creates a TCP admin handler on the socket supplied.
-    pub(super) fn new(max_msg_size: usize) -> TcpRecordHandler  {
+    /// Creates a TCP record handler.
+    ///
+    /// # Arguments
+    /// * `max_msg_size` - the maximum size of a message received from the client
+    /// * `schema_version` - the record schema version negotiated with the client
+    pub(super) fn new(max_msg_size: usize,
+                      schema_version: u8) -> TcpRecordHandler  {
         TcpRecordHandler {
-            rx_buf: ReceiveBuffer::new(PROTOCOL_VERSION as u32, max_msg_size)
+            rx_buf: ReceiveBuffer::new(protocol_info(schema_version), max_msg_size),
+            tx_buf: SendBuffer::new(protocol_info(schema_version), 32),
+            acknowledge: false,
+            record_count: 0,
+            authorization: None,
+            #[cfg(feature="compression")]
+            decompressor: None,
+            #[cfg(feature="compression")]
+            initial_data: Vec::new()
+        }
+    }
+
+    /// Enables acknowledgements for record notifications, used if the client requested
+    /// acknowledgements in its connect request.
+    pub(super) fn enable_acknowledgements(&mut self) { self.acknowledge = true; }
+
+    /// Enables application defined authorization of the records received, used if an
+    /// authorizer is installed in the server.
+    ///
+    /// # Arguments
+    /// * `authorization` - the authorization for the client's records
+    pub(super) fn enable_authorization(&mut self, authorization: RecordAuthorization) {
+        self.authorization = Some(authorization);
+    }
+
+    /// Sends an acknowledgement with the total number of record notifications received to the
+    /// client, if the client requested acknowledgements.
+    ///
+    /// # Arguments
+    /// * `socket` - the socket connected to the client
+    /// * `acked_count` - the number of record notifications acknowledged so far
+    ///
+    /// # Return values
+    /// **false** if the acknowledgement could not be sent, the connection can't be used any more
+    async fn acknowledge(&mut self,
+                         socket: &mut Box<dyn ClientStream>,
+                         acked_count: u64) -> bool {
+        if ! self.acknowledge || self.record_count == acked_count { return true }
+        self.tx_buf.store_record_acknowledgement(self.record_count);
+        if let Err(e) = socket.write_all(self.tx_buf.as_slice()).await {
+            logerror!("Error sending acknowledgement: {}, terminating TCP record handler", e);
+            return false
         }
+        true
+    }
+
+    /// Enables decompression of the message stream, used if the client requested stream
+    /// compression in its connect request.
+    ///
+    /// # Arguments
+    /// * `initial_data` - compressed data received together with the connect request
+    #[cfg(feature="compression")]
+    pub(super) fn enable_decompression(&mut self, initial_data: &[u8]) {
+        self.decompressor = Some(StreamDecompressor::new());
+        self.initial_data = initial_data.to_vec();
     }
 
     /// Runs the TCP admin handler.
@@ -246,29 +438,44 @@ impl TcpRecordHandler {
     /// - a socket I/O error ocurred
     /// - a shutdown was signaled from another part of the server
     pub(super) async fn run(&mut self,
-                            mut socket: TcpStream,
+                            mut socket: Box<dyn ClientStream>,
                             client_addr: SocketAddr,
+                            local_addr: &str,
                             mut shutdown_listener: Receiver<bool>) {
-        loginfo!("Started TCP record handler waiting for messages on address {}",
-                 local_addr_of(&socket));
+        loginfo!("Started TCP record handler waiting for messages on address {}", local_addr);
+        #[cfg(feature="compression")]
+        if let Some(d) = self.decompressor.as_mut() {
+            if ! decompress_messages(d, &self.initial_data, &client_addr,
+                                     self.authorization.as_ref(), &mut self.record_count) {
+                return
+            }
+            self.initial_data.clear();
+            if ! self.acknowledge(&mut socket, 0).await { return }
+        }
         loop {
+            let acked_count = self.record_count;
             tokio::select! {
                 maybe_msg = socket.read(self.rx_buf.as_mut_slice()) => {
                     match maybe_msg {
+                        #[cfg(feature="compression")]
+                        Ok(n) if self.decompressor.is_some() => {
+                            if n == 0 {
+                                loginfo!("Client {} closed connection", client_addr);
+                                return
+                            }
+                            let d = self.decompressor.as_mut().unwrap();
+                            if ! decompress_messages(d, &self.rx_buf.as_slice()[..n],
+                                                     &client_addr, self.authorization.as_ref(),
+                                                     &mut self.record_count) {
+                                return
+                            }
+                        },
                         Ok(n) => {
                             match self.rx_buf.message(n) {
                                 Ok(msg) => {
-                                    match msg {
-                                        Message::RecordNotification(rec) => {
-                                            agent::write_rec(&client_addr, rec);
-                                        },
-                                        Message::DisconnectNotification => {
-                                            loginfo!("Client {} disconnected", client_addr);
-                                            agent::remote_client_disconnected(&client_addr);
-                                        },
-                                        _ =>  {
-                                            loginfo!("Ignored unexpected message {:?} from {}", msg, client_addr);
-                                        }
+                                    if dispatch_message(msg, &client_addr,
+                                                        self.authorization.as_ref()) {
+                                        self.record_count += 1;
                                     }
                                 },
                                 Err(e) => {
@@ -281,6 +488,7 @@ impl TcpRecordHandler {
                             return
                         }
                     }
+                    if ! self.acknowledge(&mut socket, acked_count).await { return }
                     continue;
                 }
                 _ = shutdown_listener.recv() => { return }
@@ -289,12 +497,80 @@ impl TcpRecordHandler {
     }
 }
 
+/// Passes a message received from a client to the agent.
+///
+/// # Arguments
+/// * `msg` - the message received
+/// * `client_addr` - the client's socket address
+/// * `authorization` - the optional authorization for the client's records
+///
+/// # Return values
+/// **true** if the message was a record notification
+fn dispatch_message(msg: Message,
+                    client_addr: &SocketAddr,
+                    authorization: Option<&RecordAuthorization>) -> bool {
+    match msg {
+        Message::RecordNotification(rec) => {
+            // records denied by the authorizer are dropped, but count as received
+            if authorization.is_none_or(|a| a.permits(&rec)) {
+                agent::write_rec(client_addr, rec);
+            }
+            return true
+        },
+        Message::DisconnectNotification => {
+            loginfo!("Client {} disconnected", client_addr);
+            agent::remote_client_disconnected(client_addr);
+        },
+        _ =>  {
+            loginfo!("Ignored unexpected message {:?} from {}", msg, client_addr);
+        }
+    }
+    false
+}
+
+/// Decompresses data received from a client and passes all complete messages to the agent.
+///
+/// # Arguments
+/// * `decompressor` - the decompressor for the client's message stream
+/// * `data` - the compressed data received
+/// * `client_addr` - the client's socket address
+/// * `authorization` - the optional authorization for the client's records
+/// * `record_count` - the counter for record notifications received
+///
+/// # Return values
+/// **false** if the data could not be decompressed, the connection can't be used any more
+#[cfg(feature="compression")]
+fn decompress_messages(decompressor: &mut StreamDecompressor,
+                       data: &[u8],
+                       client_addr: &SocketAddr,
+                       authorization: Option<&RecordAuthorization>,
+                       record_count: &mut u64) -> bool {
+    if let Err(e) = decompressor.decompress(data) {
+        logerror!("Error receiving data from client {}: {}, terminating TCP record handler",
+                  client_addr, e.localized_message());
+        return false
+    }
+    while let Some(res) = decompressor.next_message() {
+        match res {
+            Ok(msg) => if dispatch_message(msg, client_addr, authorization) {
+                *record_count += 1;
+            },
+            Err(e) => { logerror!("Error receiving message: {}", e.localized_message()); }
+        }
+    }
+    true
+}
+
 #[inline]
 fn local_addr_of(socket: &TcpStream) -> String {
     if let Ok(addr) = socket.local_addr() { return addr.to_string() }
     String::from("-unknown-")
 }
 
+// maximum time to wait for the TLS handshake with a client
+#[cfg(feature="tls")]
+const TLS_HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 #[inline]
 fn local_listener_addr_of(socket: &TcpListener) -> String {
     if let Ok(addr) = socket.local_addr() { return addr.to_string() }
@@ -0,0 +1,439 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! TLS support for network resources and the log/trace server.
+//! Network resources use a synchronous TLS session on top of a standard TCP stream, the server
+//! accepts TLS sessions asynchronously on top of tokio.
+
+#[cfg(feature="tls")]
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, ServerConfig,
+             SignatureScheme, StreamOwned};
+#[cfg(feature="tls")]
+use rustls::client::WebPkiServerVerifier;
+#[cfg(feature="tls")]
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+#[cfg(feature="tls")]
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider,
+                     WebPkiSupportedAlgorithms};
+#[cfg(feature="tls")]
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+#[cfg(feature="tls")]
+use rustls::server::WebPkiClientVerifier;
+#[cfg(feature="tls")]
+use std::fs::File;
+#[cfg(feature="tls")]
+use std::io::BufReader;
+#[cfg(feature="tls")]
+use std::net::TcpStream;
+#[cfg(feature="tls")]
+use std::sync::Arc;
+#[cfg(feature="tls")]
+use std::time::Duration;
+#[cfg(feature="tls")]
+use crate::coalyxe;
+#[cfg(feature="tls")]
+use crate::config::resource::{TlsSettings, TlsVerification};
+#[cfg(feature="tls")]
+use crate::errorhandling::*;
+#[cfg(feature="tls")]
+use super::PeerAddr;
+
+#[cfg(feature="tls")]
+pub(crate) use tokio_rustls::TlsAcceptor;
+
+/// Placeholder for the server's TLS acceptor, TLS support requires feature tls.
+#[cfg(not(feature="tls"))]
+#[derive(Clone)]
+pub(crate) enum TlsAcceptor {}
+
+/// TLS session of a network resource on top of a TCP stream.
+#[cfg(feature="tls")]
+pub(crate) type ClientTlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+/// Client side of TLS connections to a log server.
+#[cfg(feature="tls")]
+#[derive(Clone)]
+pub(crate) struct TlsConnector {
+    // client configuration with trusted CAs and optional client certificate
+    config: Arc<ClientConfig>,
+    // name to verify the server certificate against
+    server_name: ServerName<'static>
+}
+#[cfg(feature="tls")]
+impl TlsConnector {
+    /// Creates a TLS connector for a network resource.
+    ///
+    /// # Arguments
+    /// * `settings` - the TLS settings of the network resource
+    /// * `remote_addr` - the address of the log server
+    ///
+    /// # Errors
+    /// Returns an error structure if a certificate or key file can't be read or the settings
+    /// are inconsistent
+    pub(crate) fn new(settings: &TlsSettings,
+                      remote_addr: &PeerAddr) -> Result<TlsConnector, CoalyException> {
+        let name = match settings.server_name().as_ref() {
+            Some(n) => n.to_string(),
+            None => remote_addr.host().unwrap_or_default()
+        };
+        let server_name = ServerName::try_from(name.clone())
+                          .map_err(|e| coalyxe!(E_TLS_CONFIG_ERR, format!("{}: {}", name, e)))?;
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+                      .with_safe_default_protocol_versions()
+                      .map_err(|e| coalyxe!(E_TLS_CONFIG_ERR, e.to_string()))?;
+        let builder = match settings.verification() {
+            TlsVerification::Full => {
+                builder.with_root_certificates(root_store(settings.ca_file().as_ref())?)
+            },
+            TlsVerification::Chain => {
+                let roots = Arc::new(root_store(settings.ca_file().as_ref())?);
+                let chain_verifier = WebPkiServerVerifier::builder_with_provider(roots,
+                                                                                 provider.clone())
+                                     .build()
+                                     .map_err(|e| coalyxe!(E_TLS_CONFIG_ERR, e.to_string()))?;
+                let verifier = RelaxedServerVerifier::new(Some(chain_verifier), &provider);
+                builder.dangerous().with_custom_certificate_verifier(Arc::new(verifier))
+            },
+            TlsVerification::None => {
+                let verifier = RelaxedServerVerifier::new(None, &provider);
+                builder.dangerous().with_custom_certificate_verifier(Arc::new(verifier))
+            }
+        };
+        let config = match (settings.cert_file(), settings.key_file()) {
+            (Some(cert_file), Some(key_file)) => {
+                builder.with_client_auth_cert(load_certs(cert_file)?, load_private_key(key_file)?)
+                       .map_err(|e| coalyxe!(E_TLS_CONFIG_ERR, e.to_string()))?
+            },
+            (None, None) => builder.with_no_client_auth(),
+            _ => return Err(coalyxe!(E_TLS_CONFIG_ERR,
+                                     String::from("client certificate requires a private key")))
+        };
+        Ok(TlsConnector { config: Arc::new(config), server_name })
+    }
+
+    /// Performs the TLS handshake on a TCP stream connected to the log server.
+    ///
+    /// # Arguments
+    /// * `socket` - the TCP stream connected to the log server
+    ///
+    /// # Return values
+    /// the TLS session
+    ///
+    /// # Errors
+    /// Returns an I/O error if the handshake fails, e.g. because the server certificate
+    /// was rejected
+    pub(crate) fn connect(&self, socket: TcpStream) -> std::io::Result<ClientTlsStream> {
+        let conn = ClientConnection::new(self.config.clone(), self.server_name.clone())
+                   .map_err(std::io::Error::other)?;
+        let mut stream = StreamOwned::new(conn, socket);
+        stream.sock.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        while stream.conn.is_handshaking() {
+            stream.conn.complete_io(&mut stream.sock)?;
+        }
+        stream.sock.set_read_timeout(None)?;
+        Ok(stream)
+    }
+}
+
+/// Creates the acceptor for TLS connections to the log server.
+///
+/// # Arguments
+/// * `cert_file` - the name of the PEM file with the server certificate chain
+/// * `key_file` - the name of the PEM file with the server's private key
+/// * `client_ca_file` - the name of the PEM file with the CA certificates to verify client
+///   certificates against, empty string if clients need not authenticate
+///
+/// # Errors
+/// Returns an error structure if a certificate or key file can't be read
+#[cfg(feature="tls")]
+pub(crate) fn server_acceptor(cert_file: &str,
+                              key_file: &str,
+                              client_ca_file: &str) -> Result<TlsAcceptor, CoalyException> {
+    Ok(TlsAcceptor::from(server_config(cert_file, key_file, client_ca_file)?))
+}
+
+/// Creates the server side TLS configuration.
+///
+/// # Arguments
+/// * `cert_file` - the name of the PEM file with the server certificate chain
+/// * `key_file` - the name of the PEM file with the server's private key
+/// * `client_ca_file` - the name of the PEM file with the CA certificates to verify client
+///   certificates against, empty string if clients need not authenticate
+///
+/// # Errors
+/// Returns an error structure if a certificate or key file can't be read
+#[cfg(feature="tls")]
+fn server_config(cert_file: &str,
+                 key_file: &str,
+                 client_ca_file: &str) -> Result<Arc<ServerConfig>, CoalyException> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+                  .with_safe_default_protocol_versions()
+                  .map_err(|e| coalyxe!(E_TLS_CONFIG_ERR, e.to_string()))?;
+    let builder = if client_ca_file.is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        let roots = Arc::new(root_store(Some(&client_ca_file.to_string()))?);
+        let verifier = WebPkiClientVerifier::builder_with_provider(roots, provider)
+                       .build()
+                       .map_err(|e| coalyxe!(E_TLS_CONFIG_ERR, e.to_string()))?;
+        builder.with_client_cert_verifier(verifier)
+    };
+    let config = builder.with_single_cert(load_certs(cert_file)?, load_private_key(key_file)?)
+                        .map_err(|e| coalyxe!(E_TLS_CONFIG_ERR, e.to_string()))?;
+    Ok(Arc::new(config))
+}
+
+/// Returns the store with the trusted CA certificates.
+///
+/// # Arguments
+/// * `ca_file` - the name of the PEM file with the CA certificates, **None** to use the
+///   certificates of the operating system
+///
+/// # Errors
+/// Returns an error structure if the file can't be read or contains no certificate
+#[cfg(feature="tls")]
+fn root_store(ca_file: Option<&String>) -> Result<RootCertStore, CoalyException> {
+    let mut roots = RootCertStore::empty();
+    match ca_file {
+        Some(file_name) => {
+            for cert in load_certs(file_name)? {
+                roots.add(cert).map_err(|e| coalyxe!(E_TLS_FILE_ERR, file_name.to_string(),
+                                                     e.to_string()))?;
+            }
+        },
+        None => {
+            let native = rustls_native_certs::load_native_certs();
+            roots.add_parsable_certificates(native.certs);
+        }
+    }
+    Ok(roots)
+}
+
+/// Reads all certificates from a PEM file.
+///
+/// # Arguments
+/// * `file_name` - the name of the PEM file
+///
+/// # Errors
+/// Returns an error structure if the file can't be read or contains no certificate
+#[cfg(feature="tls")]
+fn load_certs(file_name: &str) -> Result<Vec<CertificateDer<'static>>, CoalyException> {
+    let f = File::open(file_name).map_err(|e| coalyxe!(E_TLS_FILE_ERR, file_name.to_string(),
+                                                       e.to_string()))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(f))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| coalyxe!(E_TLS_FILE_ERR, file_name.to_string(), e.to_string()))?;
+    if certs.is_empty() {
+        return Err(coalyxe!(E_TLS_FILE_ERR, file_name.to_string(),
+                            String::from("no certificate found")))
+    }
+    Ok(certs)
+}
+
+/// Reads the first private key from a PEM file.
+///
+/// # Arguments
+/// * `file_name` - the name of the PEM file
+///
+/// # Errors
+/// Returns an error structure if the file can't be read or contains no private key
+#[cfg(feature="tls")]
+fn load_private_key(file_name: &str) -> Result<PrivateKeyDer<'static>, CoalyException> {
+    let f = File::open(file_name).map_err(|e| coalyxe!(E_TLS_FILE_ERR, file_name.to_string(),
+                                                       e.to_string()))?;
+    match rustls_pemfile::private_key(&mut BufReader::new(f)) {
+        Ok(Some(key)) => Ok(key),
+        Ok(None) => Err(coalyxe!(E_TLS_FILE_ERR, file_name.to_string(),
+                                 String::from("no private key found"))),
+        Err(e) => Err(coalyxe!(E_TLS_FILE_ERR, file_name.to_string(), e.to_string()))
+    }
+}
+
+/// Verifier for server certificates with verification chain or none.
+/// Verifies the certificate chain, if a chain verifier is given, but not the host name.
+/// Without chain verifier, any certificate is accepted. The signatures of the handshake
+/// messages are always verified.
+#[cfg(feature="tls")]
+#[derive(Debug)]
+struct RelaxedServerVerifier {
+    // verifier for the certificate chain, None to accept any certificate
+    chain_verifier: Option<Arc<WebPkiServerVerifier>>,
+    // signature algorithms supported by the crypto provider
+    algorithms: WebPkiSupportedAlgorithms
+}
+#[cfg(feature="tls")]
+impl RelaxedServerVerifier {
+    fn new(chain_verifier: Option<Arc<WebPkiServerVerifier>>,
+           provider: &CryptoProvider) -> RelaxedServerVerifier {
+        RelaxedServerVerifier {
+            chain_verifier,
+            algorithms: provider.signature_verification_algorithms
+        }
+    }
+}
+#[cfg(feature="tls")]
+impl ServerCertVerifier for RelaxedServerVerifier {
+    fn verify_server_cert(&self,
+                          end_entity: &CertificateDer<'_>,
+                          intermediates: &[CertificateDer<'_>],
+                          server_name: &ServerName<'_>,
+                          ocsp_response: &[u8],
+                          now: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        let v = match self.chain_verifier.as_ref() {
+            Some(v) => v,
+            None => return Ok(ServerCertVerified::assertion())
+        };
+        match v.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+            Err(rustls::Error::InvalidCertificate(rustls::CertificateError::NotValidForName)) |
+            Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::NotValidForNameContext { .. })) => {
+                Ok(ServerCertVerified::assertion())
+            },
+            res => res
+        }
+    }
+
+    fn verify_tls12_signature(&self,
+                              message: &[u8],
+                              cert: &CertificateDer<'_>,
+                              dss: &DigitallySignedStruct)
+                              -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(&self,
+                              message: &[u8],
+                              cert: &CertificateDer<'_>,
+                              dss: &DigitallySignedStruct)
+                              -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+// maximum time to wait for the server's handshake messages
+#[cfg(feature="tls")]
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(all(test, feature="tls"))]
+mod tests {
+    use super::*;
+    use rustls::{ServerConnection, StreamOwned};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use crate::net::parse_url;
+
+    // returns the full name of a file in the TLS test data directory
+    fn tls_file(name: &str) -> String {
+        let proj_root = std::env::var("COALY_PROJ_ROOT").unwrap();
+        format!("{}/testdata/ut/tls/{}", proj_root, name)
+    }
+
+    // runs a TLS server accepting a single connection, returns the first 5 bytes received
+    fn serve_once(listener: TcpListener,
+                  client_ca_file: &str) -> thread::JoinHandle<std::io::Result<Vec<u8>>> {
+        let config = server_config(&tls_file("server.pem"), &tls_file("server.key"),
+                                   client_ca_file).unwrap();
+        thread::spawn(move || {
+            let (sock, _) = listener.accept()?;
+            let conn = ServerConnection::new(config).map_err(std::io::Error::other)?;
+            let mut stream = StreamOwned::new(conn, sock);
+            let mut buf = vec!(0u8; 5);
+            stream.read_exact(&mut buf)?;
+            Ok(buf)
+        })
+    }
+
+    // connects to a TLS server on local host with the given settings and sends a message
+    fn send_hello(settings: &TlsSettings, client_ca_file: &str) -> bool {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = serve_once(listener, client_ca_file);
+        let remote_addr = parse_url(&format!("tls://localhost:{}", port)).unwrap();
+        let connector = TlsConnector::new(settings, &remote_addr).unwrap();
+        let client_ok = match connector.connect(TcpStream::connect(("127.0.0.1", port)).unwrap()) {
+            Ok(mut s) => s.write_all(b"hello").and_then(|_| s.flush()).is_ok(),
+            Err(_) => false
+        };
+        let server_res = server.join().unwrap();
+        client_ok && matches!(server_res, Ok(ref b) if b == b"hello")
+    }
+
+    #[test]
+    fn test_full_verification() {
+        let mut settings = TlsSettings::default();
+        settings.set_ca_file(&tls_file("ca.pem"));
+        assert!(send_hello(&settings, ""));
+        settings.set_server_name("coaly.invalid");
+        assert!(! send_hello(&settings, ""));
+    }
+
+    #[test]
+    fn test_relaxed_verification() {
+        let mut settings = TlsSettings::default();
+        settings.set_ca_file(&tls_file("ca.pem"));
+        settings.set_server_name("coaly.invalid");
+        settings.set_verification(TlsVerification::Chain);
+        assert!(send_hello(&settings, ""));
+        let mut settings = TlsSettings::default();
+        settings.set_verification(TlsVerification::None);
+        assert!(send_hello(&settings, ""));
+    }
+
+    #[test]
+    fn test_client_authentication() {
+        let mut settings = TlsSettings::default();
+        settings.set_ca_file(&tls_file("ca.pem"));
+        assert!(! send_hello(&settings, &tls_file("ca.pem")));
+        settings.set_cert_file(&tls_file("client.pem"));
+        settings.set_key_file(&tls_file("client.key"));
+        assert!(send_hello(&settings, &tls_file("ca.pem")));
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let remote_addr = parse_url("tls://localhost:3691").unwrap();
+        let mut settings = TlsSettings::default();
+        settings.set_cert_file(&tls_file("client.pem"));
+        assert!(TlsConnector::new(&settings, &remote_addr).is_err());
+        let mut settings = TlsSettings::default();
+        settings.set_ca_file(&tls_file("missing.pem"));
+        assert!(TlsConnector::new(&settings, &remote_addr).is_err());
+        assert!(server_acceptor(&tls_file("server.key"), &tls_file("server.key"), "").is_err());
+    }
+}
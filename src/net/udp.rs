@@ -33,7 +33,9 @@
 use crate::*;
 use crate::net::*;
 use crate::net::buffer::{ReceiveBuffer, SendBuffer};
+use crate::net::fragment::MessageAssembler;
 use super::clientconnection::ClientConnectionTable;
+use super::auth::ClientAdmission;
 use super::clientwhitelist::ClientWhitelist;
 
 use tokio::net::UdpSocket;
@@ -53,8 +55,8 @@ impl UdpAdminHandler {
     pub(super) fn new(socket: UdpSocket) -> UdpAdminHandler  {
         UdpAdminHandler {
             socket,
-            rx_buf: ReceiveBuffer::new(PROTOCOL_VERSION as u32, 128),
-            tx_buf: SendBuffer::new(PROTOCOL_VERSION as u32, 128)
+            rx_buf: ReceiveBuffer::new(protocol_info(SCHEMA_VERSION), 128),
+            tx_buf: SendBuffer::new(protocol_info(SCHEMA_VERSION), 128)
         }
     }
 
@@ -113,9 +115,10 @@ pub(super) struct UdpRecordHandler {
     socket: UdpSocket,
     // receive buffer for incoming messages
     rx_buf: ReceiveBuffer,
-    // list with IP addresses, optional port number and application ID of clients allowed
-    // to send log and trace messages
-    client_whitelist: ClientWhitelist,
+    // reassembles messages sent in several datagrams
+    assembler: MessageAssembler,
+    // admission control for clients allowed to send log and trace messages
+    admission: ClientAdmission,
     // sender part of broadcast channel for graceful shutdown
     // used to indicate a shutdown when an unrecoverable I/O Error on the socket occurs
     shutdown_sender: Sender<bool>,
@@ -125,14 +128,15 @@ pub(super) struct UdpRecordHandler {
 impl UdpRecordHandler {
     /// Creates a UDP record handler on the socket supplied.
     pub(super) fn new(socket: UdpSocket,
-                      client_whitelist: ClientWhitelist,
+                      admission: ClientAdmission,
                       shutdown_sender: Sender<bool>,
                       shutdown_listener: Receiver<bool>,
                       max_msg_size: usize) -> UdpRecordHandler  {
         UdpRecordHandler {
             socket,
-            rx_buf: ReceiveBuffer::new(PROTOCOL_VERSION as u32, max_msg_size),
-            client_whitelist,
+            rx_buf: ReceiveBuffer::new(protocol_info(SCHEMA_VERSION), max_msg_size),
+            assembler: MessageAssembler::new(max_msg_size),
+            admission,
             shutdown_sender,
             shutdown_listener
         }
@@ -152,32 +156,58 @@ impl UdpRecordHandler {
             tokio::select! {
                 maybe_msg = self.socket.recv_from(self.rx_buf.as_mut_slice()) => {
                     match maybe_msg {
-                        Ok((n, addr)) => {
+                        Ok((mut n, addr)) => {
+                            if self.rx_buf.protocol_info() & PROTOCOL_FLAG_FRAGMENT != 0 {
+                                // fragments are accepted from connected clients only
+                                if conn_table.get_mut(&addr).is_none() { continue }
+                                let msg = match self.assembler.add(&addr, &self.rx_buf.as_slice()[..n]) {
+                                    Some(msg) => msg,
+                                    None => continue
+                                };
+                                n = match self.rx_buf.fill(&msg) {
+                                    Some(n) => n,
+                                    None => continue
+                                };
+                            }
                             match self.rx_buf.message(n) {
                                 Ok(msg) => {
                                     match msg {
-                                        Message::ClientNotification(client) => {
+                                        Message::ClientNotification(mut client) => {
+                                            let schema = match negotiate_schema_version(self.rx_buf.protocol_info()) {
+                                                Some(v) => v,
+                                                None => {
+                                                    loginfo!("Client {} uses unsupported record schema version {}",
+                                                             addr, schema_version(self.rx_buf.protocol_info()));
+                                                    continue;
+                                                }
+                                            };
                                             let app_id = client.application_id_value();
-                                            if ! self.client_whitelist.allows_addr_and_appid(&addr, app_id) {
+                                            if ! self.admission.admits(&addr, &client) {
                                                 loginfo!("Access for client {} with app ID {} denied", addr, app_id);
                                                 continue;
                                             }
+                                            // token is not needed any more after authentication
+                                            client.set_auth_token("");
                                             if ! conn_table.add(&addr, &client, true) {
                                                 loginfo!("Connection limit exceeded, could not accept client {}", addr);
                                                 continue;
                                             }
-                                            loginfo!("Client {} with app ID {} accepted", addr, app_id);
+                                            loginfo!("Client {} with app ID {} accepted, record schema version {}",
+                                                     addr, app_id, schema);
                                             agent::remote_client_connected(&addr, client);
                                         },
                                         Message::RecordNotification(rec) => {
                                             if let Some(conn) = conn_table.get_mut(&addr) {
                                                 conn.record_received(self.rx_buf.sequence_nr());
-                                                agent::write_rec(&addr, rec);
+                                                if self.admission.permits_record(conn.client_info(), &rec) {
+                                                    agent::write_rec(&addr, rec);
+                                                }
                                             }
                                         },
                                         Message::DisconnectNotification => {
                                             loginfo!("Client {} disconnected", addr);
                                             conn_table.remove(&addr);
+                                            self.assembler.remove(&addr);
                                             agent::remote_client_disconnected(&addr);
                                         },
                                         _ =>  {
@@ -32,9 +32,13 @@
 
 //! Coaly observer types
 
-use std::fmt::{Debug, Formatter};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use crate::util::json_escaped_str;
 
 /// Kinds of observer structs that may control the output settings for log and trace records
 #[derive (Clone, Copy, PartialEq)]
@@ -105,7 +109,7 @@ impl ObserverData {
     /// * `name` - the name of the module
     /// * `file_name` - the name of the source code file where the structure was created
     /// * `line_nr` - the line number in the source code file where the structure was created
-    pub(crate) fn for_mod(name: &'static str,
+    pub(crate) fn for_mod(name: &str,
                           file_name: &'static str) -> ObserverData {
         ObserverData {
             id: CURR_OBSERVER_ID.fetch_add(1, Ordering::SeqCst),
@@ -152,9 +156,181 @@ impl ObserverData {
     pub(crate) fn value(&self) -> &Option<String> { &self.value }
 }
 
+/// Typed value of an observer attribute
+#[derive(Clone, Debug)]
+pub enum AttrValue {
+    /// boolean value
+    Bool(bool),
+    /// signed integer value
+    Int(i64),
+    /// unsigned integer value
+    UInt(u64),
+    /// floating point value
+    Float(f64),
+    /// text value
+    Str(String)
+}
+impl AttrValue {
+    /// Returns the attribute value as JSON value.
+    /// Text values are quoted, non-finite floating point values are mapped to null.
+    pub(crate) fn to_json(&self) -> String {
+        match self {
+            AttrValue::Float(v) if ! v.is_finite() => String::from("null"),
+            AttrValue::Str(v) => format!("\"{}\"", json_escaped_str(v)),
+            _ => self.to_string()
+        }
+    }
+}
+impl PartialEq for AttrValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AttrValue::Bool(a), AttrValue::Bool(b)) => a == b,
+            (AttrValue::Int(a), AttrValue::Int(b)) => a == b,
+            (AttrValue::UInt(a), AttrValue::UInt(b)) => a == b,
+            // bitwise comparison, so that equality is reflexive for NaN
+            (AttrValue::Float(a), AttrValue::Float(b)) => a.to_bits() == b.to_bits(),
+            (AttrValue::Str(a), AttrValue::Str(b)) => a == b,
+            _ => false
+        }
+    }
+}
+impl Eq for AttrValue {}
+impl Display for AttrValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttrValue::Bool(v) => write!(f, "{}", v),
+            AttrValue::Int(v) => write!(f, "{}", v),
+            AttrValue::UInt(v) => write!(f, "{}", v),
+            AttrValue::Float(v) => write!(f, "{}", v),
+            AttrValue::Str(v) => write!(f, "{}", v)
+        }
+    }
+}
+impl From<bool> for AttrValue {
+    fn from(v: bool) -> Self { AttrValue::Bool(v) }
+}
+impl From<i32> for AttrValue {
+    fn from(v: i32) -> Self { AttrValue::Int(v as i64) }
+}
+impl From<i64> for AttrValue {
+    fn from(v: i64) -> Self { AttrValue::Int(v) }
+}
+impl From<u32> for AttrValue {
+    fn from(v: u32) -> Self { AttrValue::UInt(v as u64) }
+}
+impl From<u64> for AttrValue {
+    fn from(v: u64) -> Self { AttrValue::UInt(v) }
+}
+impl From<usize> for AttrValue {
+    fn from(v: usize) -> Self { AttrValue::UInt(v as u64) }
+}
+impl From<f64> for AttrValue {
+    fn from(v: f64) -> Self { AttrValue::Float(v) }
+}
+impl From<&str> for AttrValue {
+    fn from(v: &str) -> Self { AttrValue::Str(v.to_string()) }
+}
+impl From<String> for AttrValue {
+    fn from(v: String) -> Self { AttrValue::Str(v) }
+}
+
+/// Observer attributes inherited by a record, sorted by key
+pub type AttrMap = BTreeMap<String, AttrValue>;
+
+/// Attributes of the observers of a thread
+#[derive(Default)]
+struct ThreadAttrs {
+    // attributes of every observer with at least one attribute, in order of observer creation
+    scopes: Vec<(u64, AttrMap)>,
+    // attributes of all observers merged, inner observers taking precedence; None if empty
+    merged: Option<Arc<AttrMap>>
+}
+impl ThreadAttrs {
+    /// Recalculates the merged attributes of all observers.
+    fn merge(&mut self) {
+        let mut merged = AttrMap::new();
+        for (_, attrs) in &self.scopes {
+            merged.extend(attrs.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        self.merged = if merged.is_empty() { None } else { Some(Arc::new(merged)) };
+    }
+}
+
+/// Sets an attribute of an observer for the calling thread.
+/// An existing attribute with the same key is replaced.
+///
+/// # Arguments
+/// * `observer_id` - the observer's ID
+/// * `key` - the attribute key
+/// * `value` - the attribute value
+pub(crate) fn set_attr(observer_id: u64, key: &str, value: AttrValue) {
+    THREAD_ATTRS.with(|a| {
+        let mut attrs = a.borrow_mut();
+        // observer IDs are ascending in order of creation
+        let pos = attrs.scopes.partition_point(|(id, _)| *id < observer_id);
+        if attrs.scopes.get(pos).is_none_or(|(id, _)| *id != observer_id) {
+            attrs.scopes.insert(pos, (observer_id, AttrMap::new()));
+        }
+        attrs.scopes[pos].1.insert(key.to_string(), value);
+        attrs.merge();
+    });
+}
+
+/// Removes all attributes of an observer for the calling thread.
+///
+/// # Arguments
+/// * `observer_id` - the observer's ID
+pub(crate) fn remove_attrs(observer_id: u64) {
+    THREAD_ATTRS.with(|a| {
+        let mut attrs = a.borrow_mut();
+        if let Some(pos) = attrs.scopes.iter().position(|(id, _)| *id == observer_id) {
+            attrs.scopes.remove(pos);
+            attrs.merge();
+        }
+    });
+}
+
+/// Returns the merged attributes of all observers of the calling thread, **None** if there
+/// are no attributes.
+/// The map is shared with the thread until an attribute is changed.
+#[inline]
+pub(crate) fn current_attrs() -> Option<Arc<AttrMap>> {
+    THREAD_ATTRS.with(|a| a.borrow().merged.clone())
+}
+
+thread_local! {
+    /// The observer attributes of the calling thread
+    static THREAD_ATTRS: RefCell<ThreadAttrs> = RefCell::new(ThreadAttrs::default());
+}
+
 static CURR_OBSERVER_ID: AtomicU64 = AtomicU64::new(1);
 
 // Observer kind names
 const OBSERVER_KIND_FUNCTION: &str = "function";
 const OBSERVER_KIND_MODULE: &str = "module";
 const OBSERVER_KIND_OBJECT: &str = "object";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Tests inheritance of observer attributes
+    fn test_attrs() {
+        assert!(current_attrs().is_none());
+        set_attr(20, "user", AttrValue::from("jdoe"));
+        set_attr(10, "request_id", AttrValue::from(4711u64));
+        set_attr(10, "user", AttrValue::from("root"));
+        let attrs = current_attrs().unwrap();
+        assert_eq!(Some(&AttrValue::UInt(4711)), attrs.get("request_id"));
+        assert_eq!(Some(&AttrValue::from("jdoe")), attrs.get("user"));
+        remove_attrs(20);
+        assert_eq!(Some(&AttrValue::from("root")), current_attrs().unwrap().get("user"));
+        remove_attrs(10);
+        assert!(current_attrs().is_none());
+        assert_eq!("\"a\\\"b\"", AttrValue::from("a\"b").to_json());
+        assert_eq!("-3", AttrValue::from(-3).to_json());
+        assert_eq!("null", AttrValue::from(f64::NAN).to_json());
+        assert_eq!("true", AttrValue::from(true).to_string());
+    }
+}
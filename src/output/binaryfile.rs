@@ -0,0 +1,253 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+//! Reader for plain output files written in binary format.
+//! Binary files start with a magic value followed by the information about the originator
+//! process. Each record is stored as a frame consisting of the record length and the serialized
+//! record data, the same representation used to transfer records to a log server.
+//! Records are converted to text using an output format from a configuration file.
+
+use std::collections::HashMap;
+use std::path::Path;
+use crate::coalyxe;
+use crate::config;
+use crate::errorhandling::*;
+use crate::net::serializable::Serializable;
+use crate::record::originator::OriginatorInfo;
+use crate::record::recorddata::{RecordData, RemoteRecordData};
+use super::outputformat::OutputFormat;
+use super::resource::output_format;
+
+/// Records read from a binary output file.
+#[derive(Debug)]
+pub struct BinaryFileRecords {
+    // all intact records converted to text, oldest first
+    records: Vec<String>,
+    // number of corrupted frames skipped
+    torn_count: usize
+}
+impl BinaryFileRecords {
+    /// Returns all intact records converted to text, oldest first.
+    #[inline]
+    pub fn records(&self) -> &Vec<String> { &self.records }
+
+    /// Returns the number of corrupted frames, that have been skipped.
+    /// A frame truncated at the end of the file, e.g. because the application crashed, counts
+    /// as corrupted.
+    #[inline]
+    pub fn torn_count(&self) -> usize { self.torn_count }
+}
+
+/// Reads all intact records from a binary output file and converts them to text.
+/// Process specific items in the output format are replaced with the values of the process,
+/// that has written the file.
+/// 
+/// # Arguments
+/// * `file_path` - the full path of the binary file
+/// * `config_file_name` - the optional name of the configuration file defining the output format
+/// * `format_name` - the name of the output format, **None** for the default format
+/// 
+/// # Return values
+/// the records read
+/// 
+/// # Errors
+/// Returns an error structure if the file could not be read or is not a binary record file
+pub fn read_binary_file(file_path: &Path,
+                        config_file_name: Option<&str>,
+                        format_name: Option<&str>) -> Result<BinaryFileRecords, CoalyException> {
+    let path_name = file_path.to_string_lossy().to_string();
+    let data = std::fs::read(file_path).map_err(|e| coalyxe!(E_FILE_READ_ERR,
+                                                             path_name.to_string(),
+                                                             e.to_string()))?;
+    read_binary_data(&data, &path_name, config_file_name, format_name)
+}
+
+/// Reads all intact records from the content of a binary output file and converts them to
+/// text, e.g. after the content has been decrypted.
+/// Process specific items in the output format are replaced with the values of the process,
+/// that has written the file.
+/// 
+/// # Arguments
+/// * `data` - the file content
+/// * `path_name` - the full path of the binary file, used in error messages
+/// * `config_file_name` - the optional name of the configuration file defining the output format
+/// * `format_name` - the name of the output format, **None** for the default format
+/// 
+/// # Return values
+/// the records read
+/// 
+/// # Errors
+/// Returns an error structure if the data is not the content of a binary record file
+pub fn read_binary_data(data: &[u8],
+                        path_name: &str,
+                        config_file_name: Option<&str>,
+                        format_name: Option<&str>) -> Result<BinaryFileRecords, CoalyException> {
+    let path_name = path_name.to_string();
+    if ! data.starts_with(BINARY_FILE_MAGIC) {
+        return Err(coalyxe!(E_FILE_INV_BINARY_FORMAT, path_name))
+    }
+    let mut frames = Frames { data: &data[BINARY_FILE_MAGIC.len()..], torn: false };
+    let orig_info = match frames.next().map(OriginatorInfo::deserialize_from) {
+        Some(Ok(oi)) => oi,
+        _ => return Err(coalyxe!(E_FILE_INV_BINARY_FORMAT, path_name))
+    };
+    let cfg = config::configuration(&orig_info, config_file_name);
+    let ofmt = output_format(&cfg, &format_name.map(|n| n.to_string()));
+    let mut thread_formats = HashMap::<u64, OutputFormat>::new();
+    let mut records = Vec::<String>::new();
+    let mut torn_count = 0;
+    for frame in frames.by_ref() {
+        match RemoteRecordData::deserialize_from(frame) {
+            Ok(rec) => {
+                let tfmt = thread_formats.entry(rec.thread_id()).or_insert_with(|| {
+                    ofmt.optimized_for(&orig_info, rec.thread_id(), rec.thread_name())
+                });
+                records.push(tfmt.apply_to(&rec));
+            },
+            Err(_) => torn_count += 1
+        }
+    }
+    if frames.torn { torn_count += 1; }
+    Ok(BinaryFileRecords { records, torn_count })
+}
+
+/// Indicates whether the given data is the content of a binary output file.
+/// 
+/// # Arguments
+/// * `data` - the file content
+pub fn is_binary_data(data: &[u8]) -> bool { data.starts_with(BINARY_FILE_MAGIC) }
+
+/// Returns the header for a binary output file.
+/// Authentication tokens are never written to the file.
+/// 
+/// # Arguments
+/// * `orig_info` - the information about the process writing the file
+pub(crate) fn file_header(orig_info: &OriginatorInfo) -> Vec<u8> {
+    let mut oi = orig_info.clone();
+    oi.set_auth_token("");
+    let mut header = Vec::<u8>::with_capacity(BINARY_FILE_MAGIC.len() + frame_size(&oi));
+    header.extend_from_slice(BINARY_FILE_MAGIC);
+    append_frame(&oi, &mut header);
+    header
+}
+
+/// Returns the size of the frame for the specified element in bytes.
+/// 
+/// # Arguments
+/// * `item` - the record or originator information
+#[inline]
+pub(crate) fn frame_size<'a, T: Serializable<'a> + ?Sized>(item: &T) -> usize {
+    FRAME_LENGTH_SIZE + item.serialized_size()
+}
+
+/// Appends the frame for the specified element to a buffer.
+/// 
+/// # Arguments
+/// * `item` - the record or originator information
+/// * `buffer` - the buffer receiving the frame
+pub(crate) fn append_frame<'a, T: Serializable<'a> + ?Sized>(item: &T, buffer: &mut Vec<u8>) {
+    (item.serialized_size() as u32).serialize_to(buffer);
+    item.serialize_to(buffer);
+}
+
+/// Iterator over the frames of a binary file.
+struct Frames<'a> {
+    // remaining file data
+    data: &'a [u8],
+    // indicates whether the last frame is truncated
+    torn: bool
+}
+impl<'a> Iterator for Frames<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() { return None }
+        let len = match u32::deserialize_from(self.data) {
+            Ok(len) if self.data.len() >= FRAME_LENGTH_SIZE + len as usize => len as usize,
+            _ => {
+                self.torn = true;
+                return None
+            }
+        };
+        let frame = &self.data[FRAME_LENGTH_SIZE..FRAME_LENGTH_SIZE + len];
+        self.data = &self.data[FRAME_LENGTH_SIZE + len..];
+        Some(frame)
+    }
+}
+
+// Magic value at the start of a binary file
+const BINARY_FILE_MAGIC: &[u8] = b"COALYBF1";
+
+// Size of the length field preceding the data in a frame
+const FRAME_LENGTH_SIZE: usize = 4;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use super::*;
+    use crate::record::RecordLevelId;
+    use crate::record::recorddata::LocalRecordData;
+    use crate::util::originator_info;
+
+    #[test]
+    /// Tests reading records from a binary file
+    fn test_read_binary_file() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let rw_dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap()).join("tmp");
+        let file_name = rw_dir.join(format!("binread{}.cbf", now));
+        let mut content = file_header(&originator_info());
+        for i in 0..3 {
+            let rec = LocalRecordData::for_write(1, "main", RecordLevelId::Info, file!(),
+                                                 line!(), &format!("message {}", i));
+            append_frame(&rec, &mut content);
+        }
+        // truncated last frame
+        let rec = LocalRecordData::for_write(1, "main", RecordLevelId::Error, file!(), line!(),
+                                             "lost message");
+        let mut frame = Vec::<u8>::new();
+        append_frame(&rec, &mut frame);
+        content.extend_from_slice(&frame[..frame.len() - 3]);
+        std::fs::write(&file_name, &content).unwrap();
+        let recs = read_binary_file(&file_name, None, None).unwrap();
+        assert_eq!(3, recs.records().len());
+        for (i, rec) in recs.records().iter().enumerate() {
+            assert!(rec.contains(&format!("message {}", i)));
+        }
+        assert_eq!(1, recs.torn_count());
+        // plain text file
+        std::fs::write(&file_name, b"message 0\n").unwrap();
+        assert!(read_binary_file(&file_name, None, None).is_err());
+        let _ = std::fs::remove_file(&file_name);
+    }
+}
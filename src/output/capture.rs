@@ -0,0 +1,269 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Capture files for mode changes triggered by custom observer objects.
+//! While a matching observer exists, all records written by the thread that created the
+//! observer are additionally written to the capture file. Observers with equal file names share
+//! the file. After the last observer using a file has been dropped, the file is kept open for
+//! the configured time to live, then it is closed. Hence short-lived observers, e.g. one per
+//! request, don't accumulate open files.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use crate::config::Configuration;
+use crate::context::ContextMap;
+use crate::errorhandling::CoalyException;
+use crate::modechange::CaptureDesc;
+use crate::record::originator::OriginatorInfo;
+use crate::record::recorddata::RecordData;
+use super::formatspec::{FileNameRules, FormatSpec};
+use super::outputformat::OutputFormat;
+use super::resource::Resource;
+
+/// Variable in a capture file name replaced by the observer name
+const VAR_OBSERVER_NAME: &str = "$ObserverName";
+
+/// Variable in a capture file name replaced by the observer value
+const VAR_OBSERVER_VALUE: &str = "$ObserverValue";
+
+/// Runtime state of a capture file.
+struct Capture {
+    // the file resource
+    resource: Resource,
+    // number of active observers using the file
+    users: usize,
+    // time to keep the file open after the last observer using it has been dropped
+    ttl: Duration,
+    // point in time when the file is closed, None while observers are using it
+    expiry: Option<Instant>
+}
+
+/// All currently open capture files and the observers using them.
+pub(crate) struct CaptureTable {
+    // open capture files, key is the resolved file name specification
+    captures: HashMap<FormatSpec, Capture>,
+    // active observers with capture files, key is the thread ID, value holds observer ID,
+    // file key and output format for the thread
+    observers: HashMap<u64, Vec<(u64, FormatSpec, OutputFormat)>>
+}
+impl CaptureTable {
+    /// Creates an empty capture table.
+    #[inline]
+    pub(crate) fn new() -> CaptureTable {
+        CaptureTable { captures: HashMap::new(), observers: HashMap::new() }
+    }
+
+    /// Registers an observer for the capture file with the given descriptor.
+    /// The file is created, if it isn't open yet.
+    ///
+    /// # Arguments
+    /// * `desc` - the capture descriptor
+    /// * `observer_id` - the observer's ID
+    /// * `observer_name` - the observer's name
+    /// * `observer_value` - the observer's value
+    /// * `thread_id` - the ID of the thread that created the observer
+    /// * `thread_name` - the name of the thread that created the observer
+    /// * `context` - the thread's context map
+    /// * `config` - the system configuration
+    /// * `orig_info` - information about local host and process
+    ///
+    /// # Errors
+    /// Returns an error structure if the file couldn't be created
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn acquire(&mut self,
+                          desc: &CaptureDesc,
+                          observer_id: u64,
+                          observer_name: &str,
+                          observer_value: &str,
+                          thread_id: u64,
+                          thread_name: &str,
+                          context: Option<&ContextMap>,
+                          config: &Configuration,
+                          orig_info: &OriginatorInfo) -> Result<(), CoalyException> {
+        let sys_props = config.system_properties();
+        let rules = FileNameRules::new(sys_props.file_name_replacement_char(),
+                                       sys_props.file_name_max_var_length());
+        let name = desc.name().replace(VAR_OBSERVER_NAME, &rules.sanitized(observer_name))
+                              .replace(VAR_OBSERVER_VALUE, &rules.sanitized(observer_value));
+        let key = FormatSpec::from_str(&name).unwrap()
+                      .optimized_for_originator(orig_info, &rules)
+                      .optimized_for_thread(thread_id, thread_name, context, &rules);
+        if ! self.captures.contains_key(&key) {
+            let resource = Resource::capture_file(config, key.clone())?;
+            self.captures.insert(key.clone(), Capture { resource, users: 0,
+                                                        ttl: Duration::from_secs(desc.ttl()),
+                                                        expiry: None });
+        }
+        let capture = self.captures.get_mut(&key).unwrap();
+        capture.users += 1;
+        capture.expiry = None;
+        let ofmt = capture.resource.optimized_output_format(orig_info, thread_id, thread_name);
+        self.observers.entry(thread_id).or_default().push((observer_id, key, ofmt));
+        Ok(())
+    }
+
+    /// Unregisters an observer from its capture file.
+    /// If no more observers use the file, it expires after its time to live.
+    ///
+    /// # Arguments
+    /// * `thread_id` - the ID of the thread that created the observer
+    /// * `observer_id` - the observer's ID
+    pub(crate) fn release(&mut self, thread_id: u64, observer_id: u64) {
+        let observers = match self.observers.get_mut(&thread_id) {
+            Some(o) => o,
+            None => return
+        };
+        if let Some(index) = observers.iter().position(|(id, _, _)| *id == observer_id) {
+            let (_, key, _) = observers.remove(index);
+            if observers.is_empty() { self.observers.remove(&thread_id); }
+            self.file_released(&key);
+        }
+    }
+
+    /// Unregisters all observers of a terminated thread from their capture files.
+    ///
+    /// # Arguments
+    /// * `thread_id` - the terminated thread's ID
+    pub(crate) fn release_thread(&mut self, thread_id: u64) {
+        if let Some(observers) = self.observers.remove(&thread_id) {
+            for (_, key, _) in observers { self.file_released(&key); }
+        }
+    }
+
+    /// Writes a log or trace record to all capture files of the thread that issued it.
+    ///
+    /// # Arguments
+    /// * `record` - the log or trace record
+    ///
+    /// # Errors
+    /// Returns a vector with error structures if the write operation to one or more files
+    /// failed
+    pub(crate) fn write(&mut self, record: &dyn RecordData) -> Result<(), Vec<CoalyException>> {
+        let observers = match self.observers.get(&record.thread_id()) {
+            Some(o) => o,
+            None => return Ok(())
+        };
+        let mut errors = Vec::<CoalyException>::new();
+        for (_, key, ofmt) in observers {
+            if let Some(capture) = self.captures.get_mut(key) {
+                if let Err(m) = capture.resource.write(record, ofmt, false) {
+                    errors.extend_from_slice(&m);
+                }
+            }
+        }
+        if errors.is_empty() { return Ok(()) }
+        Err(errors)
+    }
+
+    /// Closes all capture files, whose time to live has expired.
+    ///
+    /// # Arguments
+    /// * `now` - the current point in time
+    pub(crate) fn close_expired(&mut self, now: Instant) {
+        self.captures.retain(|_, c| {
+            if c.expiry.is_some_and(|exp| exp <= now) {
+                c.resource.close(None);
+                return false
+            }
+            true
+        });
+    }
+
+    /// Closes all capture files.
+    ///
+    /// # Arguments
+    /// * `shutdown_deadline` - the point in time when the shutdown must be completed,
+    ///   **None** if unlimited
+    pub(crate) fn close(&mut self, shutdown_deadline: Option<Instant>) {
+        self.observers.clear();
+        for (_, mut c) in self.captures.drain() { c.resource.close(shutdown_deadline); }
+    }
+
+    /// Decrements the number of observers using a capture file.
+    /// Sets the file's expiry time, if no more observers use it.
+    ///
+    /// # Arguments
+    /// * `key` - the key of the capture file
+    fn file_released(&mut self, key: &FormatSpec) {
+        if let Some(capture) = self.captures.get_mut(key) {
+            capture.users = capture.users.saturating_sub(1);
+            if capture.users == 0 { capture.expiry = Some(Instant::now() + capture.ttl); }
+        }
+    }
+}
+impl Default for CaptureTable {
+    fn default() -> Self { CaptureTable::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::config;
+    use crate::record::RecordLevelId;
+    use crate::record::recorddata::LocalRecordData;
+
+    #[test]
+    /// Tests that capture files are shared by observers with equal names and closed after
+    /// their time to live has expired
+    fn test_capture_table() {
+        let dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap())
+                      .join("tmp").join("output").join("capture").join("test_capture_table");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let cfg_file = dir.join("capture.toml");
+        std::fs::write(&cfg_file, format!("[system]\noutput_path = \"{}\"\n",
+                                          dir.to_string_lossy())).unwrap();
+        let local_info = OriginatorInfo::new(1, "app", "localhost", "127.0.0.1");
+        let cfg = config::configuration(&local_info, Some(&cfg_file.to_string_lossy()));
+        let desc = CaptureDesc::new("request_$ObserverValue.log", 0);
+        let mut table = CaptureTable::new();
+        table.acquire(&desc, 11, "request", "4/711", 1, "one", None, &cfg, &local_info).unwrap();
+        table.acquire(&desc, 12, "request", "4/711", 2, "two", None, &cfg, &local_info).unwrap();
+        table.acquire(&desc, 13, "request", "4712", 1, "one", None, &cfg, &local_info).unwrap();
+        assert_eq!(2, table.captures.len());
+        let rec = LocalRecordData::for_write(1, "one", RecordLevelId::Info, "a.rs", 1,
+                                             "captured");
+        table.write(&rec).unwrap();
+        assert!(dir.join("request_4_711.log").exists());
+        assert!(dir.join("request_4712.log").exists());
+        table.release(1, 11);
+        table.close_expired(Instant::now());
+        assert_eq!(2, table.captures.len());
+        table.release(2, 12);
+        table.release_thread(1);
+        table.close_expired(Instant::now());
+        assert!(table.captures.is_empty());
+        assert!(table.observers.is_empty());
+    }
+}
@@ -0,0 +1,511 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+
+//! At-rest encryption of plain and memory mapped output files.
+//! Encrypted files start with a header consisting of a magic value, the file kind, the key
+//! derivation method and a random salt. The key for the file is derived from the key in the
+//! configured key file or from the configured passphrase, using the salt.
+//! Plain files store every chunk written as frame consisting of the frame length, a random
+//! nonce and the data encrypted with AES-256-GCM. Memory mapped files store every record
+//! encrypted the same way as hexadecimal string.
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::error::Unspecified;
+use ring::pbkdf2::{self, PBKDF2_HMAC_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fmt::Debug;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::Arc;
+use crate::coalyxe;
+use crate::config::systemproperties::EncryptionKeySpec;
+use crate::errorhandling::*;
+use super::mappedfile::mapped_records;
+
+/// Content of a decrypted output file.
+#[derive(Debug)]
+pub struct DecryptedFile {
+    // decrypted file content, for memory mapped files all intact records, oldest first
+    content: Vec<u8>,
+    // number of corrupted frames or records skipped
+    torn_count: usize
+}
+impl DecryptedFile {
+    /// Returns the decrypted file content.
+    /// The content of a plain file is returned as written, for binary files including the
+    /// binary file header. For memory mapped files all intact records are returned, oldest
+    /// first.
+    #[inline]
+    pub fn content(&self) -> &[u8] { &self.content }
+
+    /// Returns the number of corrupted frames or records, that have been skipped.
+    /// A frame truncated at the end of the file, e.g. because the application crashed, counts
+    /// as corrupted.
+    #[inline]
+    pub fn torn_count(&self) -> usize { self.torn_count }
+}
+
+/// Key to decrypt output files.
+pub struct DecryptionKey(KeyMaterial);
+impl DecryptionKey {
+    /// Creates a decryption key from a key file.
+    ///
+    /// # Arguments
+    /// * `file_path` - the path of the key file, the file must contain either 32 bytes or
+    ///   64 hexadecimal digits
+    ///
+    /// # Errors
+    /// Returns an error structure if the key file could not be read or is invalid
+    pub fn from_key_file(file_path: &Path) -> Result<DecryptionKey, CoalyException> {
+        Ok(DecryptionKey(KeyMaterial::Key(read_key_file(file_path)?)))
+    }
+
+    /// Creates a decryption key from a passphrase.
+    ///
+    /// # Arguments
+    /// * `passphrase` - the passphrase
+    pub fn from_passphrase(passphrase: &str) -> DecryptionKey {
+        DecryptionKey(KeyMaterial::Passphrase(passphrase.to_string()))
+    }
+}
+
+/// Reads and decrypts an encrypted plain or memory mapped output file.
+///
+/// # Arguments
+/// * `file_path` - the full path of the encrypted file
+/// * `key` - the key to decrypt the file
+///
+/// # Return values
+/// the decrypted file content
+///
+/// # Errors
+/// Returns an error structure if the file could not be read, is not an encrypted output file
+/// or could not be decrypted with the given key
+pub fn read_encrypted_file(file_path: &Path,
+                           key: &DecryptionKey) -> Result<DecryptedFile, CoalyException> {
+    let path_name = file_path.to_string_lossy().to_string();
+    let data = std::fs::read(file_path).map_err(|e| coalyxe!(E_FILE_READ_ERR,
+                                                             path_name.to_string(),
+                                                             e.to_string()))?;
+    if data.len() < ENCRYPTION_HEADER_LEN || ! data.starts_with(ENCRYPTION_MAGIC) {
+        return Err(coalyxe!(E_ENC_INV_FILE, path_name))
+    }
+    let magic_len = ENCRYPTION_MAGIC.len();
+    let kind = data[magic_len];
+    let kdf = data[magic_len + 1];
+    let salt = &data[magic_len + 2..ENCRYPTION_HEADER_LEN];
+    if key.0.kdf() != kdf { return Err(coalyxe!(E_ENC_DECRYPTION_FAILED, path_name)) }
+    let cipher = Cipher::with_salt(&key.0, salt.try_into().unwrap(),
+                                   Arc::new(SystemRandom::new()))
+                        .map_err(|_| coalyxe!(E_ENC_DECRYPTION_FAILED, path_name.to_string()))?;
+    let body = &data[ENCRYPTION_HEADER_LEN..];
+    let (content, torn_count) = match kind {
+        FILE_KIND_PLAIN => cipher.open_frames(body),
+        FILE_KIND_MAPPED => {
+            let recs = mapped_records(body, &|r| cipher.open_hex(r));
+            (recs.records().concat().into_bytes(), recs.torn_count())
+        },
+        _ => return Err(coalyxe!(E_ENC_INV_FILE, path_name))
+    };
+    if content.is_empty() && torn_count > 0 {
+        return Err(coalyxe!(E_ENC_DECRYPTION_FAILED, path_name))
+    }
+    Ok(DecryptedFile { content, torn_count })
+}
+
+/// Encryption state for an output file resource.
+#[derive(Clone, Debug)]
+pub(crate) struct Cipher {
+    // the key derived from the configured key material and the salt
+    key: LessSafeKey,
+    // key derivation method
+    kdf: u8,
+    // random salt used for key derivation
+    salt: [u8; SALT_LEN],
+    // generator for the nonces
+    rng: Arc<dyn RandomSource>
+}
+impl Cipher {
+    /// Creates the encryption state for the key specified in the system properties.
+    /// A random salt is used, hence every process encrypts with a different key.
+    ///
+    /// # Arguments
+    /// * `spec` - the specification of the key file or passphrase
+    ///
+    /// # Errors
+    /// Returns an error structure if the key file could not be read or is invalid, or if the
+    /// key could not be derived
+    #[inline]
+    pub(crate) fn new(spec: &EncryptionKeySpec) -> Result<Cipher, CoalyException> {
+        Cipher::with_rng(spec, Arc::new(SystemRandom::new()))
+    }
+
+    /// Returns the header for an encrypted file.
+    ///
+    /// # Arguments
+    /// * `kind` - the file kind, either FILE_KIND_PLAIN or FILE_KIND_MAPPED
+    pub(crate) fn header(&self, kind: u8) -> Vec<u8> {
+        let mut header = Vec::<u8>::with_capacity(ENCRYPTION_HEADER_LEN);
+        header.extend_from_slice(ENCRYPTION_MAGIC);
+        header.push(kind);
+        header.push(self.kdf);
+        header.extend_from_slice(&self.salt);
+        header
+    }
+
+    /// Encrypts data for a plain file.
+    ///
+    /// # Arguments
+    /// * `data` - the data to encrypt
+    ///
+    /// # Return values
+    /// the frame with the encrypted data, preceded by its length
+    ///
+    /// # Errors
+    /// Returns an error structure if the data could not be encrypted
+    pub(crate) fn seal_frame(&self, data: &[u8]) -> Result<Vec<u8>, CoalyException> {
+        let sealed = self.seal(data)?;
+        let mut frame = Vec::<u8>::with_capacity(4 + sealed.len());
+        frame.extend_from_slice(&(sealed.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&sealed);
+        Ok(frame)
+    }
+
+    /// Encrypts a record for a memory mapped file.
+    ///
+    /// # Arguments
+    /// * `rec` - the formatted record
+    ///
+    /// # Return values
+    /// the encrypted record as hexadecimal string
+    ///
+    /// # Errors
+    /// Returns an error structure if the record could not be encrypted
+    #[cfg(any(test, feature="mmfile"))]
+    pub(crate) fn seal_hex(&self, rec: &str) -> Result<String, CoalyException> {
+        Ok(self.seal(rec.as_bytes())?.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Creates the encryption state for the specified key, using the given random generator
+    /// for salt and nonces.
+    ///
+    /// # Errors
+    /// Returns an error structure if the key file could not be read or is invalid, or if the
+    /// key could not be derived
+    fn with_rng(spec: &EncryptionKeySpec,
+                rng: Arc<dyn RandomSource>) -> Result<Cipher, CoalyException> {
+        let material = match spec {
+            EncryptionKeySpec::KeyFile(path) => KeyMaterial::Key(read_key_file(Path::new(path))?),
+            EncryptionKeySpec::Passphrase(p) => KeyMaterial::Passphrase(p.to_string())
+        };
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill(&mut salt).map_err(|_| coalyxe!(E_ENC_ENCRYPTION_FAILED))?;
+        Cipher::with_salt(&material, salt, rng).map_err(|_| coalyxe!(E_ENC_ENCRYPTION_FAILED))
+    }
+
+    /// Creates the encryption state for given key material and salt.
+    ///
+    /// # Errors
+    /// Returns an error if the key could not be derived
+    fn with_salt(material: &KeyMaterial,
+                 salt: [u8; SALT_LEN],
+                 rng: Arc<dyn RandomSource>) -> Result<Cipher, Unspecified> {
+        let mut key_bytes = [0u8; KEY_LEN];
+        match material {
+            KeyMaterial::Key(k) => {
+                let prk = Salt::new(HKDF_SHA256, &salt).extract(k);
+                prk.expand(&[HKDF_INFO], &AES_256_GCM)?.fill(&mut key_bytes)?;
+            },
+            KeyMaterial::Passphrase(p) => {
+                pbkdf2::derive(PBKDF2_HMAC_SHA256, NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+                               &salt, p.as_bytes(), &mut key_bytes);
+            }
+        }
+        let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key_bytes)?);
+        Ok(Cipher { key, kdf: material.kdf(), salt, rng })
+    }
+
+    /// Encrypts data with a random nonce.
+    /// Nothing is returned if no nonce could be generated or the encryption failed, hence
+    /// a nonce is never reused and plain data never passed as encrypted.
+    ///
+    /// # Return values
+    /// the nonce followed by the encrypted data and the authentication tag
+    ///
+    /// # Errors
+    /// Returns an error structure if the data could not be encrypted
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>, CoalyException> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce).map_err(|_| coalyxe!(E_ENC_ENCRYPTION_FAILED))?;
+        let mut sealed = Vec::<u8>::with_capacity(NONCE_LEN + data.len() + TAG_LEN);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(data);
+        let mut in_out = sealed.split_off(NONCE_LEN);
+        self.key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce),
+                                          Aad::empty(), &mut in_out)
+                .map_err(|_| coalyxe!(E_ENC_ENCRYPTION_FAILED))?;
+        sealed.append(&mut in_out);
+        Ok(sealed)
+    }
+
+    /// Decrypts data encrypted by method seal.
+    ///
+    /// # Return values
+    /// the decrypted data, **None** if the data is corrupted or the key is wrong
+    fn open(&self, sealed: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LEN + TAG_LEN { return None }
+        let nonce = Nonce::try_assume_unique_for_key(&sealed[..NONCE_LEN]).ok()?;
+        let mut in_out = sealed[NONCE_LEN..].to_vec();
+        let len = self.key.open_in_place(nonce, Aad::empty(), &mut in_out).ok()?.len();
+        in_out.truncate(len);
+        Some(in_out)
+    }
+
+    /// Decrypts a record written by method seal_hex.
+    ///
+    /// # Return values
+    /// the decrypted record, **None** if the record is corrupted or the key is wrong
+    fn open_hex(&self, rec: &str) -> Option<String> {
+        if ! rec.len().is_multiple_of(2) || ! rec.is_ascii() { return None }
+        let sealed = (0..rec.len()).step_by(2)
+                                   .map(|i| u8::from_str_radix(&rec[i..i + 2], 16).ok())
+                                   .collect::<Option<Vec<u8>>>()?;
+        String::from_utf8(self.open(&sealed)?).ok()
+    }
+
+    /// Decrypts all frames of a plain file.
+    ///
+    /// # Arguments
+    /// * `data` - the file data following the header
+    ///
+    /// # Return values
+    /// the decrypted data and the number of corrupted frames
+    fn open_frames(&self, data: &[u8]) -> (Vec<u8>, usize) {
+        let mut content = Vec::<u8>::with_capacity(data.len());
+        let mut torn_count = 0;
+        let mut rest = data;
+        while rest.len() >= 4 {
+            let frame_len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            if rest.len() - 4 < frame_len {
+                // frame truncated at end of file
+                torn_count += 1;
+                return (content, torn_count)
+            }
+            match self.open(&rest[4..4 + frame_len]) {
+                Some(plain) => content.extend_from_slice(&plain),
+                None => torn_count += 1
+            }
+            rest = &rest[4 + frame_len..];
+        }
+        if ! rest.is_empty() { torn_count += 1; }
+        (content, torn_count)
+    }
+}
+
+/// Generator for the random salts and nonces.
+trait RandomSource: Debug + Send + Sync {
+    /// Fills the given buffer with random bytes.
+    ///
+    /// # Errors
+    /// Returns an error if no random bytes could be generated
+    fn fill(&self, dest: &mut [u8]) -> Result<(), Unspecified>;
+}
+impl RandomSource for SystemRandom {
+    #[inline]
+    fn fill(&self, dest: &mut [u8]) -> Result<(), Unspecified> { SecureRandom::fill(self, dest) }
+}
+
+/// Key material, from which the keys for the files are derived.
+enum KeyMaterial {
+    // key read from a key file
+    Key([u8; KEY_LEN]),
+    // passphrase
+    Passphrase(String)
+}
+impl KeyMaterial {
+    /// Returns the key derivation method for this key material.
+    fn kdf(&self) -> u8 {
+        match self {
+            KeyMaterial::Key(_) => KDF_HKDF,
+            KeyMaterial::Passphrase(_) => KDF_PBKDF2
+        }
+    }
+}
+
+/// Reads the key from a key file.
+/// The file must contain either exactly 32 bytes or 64 hexadecimal digits, optionally followed
+/// by white space.
+///
+/// # Arguments
+/// * `file_path` - the path of the key file
+///
+/// # Errors
+/// Returns an error structure if the key file could not be read or is invalid
+fn read_key_file(file_path: &Path) -> Result<[u8; KEY_LEN], CoalyException> {
+    let path_name = file_path.to_string_lossy().to_string();
+    let data = std::fs::read(file_path).map_err(|e| coalyxe!(E_FILE_READ_ERR,
+                                                             path_name.to_string(),
+                                                             e.to_string()))?;
+    if data.len() == KEY_LEN { return Ok(data.try_into().unwrap()) }
+    let hex_digits = data.trim_ascii_end();
+    if hex_digits.len() == 2 * KEY_LEN && hex_digits.iter().all(u8::is_ascii_hexdigit) {
+        let mut key = [0u8; KEY_LEN];
+        for (i, b) in key.iter_mut().enumerate() {
+            let digits = std::str::from_utf8(&hex_digits[2 * i..2 * i + 2]).unwrap();
+            *b = u8::from_str_radix(digits, 16).unwrap();
+        }
+        return Ok(key)
+    }
+    Err(coalyxe!(E_ENC_INV_KEY_FILE, path_name))
+}
+
+/// File kinds in the header of encrypted files
+pub(crate) const FILE_KIND_PLAIN: u8 = b'F';
+pub(crate) const FILE_KIND_MAPPED: u8 = b'M';
+
+/// Magic value at the beginning of encrypted files
+pub(crate) const ENCRYPTION_MAGIC: &[u8] = b"COALYEN1";
+
+/// Length of the salt used for key derivation
+const SALT_LEN: usize = 16;
+
+/// Length of the header of encrypted files
+const ENCRYPTION_HEADER_LEN: usize = 8 + 2 + SALT_LEN;
+
+/// Length of keys in key files
+const KEY_LEN: usize = 32;
+
+/// Length of the authentication tag appended to encrypted data
+const TAG_LEN: usize = 16;
+
+/// Key derivation methods
+const KDF_HKDF: u8 = 1;
+const KDF_PBKDF2: u8 = 2;
+
+/// Context information for the derivation of keys from key files
+const HKDF_INFO: &[u8] = b"coaly output file";
+
+/// Number of iterations for the derivation of keys from passphrases
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use super::*;
+
+    #[test]
+    /// Tests encryption and decryption of a plain file
+    fn test_encrypted_plain_file() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let rw_dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap()).join("tmp");
+        std::fs::create_dir_all(&rw_dir).unwrap();
+        let key_file = rw_dir.join(format!("enckey{}.txt", now));
+        std::fs::write(&key_file, format!("{}\n", "0123456789abcdef".repeat(4))).unwrap();
+        let spec = EncryptionKeySpec::KeyFile(key_file.to_string_lossy().to_string());
+        let cipher = Cipher::new(&spec).unwrap();
+        let mut data = cipher.header(FILE_KIND_PLAIN);
+        data.extend_from_slice(&cipher.seal_frame(b"first record\n").unwrap());
+        data.extend_from_slice(&cipher.seal_frame(b"second record\n").unwrap());
+        let torn_frame = cipher.seal_frame(b"third record\n").unwrap();
+        data.extend_from_slice(&torn_frame[..torn_frame.len() - 3]);
+        let file_name = rw_dir.join(format!("encplain{}.log", now));
+        std::fs::write(&file_name, &data).unwrap();
+        let key = DecryptionKey::from_key_file(&key_file).unwrap();
+        let dec = read_encrypted_file(&file_name, &key).unwrap();
+        assert_eq!(b"first record\nsecond record\n", dec.content());
+        assert_eq!(1, dec.torn_count());
+        // wrong key
+        std::fs::write(&key_file, [7u8; KEY_LEN]).unwrap();
+        let key = DecryptionKey::from_key_file(&key_file).unwrap();
+        assert!(read_encrypted_file(&file_name, &key).is_err());
+        assert!(read_encrypted_file(&file_name, &DecryptionKey::from_passphrase("x")).is_err());
+        // invalid key file
+        std::fs::write(&key_file, b"0123").unwrap();
+        assert!(DecryptionKey::from_key_file(&key_file).is_err());
+        let _ = std::fs::remove_file(&key_file);
+        let _ = std::fs::remove_file(&file_name);
+    }
+
+    #[test]
+    /// Tests encryption and decryption of records with a passphrase
+    fn test_passphrase() {
+        let spec = EncryptionKeySpec::Passphrase(String::from("secret"));
+        let cipher = Cipher::new(&spec).unwrap();
+        let sealed = cipher.seal_hex("a record\n").unwrap();
+        assert_eq!("a record\n", cipher.open_hex(&sealed).unwrap());
+        let header = cipher.header(FILE_KIND_MAPPED);
+        let salt = header[ENCRYPTION_HEADER_LEN - SALT_LEN..].try_into().unwrap();
+        let reader = Cipher::with_salt(&KeyMaterial::Passphrase(String::from("secret")), salt,
+                                       Arc::new(SystemRandom::new())).unwrap();
+        assert_eq!("a record\n", reader.open_hex(&sealed).unwrap());
+        let reader = Cipher::with_salt(&KeyMaterial::Passphrase(String::from("Secret")), salt,
+                                       Arc::new(SystemRandom::new())).unwrap();
+        assert!(reader.open_hex(&sealed).is_none());
+        assert!(cipher.open_hex(&sealed[2..]).is_none());
+    }
+
+    #[test]
+    /// Tests that nothing is encrypted, if no random salt or nonce can be generated
+    fn test_random_failure() {
+        let spec = EncryptionKeySpec::Passphrase(String::from("secret"));
+        let err = Cipher::with_rng(&spec, Arc::new(LimitedRandom::new(0))).unwrap_err();
+        assert_eq!(E_ENC_ENCRYPTION_FAILED, err.id());
+        // salt and nonce for first record succeed, nonce for second record fails
+        let cipher = Cipher::with_rng(&spec, Arc::new(LimitedRandom::new(2))).unwrap();
+        assert!(cipher.seal_frame(b"first record\n").is_ok());
+        let err = cipher.seal_frame(b"second record\n").unwrap_err();
+        assert_eq!(E_ENC_ENCRYPTION_FAILED, err.id());
+        assert!(cipher.seal_hex("third record\n").is_err());
+    }
+
+    /// Random generator failing after a given number of successful calls
+    #[derive(Debug)]
+    struct LimitedRandom(AtomicUsize);
+    impl LimitedRandom {
+        fn new(success_count: usize) -> LimitedRandom {
+            LimitedRandom(AtomicUsize::new(success_count))
+        }
+    }
+    impl RandomSource for LimitedRandom {
+        fn fill(&self, dest: &mut [u8]) -> Result<(), Unspecified> {
+            self.0.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                  .map_err(|_| Unspecified)?;
+            dest.fill(0x5a);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Fault injection for output resources.
+//! Allows applications and Coaly's own tests to provoke write and rollover failures as well as
+//! slow writes deterministically, in order to verify fallback, retry and drop behaviour.
+//! The settings are process wide, since physical resources are usually handled by the
+//! Coaly worker thread.
+//! Only available with feature faultinjection, must not be used in production builds.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+use crate::coalyxe;
+use crate::errorhandling::*;
+
+// write operations are failed, if the write counter is a multiple of this value (0 means never)
+static WRITE_FAILURE_INTERVAL: AtomicU32 = AtomicU32::new(0);
+// number of write operations since last setting of the failure interval
+static WRITE_COUNT: AtomicU32 = AtomicU32::new(0);
+// delay in milliseconds applied before every write operation
+static WRITE_DELAY: AtomicU64 = AtomicU64::new(0);
+// indicates whether rollover operations shall fail
+static ROLLOVER_FAILURE: AtomicBool = AtomicBool::new(false);
+
+/// Lets every n-th write operation to a physical output resource fail.
+/// The write counter is reset upon every call.
+/// 
+/// # Arguments
+/// * `n` - the write failure interval, 0 to disable write failures
+pub fn fail_every_nth_write(n: u32) {
+    WRITE_COUNT.store(0, Ordering::SeqCst);
+    WRITE_FAILURE_INTERVAL.store(n, Ordering::SeqCst);
+}
+
+/// Delays every write operation to a physical output resource.
+/// 
+/// # Arguments
+/// * `delay` - the delay to apply before a write operation, zero to disable delays
+pub fn delay_writes(delay: Duration) {
+    WRITE_DELAY.store(delay.as_millis() as u64, Ordering::SeqCst);
+}
+
+/// Lets rollover operations for file based output resources fail.
+/// 
+/// # Arguments
+/// * `enabled` - **true** to let rollover operations fail, **false** to disable failures
+pub fn fail_rollover(enabled: bool) { ROLLOVER_FAILURE.store(enabled, Ordering::SeqCst); }
+
+/// Disables all injected faults.
+pub fn reset() {
+    fail_every_nth_write(0);
+    delay_writes(Duration::ZERO);
+    fail_rollover(false);
+}
+
+/// Applies the injected faults to a write operation.
+/// To be called before data is written to a physical resource.
+/// 
+/// # Errors
+/// Returns an error structure if the write operation shall fail
+pub(crate) fn check_write() -> Result<(), CoalyException> {
+    let delay = WRITE_DELAY.load(Ordering::SeqCst);
+    if delay > 0 { sleep(Duration::from_millis(delay)); }
+    let intvl = WRITE_FAILURE_INTERVAL.load(Ordering::SeqCst);
+    if intvl == 0 { return Ok(()) }
+    let count = WRITE_COUNT.fetch_add(1, Ordering::SeqCst) + 1;
+    if count.is_multiple_of(intvl) { return Err(coalyxe!(E_INJECTED_WRITE_ERR)) }
+    Ok(())
+}
+
+/// Applies the injected faults to a rollover operation.
+/// To be called before the active output file is archived.
+/// 
+/// # Arguments
+/// * `file_name` - the name of the active output file
+/// 
+/// # Errors
+/// Returns an error structure if the rollover operation shall fail
+pub(crate) fn check_rollover(file_name: &str) -> Result<(), CoalyException> {
+    if ROLLOVER_FAILURE.load(Ordering::SeqCst) {
+        return Err(coalyxe!(E_INJECTED_ROVR_ERR, file_name.to_string()))
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_injection() {
+        reset();
+        assert!(check_write().is_ok());
+        assert!(check_rollover("coaly.log").is_ok());
+        fail_every_nth_write(3);
+        let results: Vec<bool> = (0..6).map(|_| check_write().is_ok()).collect();
+        assert_eq!(vec!(true, true, false, true, true, false), results);
+        fail_rollover(true);
+        assert!(check_rollover("coaly.log").is_err());
+        reset();
+        assert!(check_write().is_ok());
+        assert!(check_rollover("coaly.log").is_ok());
+    }
+}
@@ -0,0 +1,120 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+
+//! Rollover forecast for file based output resources, intended for capacity planning.
+
+use chrono::{DateTime, Local};
+
+/// Forecast of the next rollover for a file based output resource.
+/// Contains the point in time of the next time based rollover, the progress towards the size
+/// threshold and the files that would be removed by the rollover policy's keep count, if the
+/// rollover took place now.
+#[derive(Clone, Debug)]
+pub struct RolloverForecast {
+    // full path name of the currently active output file
+    file_name: String,
+    // point in time of the next rollover, for time based rollover only
+    next_rollover: Option<DateTime<Local>>,
+    // number of bytes written to the active output file, None for memory mapped files
+    size: Option<usize>,
+    // file size triggering a rollover, for size based rollover only
+    size_limit: Option<usize>,
+    // full path names of all files removed by the next rollover
+    removal_candidates: Vec<String>
+}
+impl RolloverForecast {
+    /// Creates a rollover forecast.
+    ///
+    /// # Arguments
+    /// * `file_name` - the full path name of the active output file
+    /// * `next_rollover` - the point in time of the next time based rollover
+    /// * `size` - the number of bytes written to the active output file
+    /// * `size_limit` - the file size triggering a rollover
+    /// * `removal_candidates` - the full path names of the files removed by the next rollover
+    pub(crate) fn new(file_name: String,
+                      next_rollover: Option<DateTime<Local>>,
+                      size: Option<usize>,
+                      size_limit: Option<usize>,
+                      removal_candidates: Vec<String>) -> RolloverForecast {
+        RolloverForecast { file_name, next_rollover, size, size_limit, removal_candidates }
+    }
+
+    /// Returns the full path name of the currently active output file.
+    #[inline]
+    pub fn file_name(&self) -> &str { &self.file_name }
+
+    /// Returns the point in time, when the next rollover is expected.
+    /// Returns **None**, if the resource's rollover policy is not time based.
+    #[inline]
+    pub fn next_rollover(&self) -> Option<&DateTime<Local>> { self.next_rollover.as_ref() }
+
+    /// Returns the number of bytes written to the active output file since its creation.
+    /// Returns **None** for memory mapped files.
+    #[inline]
+    pub fn size(&self) -> Option<usize> { self.size }
+
+    /// Returns the file size, that triggers a rollover.
+    /// Returns **None**, if the resource's rollover is not size based.
+    #[inline]
+    pub fn size_limit(&self) -> Option<usize> { self.size_limit }
+
+    /// Returns the progress towards the size threshold in percent.
+    /// Returns **None**, if the resource's rollover is not size based.
+    pub fn size_usage(&self) -> Option<f64> {
+        match (self.size, self.size_limit) {
+            (Some(s), Some(l)) if l > 0 => Some(s as f64 * 100.0 / l as f64),
+            _ => None
+        }
+    }
+
+    /// Returns the full path names of all rollover files, that would be removed because of the
+    /// rollover policy's keep count, if the rollover took place now.
+    #[inline]
+    pub fn removal_candidates(&self) -> &[String] { &self.removal_candidates }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_usage() {
+        let fc = RolloverForecast::new(String::from("/tmp/a.log"), None, Some(250), Some(1000),
+                                       Vec::new());
+        assert_eq!(Some(25.0), fc.size_usage());
+        let fc = RolloverForecast::new(String::from("/tmp/a.log"), Some(Local::now()), Some(250),
+                                       None, Vec::new());
+        assert!(fc.size_usage().is_none());
+        assert!(fc.next_rollover().is_some());
+    }
+}
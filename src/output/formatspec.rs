@@ -37,16 +37,18 @@
 //! The specifications are usually read from the configuration file. If no such file is supplied
 //! or the file can't be read, default specification are used instead.
 
-use chrono::Local;
-use regex::{Error, Regex};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Timelike};
 use std::str::FromStr;
-use crate::record::RecordLevelMap;
+use crate::config::datetimeformat::{format_in_zone, DateTimeZone};
+use crate::context::ContextMap;
+use crate::record::{RecordLevelMap, RecordTrigger};
 use crate::record::originator::OriginatorInfo;
 use crate::record::recorddata::RecordData;
-use crate::util::{DIR_SEP, regex_escaped_str};
-use crate::variables::{Variable, VariableMap, VAR_NAME_ENV};
-#[cfg(test)]
-use chrono::DateTime;
+use crate::util::{DIR_SEP, is_valid_file_name_char};
+use crate::variables::{bracketed_arg, record_env_value, scope_stack_arg_var, Variable,
+                       VariableMap, VAR_NAME_ATTR, VAR_NAME_CONTEXT, VAR_NAME_ENV,
+                       VAR_NAME_SCOPE_STACK};
+use super::namepattern::{FileNamePattern, PatternSegment};
 
 /// Single item within a record or name format specification.
 /// Items can either be constant strings or placeholder variables, which are replaced with their
@@ -57,6 +59,38 @@ pub(crate) enum FormatItem {
     VariableItem(Variable)
 }
 
+/// Rules for variable values used in output file names.
+/// Characters not allowed in file names on the current platform are replaced, overlong values
+/// are truncated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct FileNameRules {
+    // replacement for characters not allowed in file names
+    replacement_char: char,
+    // maximum number of characters for a variable value
+    max_var_length: usize
+}
+impl FileNameRules {
+    /// Creates file name rules.
+    /// 
+    /// # Arguments
+    /// * `replacement_char` - the replacement for characters not allowed in file names
+    /// * `max_var_length` - the maximum number of characters for a variable value
+    pub(crate) fn new(replacement_char: char, max_var_length: usize) -> FileNameRules {
+        FileNameRules { replacement_char, max_var_length }
+    }
+
+    /// Returns the specified variable value sanitized for use in a file name.
+    /// 
+    /// # Arguments
+    /// * `value` - the variable value
+    pub(crate) fn sanitized(&self, value: &str) -> String {
+        value.chars()
+             .take(self.max_var_length)
+             .map(|ch| if is_valid_file_name_char(ch) { ch } else { self.replacement_char })
+             .collect()
+    }
+}
+
 /// Descriptor for the fields of a log/trace record or the parts of an output filename.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub(crate) struct FormatSpec(Vec<FormatItem>);
@@ -64,11 +98,14 @@ impl FormatSpec {
     /// Indicates whether this format specification is specific for a thread.
     /// 
     /// # Return values
-    /// **true** if the format contains at least one of the variables ThreadId or ThreadName
+    /// **true** if the format contains at least one of the variables ThreadId, ThreadName or
+    /// Context
     pub(crate) fn is_thread_specific(&self) -> bool {
         for item in &self.0 {
             if let FormatItem::VariableItem(v) = item {
-                if matches!(v, Variable::ThreadId | Variable::ThreadName) { return true; }
+                if matches!(v, Variable::ThreadId | Variable::ThreadName | Variable::Context(_)) {
+                    return true;
+                }
             }
         }
         false
@@ -83,7 +120,8 @@ impl FormatSpec {
             if let FormatItem::VariableItem(v) = item {
                 if matches!(v, Variable::ApplicationId | Variable::ApplicationName |
                                Variable::HostName | Variable::IpAddress |
-                               Variable::ProcessId | Variable::ProcessName | Variable::Env(_)
+                               Variable::ProcessId | Variable::ProcessName | Variable::Env(_) |
+                               Variable::RunId | Variable::ParentRunId | Variable::TraceId
                                ) { return true; }
             }
         }
@@ -93,12 +131,12 @@ impl FormatSpec {
     /// Indicates whether this format specification is indepenent from date and time.
     /// 
     /// # Return values
-    /// **true** if the format does not contain at least one of the variables Date, Time
-    /// or TimeStamp
+    /// **true** if the format does not contain at least one of the variables Date, Time,
+    /// TimeSlice or TimeStamp
     pub(crate) fn is_datetime_independent(&self) -> bool {
         for item in &self.0 {
             if let FormatItem::VariableItem(v) = item {
-                if matches!(v, Variable::Date | Variable::Time |
+                if matches!(v, Variable::Date | Variable::Time | Variable::TimeSlice(_) |
                                Variable::TimeStamp) { return false; }
             }
         }
@@ -149,6 +187,9 @@ impl FormatSpec {
                         Variable::ProcessName => {
                             item_str.push_str(orig_info.process_name());
                         },
+                        Variable::RunId => item_str.push_str(orig_info.run_id()),
+                        Variable::ParentRunId => item_str.push_str(orig_info.parent_run_id()),
+                        Variable::TraceId => item_str.push_str(orig_info.trace_id()),
                         Variable::ThreadId => item_str.push_str(&thread_id.to_string()),
                         Variable::ThreadName => item_str.push_str(thread_name),
                         _ => {
@@ -173,10 +214,13 @@ impl FormatSpec {
     /// 
     /// # Arguments
     /// * `orig_info` - the originator data with the potential variable values
+    /// * `rules` - the rules for variable values in file names
     /// 
     /// # Return values
     /// * the optimized format specification
-    pub(crate) fn optimized_for_originator(&self, orig_info: &OriginatorInfo) -> FormatSpec {
+    pub(crate) fn optimized_for_originator(&self,
+                                           orig_info: &OriginatorInfo,
+                                           rules: &FileNameRules) -> FormatSpec {
         let mut opt_fmt = Vec::<FormatItem>::new();
         let mut item_str = String::new();
         for source_item in &self.0 {
@@ -185,26 +229,31 @@ impl FormatSpec {
                 FormatItem::VariableItem(item) => {
                     match item {
                         Variable::ApplicationId => {
-                            item_str.push_str(&orig_info.application_id());
+                            item_str.push_str(&rules.sanitized(&orig_info.application_id()));
                         },
                         Variable::ApplicationName => {
-                            item_str.push_str(orig_info.application_name());
+                            item_str.push_str(&rules.sanitized(orig_info.application_name()));
                         },
                         Variable::Env(v) => {
                             if let Some(value) = orig_info.env_var_value(v) {
-                                item_str.push_str(value);
+                                item_str.push_str(&rules.sanitized(value));
                             }
                         },
-                        Variable::HostName => item_str.push_str(orig_info.host_name()),
+                        Variable::HostName => {
+                            item_str.push_str(&rules.sanitized(orig_info.host_name()));
+                        },
                         Variable::IpAddress => {
-                            item_str.push_str(orig_info.ip_address());
+                            item_str.push_str(&rules.sanitized(orig_info.ip_address()));
                         },
                         Variable::ProcessId => {
-                            item_str.push_str(&orig_info.process_id());
+                            item_str.push_str(&rules.sanitized(&orig_info.process_id()));
                         },
                         Variable::ProcessName => {
-                            item_str.push_str(orig_info.process_name());
+                            item_str.push_str(&rules.sanitized(orig_info.process_name()));
                         },
+                        Variable::RunId => item_str.push_str(orig_info.run_id()),
+                        Variable::ParentRunId => item_str.push_str(orig_info.parent_run_id()),
+                        Variable::TraceId => item_str.push_str(orig_info.trace_id()),
                         _ => {
                             if ! item_str.is_empty() {
                                 opt_fmt.push(FormatItem::ConstantItem(item_str.to_string()));
@@ -221,17 +270,24 @@ impl FormatSpec {
     }
 
     /// Returns this format specification optimized for a thread.
-    /// Variable items of type ThreadId or ThreadName are replace by constant items with the
-    /// values given to this function.
+    /// Variable items of type ThreadId, ThreadName or Context are replace by constant items with
+    /// the values given to this function. Context entries missing in the thread's context map
+    /// are replaced by an empty string.
     /// Adjacent constant items are combined.
     /// 
     /// # Arguments
     /// * `thread_id` - the thread's ID
     /// * `thread_name` - the thread's name
+    /// * `context` - the thread's context map at the time the first record was issued
+    /// * `rules` - the rules for variable values in file names
     /// 
     /// # Return values
     /// * the optimized format specification
-    pub(crate) fn optimized_for_thread(&self, thread_id: u64, thread_name: &str) -> FormatSpec {
+    pub(crate) fn optimized_for_thread(&self,
+                                       thread_id: u64,
+                                       thread_name: &str,
+                                       context: Option<&ContextMap>,
+                                       rules: &FileNameRules) -> FormatSpec {
         let mut opt_fmt = Vec::<FormatItem>::new();
         let mut item_str = String::new();
         for source_item in &self.0 {
@@ -242,7 +298,14 @@ impl FormatSpec {
                 FormatItem::VariableItem(item) => {
                     match item {
                         Variable::ThreadId => item_str.push_str(&thread_id.to_string()),
-                        Variable::ThreadName => item_str.push_str(thread_name),
+                        Variable::ThreadName => {
+                            item_str.push_str(&rules.sanitized(thread_name));
+                        },
+                        Variable::Context(key) => {
+                            if let Some(value) = context.and_then(|c| c.get(key)) {
+                                item_str.push_str(&rules.sanitized(value));
+                            }
+                        },
                         _ => {
                             if ! item_str.is_empty() {
                                 opt_fmt.push(FormatItem::ConstantItem(item_str.to_string()));
@@ -258,6 +321,80 @@ impl FormatSpec {
         FormatSpec { 0: opt_fmt }
     }
 
+    /// Returns this format specification with environment variable items adjusted to the
+    /// record enrichment settings from the system configuration.
+    /// Variable EnvFields is expanded to name=value pairs for all configured environment
+    /// variables, separated by blanks. Environment variables configured to be read for every
+    /// record are replaced by items not affected by originator optimization.
+    /// 
+    /// # Arguments
+    /// * `snapshot_vars` - the names of environment variables captured upon application start
+    /// * `record_vars` - the names of environment variables read for every record
+    /// 
+    /// # Return values
+    /// * the adjusted format specification
+    pub(crate) fn with_env_fields(&self,
+                                  snapshot_vars: &[String],
+                                  record_vars: &[String]) -> FormatSpec {
+        let mut adj_fmt = Vec::<FormatItem>::new();
+        for source_item in &self.0 {
+            match source_item {
+                FormatItem::VariableItem(Variable::EnvFields) => {
+                    let snapshot_items = snapshot_vars.iter()
+                                                      .map(|n| (n, Variable::Env(n.to_string())));
+                    let record_items = record_vars.iter()
+                                                  .map(|n| (n, Variable::RecordEnv(n.to_string())));
+                    for (index, (name, var)) in snapshot_items.chain(record_items).enumerate() {
+                        let sep = if index > 0 { " " } else { "" };
+                        adj_fmt.push(FormatItem::ConstantItem(format!("{}{}=", sep, name)));
+                        adj_fmt.push(FormatItem::VariableItem(var));
+                    }
+                },
+                FormatItem::VariableItem(Variable::Env(name)) if record_vars.contains(name) => {
+                    adj_fmt.push(FormatItem::VariableItem(Variable::RecordEnv(name.to_string())));
+                },
+                _ => adj_fmt.push(source_item.clone())
+            }
+        }
+        FormatSpec(adj_fmt)
+    }
+
+    /// Returns this format specification with the interval of all time slice variables set
+    /// to the given value.
+    /// 
+    /// # Arguments
+    /// * `interval` - the time slice interval in minutes
+    pub(crate) fn with_time_slice(&self, interval: u32) -> FormatSpec {
+        let items = self.0.iter()
+                          .map(|item| match item {
+                              FormatItem::VariableItem(Variable::TimeSlice(_)) => {
+                                  FormatItem::VariableItem(Variable::TimeSlice(interval))
+                              },
+                              _ => item.clone()
+                          })
+                          .collect();
+        FormatSpec(items)
+    }
+
+    /// Returns the end of the time slice containing the given timestamp.
+    /// 
+    /// # Arguments
+    /// * `now` - the timestamp
+    /// 
+    /// # Return values
+    /// the point in time when the file name changes; **None** if the format doesn't contain
+    /// a time slice variable
+    pub(crate) fn time_slice_end(&self, now: &DateTime<Local>) -> Option<DateTime<Local>> {
+        self.0.iter().find_map(|item| match item {
+            FormatItem::VariableItem(Variable::TimeSlice(interval)) => {
+                let end = time_slice_start(now, *interval) + Duration::minutes(*interval as i64);
+                Some(Local.from_local_datetime(&end).earliest()
+                          .unwrap_or_else(|| Local.from_utc_datetime(&end)))
+            },
+            _ => None
+        })
+    }
+
     /// Converts the specified log or trace record to a string according to this format.
     /// The caller must make sure, that the record is within the scope of this format by invoking
     /// function `applies_to`. The check is not done within this function.
@@ -268,11 +405,61 @@ impl FormatSpec {
     /// * `ts_fmt` - the optional format string for timestamp values
     /// * `date_fmt` - the optional format string for date values
     /// * `tm_fmt` - the optional format string for time values
+    /// * `zone` - the time zone for timestamp, date and time values
     ///
     /// # Return values
     /// the formatted string, to be written to output resource
     pub(crate) fn apply_to_record(&self, record: &dyn RecordData, levels: &RecordLevelMap,
-                           ts_fmt: &str, date_fmt: &str, tm_fmt: &str) -> String {
+                                  ts_fmt: &str, date_fmt: &str, tm_fmt: &str,
+                                  zone: DateTimeZone) -> String {
+        self.format_record(record, levels, |v, result| {
+            let fmt_str = match v {
+                Variable::Date => date_fmt,
+                Variable::Time => tm_fmt,
+                _ => ts_fmt
+            };
+            result.push_str(&format_in_zone(&record.timestamp(), fmt_str, zone))
+        })
+    }
+
+    /// Converts the specified log or trace record to a string according to this format,
+    /// using already formatted values for timestamp, date and time.
+    /// The caller must make sure, that the record is within the scope of this format by invoking
+    /// function `applies_to`. The check is not done within this function.
+    ///
+    /// # Arguments
+    /// * `record` - the record data
+    /// * `levels` - the hash table with the ID character for every record level
+    /// * `ts` - the formatted timestamp
+    /// * `date` - the formatted date
+    /// * `tm` - the formatted time
+    ///
+    /// # Return values
+    /// the formatted string, to be written to output resource
+    pub(crate) fn apply_to_record_with_times(&self, record: &dyn RecordData,
+                                             levels: &RecordLevelMap,
+                                             ts: &str, date: &str, tm: &str) -> String {
+        self.format_record(record, levels, |v, result| {
+            match v {
+                Variable::Date => result.push_str(date),
+                Variable::Time => result.push_str(tm),
+                _ => result.push_str(ts)
+            }
+        })
+    }
+
+    /// Converts the specified log or trace record to a string according to this format.
+    ///
+    /// # Arguments
+    /// * `record` - the record data
+    /// * `levels` - the hash table with the ID character for every record level
+    /// * `push_time` - the function appending the value of a date, time or timestamp variable
+    ///   to the result string
+    ///
+    /// # Return values
+    /// the formatted string, to be written to output resource
+    fn format_record<F>(&self, record: &dyn RecordData, levels: &RecordLevelMap,
+                        push_time: F) -> String where F: Fn(&Variable, &mut String) {
         let mut result = String::with_capacity(128);
         for field in self.0.iter() {
             match field {
@@ -283,8 +470,36 @@ impl FormatSpec {
                 FormatItem::VariableItem(v) => {
                     // for variable fields determine the actual values 
                     match v {
-                        Variable::Date => {
-                            result.push_str(&record.timestamp().format(date_fmt).to_string());
+                        Variable::Date | Variable::Time | Variable::TimeStamp => {
+                            push_time(v, &mut result);
+                        },
+                        Variable::Attr(key) => {
+                            if let Some(value) = record.attrs().and_then(|a| a.get(key)) {
+                                result.push_str(&value.to_string());
+                            }
+                        },
+                        Variable::Attrs => {
+                            if let Some(a) = record.attrs() {
+                                for (index, (key, value)) in a.iter().enumerate() {
+                                    if index > 0 { result.push(' '); }
+                                    result.push_str(&format!("{}={}", key, value));
+                                }
+                            }
+                        },
+                        Variable::Backtrace => {
+                            for frame in record.backtrace().unwrap_or_default().lines() {
+                                result.push_str(EOL);
+                                result.push_str(BACKTRACE_FRAME_PREFIX);
+                                result.push_str(frame);
+                            }
+                        },
+                        Variable::Context(key) => {
+                            if let Some(value) = record.context().and_then(|c| c.get(key)) {
+                                result.push_str(value);
+                            }
+                        },
+                        Variable::Function => {
+                            result.push_str(record.function().unwrap_or_default());
                         },
                         Variable::Level => {
                             let ldesc = &*levels.get(&record.level()).unwrap();
@@ -294,13 +509,33 @@ impl FormatSpec {
                             let ldesc = &*levels.get(&record.level()).unwrap();
                             result.push(ldesc.id_char());
                         },
-                        Variable::Message | Variable::ObserverValue => {
+                        Variable::Message => {
+                            let msg = record.message().as_deref().unwrap_or_default();
+                            match record.decoration() {
+                                Some(d) if record.trigger() == RecordTrigger::Message => {
+                                    result.push_str(&d.decorated(msg));
+                                },
+                                _ => result.push_str(msg)
+                            }
+                        },
+                        Variable::ModulePath => {
+                            result.push_str(record.module_path().unwrap_or_default());
+                        },
+                        Variable::ObserverValue => {
                             result.push_str(record.message().as_ref().unwrap());
                         },
                         Variable::PureSourceFileName => {
                             let pure_fn = record.source_fn().rsplit(DIR_SEP).next().unwrap_or("-");
                             result.push_str(pure_fn);
                         },
+                        Variable::RecordEnv(name) => {
+                            if let Some(value) = record_env_value(name) { result.push_str(&value); }
+                        },
+                        Variable::ScopeStack(sep, depth) => {
+                            let scopes = record.scope_stack();
+                            let skip = if *depth > 0 { scopes.len().saturating_sub(*depth) } else { 0 };
+                            result.push_str(&scopes[skip..].join(sep));
+                        },
                         Variable::SourceFileName => {
                             result.push_str(record.source_fn());
                         },
@@ -314,11 +549,19 @@ impl FormatSpec {
                         Variable::ObserverName => {
                             result.push_str(record.observer_name().as_ref().unwrap());
                         },
-                        Variable::TimeStamp => {
-                            result.push_str(&record.timestamp().format(ts_fmt).to_string());
+                        Variable::ThreadRecordsSuppressed => {
+                            result.push_str(&record.thread_write_counts().1.to_string());
                         },
-                        Variable::Time => {
-                            result.push_str(&record.timestamp().format(tm_fmt).to_string());
+                        Variable::ThreadRecordsWritten => {
+                            result.push_str(&record.thread_write_counts().0.to_string());
+                        },
+                        Variable::UnitFields => {
+                            if let Some(d) = record.decoration() {
+                                for (index, (name, value)) in d.fields().iter().enumerate() {
+                                    if index > 0 { result.push(' '); }
+                                    result.push_str(&format!("{}={}", name, value));
+                                }
+                            }
                         },
                         // other variables already covered by preceding optimization calls
                         _ => {}
@@ -336,8 +579,18 @@ impl FormatSpec {
     ///
     /// # Return values
     /// the filename string
-    pub(crate) fn to_file_name(&self) -> String {
-        let now = Local::now();
+    pub(crate) fn to_file_name(&self) -> String { self.to_file_name_at(&Local::now()) }
+
+    /// Creates a filename string from this format for the given timestamp.
+    /// All placeholder variables not related to date or time must have been resolved prior to
+    /// calling this function. 
+    ///
+    /// # Arguments
+    /// * `now` - the timestamp to use for date and time related variables
+    ///
+    /// # Return values
+    /// the filename string
+    pub(crate) fn to_file_name_at(&self, now: &DateTime<Local>) -> String {
         let mut result = String::with_capacity(256);
         for field in self.0.iter() {
             match field {
@@ -353,6 +606,10 @@ impl FormatSpec {
                         Variable::Time => {
                             result.push_str(&now.format(FN_TIME_FORMAT).to_string());
                         },
+                        Variable::TimeSlice(interval) => {
+                            let start = time_slice_start(now, *interval);
+                            result.push_str(&start.format(FN_TIME_SLICE_FORMAT).to_string());
+                        },
                         // other variables already covered by preceding optimization calls
                         _ => {}
                     }
@@ -362,7 +619,7 @@ impl FormatSpec {
         result
     }
 
-    /// Creates a regular expression to find and sort files from this specification.
+    /// Creates a pattern to find and sort files from this specification.
     /// All placeholder variables not related to date or time must have been resolved prior to
     /// calling this function. 
     ///
@@ -371,30 +628,44 @@ impl FormatSpec {
     ///                 including leading dot, empty string if compression is not used
     ///
     /// # Return values
-    /// the regular expression to find and sort matching files
+    /// the pattern to find and sort matching files
+    pub(crate) fn file_name_pattern(&self, compr_ext: &str) -> FileNamePattern {
+        FileNamePattern::with_ext(self.pattern_segments(None), compr_ext)
+    }
+
+    /// Creates a pattern to find output files from this specification, that were created by
+    /// any thread or process.
+    /// Variables not related to date or time match any non-empty text, rollover sequence
+    /// numbers and one trailing extension for compression or checksum files are accepted.
     ///
-    /// # Errors
-    /// Returns an error if the created regular expression pattern contains a syntax error
-    pub(crate) fn file_name_pattern(&self, compr_ext: &str) -> Result<Regex, Error> {
-        let mut pattern_str = String::with_capacity(256);
-        pattern_str.push_str("^(");
-        for field in self.0.iter() {
+    /// # Return values
+    /// the pattern to find matching files
+    pub(crate) fn stale_file_pattern(&self) -> FileNamePattern {
+        FileNamePattern::with_any_ext(self.pattern_segments(Some(PatternSegment::Any)))
+    }
+
+    /// Returns the file name pattern segments for this specification.
+    ///
+    /// # Arguments
+    /// * `other_var_segment` - the segment for variables not related to date or time,
+    ///                         **None** to ignore such variables
+    fn pattern_segments(&self, other_var_segment: Option<PatternSegment>) -> Vec<PatternSegment> {
+        self.0.iter().filter_map(|field| {
             match field {
-                FormatItem::ConstantItem(c) => { pattern_str.push_str(&regex_escaped_str(c)); },
+                FormatItem::ConstantItem(c) => Some(PatternSegment::Text(c.to_string())),
                 FormatItem::VariableItem(v) => {
                     match v {
-                        Variable::Date => { pattern_str.push_str(FN_DATE_PATTERN); },
-                        Variable::TimeStamp => { pattern_str.push_str(FN_TIMESTAMP_PATTERN); },
-                        Variable::Time => { pattern_str.push_str(FN_TIME_PATTERN); },
-                        _ => { }
+                        Variable::Date => Some(PatternSegment::Digits(FN_DATE_DIGITS)),
+                        Variable::TimeStamp => Some(PatternSegment::Digits(FN_TIMESTAMP_DIGITS)),
+                        Variable::Time => Some(PatternSegment::Digits(FN_TIME_DIGITS)),
+                        Variable::TimeSlice(_) => {
+                            Some(PatternSegment::Digits(FN_TIME_SLICE_DIGITS))
+                        },
+                        _ => other_var_segment.clone()
                     }
                 }
             }
-        }
-        pattern_str.push_str(r")(\.\d+){0,1}");
-        if ! compr_ext.is_empty() { pattern_str.push_str(&format!(r"(\{}){{0,1}}", compr_ext)); }
-        pattern_str.push('$');
-        Regex::new(&pattern_str)
+        }).collect()
     }
 
     /// Returns the items of this format specification.
@@ -419,6 +690,10 @@ impl FormatSpec {
                         Variable::Time => {
                             result.push_str(&dtm.format(FN_TIME_FORMAT).to_string());
                         },
+                        Variable::TimeSlice(interval) => {
+                            let start = time_slice_start(dtm, *interval);
+                            result.push_str(&start.format(FN_TIME_SLICE_FORMAT).to_string());
+                        },
                         // other variables not used
                         _ => {}
                     }
@@ -436,7 +711,6 @@ impl FromStr for FormatSpec {
         const STATE_IN_CONST: u32 = 1;
         const STATE_IN_VAR: u32 = 2;
         let var_map = VariableMap::default();
-        let env_pattern = Regex::new(&format!(r"^{}\[(.*)\]", VAR_NAME_ENV)).unwrap();
         let mut items = Vec::new();
         let mut cur_item = String::with_capacity(64);
         let mut state = STATE_IDLE;
@@ -465,12 +739,21 @@ impl FromStr for FormatSpec {
                     state = STATE_IN_VAR;
                 }
                 _ => {
-                    if env_pattern.is_match(&s[index..]) {
-                        let vname = env_pattern.captures(&s[index..]).unwrap()
-                                               .get(1).unwrap().as_str();
-                        items.push(FormatItem::VariableItem(Variable::Env(vname.to_string())));
-                        // skip var (Env[] + length of env var name)
-                        var_end_index = index + vname.len() + 5;
+                    let arg_var = if let Some((vname, len)) = bracketed_arg(&s[index..],
+                                                                             VAR_NAME_ENV) {
+                        Some((Variable::Env(vname.to_string()), len))
+                    } else if let Some((key, len)) = bracketed_arg(&s[index..],
+                                                                   VAR_NAME_CONTEXT) {
+                        Some((Variable::Context(key.to_string()), len))
+                    } else if let Some((key, len)) = bracketed_arg(&s[index..], VAR_NAME_ATTR) {
+                        Some((Variable::Attr(key.to_string()), len))
+                    } else {
+                        bracketed_arg(&s[index..], VAR_NAME_SCOPE_STACK)
+                            .map(|(arg, len)| (scope_stack_arg_var(arg), len))
+                    };
+                    if let Some((var, len)) = arg_var {
+                        items.push(FormatItem::VariableItem(var));
+                        var_end_index = index + len;
                         state = STATE_IDLE;
                         continue;
                     }
@@ -505,6 +788,19 @@ impl FromStr for FormatSpec {
     }
 }
 
+/// Returns the start of the time slice containing the given timestamp.
+/// Time slices are aligned to local midnight, the interval must divide a day evenly.
+/// 
+/// # Arguments
+/// * `ts` - the timestamp
+/// * `interval` - the time slice interval in minutes
+fn time_slice_start(ts: &DateTime<Local>, interval: u32) -> NaiveDateTime {
+    let local_ts = ts.naive_local();
+    let minute_of_day = local_ts.num_seconds_from_midnight() / 60;
+    let slice_minute = minute_of_day - minute_of_day % u32::max(interval, 1);
+    local_ts.date().and_hms_opt(slice_minute / 60, slice_minute % 60, 0).unwrap()
+}
+
 // Format for timestamps within file names
 const FN_TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S";
 
@@ -514,22 +810,31 @@ const FN_DATE_FORMAT: &str = "%Y%m%d";
 // Format for times within file names
 const FN_TIME_FORMAT: &str = "%H%M%S";
 
-const FN_TIMESTAMP_PATTERN: &str = r"\d{14}";
-const FN_DATE_PATTERN: &str = r"\d{8}";
-const FN_TIME_PATTERN: &str = r"\d{6}";
+// Format for time slice starts within file names
+const FN_TIME_SLICE_FORMAT: &str = "%Y%m%d%H%M";
+
+// Numbers of digits of timestamps, dates, times and time slice starts within file names
+const FN_TIMESTAMP_DIGITS: usize = 14;
+const FN_DATE_DIGITS: usize = 8;
+const FN_TIME_DIGITS: usize = 6;
+const FN_TIME_SLICE_DIGITS: usize = 12;
+
+// Prefix of every backtrace frame in records, each frame is written on a line of its own
+const BACKTRACE_FRAME_PREFIX: &str = "    at ";
 
 #[cfg(windows)]
-const EOL: &str = "\r\n";
+pub(super) const EOL: &str = "\r\n";
 
 #[cfg(not(windows))]
-const EOL: &str = "\n";
+pub(super) const EOL: &str = "\n";
 
 #[cfg(test)]
 mod tests {
-    extern crate regex;
-    use regex::Regex;
     use super::*;
+    use crate::record::RecordLevelId;
+    use crate::record::recorddata::LocalRecordData;
     use std::mem;
+    use std::sync::Arc;
 
     fn build_format_spec(items: &[&str]) -> FormatSpec {
         let mut spec = Vec::<FormatItem>::new();
@@ -548,7 +853,6 @@ mod tests {
     fn verify_format_spec(fmt: &[FormatItem], expected_items: &[&str]) {
         assert_eq!(expected_items.len(), fmt.len());
         let vm = VariableMap::default();
-        let env_pattern = Regex::new(r"^\$Env\[(.*)\]$").unwrap();
         for (i, fmt_item) in fmt.iter().enumerate() {
             let exp_item_str = expected_items[i];
             match &*fmt_item {
@@ -558,10 +862,9 @@ mod tests {
                 FormatItem::VariableItem(var_id) => {
                     match var_id {
                         Variable::Env(v) => {
-                            assert!(env_pattern.is_match(exp_item_str));
-                            let exp_vname = env_pattern.captures(exp_item_str).unwrap()
-                                                       .get(1).unwrap().as_str();
-                            assert_eq!(exp_vname, v);
+                            let exp_vname = exp_item_str.strip_prefix("$Env[")
+                                                        .and_then(|s| s.strip_suffix(']'));
+                            assert_eq!(Some(v.as_str()), exp_vname);
                         },
                         _ => {
                             let expected_var_id = vm.get(&exp_item_str[1..]).unwrap().clone();
@@ -584,7 +887,7 @@ mod tests {
         let tid = 1234;
         let tname = "MyThread";
         let fmt = build_format_spec(items);
-        let opt_spec = fmt.optimized_for_thread(tid, tname);
+        let opt_spec = fmt.optimized_for_thread(tid, tname, None, &FileNameRules::new('_', 64));
         verify_format_spec(opt_spec.items().as_slice(), expected_items);
     }
 
@@ -593,8 +896,9 @@ mod tests {
         oinfo.set_application_id(9876);
         oinfo.set_application_name("coalyapp");
         oinfo.add_env_var("COALYTEST", "FromEnv");
+        oinfo.set_run_context("4e5f", "2c3d", "0a1b");
         let fmt = build_format_spec(items);
-        let opt_spec = fmt.optimized_for_originator(&oinfo);
+        let opt_spec = fmt.optimized_for_originator(&oinfo, &FileNameRules::new('_', 64));
         verify_format_spec(opt_spec.items().as_slice(), expected_items);
     }
 
@@ -638,6 +942,10 @@ mod tests {
         // ThRelevant variable at the end
         check_process_optimization(&["$Time", "|", "$ProcessId", "|", "$ProcessName"],
                                    &["$Time", "|1391|coalyprocess"]);
+        // Run context of the process tree
+        check_process_optimization(&["$TraceId", "|", "$ParentRunId", "|", "$RunId", "|",
+                                     "$Message"],
+                                   &["0a1b|2c3d|4e5f|", "$Message"]);
         // Constant items only
         check_process_optimization(&["Field1", "|", "Field2", "|", "Field3"],
                                    &["Field1|Field2|Field3"]);
@@ -646,6 +954,49 @@ mod tests {
                                    &["$Time", "$LevelId", "$SourceFileName", "$Message"]);
     }
 
+    #[test]
+    fn test_env_fields() {
+        let snapshot_vars = vec!(String::from("DEPLOYMENT_ID"), String::from("REGION"));
+        let record_vars = vec!(String::from("TENANT"));
+        let fmt = FormatSpec::from_str("$Message|$EnvFields|$Env[TENANT]|$Env[REGION]").unwrap();
+        let adj_fmt = fmt.with_env_fields(&snapshot_vars, &record_vars);
+        let items = adj_fmt.items();
+        assert_eq!(12, items.len());
+        assert_eq!(FormatItem::ConstantItem(String::from("DEPLOYMENT_ID=")), items[2]);
+        assert_eq!(FormatItem::VariableItem(Variable::Env(String::from("DEPLOYMENT_ID"))),
+                   items[3]);
+        assert_eq!(FormatItem::ConstantItem(String::from(" TENANT=")), items[6]);
+        assert_eq!(FormatItem::VariableItem(Variable::RecordEnv(String::from("TENANT"))),
+                   items[7]);
+        assert_eq!(FormatItem::VariableItem(Variable::RecordEnv(String::from("TENANT"))),
+                   items[9]);
+        let mut oinfo = OriginatorInfo::new(1391, "coalyprocess", "coalyhost", "1.2.3.4");
+        oinfo.add_env_var("DEPLOYMENT_ID", "d17");
+        oinfo.add_env_var("REGION", "eu");
+        let opt_fmt = adj_fmt.optimized_for_originator(&oinfo, &FileNameRules::new('_', 64));
+        let tenant = FormatItem::VariableItem(Variable::RecordEnv(String::from("TENANT")));
+        assert_eq!(&vec!(FormatItem::VariableItem(Variable::Message),
+                         FormatItem::ConstantItem(String::from("|DEPLOYMENT_ID=d17 REGION=eu TENANT=")),
+                         tenant.clone(),
+                         FormatItem::ConstantItem(String::from("|")),
+                         tenant,
+                         FormatItem::ConstantItem(String::from("|eu"))),
+                   opt_fmt.items());
+    }
+
+    #[test]
+    fn test_record_env_cache() {
+        std::env::set_var("COALY_ENV_CACHE_TEST", "v1");
+        crate::variables::set_record_env_ttl(3600000);
+        assert_eq!(Some(String::from("v1")), record_env_value("COALY_ENV_CACHE_TEST"));
+        std::env::set_var("COALY_ENV_CACHE_TEST", "v2");
+        assert_eq!(Some(String::from("v1")), record_env_value("COALY_ENV_CACHE_TEST"));
+        crate::variables::set_record_env_ttl(0);
+        assert_eq!(Some(String::from("v2")), record_env_value("COALY_ENV_CACHE_TEST"));
+        std::env::remove_var("COALY_ENV_CACHE_TEST");
+        assert_eq!(None, record_env_value("COALY_ENV_CACHE_TEST"));
+    }
+
     #[test]
     fn test_optimize_for_thread() {
         // empty spec
@@ -666,4 +1017,110 @@ mod tests {
         check_thread_optimization(&["$Time", "$LevelId", "$Env[COALYTEST]", "$Message"],
                                   &["$Time", "$LevelId", "$Env[COALYTEST]", "$Message"]);
     }
+
+    #[test]
+    fn test_scope_stack() {
+        let fmt = FormatSpec::from_str("$ScopeStack|$ScopeStack[ / ]|$ScopeStack[::,2]|").unwrap();
+        assert_eq!(&vec!(FormatItem::VariableItem(Variable::ScopeStack(String::from(">"), 0)),
+                         FormatItem::ConstantItem(String::from("|")),
+                         FormatItem::VariableItem(Variable::ScopeStack(String::from(" / "), 0)),
+                         FormatItem::ConstantItem(String::from("|")),
+                         FormatItem::VariableItem(Variable::ScopeStack(String::from("::"), 2)),
+                         FormatItem::ConstantItem(String::from("|"))),
+                   fmt.items());
+        let levels = RecordLevelMap::default();
+        let mut rec = LocalRecordData::for_write(7, "main", RecordLevelId::Info, "src/main.rs",
+                                                 42, "payload ok");
+        assert_eq!(format!("|||{}", EOL), fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+        let scopes = vec!(String::from("main"), String::from("handle_request"),
+                          String::from("parse_payload"));
+        rec.set_scope_stack(Arc::new(scopes));
+        assert_eq!(format!("main>handle_request>parse_payload|main / handle_request / \
+                            parse_payload|handle_request::parse_payload|{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+    }
+
+    #[test]
+    fn test_thread_write_counts() {
+        let fmt = FormatSpec::from_str("$ThreadRecordsWritten/$ThreadRecordsSuppressed $Message")
+                  .unwrap();
+        assert_eq!(&vec!(FormatItem::VariableItem(Variable::ThreadRecordsWritten),
+                         FormatItem::ConstantItem(String::from("/")),
+                         FormatItem::VariableItem(Variable::ThreadRecordsSuppressed),
+                         FormatItem::ConstantItem(String::from(" ")),
+                         FormatItem::VariableItem(Variable::Message)),
+                   fmt.items());
+        let levels = RecordLevelMap::default();
+        let mut rec = LocalRecordData::for_write(7, "main", RecordLevelId::Info, "src/main.rs",
+                                                 42, "payload ok");
+        assert_eq!(format!("0/0 payload ok{}", EOL), fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+        rec.set_thread_write_counts(12, 345);
+        assert_eq!(format!("12/345 payload ok{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+    }
+
+    #[test]
+    fn test_observer_attrs() {
+        let fmt = FormatSpec::from_str("[$Attr[request_id]] $Message|$Attrs").unwrap();
+        assert_eq!(&vec!(FormatItem::ConstantItem(String::from("[")),
+                         FormatItem::VariableItem(Variable::Attr(String::from("request_id"))),
+                         FormatItem::ConstantItem(String::from("] ")),
+                         FormatItem::VariableItem(Variable::Message),
+                         FormatItem::ConstantItem(String::from("|")),
+                         FormatItem::VariableItem(Variable::Attrs)),
+                   fmt.items());
+        let levels = RecordLevelMap::default();
+        let rec = LocalRecordData::for_write(7, "main", RecordLevelId::Info, "src/main.rs",
+                                             42, "payload ok");
+        assert_eq!(format!("[] payload ok|{}", EOL), fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+        crate::observer::set_attr(1, "request_id", 4711.into());
+        crate::observer::set_attr(1, "user", "jdoe".into());
+        let rec = LocalRecordData::for_write(7, "main", RecordLevelId::Info, "src/main.rs",
+                                             42, "payload ok");
+        crate::observer::remove_attrs(1);
+        assert_eq!(format!("[4711] payload ok|request_id=4711 user=jdoe{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+    }
+
+    #[test]
+    fn test_file_name_rules() {
+        let rules = FileNameRules::new('-', 8);
+        assert_eq!("worker-1", rules.sanitized("worker/1"));
+        assert_eq!("abcdefgh", rules.sanitized("abcdefghijk"));
+        assert_eq!("", rules.sanitized(""));
+        let fmt = build_format_spec(&["app_", "$ThreadName", ".log"]);
+        let opt_spec = fmt.optimized_for_thread(1234, "pool/worker/17", None, &rules);
+        verify_format_spec(opt_spec.items().as_slice(), &["app_pool-wor.log"]);
+    }
+
+    #[test]
+    fn test_context_file_name() {
+        let fmt = FormatSpec::from_str("app_$Context[tenant]_$Context[user].log").unwrap();
+        assert!(fmt.is_thread_specific());
+        assert!(! fmt.is_originator_specific());
+        let mut context = ContextMap::new();
+        context.insert(String::from("tenant"), String::from("acme/eu"));
+        let opt_spec = fmt.optimized_for_thread(1234, "main", Some(&context),
+                                                &FileNameRules::new('-', 64));
+        verify_format_spec(opt_spec.items().as_slice(), &["app_acme-eu_.log"]);
+        let opt_spec = fmt.optimized_for_thread(1234, "main", None, &FileNameRules::new('-', 64));
+        verify_format_spec(opt_spec.items().as_slice(), &["app__.log"]);
+    }
+
+    #[test]
+    fn test_time_slice() {
+        let fmt = build_format_spec(&["app_", "$TimeSlice", ".log"]).with_time_slice(15);
+        let now = Local.with_ymd_and_hms(2024, 3, 7, 14, 22, 41).unwrap();
+        assert_eq!("app_202403071415.log", fmt.to_file_name_at(&now));
+        let end = Local.with_ymd_and_hms(2024, 3, 7, 14, 30, 0).unwrap();
+        assert_eq!(Some(end), fmt.time_slice_end(&now));
+        let now = Local.with_ymd_and_hms(2024, 3, 7, 23, 59, 59).unwrap();
+        let fmt = build_format_spec(&["app_", "$TimeSlice", ".log"]);
+        assert_eq!("app_202403072300.log", fmt.to_file_name_at(&now));
+        let pattern = fmt.file_name_pattern("");
+        assert!(pattern.is_match("app_202403072300.log"));
+        assert!(!pattern.is_match("app_2024030723.log"));
+        let fmt = build_format_spec(&["app_", "$Date", ".log"]);
+        assert!(fmt.time_slice_end(&now).is_none());
+    }
 }
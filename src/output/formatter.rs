@@ -0,0 +1,139 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+//! Application defined record formatters.
+//! Applications register formatters for bespoke layouts, e.g. CEF or LEEF, under a name before
+//! Coaly is initialized. A resource uses a registered formatter, if its parameter output_format
+//! or one of its level_formats references the formatter's name and the configuration doesn't
+//! define an output format with that name.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
+use crate::record::recorddata::RecordData;
+
+/// Trait for application defined record formatters.
+pub trait RecordFormatter: Send + Sync {
+    /// Converts the specified log or trace record to a string.
+    ///
+    /// # Arguments
+    /// * `record` - the record data
+    ///
+    /// # Return values
+    /// the formatted record without line terminator, the terminator is appended by Coaly
+    fn format(&self, record: &dyn RecordData) -> String;
+}
+
+lazy_static! {
+    /// All record formatters registered by the application, keyed by formatter name
+    static ref FORMATTERS: RwLock<HashMap<String, Arc<dyn RecordFormatter>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Registers a record formatter under the given name.
+/// A formatter registered before under the same name is replaced.
+///
+/// # Arguments
+/// * `name` - the formatter name, referenced in the configuration
+/// * `formatter` - the formatter
+pub fn register_formatter(name: &str, formatter: Arc<dyn RecordFormatter>) {
+    if let Ok(mut formatters) = FORMATTERS.write() {
+        formatters.insert(name.to_string(), formatter);
+    }
+}
+
+/// Returns the record formatter registered under the given name.
+///
+/// # Arguments
+/// * `name` - the formatter name
+///
+/// # Return values
+/// the formatter, **None** if no formatter is registered under the name
+pub(crate) fn registered_formatter(name: &str) -> Option<CustomFormatter> {
+    let formatters = FORMATTERS.read().ok()?;
+    formatters.get(name).map(|f| CustomFormatter { name: name.to_string(),
+                                                  formatter: f.clone() })
+}
+
+/// Record formatter registered by the application, used by an output format.
+#[derive(Clone)]
+pub(crate) struct CustomFormatter {
+    // name the formatter was registered under
+    name: String,
+    // the formatter
+    formatter: Arc<dyn RecordFormatter>
+}
+impl CustomFormatter {
+    /// Converts the specified log or trace record to a string, including line terminator.
+    ///
+    /// # Arguments
+    /// * `record` - the record data
+    pub(crate) fn apply_to(&self, record: &dyn RecordData) -> String {
+        let mut msg = self.formatter.format(record);
+        msg.push_str(super::formatspec::EOL);
+        msg
+    }
+}
+impl Debug for CustomFormatter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CF:{}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::RecordLevelId;
+    use crate::record::recorddata::LocalRecordData;
+
+    struct CefFormatter;
+    impl RecordFormatter for CefFormatter {
+        fn format(&self, record: &dyn RecordData) -> String {
+            format!("CEF:0|Coaly|{}|{}", record.level() as u32,
+                    record.message().as_deref().unwrap_or(""))
+        }
+    }
+
+    #[test]
+    fn test_custom_formatter() {
+        assert!(registered_formatter("ut_cef").is_none());
+        register_formatter("ut_cef", Arc::new(CefFormatter));
+        let f = registered_formatter("ut_cef").unwrap();
+        assert_eq!("CF:ut_cef", format!("{:?}", f));
+        let rec = LocalRecordData::for_write(1, "main", RecordLevelId::Error, "formatter.rs", 1,
+                                             "disk full");
+        assert_eq!(format!("CEF:0|Coaly|{}|disk full{}", RecordLevelId::Error as u32,
+                           super::super::formatspec::EOL),
+                   f.apply_to(&rec));
+    }
+}
@@ -33,7 +33,12 @@
 //! Resource inventory for handling of all output resources.
 
 use chrono::{DateTime, Local};
+use std::path::Path;
+use std::time::Instant;
+use crate::errorhandling::CoalyException;
+use crate::context::ContextMap;
 use super::Interface;
+use super::forecast::RolloverForecast;
 
 #[cfg(feature="net")]
 use std::net::SocketAddr;
@@ -48,8 +53,13 @@ use crate::record::originator::OriginatorInfo;
 pub(crate) trait Inventory {
 
     /// Closes the inventory.
-    /// Flushes all buffer configured for flush on exit.
-    fn close(&mut self);
+    /// Flushes all buffer configured for flush on exit, resources with higher flush priority
+    /// first.
+    /// 
+    /// # Arguments
+    /// * `shutdown_deadline` - the point in time when the shutdown must be completed,
+    ///   **None** if unlimited
+    fn close(&mut self, shutdown_deadline: Option<Instant>);
 
     /// Performs a rollover for file based resources if rollover is due.
     /// 
@@ -57,15 +67,44 @@ pub(crate) trait Inventory {
     /// * `now` - current timestamp
     fn rollover_if_due(&mut self, now: &DateTime<Local>);
 
+    /// Handles a flush or rollover request received by signal.
+    /// Flushes the memory buffers of all resources with flush condition signal and performs
+    /// a rollover of all file based resources, if requested.
+    ///
+    /// # Arguments
+    /// * `rollover` - indicates whether a file rollover was requested
+    fn handle_signal(&mut self, rollover: bool);
+
+    /// Returns the forecasts for the next rollover of all file based resources.
+    fn rollover_forecast(&self) -> Vec<RolloverForecast>;
+
+    /// Opens the file handles and network sockets of all resources anew.
+    ///
+    /// # Return values
+    /// the errors that occurred, empty if all resources could be opened
+    fn reopen_resources(&mut self) -> Vec<CoalyException>;
+
+    /// Changes the output directory for all files created from now on.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    fn change_output_dir(&mut self, dir: &Path);
+
+    /// Flushes and closes all final thread-specific resources, that are no longer used by any
+    /// thread's output interface.
+    fn release_thread_resources(&mut self);
+
     /// Creates and returns the output interface for a local thread.
     /// The caller must make sure that resources for the thread have not been allocated yet.
     /// 
     /// # Arguments
     /// * `thread_id` - the thread's ID
     /// * `thread_name` - the thread's name
+    /// * `context` - the thread's context map, used for thread specific file names
     fn local_thread_interface(&mut self,
                               thread_id: u64,
-                              thread_name: &str) -> Interface;
+                              thread_name: &str,
+                              context: Option<&ContextMap>) -> Interface;
 
     /// Creates and returns the output interface for a remote thread.
     /// The caller must make sure that resources for the thread have not been allocated yet.
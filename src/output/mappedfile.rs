@@ -0,0 +1,440 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+//! Reader for memory mapped output files.
+//! Records are read in chronological order from the frames stored in the file.
+//! Frames torn by an application or system crash are detected by a missing commit marker or
+//! a checksum mismatch and skipped.
+//! Files written with timestamp domain ticks start with a calibration header, the tick values
+//! in the records are converted to wall clock time when the file is read.
+
+#[cfg(feature="mmfile")]
+use chrono::{Local, Offset};
+use chrono::{DateTime, FixedOffset, TimeZone};
+#[cfg(feature="mmfile")]
+use crate::datetime::tick_base;
+use std::path::Path;
+use crate::coalyxe;
+use crate::errorhandling::*;
+use crate::util::crc32;
+use super::recordbuffer::{FRAME_COMMIT, FRAME_HEADER_LEN, FRAME_START, MAPPED_ADMIN_DATA_LEN};
+
+/// Records read from a memory mapped output file.
+#[derive(Debug)]
+pub struct MappedFileRecords {
+    // all intact records, oldest first
+    records: Vec<String>,
+    // number of torn or otherwise corrupted areas skipped
+    torn_count: usize
+}
+impl MappedFileRecords {
+    /// Returns all intact records, oldest first.
+    #[inline]
+    pub fn records(&self) -> &Vec<String> { &self.records }
+
+    /// Returns the number of torn or otherwise corrupted areas, that have been skipped.
+    #[inline]
+    pub fn torn_count(&self) -> usize { self.torn_count }
+}
+
+/// Reads all intact records from a memory mapped output file.
+/// 
+/// # Arguments
+/// * `file_path` - the full path of the memory mapped file
+/// 
+/// # Return values
+/// the records read
+/// 
+/// # Errors
+/// Returns an error structure if the file could not be read
+pub fn read_mapped_file(file_path: &Path) -> Result<MappedFileRecords, CoalyException> {
+    match std::fs::read(file_path) {
+        Ok(data) => Ok(mapped_records(&data, &|r| Some(r.to_string()))),
+        Err(e) => Err(coalyxe!(E_FILE_READ_ERR, file_path.to_string_lossy().to_string(),
+                               e.to_string()))
+    }
+}
+
+/// Extracts all intact records from the data of a memory mapped output file.
+/// 
+/// # Arguments
+/// * `data` - the file data, starting with the optional calibration header
+/// * `decode` - the function to apply to every record read, returning **None** for
+///   corrupted records
+/// 
+/// # Return values
+/// the records read
+pub(crate) fn mapped_records(data: &[u8],
+                             decode: &dyn Fn(&str) -> Option<String>) -> MappedFileRecords {
+    let (calibration, data) = split_calibration_header(data);
+    let recs = parse_frames(&logical_content(data));
+    let mut records = Vec::<String>::with_capacity(recs.records.len());
+    let mut torn_count = recs.torn_count;
+    for rec in recs.records.iter() {
+        match (decode(rec), calibration.as_ref()) {
+            (Some(r), Some(base)) => records.push(resolve_ticks(&r, base)),
+            (Some(r), None) => records.push(r),
+            (None, _) => torn_count += 1
+        }
+    }
+    MappedFileRecords { records, torn_count }
+}
+
+/// Extracts the raw records retained in a memory mapped file operated in ring mode, so they
+/// can be carried over when the file is mapped anew.
+/// Records are only retained, if the file starts with the same header as the one used for the
+/// new mapping, since calibration or encryption data differing from the new header would render
+/// them unreadable.
+/// 
+/// # Arguments
+/// * `data` - the file data, starting with the optional header
+/// * `header` - the header of the new mapping
+/// 
+/// # Return values
+/// the intact raw records, oldest first
+#[cfg(feature="mmfile")]
+pub(crate) fn retained_records(data: &[u8], header: &[u8]) -> Vec<String> {
+    if data.len() <= header.len() + MAPPED_ADMIN_DATA_LEN || ! data.starts_with(header) {
+        return Vec::new()
+    }
+    if header.is_empty() && data.starts_with(CALIBRATION_MAGIC.as_bytes()) { return Vec::new() }
+    #[cfg(feature="encryption")]
+    if header.is_empty() && data.starts_with(super::encryption::ENCRYPTION_MAGIC) {
+        return Vec::new()
+    }
+    parse_frames(&logical_content(&data[header.len()..])).records
+}
+
+/// Calibration of the tick timestamps written to a memory mapped file.
+/// Records hold the monotonic clock reading taken upon their creation, i.e. the nanoseconds
+/// since the tick base. The wall clock time of the tick base is stored once in the file header.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TickCalibration {
+    // wall clock time corresponding to tick value 0
+    #[cfg(feature="mmfile")]
+    base: DateTime<Local>
+}
+impl TickCalibration {
+    /// Creates a calibration for the tick base of the process.
+    #[cfg(feature="mmfile")]
+    pub(crate) fn new() -> TickCalibration {
+        let (secs, nanos) = tick_base();
+        TickCalibration { base: Local.timestamp_opt(secs, nanos).unwrap() }
+    }
+
+    /// Returns the tick token for a timestamp, date or time item in a record.
+    ///
+    /// # Arguments
+    /// * `item` - the item kind, one of the TICK_ITEM_xxx constants
+    /// * `ticks` - the monotonic clock reading of the record
+    pub(crate) fn token(&self, item: &str, ticks: u64) -> String {
+        format!("{}{}{:x}{}", TICK_MARKER, item, ticks, TICK_MARKER)
+    }
+
+    /// Returns the file header with the calibration data.
+    /// The header consists of a magic value followed by the seconds and nanoseconds of the base
+    /// timestamp since Unix epoch and the UTC offset in seconds, all as hexadecimal numbers.
+    #[cfg(feature="mmfile")]
+    pub(crate) fn header(&self) -> Vec<u8> {
+        format!("{}{:016x}{:08x}{:08x}", CALIBRATION_MAGIC, self.base.timestamp(),
+                self.base.timestamp_subsec_nanos(),
+                self.base.offset().fix().local_minus_utc()).into_bytes()
+    }
+}
+
+/// Splits the calibration header from the data of a memory mapped file.
+///
+/// # Arguments
+/// * `data` - the file data
+///
+/// # Return values
+/// the calibration base timestamp, if the file has a valid calibration header, and the
+/// remaining file data
+fn split_calibration_header(data: &[u8]) -> (Option<DateTime<FixedOffset>>, &[u8]) {
+    if data.len() < CALIBRATION_HEADER_LEN || ! data.starts_with(CALIBRATION_MAGIC.as_bytes()) {
+        return (None, data)
+    }
+    let magic_len = CALIBRATION_MAGIC.len();
+    let secs = hex_value64(&data[magic_len..magic_len + 16]).map(|v| v as i64);
+    let nanos = hex_value(&data[magic_len + 16..magic_len + 24]);
+    let offset = hex_value(&data[magic_len + 24..CALIBRATION_HEADER_LEN])
+                     .and_then(|v| FixedOffset::east_opt(v as i32));
+    let base = match (secs, nanos, offset) {
+        (Some(s), Some(n), Some(o)) => o.timestamp_opt(s, n).single(),
+        _ => None
+    };
+    (base, &data[CALIBRATION_HEADER_LEN..])
+}
+
+/// Replaces all tick tokens in a record with the corresponding wall clock values.
+///
+/// # Arguments
+/// * `rec` - the record read from the file
+/// * `base` - the calibration base timestamp
+fn resolve_ticks(rec: &str, base: &DateTime<FixedOffset>) -> String {
+    let mut result = String::with_capacity(rec.len() + 32);
+    let mut rest = rec;
+    while let Some(start) = rest.find(TICK_MARKER) {
+        let token = &rest[start + 1..];
+        let end = match token.find(TICK_MARKER) {
+            Some(end) if end > 1 => end,
+            _ => break
+        };
+        result.push_str(&rest[..start]);
+        let (item, value) = token[..end].split_at(1);
+        let ticks = i64::from_str_radix(value, 16);
+        let fmt = match item {
+            TICK_ITEM_DATE => "%Y-%m-%d",
+            TICK_ITEM_TIME => "%H:%M:%S%.6f",
+            _ => "%Y-%m-%d %H:%M:%S%.6f"
+        };
+        match ticks {
+            Ok(t) => {
+                let ts = *base + chrono::Duration::nanoseconds(t);
+                result.push_str(&ts.format(fmt).to_string());
+            },
+            Err(_) => result.push_str(&rest[start..start + end + 2])
+        }
+        rest = &token[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Returns the content of a memory mapped file in chronological order.
+/// Uses the administrative data at the end of the file to determine the positions of oldest
+/// record and first free byte. If the administrative data is not available or invalid,
+/// the entire content area is returned.
+/// 
+/// # Arguments
+/// * `data` - the file data
+fn logical_content(data: &[u8]) -> Vec<u8> {
+    if data.len() < MAPPED_ADMIN_DATA_LEN { return data.to_vec() }
+    let content_size = data.len() - MAPPED_ADMIN_DATA_LEN;
+    let admin_data = &data[content_size..];
+    let extra_bytes = admin_data[3].wrapping_sub(b'0') as usize;
+    let oldest = offset_value(&admin_data[4..18]);
+    let ins = offset_value(&admin_data[18..32]);
+    match (oldest, ins) {
+        (Some(oldest), Some(ins)) if extra_bytes <= 3 && oldest <= content_size
+                                     && ins <= content_size => {
+            if ins > oldest { return data[oldest..ins].to_vec() }
+            let mut content = data[oldest..content_size + extra_bytes].to_vec();
+            content.extend_from_slice(&data[..ins]);
+            content
+        },
+        _ => data[..content_size].to_vec()
+    }
+}
+
+/// Extracts all intact records from the given memory mapped file content.
+/// 
+/// # Arguments
+/// * `content` - the file content in chronological order
+fn parse_frames(content: &[u8]) -> MappedFileRecords {
+    let mut records = Vec::<String>::new();
+    let mut torn_count = 0;
+    let mut skipping = false;
+    let mut pos = 0;
+    while pos < content.len() {
+        if content[pos] == FRAME_START {
+            if let Some((rec, frame_len)) = parse_frame(&content[pos..]) {
+                if skipping {
+                    torn_count += 1;
+                    skipping = false;
+                }
+                records.push(rec);
+                pos += frame_len;
+                continue
+            }
+        }
+        // zero bytes belong to unused parts of the file
+        if content[pos] != 0 { skipping = true; }
+        pos += 1;
+    }
+    if skipping { torn_count += 1; }
+    MappedFileRecords { records, torn_count }
+}
+
+/// Parses a single frame.
+/// 
+/// # Arguments
+/// * `data` - the data, starting with the frame start marker
+/// 
+/// # Return values
+/// the record and the length of the frame; **None** if the frame is torn or corrupted
+fn parse_frame(data: &[u8]) -> Option<(String, usize)> {
+    if data.len() <= FRAME_HEADER_LEN { return None }
+    let rec_len = hex_value(&data[1..9])? as usize;
+    let checksum = hex_value(&data[9..FRAME_HEADER_LEN])?;
+    let commit_pos = FRAME_HEADER_LEN + rec_len;
+    if commit_pos >= data.len() || data[commit_pos] != FRAME_COMMIT { return None }
+    let payload = &data[FRAME_HEADER_LEN..commit_pos];
+    if crc32(payload) != checksum { return None }
+    let rec = String::from_utf8(payload.to_vec()).ok()?;
+    Some((rec, commit_pos + 1))
+}
+
+/// Returns the value of a hexadecimal number in the given bytes.
+fn hex_value(data: &[u8]) -> Option<u32> {
+    if ! data.iter().all(u8::is_ascii_hexdigit) { return None }
+    u32::from_str_radix(std::str::from_utf8(data).ok()?, 16).ok()
+}
+
+/// Returns the value of a 64 bit hexadecimal number in the given bytes.
+fn hex_value64(data: &[u8]) -> Option<u64> {
+    if ! data.iter().all(u8::is_ascii_hexdigit) { return None }
+    u64::from_str_radix(std::str::from_utf8(data).ok()?, 16).ok()
+}
+
+/// Returns the value of a decimal offset in the given bytes.
+fn offset_value(data: &[u8]) -> Option<usize> {
+    if ! data.iter().all(u8::is_ascii_digit) { return None }
+    std::str::from_utf8(data).ok()?.parse::<usize>().ok()
+}
+
+/// Magic value at the beginning of a calibration header
+const CALIBRATION_MAGIC: &str = "\x01CAL";
+
+/// Length of a calibration header in bytes
+const CALIBRATION_HEADER_LEN: usize = 36;
+
+/// Marker enclosing tick tokens in records
+const TICK_MARKER: char = '\x01';
+
+/// Item kinds in tick tokens
+pub(crate) const TICK_ITEM_TIMESTAMP: &str = "T";
+pub(crate) const TICK_ITEM_DATE: &str = "D";
+pub(crate) const TICK_ITEM_TIME: &str = "H";
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature="mmfile")]
+    use std::path::Path;
+    #[cfg(feature="mmfile")]
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use super::*;
+    use crate::output::recordbuffer::framed_rec;
+    #[cfg(feature="mmfile")]
+    use crate::output::recordbuffer::RecordBuffer;
+
+    const REC_1: &str = "first record\n";
+    const REC_2: &str = "second record œ\n";
+    const REC_3: &str = "third record\n";
+
+    #[test]
+    /// Tests checksum calculation
+    fn test_crc32() {
+        assert_eq!(0, crc32(b""));
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+    }
+
+    #[test]
+    /// Tests detection of torn and corrupted frames
+    fn test_parse_frames() {
+        let f1 = framed_rec(REC_1, 1024);
+        let f2 = framed_rec(REC_2, 1024);
+        let f3 = framed_rec(REC_3, 1024);
+        // intact frames
+        let content = format!("{}{}{}", f1, f2, f3);
+        let recs = parse_frames(content.as_bytes());
+        assert_eq!(&vec![REC_1, REC_2, REC_3], recs.records());
+        assert_eq!(0, recs.torn_count());
+        // torn frame in the middle
+        let content = format!("{}{}{}", f1, &f2[..f2.len() - 5], f3);
+        let recs = parse_frames(content.as_bytes());
+        assert_eq!(&vec![REC_1, REC_3], recs.records());
+        assert_eq!(1, recs.torn_count());
+        // corrupted record data
+        let content = format!("{}{}{}", f1, f2.replace("second", "secund"), f3);
+        let recs = parse_frames(content.as_bytes());
+        assert_eq!(&vec![REC_1, REC_3], recs.records());
+        assert_eq!(1, recs.torn_count());
+        // torn last frame followed by unused space
+        let mut content = format!("{}{}", f1, &f2[..f2.len() - 1]).into_bytes();
+        content.extend_from_slice(&[0u8; 16]);
+        let recs = parse_frames(&content);
+        assert_eq!(&vec![REC_1], recs.records());
+        assert_eq!(1, recs.torn_count());
+        // truncated record
+        let recs = parse_frames(framed_rec(REC_2, 15).as_bytes());
+        assert_eq!(&vec!["second record "], recs.records());
+    }
+
+    #[cfg(feature="mmfile")]
+    #[test]
+    /// Tests reading records from a memory mapped file
+    fn test_read_mapped_file() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let rw_dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap()).join("tmp");
+        let file_name = rw_dir.join(format!("mmread{}.bin", now));
+        let mut buf = RecordBuffer::backed_by_file(&file_name, 4096, 100, &[]).unwrap();
+        for i in 0..200 { buf.write(&format!("record {:03}\n", i)); }
+        drop(buf);
+        let recs = read_mapped_file(&file_name).unwrap();
+        assert_eq!(0, recs.torn_count());
+        assert!(! recs.records().is_empty());
+        let first_nr = 200 - recs.records().len();
+        for (i, rec) in recs.records().iter().enumerate() {
+            assert_eq!(&format!("record {:03}\n", first_nr + i), rec);
+        }
+        let _ = std::fs::remove_file(&file_name);
+        assert!(read_mapped_file(&file_name).is_err());
+    }
+
+    #[cfg(feature="mmfile")]
+    #[test]
+    /// Tests conversion of tick timestamps when reading a memory mapped file
+    fn test_read_mapped_file_with_ticks() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let rw_dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap()).join("tmp");
+        let file_name = rw_dir.join(format!("mmticks{}.bin", now));
+        let calibration = TickCalibration::new();
+        let ts = calibration.base + chrono::Duration::microseconds(1_500_250);
+        let later = calibration.base + chrono::Duration::seconds(7200);
+        let mut buf = RecordBuffer::backed_by_file(&file_name, 4096, 100,
+                                                   &calibration.header()).unwrap();
+        buf.write(&format!("{}|{}|rec\n", calibration.token(TICK_ITEM_DATE, 1_500_250_000),
+                           calibration.token(TICK_ITEM_TIME, 1_500_250_000)));
+        buf.write(&format!("{} rec\n",
+                           calibration.token(TICK_ITEM_TIMESTAMP, 7_200_000_000_000)));
+        buf.write("plain \x01Tzz\x01 rec\n");
+        drop(buf);
+        let recs = read_mapped_file(&file_name).unwrap();
+        assert_eq!(0, recs.torn_count());
+        assert_eq!(&vec![format!("{}|rec\n", ts.format("%Y-%m-%d|%H:%M:%S%.6f")),
+                         format!("{} rec\n", later.format("%Y-%m-%d %H:%M:%S%.6f")),
+                         String::from("plain \x01Tzz\x01 rec\n")],
+                   recs.records());
+        let _ = std::fs::remove_file(&file_name);
+    }
+}
@@ -32,15 +32,29 @@
 
 //! Top level module for output handling.
 
+#[cfg(feature="net")]
+pub mod binaryfile;
+pub(crate) mod capture;
+#[cfg(feature="encryption")]
+pub mod encryption;
+#[cfg(feature="faultinjection")]
+pub mod faultinjection;
+pub mod forecast;
 mod formatspec;
+pub mod formatter;
 pub mod inventory;
+pub mod mappedfile;
+mod namepattern;
+pub mod probe;
 mod outputformat;
-mod recordbuffer;
+pub(crate) mod recordbuffer;
 mod recordformat;
 pub(crate) mod resource;
 pub mod standaloneinventory;
 #[cfg(feature="net")]
 pub mod serverinventory;
+#[cfg(feature="net")]
+pub(crate) mod storage;
 
 use crate::errorhandling::CoalyException;
 use crate::record::recorddata::RecordData;
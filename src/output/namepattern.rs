@@ -0,0 +1,227 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Patterns matching the names of output files created from a file name specification.
+//! A pattern consists of constant texts, digit sequences of fixed length for date and time
+//! related variables and arbitrary texts for other variables, followed by an optional rollover
+//! sequence number and an optional file extension.
+
+/// Single segment of a file name pattern.
+#[derive(Clone, Debug)]
+pub(crate) enum PatternSegment {
+    // constant text
+    Text(String),
+    // sequence of decimal digits with given length
+    Digits(usize),
+    // arbitrary non-empty text
+    Any
+}
+
+/// Parts of a file name matching a pattern.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FileNameMatch<'a> {
+    // the part matching the pattern segments
+    pub(crate) stem: &'a str,
+    // the rollover sequence number, without leading dot
+    pub(crate) seq_nr: Option<&'a str>,
+    // the file extension, including leading dot
+    pub(crate) ext: Option<&'a str>
+}
+
+/// Pattern to find the files belonging to an output resource.
+#[derive(Clone, Debug)]
+pub(crate) struct FileNamePattern {
+    // the segments derived from the file name specification
+    segments: Vec<PatternSegment>,
+    // the only file extension accepted, None if any alphanumeric extension is accepted
+    ext: Option<String>
+}
+impl FileNamePattern {
+    /// Creates a pattern accepting the given extension after the optional sequence number.
+    ///
+    /// # Arguments
+    /// * `segments` - the pattern segments
+    /// * `ext` - the accepted extension including leading dot, empty string for no extension
+    pub(crate) fn with_ext(segments: Vec<PatternSegment>, ext: &str) -> FileNamePattern {
+        FileNamePattern { segments, ext: Some(ext.to_string()) }
+    }
+
+    /// Creates a pattern accepting any alphanumeric extension after the optional sequence
+    /// number.
+    ///
+    /// # Arguments
+    /// * `segments` - the pattern segments
+    pub(crate) fn with_any_ext(segments: Vec<PatternSegment>) -> FileNamePattern {
+        FileNamePattern { segments, ext: None }
+    }
+
+    /// Indicates whether the given file name matches this pattern.
+    ///
+    /// # Arguments
+    /// * `file_name` - the pure file name without path
+    #[inline]
+    pub(crate) fn is_match(&self, file_name: &str) -> bool { self.captures(file_name).is_some() }
+
+    /// Matches the given file name against this pattern.
+    ///
+    /// # Arguments
+    /// * `file_name` - the pure file name without path
+    ///
+    /// # Return values
+    /// the parts of the file name; **None** if the name doesn't match
+    pub(crate) fn captures<'a>(&self, file_name: &'a str) -> Option<FileNameMatch<'a>> {
+        let mut result = None;
+        match_segments(&self.segments, file_name, 0, &mut |stem_end| {
+            result = self.suffix(&file_name[stem_end ..]).map(|(seq_nr, ext)| {
+                FileNameMatch { stem: &file_name[.. stem_end], seq_nr, ext }
+            });
+            result.is_some()
+        });
+        result
+    }
+
+    /// Splits the part of a file name following the pattern segments into sequence number
+    /// and extension.
+    ///
+    /// # Arguments
+    /// * `suffix` - the part of the file name following the pattern segments
+    ///
+    /// # Return values
+    /// sequence number without leading dot and extension including leading dot;
+    /// **None** if the suffix is not accepted
+    fn suffix<'a>(&self, suffix: &'a str) -> Option<(Option<&'a str>, Option<&'a str>)> {
+        if suffix.is_empty() { return Some((None, None)) }
+        if let Some(digits_and_ext) = suffix.strip_prefix('.') {
+            let digit_count = digits_and_ext.bytes().take_while(u8::is_ascii_digit).count();
+            if digit_count > 0 {
+                let (seq_nr, ext) = digits_and_ext.split_at(digit_count);
+                if ext.is_empty() { return Some((Some(seq_nr), None)) }
+                if self.accepts_ext(ext) { return Some((Some(seq_nr), Some(ext))) }
+            }
+        }
+        if self.accepts_ext(suffix) { return Some((None, Some(suffix))) }
+        None
+    }
+
+    /// Indicates whether the given text is an accepted file extension.
+    ///
+    /// # Arguments
+    /// * `ext` - the text, including leading dot
+    fn accepts_ext(&self, ext: &str) -> bool {
+        match &self.ext {
+            Some(e) => ! e.is_empty() && ext == e,
+            None => ext.strip_prefix('.')
+                       .is_some_and(|e| ! e.is_empty() && e.bytes().all(|b| b.is_ascii_alphanumeric()))
+        }
+    }
+}
+
+/// Matches the given pattern segments against the start of a text.
+/// Arbitrary texts match as many characters as possible first, shorter matches are tried
+/// until the given function accepts the remainder of the text.
+///
+/// # Arguments
+/// * `segments` - the pattern segments
+/// * `text` - the text to match
+/// * `pos` - the position within the text, where the first segment must match
+/// * `accept` - the function deciding whether the text following the segments is accepted
+///
+/// # Return values
+/// **true** if the segments and the remainder of the text were accepted
+fn match_segments(segments: &[PatternSegment],
+                  text: &str,
+                  pos: usize,
+                  accept: &mut dyn FnMut(usize) -> bool) -> bool {
+    let Some((segment, other_segments)) = segments.split_first() else { return accept(pos) };
+    let rest = &text[pos ..];
+    match segment {
+        PatternSegment::Text(t) => {
+            rest.starts_with(t.as_str()) &&
+                match_segments(other_segments, text, pos + t.len(), accept)
+        },
+        PatternSegment::Digits(n) => {
+            rest.len() >= *n && rest.bytes().take(*n).all(|b| b.is_ascii_digit()) &&
+                match_segments(other_segments, text, pos + n, accept)
+        },
+        PatternSegment::Any => {
+            let ends: Vec<usize> = rest.char_indices().skip(1).map(|(i, _)| i)
+                                       .chain(std::iter::once(rest.len()))
+                                       .collect();
+            ends.into_iter().rev().any(|end| match_segments(other_segments, text, pos + end,
+                                                            accept))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Tests patterns for rollover files with date and time related segments
+    fn test_rollover_pattern() {
+        let segs = vec!(PatternSegment::Text(String::from("app_")), PatternSegment::Digits(8),
+                        PatternSegment::Text(String::from(".log")));
+        let pattern = FileNamePattern::with_ext(segs.clone(), ".gz");
+        let m = pattern.captures("app_20240307.log.3.gz").unwrap();
+        assert_eq!(FileNameMatch { stem: "app_20240307.log", seq_nr: Some("3"), ext: Some(".gz") },
+                   m);
+        let m = pattern.captures("app_20240307.log").unwrap();
+        assert_eq!(FileNameMatch { stem: "app_20240307.log", seq_nr: None, ext: None }, m);
+        assert!(pattern.is_match("app_20240307.log.gz"));
+        assert!(pattern.is_match("app_20240307.log.12"));
+        assert!(! pattern.is_match("app_2024030.log"));
+        assert!(! pattern.is_match("app_20240307.log.zip"));
+        assert!(! pattern.is_match("app_20240307.log."));
+        let pattern = FileNamePattern::with_ext(segs, "");
+        assert!(pattern.is_match("app_20240307.log.1"));
+        assert!(! pattern.is_match("app_20240307.log.1.gz"));
+        let pattern = FileNamePattern::with_ext(vec!(PatternSegment::Text(String::from("a"))),
+                                                ".7z");
+        assert_eq!(Some(".7z"), pattern.captures("a.7z").unwrap().ext);
+    }
+
+    #[test]
+    /// Tests patterns with arbitrary texts and extensions, as used for stale files
+    fn test_stale_pattern() {
+        let segs = vec!(PatternSegment::Text(String::from("app_")), PatternSegment::Any,
+                        PatternSegment::Text(String::from(".log")));
+        let pattern = FileNamePattern::with_any_ext(segs);
+        assert!(pattern.is_match("app_17.log"));
+        assert!(pattern.is_match("app_a.log.log"));
+        assert!(pattern.is_match("app_18.log.1.sha256"));
+        assert!(pattern.is_match("app_18.log.gz"));
+        assert!(! pattern.is_match("app_.log"));
+        assert!(! pattern.is_match("app_18.log.1.tar.gz"));
+        assert!(! pattern.is_match("other_18.log"));
+    }
+}
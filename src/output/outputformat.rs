@@ -34,19 +34,28 @@
 
 use crate::config::datetimeformat::DateTimeFormatDescMap;
 use crate::config::output::{OutputFormatDesc, RecordFormatDesc};
+use crate::config::resource::ClockGranularity;
+use crate::config::systemproperties::SystemProperties;
 use crate::record::RecordLevelMap;
 use crate::record::originator::OriginatorInfo;
 use crate::record::recorddata::RecordData;
+use super::formatter::CustomFormatter;
+#[cfg(feature="mmfile")]
+use super::mappedfile::TickCalibration;
 use super::recordformat::RecordFormat;
 
 /// An output format structure defines how log or trace records are formatted for a resource.
 /// An output format consists of a list of record formats, since different formats can be used
 /// depending on the record level and/or the occasion, why the record was triggered.
+/// Additionally, entirely different output formats may be assigned to specific record levels.
 #[derive (Clone, Debug)]
 pub(crate) struct OutputFormat {
     specific_formats: Vec<RecordFormat>,
     default_format: RecordFormat,
-    levels: RecordLevelMap
+    levels: RecordLevelMap,
+    level_formats: Vec<(u32, OutputFormat)>,
+    level_colors: Vec<(u32, u8)>,
+    custom_formatter: Option<CustomFormatter>
 }
 impl OutputFormat {
     /// Creates an output format for a resource.
@@ -54,15 +63,75 @@ impl OutputFormat {
     /// # Arguments
     /// * `desc` - the output format descriptor from the configuration
     /// * `dtm_formats` - the map with all date time formats
+    /// * `sys_props` - the system properties with record levels and record enrichment settings
     pub(crate) fn from_desc(desc: &OutputFormatDesc,
                             dtm_formats: &DateTimeFormatDescMap,
-                            levels: &RecordLevelMap) -> OutputFormat {
+                            sys_props: &SystemProperties) -> OutputFormat {
         let mut specific_formats = Vec::<RecordFormat>::new();
         for sp_desc in desc.specific_formats() {
-            specific_formats.push(RecordFormat::from_desc(sp_desc, dtm_formats));
+            specific_formats.push(RecordFormat::from_desc(sp_desc, dtm_formats, sys_props));
         }
-        let default_format = RecordFormat::from_desc(&RecordFormatDesc::default(), dtm_formats);
-        OutputFormat { specific_formats, default_format, levels: levels.clone() }
+        let default_format = RecordFormat::from_desc(&RecordFormatDesc::default(), dtm_formats,
+                                                     sys_props);
+        OutputFormat { specific_formats, default_format,
+                       levels: sys_props.record_levels().clone(),
+                       level_formats: Vec::new(),
+                       level_colors: Vec::new(),
+                       custom_formatter: None }
+    }
+
+    /// Lets an application defined formatter convert the records instead of the record formats.
+    /// Alternative formats for specific record levels still take precedence.
+    ///
+    /// # Arguments
+    /// * `formatter` - the formatter registered by the application
+    pub(crate) fn set_custom_formatter(&mut self, formatter: CustomFormatter) {
+        self.custom_formatter = Some(formatter);
+    }
+
+    /// Assigns an alternative output format to specific record levels.
+    /// The alternative format is used instead of this format for all records with one of the
+    /// given levels. If several alternative formats apply to a record level, the one added first
+    /// is used.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with the record levels
+    /// * `format` - the output format to use for the record levels
+    pub(crate) fn add_level_format(&mut self, levels: u32, format: OutputFormat) {
+        self.level_formats.push((levels, format));
+    }
+
+    /// Sets the ANSI colors for records written to a console.
+    /// If several colors apply to a record level, the first one is used.
+    ///
+    /// # Arguments
+    /// * `level_colors` - bit masks with record levels and associated ANSI color codes
+    pub(crate) fn set_level_colors(&mut self, level_colors: Vec<(u32, u8)>) {
+        self.level_colors = level_colors;
+    }
+
+    /// Sets the precision of the timestamps written, including all alternative formats for
+    /// specific record levels.
+    ///
+    /// # Arguments
+    /// * `granularity` - the clock granularity
+    pub(crate) fn set_clock_granularity(&mut self, granularity: ClockGranularity) {
+        self.specific_formats.iter_mut().for_each(|sf| sf.set_clock_granularity(granularity));
+        self.default_format.set_clock_granularity(granularity);
+        self.level_formats.iter_mut().for_each(|(_, lf)| lf.set_clock_granularity(granularity));
+    }
+
+    /// Sets the calibration for tick timestamps, including all alternative formats for
+    /// specific record levels.
+    /// Timestamp, date and time items are written as tick tokens relative to the calibration.
+    ///
+    /// # Arguments
+    /// * `calibration` - the calibration for tick timestamps
+    #[cfg(feature="mmfile")]
+    pub(crate) fn set_tick_calibration(&mut self, calibration: TickCalibration) {
+        self.specific_formats.iter_mut().for_each(|sf| sf.set_tick_calibration(calibration));
+        self.default_format.set_tick_calibration(calibration);
+        self.level_formats.iter_mut().for_each(|(_, lf)| lf.set_tick_calibration(calibration));
     }
 
     /// Converts the specified log or trace record to a string according to this format.
@@ -74,7 +143,31 @@ impl OutputFormat {
     /// # Return values
     /// the formatted string, to be written to output resource
     pub(crate) fn apply_to(&self, record: &dyn RecordData) -> String {
+        let msg = self.format(record);
+        match self.level_colors.iter().find(|(lvls, _)| *lvls & record.level() as u32 != 0) {
+            Some((_, code)) => {
+                // reset color before the line terminator
+                let text = msg.trim_end_matches(['\r', '\n']);
+                format!("\x1b[{}m{}\x1b[0m{}", code, text, &msg[text.len()..])
+            },
+            None => msg
+        }
+    }
+
+    /// Converts the specified log or trace record to a string according to this format, without
+    /// colors.
+    ///
+    /// # Arguments
+    /// * `record` - the record data
+    ///
+    /// # Return values
+    /// the formatted string
+    fn format(&self, record: &dyn RecordData) -> String {
         let level = record.level();
+        for (lvls, lf) in self.level_formats.iter() {
+            if *lvls & level as u32 != 0 { return lf.apply_to(record) }
+        }
+        if let Some(cf) = self.custom_formatter.as_ref() { return cf.apply_to(record) }
         let trigger = record.trigger();
         for sf in self.specific_formats.iter() {
             if sf.applies_to(level, trigger) {
@@ -101,6 +194,8 @@ impl OutputFormat {
         // default format doesn't contain process or thread specific items
         self.specific_formats.iter_mut().for_each(|sf| sf.optimize_for(orig_info,
                                                                        thread_id, thread_name));
+        self.level_formats.iter_mut().for_each(|(_, lf)| lf.optimize_for(orig_info,
+                                                                         thread_id, thread_name));
     }
 
     /// Returns a clone optimized for the specified originator thread.
@@ -0,0 +1,277 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Startup self-test of output resources.
+//! Resources with parameter probe are tested when the configuration is applied, file based
+//! resources by writing and verifying a test record in their output directory and checking the
+//! free disk space, network based resources by checking that the peer is reachable.
+
+use chrono::Local;
+use std::path::Path;
+use std::sync::Mutex;
+use crate::coalyxw;
+use crate::errorhandling::*;
+use crate::util::free_disk_space;
+#[cfg(feature="net")]
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(feature="net")]
+use std::time::Duration;
+#[cfg(feature="net")]
+use crate::net::{NetworkProtocol, PeerAddr};
+
+/// Outcome of the startup probe of an output resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeStatus {
+    /// The resource passed all checks
+    Passed,
+    /// At least one check failed
+    Failed,
+    /// No checks are available for the resource kind
+    Skipped
+}
+
+/// Result of the startup probe of an output resource.
+#[derive(Clone, Debug)]
+pub struct ProbeResult {
+    // label identifying the resource, kind followed by file name specification or URL
+    label: String,
+    // outcome of the probe
+    status: ProbeStatus,
+    // bytes available in the output directory, for file based resources only
+    free_space: Option<u64>,
+    // the reason, if the probe failed
+    problem: Option<CoalyException>
+}
+impl ProbeResult {
+    /// Creates the result for a resource that passed the probe.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    /// * `free_space` - the bytes available in the output directory, if applicable
+    pub(crate) fn passed(label: &str, free_space: Option<u64>) -> ProbeResult {
+        ProbeResult { label: label.to_string(), status: ProbeStatus::Passed, free_space,
+                      problem: None }
+    }
+
+    /// Creates the result for a resource that failed the probe.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    /// * `free_space` - the bytes available in the output directory, if applicable
+    /// * `problem` - the reason for the failure
+    pub(crate) fn failed(label: &str,
+                         free_space: Option<u64>,
+                         problem: CoalyException) -> ProbeResult {
+        ProbeResult { label: label.to_string(), status: ProbeStatus::Failed, free_space,
+                      problem: Some(problem) }
+    }
+
+    /// Creates the result for a resource kind without checks.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    pub(crate) fn skipped(label: &str) -> ProbeResult {
+        ProbeResult { label: label.to_string(), status: ProbeStatus::Skipped, free_space: None,
+                      problem: None }
+    }
+
+    /// Returns the label identifying the resource.
+    /// The label consists of the resource kind followed by file name specification, pipe name
+    /// or URL, e.g. `file:app.log`.
+    #[inline]
+    pub fn label(&self) -> &str { &self.label }
+
+    /// Returns the outcome of the probe.
+    #[inline]
+    pub fn status(&self) -> ProbeStatus { self.status }
+
+    /// Indicates whether the resource failed the probe.
+    #[inline]
+    pub fn failed_probe(&self) -> bool { self.status == ProbeStatus::Failed }
+
+    /// Returns the number of bytes available in the output directory.
+    /// Returns **None** for resources other than files or if the file system can't be queried.
+    #[inline]
+    pub fn free_space(&self) -> Option<u64> { self.free_space }
+
+    /// Returns the reason, why the resource failed the probe.
+    #[inline]
+    pub fn problem(&self) -> Option<&CoalyException> { self.problem.as_ref() }
+}
+
+/// Tests the output directory of a file based resource.
+/// A test record is written to a temporary file, read back and compared, then the free disk
+/// space is checked.
+///
+/// # Arguments
+/// * `label` - the label identifying the resource
+/// * `dir` - the output directory, created if it doesn't exist
+/// * `min_free_space` - the number of bytes needed in the output directory
+pub(crate) fn probe_directory(label: &str, dir: &Path, min_free_space: u64) -> ProbeResult {
+    if let Err(ex) = write_test_record(dir) { return ProbeResult::failed(label, None, ex) }
+    let free_space = free_disk_space(dir);
+    if let Some(free) = free_space {
+        if free < min_free_space {
+            let ex = coalyxw!(W_RES_PROBE_LOW_DISK_SPACE, free.to_string(),
+                              dir.to_string_lossy().to_string(), min_free_space.to_string());
+            return ProbeResult::failed(label, free_space, ex)
+        }
+    }
+    ProbeResult::passed(label, free_space)
+}
+
+/// Writes a test record to a temporary file in the given directory and reads it back.
+/// The file is removed afterwards.
+///
+/// # Arguments
+/// * `dir` - the directory, created if it doesn't exist
+///
+/// # Errors
+/// Returns an error structure if the record can't be written, read or differs
+fn write_test_record(dir: &Path) -> Result<(), CoalyException> {
+    let dir_name = dir.to_string_lossy().to_string();
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        return Err(coalyxw!(W_RES_PROBE_WRITE_FAILED, dir_name, e.to_string()))
+    }
+    let path = dir.join(format!("{}{}", PROBE_FILE_PREFIX, std::process::id()));
+    let record = format!("Coaly startup probe {}\n", Local::now().to_rfc3339());
+    let result = std::fs::write(&path, &record).and_then(|_| std::fs::read_to_string(&path));
+    let _ = std::fs::remove_file(&path);
+    match result {
+        Ok(s) if s == record => Ok(()),
+        Ok(_) => Err(coalyxw!(W_RES_PROBE_VERIFY_FAILED, dir_name)),
+        Err(e) => Err(coalyxw!(W_RES_PROBE_WRITE_FAILED, dir_name, e.to_string()))
+    }
+}
+
+/// Tests whether the peer of a network based resource is reachable.
+/// Host names are resolved, stream based peers must accept a connection. Datagram based peers
+/// can't be tested beyond address resolution.
+///
+/// # Arguments
+/// * `label` - the label identifying the resource
+/// * `peer` - the address of the peer
+#[cfg(feature="net")]
+pub(crate) fn probe_peer(label: &str, peer: &PeerAddr) -> ProbeResult {
+    match connect_peer(peer) {
+        Ok(()) => ProbeResult::passed(label, None),
+        Err(cause) => {
+            ProbeResult::failed(label, None, coalyxw!(W_RES_PROBE_UNREACHABLE, peer.to_string(),
+                                                      cause))
+        }
+    }
+}
+
+/// Resolves the address of a peer and connects to stream based peers.
+///
+/// # Arguments
+/// * `peer` - the address of the peer
+///
+/// # Errors
+/// Returns the reason, if the address can't be resolved or the connection is refused
+#[cfg(feature="net")]
+fn connect_peer(peer: &PeerAddr) -> Result<(), String> {
+    let addrs: Vec<std::net::SocketAddr> = match peer {
+        PeerAddr::IpSocket(_, addr) => vec!(*addr),
+        PeerAddr::HostSocket(_, host, port) => {
+            (host.as_str(), *port).to_socket_addrs().map_err(|e| e.to_string())?.collect()
+        },
+        #[cfg(unix)]
+        PeerAddr::UnixSocket(path) => {
+            return std::os::unix::net::UnixStream::connect(path).map(|_| ())
+                                                                 .map_err(|e| e.to_string())
+        }
+    };
+    if matches!(peer.protocol(), NetworkProtocol::Udp) { return Ok(()) }
+    let mut cause = String::new();
+    for addr in addrs.iter() {
+        match TcpStream::connect_timeout(addr, PROBE_CONNECT_TIMEOUT) {
+            Ok(_) => return Ok(()),
+            Err(e) => cause = e.to_string()
+        }
+    }
+    Err(cause)
+}
+
+/// Replaces the probe results of the previous configuration.
+///
+/// # Arguments
+/// * `results` - the results of all probed resources
+pub(crate) fn publish(results: Vec<ProbeResult>) {
+    if let Ok(mut r) = PROBE_RESULTS.lock() { *r = results; }
+}
+
+/// Returns the probe results of the active configuration.
+pub(crate) fn results() -> Vec<ProbeResult> {
+    PROBE_RESULTS.lock().map(|r| r.clone()).unwrap_or_default()
+}
+
+// free disk space needed by file based resources without size limit
+pub(crate) const DEFAULT_MIN_FREE_SPACE: u64 = 1048576;
+
+// prefix of the temporary file name used to test output directories, followed by process ID
+const PROBE_FILE_PREFIX: &str = ".coaly_probe_";
+
+// maximum time to wait for a connection to a stream based peer
+#[cfg(feature="net")]
+const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+lazy_static! {
+    // results of the startup probes of the active configuration
+    static ref PROBE_RESULTS: Mutex<Vec<ProbeResult>> = Mutex::new(Vec::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_directory() {
+        let dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap())
+                       .join("tmp").join("output").join("probe").join("test_probe_directory");
+        let _ = std::fs::remove_dir_all(&dir);
+        let result = probe_directory("file:probe.log", &dir, 0);
+        assert_eq!(ProbeStatus::Passed, result.status());
+        assert!(result.free_space().is_some());
+        // probe file must be removed
+        assert_eq!(0, std::fs::read_dir(&dir).unwrap().count());
+        let result = probe_directory("file:probe.log", &dir, u64::MAX);
+        assert!(result.failed_probe());
+        assert_eq!(W_RES_PROBE_LOW_DISK_SPACE, result.problem().unwrap().id());
+        // output directory below a regular file can't be created
+        let blocker = dir.join("blocker");
+        std::fs::write(&blocker, "x").unwrap();
+        let result = probe_directory("file:probe.log", &blocker.join("out"), 0);
+        assert_eq!(W_RES_PROBE_WRITE_FAILED, result.problem().unwrap().id());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
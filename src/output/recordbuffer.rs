@@ -34,23 +34,40 @@
 //! If the buffer is full, a write to the buffer will overwrite as many of the oldest records as
 //! needed to store the new record.
 //! The buffer is allocated by the caller and may be a pure memory buffer or backed by a file.
-
+//! Records in buffers backed by a file are stored in frames, allowing readers to detect
+//! records torn by an application or system crash.
+//! A frame consists of a start marker, the record length and CRC-32 checksum as 8-digit
+//! hexadecimal numbers, the record itself and a commit marker.
+//! The memory allocated by all memory buffers of the process is tracked and may be limited.
+//! If the limit is reached, every buffer is granted at least a fair share of the limit, buffers
+//! exceeding their fair share must release memory by dropping their oldest records.
+
+#[cfg(feature="mmfile")]
 use memmap2::MmapMut;
 use std::alloc::*;
+#[cfg(feature="mmfile")]
 use std::cell::RefCell;
 use std::cmp::{max, min};
 use std::fmt::Formatter;
-use std::fs::OpenOptions;
-use std::path::PathBuf;
+#[cfg(feature="mmfile")]
+use std::path::{Path, PathBuf};
+#[cfg(feature="mmfile")]
 use std::rc::Rc;
 use std::slice::from_raw_parts;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature="mmfile")]
 use crate::coalyxe;
+#[cfg(feature="mmfile")]
 use crate::errorhandling::*;
+use crate::util::crc32;
+#[cfg(feature="mmfile")]
+use crate::util::shared_open_options;
 
 /// Cyclic buffer for string or binary records.
 #[derive(Clone)]
 pub struct RecordBuffer {
     /// optional memory map
+    #[cfg(feature="mmfile")]
     map: Option<Rc<RefCell<MmapMut>>>,
     /// pointer array with record start positions
     records: Vec<*mut u8>,
@@ -73,7 +90,12 @@ pub struct RecordBuffer {
     /// raw pointer to byte after last content byte in buffer
     tail: *mut u8,
     /// possible extra bytes after last content byte
-    extra_bytes : usize
+    extra_bytes : usize,
+    /// indicates whether records are stored in frames
+    framed: bool,
+    /// header at the beginning of the backing file, preceding the buffer content
+    #[cfg(feature="mmfile")]
+    header: Vec<u8>
 }
 
 impl RecordBuffer {
@@ -93,10 +115,13 @@ impl RecordBuffer {
             let content_size = buf_size - 4;
             let layout = Layout::from_size_align_unchecked(buffer_size, 8);
             let head = System.alloc(layout);
+            MEMORY_USAGE.fetch_add(buffer_size, Ordering::SeqCst);
+            MEMORY_BUFFER_COUNT.fetch_add(1, Ordering::SeqCst);
             let index_size = max(MIN_INDEX_SIZE, max_record_count);
             let mut records = Vec::<*mut u8>::with_capacity(index_size);
             records.resize(index_size, head);
             RecordBuffer {
+                #[cfg(feature="mmfile")]
                 map: None,
                 records,
                 buffer_size,
@@ -108,7 +133,10 @@ impl RecordBuffer {
                 oldest_rec_index: 0,
                 max_rec_index: index_size - 1,
                 record_count: 0,
-                extra_bytes: 0
+                extra_bytes: 0,
+                framed: false,
+                #[cfg(feature="mmfile")]
+                header: Vec::new()
             }
         }
     }
@@ -116,18 +144,23 @@ impl RecordBuffer {
     /// Constructs a record buffer backed by a file.
     /// The allocated buffer uses the last 32 bytes internally for easier multi-byte character
     /// handling and offset storage.
+    /// An optional header is written to the beginning of the file, the record content follows
+    /// the header.
     ///
     /// # Arguments
     /// * `file_path` - the full path of the backing file
     /// * `buf_size` - the size of the buffer in bytes
     /// * `max_record_count` - the maximum number of records
+    /// * `header` - the file header, empty if the file shall not contain a header
+    #[cfg(feature="mmfile")]
     pub fn backed_by_file(file_path: &PathBuf,
                           buf_size: usize,
-                          max_record_count: usize) -> Result<RecordBuffer, CoalyException> {
+                          max_record_count: usize,
+                          header: &[u8]) -> Result<RecordBuffer, CoalyException> {
         unsafe {
-            let buffer_size = max(MIN_MAPPED_BUFFER_SIZE, buf_size);
-            let content_size = buffer_size - 32;
-            let res = OpenOptions::new().read(true).write(true).create(true).open(file_path);
+            let buffer_size = max(MIN_MAPPED_BUFFER_SIZE + header.len(), buf_size);
+            let content_size = buffer_size - MAPPED_ADMIN_DATA_LEN - header.len();
+            let res = shared_open_options().read(true).write(true).create(true).open(file_path);
             if let Err(io_err) = res {
                 let file_name = file_path.to_string_lossy().to_string();
                 return Err(coalyxe!(E_FILE_CRE_ERR, file_name, io_err.to_string()))
@@ -143,7 +176,8 @@ impl RecordBuffer {
                 return Err(coalyxe!(E_FILE_CRE_ERR, file_name, io_err.to_string()))
             }
             let mut m = res.unwrap();
-            let head = m.as_mut().as_mut_ptr();
+            m[..header.len()].copy_from_slice(header);
+            let head = m.as_mut().as_mut_ptr().add(header.len());
             let index_size = max(MIN_INDEX_SIZE, max_record_count);
             let mut records = Vec::<*mut u8>::with_capacity(index_size);
             records.resize(index_size, head);
@@ -159,7 +193,9 @@ impl RecordBuffer {
                 oldest_rec_index: 0,
                 max_rec_index: index_size - 1,
                 record_count: 0,
-                extra_bytes: 0
+                extra_bytes: 0,
+                framed: true,
+                header: header.to_vec()
             })
         }
     }
@@ -171,11 +207,13 @@ impl RecordBuffer {
     /// # Arguments
     /// * `file_path` - the full path of the backing file
     /// * `create_file` - indicates whether to create the backing file 
+    #[cfg(feature="mmfile")]
     pub fn reopen(&mut self,
                   file_path: &PathBuf,
                   create_file: bool) -> Result<(), CoalyException> {
         unsafe {
-            let res = OpenOptions::new().read(true).write(true).create(create_file).open(file_path);
+            let res = shared_open_options().read(true).write(true).create(create_file)
+                                           .open(file_path);
             if let Err(io_err) = res {
                 let file_name = file_path.to_string_lossy().to_string();
                 return Err(coalyxe!(E_FILE_CRE_ERR, file_name, io_err.to_string()))
@@ -190,12 +228,51 @@ impl RecordBuffer {
                 let file_name = file_path.to_string_lossy().to_string();
                 return Err(coalyxe!(E_FILE_CRE_ERR, file_name, io_err.to_string()))
             }
-            self.map = Some(Rc::new(RefCell::new(res.unwrap())));
+            let mut m = res.unwrap();
+            m[..self.header.len()].copy_from_slice(&self.header);
+            self.head = m.as_mut().as_mut_ptr().add(self.header.len());
+            self.tail = self.head.add(self.content_size);
+            self.map = Some(Rc::new(RefCell::new(m)));
             self.clear();
             Ok(())
         }
     }
 
+    /// Maps the backing file anew, e.g. after the process changed its user identity.
+    /// Buffer content and record positions are retained.
+    /// A call to this method has no effect for buffers in main memory.
+    ///
+    /// # Arguments
+    /// * `file_path` - the full path of the backing file
+    ///
+    /// # Errors
+    /// Returns an error structure if the backing file can't be opened or mapped
+    #[cfg(feature="mmfile")]
+    pub fn remap(&mut self, file_path: &Path) -> Result<(), CoalyException> {
+        if self.map.is_none() { return Ok(()) }
+        let file_name = file_path.to_string_lossy().to_string();
+        let f = shared_open_options().read(true).write(true).open(file_path)
+                                     .map_err(|e| coalyxe!(E_FILE_CRE_ERR, file_name.to_string(),
+                                                           e.to_string()))?;
+        unsafe {
+            let mut m = MmapMut::map_mut(&f).map_err(|e| coalyxe!(E_FILE_CRE_ERR,
+                                                                  file_name.to_string(),
+                                                                  e.to_string()))?;
+            if m.len() < self.buffer_size {
+                return Err(coalyxe!(E_FILE_CRE_ERR, file_name, m.len().to_string()))
+            }
+            if let Some(old_map) = &self.map { let _ = old_map.borrow().flush(); }
+            let new_head = m.as_mut().as_mut_ptr().add(self.header.len());
+            for rec_ptr in self.records.iter_mut() {
+                *rec_ptr = new_head.add(rec_ptr.offset_from(self.head) as usize);
+            }
+            self.head = new_head;
+            self.tail = new_head.add(self.content_size);
+            self.map = Some(Rc::new(RefCell::new(m)));
+        }
+        Ok(())
+    }
+
     /// Writes a record to this buffer.
     /// Older records will be overwritten if there's not enough free space in the buffer to store
     /// the record.
@@ -207,6 +284,14 @@ impl RecordBuffer {
     #[inline]
     pub fn write(&mut self, rec: &str) {
         if rec.is_empty() { return }
+        if self.framed {
+            let max_payload_len = self.max_rec_len.saturating_sub(FRAME_OVERHEAD);
+            if max_payload_len == 0 { return }
+            let frame = framed_rec(rec, max_payload_len);
+            self.create_free_space(frame.len());
+            self.push_str(&frame);
+            return
+        }
         self.create_free_space(min(self.max_rec_len, rec.len()));
         self.push_str(rec);
     }
@@ -219,7 +304,6 @@ impl RecordBuffer {
     ///
     /// # Arguments
     /// * `rec` - the record to write
-    #[cfg(feature="net")]
     #[inline]
     pub fn cache(&mut self, rec: &[u8]) {
         self.create_free_space(min(self.max_rec_len, rec.len()));
@@ -227,6 +311,7 @@ impl RecordBuffer {
     }
 
     /// Closes the buffer.
+    #[cfg(feature="mmfile")]
     pub fn close(&mut self) {
         if let Some(ref mut m) = self.map { let _ = m.borrow_mut().flush(); }
         self.map = None;
@@ -295,9 +380,49 @@ impl RecordBuffer {
     #[inline]
     pub fn is_empty(&self) -> bool { self.record_count == 0 }
 
+    /// Returns the number of records currently stored in this buffer.
+    #[inline]
+    pub fn record_count(&self) -> usize { self.record_count }
+
+    /// Returns the number of content bytes currently used by records in this buffer.
+    #[inline]
+    pub fn used_space(&self) -> usize { self.content_size - self.free_space() }
+
+    /// Returns the size of this buffer in bytes.
+    #[inline]
+    pub fn size(&self) -> usize { self.buffer_size }
+
+    /// Returns a copy of this memory buffer with a different size.
+    /// If the new buffer is smaller, only the most recent records fitting into the new buffer
+    /// are copied.
+    ///
+    /// # Arguments
+    /// * `buf_size` - the size of the new buffer in bytes
+    /// * `max_record_len` - the maximum allowed length for records
+    pub fn resized(&self,
+                   buf_size: usize,
+                   max_record_len: usize) -> RecordBuffer {
+        let mut buf = RecordBuffer::in_memory(buf_size, self.records.len(), max_record_len);
+        for (part1, part2) in self.records() {
+            let rec_len = part1.len() + part2.map_or(0, |p| p.len());
+            // records longer than allowed in the new buffer can't be stored
+            if rec_len > buf.max_rec_len { continue }
+            if let Some(part2) = part2 {
+                let mut rec = Vec::<u8>::with_capacity(rec_len);
+                rec.extend_from_slice(part1);
+                rec.extend_from_slice(part2);
+                buf.cache(&rec);
+            } else {
+                buf.cache(part1);
+            }
+        }
+        buf
+    }
+
     /// Writes administrative data to buffer.
     /// Used for memory mapped files only, where offset of oldest record and first free byte may be
     /// needed to reconstruct the file in case of application crash.
+    #[cfg(feature="mmfile")]
     pub fn update_admin_data(&mut self) {
         unsafe {
             // write extra byte count to tail+3, length 1 byte
@@ -326,7 +451,6 @@ impl RecordBuffer {
     /// Returns all records in this buffer for iteration.
     /// Because of the circular buffer nature, one record may consist of two parts.
     /// This is the reason for the tuple items in the returned vector.
-    #[cfg(feature="net")]
     pub fn records(&self) -> Vec::<(&[u8], Option<&[u8]>)> {
         if self.record_count == 0 { return Vec::<(&[u8], Option<&[u8]>)>::new() }
         let mut recs = Vec::<(&[u8], Option<&[u8]>)>::with_capacity(self.record_count);
@@ -368,6 +492,8 @@ impl RecordBuffer {
             self.clear();
             return
         }
+        // at least one unused entry in record index table is needed
+        while self.record_count >= self.max_rec_index { self.remove_oldest_record(); }
         let mut available_space = self.free_space();
         while available_space < needed_space { available_space += self.remove_oldest_record(); }
     }
@@ -407,13 +533,13 @@ impl RecordBuffer {
         *self.records.get_mut(new_ins_index).unwrap() = ins;
         self.ins_index = new_ins_index;
         self.record_count += 1;
+        #[cfg(feature="mmfile")]
         if self.map.is_some() { self.update_admin_data(); }
     }
 
     /// Stores the given record in the buffer.
     /// The caller must have made sure that the buffer can store the record without overwriting
     /// older records.
-    #[cfg(feature="net")]
     fn push_slice(&mut self, rec: &[u8]) {
         let mut ins = *self.records.get(self.ins_index).unwrap();
         let oldest = *self.records.get(self.oldest_rec_index).unwrap() as usize;
@@ -500,15 +626,88 @@ impl std::fmt::Debug for RecordBuffer {
 }
 impl Drop for RecordBuffer {
     fn drop(&mut self) {
-        if self.map.is_none() {
+        // buffers backed by a file point into the memory map, even after the map was closed
+        if ! self.framed {
             unsafe {
                 let layout = Layout::from_size_align_unchecked(self.buffer_size, 8);
                 System.dealloc(self.head, layout);
             }
+            MEMORY_USAGE.fetch_sub(self.buffer_size, Ordering::SeqCst);
+            MEMORY_BUFFER_COUNT.fetch_sub(1, Ordering::SeqCst);
         }
     }
 }
 
+/// Returns the number of bytes currently allocated by all memory buffers of the process.
+#[inline]
+pub(crate) fn buffer_memory_usage() -> usize { MEMORY_USAGE.load(Ordering::SeqCst) }
+
+/// Sets the maximum number of bytes for all memory buffers of the process.
+///
+/// # Arguments
+/// * `limit` - the memory limit in bytes, 0 if unlimited
+#[inline]
+pub(crate) fn set_buffer_memory_limit(limit: usize) {
+    MEMORY_LIMIT.store(limit, Ordering::SeqCst);
+}
+
+/// Indicates whether the memory allocated by all memory buffers exceeds the limit.
+#[inline]
+pub(crate) fn buffer_memory_limit_exceeded() -> bool {
+    let limit = MEMORY_LIMIT.load(Ordering::SeqCst);
+    limit > 0 && MEMORY_USAGE.load(Ordering::SeqCst) > limit
+}
+
+/// Returns the fair share of the memory limit for a single memory buffer.
+/// Returns **usize::MAX**, if memory usage isn't limited.
+pub(crate) fn buffer_fair_share() -> usize {
+    fair_share(MEMORY_LIMIT.load(Ordering::SeqCst), MEMORY_BUFFER_COUNT.load(Ordering::SeqCst))
+}
+
+/// Returns the size granted to a memory buffer, considering the memory limit.
+///
+/// # Arguments
+/// * `requested_size` - the desired buffer size in bytes
+/// * `current_size` - the current buffer size in bytes, 0 for a buffer about to be allocated
+pub(crate) fn granted_buffer_size(requested_size: usize,
+                                  current_size: usize) -> usize {
+    granted_size(requested_size, current_size, MEMORY_LIMIT.load(Ordering::SeqCst),
+                 MEMORY_USAGE.load(Ordering::SeqCst), MEMORY_BUFFER_COUNT.load(Ordering::SeqCst))
+}
+
+/// Returns the fair share of the memory limit for a single memory buffer.
+///
+/// # Arguments
+/// * `limit` - the memory limit in bytes, 0 if unlimited
+/// * `buffer_count` - the number of memory buffers sharing the limit
+fn fair_share(limit: usize,
+              buffer_count: usize) -> usize {
+    if limit == 0 { return usize::MAX }
+    max(MIN_MEM_BUFFER_SIZE, limit / max(1, buffer_count))
+}
+
+/// Returns the size granted to a memory buffer.
+/// The requested size is granted, if it fits into the memory left by all other buffers.
+/// Otherwise the buffer gets the remaining memory, but at least its fair share of the limit.
+///
+/// # Arguments
+/// * `requested_size` - the desired buffer size in bytes
+/// * `current_size` - the current buffer size in bytes, 0 for a buffer about to be allocated
+/// * `limit` - the memory limit in bytes, 0 if unlimited
+/// * `usage` - the number of bytes currently allocated by all memory buffers
+/// * `buffer_count` - the number of currently allocated memory buffers
+fn granted_size(requested_size: usize,
+                current_size: usize,
+                limit: usize,
+                usage: usize,
+                buffer_count: usize) -> usize {
+    if limit == 0 { return requested_size }
+    let available = limit.saturating_sub(usage.saturating_sub(current_size));
+    if requested_size <= available { return requested_size }
+    let buffer_count = if current_size == 0 { buffer_count + 1 } else { buffer_count };
+    min(requested_size, max(available, fair_share(limit, buffer_count)))
+}
+
 /// Returns the encoded bytes for the given record, truncated to the specified length.
 /// Function considers character boundaries, hence the length of the returned bytes may be
 /// smaller than the specified maximum.
@@ -527,6 +726,21 @@ fn truncate_rec(rec: &str, max_encoded_length: usize) -> &[u8] {
     &rec.as_bytes()[0..trunc_len]
 }
 
+/// Returns the frame for the given record, as it is stored in buffers backed by a file.
+/// The record is truncated, if it exceeds the specified maximum length.
+///
+/// # Arguments
+/// * `rec` - the record
+/// * `max_payload_len` - the maximum allowed length for the encoded record bytes
+pub(super) fn framed_rec(rec: &str, max_payload_len: usize) -> String {
+    let payload = if rec.len() > max_payload_len { truncate_rec(rec, max_payload_len) }
+                  else { rec.as_bytes() };
+    // truncation considers character boundaries, conversion can't fail
+    let payload_str = std::str::from_utf8(payload).unwrap_or_default();
+    format!("{}{:08x}{:08x}{}{}", FRAME_START as char, payload.len(), crc32(payload),
+            payload_str, FRAME_COMMIT as char)
+}
+
 /// Splits a record into two byte slices at the desired byte position.
 /// Function considers character boundaries, the length of the first part may exceed the
 /// specified split position by a maximum of two bytes.
@@ -567,13 +781,40 @@ fn split_rec(rec: &str, desired_split_pos: usize) -> (&[u8], &[u8]) {
 }
 
 const MIN_MEM_BUFFER_SIZE: usize = 20;
+#[cfg(feature="mmfile")]
 const MIN_MAPPED_BUFFER_SIZE: usize = 48;
 const MIN_INDEX_SIZE: usize = 4;
 
+/// Number of bytes at the end of a file backed buffer reserved for administrative data
+pub(super) const MAPPED_ADMIN_DATA_LEN: usize = 32;
+
+/// Marker for the start of a record frame
+pub(super) const FRAME_START: u8 = 0x02;
+
+/// Marker for the end of a completely written record frame
+pub(super) const FRAME_COMMIT: u8 = 0x03;
+
+/// Length of a frame header, containing start marker, record length and checksum
+pub(super) const FRAME_HEADER_LEN: usize = 17;
+
+/// Number of bytes needed by a frame in addition to the record
+const FRAME_OVERHEAD: usize = FRAME_HEADER_LEN + 1;
+
+// number of bytes allocated by all memory buffers of the process
+static MEMORY_USAGE: AtomicUsize = AtomicUsize::new(0);
+
+// number of memory buffers currently allocated
+static MEMORY_BUFFER_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// maximum number of bytes for all memory buffers, 0 if unlimited
+static MEMORY_LIMIT: AtomicUsize = AtomicUsize::new(0);
+
 #[cfg(test)]
 mod tests {
+    #[cfg(feature="mmfile")]
     use std::path::Path;
     use std::str;
+    #[cfg(feature="mmfile")]
     use std::time::{SystemTime, UNIX_EPOCH};
     use super::*;
     #[cfg(feature="net")]
@@ -614,6 +855,7 @@ mod tests {
     const REC_8_7_U4_4: &str = "123456781234567𝄞1234";
     const REC_16: &str = "1234567890123456";
     const REC_20: &str = "12345678901234567890";
+    #[cfg(feature="mmfile")]
     const REC_42: &str = "12345678901234567890123456789012345678901\n";
 
     /// Verifies a buffer's administrative attributes
@@ -724,6 +966,50 @@ mod tests {
         verify_attrs(&buf, "CS:124/IS:8/ML:124/MI:7/RC:0/EX:0/IX:0/IP:0/OX:0/OP:0", "maxrl>size");
     }
 
+    #[test]
+    /// Tests growing and shrinking a memory buffer
+    fn test_resized() {
+        let mut buf = RecordBuffer::in_memory(40, 8, 32);
+        buf.write("aaaaaaa\n");
+        buf.write("bbbbbbb\n");
+        buf.write("ccccccc\n");
+        // grown buffer keeps all records
+        let mut grown = buf.resized(80, 32);
+        verify_attrs(&grown, "CS:76/IS:8/ML:32/MI:7/RC:3/EX:0/IX:3/IP:24/OX:0/OP:0", "grown");
+        verify_contents(&grown, "aaaaaaa\nbbbbbbb\nccccccc\n", "grown");
+        grown.write("ddddddd\n");
+        verify_contents(&grown, "aaaaaaa\nbbbbbbb\nccccccc\nddddddd\n", "grown+1");
+        // shrunk buffer keeps most recent records only
+        let shrunk = grown.resized(20, 16);
+        verify_contents(&shrunk, "ccccccc\nddddddd\n", "shrunk");
+        // records exceeding maximum length in shrunk buffer are dropped
+        let mut buf = RecordBuffer::in_memory(40, 8, 32);
+        buf.write(REC_20);
+        buf.write(REC_8);
+        let shrunk = buf.resized(20, 16);
+        verify_contents(&shrunk, REC_8, "shrunk with long record");
+    }
+
+    #[test]
+    /// Tests calculation of granted buffer sizes under a memory limit
+    fn test_granted_size() {
+        // no limit
+        assert_eq!(usize::MAX, fair_share(0, 10));
+        assert_eq!(4096, granted_size(4096, 0, 0, 100000, 10));
+        // limit not reached
+        assert_eq!(1000, fair_share(4000, 4));
+        assert_eq!(1024, granted_size(1024, 0, 4096, 2048, 2));
+        assert_eq!(2048, granted_size(2048, 1024, 4096, 3072, 3));
+        // limit reached, remaining memory is granted
+        assert_eq!(1536, granted_size(2048, 0, 4096, 2560, 2));
+        // limit exhausted, fair share is granted
+        assert_eq!(1024, granted_size(2048, 0, 4096, 4096, 3));
+        assert_eq!(1365, granted_size(2048, 512, 4096, 4096, 3));
+        assert_eq!(512, granted_size(512, 0, 4096, 4096, 3));
+        assert_eq!(MIN_MEM_BUFFER_SIZE, granted_size(2048, 0, 40, 40, 3));
+    }
+
+    #[cfg(feature="mmfile")]
     #[test]
     /// Tests construction of buffer backed by a file.
     fn test_file_construction() {
@@ -732,7 +1018,7 @@ mod tests {
         // normal case
         let rw_dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap()).join("tmp");
         let file_name = rw_dir.join(&pure_file_name);
-        if let Ok(mut buf) = RecordBuffer::backed_by_file(&file_name, 4096, 100) {
+        if let Ok(mut buf) = RecordBuffer::backed_by_file(&file_name, 4096, 100, &[]) {
             verify_attrs(&buf, "CS:4064/IS:100/ML:4064/MI:99/RC:0/EX:0/IX:0/IP:0/OX:0/OP:0", "");
             for _ in 0..100 { buf.write(REC_42); }
         } else {
@@ -741,7 +1027,32 @@ mod tests {
         // creation in non-writable directory must fail
         let ro_dir = Path::new(&std::env::var("TESTING_ROOT").unwrap()).join("readonly");
         let file_name = ro_dir.join(&pure_file_name);
-        assert!(RecordBuffer::backed_by_file(&file_name, 4096, 100).is_err());
+        assert!(RecordBuffer::backed_by_file(&file_name, 4096, 100, &[]).is_err());
+    }
+
+    #[cfg(feature="mmfile")]
+    #[test]
+    /// Tests mapping the backing file of a buffer anew.
+    fn test_remap() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let rw_dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap()).join("tmp");
+        std::fs::create_dir_all(&rw_dir).unwrap();
+        let file_name = rw_dir.join(format!("remap{}.bin", now));
+        let mut buf = RecordBuffer::backed_by_file(&file_name, 4096, 100, &[]).unwrap();
+        buf.write("first");
+        buf.write("second");
+        let recs_before: Vec<Vec<u8>> = buf.records().iter().map(|(p1, _)| p1.to_vec()).collect();
+        assert!(buf.remap(&file_name).is_ok());
+        let recs_after: Vec<Vec<u8>> = buf.records().iter().map(|(p1, _)| p1.to_vec()).collect();
+        assert_eq!(recs_before, recs_after);
+        buf.write("third");
+        assert_eq!(3, buf.records().len());
+        // missing backing file must fail
+        let _ = std::fs::remove_file(&file_name);
+        assert!(buf.remap(&file_name).is_err());
+        // buffers in main memory are not affected
+        let mut mem_buf = RecordBuffer::in_memory(64, 8, 32);
+        assert!(mem_buf.remap(&file_name).is_ok());
     }
 
     #[test]
@@ -32,13 +32,20 @@
 
 //! Format descriptor for Coaly log or trace records.
 
+use chrono::{DateTime, Local, SecondsFormat, Timelike, Utc};
+use std::cell::RefCell;
 use std::str::FromStr;
-use crate::config::datetimeformat::{DateTimeFormatDesc, DateTimeFormatDescMap};
-use crate::config::output::{RecordFormatDesc};
+use crate::config::datetimeformat::{format_in_zone, DateTimeFormatDesc, DateTimeFormatDescMap,
+                                    DateTimeZone};
+use crate::config::output::{RecordFormatDesc, RecordLayout};
+use crate::config::resource::ClockGranularity;
+use crate::config::systemproperties::SystemProperties;
 use crate::record::{RecordLevelId, RecordLevelMap, RecordTrigger};
 use crate::record::originator::OriginatorInfo;
 use crate::record::recorddata::RecordData;
-use super::formatspec::{FormatSpec};
+use crate::util::json_escaped_str;
+use super::formatspec::{FormatSpec, EOL};
+use super::mappedfile::{TickCalibration, TICK_ITEM_DATE, TICK_ITEM_TIME, TICK_ITEM_TIMESTAMP};
 
 /// A record format structure specifies the fields of a log or trace message in the output.
 /// The components of a log or trace record are converted to a string according to this format.
@@ -48,14 +55,24 @@ pub(crate) struct RecordFormat {
     levels: u32,
     // bit mask of all record triggers, for which the format is defined
     triggers: u32,
+    // record layout
+    layout: RecordLayout,
     // format for timestamp values
     timestamp_format: String,
     // format for date values
     date_format: String,
     // format for time values
     time_format: String,
+    // time zone for timestamp, date and time values
+    zone: DateTimeZone,
     // list of fields that form the record format
-    fields: FormatSpec
+    fields: FormatSpec,
+    // precision of the timestamps written
+    granularity: ClockGranularity,
+    // formatted date and time values, reused for records within the same clock granule
+    time_cache: RefCell<TimeCache>,
+    // calibration, if timestamps are written as ticks instead of formatted values
+    calibration: Option<TickCalibration>
 }
 impl RecordFormat {
     /// Creates a record format.
@@ -63,20 +80,26 @@ impl RecordFormat {
     /// # Arguments
     /// * `levels` - the bit mask of all record levels valid for the format
     /// * `triggers` - the bit mask of all record triggers valid for the format
+    /// * `layout` - the record layout
     /// * `ts_fmt` - the format string for timestamp values
     /// * `date_fmt` - the format string for date values
     /// * `tm_fmt` - the format string for time values
     /// * `fields` - the specification of all fields in the format
-    pub(crate) fn new(levels: u32, triggers: u32,
+    pub(crate) fn new(levels: u32, triggers: u32, layout: RecordLayout,
                ts_fmt: &str, date_fmt: &str, tm_fmt: &str,
                fields: FormatSpec) -> RecordFormat {
         RecordFormat {
             levels,
             triggers,
+            layout,
             timestamp_format: ts_fmt.to_string(),
             date_format: date_fmt.to_string(),
             time_format: tm_fmt.to_string(),
-            fields
+            zone: DateTimeZone::Local,
+            fields,
+            granularity: ClockGranularity::Full,
+            time_cache: RefCell::new(TimeCache::default()),
+            calibration: None
         }
     }
 
@@ -85,15 +108,21 @@ impl RecordFormat {
     /// # Arguments
     /// * `desc` - the record format descriptor from the configuration
     /// * `dtm_formats` - the map with all date time formats
+    /// * `sys_props` - the system properties with the environment variables for record enrichment
     pub(crate) fn from_desc(desc: &RecordFormatDesc,
-                            dtm_formats: &DateTimeFormatDescMap) -> RecordFormat {
+                            dtm_formats: &DateTimeFormatDescMap,
+                            sys_props: &SystemProperties) -> RecordFormat {
         let dtm_fmt: &DateTimeFormatDesc = dtm_formats.find(desc.date_time_format_name());
-        let items = FormatSpec::from_str(desc.items()).unwrap();
-        RecordFormat::new(desc.levels(), desc.triggers(),
-                          &dtm_fmt.timestamp_format_for_recs(),
-                          &dtm_fmt.date_format_for_recs(),
-                          &dtm_fmt.time_format_for_recs(),
-                          items)
+        let items = FormatSpec::from_str(desc.items()).unwrap()
+                                .with_env_fields(sys_props.env_snapshot(),
+                                                 sys_props.env_per_record());
+        let mut fmt = RecordFormat::new(desc.levels(), desc.triggers(), desc.layout(),
+                                        dtm_fmt.timestamp_format_for_recs(),
+                                        dtm_fmt.date_format_for_recs(),
+                                        dtm_fmt.time_format_for_recs(),
+                                        items);
+        fmt.zone = dtm_fmt.zone();
+        fmt
     }
 
     /// Indicates, whether the given record level and trigger are within the scope of
@@ -109,6 +138,29 @@ impl RecordFormat {
         self.levels & level as u32 != 0 && self.triggers & trigger as u32 != 0
     }
 
+    /// Sets the precision of the timestamps written.
+    /// With a granularity coarser than the record timestamps, date and time values are truncated
+    /// and formatted only once per clock granule.
+    ///
+    /// # Arguments
+    /// * `granularity` - the clock granularity
+    pub(crate) fn set_clock_granularity(&mut self, granularity: ClockGranularity) {
+        self.granularity = granularity;
+        self.time_cache = RefCell::new(TimeCache::default());
+    }
+
+    /// Sets the calibration for tick timestamps.
+    /// Timestamp, date and time items are written as tick tokens with the monotonic clock
+    /// reading of the record instead of formatted values, clock granularity is ignored.
+    /// Records received from remote clients carry no clock reading and are formatted as usual.
+    ///
+    /// # Arguments
+    /// * `calibration` - the calibration for tick timestamps
+    #[cfg(feature="mmfile")]
+    pub(crate) fn set_tick_calibration(&mut self, calibration: TickCalibration) {
+        self.calibration = Some(calibration);
+    }
+
     /// Converts the specified log or trace record to a string according to this format.
     /// The caller must make sure, that the record is within the scope of this format by invoking
     /// function `applies_to`. The check is not done within this function.
@@ -120,8 +172,87 @@ impl RecordFormat {
     /// # Return values
     /// the formatted string, to be written to output resource
     pub(crate) fn apply_to(&self, record: &dyn RecordData, levels: &RecordLevelMap) -> String {
-        self.fields.apply_to_record(record, levels,
-                                    &self.timestamp_format, &self.date_format, &self.time_format)
+        if let (Some(calibration), Some(ticks)) = (&self.calibration, record.ticks()) {
+            let ts_token = calibration.token(TICK_ITEM_TIMESTAMP, ticks);
+            return match self.layout {
+                RecordLayout::Json => json_record(record, levels, &ts_token),
+                RecordLayout::Pretty => pretty_record(record, levels, &ts_token),
+                RecordLayout::Plain => {
+                    self.fields.apply_to_record_with_times(record, levels, &ts_token,
+                                                           &calibration.token(TICK_ITEM_DATE, ticks),
+                                                           &calibration.token(TICK_ITEM_TIME, ticks))
+                }
+            }
+        }
+        if self.granularity == ClockGranularity::Full {
+            let ts = record.timestamp();
+            return match self.layout {
+                RecordLayout::Json => {
+                    json_record(record, levels, &self.rfc3339(&ts, SecondsFormat::Micros))
+                },
+                RecordLayout::Pretty => {
+                    pretty_record(record, levels,
+                                  &format_in_zone(&ts, &self.timestamp_format, self.zone))
+                },
+                RecordLayout::Plain => {
+                    self.fields.apply_to_record(record, levels, &self.timestamp_format,
+                                                &self.date_format, &self.time_format, self.zone)
+                }
+            }
+        }
+        let mut cache = self.time_cache.borrow_mut();
+        self.refresh_time_cache(&mut cache, record.timestamp());
+        match self.layout {
+            RecordLayout::Json => json_record(record, levels, &cache.timestamp),
+            RecordLayout::Pretty => pretty_record(record, levels, &cache.timestamp),
+            RecordLayout::Plain => {
+                self.fields.apply_to_record_with_times(record, levels, &cache.timestamp,
+                                                       &cache.date, &cache.time)
+            }
+        }
+    }
+
+    /// Formats the date and time values for the given record timestamp, unless the cache
+    /// already holds the values for the timestamp's clock granule.
+    ///
+    /// # Arguments
+    /// * `cache` - the cache with the formatted values
+    /// * `ts` - the record timestamp
+    fn refresh_time_cache(&self, cache: &mut TimeCache, ts: DateTime<Local>) {
+        let (granule, secs_fmt) = match self.granularity {
+            ClockGranularity::Second => (ts.with_nanosecond(0), SecondsFormat::Secs),
+            ClockGranularity::Millisecond => {
+                (ts.with_nanosecond(ts.nanosecond() / 1_000_000 * 1_000_000), SecondsFormat::Millis)
+            },
+            ClockGranularity::Full => (Some(ts), SecondsFormat::Micros)
+        };
+        let granule = granule.unwrap_or(ts);
+        if cache.granule == Some(granule) { return }
+        cache.granule = Some(granule);
+        match self.layout {
+            RecordLayout::Json => cache.timestamp = self.rfc3339(&granule, secs_fmt),
+            RecordLayout::Pretty => {
+                cache.timestamp = format_in_zone(&granule, &self.timestamp_format, self.zone);
+            },
+            RecordLayout::Plain => {
+                cache.timestamp = format_in_zone(&granule, &self.timestamp_format, self.zone);
+                cache.date = format_in_zone(&granule, &self.date_format, self.zone);
+                cache.time = format_in_zone(&granule, &self.time_format, self.zone);
+            }
+        }
+    }
+
+    /// Formats a record timestamp in RFC 3339 format using the time zone of this format.
+    /// UTC timestamps are written with suffix Z instead of an offset.
+    ///
+    /// # Arguments
+    /// * `ts` - the record timestamp
+    /// * `secs_fmt` - the precision for fractional seconds
+    fn rfc3339(&self, ts: &DateTime<Local>, secs_fmt: SecondsFormat) -> String {
+        match self.zone {
+            DateTimeZone::Local => ts.to_rfc3339_opts(secs_fmt, false),
+            DateTimeZone::Utc => ts.with_timezone(&Utc).to_rfc3339_opts(secs_fmt, true)
+        }
     }
 
     /// Optimizes the format.
@@ -140,3 +271,384 @@ impl RecordFormat {
         self.fields = self.fields.optimized_for(orig_info, thread_id, thread_name);
     }
 }
+
+/// Formatted date and time values of the most recent clock granule.
+#[derive(Clone, Debug, Default)]
+struct TimeCache {
+    // start of the clock granule, the values were formatted for
+    granule: Option<DateTime<Local>>,
+    // formatted timestamp, date and time
+    timestamp: String,
+    date: String,
+    time: String
+}
+
+/// Converts the specified log or trace record to a JSON object.
+/// The object contains the timestamp in RFC 3339 format, record level name, thread ID and name,
+/// source file name and line number and the record trigger. Module path and function name of
+/// the issuing source code, observer name and message are included, if present in the record.
+/// Fields attached by a mode change for a unit are included as object with string attributes,
+/// the entries of the issuing thread's context map as object `context` and the observer
+/// attributes as object `attrs` with typed values.
+/// A backtrace captured for the record is included as array `backtrace` of frame strings.
+///
+/// # Arguments
+/// * `record` - the record data
+/// * `levels` - the hash table with the name for every record level
+/// * `ts` - the timestamp in RFC 3339 format
+///
+/// # Return values
+/// the JSON object on a single line, to be written to output resource
+fn json_record(record: &dyn RecordData, levels: &RecordLevelMap, ts: &str) -> String {
+    let mut result = String::with_capacity(256);
+    result.push_str("{\"timestamp\":\"");
+    result.push_str(ts);
+    result.push_str("\",\"level\":\"");
+    let ldesc = levels.get(&record.level()).unwrap();
+    result.push_str(&json_escaped_str(ldesc.name()));
+    result.push_str(&format!("\",\"thread_id\":{},\"thread\":\"", record.thread_id()));
+    result.push_str(&json_escaped_str(record.thread_name()));
+    result.push_str("\",\"file\":\"");
+    result.push_str(&json_escaped_str(record.source_fn()));
+    match record.line_nr() {
+        Some(line_nr) => result.push_str(&format!("\",\"line\":{}", line_nr)),
+        None => result.push_str("\",\"line\":null")
+    }
+    if let Some(module_path) = record.module_path() {
+        result.push_str(",\"module\":\"");
+        result.push_str(&json_escaped_str(module_path));
+        result.push('"');
+    }
+    if let Some(function) = record.function() {
+        result.push_str(",\"function\":\"");
+        result.push_str(&json_escaped_str(function));
+        result.push('"');
+    }
+    result.push_str(&format!(",\"trigger\":\"{}\"", record.trigger()));
+    if let Some(obs_name) = record.observer_name() {
+        result.push_str(",\"observer\":\"");
+        result.push_str(&json_escaped_str(obs_name));
+        result.push('"');
+    }
+    if let Some(msg) = record.message() {
+        result.push_str(",\"message\":\"");
+        match record.decoration() {
+            Some(d) if record.trigger() == RecordTrigger::Message => {
+                result.push_str(&json_escaped_str(&d.decorated(msg)));
+            },
+            _ => result.push_str(&json_escaped_str(msg))
+        }
+        result.push('"');
+    }
+    if let Some(d) = record.decoration().filter(|d| ! d.fields().is_empty()) {
+        result.push_str(",\"fields\":{");
+        for (index, (name, value)) in d.fields().iter().enumerate() {
+            if index > 0 { result.push(','); }
+            result.push_str(&format!("\"{}\":\"{}\"", json_escaped_str(name),
+                                     json_escaped_str(value)));
+        }
+        result.push('}');
+    }
+    if let Some(c) = record.context().filter(|c| ! c.is_empty()) {
+        result.push_str(",\"context\":{");
+        for (index, (key, value)) in c.iter().enumerate() {
+            if index > 0 { result.push(','); }
+            result.push_str(&format!("\"{}\":\"{}\"", json_escaped_str(key),
+                                     json_escaped_str(value)));
+        }
+        result.push('}');
+    }
+    if let Some(a) = record.attrs().filter(|a| ! a.is_empty()) {
+        result.push_str(",\"attrs\":{");
+        for (index, (key, value)) in a.iter().enumerate() {
+            if index > 0 { result.push(','); }
+            result.push_str(&format!("\"{}\":{}", json_escaped_str(key), value.to_json()));
+        }
+        result.push('}');
+    }
+    if let Some(bt) = record.backtrace().filter(|b| ! b.is_empty()) {
+        result.push_str(",\"backtrace\":[");
+        for (index, frame) in bt.lines().enumerate() {
+            if index > 0 { result.push(','); }
+            result.push_str(&format!("\"{}\"", json_escaped_str(frame)));
+        }
+        result.push(']');
+    }
+    result.push('}');
+    result.push_str(EOL);
+    result
+}
+
+/// Converts the specified log or trace record to a multi-line string for human readers.
+/// The first line contains timestamp, record level and message, the following lines contain
+/// thread, source location, issuing function, observer, scope, the fields attached by a mode
+/// change for a unit, the context map entries and the observer attributes as an indented block,
+/// with the field values aligned. A backtrace captured for the record follows the block, one
+/// frame per line.
+/// Intended for local development consoles, not for files processed by tools.
+///
+/// # Arguments
+/// * `record` - the record data
+/// * `levels` - the hash table with the name for every record level
+/// * `ts` - the formatted timestamp
+///
+/// # Return values
+/// the formatted record including line breaks, to be written to output resource
+fn pretty_record(record: &dyn RecordData, levels: &RecordLevelMap, ts: &str) -> String {
+    let mut result = String::with_capacity(256);
+    result.push_str(ts);
+    let ldesc = levels.get(&record.level()).unwrap();
+    result.push_str(&format!(" {:<width$} ", ldesc.name(), width = PRETTY_LEVEL_WIDTH));
+    let obs_name = record.observer_name().as_deref().unwrap_or_default();
+    let is_unit = record.level() as u32 & RecordLevelId::Units as u32 != 0;
+    match record.trigger() {
+        RecordTrigger::Message => {
+            let msg = record.message().as_deref().unwrap_or_default();
+            match record.decoration() {
+                Some(d) => result.push_str(&d.decorated(msg)),
+                None => result.push_str(msg)
+            }
+        },
+        RecordTrigger::ObserverCreated => {
+            result.push_str(obs_name);
+            result.push_str(if is_unit { " -in-" } else { " created" });
+        },
+        _ => {
+            result.push_str(obs_name);
+            result.push_str(if is_unit { " -out-" } else { " dropped" });
+        }
+    }
+    result.push_str(EOL);
+    let mut fields = Vec::<(&str, String)>::with_capacity(4);
+    fields.push((PRETTY_FIELD_THREAD, format!("{} ({})", record.thread_name(),
+                                              record.thread_id())));
+    let line_nr = record.line_nr().map_or(String::from("-"), |n| n.to_string());
+    fields.push((PRETTY_FIELD_SOURCE, format!("{}:{}", record.source_fn(), line_nr)));
+    match (record.module_path(), record.function()) {
+        (Some(m), Some(f)) => fields.push((PRETTY_FIELD_FUNCTION, format!("{}::{}", m, f))),
+        (Some(m), None) => fields.push((PRETTY_FIELD_MODULE, m.to_string())),
+        _ => ()
+    }
+    if record.trigger() == RecordTrigger::Message && ! obs_name.is_empty() {
+        fields.push((PRETTY_FIELD_OBSERVER, obs_name.to_string()));
+    }
+    if record.trigger() == RecordTrigger::ObserverCreated {
+        if let Some(value) = record.message() { fields.push((PRETTY_FIELD_VALUE, value.clone())); }
+    }
+    if ! record.scope_stack().is_empty() {
+        fields.push((PRETTY_FIELD_SCOPE, record.scope_stack().join(" > ")));
+    }
+    if let Some(d) = record.decoration() {
+        for (name, value) in d.fields() { fields.push((name, value.clone())); }
+    }
+    if let Some(c) = record.context() {
+        for (key, value) in c { fields.push((key, value.clone())); }
+    }
+    if let Some(a) = record.attrs() {
+        for (key, value) in a { fields.push((key, value.to_string())); }
+    }
+    let key_width = fields.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    for (key, value) in fields {
+        result.push_str(&format!("{}{:<width$} : {}", PRETTY_INDENT, key, value,
+                                 width = key_width));
+        result.push_str(EOL);
+    }
+    for frame in record.backtrace().unwrap_or_default().lines() {
+        result.push_str(&format!("{}{}{}", PRETTY_INDENT, PRETTY_BACKTRACE_PREFIX, frame));
+        result.push_str(EOL);
+    }
+    result
+}
+
+// Minimum width of the record level name in pretty records
+const PRETTY_LEVEL_WIDTH: usize = 6;
+
+// Indentation of the field block in pretty records
+const PRETTY_INDENT: &str = "    ";
+
+// Prefix of the backtrace frames below the field block in pretty records
+const PRETTY_BACKTRACE_PREFIX: &str = "at ";
+
+// Field names in pretty records
+const PRETTY_FIELD_FUNCTION: &str = "function";
+const PRETTY_FIELD_MODULE: &str = "module";
+const PRETTY_FIELD_OBSERVER: &str = "observer";
+const PRETTY_FIELD_SCOPE: &str = "scope";
+const PRETTY_FIELD_SOURCE: &str = "source";
+const PRETTY_FIELD_THREAD: &str = "thread";
+const PRETTY_FIELD_VALUE: &str = "value";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::record::recorddata::{LocalRecordData, RecordDecoration, SourceUnit};
+
+    #[test]
+    /// Tests conversion of records to JSON objects
+    fn test_json_record() {
+        let levels = RecordLevelMap::default();
+        let rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/main.rs", 42,
+                                             "disk \"/var\" full\\n");
+        let json = json_record(&rec, &levels, "2024-05-01T12:00:00+02:00");
+        assert!(json.starts_with("{\"timestamp\":\""));
+        assert!(json.ends_with(EOL));
+        let expected_tail = "\"level\":\"ERROR\",\"thread_id\":7,\"thread\":\"main\",\
+                             \"file\":\"src/main.rs\",\"line\":42,\"trigger\":\"message\",\
+                             \"message\":\"disk \\\"/var\\\" full\\\\n\"}";
+        assert!(json.contains(expected_tail), "{}", json);
+    }
+
+    #[test]
+    /// Tests conversion of records to multi-line strings with aligned fields
+    fn test_pretty_record() {
+        let levels = RecordLevelMap::default();
+        let mut rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/main.rs",
+                                                 42, "disk full");
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert_eq!(4, lines.len());
+        assert!(lines[0].ends_with(" ERROR  disk full"), "{}", pretty);
+        assert_eq!("    thread : main (7)", lines[1]);
+        assert_eq!("    source : src/main.rs:42", lines[2]);
+        assert!(lines[3].is_empty());
+        let scopes = vec!(String::from("handle_request"), String::from("parse"));
+        rec.set_scope_stack(std::sync::Arc::new(scopes));
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert_eq!("    thread : main (7)", lines[1]);
+        assert_eq!("    scope  : handle_request > parse", lines[3]);
+    }
+
+    #[test]
+    /// Tests prefix, suffix and fields attached by a mode change for a unit
+    fn test_decorated_record() {
+        let levels = RecordLevelMap::default();
+        let mut rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/main.rs",
+                                                 42, "disk full");
+        let fields = vec!((String::from("origin"), String::from("vendor")));
+        rec.set_decoration(Some(Arc::new(RecordDecoration::new("[LEGACY] ", " (v1)", fields))));
+        let json = json_record(&rec, &levels, "2024-05-01T12:00:00+02:00");
+        let expected_tail = "\"message\":\"[LEGACY] disk full (v1)\",\
+                             \"fields\":{\"origin\":\"vendor\"}}";
+        assert!(json.contains(expected_tail), "{}", json);
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert!(lines[0].ends_with(" ERROR  [LEGACY] disk full (v1)"), "{}", pretty);
+        assert_eq!("    origin : vendor", lines[3]);
+        let fmt = FormatSpec::from_str("$Message|$UnitFields").unwrap();
+        assert_eq!(format!("[LEGACY] disk full (v1)|origin=vendor{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+    }
+
+    #[test]
+    /// Tests enrichment of records with the context map of the issuing thread
+    fn test_context_record() {
+        let levels = RecordLevelMap::default();
+        let _request = crate::context::scoped("request_id", "4711");
+        let rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/main.rs", 42,
+                                             "disk full");
+        let json = json_record(&rec, &levels, "2024-05-01T12:00:00+02:00");
+        let expected_tail = "\"message\":\"disk full\",\"context\":{\"request_id\":\"4711\"}}";
+        assert!(json.contains(expected_tail), "{}", json);
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert_eq!("    request_id : 4711", lines[3]);
+        let fmt = FormatSpec::from_str("[$Context[request_id]$Context[user]] $Message").unwrap();
+        assert_eq!(format!("[4711] disk full{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+    }
+
+    #[test]
+    /// Tests records with a captured backtrace
+    fn test_backtrace_record() {
+        let levels = RecordLevelMap::default();
+        let mut rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/main.rs",
+                                                 42, "disk full");
+        let fmt = FormatSpec::from_str("$Message$Backtrace").unwrap();
+        assert_eq!(format!("disk full{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+        rec.set_backtrace("myapp::save (./src/db.rs:17:9)\nmyapp::main");
+        let json = json_record(&rec, &levels, "2024-05-01T12:00:00+02:00");
+        let expected_tail = "\"message\":\"disk full\",\
+                             \"backtrace\":[\"myapp::save (./src/db.rs:17:9)\",\"myapp::main\"]}";
+        assert!(json.contains(expected_tail), "{}", json);
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert_eq!("    at myapp::save (./src/db.rs:17:9)", lines[3]);
+        assert_eq!("    at myapp::main", lines[4]);
+        assert_eq!(format!("disk full{0}    at myapp::save (./src/db.rs:17:9){0}    \
+                            at myapp::main{0}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+    }
+
+    #[test]
+    /// Tests records with module path and function name determined by the log macros
+    fn test_source_unit_record() {
+        let levels = RecordLevelMap::default();
+        let mut rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/db.rs",
+                                                 42, "disk full");
+        let fmt = FormatSpec::from_str("[$ModulePath|$Function] $Message").unwrap();
+        assert_eq!(format!("[|] disk full{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+        rec.set_source_unit(SourceUnit::new("myapp::db", "myapp::db::save::{{closure}}::f"));
+        assert_eq!(format!("[myapp::db|save] disk full{}", EOL),
+                   fmt.apply_to_record(&rec, &levels, "", "", "", DateTimeZone::Local));
+        let json = json_record(&rec, &levels, "2024-05-01T12:00:00+02:00");
+        let expected = "\"line\":42,\"module\":\"myapp::db\",\"function\":\"save\",\"trigger\"";
+        assert!(json.contains(expected), "{}", json);
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert_eq!("    function : myapp::db::save", lines[3]);
+        rec.set_source_unit(SourceUnit::new("myapp::db", ""));
+        let json = json_record(&rec, &levels, "2024-05-01T12:00:00+02:00");
+        assert!(json.contains("\"module\":\"myapp::db\",\"trigger\""), "{}", json);
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert_eq!("    module : myapp::db", lines[3]);
+    }
+
+    #[test]
+    /// Tests enrichment of records with the observer attributes of the issuing thread
+    fn test_attrs_record() {
+        let levels = RecordLevelMap::default();
+        crate::observer::set_attr(1, "request_id", 4711.into());
+        crate::observer::set_attr(1, "user", "jdoe".into());
+        let rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/main.rs", 42,
+                                             "disk full");
+        crate::observer::remove_attrs(1);
+        let json = json_record(&rec, &levels, "2024-05-01T12:00:00+02:00");
+        let expected_tail = "\"message\":\"disk full\",\
+                             \"attrs\":{\"request_id\":4711,\"user\":\"jdoe\"}}";
+        assert!(json.contains(expected_tail), "{}", json);
+        let pretty = pretty_record(&rec, &levels, "12");
+        let lines: Vec<&str> = pretty.split(EOL).collect();
+        assert_eq!("    request_id : 4711", lines[3]);
+        assert_eq!("    user       : jdoe", lines[4]);
+    }
+
+    #[test]
+    /// Tests truncation and reuse of timestamps for coarse clock granularities
+    fn test_clock_granularity() {
+        let levels = RecordLevelMap::default();
+        let rec = LocalRecordData::for_write(7, "main", RecordLevelId::Error, "src/main.rs", 42,
+                                             "disk full");
+        let fields = FormatSpec::from_str("$TimeStamp|$Time|$Message").unwrap();
+        let mut fmt = RecordFormat::new(u32::MAX, u32::MAX, RecordLayout::Plain,
+                                        "%S%.6f", "%d", "%S%.3f", fields);
+        fmt.set_clock_granularity(ClockGranularity::Second);
+        let secs = rec.timestamp().second();
+        let expected = format!("{:02}.000000|{:02}.000|disk full{}", secs, secs, EOL);
+        assert_eq!(expected, fmt.apply_to(&rec, &levels));
+        assert_eq!(expected, fmt.apply_to(&rec, &levels));
+        let fields = FormatSpec::from_str("$Message").unwrap();
+        let mut fmt = RecordFormat::new(u32::MAX, u32::MAX, RecordLayout::Json,
+                                        "", "", "", fields);
+        fmt.set_clock_granularity(ClockGranularity::Millisecond);
+        let ts = rec.timestamp();
+        let ts = ts.with_nanosecond(ts.nanosecond() / 1_000_000 * 1_000_000).unwrap();
+        let expected = format!("{{\"timestamp\":\"{}\"",
+                               ts.to_rfc3339_opts(SecondsFormat::Millis, false));
+        assert!(fmt.apply_to(&rec, &levels).starts_with(&expected));
+    }
+}
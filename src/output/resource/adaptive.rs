@@ -0,0 +1,154 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+
+//! Flush of the memory buffer adapted to the latency of the physical resource, as configured in
+//! a buffer policy.
+//! The time needed to write the buffered records is measured upon every flush. While the
+//! average latency per record stays below the policy's target, records are written immediately.
+//! While it exceeds the target, records are collected in batches, whose size doubles after every
+//! slow flush and halves after every flush taking less than half the target.
+
+use std::cmp::{max, min};
+use std::time::Duration;
+use crate::policies::BufferPolicy;
+
+// minimum size of a batch in bytes, smaller batches cause records to be written immediately
+const MIN_BATCH_SIZE: usize = 4096;
+// weight of the previous average latency relative to the latest measurement
+const AVERAGE_WEIGHT: u32 = 3;
+
+/// Runtime state of the adaptive flush for a resource.
+#[derive (Clone)]
+pub(crate) struct AdaptiveFlush {
+    // target latency per record written to the physical resource
+    target: Duration,
+    // maximum size of a batch in bytes, the initial buffer content size
+    max_batch_size: usize,
+    // moving average of the latency per record, None before the first flush
+    average: Option<Duration>,
+    // number of buffered bytes, that cause a flush
+    batch_size: usize
+}
+impl AdaptiveFlush {
+    /// Creates the runtime state for the adaptive flush of the given buffer policy.
+    /// Records are written immediately, until the first measurement exceeds the target latency.
+    ///
+    /// # Arguments
+    /// * `policy` - the buffer policy
+    ///
+    /// # Return values
+    /// the runtime state, **None** if the policy doesn't adapt the flush to the output latency
+    pub(crate) fn new(policy: &BufferPolicy) -> Option<AdaptiveFlush> {
+        if policy.adaptive_latency() == 0 { return None }
+        Some(AdaptiveFlush {
+            target: Duration::from_micros(policy.adaptive_latency()),
+            max_batch_size: max(MIN_BATCH_SIZE, policy.content_size()),
+            average: None,
+            batch_size: 0
+        })
+    }
+
+    /// Returns the number of buffered bytes, that currently cause a flush.
+    #[cfg(test)]
+    pub(crate) fn batch_size(&self) -> usize { self.batch_size }
+
+    /// Indicates whether the buffered records shall be written to the physical resource.
+    ///
+    /// # Arguments
+    /// * `buffered_bytes` - the number of bytes currently held in the memory buffer
+    #[inline]
+    pub(crate) fn flush_due(&self, buffered_bytes: usize) -> bool {
+        buffered_bytes >= self.batch_size
+    }
+
+    /// Updates the average latency with the duration of a flush and adjusts the batch size.
+    ///
+    /// # Arguments
+    /// * `elapsed` - the time needed to write the buffered records to the physical resource
+    /// * `record_count` - the number of records written
+    pub(crate) fn record_flush(&mut self, elapsed: Duration, record_count: usize) {
+        if record_count == 0 { return }
+        let latency = elapsed / min(record_count, u32::MAX as usize) as u32;
+        let average = match self.average {
+            Some(avg) => (avg * AVERAGE_WEIGHT + latency) / (AVERAGE_WEIGHT + 1),
+            None => latency
+        };
+        self.average = Some(average);
+        if average > self.target {
+            self.batch_size = min(self.max_batch_size,
+                                  max(MIN_BATCH_SIZE, self.batch_size.saturating_mul(2)));
+        } else if average * 2 <= self.target {
+            self.batch_size /= 2;
+            if self.batch_size < MIN_BATCH_SIZE { self.batch_size = 0; }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_flush() {
+        let mut pol = BufferPolicy::default();
+        assert!(AdaptiveFlush::new(&pol).is_none());
+        pol.set_adaptive_latency(100);
+        let mut af = AdaptiveFlush::new(&pol).unwrap();
+        // fast sink, records are written immediately
+        assert!(af.flush_due(0));
+        af.record_flush(Duration::from_micros(20), 1);
+        assert_eq!(0, af.batch_size());
+        // slow sink, batch size doubles with every slow flush up to the buffer content size
+        af.record_flush(Duration::from_micros(1000), 1);
+        assert_eq!(MIN_BATCH_SIZE, af.batch_size());
+        assert!(! af.flush_due(100));
+        assert!(af.flush_due(MIN_BATCH_SIZE));
+        af.record_flush(Duration::from_micros(1000), 1);
+        assert_eq!(2 * MIN_BATCH_SIZE, af.batch_size());
+        for _ in 0 .. 64 { af.record_flush(Duration::from_millis(1), 1); }
+        assert_eq!(pol.content_size(), af.batch_size());
+        // latency between half target and target keeps batch size
+        af.average = Some(Duration::from_micros(75));
+        af.record_flush(Duration::from_micros(7500), 100);
+        assert_eq!(pol.content_size(), af.batch_size());
+        // batching made the sink fast, batch size halves down to immediate writes
+        let mut prev_size = af.batch_size();
+        for _ in 0 .. 20 {
+            af.record_flush(Duration::from_micros(1000), 100);
+            assert!(af.batch_size() <= prev_size);
+            prev_size = af.batch_size();
+        }
+        assert_eq!(0, af.batch_size());
+    }
+}
@@ -0,0 +1,171 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+
+//! Suppression of duplicate records within the window configured in a buffer policy.
+//! A record is a duplicate, if it has the same call site and message as a record written
+//! within the window. The number of suppressed records is reported in a single summary record
+//! when the buffer is flushed.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+use crate::coalyxw;
+use crate::errorhandling::*;
+use crate::output::outputformat::OutputFormat;
+use crate::policies::BufferPolicy;
+use crate::record::recorddata::RecordData;
+
+// number of remembered records, above which outdated entries are removed
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Runtime state of the duplicate suppression for a resource.
+#[derive (Clone)]
+pub(crate) struct Deduplicator {
+    // name of the buffer policy
+    policy_name: String,
+    // length of the suppression window
+    window: Duration,
+    // hash of call site and message for all records written, with the time they were written
+    last_written: HashMap<u64, Instant>,
+    // number of records suppressed since the last flush
+    suppressed_count: u64,
+    // thread ID, thread name and output format of the first suppressed record, used for the
+    // summary record
+    summary_origin: Option<(u64, String, OutputFormat)>
+}
+impl Deduplicator {
+    /// Creates the runtime state for the duplicate suppression of the given buffer policy.
+    ///
+    /// # Arguments
+    /// * `policy` - the buffer policy
+    ///
+    /// # Return values
+    /// the runtime state, **None** if the policy doesn't suppress duplicates
+    pub(crate) fn new(policy: &BufferPolicy) -> Option<Deduplicator> {
+        if policy.duplicate_window() == 0 { return None }
+        Some(Deduplicator {
+            policy_name: policy.name().to_string(),
+            window: Duration::from_millis(policy.duplicate_window()),
+            last_written: HashMap::new(),
+            suppressed_count: 0,
+            summary_origin: None
+        })
+    }
+
+    /// Checks whether a record may be written.
+    /// Counts the record as suppressed, if a record with the same call site and message has
+    /// been written within the window.
+    ///
+    /// # Arguments
+    /// * `record` - the log or trace record
+    /// * `output_format` - the output format used for the record
+    /// * `now` - the current time
+    ///
+    /// # Return values
+    /// **true** if the record may be written, **false** if it must be suppressed
+    pub(crate) fn admit(&mut self,
+                        record: &dyn RecordData,
+                        output_format: &OutputFormat,
+                        now: Instant) -> bool {
+        let mut hasher = DefaultHasher::new();
+        record.source_fn().hash(&mut hasher);
+        record.line_nr().hash(&mut hasher);
+        record.message().hash(&mut hasher);
+        let key = hasher.finish();
+        if let Some(written) = self.last_written.get(&key) {
+            if now.duration_since(*written) < self.window {
+                if self.summary_origin.is_none() {
+                    self.summary_origin = Some((record.thread_id(),
+                                                record.thread_name().to_string(),
+                                                output_format.clone()));
+                }
+                self.suppressed_count += 1;
+                return false
+            }
+        }
+        if self.last_written.len() >= PRUNE_THRESHOLD {
+            let window = self.window;
+            self.last_written.retain(|_, w| now.duration_since(*w) < window);
+        }
+        self.last_written.insert(key, now);
+        true
+    }
+
+    /// Returns the data for the summary record, if records have been suppressed since the
+    /// last call, and resets the suppression counter.
+    ///
+    /// # Return values
+    /// summary message, thread ID, thread name and output format for the summary record
+    pub(crate) fn take_summary(&mut self) -> Option<(String, u64, String, OutputFormat)> {
+        let (tid, tname, ofmt) = self.summary_origin.take()?;
+        let msg = coalyxw!(W_BUFFER_DUPLICATES_SUPPRESSED, self.suppressed_count.to_string(),
+                           self.policy_name.clone()).localized_message();
+        self.suppressed_count = 0;
+        Some((msg, tid, tname, ofmt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Configuration;
+    use crate::record::RecordLevelId;
+    use crate::record::recorddata::LocalRecordData;
+
+    #[test]
+    fn test_deduplicator() {
+        let mut pol = BufferPolicy::default();
+        assert!(Deduplicator::new(&pol).is_none());
+        pol.set_duplicate_window(100);
+        let mut dedup = Deduplicator::new(&pol).unwrap();
+        let cfg = Configuration::default();
+        let ofmt = OutputFormat::from_desc(cfg.output_format(&None), cfg.date_time_formats(),
+                                           cfg.system_properties());
+        let rec1 = LocalRecordData::for_write(1, "main", RecordLevelId::Info, "a.rs", 10, "m");
+        let rec2 = LocalRecordData::for_write(2, "worker", RecordLevelId::Info, "a.rs", 10, "m");
+        let rec3 = LocalRecordData::for_write(1, "main", RecordLevelId::Info, "a.rs", 11, "m");
+        let start = Instant::now();
+        assert!(dedup.admit(&rec1, &ofmt, start));
+        assert!(! dedup.admit(&rec2, &ofmt, start + Duration::from_millis(50)));
+        assert!(dedup.admit(&rec3, &ofmt, start + Duration::from_millis(50)));
+        assert!(! dedup.admit(&rec1, &ofmt, start + Duration::from_millis(99)));
+        assert!(dedup.admit(&rec1, &ofmt, start + Duration::from_millis(100)));
+        let (msg, tid, tname, _) = dedup.take_summary().unwrap();
+        assert!(msg.starts_with("2 "));
+        assert_eq!(2, tid);
+        assert_eq!("worker", tname);
+        assert!(dedup.take_summary().is_none());
+    }
+}
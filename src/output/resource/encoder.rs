@@ -0,0 +1,164 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Transcoding of formatted output records for plain files with character encoding other
+//! than UTF-8.
+
+use std::borrow::Cow;
+use crate::config::resource::CharEncoding;
+
+// Byte order mark for UTF-16 little endian
+const UTF16LE_BOM: [u8; 2] = [0xff, 0xfe];
+
+// Replacement for characters not representable in target encoding
+const REPLACEMENT_CHAR: char = '?';
+
+/// Encoder stage for output records, converts UTF-8 encoded data to the target encoding.
+/// Multi-byte characters split across two write operations are handled correctly.
+pub(crate) struct Encoder {
+    // target encoding
+    encoding: CharEncoding,
+    // bytes of an incomplete UTF-8 sequence from the end of the last data chunk
+    pending: Vec<u8>
+}
+impl Encoder {
+    /// Creates an encoder for the specified target encoding.
+    /// 
+    /// # Arguments
+    /// * `encoding` - the target encoding
+    pub(crate) fn new(encoding: CharEncoding) -> Encoder {
+        Encoder { encoding, pending: Vec::new() }
+    }
+
+    /// Returns the target encoding.
+    #[cfg(feature="net")]
+    #[inline]
+    pub(crate) fn encoding(&self) -> CharEncoding { self.encoding }
+
+    /// Returns the byte order mark to write at the start of a file in the target encoding.
+    pub(crate) fn bom(&self) -> &'static [u8] {
+        match self.encoding {
+            CharEncoding::Utf16Le => &UTF16LE_BOM,
+            _ => &[]
+        }
+    }
+
+    /// Resets the encoder, any incomplete UTF-8 sequence is discarded.
+    pub(crate) fn reset(&mut self) { self.pending.clear(); }
+
+    /// Converts the specified UTF-8 encoded data to the target encoding.
+    /// Invalid UTF-8 sequences and characters not representable in the target encoding are
+    /// replaced by question marks.
+    /// 
+    /// # Arguments
+    /// * `data` - the UTF-8 encoded data
+    /// 
+    /// # Return values
+    /// the data in target encoding
+    pub(crate) fn encode<'a>(&mut self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.encoding == CharEncoding::Utf8 { return Cow::Borrowed(data) }
+        let mut input = std::mem::take(&mut self.pending);
+        input.extend_from_slice(data);
+        let mut result = Vec::<u8>::with_capacity(input.len() * 2);
+        let mut rest = input.as_slice();
+        while ! rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(s) => { self.push_str(s, &mut result); break },
+                Err(e) => {
+                    let (valid, invalid) = rest.split_at(e.valid_up_to());
+                    self.push_str(std::str::from_utf8(valid).unwrap(), &mut result);
+                    match e.error_len() {
+                        Some(n) => {
+                            self.push_char(REPLACEMENT_CHAR, &mut result);
+                            rest = &invalid[n..];
+                        },
+                        None => {
+                            // incomplete sequence at the end, wait for more data
+                            self.pending.extend_from_slice(invalid);
+                            break
+                        }
+                    }
+                }
+            }
+        }
+        Cow::Owned(result)
+    }
+
+    /// Appends the specified string in target encoding to a buffer.
+    fn push_str(&self, s: &str, buf: &mut Vec<u8>) {
+        for ch in s.chars() { self.push_char(ch, buf); }
+    }
+
+    /// Appends the specified character in target encoding to a buffer.
+    fn push_char(&self, ch: char, buf: &mut Vec<u8>) {
+        match self.encoding {
+            CharEncoding::Latin1 => {
+                let code = ch as u32;
+                buf.push(if code <= 0xff { code as u8 } else { REPLACEMENT_CHAR as u8 });
+            },
+            CharEncoding::Utf16Le => {
+                let mut units = [0u16; 2];
+                for unit in ch.encode_utf16(&mut units) {
+                    buf.extend_from_slice(&unit.to_le_bytes());
+                }
+            },
+            CharEncoding::Utf8 => {
+                let mut bytes = [0u8; 4];
+                buf.extend_from_slice(ch.encode_utf8(&mut bytes).as_bytes());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoder() {
+        let mut enc = Encoder::new(CharEncoding::Utf8);
+        assert!(enc.bom().is_empty());
+        assert_eq!("Grüße €".as_bytes(), enc.encode("Grüße €".as_bytes()).as_ref());
+        let mut enc = Encoder::new(CharEncoding::Latin1);
+        assert!(enc.bom().is_empty());
+        assert_eq!(b"Gr\xfc\xdfe ?", enc.encode("Grüße €".as_bytes()).as_ref());
+        // multi-byte character split across two chunks
+        let data = "aü".as_bytes();
+        assert_eq!(b"a", enc.encode(&data[..2]).as_ref());
+        assert_eq!(b"\xfc", enc.encode(&data[2..]).as_ref());
+        // invalid UTF-8
+        assert_eq!(b"a?b", enc.encode(b"a\xffb").as_ref());
+        let mut enc = Encoder::new(CharEncoding::Utf16Le);
+        assert_eq!(&[0xff, 0xfe], enc.bom());
+        assert_eq!(&[0x41, 0x00, 0xac, 0x20], enc.encode("A€".as_bytes()).as_ref());
+    }
+}
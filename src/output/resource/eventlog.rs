@@ -0,0 +1,183 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Output resources of type Windows event log.
+//! Records are reported to the Application log under the configured event source name.
+//! The event source is registered with the first record written, if registration fails,
+//! another attempt is made after a short interval.
+//! Coaly record levels are mapped to event log severities, levels emergency through error
+//! are reported as errors, level warning as warnings and all other levels as information.
+
+use std::ffi::OsStr;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::ptr;
+use std::time::{Duration, Instant};
+use winapi::shared::minwindef::WORD;
+use winapi::um::winbase::{DeregisterEventSource, RegisterEventSourceW, ReportEventW};
+use winapi::um::winnt::{HANDLE, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE,
+                        EVENTLOG_WARNING_TYPE};
+use crate::coalyxe;
+use crate::errorhandling::*;
+use crate::record::RecordLevelId;
+
+/// Specific data for physical resources of kind Windows event log.
+pub(crate) struct EventLogData {
+    // event source name, UTF-16 encoded and null terminated
+    source: Vec<u16>,
+    // event source name, for error messages
+    source_name: String,
+    // event source handle, if the source is currently registered
+    handle: Option<HANDLE>,
+    // time of last attempt to register the event source
+    last_register_attempt: Option<Instant>
+}
+impl EventLogData {
+    /// Creates a Windows event log resource.
+    /// The event source is not registered before the first record is written.
+    /// 
+    /// # Arguments
+    /// * `source_name` - the event source name
+    pub(crate) fn new(source_name: &str) -> EventLogData {
+        EventLogData {
+            source: wide_string(source_name),
+            source_name: source_name.to_string(),
+            handle: None,
+            last_register_attempt: None
+        }
+    }
+
+    /// Reports a formatted record to the event log.
+    /// The record is discarded, if the event source can't be registered.
+    /// 
+    /// # Arguments
+    /// * `level` - the record level
+    /// * `data` - the formatted record
+    /// 
+    /// # Errors
+    /// Returns an error structure if the event log rejects the record
+    pub(crate) fn write(&mut self,
+                        level: RecordLevelId,
+                        data: &[u8]) -> Result<(), CoalyException> {
+        if self.handle.is_none() && ! self.register() { return Ok(()) }
+        let text = wide_string(String::from_utf8_lossy(data).trim_end());
+        let mut strings = [text.as_ptr()];
+        let rc = unsafe {
+            ReportEventW(self.handle.unwrap(), event_type(level), 0, COALY_EVENT_ID,
+                         ptr::null_mut(), 1, 0, strings.as_mut_ptr(), ptr::null_mut())
+        };
+        if rc == 0 {
+            let err = io::Error::last_os_error();
+            return Err(coalyxe!(E_EVENTLOG_WRITE_ERR, self.source_name.clone(), err.to_string()))
+        }
+        Ok(())
+    }
+
+    /// Deregisters the event source.
+    pub(crate) fn close(&mut self) {
+        if let Some(h) = self.handle.take() {
+            unsafe { DeregisterEventSource(h); }
+        }
+    }
+
+    /// Registers the event source, unless the last attempt was made only recently.
+    /// 
+    /// # Return values
+    /// **true** if the event source is registered
+    fn register(&mut self) -> bool {
+        if let Some(t) = self.last_register_attempt {
+            if t.elapsed() < REGISTER_RETRY_INTERVAL { return false }
+        }
+        self.last_register_attempt = Some(Instant::now());
+        let h = unsafe { RegisterEventSourceW(ptr::null(), self.source.as_ptr()) };
+        if h.is_null() { return false }
+        self.handle = Some(h);
+        true
+    }
+}
+impl Drop for EventLogData {
+    fn drop(&mut self) { self.close(); }
+}
+
+/// Returns the event log severity for a record level.
+/// 
+/// # Arguments
+/// * `level` - the record level
+fn event_type(level: RecordLevelId) -> WORD {
+    match level {
+        RecordLevelId::Emergency | RecordLevelId::Alert |
+        RecordLevelId::Critical | RecordLevelId::Error => EVENTLOG_ERROR_TYPE,
+        RecordLevelId::Warning => EVENTLOG_WARNING_TYPE,
+        _ => EVENTLOG_INFORMATION_TYPE
+    }
+}
+
+/// Returns the null terminated UTF-16 representation of a string.
+/// 
+/// # Arguments
+/// * `s` - the string
+fn wide_string(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+}
+
+// event ID used for all records
+const COALY_EVENT_ID: u32 = 1;
+
+// minimum interval between two attempts to register the event source
+const REGISTER_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Tests mapping of record levels to event log severities
+    fn test_event_type() {
+        assert_eq!(EVENTLOG_ERROR_TYPE, event_type(RecordLevelId::Emergency));
+        assert_eq!(EVENTLOG_ERROR_TYPE, event_type(RecordLevelId::Alert));
+        assert_eq!(EVENTLOG_ERROR_TYPE, event_type(RecordLevelId::Critical));
+        assert_eq!(EVENTLOG_ERROR_TYPE, event_type(RecordLevelId::Error));
+        assert_eq!(EVENTLOG_WARNING_TYPE, event_type(RecordLevelId::Warning));
+        assert_eq!(EVENTLOG_INFORMATION_TYPE, event_type(RecordLevelId::Notice));
+        assert_eq!(EVENTLOG_INFORMATION_TYPE, event_type(RecordLevelId::Info));
+        assert_eq!(EVENTLOG_INFORMATION_TYPE, event_type(RecordLevelId::Debug));
+        assert_eq!(EVENTLOG_INFORMATION_TYPE, event_type(RecordLevelId::Function));
+        assert_eq!(EVENTLOG_INFORMATION_TYPE, event_type(RecordLevelId::Object));
+    }
+
+    #[test]
+    /// Tests conversion of strings to UTF-16
+    fn test_wide_string() {
+        assert_eq!(vec!(0x43u16, 0x6fu16, 0u16), wide_string("Co"));
+        assert_eq!(vec!(0u16), wide_string(""));
+    }
+}
@@ -33,28 +33,56 @@
 //! Output resources of type plain or memory mapped file.
 
 use chrono::{DateTime, Local, TimeZone};
+#[cfg(feature="mmfile")]
 use std::cmp::min;
 use std::fs::File;
 use std::io::Write;
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::{coalyxe, coalyxw};
 use crate::errorhandling::*;
-use crate::output::formatspec::FormatSpec;
+use crate::context::ContextMap;
+use crate::output::forecast::RolloverForecast;
+use crate::output::formatspec::{FileNameRules, FormatSpec};
+use crate::config::resource::{CharEncoding, FileSharing};
+#[cfg(feature="encryption")]
+use crate::output::encryption::{Cipher, FILE_KIND_PLAIN};
+#[cfg(all(feature="encryption", feature="mmfile"))]
+use crate::output::encryption::FILE_KIND_MAPPED;
+#[cfg(feature="net")]
+use crate::output::binaryfile::{append_frame, frame_size};
+#[cfg(feature="mmfile")]
+use crate::output::mappedfile::{retained_records, TickCalibration};
+use crate::output::probe::{probe_directory, ProbeResult, DEFAULT_MIN_FREE_SPACE};
+#[cfg(feature="mmfile")]
 use crate::output::recordbuffer::RecordBuffer;
 use crate::policies::*;
 use crate::record::originator::OriginatorInfo;
-use super::rollover::archive_resource;
+#[cfg(feature="net")]
+use crate::record::recorddata::RecordData;
+use crate::stats;
+use crate::util::{is_same_file, lock_file, shared_open_options, unlock_file};
+use super::encoder::Encoder;
+use super::rollover::{archive_resource, retention_candidates};
 
 /// Specific data for physical resources of kind plain file.
 pub(crate) struct FileData {
     // pure file name, without path
     name: String,
     // file handle
-    f: Option<File>,
+    f: Option<TrackedFile>,
     // meta data for rollover handling
     meta_data: RolloverMetaData,
     // number of bytes written to file
-    bytes_written: usize
+    bytes_written: usize,
+    // encoder for the file's character encoding
+    encoder: Encoder,
+    // indicates whether the file belongs to a thread or originator specific resource, such
+    // files are refused when the limit for open files is reached
+    limited: bool,
+    // header written at the start of binary files, **None** for text files
+    header: Option<Vec<u8>>
 }
 impl FileData {
     /// Creates descriptive data for a plain file.
@@ -64,18 +92,63 @@ impl FileData {
     /// * `output_dir` - the output directory path
     /// * `name_spec` - the file name specification, already optimized for process
     /// * `rollover_policy` - the rollover policy descriptor
+    /// * `name_rules` - the rules for variable values in file names
+    /// * `encoding` - the character encoding
     pub(crate) fn new(output_dir: &Path,
                       name_spec: FormatSpec,
-                      rollover_policy: &RolloverPolicy) -> Result<FileData, CoalyException> {
-        let meta_data = RolloverMetaData::new(output_dir, name_spec, rollover_policy, 0);
+                      rollover_policy: &RolloverPolicy,
+                      name_rules: &FileNameRules,
+                      encoding: CharEncoding) -> Result<FileData, CoalyException> {
+        let meta_data = RolloverMetaData::new(output_dir, name_spec, rollover_policy, 0,
+                                              name_rules);
         Ok(FileData {
                name: String::from(""),
                f: None,
                meta_data,
-               bytes_written: 0
+               bytes_written: 0,
+               encoder: Encoder::new(encoding),
+               limited: false,
+               header: None
            })
     }
 
+    /// Switches the file to binary format.
+    /// Records must be written as frames afterwards, character encoding is not applied.
+    ///
+    /// # Arguments
+    /// * `header` - the header to write at the start of each file
+    #[cfg(feature="net")]
+    pub(crate) fn set_binary_header(&mut self, header: Vec<u8>) {
+        self.encoder = Encoder::new(CharEncoding::Utf8);
+        self.header = Some(header);
+    }
+
+    /// Indicates, whether this file is written in binary format.
+    #[cfg(feature="net")]
+    #[inline]
+    pub(crate) fn is_binary(&self) -> bool { self.header.is_some() }
+
+    /// Encrypts the file content with the given cipher.
+    ///
+    /// # Arguments
+    /// * `cipher` - the encryption state
+    #[cfg(feature="encryption")]
+    pub(crate) fn set_cipher(&mut self, cipher: Cipher) { self.meta_data.cipher = Some(cipher); }
+
+    /// Writes a log or trace record as frame to the associated binary file.
+    ///
+    /// # Arguments
+    /// * `rec` - the log or trace record
+    ///
+    /// # Errors
+    /// Returns an error structure if the write operation fails
+    #[cfg(feature="net")]
+    pub(crate) fn write_frame(&mut self, rec: &dyn RecordData) -> Result<(), CoalyException> {
+        let mut frame = Vec::<u8>::with_capacity(frame_size(rec));
+        append_frame(rec, &mut frame);
+        self.write(&frame)
+    }
+
     /// Indicates, whether this file is specific for an originator.
     pub(crate) fn is_originator_specific(&self) -> bool {
         self.meta_data.name_spec.is_originator_specific()
@@ -88,7 +161,7 @@ impl FileData {
     /// * `orig_info` - the originator information
     pub(crate) fn originator_optimized_name(&self,
                                             orig_info: &OriginatorInfo) -> FormatSpec {
-        self.meta_data.name_spec.optimized_for_originator(orig_info)
+        self.meta_data.name_spec.optimized_for_originator(orig_info, &self.meta_data.name_rules)
     }
 
     /// Replaces the internal file name specification with the given value.
@@ -100,6 +173,22 @@ impl FileData {
         self.meta_data.name_spec = new_spec;
     }
 
+    /// Limits the file size, a rollover takes place when the limit is reached.
+    /// 
+    /// # Arguments
+    /// * `max_size` - the maximum file size in bytes, 0 for no limit
+    pub(crate) fn limit_size(&mut self, max_size: usize) {
+        self.meta_data.limit_size(max_size);
+    }
+
+    /// Sets the coordination mode for several processes writing to the file.
+    ///
+    /// # Arguments
+    /// * `sharing` - the sharing mode
+    pub(crate) fn set_sharing(&mut self, sharing: FileSharing) {
+        self.meta_data.sharing = sharing;
+    }
+
     /// Writes the given slice to the associated file.
     ///
     /// # Arguments
@@ -108,8 +197,20 @@ impl FileData {
     /// # Errors
     /// Returns an error structure if the write operation fails
     pub(crate) fn write(&mut self, data: &[u8]) -> Result<(), CoalyException> {
+        // switch to the file for the next time slice without waiting for the timer
+        if self.f.is_some() && self.meta_data.slice_end.is_some_and(|end| Local::now() >= end) {
+            self.close();
+        }
+        if self.meta_data.sharing.is_shared() { return self.write_shared(data) }
         if self.f.is_none() { self.open()?;  }
-        if let Err(m) = self.f.as_ref().unwrap().write_all(data) {
+        let data = self.encoder.encode(data);
+        #[cfg(feature="encryption")]
+        let data = match &self.meta_data.cipher {
+            Some(c) => std::borrow::Cow::Owned(c.seal_frame(&data)
+                                                .map_err(|e| encryption_error(&self.name, e))?),
+            None => data
+        };
+        if let Err(m) = self.f.as_mut().unwrap().write_all(&data) {
             return Err(coalyxe!(E_FILE_WRITE_ERR, self.name.to_string(), m.to_string()))
         }
         self.bytes_written += data.len();
@@ -120,6 +221,38 @@ impl FileData {
         Ok(())
     }    
 
+    /// Writes the given slice to a file shared with other processes.
+    /// The file is joined anew, if another process performed a rollover in the meantime.
+    ///
+    /// # Arguments
+    /// * `data` - the data to write
+    ///
+    /// # Errors
+    /// Returns an error structure if the lock file can't be locked or the write operation fails
+    fn write_shared(&mut self, data: &[u8]) -> Result<(), CoalyException> {
+        if self.f.is_none() { self.open()?; }
+        let mut lock = SharedFileLock::new(self.lock_path());
+        if self.meta_data.sharing == FileSharing::Lock { lock.acquire()?; }
+        if self.is_replaced() {
+            lock.acquire()?;
+            self.join()?;
+        }
+        let data = self.encoder.encode(data);
+        // a single write operation, the operating system appends it atomically
+        let f = self.f.as_mut().unwrap();
+        if let Err(m) = f.write_all(&data) {
+            return Err(coalyxe!(E_FILE_WRITE_ERR, self.name.to_string(), m.to_string()))
+        }
+        // the file size includes the records of all processes
+        self.bytes_written = f.metadata().map(|md| md.len() as usize)
+                                         .unwrap_or(self.bytes_written + data.len());
+        if self.meta_data.max_size > 0 && self.bytes_written >= self.meta_data.max_size {
+            lock.acquire()?;
+            return self.rollover_shared()
+        }
+        Ok(())
+    }
+
     /// Opens the associated file.
     /// It is guaranteed, that the structure's file handle is valid in case of success.
     ///
@@ -127,15 +260,59 @@ impl FileData {
     /// Returns an error structure if the output file can't be created
     fn open(&mut self) -> Result<(), CoalyException> {
         self.close();
-        self.name = self.meta_data.file_name();
-        self.f = Some(create_file(self.meta_data.output_dir(), &self.name)?);
+        self.meta_data.adopt_next_dir();
+        self.name = self.meta_data.start_file_name();
+        if self.limited { admit_limited_file(self.meta_data.output_dir(), &self.name)?; }
+        if self.meta_data.sharing.is_shared() {
+            let mut lock = SharedFileLock::new(self.lock_path());
+            lock.acquire()?;
+            return self.join()
+        }
+        self.f = Some(create_file(self.meta_data.output_dir(), &self.name, &self.file_header()?)?);
         Ok(())
     }
 
+    /// Opens the associated file shared with other processes in append mode.
+    /// The caller must hold the lock file, so that the file header is written only once.
+    ///
+    /// # Errors
+    /// Returns an error structure if the output file can't be opened
+    fn join(&mut self) -> Result<(), CoalyException> {
+        self.close();
+        let f = join_file(self.meta_data.output_dir(), &self.name, &self.file_header()?)?;
+        self.bytes_written = f.metadata().map(|md| md.len() as usize).unwrap_or(0);
+        self.f = Some(f);
+        Ok(())
+    }
+
+    /// Indicates whether the open file has been replaced by a rollover of another process.
+    fn is_replaced(&self) -> bool {
+        let f = match &self.f { Some(f) => f, None => return false };
+        let path_md = std::fs::metadata(self.meta_data.output_dir().join(&self.name));
+        match (f.metadata(), path_md) {
+            (Ok(open_md), Ok(path_md)) => ! is_same_file(&open_md, &path_md),
+            _ => true
+        }
+    }
+
+    /// Returns the path of the lock file coordinating processes sharing the file.
+    fn lock_path(&self) -> PathBuf {
+        self.meta_data.output_dir().join(format!(".{}.lock", self.name))
+    }
+
+    /// Returns the data to write at the start of the file.
+    ///
+    /// # Errors
+    /// Returns an error structure if the header could not be encrypted
+    fn file_header(&self) -> Result<Vec<u8>, CoalyException> {
+        plain_file_header(&self.meta_data, &self.name, &self.header, &self.encoder)
+    }
+
     /// Closes the associatedfile.
     /// It is guaranteed, that the structure's file handle is None after a call to this function.
     pub(crate) fn close(&mut self) {
         self.bytes_written = 0;
+        self.encoder.reset();
         if let Some(ref mut f) = &mut self.f {
             let _ = f.flush();
             let _ = f.sync_all();
@@ -153,12 +330,130 @@ impl FileData {
     pub(crate) fn rollover_if_due(&mut self,
                                   now: &DateTime<Local>) -> Result<(), CoalyException> {
         if self.meta_data.is_rollover_due(now) {
-            self.meta_data.determine_next_rollover();
+            self.meta_data.determine_next_rollover(now);
+            if self.meta_data.sharing.is_shared() { return self.locked_rollover() }
             return self.rollover()
         }
+        // file for the next time slice is opened with the next write
+        if self.f.is_some() && self.meta_data.is_slice_due(now) { self.close(); }
         Ok(())
     }
 
+    /// Creates descriptive data for a plain file specific for a remote originator.
+    /// Does not create the file yet.
+    ///
+    /// # Arguments
+    /// * `namespec` - name specification, optimized for originator
+    #[cfg(feature="net")]
+    pub(crate) fn for_originator(&self, namespec: FormatSpec) -> FileData {
+        let mut meta_data = self.meta_data.clone();
+        meta_data.name_spec = namespec;
+        FileData {
+            name: String::from(""),
+            f: None,
+            meta_data,
+            bytes_written: 0,
+            encoder: Encoder::new(self.encoder.encoding()),
+            limited: true,
+            header: self.header.clone()
+        }
+    }
+
+    /// Creates the file, if it hasn't been created yet.
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be created
+    pub(crate) fn create(&mut self) -> Result<(), CoalyException> {
+        if self.f.is_none() { self.open()?; }
+        Ok(())
+    }
+
+    /// Opens the file anew in append mode, e.g. after the process changed its user identity.
+    /// Has no effect, if the file hasn't been opened yet.
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be opened
+    pub(crate) fn reopen(&mut self) -> Result<(), CoalyException> {
+        if let Some(mut f) = self.f.take() {
+            let _ = f.flush();
+            let _ = f.sync_all();
+            drop(f);
+            self.f = Some(append_file(self.meta_data.output_dir(), &self.name)?);
+        }
+        Ok(())
+    }
+
+    /// Changes the directory, where the file is created.
+    /// Has no effect, if the file has already been created.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    pub(crate) fn relocate(&mut self, dir: &Path) {
+        if self.f.is_none() { self.meta_data.dir = dir.to_path_buf(); }
+    }
+
+    /// Changes the output directory for files created from now on.
+    /// An open file is continued in the current directory and archived there upon its next
+    /// rollover, the following file is created in the new directory.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    pub(crate) fn change_output_dir(&mut self, dir: &Path) {
+        self.meta_data.change_dir(dir, self.f.is_some());
+    }
+
+    /// Returns the full path name of the file.
+    pub(crate) fn file_path(&self) -> String {
+        self.meta_data.output_dir().join(self.meta_data.file_name()).to_string_lossy().to_string()
+    }
+
+    /// Tests the output directory of the file.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    pub(crate) fn probe(&self, label: &str) -> ProbeResult { self.meta_data.probe(label) }
+
+    /// Performs a rollover regardless of the rollover policy.
+    /// Has no effect, if the file has not been created yet.
+    ///
+    /// # Errors
+    /// Returns an error descriptor if any part of the rollover process fails
+    pub(crate) fn rollover_now(&mut self) -> Result<(), CoalyException> {
+        if self.f.is_none() { return Ok(()) }
+        if self.meta_data.sharing.is_shared() { return self.locked_rollover() }
+        self.rollover()
+    }
+
+    /// Returns the forecast for the next rollover of the file.
+    pub(crate) fn rollover_forecast(&self) -> RolloverForecast {
+        self.meta_data.forecast(&self.name, Some(self.bytes_written))
+    }
+
+    /// Performs a rollover of a file shared with other processes under protection of the
+    /// lock file.
+    /// Has no effect, if the file has not been opened yet.
+    ///
+    /// # Errors
+    /// Returns an error descriptor if the lock file can't be locked or any part of the
+    /// rollover process fails
+    fn locked_rollover(&mut self) -> Result<(), CoalyException> {
+        if self.f.is_none() { return Ok(()) }
+        let mut lock = SharedFileLock::new(self.lock_path());
+        lock.acquire()?;
+        self.rollover_shared()
+    }
+
+    /// Performs a rollover of a file shared with other processes.
+    /// The caller must hold the lock file. If another process performed the rollover already,
+    /// the new file is just joined.
+    ///
+    /// # Errors
+    /// Returns an error descriptor if any part of the rollover process fails
+    fn rollover_shared(&mut self) -> Result<(), CoalyException> {
+        if self.is_replaced() { return self.join() }
+        self.rollover()
+    }
+
     /// Performs a rollover.
     ///
     /// # Errors
@@ -167,37 +462,52 @@ impl FileData {
         // close current output file
         self.close();
         // archive current output file
-        let new_name = self.meta_data.file_name();
+        let new_name = self.meta_data.start_file_name();
         let dir = self.meta_data.output_dir();
-        if let Err(e) = archive_resource(dir, &self.name, &new_name, self.meta_data.name_spec(),
-                                         self.meta_data.keep_count(),
-                                         &self.meta_data.compression()) {
-            // archive operation failed, try to re-open old output file
-            let old_path = dir.join(&self.name);
-            let old_path_name = old_path.to_string_lossy().to_string();
-            match File::options().append(true).open(&old_path) {
-                Ok(f) => {
-                    // re-open old file succeeded
-                    self.f = Some(f);
-                    let new_path_name = dir.join(&new_name).to_string_lossy().to_string();
-                    let mut ex = coalyxw!(W_ROVR_USING_OLD, new_path_name, old_path_name);
-                    ex.set_cause(e);
-                    return Err(ex)
-                },
-                Err(e) => {
-                    // re-open old file failed
-                    return Err(coalyxe!(E_FILE_CRE_ERR, old_path_name, e.to_string()))
+        let warning = match archive_resource(dir, &self.name, &new_name,
+                                             self.meta_data.name_spec(),
+                                             self.meta_data.keep_count(),
+                                             self.meta_data.max_total_size(),
+                                             &self.meta_data.compression(),
+                                             self.meta_data.checksums()) {
+            Ok(w) => w,
+            Err(e) => {
+                // archive operation failed, try to re-open old output file
+                let old_path = dir.join(&self.name);
+                let old_path_name = old_path.to_string_lossy().to_string();
+                match shared_open_options().append(true).open(&old_path) {
+                    Ok(f) => {
+                        // re-open old file succeeded
+                        self.f = Some(TrackedFile::new(f));
+                        let new_path_name = dir.join(&new_name).to_string_lossy().to_string();
+                        let mut ex = coalyxw!(W_ROVR_USING_OLD, new_path_name, old_path_name);
+                        ex.set_cause(e);
+                        return Err(ex)
+                    },
+                    Err(e) => {
+                        // re-open old file failed
+                        return Err(coalyxe!(E_FILE_CRE_ERR, old_path_name, e.to_string()))
+                    }
                 }
             }
-        }
+        };
+        stats::rollover_done();
         self.name = new_name;
-        self.f = Some(create_file(dir, &self.name)?);
+        self.meta_data.adopt_next_dir();
+        if self.meta_data.sharing.is_shared() {
+            self.join()?;
+        } else {
+            self.f = Some(create_file(self.meta_data.output_dir(), &self.name,
+                                      &self.file_header()?)?);
+        }
+        if let Some(w) = warning { return Err(w) }
         Ok(())
     }
 }
 
 /// Specific data for templates of plain file physical resources.
-pub(crate) struct FileTemplateData(RolloverMetaData);
+/// Holds the binary file header in addition to the rollover meta data and character encoding.
+pub(crate) struct FileTemplateData(RolloverMetaData, CharEncoding, Option<Vec<u8>>);
 impl FileTemplateData {
     /// Creates template for a plain file.
     ///
@@ -205,14 +515,34 @@ impl FileTemplateData {
     /// * `output_dir` - the output directory path
     /// * `name_spec` - the file name specification, already optimized for process
     /// * `rollover_policy` - the rollover policy descriptor
+    /// * `name_rules` - the rules for variable values in file names
+    /// * `encoding` - the character encoding
     pub(crate) fn new(output_dir: &Path,
                       name_spec: FormatSpec,
-                      rollover_policy: &RolloverPolicy) -> FileTemplateData {
-        FileTemplateData {
-            0: RolloverMetaData::new(output_dir, name_spec, rollover_policy, 0)
-        }
+                      rollover_policy: &RolloverPolicy,
+                      name_rules: &FileNameRules,
+                      encoding: CharEncoding) -> FileTemplateData {
+        FileTemplateData(RolloverMetaData::new(output_dir, name_spec, rollover_policy, 0,
+                                               name_rules), encoding, None)
+    }
+
+    /// Switches files created from this template to binary format.
+    ///
+    /// # Arguments
+    /// * `header` - the header to write at the start of each file
+    #[cfg(feature="net")]
+    pub(crate) fn set_binary_header(&mut self, header: Vec<u8>) {
+        self.1 = CharEncoding::Utf8;
+        self.2 = Some(header);
     }
 
+    /// Encrypts the content of files created from this template with the given cipher.
+    ///
+    /// # Arguments
+    /// * `cipher` - the encryption state
+    #[cfg(feature="encryption")]
+    pub(crate) fn set_cipher(&mut self, cipher: Cipher) { self.0.cipher = Some(cipher); }
+
     /// Creates a final resource from this template.
     ///
     /// # Arguments
@@ -222,11 +552,16 @@ impl FileTemplateData {
     /// final file resource
     pub(crate) fn instantiate(&self,
                               namespec: FormatSpec) -> Result<FileData, CoalyException> {
-        let name = namespec.to_file_name();
-        let f = create_file(self.0.output_dir(), &name)?;
         let mut meta_data = self.0.clone();
         meta_data.name_spec = namespec;
-        Ok(FileData { name, f: Some(f), meta_data, bytes_written: 0 })
+        let name = meta_data.start_file_name();
+        let encoder = Encoder::new(self.1);
+        let header = self.2.clone();
+        admit_limited_file(meta_data.output_dir(), &name)?;
+        let f = create_file(meta_data.output_dir(), &name,
+                            &plain_file_header(&meta_data, &name, &header, &encoder)?)?;
+        Ok(FileData { name, f: Some(f), meta_data, bytes_written: 0, encoder, limited: true,
+                      header })
     }
 
     /// Creates a thread-specific template from this template.
@@ -241,7 +576,7 @@ impl FileTemplateData {
                                  namespec: FormatSpec) -> FileTemplateData {
         let mut opt_meta_data = self.0.clone();
         opt_meta_data.name_spec = namespec;
-        FileTemplateData { 0: opt_meta_data }
+        FileTemplateData(opt_meta_data, self.1, self.2.clone())
     }
 
     /// Indicates, whether this template is specific for an originator.
@@ -263,6 +598,34 @@ impl FileTemplateData {
         self.0.name_spec = new_spec;
     }
 
+    /// Limits the size of files created from this template, a rollover takes place when the
+    /// limit is reached.
+    /// 
+    /// # Arguments
+    /// * `max_size` - the maximum file size in bytes, 0 for no limit
+    pub(crate) fn limit_size(&mut self, max_size: usize) {
+        self.0.limit_size(max_size);
+    }
+
+    /// Sets the coordination mode for several processes writing to files created from this
+    /// template.
+    ///
+    /// # Arguments
+    /// * `sharing` - the sharing mode
+    pub(crate) fn set_sharing(&mut self, sharing: FileSharing) { self.0.sharing = sharing; }
+
+    /// Changes the output directory for files created from this template.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    pub(crate) fn change_output_dir(&mut self, dir: &Path) { self.0.change_dir(dir, false); }
+
+    /// Tests the output directory of files created from this template.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    pub(crate) fn probe(&self, label: &str) -> ProbeResult { self.0.probe(label) }
+
     /// Returns the file name specification with all originator specific variable items
     /// replaced with values from given originator information structure.
     /// 
@@ -270,7 +633,7 @@ impl FileTemplateData {
     /// * `orig_info` - the originator information
     pub(crate) fn originator_optimized_name(&self,
                                             orig_info: &OriginatorInfo) -> FormatSpec {
-        self.0.name_spec.optimized_for_originator(orig_info)
+        self.0.name_spec.optimized_for_originator(orig_info, &self.0.name_rules)
     }
 
     /// Returns the file name specification with all thread specific variable items
@@ -279,23 +642,30 @@ impl FileTemplateData {
     /// # Arguments
     /// * `thread_id` - the thread ID
     /// * `thread_name` - the thread name
+    /// * `context` - the thread's context map
     pub(crate) fn thread_optimized_name(&self,
                                         thread_id: u64,
-                                        thread_name: &str) -> FormatSpec {
-        self.0.name_spec.optimized_for_thread(thread_id, thread_name)
+                                        thread_name: &str,
+                                        context: Option<&ContextMap>) -> FormatSpec {
+        self.0.name_spec.optimized_for_thread(thread_id, thread_name, context,
+                                              &self.0.name_rules)
     }
 }
 
 /// Specific data for physical resources of kind memory mapped file.
 /// 
+#[cfg(feature="mmfile")]
 pub(crate) struct MemMappedFileData {
     // pure file name without path
     name: String,
-    // buffer wrapped around memory map
-    rec_buffer: RecordBuffer,
+    // buffer wrapped around memory map, None if the file hasn't been created yet
+    rec_buffer: Option<RecordBuffer>,
     // meta data for rollover handling
-    meta_data: RolloverMetaData
+    meta_data: RolloverMetaData,
+    // calibration for tick timestamps, None if records contain wall clock timestamps
+    calibration: Option<TickCalibration>
 }
+#[cfg(feature="mmfile")]
 impl MemMappedFileData {
     /// Creates data for a memory mapped file.
     ///
@@ -304,22 +674,121 @@ impl MemMappedFileData {
     /// * `name_spec` - the file name specification, already optimized for process
     /// * `file_size` - the size of the backing file
     /// * `rollover_policy` - the rollover policy descriptor
+    /// * `name_rules` - the rules for variable values in file names
+    /// * `calibration` - the calibration for tick timestamps, **None** for wall clock timestamps
+    /// * `ring` - indicates whether the file wraps around instead of rolling over
+    /// * `deferred` - indicates whether to defer the creation of the file until method create
+    ///   is called
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(output_dir: &Path,
                       name_spec: FormatSpec,
                       file_size: usize,
-                      rollover_policy: &RolloverPolicy) -> Result<MemMappedFileData, CoalyException> {
+                      rollover_policy: &RolloverPolicy,
+                      name_rules: &FileNameRules,
+                      calibration: Option<TickCalibration>,
+                      ring: bool,
+                      deferred: bool) -> Result<MemMappedFileData, CoalyException> {
         let name = name_spec.to_file_name();
-        let f_path = output_dir.join(&name);
         let f_size = min(MIN_FILE_SIZE, file_size);
+        let mut meta_data = RolloverMetaData::new(output_dir, name_spec, rollover_policy, f_size,
+                                                  name_rules);
+        meta_data.ring = ring;
+        let mut mmf = MemMappedFileData {
+                          name,
+                          rec_buffer: None,
+                          meta_data,
+                          calibration
+                      };
+        if ! deferred { mmf.create()?; }
+        Ok(mmf)
+    }
+
+    /// Creates the memory mapped file, if it hasn't been created yet.
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be created
+    pub(crate) fn create(&mut self) -> Result<(), CoalyException> {
+        if self.rec_buffer.is_some() { return Ok(()) }
+        self.meta_data.adopt_next_dir();
+        self.name = self.meta_data.start_file_name();
+        let f_path = self.meta_data.output_dir().join(&self.name);
+        let f_size = self.meta_data.file_size;
         let max_rec_count = f_size >> 5;
-        let rec_buffer = RecordBuffer::backed_by_file(&f_path, f_size, max_rec_count)?;
-        Ok(MemMappedFileData {
-               name,
-               rec_buffer,
-               meta_data: RolloverMetaData::new(output_dir, name_spec, rollover_policy, f_size)
-        })
+        let header = mapped_file_header(&self.meta_data, self.calibration);
+        self.rec_buffer = Some(map_file(&self.meta_data, &f_path, f_size, max_rec_count,
+                                        &header)?);
+        Ok(())
+    }
+
+    /// Encrypts the records written to the file with the given cipher.
+    /// Must be called before the file is created.
+    ///
+    /// # Arguments
+    /// * `cipher` - the encryption state
+    #[cfg(feature="encryption")]
+    pub(crate) fn set_cipher(&mut self, cipher: Cipher) { self.meta_data.cipher = Some(cipher); }
+
+    /// Maps the file anew, e.g. after the process changed its user identity.
+    /// Has no effect, if the file hasn't been created yet.
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be mapped
+    pub(crate) fn reopen(&mut self) -> Result<(), CoalyException> {
+        let f_path = self.meta_data.output_dir().join(&self.name);
+        match self.rec_buffer.as_mut() {
+            Some(buf) => buf.remap(&f_path),
+            None => Ok(())
+        }
+    }
+
+    /// Creates a memory mapped file specific for a remote originator.
+    ///
+    /// # Arguments
+    /// * `namespec` - name specification, optimized for originator
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be created
+    #[cfg(feature="net")]
+    pub(crate) fn for_originator(&self,
+                                 namespec: FormatSpec) -> Result<MemMappedFileData, CoalyException> {
+        let mut meta_data = self.meta_data.clone();
+        meta_data.name_spec = namespec;
+        let mut mmf = MemMappedFileData { name: String::from(""), rec_buffer: None, meta_data,
+                                          calibration: self.calibration };
+        mmf.create()?;
+        Ok(mmf)
+    }
+
+    /// Changes the directory, where the file is created.
+    /// Has no effect, if the file has already been created.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    pub(crate) fn relocate(&mut self, dir: &Path) {
+        if self.rec_buffer.is_none() { self.meta_data.dir = dir.to_path_buf(); }
     }
 
+    /// Changes the output directory for files created from now on.
+    /// An existing file is continued in the current directory and archived there upon its next
+    /// rollover, the following file is created in the new directory.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    pub(crate) fn change_output_dir(&mut self, dir: &Path) {
+        self.meta_data.change_dir(dir, self.rec_buffer.is_some());
+    }
+
+    /// Returns the full path name of the file.
+    pub(crate) fn file_path(&self) -> String {
+        self.meta_data.output_dir().join(&self.name).to_string_lossy().to_string()
+    }
+
+    /// Tests the output directory of the file.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    pub(crate) fn probe(&self, label: &str) -> ProbeResult { self.meta_data.probe(label) }
+
     /// Indicates, whether this file is specific for an originator.
     pub(crate) fn is_originator_specific(&self) -> bool {
         self.meta_data.name_spec.is_originator_specific()
@@ -332,7 +801,7 @@ impl MemMappedFileData {
     /// * `orig_info` - the originator information
     pub(crate) fn originator_optimized_name(&self,
                                             orig_info: &OriginatorInfo) -> FormatSpec {
-        self.meta_data.name_spec.optimized_for_originator(orig_info)
+        self.meta_data.name_spec.optimized_for_originator(orig_info, &self.meta_data.name_rules)
     }
 
     /// Replaces the internal file name specification with the given value.
@@ -350,11 +819,23 @@ impl MemMappedFileData {
     /// * `data` - the data to write
     /// 
     /// # Errors
-    /// Returns an error structure if the write operation fails
-    pub(crate) fn write_record(&mut self, s: &str) { self.rec_buffer.write(s); }
+    /// Returns an error structure if the record could not be encrypted
+    pub(crate) fn write_record(&mut self, s: &str) -> Result<(), CoalyException> {
+        if let Some(ref mut buf) = self.rec_buffer {
+            #[cfg(feature="encryption")]
+            if let Some(c) = &self.meta_data.cipher {
+                buf.write(&c.seal_hex(s).map_err(|e| encryption_error(&self.name, e))?);
+                return Ok(())
+            }
+            buf.write(s);
+        }
+        Ok(())
+    }
 
     /// Closes the memory mapped file.
-    pub(crate) fn close(&mut self) { self.rec_buffer.close(); }
+    pub(crate) fn close(&mut self) {
+        if let Some(ref mut buf) = self.rec_buffer { buf.close(); }
+    }
 
     /// Performs a rollover if it is due.
     /// 
@@ -362,13 +843,35 @@ impl MemMappedFileData {
     /// * `now` - current timestamp
     pub(crate) fn rollover_if_due(&mut self,
                                   now: &DateTime<Local>) -> Result<(), CoalyException> {
+        if self.meta_data.ring { return Ok(()) }
         if self.meta_data.is_rollover_due(now) {
-            self.meta_data.determine_next_rollover();
+            self.meta_data.determine_next_rollover(now);
+            if self.rec_buffer.is_none() { return Ok(()) }
             return self.rollover()
         }
+        if self.rec_buffer.is_some() && self.meta_data.is_slice_due(now) {
+            self.close();
+            self.rec_buffer = None;
+            return self.create()
+        }
         Ok(())
     }
 
+    /// Performs a rollover regardless of the rollover policy.
+    /// Has no effect for files in ring mode.
+    ///
+    /// # Errors
+    /// Returns an error descriptor if any part of the rollover process fails
+    pub(crate) fn rollover_now(&mut self) -> Result<(), CoalyException> {
+        if self.rec_buffer.is_none() || self.meta_data.ring { return Ok(()) }
+        self.rollover()
+    }
+
+    /// Returns the forecast for the next rollover of the memory mapped file.
+    pub(crate) fn rollover_forecast(&self) -> RolloverForecast {
+        self.meta_data.forecast(&self.name, None)
+    }
+
     /// Performs a rollover.
     ///
     /// # Errors
@@ -377,29 +880,41 @@ impl MemMappedFileData {
         // close current file
         self.close();
         // archive current file
-        let new_name = self.meta_data.file_name();
+        let new_name = self.meta_data.start_file_name();
         let dir = self.meta_data.output_dir();
-        if let Err(e) = archive_resource(dir, &self.name, &new_name, self.meta_data.name_spec(),
-                                         self.meta_data.keep_count(),
-                                         &self.meta_data.compression()) {
-            // archive operation failed, try to re-open old output file
-            let old_path = dir.join(&self.name);
-            let old_path_name = old_path.to_string_lossy().to_string();
-            self.rec_buffer.reopen(&old_path, false)?;
-            // re-open old file succeeded
-            let new_path_name = dir.join(&new_name).to_string_lossy().to_string();
-            let mut ex = coalyxw!(W_ROVR_USING_OLD, new_path_name, old_path_name);
-            ex.set_cause(e);
-            return Err(ex)
-        }
-        self.rec_buffer.reopen(&dir.join(&new_name), true)?;
+        let warning = match archive_resource(dir, &self.name, &new_name,
+                                             self.meta_data.name_spec(),
+                                             self.meta_data.keep_count(),
+                                             self.meta_data.max_total_size(),
+                                             &self.meta_data.compression(),
+                                             self.meta_data.checksums()) {
+            Ok(w) => w,
+            Err(e) => {
+                // archive operation failed, try to re-open old output file
+                let old_path = dir.join(&self.name);
+                let old_path_name = old_path.to_string_lossy().to_string();
+                if let Some(ref mut buf) = self.rec_buffer { buf.reopen(&old_path, false)?; }
+                // re-open old file succeeded
+                let new_path_name = dir.join(&new_name).to_string_lossy().to_string();
+                let mut ex = coalyxw!(W_ROVR_USING_OLD, new_path_name, old_path_name);
+                ex.set_cause(e);
+                return Err(ex)
+            }
+        };
+        self.meta_data.adopt_next_dir();
+        let new_path = self.meta_data.output_dir().join(&new_name);
+        if let Some(ref mut buf) = self.rec_buffer { buf.reopen(&new_path, true)?; }
+        stats::rollover_done();
         self.name = new_name;
+        if let Some(w) = warning { return Err(w) }
         Ok(())
     }
 }
 
 /// Specific data for templates of memory mapped file physical resources.
-pub(crate) struct MemMappedFileTemplateData(RolloverMetaData);
+#[cfg(feature="mmfile")]
+pub(crate) struct MemMappedFileTemplateData(RolloverMetaData, Option<TickCalibration>);
+#[cfg(feature="mmfile")]
 impl MemMappedFileTemplateData {
     /// Creates template for a memory mapped file.
     ///
@@ -408,15 +923,42 @@ impl MemMappedFileTemplateData {
     /// * `name_spec` - the file name specification, already optimized for process
     /// * `rollover_policy` - the rollover policy descriptor
     /// * `file_size` - the size of the backing file
+    /// * `name_rules` - the rules for variable values in file names
+    /// * `calibration` - the calibration for tick timestamps, **None** for wall clock timestamps
+    /// * `ring` - indicates whether the files wrap around instead of rolling over
     pub(crate) fn new(output_dir: &Path,
                       name_spec: FormatSpec,
                       file_size: usize,
-                      rollover_policy: &RolloverPolicy) -> MemMappedFileTemplateData {
-        MemMappedFileTemplateData {
-            0: RolloverMetaData::new(output_dir, name_spec, rollover_policy, file_size)
-        }
+                      rollover_policy: &RolloverPolicy,
+                      name_rules: &FileNameRules,
+                      calibration: Option<TickCalibration>,
+                      ring: bool) -> MemMappedFileTemplateData {
+        let mut meta_data = RolloverMetaData::new(output_dir, name_spec, rollover_policy,
+                                                  file_size, name_rules);
+        meta_data.ring = ring;
+        MemMappedFileTemplateData(meta_data, calibration)
     }
 
+    /// Encrypts the records written to files created from this template with the given
+    /// cipher.
+    ///
+    /// # Arguments
+    /// * `cipher` - the encryption state
+    #[cfg(feature="encryption")]
+    pub(crate) fn set_cipher(&mut self, cipher: Cipher) { self.0.cipher = Some(cipher); }
+
+    /// Tests the output directory of files created from this template.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    pub(crate) fn probe(&self, label: &str) -> ProbeResult { self.0.probe(label) }
+
+    /// Changes the output directory for files created from this template.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    pub(crate) fn change_output_dir(&mut self, dir: &Path) { self.0.change_dir(dir, false); }
+
     /// Creates a thread specific resource from this template.
     ///
     /// # Arguments
@@ -426,18 +968,20 @@ impl MemMappedFileTemplateData {
     /// thread specific file resource
     pub(crate) fn instantiate(&self,
                               namespec: FormatSpec) -> Result<MemMappedFileData, CoalyException> {
-        let name = namespec.to_file_name();
+        let mut meta_data = self.0.clone();
+        meta_data.name_spec = namespec;
+        let name = meta_data.start_file_name();
         let f_path = self.0.dir.join(&name);
         let f_size = self.0.file_size;
         let buf_content_size = f_size - 32;
         let max_rec_count = buf_content_size >> 5;
-        let rec_buffer = RecordBuffer::backed_by_file(&f_path, f_size, max_rec_count)?;
-        let mut meta_data = self.0.clone();
-        meta_data.name_spec = namespec;
+        let header = mapped_file_header(&meta_data, self.1);
+        let rec_buffer = map_file(&meta_data, &f_path, f_size, max_rec_count, &header)?;
         Ok(MemMappedFileData {
                name,
-               rec_buffer,
-               meta_data
+               rec_buffer: Some(rec_buffer),
+               meta_data,
+               calibration: self.1
         })
     }
 
@@ -453,7 +997,7 @@ impl MemMappedFileTemplateData {
                                  namespec: FormatSpec) -> MemMappedFileTemplateData {
         let mut opt_meta_data = self.0.clone();
         opt_meta_data.name_spec = namespec;
-        MemMappedFileTemplateData { 0: opt_meta_data }
+        MemMappedFileTemplateData(opt_meta_data, self.1)
     }
 
     /// Indicates, whether this template is specific for an originator.
@@ -482,7 +1026,7 @@ impl MemMappedFileTemplateData {
     /// * `orig_info` - the originator information
     pub(crate) fn originator_optimized_name(&self,
                                             orig_info: &OriginatorInfo) -> FormatSpec {
-        self.0.name_spec.optimized_for_originator(orig_info)
+        self.0.name_spec.optimized_for_originator(orig_info, &self.0.name_rules)
     }
 
     /// Returns the file name specification with all thread specific variable items
@@ -491,10 +1035,13 @@ impl MemMappedFileTemplateData {
     /// # Arguments
     /// * `thread_id` - the thread ID
     /// * `thread_name` - the thread name
+    /// * `context` - the thread's context map
     pub(crate) fn thread_optimized_name(&self,
                                         thread_id: u64,
-                                        thread_name: &str) -> FormatSpec {
-        self.0.name_spec.optimized_for_thread(thread_id, thread_name)
+                                        thread_name: &str,
+                                        context: Option<&ContextMap>) -> FormatSpec {
+        self.0.name_spec.optimized_for_thread(thread_id, thread_name, context,
+                                              &self.0.name_rules)
     }
 }
 
@@ -503,6 +1050,8 @@ impl MemMappedFileTemplateData {
 struct RolloverMetaData {
     // output directory path
     dir: PathBuf,
+    // output directory for the next file, if the directory was changed while a file was open
+    next_dir: Option<PathBuf>,
     // file name specification from system configuration
     name_spec: FormatSpec,
     // fix file size, for memory mapped files only (0 for plain files)
@@ -512,7 +1061,19 @@ struct RolloverMetaData {
     // rollover policy
     rollover_policy: RolloverPolicy,
     // timestamp for next rollover of the file
-    next_rovr_ts: DateTime<Local>
+    next_rovr_ts: DateTime<Local>,
+    // end of the time slice of the active file, None if the file name has no time slice
+    slice_end: Option<DateTime<Local>>,
+    // rules for variable values in file names
+    name_rules: FileNameRules,
+    // indicates whether a memory mapped file wraps around instead of rolling over
+    #[cfg(feature="mmfile")]
+    ring: bool,
+    // coordination of several processes writing to a plain file
+    sharing: FileSharing,
+    // encryption state, None if the file content is not encrypted
+    #[cfg(feature="encryption")]
+    cipher: Option<Cipher>
 }
 impl RolloverMetaData {
     /// Creates rollover meta data for a file.
@@ -522,10 +1083,12 @@ impl RolloverMetaData {
     /// * `name_spec` - the file name specification
     /// * `rollover_policy` - the rollover policy descriptor
     /// * `file_size` - the file size (memory mapped files only, 0 for plain files)
+    /// * `name_rules` - the rules for variable values in file names
     fn new(output_dir: &Path,
            name_spec: FormatSpec,
            rollover_policy: &RolloverPolicy,
-           file_size: usize) -> RolloverMetaData {
+           file_size: usize,
+           name_rules: &FileNameRules) -> RolloverMetaData {
         let mut max_size: usize = 0;
         let mut next_rovr_ts = Local.ymd(2200, 12, 31).and_hms(23, 59, 59);
         match rollover_policy.condition() {
@@ -533,22 +1096,74 @@ impl RolloverMetaData {
             RolloverCondition::TimeElapsed(i) => {
                 next_rovr_ts = i.next_elapse(&Local::now())
             },
+            RolloverCondition::Scheduled(c) => next_rovr_ts = c.next_elapse(&Local::now()),
             _ => ()
         }
         RolloverMetaData {
             dir: output_dir.to_path_buf(),
+            next_dir: None,
             name_spec,
             file_size,
             max_size,
             rollover_policy: rollover_policy.clone(),
-            next_rovr_ts
+            next_rovr_ts,
+            slice_end: None,
+            name_rules: name_rules.clone(),
+            #[cfg(feature="mmfile")]
+            ring: false,
+            sharing: FileSharing::Exclusive,
+            #[cfg(feature="encryption")]
+            cipher: None
         }
     }
 
+    /// Limits the file size in addition to the rollover policy's condition.
+    /// If the rollover policy is size based as well, the smaller size applies.
+    /// 
+    /// # Arguments
+    /// * `max_size` - the maximum file size in bytes, 0 for no limit
+    fn limit_size(&mut self, max_size: usize) {
+        if max_size == 0 { return }
+        if self.max_size == 0 || max_size < self.max_size { self.max_size = max_size; }
+    }
+
     /// Returns the output directory
     #[inline]
     fn output_dir(&self) -> &PathBuf { &self.dir }
 
+    /// Changes the output directory for files created from now on.
+    /// If a file is open, it remains in the current directory until it is closed or rolled
+    /// over.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    /// * `file_open` - indicates whether a file is currently open
+    fn change_dir(&mut self, dir: &Path, file_open: bool) {
+        if file_open {
+            self.next_dir = Some(dir.to_path_buf());
+        } else {
+            self.dir = dir.to_path_buf();
+            self.next_dir = None;
+        }
+    }
+
+    /// Switches to the output directory set while a file was open, if any.
+    /// To be called when the next file is about to be created.
+    fn adopt_next_dir(&mut self) {
+        if let Some(dir) = self.next_dir.take() { self.dir = dir; }
+    }
+
+    /// Tests the output directory, the free disk space must suffice for the next file.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    fn probe(&self, label: &str) -> ProbeResult {
+        let min_free_space = if self.file_size > 0 { self.file_size as u64 }
+                             else if self.max_size > 0 { self.max_size as u64 }
+                             else { DEFAULT_MIN_FREE_SPACE };
+        probe_directory(label, &self.dir, min_free_space)
+    }
+
     /// Returns the name specification from system configuration
     #[inline]
     fn name_spec(&self) -> &FormatSpec { &self.name_spec }
@@ -557,6 +1172,20 @@ impl RolloverMetaData {
     #[inline]
     fn file_name(&self) -> String { self.name_spec.to_file_name() }
 
+    /// Returns the file name from name specification and current timestamp for a file to be
+    /// created, and registers the end of the file's time slice.
+    fn start_file_name(&mut self) -> String {
+        let now = Local::now();
+        self.slice_end = self.name_spec.time_slice_end(&now);
+        self.name_spec.to_file_name_at(&now)
+    }
+
+    /// Indicates whether the time slice of the active file has ended.
+    #[inline]
+    fn is_slice_due(&self, now: &DateTime<Local>) -> bool {
+        self.slice_end.is_some_and(|end| *now >= end)
+    }
+
     /// Returns the compression algorithm to use for rollover files
     #[inline]
     fn compression(&self) -> CompressionAlgorithm { self.rollover_policy.compression() }
@@ -565,19 +1194,159 @@ impl RolloverMetaData {
     #[inline]
     fn keep_count(&self) -> u32 { self.rollover_policy.keep_count() }
 
+    /// Indicates whether checksum files shall be written for rollover files
+    #[inline]
+    fn checksums(&self) -> bool { self.rollover_policy.checksums() }
+
+    /// Returns the maximum total size of all rollover files, 0 if unlimited
+    #[inline]
+    fn max_total_size(&self) -> usize { self.rollover_policy.max_total_size() }
+
     /// Indicates whether a rollover must be executed.
     #[inline]
     fn is_rollover_due(&self, now: &DateTime<Local>) -> bool {
         now.timestamp() >= self.next_rovr_ts.timestamp()
     }
 
-    /// Determines time stamp for next rollover
-    #[inline]
-    fn determine_next_rollover(&mut self) {
-        if let RolloverCondition::TimeElapsed(intvl) = self.rollover_policy.condition() {
-            self.next_rovr_ts = intvl.next_elapse(&self.next_rovr_ts);
+    /// Determines time stamp for next rollover.
+    /// If the process was suspended past one or more scheduled rollovers, the missed elapses
+    /// are skipped. Hence only a single catch-up rollover takes place after resume, and the
+    /// following rollovers keep the alignment of the rollover condition.
+    ///
+    /// # Arguments
+    /// * `now` - current timestamp
+    fn determine_next_rollover(&mut self, now: &DateTime<Local>) {
+        match self.rollover_policy.condition() {
+            RolloverCondition::TimeElapsed(intvl) => {
+                let mut next = intvl.next_elapse(&self.next_rovr_ts);
+                while next <= *now {
+                    let following = intvl.next_elapse(&next);
+                    if following <= next {
+                        // anchor doesn't advance the interval, continue from current time
+                        next = intvl.next_elapse(now);
+                        break
+                    }
+                    next = following;
+                }
+                self.next_rovr_ts = next;
+            },
+            RolloverCondition::Scheduled(sched) => {
+                let last_elapsed = std::cmp::max(self.next_rovr_ts, *now);
+                self.next_rovr_ts = sched.next_elapse(&last_elapsed);
+            },
+            _ => ()
+        }
+    }
+
+    /// Returns the forecast for the next rollover.
+    /// The files removed by the next rollover are determined without modifying the output
+    /// directory; if the directory can't be read, no removal candidates are reported.
+    ///
+    /// # Arguments
+    /// * `active_file_name` - the pure name of the active output file, empty if not yet created
+    /// * `size` - the number of bytes written to the active output file, None if unknown
+    fn forecast(&self, active_file_name: &str, size: Option<usize>) -> RolloverForecast {
+        let active_file_name = if active_file_name.is_empty() { self.file_name() }
+                               else { active_file_name.to_string() };
+        let next_rollover = match self.rollover_policy.condition() {
+            RolloverCondition::TimeElapsed(_) |
+            RolloverCondition::Scheduled(_) => Some(self.next_rovr_ts),
+            _ => None
+        };
+        let size_limit = if self.max_size > 0 { Some(self.max_size) } else { None };
+        let candidates = retention_candidates(&self.dir, &active_file_name, &self.name_spec,
+                                              self.keep_count(), self.max_total_size(),
+                                              &self.compression())
+                             .unwrap_or_default();
+        RolloverForecast::new(self.dir.join(&active_file_name).to_string_lossy().to_string(),
+                              next_rollover, size, size_limit, candidates)
+    }
+}
+
+/// Returns the data to write at the start of a plain file.
+/// The data consists of the binary file header or the byte order mark of the character
+/// encoding. For encrypted files, it is preceded by the encryption header and encrypted itself.
+///
+/// # Arguments
+/// * `meta_data` - the file's meta data
+/// * `file_name` - the file name
+/// * `header` - the binary file header, **None** for text files
+/// * `encoder` - the encoder for the file's character encoding
+///
+/// # Errors
+/// Returns an error structure if the header could not be encrypted
+fn plain_file_header(#[allow(unused_variables)] meta_data: &RolloverMetaData,
+                     #[allow(unused_variables)] file_name: &str,
+                     header: &Option<Vec<u8>>,
+                     encoder: &Encoder) -> Result<Vec<u8>, CoalyException> {
+    let header = header.as_deref().unwrap_or(encoder.bom());
+    #[cfg(feature="encryption")]
+    if let Some(c) = &meta_data.cipher {
+        let mut enc_header = c.header(FILE_KIND_PLAIN);
+        if ! header.is_empty() {
+            enc_header.extend_from_slice(&c.seal_frame(header)
+                                           .map_err(|e| encryption_error(file_name, e))?);
         }
+        return Ok(enc_header)
     }
+    Ok(header.to_vec())
+}
+
+/// Returns the write error for data, that could not be encrypted.
+///
+/// # Arguments
+/// * `file_name` - the name of the file to write
+/// * `cause` - the encryption error
+#[cfg(feature="encryption")]
+fn encryption_error(file_name: &str, cause: CoalyException) -> CoalyException {
+    let mut x = coalyxe!(E_FILE_WRITE_ERR, file_name.to_string());
+    x.set_cause(cause);
+    x
+}
+
+/// Returns the data to write at the start of a memory mapped file.
+/// The data consists of the encryption header for encrypted files, followed by the calibration
+/// header for files with tick timestamps.
+///
+/// # Arguments
+/// * `meta_data` - the file's meta data
+/// * `calibration` - the calibration for tick timestamps, **None** for wall clock timestamps
+#[cfg(feature="mmfile")]
+fn mapped_file_header(#[allow(unused_variables)] meta_data: &RolloverMetaData,
+                      calibration: Option<TickCalibration>) -> Vec<u8> {
+    let mut header = Vec::<u8>::new();
+    #[cfg(feature="encryption")]
+    if let Some(c) = &meta_data.cipher { header = c.header(FILE_KIND_MAPPED); }
+    if let Some(c) = calibration { header.extend_from_slice(&c.header()); }
+    header
+}
+
+/// Maps a memory mapped file.
+/// For files in ring mode, the records retained in an existing file are carried over, so the
+/// file always holds the most recent records, even across application restarts.
+///
+/// # Arguments
+/// * `meta_data` - the file's meta data
+/// * `f_path` - the full path of the file
+/// * `f_size` - the size of the file
+/// * `max_rec_count` - the maximum number of records
+/// * `header` - the file header
+///
+/// # Errors
+/// Returns an error structure if the file can't be created or mapped
+#[cfg(feature="mmfile")]
+fn map_file(meta_data: &RolloverMetaData,
+            f_path: &PathBuf,
+            f_size: usize,
+            max_rec_count: usize,
+            header: &[u8]) -> Result<RecordBuffer, CoalyException> {
+    let retained = if meta_data.ring {
+                       std::fs::read(f_path).map(|data| retained_records(&data, header))
+                                            .unwrap_or_default()
+                   } else { Vec::new() };
+    let mut buf = RecordBuffer::backed_by_file(f_path, f_size, max_rec_count, header)?;
+    for rec in retained.iter() { buf.write(rec); }
+    Ok(buf)
 }
 
 /// Creates and opens a plain file for output.
@@ -586,22 +1355,356 @@ impl RolloverMetaData {
 /// # Arguments
 /// * `output_dir` - the output directory path
 /// * `file_name` - the pure file name without path
+/// * `bom` - the byte order mark to write at the start of the file, empty if not needed
 /// 
 /// # Return values
 /// handle to the created file
 /// 
 /// # Errors
 /// Returns an error structure if the file could not be created
-fn create_file(dir: &PathBuf, file_name: &str) -> Result<File, CoalyException> {
+fn create_file(dir: &PathBuf,
+               file_name: &str,
+               bom: &[u8]) -> Result<TrackedFile, CoalyException> {
     let file_path = dir.join(file_name);
     let full_file_name = file_path.to_string_lossy().to_string();
     if let Err(m) = std::fs::create_dir_all(dir) {
         return Err(coalyxe!(E_FILE_CRE_ERR, full_file_name, m.to_string()))
     }
-    File::create(file_path).map_err(|e| coalyxe!(E_FILE_CRE_ERR, full_file_name.to_string(),
+    let mut f = shared_open_options().write(true).create(true).truncate(true)
+                                     .open(file_path)
+                                     .map_err(|e| coalyxe!(E_FILE_CRE_ERR,
+                                                           full_file_name.to_string(),
+                                                           e.to_string()))?;
+    if ! bom.is_empty() {
+        if let Err(e) = f.write_all(bom) {
+            return Err(coalyxe!(E_FILE_WRITE_ERR, full_file_name, e.to_string()))
+        }
+    }
+    Ok(TrackedFile::new(f))
+}
+
+/// Opens an existing file for appending, the file is created if it doesn't exist.
+///
+/// # Arguments
+/// * `dir` - the directory where the file is located
+/// * `file_name` - the pure file name
+///
+/// # Errors
+/// Returns an error structure if the file can't be opened
+fn append_file(dir: &Path, file_name: &str) -> Result<TrackedFile, CoalyException> {
+    let file_path = dir.join(file_name);
+    shared_open_options().append(true).create(true)
+                         .open(&file_path)
+                         .map(TrackedFile::new)
+                         .map_err(|e| coalyxe!(E_FILE_CRE_ERR,
+                                               file_path.to_string_lossy().to_string(),
                                                e.to_string()))
 }
 
+/// Opens a plain file shared with other processes for appending, the file is created if it
+/// doesn't exist. Creates missing parent directories, if needed.
+/// The data for the start of the file is written only if the file is empty.
+///
+/// # Arguments
+/// * `dir` - the directory where the file is located
+/// * `file_name` - the pure file name
+/// * `header` - the data to write at the start of the file, empty if not needed
+///
+/// # Errors
+/// Returns an error structure if the file can't be opened or written
+fn join_file(dir: &Path, file_name: &str, header: &[u8]) -> Result<TrackedFile, CoalyException> {
+    if let Err(m) = std::fs::create_dir_all(dir) {
+        return Err(coalyxe!(E_FILE_CRE_ERR, dir.join(file_name).to_string_lossy().to_string(),
+                            m.to_string()))
+    }
+    let mut f = append_file(dir, file_name)?;
+    if ! header.is_empty() && f.metadata().is_ok_and(|md| md.len() == 0) {
+        if let Err(e) = f.write_all(header) {
+            return Err(coalyxe!(E_FILE_WRITE_ERR,
+                                dir.join(file_name).to_string_lossy().to_string(),
+                                e.to_string()))
+        }
+    }
+    Ok(f)
+}
+
+/// Exclusive lock on the lock file coordinating processes sharing a plain output file.
+/// The lock is acquired on demand and released when the structure is dropped.
+struct SharedFileLock {
+    // full path of the lock file
+    path: PathBuf,
+    // handle of the locked lock file, None if the lock hasn't been acquired yet
+    f: Option<File>
+}
+impl SharedFileLock {
+    /// Creates the structure, without acquiring the lock yet.
+    ///
+    /// # Arguments
+    /// * `path` - the full path of the lock file
+    fn new(path: PathBuf) -> SharedFileLock { SharedFileLock { path, f: None } }
+
+    /// Acquires the lock, blocking until it is granted.
+    /// Has no effect, if the lock is already held.
+    ///
+    /// # Errors
+    /// Returns an error structure if the lock file can't be created or locked
+    fn acquire(&mut self) -> Result<(), CoalyException> {
+        if self.f.is_some() { return Ok(()) }
+        let path_name = self.path.to_string_lossy().to_string();
+        if let Some(dir) = self.path.parent() { let _ = std::fs::create_dir_all(dir); }
+        let f = shared_open_options().write(true).create(true).truncate(false).open(&self.path)
+                                     .map_err(|e| coalyxe!(E_FILE_LOCK_ERR, path_name.to_string(),
+                                                           e.to_string()))?;
+        lock_file(&f).map_err(|e| coalyxe!(E_FILE_LOCK_ERR, path_name, e.to_string()))?;
+        self.f = Some(f);
+        Ok(())
+    }
+}
+impl Drop for SharedFileLock {
+    fn drop(&mut self) {
+        if let Some(f) = &self.f { unlock_file(f); }
+    }
+}
+
+/// Handle of a plain output file, counted as open file held by Coaly while it exists.
+struct TrackedFile(File);
+impl TrackedFile {
+    /// Takes ownership of an opened file and counts it.
+    ///
+    /// # Arguments
+    /// * `f` - the opened file
+    fn new(f: File) -> TrackedFile {
+        OPEN_FILES.fetch_add(1, Ordering::Relaxed);
+        TrackedFile(f)
+    }
+}
+impl Drop for TrackedFile {
+    fn drop(&mut self) { OPEN_FILES.fetch_sub(1, Ordering::Relaxed); }
+}
+impl Deref for TrackedFile {
+    type Target = File;
+    fn deref(&self) -> &File { &self.0 }
+}
+impl DerefMut for TrackedFile {
+    fn deref_mut(&mut self) -> &mut File { &mut self.0 }
+}
+
+/// Returns the number of plain output files currently held open by Coaly.
+#[inline]
+pub(crate) fn open_file_count() -> usize { OPEN_FILES.load(Ordering::Relaxed) }
+
+/// Sets the maximum number of plain output files held open by Coaly.
+///
+/// # Arguments
+/// * `limit` - the maximum number of open files, 0 if unlimited
+#[inline]
+pub(crate) fn set_open_file_limit(limit: usize) { OPEN_FILE_LIMIT.store(limit, Ordering::Relaxed); }
+
+/// Checks whether a file of a thread or originator specific resource may be opened.
+/// Files of process wide resources are always opened, they count against the limit though,
+/// hence the limit is enforced on the less important per-thread files first.
+///
+/// # Arguments
+/// * `dir` - the directory where the file shall be created
+/// * `file_name` - the pure file name
+///
+/// # Errors
+/// Returns an error structure if the limit for open files is reached
+fn admit_limited_file(dir: &Path, file_name: &str) -> Result<(), CoalyException> {
+    let limit = OPEN_FILE_LIMIT.load(Ordering::Relaxed);
+    if limit == 0 || open_file_count() < limit { return Ok(()) }
+    stats::file_refused();
+    Err(coalyxw!(W_RES_OPEN_FILE_LIMIT, limit.to_string(),
+                 dir.join(file_name).to_string_lossy().to_string()))
+}
+
+// number of plain output files currently held open
+static OPEN_FILES: AtomicUsize = AtomicUsize::new(0);
+
+// maximum number of open plain output files, 0 if unlimited
+static OPEN_FILE_LIMIT: AtomicUsize = AtomicUsize::new(0);
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use std::str::FromStr;
+    #[cfg(feature="mmfile")]
+    use crate::output::mappedfile::read_mapped_file;
+    use crate::output::resource::tests::{clear_test_dir, test_dir_path};
+
+    #[test]
+    /// Tests rollover of a plain file upon size limit, in addition to policy's time condition
+    fn test_size_limit() {
+        let dir = test_dir_path(&["output", "resource", "file", "test_size_limit"]);
+        clear_test_dir(&dir);
+        let cond = RolloverCondition::from_str("every day").unwrap();
+        let policy = RolloverPolicy::new("test", cond, 2, CompressionAlgorithm::None);
+        let name_spec = FormatSpec::from_str("size.log").unwrap();
+        let mut fdata = FileData::new(&dir, name_spec, &policy, &FileNameRules::new('_', 64),
+                                      CharEncoding::Utf8).unwrap();
+        fdata.limit_size(100);
+        let rec = format!("{}\n", "x".repeat(39));
+        for _ in 0 .. 3 { assert!(fdata.write(rec.as_bytes()).is_ok()); }
+        fdata.close();
+        // third record exceeds size limit, all records are in the archived file
+        let archives: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap()
+                                         .map(|e| e.unwrap().path())
+                                         .filter(|p| ! p.ends_with("size.log"))
+                                         .collect();
+        assert_eq!(1, archives.len());
+        assert_eq!(120, std::fs::metadata(&archives[0]).unwrap().len());
+        assert_eq!(0, std::fs::metadata(dir.join("size.log")).unwrap().len());
+        clear_test_dir(&dir);
+    }
+
+    #[test]
+    /// Tests the rollover timestamp after the process was suspended past several rollovers,
+    /// exactly one catch-up rollover must be due, the next one aligned to the interval
+    fn test_rollover_catch_up() {
+        let cond = RolloverCondition::from_str("every hour").unwrap();
+        let policy = RolloverPolicy::new("test", cond, 2, CompressionAlgorithm::None);
+        let name_spec = FormatSpec::from_str("catch_up.log").unwrap();
+        let mut meta_data = RolloverMetaData::new(Path::new("."), name_spec, &policy, 0,
+                                                  &FileNameRules::new('_', 64));
+        let now = Local::now();
+        let missed_ts = now - chrono::Duration::seconds(5 * 3600 + 600);
+        meta_data.next_rovr_ts = missed_ts;
+        assert!(meta_data.is_rollover_due(&now));
+        meta_data.determine_next_rollover(&now);
+        assert!(! meta_data.is_rollover_due(&now));
+        assert_eq!(missed_ts + chrono::Duration::seconds(6 * 3600), meta_data.next_rovr_ts);
+        // schedule continues from current time
+        let cond = RolloverCondition::from_str("cron:0 * * * *").unwrap();
+        let policy = RolloverPolicy::new("test", cond, 2, CompressionAlgorithm::None);
+        meta_data.rollover_policy = policy;
+        meta_data.next_rovr_ts = missed_ts;
+        meta_data.determine_next_rollover(&now);
+        assert!(meta_data.next_rovr_ts > now);
+        assert!(meta_data.next_rovr_ts <= now + chrono::Duration::seconds(3600));
+    }
+
+    #[test]
+    /// Tests creation of a plain file in an invalid output directory and in a fallback directory
+    fn test_create_relocate() {
+        let dir = test_dir_path(&["output", "resource", "file", "test_create_relocate"]);
+        clear_test_dir(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        // output directory below a regular file can't be created
+        let blocker = dir.join("blocker");
+        std::fs::write(&blocker, "x").unwrap();
+        let cond = RolloverCondition::from_str("never").unwrap();
+        let policy = RolloverPolicy::new("test", cond, 0, CompressionAlgorithm::None);
+        let name_spec = FormatSpec::from_str("create.log").unwrap();
+        let mut fdata = FileData::new(&blocker.join("out"), name_spec, &policy,
+                                      &FileNameRules::new('_', 64), CharEncoding::Utf8).unwrap();
+        assert!(fdata.create().is_err());
+        let fallback_dir = dir.join("fallback");
+        fdata.relocate(&fallback_dir);
+        assert!(fdata.create().is_ok());
+        assert!(fallback_dir.join("create.log").exists());
+        // relocation has no effect once the file exists
+        fdata.relocate(&dir);
+        assert_eq!(fallback_dir.join("create.log").to_string_lossy(), fdata.file_path());
+        fdata.close();
+        clear_test_dir(&dir);
+    }
+
+    #[test]
+    /// Tests change of the output directory for a plain file, the open file must be continued
+    /// and archived in the old directory, the file after the rollover created in the new one
+    fn test_change_output_dir() {
+        let dir = test_dir_path(&["output", "resource", "file", "test_change_output_dir"]);
+        clear_test_dir(&dir);
+        let old_dir = dir.join("old");
+        let new_dir = dir.join("new");
+        std::fs::create_dir_all(&new_dir).unwrap();
+        let cond = RolloverCondition::from_str("never").unwrap();
+        let policy = RolloverPolicy::new("test", cond, 2, CompressionAlgorithm::None);
+        let name_spec = FormatSpec::from_str("move.log").unwrap();
+        let mut fdata = FileData::new(&old_dir, name_spec, &policy, &FileNameRules::new('_', 64),
+                                      CharEncoding::Utf8).unwrap();
+        fdata.limit_size(100);
+        let rec = format!("{}\n", "x".repeat(39));
+        assert!(fdata.write(rec.as_bytes()).is_ok());
+        fdata.change_output_dir(&new_dir);
+        assert_eq!(old_dir.join("move.log").to_string_lossy(), fdata.file_path());
+        // third record exceeds size limit
+        for _ in 0 .. 3 { assert!(fdata.write(rec.as_bytes()).is_ok()); }
+        fdata.close();
+        assert_eq!(new_dir.join("move.log").to_string_lossy(), fdata.file_path());
+        let archives: Vec<PathBuf> = std::fs::read_dir(&old_dir).unwrap()
+                                         .map(|e| e.unwrap().path())
+                                         .collect();
+        assert_eq!(1, archives.len());
+        assert_eq!(120, std::fs::metadata(&archives[0]).unwrap().len());
+        assert_eq!(rec, std::fs::read_to_string(new_dir.join("move.log")).unwrap());
+        clear_test_dir(&dir);
+    }
+
+    #[cfg(feature="mmfile")]
+    #[test]
+    /// Tests a memory mapped file in ring mode, records must survive remapping of the file
+    fn test_ring_mode() {
+        let dir = test_dir_path(&["output", "resource", "file", "test_ring_mode"]);
+        clear_test_dir(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let cond = RolloverCondition::from_str("never").unwrap();
+        let policy = RolloverPolicy::new("test", cond, 0, CompressionAlgorithm::None);
+        let rules = FileNameRules::new('_', 64);
+        let name_spec = FormatSpec::from_str("ring.mmf").unwrap();
+        let mut mmf = MemMappedFileData::new(&dir, name_spec.clone(), 4096, &policy, &rules,
+                                             None, true, false).unwrap();
+        for i in 0 .. 3 { mmf.write_record(&format!("first {}\n", i)).unwrap(); }
+        assert!(mmf.rollover_now().is_ok());
+        mmf.close();
+        drop(mmf);
+        let mut mmf = MemMappedFileData::new(&dir, name_spec, 4096, &policy, &rules,
+                                             None, true, false).unwrap();
+        mmf.write_record("second 0\n").unwrap();
+        mmf.close();
+        drop(mmf);
+        let recs = read_mapped_file(&dir.join("ring.mmf")).unwrap();
+        assert_eq!(&vec!["first 0\n", "first 1\n", "first 2\n", "second 0\n"],
+                   recs.records());
+        assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+        clear_test_dir(&dir);
+    }
+
+    #[test]
+    /// Tests a plain file shared by two writers, the second one must continue with the file
+    /// created by the rollover of the first one
+    fn test_shared_file() {
+        let dir = test_dir_path(&["output", "resource", "file", "test_shared_file"]);
+        clear_test_dir(&dir);
+        let cond = RolloverCondition::from_str("never").unwrap();
+        let policy = RolloverPolicy::new("test", cond, 2, CompressionAlgorithm::None);
+        let rules = FileNameRules::new('_', 64);
+        let name_spec = FormatSpec::from_str("shared.log").unwrap();
+        let mut writer1 = FileData::new(&dir, name_spec.clone(), &policy, &rules,
+                                        CharEncoding::Utf8).unwrap();
+        writer1.set_sharing(FileSharing::Append);
+        writer1.limit_size(100);
+        let mut writer2 = FileData::new(&dir, name_spec, &policy, &rules,
+                                        CharEncoding::Utf8).unwrap();
+        writer2.set_sharing(FileSharing::Lock);
+        writer2.limit_size(100);
+        let rec1 = format!("{}\n", "1".repeat(39));
+        let rec2 = format!("{}\n", "2".repeat(39));
+        for _ in 0 .. 2 { assert!(writer1.write(rec1.as_bytes()).is_ok()); }
+        // file is shared, hence third record exceeds size limit
+        assert!(writer2.write(rec2.as_bytes()).is_ok());
+        assert!(writer1.write(rec1.as_bytes()).is_ok());
+        writer1.close();
+        writer2.close();
+        let archives: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap()
+                                         .map(|e| e.unwrap().path())
+                                         .filter(|p| p.extension().unwrap() != "lock" &&
+                                                     ! p.ends_with("shared.log"))
+                                         .collect();
+        assert_eq!(1, archives.len());
+        assert_eq!(format!("{}{}{}", rec1, rec1, rec2),
+                   std::fs::read_to_string(&archives[0]).unwrap());
+        assert_eq!(rec1, std::fs::read_to_string(dir.join("shared.log")).unwrap());
+        clear_test_dir(&dir);
+    }
 }
@@ -0,0 +1,478 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Output resources of type GELF.
+//! Records are sent to a Graylog input in Graylog Extended Log Format (GELF) version 1.1, a JSON
+//! object per record. Besides the formatted record as message, every object contains the record
+//! level as syslog severity, the source code location, thread ID and name, the originator
+//! information and the fields of a matching mode change.
+//! UDP transport splits messages exceeding the maximum datagram size into chunks, TCP transport
+//! terminates every message with a null byte. A broken TCP connection is established again with
+//! the next record written.
+
+use std::io::Write;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+use crate::coalyxe;
+use crate::errorhandling::*;
+use crate::net::{parse_gelf_url, NetworkProtocol, PeerAddr};
+use crate::record::RecordLevelId;
+use crate::record::originator::OriginatorInfo;
+use crate::record::recorddata::RecordData;
+use crate::util::json_escaped_str;
+
+/// Specific data for physical resources of kind GELF.
+pub(crate) struct GelfData {
+    // URL of the Graylog input
+    url: String,
+    // transport protocol, either TCP or UDP
+    protocol: NetworkProtocol,
+    // host name or address and port parsed from URL
+    host: String,
+    port: u16,
+    // JSON fragment with the fields derived from originator information
+    originator_fields: String,
+    // base value for the message IDs of chunked messages
+    msg_id_base: u64,
+    // number of chunked messages sent
+    chunked_count: u32,
+    // socket for UDP transport, created with the first record written
+    udp_socket: Option<UdpSocket>,
+    // connection for TCP transport, established with the first record written
+    tcp_stream: Option<TcpStream>
+}
+impl GelfData {
+    /// Creates a GELF resource.
+    /// 
+    /// # Arguments
+    /// * `url` - the URL of the Graylog input
+    /// * `orig_info` - the information about the application issuing the records
+    /// 
+    /// # Errors
+    /// Returns an error structure if the URL is not a valid GELF URL
+    pub(crate) fn new(url: &str,
+                      orig_info: &OriginatorInfo) -> Result<GelfData, CoalyException> {
+        if let Some((protocol, host, port)) = parse_gelf_url(url) {
+            return Ok(GelfData {
+                url: url.to_string(),
+                protocol,
+                host,
+                port,
+                originator_fields: originator_fields(orig_info),
+                msg_id_base: (orig_info.process_id_value() as u64) << 32,
+                chunked_count: 0,
+                udp_socket: None,
+                tcp_stream: None
+            })
+        }
+        Err(coalyxe!(E_INVALID_URL, url.to_string()))
+    }
+
+    /// Returns the address of the GELF server.
+    #[inline]
+    pub(crate) fn peer_addr(&self) -> PeerAddr {
+        PeerAddr::HostSocket(self.protocol.clone(), self.host.clone(), self.port)
+    }
+
+    /// Sends a record to the Graylog input.
+    /// 
+    /// # Arguments
+    /// * `record` - the log or trace record
+    /// * `msg` - the formatted record
+    /// 
+    /// # Errors
+    /// Returns an error structure if the socket can't be created, the message is too large for
+    /// UDP transport or the send operation fails
+    pub(crate) fn write(&mut self,
+                        record: &dyn RecordData,
+                        msg: &str) -> Result<(), CoalyException> {
+        let payload = payload_for(record, msg, &self.originator_fields);
+        match self.protocol {
+            NetworkProtocol::Tcp => self.send_tcp(payload.as_bytes()),
+            _ => self.send_udp(payload.as_bytes())
+        }
+    }
+
+    /// Closes the socket, it is created again with the next record written.
+    pub(crate) fn close(&mut self) {
+        self.udp_socket = None;
+        self.tcp_stream = None;
+    }
+
+    /// Sends a GELF message by UDP, split into chunks if necessary.
+    /// 
+    /// # Arguments
+    /// * `payload` - the GELF message
+    /// 
+    /// # Errors
+    /// Returns an error structure if the socket can't be created, the message is too large or
+    /// the send operation fails
+    fn send_udp(&mut self, payload: &[u8]) -> Result<(), CoalyException> {
+        if self.udp_socket.is_none() {
+            let addr = self.remote_addr()?;
+            let local = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+            let socket = UdpSocket::bind(local)
+                         .and_then(|s| s.connect(addr).map(|_| s))
+                         .map_err(|e| coalyxe!(E_SOCKET_CRE_ERR, self.url.clone(), e.to_string()))?;
+            self.udp_socket = Some(socket);
+        }
+        let datagrams = if payload.len() <= MAX_DATAGRAM_SIZE {
+                            vec!(payload.to_vec())
+                        } else {
+                            self.chunked_count = self.chunked_count.wrapping_add(1);
+                            let msg_id = self.msg_id_base | self.chunked_count as u64;
+                            chunks_for(payload, msg_id).ok_or_else(|| {
+                                coalyxe!(E_GELF_MSG_TOO_LARGE, payload.len().to_string(),
+                                         self.url.clone())
+                            })?
+                        };
+        let socket = self.udp_socket.as_ref().unwrap();
+        for dgram in datagrams {
+            if let Err(e) = socket.send(&dgram) {
+                let local_addr = socket.local_addr().map_or(String::from("-"), |a| a.to_string());
+                return Err(coalyxe!(E_SOCKET_WRITE_ERR, local_addr, self.url.clone(),
+                                    e.to_string()))
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends a GELF message by TCP, terminated by a null byte.
+    /// The connection is established, if it doesn't exist. It is dropped upon failure and
+    /// established again with the next message.
+    /// 
+    /// # Arguments
+    /// * `payload` - the GELF message
+    /// 
+    /// # Errors
+    /// Returns an error structure if the connection can't be established or the send
+    /// operation fails
+    fn send_tcp(&mut self, payload: &[u8]) -> Result<(), CoalyException> {
+        if self.tcp_stream.is_none() {
+            let addr = self.remote_addr()?;
+            let stream = TcpStream::connect_timeout(&addr, GELF_TIMEOUT)
+                         .map_err(|e| coalyxe!(E_SOCKET_CRE_ERR, self.url.clone(), e.to_string()))?;
+            let _ = stream.set_write_timeout(Some(GELF_TIMEOUT));
+            self.tcp_stream = Some(stream);
+        }
+        let stream = self.tcp_stream.as_mut().unwrap();
+        let mut frame = Vec::<u8>::with_capacity(payload.len() + 1);
+        frame.extend_from_slice(payload);
+        frame.push(0);
+        if let Err(e) = stream.write_all(&frame) {
+            let local_addr = stream.local_addr().map_or(String::from("-"), |a| a.to_string());
+            self.tcp_stream = None;
+            return Err(coalyxe!(E_SOCKET_WRITE_ERR, local_addr, self.url.clone(), e.to_string()))
+        }
+        Ok(())
+    }
+
+    /// Resolves the socket address of the Graylog input.
+    /// 
+    /// # Errors
+    /// Returns an error structure if the host name can't be resolved
+    fn remote_addr(&self) -> Result<SocketAddr, CoalyException> {
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+        match (host, self.port).to_socket_addrs() {
+            Ok(mut addrs) => addrs.next().ok_or_else(|| {
+                coalyxe!(E_HOST_RESOLVE_ERR, host.to_string(), String::from("no address found"))
+            }),
+            Err(e) => Err(coalyxe!(E_HOST_RESOLVE_ERR, host.to_string(), e.to_string()))
+        }
+    }
+}
+
+/// Returns the JSON fragment with the GELF fields derived from originator information.
+/// The fragment starts with the mandatory host field, all other fields are additional fields.
+/// 
+/// # Arguments
+/// * `orig_info` - the information about the application issuing the records
+fn originator_fields(orig_info: &OriginatorInfo) -> String {
+    let mut fields = format!("\"host\":\"{}\",\"_process_id\":{},\"_process_name\":\"{}\"",
+                             json_escaped_str(orig_info.host_name()),
+                             orig_info.process_id_value(),
+                             json_escaped_str(orig_info.process_name()));
+    if orig_info.application_id_value() != 0 {
+        fields.push_str(&format!(",\"_app_id\":{}", orig_info.application_id_value()));
+    }
+    if ! orig_info.application_name().is_empty() {
+        fields.push_str(&format!(",\"_app_name\":\"{}\"",
+                                 json_escaped_str(orig_info.application_name())));
+    }
+    if ! orig_info.ip_address().is_empty() {
+        fields.push_str(&format!(",\"_ip_address\":\"{}\"",
+                                 json_escaped_str(orig_info.ip_address())));
+    }
+    for (name, value) in [("_run_id", orig_info.run_id()),
+                          ("_parent_run_id", orig_info.parent_run_id()),
+                          ("_trace_id", orig_info.trace_id())] {
+        if ! value.is_empty() { fields.push_str(&format!(",\"{}\":\"{}\"", name, value)); }
+    }
+    for (name, value) in orig_info.env_vars() {
+        if let Some(fname) = field_name(&format!("env_{}", name)) {
+            fields.push_str(&format!(",\"{}\":\"{}\"", fname, json_escaped_str(value)));
+        }
+    }
+    fields
+}
+
+/// Returns the GELF message for a record.
+/// 
+/// # Arguments
+/// * `record` - the log or trace record
+/// * `msg` - the formatted record
+/// * `originator_fields` - the JSON fragment with the fields derived from originator information
+fn payload_for(record: &dyn RecordData, msg: &str, originator_fields: &str) -> String {
+    let msg = msg.trim_end();
+    let short_msg = msg.lines().next().unwrap_or("");
+    let ts = record.timestamp();
+    let mut payload = format!("{{\"version\":\"1.1\",{},\"short_message\":\"{}\"",
+                              originator_fields, json_escaped_str(short_msg));
+    if short_msg.len() < msg.len() {
+        payload.push_str(&format!(",\"full_message\":\"{}\"", json_escaped_str(msg)));
+    }
+    payload.push_str(&format!(",\"timestamp\":{}.{:03},\"level\":{}",
+                              ts.timestamp(), ts.timestamp_subsec_millis(),
+                              severity(record.level())));
+    payload.push_str(&format!(",\"_file\":\"{}\"", json_escaped_str(record.source_fn())));
+    if let Some(lnr) = record.line_nr() {
+        payload.push_str(&format!(",\"_line\":{}", lnr));
+    }
+    payload.push_str(&format!(",\"_thread_id\":{},\"_thread_name\":\"{}\"",
+                              record.thread_id(), json_escaped_str(record.thread_name())));
+    if let Some(deco) = record.decoration() {
+        for (name, value) in deco.fields() {
+            if let Some(fname) = field_name(name) {
+                payload.push_str(&format!(",\"{}\":\"{}\"", fname, json_escaped_str(value)));
+            }
+        }
+    }
+    payload.push('}');
+    payload
+}
+
+/// Returns the GELF additional field name for the given name.
+/// 
+/// # Arguments
+/// * `name` - the field name without leading underscore
+/// 
+/// # Return values
+/// the name with leading underscore, **None** if the name contains characters not allowed
+/// in GELF or is reserved
+fn field_name(name: &str) -> Option<String> {
+    if name.is_empty() || name == "id" { return None }
+    if ! name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-') {
+        return None
+    }
+    Some(format!("_{}", name))
+}
+
+/// Splits a GELF message into chunks for UDP transport.
+/// 
+/// # Arguments
+/// * `payload` - the GELF message
+/// * `msg_id` - the message ID, must be unique for all chunked messages sent recently
+/// 
+/// # Return values
+/// the datagrams for all chunks; **None** if the message exceeds the maximum number of chunks
+fn chunks_for(payload: &[u8], msg_id: u64) -> Option<Vec<Vec<u8>>> {
+    let chunk_data_size = MAX_DATAGRAM_SIZE - CHUNK_HEADER_SIZE;
+    let count = payload.len().div_ceil(chunk_data_size);
+    if count > MAX_CHUNK_COUNT { return None }
+    let chunks = payload.chunks(chunk_data_size).enumerate().map(|(seq, data)| {
+        let mut dgram = Vec::<u8>::with_capacity(CHUNK_HEADER_SIZE + data.len());
+        dgram.extend_from_slice(&CHUNK_MAGIC);
+        dgram.extend_from_slice(&msg_id.to_be_bytes());
+        dgram.push(seq as u8);
+        dgram.push(count as u8);
+        dgram.extend_from_slice(data);
+        dgram
+    });
+    Some(chunks.collect())
+}
+
+/// Returns the syslog severity for a record level.
+/// Levels emergency through info map to the syslog severities with same name, all trace
+/// levels to debug.
+/// 
+/// # Arguments
+/// * `level` - the record level
+fn severity(level: RecordLevelId) -> u32 { level.syslog_severity() }
+
+// maximum size of an UDP datagram, larger messages are sent in chunks
+const MAX_DATAGRAM_SIZE: usize = 1420;
+
+// magic bytes identifying a chunk
+const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+
+// size of the chunk header: magic bytes, message ID, sequence number and count
+const CHUNK_HEADER_SIZE: usize = 12;
+
+// maximum number of chunks per message
+const MAX_CHUNK_COUNT: usize = 128;
+
+// timeout for connect and send operations
+const GELF_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use crate::record::recorddata::{LocalRecordData, RecordDecoration};
+
+    fn orig_info() -> OriginatorInfo {
+        let mut orig_info = OriginatorInfo::new(4711, "myapp", "myhost", "10.1.1.1");
+        orig_info.set_application_id(3);
+        orig_info.set_application_name("billing");
+        orig_info.add_env_var("STAGE", "prod");
+        orig_info
+    }
+
+    #[test]
+    /// Tests the fields derived from originator information
+    fn test_originator_fields() {
+        assert_eq!(r#""host":"myhost","_process_id":4711,"_process_name":"myapp","_app_id":3,"#
+                   .to_string() +
+                   r#""_app_name":"billing","_ip_address":"10.1.1.1","_env_STAGE":"prod""#,
+                   originator_fields(&orig_info()));
+        let orig_info = OriginatorInfo::new(1, "a\"b", "h", "");
+        assert_eq!(r#""host":"h","_process_id":1,"_process_name":"a\"b""#,
+                   originator_fields(&orig_info));
+        let mut orig_info = OriginatorInfo::new(1, "a", "h", "");
+        orig_info.set_run_context("0a1b", "2c3d", "4e5f");
+        assert_eq!(r#""host":"h","_process_id":1,"_process_name":"a","_run_id":"0a1b","#
+                   .to_string() +
+                   r#""_parent_run_id":"2c3d","_trace_id":"4e5f""#,
+                   originator_fields(&orig_info));
+    }
+
+    #[test]
+    /// Tests GELF messages for records
+    fn test_payload_for() {
+        let mut rec = LocalRecordData::for_write(1234, "main", RecordLevelId::Error,
+                                                 "test.rs", 393, "disk full");
+        let ts = rec.timestamp();
+        let ts = format!("{}.{:03}", ts.timestamp(), ts.timestamp_subsec_millis());
+        assert_eq!(format!("{{\"version\":\"1.1\",\"host\":\"h\",\"short_message\":\"disk full\",\
+                            \"timestamp\":{},\"level\":3,\"_file\":\"test.rs\",\"_line\":393,\
+                            \"_thread_id\":1234,\"_thread_name\":\"main\"}}", ts),
+                   payload_for(&rec, "disk full\n", "\"host\":\"h\""));
+        let fields = vec!((String::from("order"), String::from("17")),
+                          (String::from("id"), String::from("x")),
+                          (String::from("a b"), String::from("y")));
+        rec.set_decoration(Some(Arc::new(RecordDecoration::new("", "", fields))));
+        assert_eq!(format!("{{\"version\":\"1.1\",\"host\":\"h\",\"short_message\":\"line 1\",\
+                            \"full_message\":\"line 1\\nline 2\",\
+                            \"timestamp\":{},\"level\":3,\"_file\":\"test.rs\",\"_line\":393,\
+                            \"_thread_id\":1234,\"_thread_name\":\"main\",\"_order\":\"17\"}}",
+                           ts),
+                   payload_for(&rec, "line 1\nline 2\n", "\"host\":\"h\""));
+    }
+
+    #[test]
+    /// Tests mapping of record levels to syslog severities
+    fn test_severity() {
+        assert_eq!(0, severity(RecordLevelId::Emergency));
+        assert_eq!(3, severity(RecordLevelId::Error));
+        assert_eq!(6, severity(RecordLevelId::Info));
+        assert_eq!(7, severity(RecordLevelId::Debug));
+        assert_eq!(7, severity(RecordLevelId::Object));
+    }
+
+    #[test]
+    /// Tests splitting of large messages into chunks
+    fn test_chunks_for() {
+        let chunk_data_size = MAX_DATAGRAM_SIZE - CHUNK_HEADER_SIZE;
+        let payload = vec![b'x'; chunk_data_size * 2 + 10];
+        let chunks = chunks_for(&payload, 0x0102030405060708).unwrap();
+        assert_eq!(3, chunks.len());
+        assert_eq!(&[0x1e, 0x0f, 1, 2, 3, 4, 5, 6, 7, 8, 0, 3], &chunks[0][..CHUNK_HEADER_SIZE]);
+        assert_eq!(MAX_DATAGRAM_SIZE, chunks[0].len());
+        assert_eq!(&[0x1e, 0x0f, 1, 2, 3, 4, 5, 6, 7, 8, 2, 3], &chunks[2][..CHUNK_HEADER_SIZE]);
+        assert_eq!(CHUNK_HEADER_SIZE + 10, chunks[2].len());
+        let payload = vec![b'x'; chunk_data_size * MAX_CHUNK_COUNT];
+        assert_eq!(MAX_CHUNK_COUNT, chunks_for(&payload, 1).unwrap().len());
+        let payload = vec![b'x'; chunk_data_size * MAX_CHUNK_COUNT + 1];
+        assert!(chunks_for(&payload, 1).is_none());
+    }
+
+    #[test]
+    /// Tests sending records by UDP
+    fn test_gelf_udp() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let url = format!("gelf+udp://{}", server.local_addr().unwrap());
+        let mut gelf = GelfData::new(&url, &orig_info()).unwrap();
+        let rec = LocalRecordData::for_write(1234, "main", RecordLevelId::Warning,
+                                             "test.rs", 393, "low memory");
+        assert!(gelf.write(&rec, "low memory\n").is_ok());
+        let mut buf = [0u8; 2048];
+        let n = server.recv(&mut buf).unwrap();
+        let msg = String::from_utf8_lossy(&buf[..n]).to_string();
+        assert!(msg.starts_with("{\"version\":\"1.1\",\"host\":\"myhost\""));
+        assert!(msg.contains("\"short_message\":\"low memory\""));
+        assert!(msg.contains("\"level\":4"));
+        let long_msg = "x".repeat(3000);
+        assert!(gelf.write(&rec, &long_msg).is_ok());
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(MAX_DATAGRAM_SIZE, n);
+        assert_eq!(&CHUNK_MAGIC, &buf[..2]);
+        assert_eq!(&[0, 0, 0x12, 0x67, 0, 0, 0, 1, 0, 3], &buf[2..12]);
+    }
+
+    #[test]
+    /// Tests sending records by TCP
+    fn test_gelf_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("gelf+tcp://{}", listener.local_addr().unwrap());
+        let mut gelf = GelfData::new(&url, &orig_info()).unwrap();
+        let rec = LocalRecordData::for_write(1234, "main", RecordLevelId::Info,
+                                             "test.rs", 393, "started");
+        assert!(gelf.write(&rec, "started\n").is_ok());
+        assert!(gelf.write(&rec, "started\n").is_ok());
+        gelf.close();
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut data = Vec::<u8>::new();
+        stream.read_to_end(&mut data).unwrap();
+        let msgs: Vec<&[u8]> = data.split(|b| *b == 0).collect();
+        assert_eq!(3, msgs.len());
+        assert!(msgs[2].is_empty());
+        assert!(String::from_utf8_lossy(msgs[1]).contains("\"short_message\":\"started\""));
+    }
+
+    #[test]
+    /// Tests rejection of invalid URLs
+    fn test_invalid_url() {
+        assert!(GelfData::new("udp://127.0.0.1:12201", &orig_info()).is_err());
+    }
+}
@@ -0,0 +1,472 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Output resources of type HTTP.
+//! Records are collected in batches, which are sent by HTTP POST to the configured URL, either
+//! as JSON lines or as push request for the Loki API. A batch is posted when it has reached the
+//! configured size, or when its oldest record has been held for the configured batch interval.
+//! Batches that couldn't be posted are kept and posted again after the retry interval, until
+//! the maximum number of retries is exceeded. Remaining records are posted when the resource
+//! is closed.
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::coalyxe;
+use crate::config::resource::{BatchFormat, HttpResourceDesc};
+use crate::errorhandling::*;
+use crate::net::parse_http_or_https_url;
+use crate::record::originator::OriginatorInfo;
+use crate::stats;
+use crate::util::json_escaped_str;
+use crate::net::{NetworkProtocol, PeerAddr};
+#[cfg(feature="tls")]
+use crate::net::tls::TlsConnector;
+
+/// Specific data for physical resources of kind HTTP.
+pub(crate) struct HttpData {
+    // URL where to post the batches to
+    url: String,
+    // host name or address, port and path parsed from URL
+    host: String,
+    port: u16,
+    path: String,
+    // payload format
+    format: BatchFormat,
+    // maximum number of records per batch
+    batch_size: usize,
+    // maximum time a record is held before its batch is posted
+    batch_interval: Duration,
+    // maximum number of attempts to post a failed batch again
+    max_retries: u32,
+    // interval between two attempts to post a failed batch
+    retry_interval: Duration,
+    // JSON object with the Loki stream labels derived from originator information
+    stream_labels: String,
+    // TLS client for HTTPS URLs
+    #[cfg(feature="tls")]
+    tls_connector: Option<TlsConnector>,
+    // records of the current batch together with the time they were added, in nanoseconds
+    // since the epoch
+    batch: Vec<(u128, String)>,
+    // time when the first record of the current batch was added
+    batch_start: Option<Instant>,
+    // batches that couldn't be posted, oldest first
+    failed_batches: VecDeque<FailedBatch>
+}
+impl HttpData {
+    /// Creates an HTTP resource.
+    /// 
+    /// # Arguments
+    /// * `desc` - the HTTP resource descriptor
+    /// * `orig_info` - the information about the application issuing the records
+    /// 
+    /// # Errors
+    /// Returns an error structure if the URL is not a valid HTTP or HTTPS URL, or the TLS
+    /// settings can't be applied
+    pub(crate) fn new(desc: &HttpResourceDesc,
+                      orig_info: &OriginatorInfo) -> Result<HttpData, CoalyException> {
+        let (_secure, host, port, path) = parse_http_or_https_url(desc.url())
+                                          .ok_or_else(|| coalyxe!(E_INVALID_URL,
+                                                                  desc.url().to_string()))?;
+        #[cfg(feature="tls")]
+        let tls_connector = if _secure {
+                                let addr = PeerAddr::HostSocket(NetworkProtocol::Tls,
+                                                                host.clone(), port);
+                                Some(TlsConnector::new(desc.tls_settings(), &addr)?)
+                            } else { None };
+        #[cfg(not(feature="tls"))]
+        if _secure { return Err(coalyxe!(E_INVALID_URL, desc.url().to_string())) }
+        Ok(HttpData {
+            url: desc.url().to_string(),
+            host,
+            port,
+            path,
+            format: desc.format(),
+            batch_size: desc.batch_size(),
+            batch_interval: Duration::from_secs(desc.batch_interval()),
+            max_retries: desc.max_retries(),
+            retry_interval: Duration::from_secs(desc.retry_interval()),
+            stream_labels: stream_labels(orig_info),
+            #[cfg(feature="tls")]
+            tls_connector,
+            batch: Vec::new(),
+            batch_start: None,
+            failed_batches: VecDeque::new()
+        })
+    }
+
+    /// Returns the address of the web server.
+    #[inline]
+    pub(crate) fn peer_addr(&self) -> PeerAddr {
+        PeerAddr::HostSocket(NetworkProtocol::Tcp, self.host.clone(), self.port)
+    }
+
+    /// Adds formatted records to the current batch.
+    /// Every line is treated as a record of its own. The batch is posted, if it has reached
+    /// the maximum size.
+    /// 
+    /// # Arguments
+    /// * `data` - the formatted records
+    /// 
+    /// # Errors
+    /// Returns an array of error structures if the batch could not be posted
+    pub(crate) fn add(&mut self, data: &str) -> Result<(), Vec<CoalyException>> {
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+        let mut errs = Vec::<CoalyException>::new();
+        for line in data.lines().filter(|l| ! l.trim().is_empty()) {
+            if self.batch_start.is_none() { self.batch_start = Some(Instant::now()); }
+            self.batch.push((ts, line.to_string()));
+            if self.batch.len() >= self.batch_size {
+                if let Err(e) = self.post_batch(Instant::now()) { errs.push(e); }
+            }
+        }
+        if errs.is_empty() { Ok(()) } else { Err(errs) }
+    }
+
+    /// Posts the current batch, if its batch interval has elapsed, and all failed batches
+    /// whose retry interval has elapsed.
+    /// 
+    /// # Arguments
+    /// * `now` - the current time
+    /// 
+    /// # Errors
+    /// Returns an error structure for the first batch that could not be posted
+    pub(crate) fn post_if_due(&mut self, now: Instant) -> Result<(), CoalyException> {
+        let mut first_err: Option<CoalyException> = None;
+        while self.failed_batches.front().is_some_and(|b| b.due <= now) {
+            let mut failed = self.failed_batches.pop_front().unwrap();
+            if let Err(e) = self.post(&failed.records) {
+                stats::network_error();
+                failed.attempts += 1;
+                if failed.attempts > self.max_retries {
+                    first_err.get_or_insert(coalyxe!(E_HTTP_BATCH_DISCARDED,
+                                                     failed.records.len().to_string(),
+                                                     (failed.attempts + 1).to_string(),
+                                                     self.url.clone()));
+                } else {
+                    first_err.get_or_insert(e);
+                    failed.due = now + self.retry_interval;
+                    self.failed_batches.push_back(failed);
+                }
+                // the endpoint is most likely unavailable, don't try the remaining batches
+                break
+            }
+        }
+        if self.batch_start.is_some_and(|t| now.saturating_duration_since(t) >= self.batch_interval) {
+            if let Err(e) = self.post_batch(now) { first_err.get_or_insert(e); }
+        }
+        match first_err {
+            Some(e) => Err(e),
+            None => Ok(())
+        }
+    }
+
+    /// Posts the current batch and all failed batches a last time.
+    pub(crate) fn close(&mut self) {
+        let records = std::mem::take(&mut self.batch);
+        self.batch_start = None;
+        if ! records.is_empty() { let _ = self.post(&records); }
+        while let Some(failed) = self.failed_batches.pop_front() {
+            if self.post(&failed.records).is_err() { break }
+        }
+        self.failed_batches.clear();
+    }
+
+    /// Posts the current batch. A batch that couldn't be posted is kept for a later retry,
+    /// if retries are configured.
+    /// 
+    /// # Arguments
+    /// * `now` - the current time
+    /// 
+    /// # Errors
+    /// Returns an error structure if the batch could not be posted
+    fn post_batch(&mut self, now: Instant) -> Result<(), CoalyException> {
+        let records = std::mem::take(&mut self.batch);
+        self.batch_start = None;
+        if records.is_empty() { return Ok(()) }
+        let res = self.post(&records);
+        if res.is_err() { stats::network_error(); }
+        if res.is_ok() || self.max_retries == 0 { return res }
+        self.failed_batches.push_back(FailedBatch::new(records, now + self.retry_interval));
+        if self.failed_batches.len() > MAX_FAILED_BATCHES {
+            // drop oldest batch to keep memory consumption limited
+            let oldest = self.failed_batches.pop_front().unwrap();
+            return Err(coalyxe!(E_HTTP_BATCH_DISCARDED, oldest.records.len().to_string(),
+                                (oldest.attempts + 1).to_string(), self.url.clone()))
+        }
+        res
+    }
+
+    /// Posts the given records to the HTTP endpoint.
+    /// 
+    /// # Arguments
+    /// * `records` - the records along with their timestamps
+    /// 
+    /// # Errors
+    /// Returns an error structure if the connection fails or the endpoint doesn't respond
+    /// with a success status
+    fn post(&self, records: &[(u128, String)]) -> Result<(), CoalyException> {
+        #[cfg(feature="faultinjection")]
+        crate::output::faultinjection::check_write()?;
+        let (content_type, body) = match self.format {
+            BatchFormat::JsonLines => ("application/x-ndjson", jsonl_payload(records)),
+            BatchFormat::Loki => ("application/json", loki_payload(records, &self.stream_labels))
+        };
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+        let addr = (host, self.port).to_socket_addrs()
+                                    .ok()
+                                    .and_then(|mut a| a.next())
+                                    .ok_or_else(|| coalyxe!(E_INVALID_URL, self.url.to_string()))?;
+        let stream = TcpStream::connect_timeout(&addr, HTTP_TIMEOUT)
+                     .map_err(|e| coalyxe!(E_SOCKET_CRE_ERR, self.url.clone(), e.to_string()))?;
+        let _ = stream.set_read_timeout(Some(HTTP_TIMEOUT));
+        let _ = stream.set_write_timeout(Some(HTTP_TIMEOUT));
+        let local_addr = stream.local_addr().map_or(String::from("-"), |a| a.to_string());
+        let request = format!("POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\n\
+                               Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                              self.path, self.host, content_type, body.len(), body);
+        #[cfg(feature="tls")]
+        if let Some(connector) = &self.tls_connector {
+            let mut tls_stream = connector.connect(stream)
+                                 .map_err(|e| coalyxe!(E_SOCKET_CRE_ERR, self.url.clone(),
+                                                       e.to_string()))?;
+            let status_line = self.exchange(&mut tls_stream, &request, &local_addr)?;
+            return self.check_status(&status_line, records.len())
+        }
+        let mut stream = stream;
+        let status_line = self.exchange(&mut stream, &request, &local_addr)?;
+        self.check_status(&status_line, records.len())
+    }
+
+    /// Sends an HTTP request and returns the status line of the response.
+    /// 
+    /// # Arguments
+    /// * `stream` - the stream connected to the HTTP endpoint
+    /// * `request` - the HTTP request
+    /// * `local_addr` - the local socket address, used in error messages
+    /// 
+    /// # Errors
+    /// Returns an error structure if the send or receive operation fails
+    fn exchange<S: Read + Write>(&self,
+                                 stream: &mut S,
+                                 request: &str,
+                                 local_addr: &str) -> Result<String, CoalyException> {
+        if let Err(e) = stream.write_all(request.as_bytes()).and_then(|_| stream.flush()) {
+            return Err(coalyxe!(E_SOCKET_WRITE_ERR, local_addr.to_string(), self.url.clone(),
+                                e.to_string()))
+        }
+        let mut response = [0u8; 256];
+        let n = stream.read(&mut response)
+                      .map_err(|e| coalyxe!(E_SOCKET_READ_ERR, local_addr.to_string(),
+                                            self.url.clone(), e.to_string()))?;
+        let status_line = String::from_utf8_lossy(&response[..n]);
+        Ok(status_line.lines().next().unwrap_or("").to_string())
+    }
+
+    /// Checks the status line of an HTTP response for success.
+    /// 
+    /// # Arguments
+    /// * `status_line` - the status line of the response
+    /// * `record_count` - the number of records in the batch posted
+    /// 
+    /// # Errors
+    /// Returns an error structure if the status doesn't indicate success
+    fn check_status(&self, status_line: &str, record_count: usize) -> Result<(), CoalyException> {
+        match status_line.split_whitespace().nth(1) {
+            Some(status) if status.starts_with('2') => Ok(()),
+            _ => Err(coalyxe!(E_HTTP_BATCH_REJECTED, self.url.clone(), record_count.to_string(),
+                              status_line.to_string()))
+        }
+    }
+}
+
+/// Batch that couldn't be posted.
+struct FailedBatch {
+    // records along with their timestamps
+    records: Vec<(u128, String)>,
+    // number of attempts made to post the batch again
+    attempts: u32,
+    // time of the next attempt
+    due: Instant
+}
+impl FailedBatch {
+    fn new(records: Vec<(u128, String)>, due: Instant) -> FailedBatch {
+        FailedBatch { records, attempts: 0, due }
+    }
+}
+
+/// Returns the JSON object with the Loki stream labels derived from originator information.
+/// 
+/// # Arguments
+/// * `orig_info` - the information about the application issuing the records
+fn stream_labels(orig_info: &OriginatorInfo) -> String {
+    let app = if orig_info.application_name().is_empty() { orig_info.process_name() }
+              else { orig_info.application_name() };
+    format!("{{\"host\":\"{}\",\"app\":\"{}\"}}",
+            json_escaped_str(orig_info.host_name()), json_escaped_str(app))
+}
+
+/// Returns the payload for a batch in JSON lines format.
+/// 
+/// # Arguments
+/// * `records` - the records along with their timestamps
+fn jsonl_payload(records: &[(u128, String)]) -> String {
+    let mut payload = String::new();
+    for (_, rec) in records {
+        payload.push_str(rec.trim_end());
+        payload.push('\n');
+    }
+    payload
+}
+
+/// Returns the payload for a batch as push request for the Loki API.
+/// 
+/// # Arguments
+/// * `records` - the records along with their timestamps
+/// * `stream_labels` - the JSON object with the stream labels
+fn loki_payload(records: &[(u128, String)], stream_labels: &str) -> String {
+    let values: Vec<String> = records.iter()
+                                     .map(|(ts, rec)| format!("[\"{}\",\"{}\"]", ts,
+                                                              json_escaped_str(rec.trim_end())))
+                                     .collect();
+    format!("{{\"streams\":[{{\"stream\":{},\"values\":[{}]}}]}}", stream_labels,
+            values.join(","))
+}
+
+// maximum number of failed batches kept for a retry
+const MAX_FAILED_BATCHES: usize = 16;
+
+// timeout for connect, send and receive operations
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Starts a web server accepting the given number of requests, responding with the given
+    /// status. Returns the URL of the server and a handle returning the request bodies received.
+    fn web_server(status: &'static str, count: usize) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/push", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut bodies = Vec::<String>::new();
+            for _ in 0 .. count {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::<u8>::new();
+                let mut buf = [0u8; 1024];
+                loop {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let req = String::from_utf8_lossy(&request).to_string();
+                    if let Some(pos) = req.find("\r\n\r\n") {
+                        let len_line = req.lines()
+                                          .find(|l| l.starts_with("Content-Length:")).unwrap();
+                        let len: usize = len_line[15..].trim().parse().unwrap();
+                        if request.len() >= pos + 4 + len {
+                            stream.write_all(format!("HTTP/1.1 {}\r\n\r\n", status).as_bytes())
+                                  .unwrap();
+                            bodies.push(req[pos + 4..].to_string());
+                            break
+                        }
+                    }
+                }
+            }
+            bodies
+        });
+        (url, handle)
+    }
+
+    fn http_data(url: &str, format: BatchFormat, batch_size: usize, max_retries: u32) -> HttpData {
+        let mut desc = HttpResourceDesc::new(url, format, batch_size, 5);
+        desc.set_retry_policy(max_retries, 1);
+        HttpData::new(&desc, &OriginatorInfo::new(4711, "myapp", "myhost", "")).unwrap()
+    }
+
+    #[test]
+    /// Tests batch payloads
+    fn test_payloads() {
+        let recs = vec!((1u128, String::from("{\"msg\":\"a\"}")), (2u128, String::from("b\"c")));
+        assert_eq!("{\"msg\":\"a\"}\nb\"c\n", jsonl_payload(&recs));
+        let labels = stream_labels(&OriginatorInfo::new(1, "myapp", "myhost", ""));
+        assert_eq!(r#"{"host":"myhost","app":"myapp"}"#, labels);
+        assert_eq!(r#"{"streams":[{"stream":{"host":"myhost","app":"myapp"},"values":[["1","{\"msg\":\"a\"}"],["2","b\"c"]]}]}"#,
+                   loki_payload(&recs, &labels));
+    }
+
+    #[test]
+    /// Tests posting of batches upon size and interval
+    fn test_batches() {
+        let (url, server) = web_server("204 No Content", 2);
+        let mut res = http_data(&url, BatchFormat::JsonLines, 3, 0);
+        assert!(res.add("r1\nr2\n").is_ok());
+        assert!(res.post_if_due(Instant::now()).is_ok());
+        assert!(res.add("r3\nr4\n").is_ok());
+        assert_eq!(1, res.batch.len());
+        assert!(res.post_if_due(Instant::now() + Duration::from_secs(6)).is_ok());
+        assert!(res.batch.is_empty());
+        assert_eq!(vec!("r1\nr2\nr3\n", "r4\n"), server.join().unwrap());
+    }
+
+    #[test]
+    /// Tests retries of failed batches
+    fn test_retries() {
+        let (url, server) = web_server("500 Internal Server Error", 2);
+        let mut res = http_data(&url, BatchFormat::JsonLines, 1, 1);
+        assert!(res.add("r1\n").is_err());
+        assert_eq!(1, res.failed_batches.len());
+        // retry not due yet
+        assert!(res.post_if_due(Instant::now()).is_ok());
+        let now = Instant::now() + Duration::from_secs(2);
+        assert!(res.post_if_due(now).is_err());
+        assert_eq!(1, res.failed_batches.len());
+        assert_eq!(vec!("r1\n", "r1\n"), server.join().unwrap());
+        // maximum number of retries exceeded, batch is discarded
+        let (url, server) = web_server("500 Internal Server Error", 1);
+        res.url = url.clone();
+        res.port = parse_http_or_https_url(&url).unwrap().2;
+        let ex = res.post_if_due(now + Duration::from_secs(2)).unwrap_err();
+        assert_eq!(E_HTTP_BATCH_DISCARDED, ex.id());
+        assert!(res.failed_batches.is_empty());
+        assert_eq!(vec!("r1\n"), server.join().unwrap());
+    }
+
+    #[test]
+    /// Tests invalid URLs
+    fn test_invalid_url() {
+        let desc = HttpResourceDesc::new("tcp://127.0.0.1:3100", BatchFormat::Loki, 10, 5);
+        assert!(HttpData::new(&desc, &OriginatorInfo::new(1, "a", "h", "")).is_err());
+    }
+}
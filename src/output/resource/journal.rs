@@ -0,0 +1,219 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Output resources of type systemd journal.
+//! Records are sent to systemd-journald using the native journal protocol, a datagram per
+//! record to the journal socket. Besides the formatted record as message, every entry contains
+//! the fields PRIORITY derived from the record level, CODE_FILE and CODE_LINE for the source
+//! code location, TID for the thread ID and the optional SYSLOG_IDENTIFIER.
+//! Records are discarded, if journald is not running.
+
+use std::io::ErrorKind;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use crate::coalyxe;
+use crate::errorhandling::*;
+use crate::record::RecordLevelId;
+use crate::record::recorddata::RecordData;
+
+/// Specific data for physical resources of kind systemd journal.
+pub(crate) struct JournalData {
+    // path of the journal socket
+    socket_path: PathBuf,
+    // optional syslog identifier attached to all records
+    identifier: Option<String>,
+    // unbound datagram socket, created with the first record written
+    socket: Option<UnixDatagram>,
+    // buffer for the serialized journal entry
+    entry: Vec<u8>
+}
+impl JournalData {
+    /// Creates a systemd journal resource.
+    /// 
+    /// # Arguments
+    /// * `identifier` - the optional syslog identifier attached to all records
+    pub(crate) fn new(identifier: &Option<String>) -> JournalData {
+        JournalData::with_socket(Path::new(JOURNAL_SOCKET_PATH), identifier)
+    }
+
+    /// Creates a systemd journal resource using the specified socket.
+    /// 
+    /// # Arguments
+    /// * `socket_path` - the path of the journal socket
+    /// * `identifier` - the optional syslog identifier attached to all records
+    fn with_socket(socket_path: &Path,
+                   identifier: &Option<String>) -> JournalData {
+        JournalData {
+            socket_path: socket_path.to_path_buf(),
+            identifier: identifier.clone(),
+            socket: None,
+            entry: Vec::<u8>::with_capacity(1024)
+        }
+    }
+
+    /// Sends a record to the journal.
+    /// The record is discarded, if journald is not running.
+    /// 
+    /// # Arguments
+    /// * `record` - the log or trace record
+    /// * `msg` - the formatted record
+    /// 
+    /// # Errors
+    /// Returns an error structure if the journal socket can't be created or the send operation
+    /// fails for another reason than a missing journald
+    pub(crate) fn write(&mut self,
+                        record: &dyn RecordData,
+                        msg: &str) -> Result<(), CoalyException> {
+        if self.socket.is_none() {
+            match UnixDatagram::unbound() {
+                Ok(s) => self.socket = Some(s),
+                Err(e) => return Err(coalyxe!(E_SOCKET_CRE_ERR, self.socket_name(), e.to_string()))
+            }
+        }
+        self.entry.clear();
+        append_field(&mut self.entry, "MESSAGE", msg.trim_end());
+        append_field(&mut self.entry, "PRIORITY", &priority(record.level()).to_string());
+        append_field(&mut self.entry, "CODE_FILE", record.source_fn());
+        if let Some(lnr) = record.line_nr() {
+            append_field(&mut self.entry, "CODE_LINE", &lnr.to_string());
+        }
+        append_field(&mut self.entry, "TID", &record.thread_id().to_string());
+        if let Some(ident) = &self.identifier {
+            append_field(&mut self.entry, "SYSLOG_IDENTIFIER", ident);
+        }
+        let socket = self.socket.as_ref().unwrap();
+        if let Err(e) = socket.send_to(&self.entry, &self.socket_path) {
+            match e.kind() {
+                // journald not running, record is discarded
+                ErrorKind::NotFound | ErrorKind::ConnectionRefused => (),
+                _ => return Err(coalyxe!(E_SOCKET_WRITE_ERR, String::from(""),
+                                         self.socket_name(), e.to_string()))
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes the journal socket.
+    pub(crate) fn close(&mut self) { self.socket = None; }
+
+    /// Returns the path of the journal socket as string, used for error messages.
+    fn socket_name(&self) -> String { self.socket_path.to_string_lossy().to_string() }
+}
+
+/// Returns the syslog priority for a record level.
+/// Levels emergency through info map to the syslog severities with same name, all trace
+/// levels to debug.
+/// 
+/// # Arguments
+/// * `level` - the record level
+fn priority(level: RecordLevelId) -> u32 {
+    level.syslog_severity()
+}
+
+/// Appends a field to a journal entry in native journal protocol format.
+/// Values containing line breaks are stored in binary format with explicit length.
+/// 
+/// # Arguments
+/// * `entry` - the journal entry
+/// * `name` - the field name
+/// * `value` - the field value
+fn append_field(entry: &mut Vec<u8>, name: &str, value: &str) {
+    entry.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
+// path of the socket, where journald receives entries in native protocol format
+const JOURNAL_SOCKET_PATH: &str = "/run/systemd/journal/socket";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::recorddata::LocalRecordData;
+    use crate::output::resource::tests::test_dir_path;
+
+    #[test]
+    /// Tests mapping of record levels to syslog priorities
+    fn test_priority() {
+        assert_eq!(0, priority(RecordLevelId::Emergency));
+        assert_eq!(1, priority(RecordLevelId::Alert));
+        assert_eq!(2, priority(RecordLevelId::Critical));
+        assert_eq!(3, priority(RecordLevelId::Error));
+        assert_eq!(4, priority(RecordLevelId::Warning));
+        assert_eq!(5, priority(RecordLevelId::Notice));
+        assert_eq!(6, priority(RecordLevelId::Info));
+        assert_eq!(7, priority(RecordLevelId::Debug));
+        assert_eq!(7, priority(RecordLevelId::Function));
+        assert_eq!(7, priority(RecordLevelId::Module));
+        assert_eq!(7, priority(RecordLevelId::Object));
+    }
+
+    #[test]
+    /// Tests field serialization in native journal protocol format
+    fn test_append_field() {
+        let mut entry = Vec::<u8>::new();
+        append_field(&mut entry, "PRIORITY", "3");
+        assert_eq!(b"PRIORITY=3\n", entry.as_slice());
+        entry.clear();
+        append_field(&mut entry, "MESSAGE", "a\nb");
+        assert_eq!(b"MESSAGE\n\x03\x00\x00\x00\x00\x00\x00\x00a\nb\n", entry.as_slice());
+    }
+
+    #[test]
+    /// Tests sending records to a journal socket
+    fn test_journal() {
+        let dir = test_dir_path(&["output", "resource", "journal", "test_journal"]);
+        let _ = std::fs::create_dir_all(&dir);
+        let socket_path = dir.join("socket");
+        let _ = std::fs::remove_file(&socket_path);
+        let ident = Some(String::from("myapp"));
+        let mut journal = JournalData::with_socket(&socket_path, &ident);
+        let rec = LocalRecordData::for_write(1234, "mythread", RecordLevelId::Error,
+                                             "test.rs", 393, "disk full");
+        // without journald records are discarded
+        assert!(journal.write(&rec, "disk full\n").is_ok());
+        let journald = UnixDatagram::bind(&socket_path).unwrap();
+        assert!(journal.write(&rec, "disk full\n").is_ok());
+        let mut buf = [0u8; 256];
+        let n = journald.recv(&mut buf).unwrap();
+        assert_eq!("MESSAGE=disk full\nPRIORITY=3\nCODE_FILE=test.rs\nCODE_LINE=393\n\
+                    TID=1234\nSYSLOG_IDENTIFIER=myapp\n",
+                   String::from_utf8_lossy(&buf[..n]));
+        let _ = std::fs::remove_file(&socket_path);
+    }
+}
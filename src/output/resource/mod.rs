@@ -34,52 +34,201 @@
 
 use chrono::{DateTime, Local};
 use std::cell::RefCell;
-use std::io::{self, Write};
+use std::cmp::min;
+use std::io::{self, IsTerminal, Write};
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::str::FromStr;
-use crate::coalyxe;
+use std::time::{Duration, Instant};
+use crate::{coalyxe, coalyxw};
 use crate::config::Configuration;
-use crate::config::resource::{ResourceDesc, ResourceKind};
+use crate::context::ContextMap;
+use crate::config::filter::RecordFilterDesc;
+use crate::config::resource::{CharEncoding, CreateErrorPolicy, FileSharing, ResourceDesc,
+                              ResourceKind};
+#[cfg(feature="mmfile")]
+use crate::config::resource::TimestampDomain;
+#[cfg(feature="net")]
+use crate::config::resource::FileFormat;
+use crate::config::systemproperties::SystemProperties;
+use crate::degradation;
 use crate::errorhandling::*;
 use crate::policies::*;
 use crate::record::originator::OriginatorInfo;
-use crate::record::recorddata::RecordData;
-use super::formatspec::FormatSpec;
+use crate::record::RecordLevelId;
+use crate::record::recorddata::{LocalRecordData, RecordData};
+use crate::stats::{self, ResourceCounters};
+use crate::variables::DEFAULT_TIME_SLICE_INTERVAL;
+use super::forecast::RolloverForecast;
+use super::probe::ProbeResult;
+#[cfg(feature="net")]
+use super::probe::probe_peer;
+use super::formatspec::{FileNameRules, FormatSpec};
+use super::namepattern::FileNamePattern;
+#[cfg(feature="net")]
+use super::binaryfile::{append_frame, file_header, frame_size};
+#[cfg(feature="encryption")]
+use super::encryption::Cipher;
+use super::formatter::registered_formatter;
+#[cfg(feature="mmfile")]
+use super::mappedfile::TickCalibration;
 use super::outputformat::OutputFormat;
-use super::recordbuffer::RecordBuffer;
+use super::recordbuffer::{RecordBuffer, buffer_fair_share, buffer_memory_limit_exceeded,
+                          granted_buffer_size};
 
+mod encoder;
 mod file;
+#[cfg(feature="os-resources")]
+mod notification;
+#[cfg(feature="os-resources")]
+mod pipe;
 mod rollover;
-use file::{FileData, FileTemplateData, MemMappedFileData, MemMappedFileTemplateData};
+mod dedup;
+mod adaptive;
+mod sampler;
+mod throttle;
+
+pub(crate) use file::{open_file_count, set_open_file_limit};
+use file::{FileData, FileTemplateData};
+#[cfg(feature="mmfile")]
+use file::{MemMappedFileData, MemMappedFileTemplateData};
+#[cfg(feature="os-resources")]
+use notification::NotificationData;
+#[cfg(feature="os-resources")]
+use pipe::PipeData;
+use dedup::Deduplicator;
+use adaptive::AdaptiveFlush;
+use sampler::Sampler;
+use throttle::Throttle;
 
+#[cfg(all(feature="os-resources", windows))]
+mod eventlog;
+#[cfg(all(feature="os-resources", windows))]
+use eventlog::EventLogData;
+#[cfg(all(feature="os-resources", target_os="linux"))]
+mod journal;
+#[cfg(all(feature="os-resources", target_os="linux"))]
+use journal::JournalData;
+
+#[cfg(feature="net")]
+mod gelf;
+#[cfg(feature="net")]
+mod http;
 #[cfg(feature="net")]
 pub(crate) mod network;
 #[cfg(feature="net")]
 pub(crate) mod syslog;
 #[cfg(feature="net")]
+mod reconnect;
+#[cfg(feature="net")]
+mod spool;
+#[cfg(feature="net")]
+mod webhook;
+#[cfg(feature="net")]
+use gelf::GelfData;
+#[cfg(feature="net")]
+use http::HttpData;
+#[cfg(feature="net")]
 use network::NetworkData;
 #[cfg(feature="net")]
+use reconnect::Reconnection;
+#[cfg(feature="net")]
+use spool::Spool;
+#[cfg(feature="net")]
 use syslog::SyslogData;
 #[cfg(feature="net")]
-use crate::config::resource::{NetworkResourceDesc, SyslogResourceDesc};
+use webhook::WebhookData;
+#[cfg(feature="net")]
+use crate::config::resource::{HttpResourceDesc, NetworkResourceDesc, SyslogResourceDesc,
+                              WebhookResourceDesc};
 #[cfg(feature="net")]
 use crate::net::{parse_url, PeerAddr};
+#[cfg(feature="tls")]
+use crate::net::NetworkProtocol;
+#[cfg(feature="tls")]
+use crate::net::tls::TlsConnector;
 
 pub(crate) type ResourceRef = Rc<RefCell<Resource>>;
 
+/// Closes the given resources in the order of their flush priority, resources with higher
+/// priority first.
+/// Resources with equal priority are closed in the given order.
+/// 
+/// # Arguments
+/// * `resources` - the resources to close
+/// * `shutdown_deadline` - the point in time when the shutdown must be completed,
+///   **None** if unlimited
+pub(crate) fn close_resources(resources: &[ResourceRef], shutdown_deadline: Option<Instant>) {
+    let mut ordered: Vec<&ResourceRef> = resources.iter().collect();
+    ordered.sort_by_key(|r| std::cmp::Reverse(r.borrow().flush_priority()));
+    ordered.iter().for_each(|r| r.borrow_mut().close(shutdown_deadline));
+}
+
+/// Removes or compresses orphaned output files left behind by earlier runs, if the cleanup is
+/// enabled in the system properties.
+/// Only files of resources with thread or process specific names are considered, files with
+/// constant names are reused by every run.
+/// 
+/// # Arguments
+/// * `config` - the system configuration
+/// 
+/// # Return values
+/// the warnings for all files, that couldn't be removed or compressed
+pub(crate) fn cleanup_stale_files(config: &Configuration) -> Vec<CoalyException> {
+    let sys_props = config.system_properties();
+    let cleanup = match sys_props.stale_file_cleanup() {
+        Some(c) => c,
+        None => return Vec::new()
+    };
+    let patterns: Vec<FileNamePattern> = config.resources().elements()
+        .filter_map(|rdesc| rdesc.file_data())
+        .filter_map(|fdata| FormatSpec::from_str(fdata.file_name_spec()).ok())
+        .filter(|spec| spec.is_thread_specific() || spec.is_originator_specific())
+        .map(|spec| spec.stale_file_pattern())
+        .collect();
+    rollover::cleanup_stale_files(Path::new(sys_props.output_path()), &patterns,
+                                  Duration::from_secs(cleanup.retention()),
+                                  cleanup.compression())
+}
+
 /// Logical output resource, a physical resource enhanced with common attributes needed for all
 /// kinds of physical resources.
 pub(crate) struct Resource {
     // bit mask with all record levels associated with the resource
     levels: u32,
+    // optional filter for records by source file or module path
+    filter: Option<RecordFilterDesc>,
+    // optional runtime state of a policy limiting the number of records per interval
+    throttle: Option<Throttle>,
+    // optional runtime state of a policy writing only a sample of the records
+    sampler: Option<Sampler>,
+    // optional runtime state of the buffer policy's suppression of duplicate records
+    deduplicator: Option<Deduplicator>,
+    // optional runtime state of the buffer policy's latency dependent flush
+    adaptive_flush: Option<AdaptiveFlush>,
+    // indicates whether the resource is suspended in degraded mode
+    degradable: bool,
+    // priority for flushing upon shutdown, resources with higher values are flushed first
+    flush_priority: u32,
+    // maximum time for flushing upon shutdown
+    flush_deadline: Option<Duration>,
+    // interval and next point in time for an attempt to create the output file, if the file
+    // couldn't be created upon application start
+    create_retry: Option<(Duration, Instant)>,
     // memory buffer policy
     buffer_policy: BufferPolicy,
     // memory buffer
     buffer: Option<RecordBuffer>,
+    // time of last write to memory buffer
+    last_buffer_write: Option<Instant>,
     // output format for log and trace records as defined in configuration, i.e. not optimized for
     // a specific originator and thread
     output_format_template: OutputFormat,
+    // pipeline statistics counters for the resource
+    counters: Option<Arc<ResourceCounters>>,
+    // label of the resource for pipeline trace messages, e.g. file:app.log
+    label: String,
     // physical resource
     physical_resource: PhysicalResource,
     // buffer for local record serialization
@@ -100,28 +249,109 @@ impl Resource {
                               #[cfg(feature="net")]
                               orig_info: &OriginatorInfo) -> Result<Resource, CoalyException> {
         let buf_pol = config.buffer_policy(desc.buffer_policy_name());
-        let levels = config.system_properties().record_levels();
-        let ofmt_desc = config.output_format(desc.output_format_name());
-        let ofmt = OutputFormat::from_desc(ofmt_desc, config.date_time_formats(), levels);
-        let output_dir = Path::new(config.system_properties().output_path());
-        match desc.kind() {
+        let mut ofmt = output_format(config, desc.output_format_name());
+        for (lvls, lfmt_name) in desc.level_format_names() {
+            ofmt.add_level_format(*lvls, output_format(config, &Some(lfmt_name.to_string())));
+        }
+        ofmt.set_clock_granularity(desc.clock_granularity());
+        // timestamp domain ticks is accepted for memory mapped files only
+        #[cfg(feature="mmfile")]
+        let calibration = match desc.file_data().map(|fd| fd.timestamp_domain()) {
+            Some(TimestampDomain::Ticks) => {
+                let c = TickCalibration::new();
+                ofmt.set_tick_calibration(c);
+                Some(c)
+            },
+            _ => None
+        };
+        let sys_props = config.system_properties();
+        #[cfg(feature="encryption")]
+        let cipher = resource_cipher(desc, sys_props)?;
+        let mut res = match desc.kind() {
             ResourceKind::PlainFile => {
                 let fdata = desc.file_data().unwrap();
                 let rov_pol = config.rollover_policy(fdata.rollover_policy_name());
-                let name_spec = FormatSpec::from_str(fdata.file_name_spec()).unwrap();
-                Resource::plain_file(desc.levels(), &output_dir, name_spec,
-                                     buf_pol, rov_pol, ofmt)
+                let name_spec = FormatSpec::from_str(fdata.file_name_spec()).unwrap()
+                                    .with_time_slice(fdata.time_slice()
+                                                          .unwrap_or(DEFAULT_TIME_SLICE_INTERVAL));
+                let mut res = Resource::plain_file(desc.levels(), sys_props, name_spec,
+                                                   buf_pol, rov_pol, ofmt, fdata.encoding())?;
+                res.physical_resource.limit_file_size(fdata.file_size());
+                res.physical_resource.set_sharing(fdata.sharing());
+                #[cfg(feature="net")]
+                if fdata.file_format() == FileFormat::Binary {
+                    res.physical_resource.set_binary_header(file_header(orig_info));
+                }
+                #[cfg(feature="encryption")]
+                if let Some(c) = cipher { res.physical_resource.set_cipher(c); }
+                Ok::<_, CoalyException>(res)
             },
+            #[cfg(feature="mmfile")]
             ResourceKind::MemoryMappedFile => {
                 let fdata = desc.file_data().unwrap();
                 let rov_pol = config.rollover_policy(fdata.rollover_policy_name());
-                let name_spec = FormatSpec::from_str(fdata.file_name_spec()).unwrap();
+                let name_spec = FormatSpec::from_str(fdata.file_name_spec()).unwrap()
+                                    .with_time_slice(fdata.time_slice()
+                                                          .unwrap_or(DEFAULT_TIME_SLICE_INTERVAL));
                 let fsize = fdata.file_size();
-                Resource::mm_file(desc.levels(), &output_dir, name_spec, fsize,
-                                  buf_pol, rov_pol, ofmt)
+                // files with originator specific names are created when the name is resolved
+                let deferred = fdata.create_error_policy() != CreateErrorPolicy::Warn ||
+                               name_spec.is_originator_specific();
+                #[cfg(not(feature="encryption"))]
+                let res = Resource::mm_file(desc.levels(), sys_props, name_spec, fsize,
+                                            buf_pol, rov_pol, ofmt, calibration, fdata.ring(),
+                                            deferred);
+                // the cipher is needed for the file header, hence the file is created afterwards
+                #[cfg(feature="encryption")]
+                let res = Resource::mm_file(desc.levels(), sys_props, name_spec, fsize,
+                                            buf_pol, rov_pol, ofmt, calibration, fdata.ring(),
+                                            deferred || cipher.is_some())
+                              .and_then(|mut res| {
+                                  if let Some(c) = cipher {
+                                      res.physical_resource.set_cipher(c);
+                                      if ! deferred { res.physical_resource.create()?; }
+                                  }
+                                  Ok(res)
+                              });
+                res
+            },
+            ResourceKind::StdOut => {
+                if let Some(c) = config.console_colors() {
+                    if let Some(lc) = c.level_colors(io::stdout().is_terminal()) {
+                        ofmt.set_level_colors(lc);
+                    }
+                }
+                Ok(Resource::stdout(desc.levels(), buf_pol, ofmt))
+            },
+            ResourceKind::StdErr => {
+                if let Some(c) = config.console_colors() {
+                    if let Some(lc) = c.level_colors(io::stderr().is_terminal()) {
+                        ofmt.set_level_colors(lc);
+                    }
+                }
+                Ok(Resource::stderr(desc.levels(), buf_pol, ofmt))
+            },
+            #[cfg(feature="os-resources")]
+            ResourceKind::Pipe => {
+                let pdata = desc.pipe_data().unwrap();
+                Ok(Resource::pipe(desc.levels(), sys_props, pdata.pipe_name(), buf_pol, ofmt))
+            },
+            #[cfg(feature="os-resources")]
+            ResourceKind::Notification => {
+                let ndata = desc.notification_data().unwrap();
+                Ok(Resource::notification(desc.levels(), ndata.app_name(), ndata.min_interval(),
+                                          buf_pol, ofmt))
+            },
+            #[cfg(all(feature="os-resources", windows))]
+            ResourceKind::WindowsEventLog => {
+                let edata = desc.eventlog_data().unwrap();
+                Ok(Resource::eventlog(desc.levels(), edata.source_name(), buf_pol, ofmt))
+            },
+            #[cfg(all(feature="os-resources", target_os="linux"))]
+            ResourceKind::Journald => {
+                let jdata = desc.journal_data().unwrap();
+                Ok(Resource::journal(desc.levels(), jdata.identifier(), buf_pol, ofmt))
             },
-            ResourceKind::StdOut => Ok(Resource::stdout(desc.levels(), buf_pol, ofmt)),
-            ResourceKind::StdErr => Ok(Resource::stderr(desc.levels(), buf_pol, ofmt)),
             #[cfg(feature="net")]
             ResourceKind::Syslog => {
                 let ldata = desc.syslog_data().unwrap();
@@ -130,9 +360,38 @@ impl Resource {
             #[cfg(feature="net")]
             ResourceKind::Network => {
                 let ndata = desc.network_data().unwrap();
-                Resource::network(desc.levels(), ndata, buf_pol, orig_info, ofmt)
+                Resource::network(desc.levels(), ndata, sys_props, buf_pol, orig_info, ofmt)
+            },
+            #[cfg(feature="net")]
+            ResourceKind::Webhook => {
+                let wdata = desc.webhook_data().unwrap();
+                Resource::webhook(desc.levels(), wdata, buf_pol, ofmt)
+            },
+            #[cfg(feature="net")]
+            ResourceKind::Gelf => {
+                let gdata = desc.gelf_data().unwrap();
+                Resource::gelf(desc.levels(), gdata.url(), buf_pol, orig_info, ofmt)
+            },
+            #[cfg(feature="net")]
+            ResourceKind::Http => {
+                let hdata = desc.http_data().unwrap();
+                Resource::http(desc.levels(), hdata, buf_pol, orig_info, ofmt)
             }
+        }?;
+        res.filter = desc.filter().clone();
+        res.throttle = desc.throttle_policy().as_ref().map(Throttle::new);
+        res.sampler = desc.sampling_policy().as_ref().map(Sampler::new);
+        res.deduplicator = Deduplicator::new(&res.buffer_policy);
+        res.adaptive_flush = AdaptiveFlush::new(&res.buffer_policy);
+        res.degradable = config.system_properties().degradation().as_ref()
+                               .is_some_and(|d| ! d.keeps_resource_kind(&desc.kind().to_string()));
+        res.flush_priority = desc.flush_priority();
+        res.counters = Some(stats::resource_counters(&desc.label()));
+        res.label = desc.label();
+        if desc.flush_deadline() > 0 {
+            res.flush_deadline = Some(Duration::from_millis(desc.flush_deadline()));
         }
+        Ok(res)
     }
 
     /// Writes a log or trace record to this resource.
@@ -150,7 +409,93 @@ impl Resource {
                         output_format: &OutputFormat,
                         use_buffer: bool) -> Result<(), Vec<CoalyException>> {
         // if record level is not associated with this resource, we're finished
-        if self.levels & record.level() as u32  == 0 { return Ok(()) }
+        if self.levels & record.level() as u32  == 0 {
+            self.trace(record, W_PIPE_RES_LEVEL);
+            return Ok(())
+        }
+        // same if the record's source file or module is filtered out
+        if let Some(f) = &self.filter {
+            if ! f.passes(record.source_fn()) {
+                self.trace(record, W_PIPE_RES_FILTERED);
+                return Ok(())
+            }
+        }
+        // same if the record is not selected by the sampling policy
+        if let Some(s) = &mut self.sampler {
+            if ! s.admit(record.level() as u32) {
+                self.trace(record, W_PIPE_RES_NOT_SAMPLED);
+                return Ok(())
+            }
+        }
+        // records are lost, if the resource couldn't be created yet and creation is retried
+        // later, if the resource is suspended in degraded mode, if the record exceeds the
+        // limit of the throttle policy, or if the record duplicates a buffered one
+        let drop_reason =
+            if self.create_retry.is_some() || (self.degradable && degradation::is_degraded()) {
+                Some(W_PIPE_RES_UNAVAILABLE)
+            } else if self.throttle.is_some() && ! self.throttle_admits(record, output_format)? {
+                Some(W_PIPE_RES_THROTTLED)
+            } else if use_buffer && self.deduplicator.as_mut()
+                                        .is_some_and(|d| ! d.admit(record, output_format,
+                                                                   Instant::now())) {
+                Some(W_PIPE_RES_DUPLICATE)
+            } else {
+                None
+            };
+        if let Some(reason) = drop_reason {
+            if let Some(c) = &self.counters { c.record_dropped(); }
+            self.trace(record, reason);
+            return Ok(())
+        }
+        let mut result = self.write_admitted(record, output_format, use_buffer);
+        if result.is_ok() && use_buffer { result = self.flush_if_batch_complete(); }
+        if result.is_ok() { self.trace(record, W_PIPE_RES_WRITTEN); }
+        if let Some(c) = &self.counters {
+            if result.is_ok() {
+                c.record_written();
+            } else {
+                c.write_failed();
+                #[cfg(feature="net")]
+                if self.physical_resource.is_network() { stats::network_error(); }
+            }
+        }
+        result
+    }
+
+    /// Writes a log or trace record, that passed all admission checks, to this resource.
+    /// 
+    /// # Arguments
+    /// * `record` - the log or trace record
+    /// * `output_format` - the output format to use
+    /// * `use_buffer` - indicates whether to buffer the record in memory instead of writing to
+    ///                  physical resource
+    /// 
+    /// # Errors
+    /// Returns an error structure if the write operation fails
+    fn write_admitted(&mut self,
+                      record: &dyn RecordData,
+                      output_format: &OutputFormat,
+                      use_buffer: bool) -> Result<(), Vec<CoalyException>> {
+        // event log entries carry the record level, hence they are never buffered
+        #[cfg(all(feature="os-resources", windows))]
+        if let PhysicalResource::EventLog(e) = &mut self.physical_resource {
+            let msg = output_format.apply_to(record);
+            return e.write(record.level(), msg.as_bytes()).map_err(|e| vec!(e))
+        }
+        // journal entries carry structured fields derived from the record, hence they are
+        // never buffered
+        #[cfg(all(feature="os-resources", target_os="linux"))]
+        if let PhysicalResource::Journal(j) = &mut self.physical_resource {
+            let msg = output_format.apply_to(record);
+            return j.write(record, &msg).map_err(|e| vec!(e))
+        }
+        // GELF messages carry structured fields derived from the record, hence they are
+        // never buffered
+        #[cfg(feature="net")]
+        if let PhysicalResource::Gelf(g) = &mut self.physical_resource {
+            let msg = output_format.apply_to(record);
+            return g.write(record, &msg).map_err(|e| vec!(e))
+        }
         // without buffering, write record to physical resource
         if ! use_buffer { return self.write_through(record, output_format) }
         // write record to memory buffer
@@ -159,14 +504,17 @@ impl Resource {
         #[cfg(not(feature="net"))]
         let bytes_to_write = msg.len();
         #[cfg(feature="net")]
-        let msg: Option<String> = if self.physical_resource.is_proxy() { None }
+        let msg: Option<String> = if self.physical_resource.writes_serialized() { None }
                                   else { Some(output_format.apply_to(record)) };
         #[cfg(feature="net")]
         let bytes_to_write = if msg.is_some() { msg.as_ref().unwrap().len() } 
+                             else if self.physical_resource.is_binary() { frame_size(record) }
                              else { record.serialized_size() };
+        self.last_buffer_write = Some(Instant::now());
         if self.buffer.is_none() {
             // buffer doesn't exist, allocate it
-            self.buffer = Some(RecordBuffer::in_memory(self.buffer_policy.content_size(),
+            let buf_size = granted_buffer_size(self.buffer_policy.content_size(), 0);
+            self.buffer = Some(RecordBuffer::in_memory(buf_size,
                                                        self.buffer_policy.index_size(),
                                                        self.buffer_policy.max_record_length()));
         } else {
@@ -184,12 +532,19 @@ impl Resource {
                 #[cfg(not(feature="net"))]
                 return self.physical_resource.write_record(&msg)
             }
+            self.limit_buffer_memory();
+            if ! self.buffer.as_ref().unwrap().can_lossless_hold(bytes_to_write) {
+                self.grow_buffer();
+            }
             if self.buffer_flush_required_upon(BufferFlushCondition::Full as u32) {
                 if ! self.buffer.as_mut().unwrap().can_lossless_hold(bytes_to_write) {
                     self.flush_buffer()?;
                 }
             }
         }
+        if record.traced() && ! self.buffer.as_ref().unwrap().can_lossless_hold(bytes_to_write) {
+            self.trace(record, W_PIPE_RES_BUFFER_FULL);
+        }
         #[cfg(not(feature="net"))]
         return Ok(self.buffer.as_mut().unwrap().write(&msg));
         #[cfg(feature="net")]
@@ -203,8 +558,10 @@ impl Resource {
                     self.serialization_buffer = Some(Vec::<u8>::with_capacity(bytes_to_write));
                 }
                 let buf = self.serialization_buffer.as_mut().unwrap();
+                buf.clear();
                 if bytes_to_write > buf.capacity() { buf.reserve(bytes_to_write - buf.capacity()); }
-                record.serialize_to(buf);
+                if self.physical_resource.is_binary() { append_frame(record, buf); }
+                else { record.serialize_to(buf); }
                 let buf = self.buffer.as_mut().unwrap();
                 return Ok(buf.cache(self.serialization_buffer.as_ref().unwrap().as_slice()))
             }
@@ -223,30 +580,251 @@ impl Resource {
                      record: &dyn RecordData,
                      output_format: &OutputFormat) -> Result<(), Vec<CoalyException>> {
         #[cfg(feature="net")]
-        if self.physical_resource.is_proxy() {
+        if self.physical_resource.writes_serialized() {
             return self.physical_resource.send_record(record)
         }
         let msg = output_format.apply_to(record);
         self.physical_resource.write_record(&msg)
     }
 
+    /// Reports a decision about a log or trace record, if the record is traced.
+    /// 
+    /// # Arguments
+    /// * `record` - the log or trace record
+    /// * `msg_id` - the ID of the message describing the decision
+    #[inline]
+    fn trace(&self, record: &dyn RecordData, msg_id: &'static str) {
+        if record.traced() { pipetrace::resource_decision(record, msg_id, &self.label); }
+    }
+
+    /// Applies the throttle policy to a log or trace record.
+    /// Writes the summary record for the previous interval, if records have been suppressed
+    /// there.
+    /// 
+    /// # Arguments
+    /// * `record` - the log or trace record
+    /// * `output_format` - the output format to use
+    /// 
+    /// # Return values
+    /// **true** if the record may be written, **false** if it exceeds the policy's limit
+    /// 
+    /// # Errors
+    /// Returns an error structure if the summary record could not be written
+    fn throttle_admits(&mut self,
+                       record: &dyn RecordData,
+                       output_format: &OutputFormat) -> Result<bool, Vec<CoalyException>> {
+        let throttle = self.throttle.as_mut().unwrap();
+        let summary = throttle.end_interval_if_due(Instant::now());
+        let admitted = throttle.admit(record.level() as u32, record.thread_id(),
+                                      record.thread_name(), output_format);
+        if let Some(summary) = summary { self.write_summary(summary)?; }
+        Ok(admitted)
+    }
+
+    /// Writes the summary record about records suppressed by the throttle policy or the
+    /// duplicate suppression of the buffer policy.
+    /// The record bypasses the memory buffer.
+    /// 
+    /// # Arguments
+    /// * `summary` - summary message, thread ID, thread name and output format for the record
+    /// 
+    /// # Errors
+    /// Returns an error structure if the write operation fails
+    fn write_summary(&mut self,
+                     summary: (String, u64, String, OutputFormat))
+                     -> Result<(), Vec<CoalyException>> {
+        let (msg, thread_id, thread_name, ofmt) = summary;
+        let rec = LocalRecordData::for_write(thread_id, &thread_name, RecordLevelId::Warning,
+                                             file!(), line!(), &msg);
+        self.write_through(&rec, &ofmt)
+    }
+
     /// Closes the resource.
     /// Flushes buffer to physical resource, if configured for flush on exit.
+    /// Writes the summary record for records suppressed by the throttle policy.
     /// Closes physical resource, if applicable.
-    pub(crate) fn close(&mut self) {
-        let _ = self.flush_buffer();
-        self.physical_resource.close();
+    /// The flush is skipped, if the shutdown deadline or the resource's own flush deadline has
+    /// already passed.
+    /// 
+    /// # Arguments
+    /// * `shutdown_deadline` - the point in time when the shutdown must be completed,
+    ///   **None** if unlimited
+    pub(crate) fn close(&mut self, shutdown_deadline: Option<Instant>) {
+        let now = Instant::now();
+        let deadline = match (shutdown_deadline, self.flush_deadline) {
+            (Some(sd), Some(fd)) => Some(min(sd, now + fd)),
+            (sd, fd) => sd.or_else(|| fd.map(|fd| now + fd))
+        };
+        if deadline.is_none_or(|d| now < d) {
+            let _ = self.flush_buffer();
+            if let Some(summary) = self.throttle.as_mut().and_then(|t| t.take_summary()) {
+                let _ = self.write_summary(summary);
+            }
+        }
+        self.physical_resource.close(deadline);
     }
 
+    /// Returns the priority for flushing the resource upon shutdown.
+    #[inline]
+    pub(crate) fn flush_priority(&self) -> u32 { self.flush_priority }
+
     /// Performs a rollover of a file based resource if the rollover is due.
+    /// Posts the pending batches of an HTTP resource, if they are due.
+    /// Shrinks a grown memory buffer to its initial size, if it has been idle long enough.
+    /// Writes the summary record for records suppressed by the throttle policy, if the
+    /// throttle interval has elapsed.
     /// 
     /// # Arguments
     /// * `now` - current timestamp
     pub(crate) fn rollover_if_due(&mut self,
                                   now: &DateTime<Local>) -> Result<(), CoalyException> {
+        if let Some((interval, due)) = self.create_retry {
+            // creation failed at startup, nothing to roll over until the file exists
+            let inst_now = Instant::now();
+            if inst_now < due { return Ok(()) }
+            match self.physical_resource.create() {
+                Ok(()) => self.create_retry = None,
+                Err(_) => {
+                    self.create_retry = Some((interval, inst_now + interval));
+                    return Ok(())
+                }
+            }
+        }
+        self.shrink_buffer_if_idle();
+        let summary = self.throttle.as_mut().and_then(|t| t.end_interval_if_due(Instant::now()));
+        if let Some(summary) = summary {
+            if let Err(mut exs) = self.write_summary(summary) {
+                if ! exs.is_empty() { return Err(exs.remove(0)) }
+            }
+        }
         self.physical_resource.rollover_if_due(now)
     }
 
+    /// Creates a plain or memory mapped file upon application start, if the resource's policy
+    /// for file creation errors is not the default warning.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `desc` - the resource descriptor from the configuration
+    /// * `sys_props` - the system properties
+    ///
+    /// # Return values
+    /// a warning, if the file creation is retried later or the file was created in the
+    /// fallback path; otherwise **None**
+    ///
+    /// # Errors
+    /// Returns an error structure if the file couldn't be created and the policy demands to fail
+    /// initialization, or if the file couldn't be created in the fallback path either
+    pub(crate) fn create_at_startup(&mut self,
+                                    desc: &ResourceDesc,
+                                    sys_props: &SystemProperties)
+                                    -> Result<Option<CoalyException>, CoalyException> {
+        let fdata = match desc.file_data() {
+            Some(fdata) => fdata,
+            None => return Ok(None)
+        };
+        let policy = fdata.create_error_policy();
+        if policy == CreateErrorPolicy::Warn {
+            // plain files are created upon first write, memory mapped files right now
+            #[cfg(feature="mmfile")]
+            if let PhysicalResource::MemMappedFile(f) = &mut self.physical_resource { f.create()?; }
+            return Ok(None)
+        }
+        let file_path = self.physical_resource.file_path();
+        let cause = match self.physical_resource.create() {
+            Ok(()) => return Ok(None),
+            Err(ex) => ex
+        };
+        match policy {
+            CreateErrorPolicy::Retry => {
+                let interval = Duration::from_secs(fdata.retry_interval());
+                self.create_retry = Some((interval, Instant::now() + interval));
+                let mut ex = coalyxw!(W_RES_CREATE_RETRY, file_path,
+                                      fdata.retry_interval().to_string());
+                ex.set_cause(cause);
+                Ok(Some(ex))
+            },
+            CreateErrorPolicy::Fallback => {
+                self.physical_resource.relocate(Path::new(sys_props.fallback_path()));
+                match self.physical_resource.create() {
+                    Ok(()) => {
+                        let mut ex = coalyxw!(W_RES_USING_FALLBACK, file_path,
+                                              sys_props.fallback_path().to_string());
+                        ex.set_cause(cause);
+                        Ok(Some(ex))
+                    },
+                    Err(fb_cause) => {
+                        let mut ex = coalyxe!(E_RES_CREATE_FAILED, file_path);
+                        ex.set_cause(fb_cause);
+                        Err(ex)
+                    }
+                }
+            },
+            _ => {
+                let mut ex = coalyxe!(E_RES_CREATE_FAILED, file_path);
+                ex.set_cause(cause);
+                Err(ex)
+            }
+        }
+    }
+
+    /// Handles a flush or rollover request received by signal.
+    /// Flushes the memory buffer, if the buffer policy contains flush condition signal.
+    /// Performs a rollover of a plain or memory mapped file afterwards, if requested.
+    ///
+    /// # Arguments
+    /// * `rollover` - indicates whether a file rollover was requested
+    ///
+    /// # Errors
+    /// Returns an array with error structures if flush or rollover fail
+    pub(crate) fn handle_signal(&mut self, rollover: bool) -> Result<(), Vec<CoalyException>> {
+        if self.buffer_flush_required_upon(BufferFlushCondition::Signal as u32) {
+            self.flush_buffer()?;
+        }
+        if rollover { self.physical_resource.rollover().map_err(|e| vec!(e))?; }
+        Ok(())
+    }
+
+    /// Opens the underlying file handles and network sockets of this resource anew.
+    /// Used after the process changed its user identity, so that no handles opened under the
+    /// previous identity remain.
+    ///
+    /// # Errors
+    /// Returns an array with error structures if a handle can't be opened
+    pub(crate) fn reopen(&mut self) -> Result<(), Vec<CoalyException>> {
+        // file not created yet, nothing to do
+        if self.create_retry.is_some() { return Ok(()) }
+        self.flush_buffer()?;
+        self.physical_resource.reopen().map_err(|e| vec!(e))
+    }
+
+    /// Changes the output directory for files created from now on.
+    /// An open file is continued in its current directory until the next rollover.
+    /// A call to this method has no effect for resources not based on files.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    #[inline]
+    pub(crate) fn change_output_dir(&mut self, dir: &Path) {
+        self.physical_resource.change_output_dir(dir)
+    }
+
+    /// Returns the forecast for the next rollover of a file based resource.
+    /// Returns **None** for all other resource kinds.
+    #[inline]
+    pub(crate) fn rollover_forecast(&self) -> Option<RolloverForecast> {
+        self.physical_resource.rollover_forecast()
+    }
+
+    /// Performs the startup probe of the resource.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    #[inline]
+    pub(crate) fn probe(&self, label: &str) -> ProbeResult {
+        self.physical_resource.probe(label)
+    }
+
     /// Indicates, whether this resource is specific for an originator.
     #[inline]
     pub(crate) fn is_originator_specific(&self) -> bool {
@@ -289,10 +867,12 @@ impl Resource {
     /// # Arguments
     /// * `thread_id` - thread ID
     /// * `thread_name` - thread name
+    /// * `context` - the thread's context map
     pub(crate) fn thread_optimized_name(&self,
                                         thread_id: u64,
-                                        thread_name: &str) -> Option<FormatSpec> {
-        self.physical_resource.thread_optimized_name(thread_id, thread_name)
+                                        thread_name: &str,
+                                        context: Option<&ContextMap>) -> Option<FormatSpec> {
+        self.physical_resource.thread_optimized_name(thread_id, thread_name, context)
     }
 
     /// Updates the file name specification with the given value.
@@ -315,9 +895,21 @@ impl Resource {
                              name_spec: FormatSpec) -> Result<Resource, CoalyException> {
         let phy_res = self.physical_resource.for_thread(name_spec)?;
         Ok(Resource { levels: self.levels,
+                      filter: self.filter.clone(),
+                      throttle: self.throttle.clone(),
+                      sampler: self.sampler.clone(),
+                      deduplicator: self.deduplicator.clone(),
+                      adaptive_flush: self.adaptive_flush.clone(),
+                      degradable: self.degradable,
+                      flush_priority: self.flush_priority,
+                      flush_deadline: self.flush_deadline,
+                      create_retry: None,
                       buffer: None,
+                      last_buffer_write: None,
                       buffer_policy: self.buffer_policy.clone(),
                       output_format_template: self.output_format_template.clone(),
+                      counters: self.counters.clone(),
+                      label: self.label.clone(),
                       physical_resource: phy_res,
                       #[cfg(feature="net")]
                       serialization_buffer: None
@@ -338,15 +930,44 @@ impl Resource {
                                  name_spec: FormatSpec) -> Result<Resource, CoalyException> {
         let phy_res = self.physical_resource.for_originator(name_spec)?;
         Ok(Resource { levels: self.levels,
+                      filter: self.filter.clone(),
+                      throttle: self.throttle.clone(),
+                      sampler: self.sampler.clone(),
+                      deduplicator: self.deduplicator.clone(),
+                      adaptive_flush: self.adaptive_flush.clone(),
+                      degradable: self.degradable,
+                      flush_priority: self.flush_priority,
+                      flush_deadline: self.flush_deadline,
+                      create_retry: None,
                       buffer: None,
+                      last_buffer_write: None,
                       buffer_policy: self.buffer_policy.clone(),
                       output_format_template: self.output_format_template.clone(),
+                      counters: self.counters.clone(),
+                      label: self.label.clone(),
                       physical_resource: phy_res,
                       #[cfg(feature="net")]
                       serialization_buffer: None
                    })
     }
 
+    /// Creates the capture file of a mode change, using the default output format and rollover
+    /// policy. All record levels are associated with the file, records are never buffered.
+    ///
+    /// # Arguments
+    /// * `config` - the system configuration
+    /// * `name_spec` - the file name specification, optimized for originator and thread
+    pub(crate) fn capture_file(config: &Configuration,
+                               name_spec: FormatSpec) -> Result<Resource, CoalyException> {
+        let label = format!("{}:{}", ResourceKind::PlainFile, name_spec.to_file_name());
+        let mut res = Resource::plain_file(RecordLevelId::All as u32, config.system_properties(),
+                                           name_spec, config.buffer_policy(&None),
+                                           config.rollover_policy(&None),
+                                           output_format(config, &None), CharEncoding::Utf8)?;
+        res.label = label;
+        Ok(res)
+    }
+
     /// Indicates whether the memory buffer must be flushed upon the specified event.
     /// 
     /// # Arguments
@@ -362,37 +983,67 @@ impl Resource {
     ///
     /// # Arguments
     /// * `levels` - the bit mask with all record levels associated with the resource
-    /// * `output_dir` - the output directory
+    /// * `sys_props` - the system properties
     /// * `name_spec` - the file name specification
     /// * `buffer_policy` - the buffer policy
     /// * `rollover_policy` - the rollover policy
     /// * `output_format_template` - the output format template
+    /// * `encoding` - the character encoding
     fn plain_file(levels: u32,
-                  output_dir: &Path,
+                  sys_props: &SystemProperties,
                   name_spec: FormatSpec,
                   buffer_policy: &BufferPolicy,
                   rollover_policy: &RolloverPolicy,
-                  output_format_template: OutputFormat) -> Result<Resource, CoalyException> {
+                  output_format_template: OutputFormat,
+                  encoding: CharEncoding) -> Result<Resource, CoalyException> {
+        let output_dir = Path::new(sys_props.output_path());
+        let name_rules = &file_name_rules(sys_props);
         if name_spec.is_thread_specific() {
             // name spec contains thread ID or name, create file template
-            let tpl = FileTemplateData::new(output_dir, name_spec, rollover_policy);
+            let tpl = FileTemplateData::new(output_dir, name_spec, rollover_policy, name_rules,
+                                            encoding);
             return Ok(Resource {
                           levels,
+                          filter: None,
+                          throttle: None,
+                          sampler: None,
+                          deduplicator: None,
+                          adaptive_flush: None,
+                          degradable: false,
+                          flush_priority: 0,
+                          flush_deadline: None,
+                          create_retry: None,
                           buffer: None,
+                          last_buffer_write: None,
                           buffer_policy: buffer_policy.clone(),
                           output_format_template,
+                          counters: None,
+                          label: String::new(),
                           physical_resource: PhysicalResource::FileTemplate(tpl),
                           #[cfg(feature="net")]
                           serialization_buffer: None
                         })
         }
         // name spec is not thread specific, create file
-        let phy_res = FileData::new(output_dir, name_spec, rollover_policy)?;
+        let phy_res = FileData::new(output_dir, name_spec, rollover_policy, name_rules,
+                                    encoding)?;
         Ok(Resource {
                levels,
+               filter: None,
+               throttle: None,
+               sampler: None,
+               deduplicator: None,
+               adaptive_flush: None,
+               degradable: false,
+               flush_priority: 0,
+               flush_deadline: None,
+               create_retry: None,
                buffer: None,
+               last_buffer_write: None,
                buffer_policy: buffer_policy.clone(),
                output_format_template,
+               counters: None,
+               label: String::new(),
                physical_resource: PhysicalResource::File(phy_res),
                 #[cfg(feature="net")]
                 serialization_buffer: None
@@ -405,40 +1056,77 @@ impl Resource {
     ///
     /// # Arguments
     /// * `levels` - the bit mask with all record levels associated with the resource
-    /// * `output_dir` - the output directory
+    /// * `sys_props` - the system properties
     /// * `name_spec` - the file name specification
     /// * `buffer_policy` - the buffer policy
     /// * `rollover_policy` - the rollover policy
     /// * `output_format_template` - the output format template
     /// * `file_size` - the size of the backing file
+    /// * `calibration` - the calibration for tick timestamps, **None** for wall clock timestamps
+    /// * `ring` - indicates whether the file wraps around instead of rolling over
+    /// * `deferred` - indicates whether to defer the creation of the backing file
+    #[cfg(feature="mmfile")]
+    #[allow(clippy::too_many_arguments)]
     fn mm_file(levels: u32,
-               output_dir: &Path,
+               sys_props: &SystemProperties,
                name_spec: FormatSpec,
                file_size: usize,
                buffer_policy: &BufferPolicy,
                rollover_policy: &RolloverPolicy,
-               output_format_template: OutputFormat) -> Result<Resource, CoalyException> {
+               output_format_template: OutputFormat,
+               calibration: Option<TickCalibration>,
+               ring: bool,
+               deferred: bool) -> Result<Resource, CoalyException> {
+        let output_dir = Path::new(sys_props.output_path());
+        let name_rules = &file_name_rules(sys_props);
         if name_spec.is_thread_specific() {
             // name spec contains thread ID or name, create file template
             let tpl = MemMappedFileTemplateData::new(output_dir, name_spec,
-                                                     file_size, rollover_policy);
+                                                     file_size, rollover_policy, name_rules,
+                                                     calibration, ring);
             return Ok(Resource {
                           levels,
+                          filter: None,
+                          throttle: None,
+                          sampler: None,
+                          deduplicator: None,
+                          adaptive_flush: None,
+                          degradable: false,
+                          flush_priority: 0,
+                          flush_deadline: None,
+                          create_retry: None,
                           buffer: None,
+                          last_buffer_write: None,
                           buffer_policy: buffer_policy.clone(),
                           output_format_template,
+                          counters: None,
+                          label: String::new(),
                           physical_resource: PhysicalResource::MemMappedFileTemplate(tpl),
                           #[cfg(feature="net")]
                           serialization_buffer: None
                         })
         }
         // name spec is not thread specific, create file
-        let phy_res = MemMappedFileData::new(output_dir, name_spec, file_size, rollover_policy)?;
+        let phy_res = MemMappedFileData::new(output_dir, name_spec, file_size,
+                                             rollover_policy, name_rules, calibration,
+                                             ring, deferred)?;
         Ok(Resource {
             levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
             buffer: None,
+            last_buffer_write: None,
             buffer_policy: buffer_policy.clone(),
             output_format_template,
+            counters: None,
+            label: String::new(),
             physical_resource: PhysicalResource::MemMappedFile(phy_res),
             #[cfg(feature="net")]
             serialization_buffer: None
@@ -459,6 +1147,11 @@ impl Resource {
               orig_info: &OriginatorInfo,
               output_format_template: OutputFormat) -> Result<Resource, CoalyException> {
         let peer_addr = parse_url(desc.remote_url())?;
+        // syslog over TLS is not supported
+        #[cfg(feature="tls")]
+        if *peer_addr.protocol() == NetworkProtocol::Tls {
+            return Err(coalyxe!(E_INVALID_URL, desc.remote_url().to_string()))
+        }
         let mut local_addr: Option<PeerAddr> = None;
         if let Some(la) = desc.local_url() {
             let laddr = parse_url(la)?;
@@ -469,9 +1162,21 @@ impl Resource {
         syslog_res.connect(local_addr)?;
         Ok(Resource {
             levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
             buffer: None,
+            last_buffer_write: None,
             buffer_policy: buffer_policy.clone(),
             output_format_template,
+            counters: None,
+            label: String::new(),
             physical_resource: PhysicalResource::Syslog(syslog_res),
             serialization_buffer: None
         })
@@ -482,12 +1187,14 @@ impl Resource {
     /// # Arguments
     /// * `levels` - the bit mask with all record levels associated with the resource
     /// * `desc` - the network interface resource descriptor
+    /// * `sys_props` - the system properties, needed for the spool file's directory
     /// * `buffer_policy` - the buffer policy
     /// * `orig_info` - information about application process and local host
     /// * `output_format_template` - the output format template
     #[cfg(feature="net")]
     fn network(levels: u32,
                desc: &NetworkResourceDesc,
+               sys_props: &SystemProperties,
                buffer_policy: &BufferPolicy,
                orig_info: &OriginatorInfo,
                output_format_template: OutputFormat) -> Result<Resource, CoalyException> {
@@ -498,18 +1205,303 @@ impl Resource {
             if ! peer_addr.can_talk_to(&laddr) { return Err(coalyxe!(E_CFG_NW_PROT_MISMATCH)) }
             local_addr = Some(laddr);
         }
-        let mut nw_res = NetworkData::new(peer_addr);
+        let spool = match desc.spool_file_name() {
+            Some(n) => Some(Spool::open(&Path::new(sys_props.output_path()).join(n))?),
+            None => None
+        };
+        #[cfg(feature="tls")]
+        let tls_connector = if *peer_addr.protocol() == NetworkProtocol::Tls {
+            Some(TlsConnector::new(desc.tls_settings(), &peer_addr)?)
+        } else { None };
+        let spill = match desc.spill_file_name() {
+            Some(n) => Some(Spool::open(&Path::new(sys_props.output_path()).join(n))?),
+            None => None
+        };
+        let reconnection = Reconnection::new(Duration::from_secs(desc.reconnect_delay()),
+                                             Duration::from_secs(desc.max_reconnect_delay()),
+                                             spill);
+        let mut nw_res = NetworkData::new(peer_addr, desc.stream_compression(), spool,
+                                          reconnection);
+        #[cfg(feature="tls")]
+        if let Some(c) = tls_connector { nw_res.use_tls(c); }
         nw_res.connect(local_addr, orig_info)?;
         Ok(Resource {
             levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
             buffer: None,
+            last_buffer_write: None,
             buffer_policy: buffer_policy.clone(),
             output_format_template,
+            counters: None,
+            label: String::new(),
             physical_resource: PhysicalResource::Network(nw_res),
             serialization_buffer: None
         })
     }
 
+    /// Creates a webhook resource.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with all record levels associated with the resource
+    /// * `desc` - the webhook resource descriptor
+    /// * `buffer_policy` - the buffer policy
+    /// * `output_format_template` - the output format template
+    #[cfg(feature="net")]
+    fn webhook(levels: u32,
+               desc: &WebhookResourceDesc,
+               buffer_policy: &BufferPolicy,
+               output_format_template: OutputFormat) -> Result<Resource, CoalyException> {
+        let hook = WebhookData::new(desc.url(), desc.payload(), desc.min_interval())?;
+        Ok(Resource {
+            levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
+            buffer: None,
+            last_buffer_write: None,
+            buffer_policy: buffer_policy.clone(),
+            output_format_template,
+            counters: None,
+            label: String::new(),
+            physical_resource: PhysicalResource::Webhook(hook),
+            serialization_buffer: None
+        })
+    }
+
+    /// Creates a GELF resource.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with all record levels associated with the resource
+    /// * `url` - the URL of the Graylog input
+    /// * `buffer_policy` - the buffer policy
+    /// * `orig_info` - the information about the application issuing the records
+    /// * `output_format_template` - the output format template
+    #[cfg(feature="net")]
+    fn gelf(levels: u32,
+            url: &str,
+            buffer_policy: &BufferPolicy,
+            orig_info: &OriginatorInfo,
+            output_format_template: OutputFormat) -> Result<Resource, CoalyException> {
+        let gelf_res = GelfData::new(url, orig_info)?;
+        Ok(Resource {
+            levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
+            buffer: None,
+            last_buffer_write: None,
+            buffer_policy: buffer_policy.clone(),
+            output_format_template,
+            counters: None,
+            label: String::new(),
+            physical_resource: PhysicalResource::Gelf(gelf_res),
+            serialization_buffer: None
+        })
+    }
+
+    /// Creates an HTTP resource.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with all record levels associated with the resource
+    /// * `desc` - the HTTP resource descriptor
+    /// * `buffer_policy` - the buffer policy
+    /// * `orig_info` - the information about the application issuing the records
+    /// * `output_format_template` - the output format template
+    #[cfg(feature="net")]
+    fn http(levels: u32,
+            desc: &HttpResourceDesc,
+            buffer_policy: &BufferPolicy,
+            orig_info: &OriginatorInfo,
+            output_format_template: OutputFormat) -> Result<Resource, CoalyException> {
+        let http_res = HttpData::new(desc, orig_info)?;
+        Ok(Resource {
+            levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
+            buffer: None,
+            last_buffer_write: None,
+            buffer_policy: buffer_policy.clone(),
+            output_format_template,
+            counters: None,
+            label: String::new(),
+            physical_resource: PhysicalResource::Http(Box::new(http_res)),
+            serialization_buffer: None
+        })
+    }
+
+    /// Creates a named pipe resource.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with all record levels associated with the resource
+    /// * `sys_props` - the system properties
+    /// * `pipe_name` - the name of the pipe
+    /// * `buffer_policy` - the buffer policy
+    /// * `output_format_template` - the output format template
+    #[cfg(feature="os-resources")]
+    fn pipe(levels: u32,
+            sys_props: &SystemProperties,
+            pipe_name: &str,
+            buffer_policy: &BufferPolicy,
+            output_format_template: OutputFormat) -> Resource {
+        let output_dir = Path::new(sys_props.output_path());
+        Resource {
+            levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
+            buffer: None,
+            last_buffer_write: None,
+            buffer_policy: buffer_policy.clone(),
+            output_format_template,
+            counters: None,
+            label: String::new(),
+            physical_resource: PhysicalResource::Pipe(PipeData::new(output_dir, pipe_name)),
+            #[cfg(feature="net")]
+            serialization_buffer: None
+        }
+    }
+
+    /// Creates a desktop notification resource.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with all record levels associated with the resource
+    /// * `app_name` - the application name shown in the notification
+    /// * `min_interval` - the minimum interval between two notifications, in seconds
+    /// * `buffer_policy` - the buffer policy
+    /// * `output_format_template` - the output format template
+    #[cfg(feature="os-resources")]
+    fn notification(levels: u32,
+                    app_name: &str,
+                    min_interval: u64,
+                    buffer_policy: &BufferPolicy,
+                    output_format_template: OutputFormat) -> Resource {
+        let ndata = NotificationData::new(app_name, min_interval);
+        Resource {
+            levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
+            buffer: None,
+            last_buffer_write: None,
+            buffer_policy: buffer_policy.clone(),
+            output_format_template,
+            counters: None,
+            label: String::new(),
+            physical_resource: PhysicalResource::Notification(ndata),
+            #[cfg(feature="net")]
+            serialization_buffer: None
+        }
+    }
+
+    /// Creates a Windows event log resource.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with all record levels associated with the resource
+    /// * `source_name` - the event source name
+    /// * `buffer_policy` - the buffer policy
+    /// * `output_format_template` - the output format template
+    #[cfg(all(feature="os-resources", windows))]
+    fn eventlog(levels: u32,
+                source_name: &str,
+                buffer_policy: &BufferPolicy,
+                output_format_template: OutputFormat) -> Resource {
+        Resource {
+            levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
+            buffer: None,
+            last_buffer_write: None,
+            buffer_policy: buffer_policy.clone(),
+            output_format_template,
+            counters: None,
+            label: String::new(),
+            physical_resource: PhysicalResource::EventLog(EventLogData::new(source_name)),
+            #[cfg(feature="net")]
+            serialization_buffer: None
+        }
+    }
+
+    /// Creates a systemd journal resource.
+    ///
+    /// # Arguments
+    /// * `levels` - the bit mask with all record levels associated with the resource
+    /// * `identifier` - the optional syslog identifier attached to all records
+    /// * `buffer_policy` - the buffer policy
+    /// * `output_format_template` - the output format template
+    #[cfg(all(feature="os-resources", target_os="linux"))]
+    fn journal(levels: u32,
+               identifier: &Option<String>,
+               buffer_policy: &BufferPolicy,
+               output_format_template: OutputFormat) -> Resource {
+        Resource {
+            levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
+            buffer: None,
+            last_buffer_write: None,
+            buffer_policy: buffer_policy.clone(),
+            output_format_template,
+            counters: None,
+            label: String::new(),
+            physical_resource: PhysicalResource::Journal(JournalData::new(identifier)),
+            #[cfg(feature="net")]
+            serialization_buffer: None
+        }
+    }
+
     /// Creates a stdout resource.
     ///
     /// # Arguments
@@ -521,9 +1513,21 @@ impl Resource {
               output_format_template: OutputFormat) -> Resource {
         Resource {
             levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
             buffer: None,
+            last_buffer_write: None,
             buffer_policy: buffer_policy.clone(),
             output_format_template,
+            counters: None,
+            label: String::new(),
             physical_resource: PhysicalResource::StdOut,
             #[cfg(feature="net")]
             serialization_buffer: None
@@ -541,29 +1545,132 @@ impl Resource {
               output_format_template: OutputFormat) -> Resource {
         Resource {
             levels,
+            filter: None,
+            throttle: None,
+            sampler: None,
+            deduplicator: None,
+            adaptive_flush: None,
+            degradable: false,
+            flush_priority: 0,
+            flush_deadline: None,
+            create_retry: None,
             buffer: None,
+            last_buffer_write: None,
             buffer_policy: buffer_policy.clone(),
             output_format_template,
+            counters: None,
+            label: String::new(),
             physical_resource: PhysicalResource::StdErr,
             #[cfg(feature="net")]
             serialization_buffer: None
         }
     }
 
+    /// Doubles the size of the memory buffer, limited to the maximum content size specified in
+    /// the buffer policy.
+    fn grow_buffer(&mut self) {
+        if let Some(ref buf) = self.buffer {
+            let max_size = self.buffer_policy.max_content_size();
+            if buf.size() >= max_size { return }
+            let new_size = granted_buffer_size(min(max_size, buf.size().saturating_mul(2)),
+                                               buf.size());
+            if new_size <= buf.size() { return }
+            self.buffer = Some(buf.resized(new_size, self.buffer_policy.max_record_length()));
+        }
+    }
+
+    /// Shrinks a grown memory buffer to its initial size, if no record has been written to the
+    /// buffer for the time specified in the buffer policy.
+    /// If the buffer policy demands a flush when the buffer is full, the buffer is flushed
+    /// before, otherwise only the most recent records fitting into the shrunk buffer are kept.
+    fn shrink_buffer_if_idle(&mut self) {
+        let shrink_after = self.buffer_policy.shrink_after();
+        if shrink_after == 0 { return }
+        if let Some(ref buf) = self.buffer {
+            let initial_size = self.buffer_policy.content_size();
+            if buf.size() <= initial_size { return }
+            if let Some(t) = self.last_buffer_write {
+                if t.elapsed() < Duration::from_secs(shrink_after) { return }
+            }
+            if self.buffer_flush_required_upon(BufferFlushCondition::Full as u32) {
+                let _ = self.flush_buffer();
+            }
+            let buf = self.buffer.as_ref().unwrap();
+            self.buffer = Some(buf.resized(initial_size, self.buffer_policy.max_record_length()));
+        }
+    }
+
+    /// Shrinks the memory buffer to its fair share of the process wide memory limit, if the
+    /// limit is exceeded and the buffer is larger than its share.
+    /// If the buffer policy demands a flush when the buffer is full, the buffer is flushed
+    /// before, otherwise the oldest records not fitting into the shrunk buffer are dropped.
+    fn limit_buffer_memory(&mut self) {
+        if ! buffer_memory_limit_exceeded() { return }
+        if let Some(ref buf) = self.buffer {
+            let fair_share = buffer_fair_share();
+            if buf.size() <= fair_share { return }
+            if self.buffer_flush_required_upon(BufferFlushCondition::Full as u32) {
+                let _ = self.flush_buffer();
+            }
+            let buf = self.buffer.as_ref().unwrap();
+            self.buffer = Some(buf.resized(fair_share, self.buffer_policy.max_record_length()));
+        }
+    }
+
+    /// Flushes the memory buffer, if the buffer policy adapts the flush to the output latency
+    /// and the buffered records reached the current batch size.
+    ///
+    /// # Errors
+    /// Returns an error structure if the write operation failed
+    fn flush_if_batch_complete(&mut self) -> Result<(), Vec<CoalyException>> {
+        let due = match (&self.adaptive_flush, &self.buffer) {
+            (Some(a), Some(b)) => ! b.is_empty() && a.flush_due(b.used_space()),
+            _ => false
+        };
+        if due { self.flush_buffer() } else { Ok(()) }
+    }
+
     /// Flush contents of associated memory buffer to physical resource.
+    /// Writes the summary record for duplicate records suppressed since the last flush.
     /// 
     /// # Errors
     /// Returns an error structure if the write operation failed
     fn flush_buffer(&mut self) -> Result<(), Vec<CoalyException>> {
         if let Some(ref mut buf) = &mut self.buffer {
-            match &self.physical_resource {
-                PhysicalResource::File(_) | PhysicalResource::StdOut | PhysicalResource::StdErr => {
+            stats::buffer_flushed();
+            let started = Instant::now();
+            let record_count = buf.record_count();
+            let written = match &self.physical_resource {
+                PhysicalResource::File(_) | PhysicalResource::StdOut
+                                          | PhysicalResource::StdErr => {
+                    if let Some(data) = buf.chunk(0) { self.physical_resource.write_chunk(data)?; }
+                    if let Some(data) = buf.chunk(1) { self.physical_resource.write_chunk(data)?; }
+                    buf.clear();
+                    true
+                },
+                #[cfg(feature="os-resources")]
+                PhysicalResource::Pipe(_) | PhysicalResource::Notification(_) => {
+                    if let Some(data) = buf.chunk(0) { self.physical_resource.write_chunk(data)?; }
+                    if let Some(data) = buf.chunk(1) { self.physical_resource.write_chunk(data)?; }
+                    buf.clear();
+                    true
+                },
+                #[cfg(feature="net")]
+                PhysicalResource::Webhook(_) | PhysicalResource::Http(_) => {
                     if let Some(data) = buf.chunk(0) { self.physical_resource.write_chunk(data)?; }
                     if let Some(data) = buf.chunk(1) { self.physical_resource.write_chunk(data)?; }
                     buf.clear();
+                    true
                 },
-                PhysicalResource::FileTemplate(_) | PhysicalResource::MemMappedFileTemplate(_)
-                                                  | PhysicalResource::MemMappedFile(_) => (),
+                PhysicalResource::FileTemplate(_) => false,
+                #[cfg(feature="mmfile")]
+                PhysicalResource::MemMappedFileTemplate(_) | PhysicalResource::MemMappedFile(_) => false,
+                #[cfg(all(feature="os-resources", windows))]
+                PhysicalResource::EventLog(_) => false,
+                #[cfg(all(feature="os-resources", target_os="linux"))]
+                PhysicalResource::Journal(_) => false,
+                #[cfg(feature="net")]
+                PhysicalResource::Gelf(_) => false,
                 #[cfg(feature="net")]
                 PhysicalResource::Network(_) | PhysicalResource::Syslog(_) => {
                     for rec in buf.records().iter() {
@@ -576,9 +1683,19 @@ impl Resource {
                             self.physical_resource.write_chunk(rec.0)?;
                         }
                     }
+                    buf.clear();
+                    true
+                }
+            };
+            if written {
+                if let Some(ref mut a) = self.adaptive_flush {
+                    a.record_flush(started.elapsed(), record_count);
                 }
             }
         }
+        if let Some(summary) = self.deduplicator.as_mut().and_then(|d| d.take_summary()) {
+            self.write_summary(summary)?;
+        }
         Ok(())
     }
 }
@@ -586,16 +1703,111 @@ impl Resource {
 enum PhysicalResource {
     File(FileData),
     FileTemplate(FileTemplateData),
+    #[cfg(feature="mmfile")]
     MemMappedFile(MemMappedFileData),
+    #[cfg(feature="mmfile")]
     MemMappedFileTemplate(MemMappedFileTemplateData),
     StdOut,
     StdErr,
+    #[cfg(feature="os-resources")]
+    Pipe(PipeData),
+    #[cfg(feature="os-resources")]
+    Notification(NotificationData),
+    #[cfg(all(feature="os-resources", windows))]
+    EventLog(EventLogData),
+    #[cfg(all(feature="os-resources", target_os="linux"))]
+    Journal(JournalData),
     #[cfg(feature="net")]
     Network(NetworkData),
     #[cfg(feature="net")]
     Syslog(SyslogData),
+    #[cfg(feature="net")]
+    Webhook(WebhookData),
+    #[cfg(feature="net")]
+    Gelf(GelfData),
+    #[cfg(feature="net")]
+    Http(Box<HttpData>),
 }
 impl PhysicalResource {
+    /// Limits the size of a plain file, a rollover takes place when the limit is reached.
+    /// A call to this method has no effect for other resource kinds.
+    /// 
+    /// # Arguments
+    /// * `max_size` - the maximum file size in bytes, 0 for no limit
+    fn limit_file_size(&mut self, max_size: usize) {
+        match self {
+            PhysicalResource::File(f) => f.limit_size(max_size),
+            PhysicalResource::FileTemplate(t) => t.limit_size(max_size),
+            _ => ()
+        }
+    }
+
+    /// Sets the coordination mode for several processes writing to a plain file.
+    /// A call to this method has no effect for other resource kinds.
+    /// 
+    /// # Arguments
+    /// * `sharing` - the sharing mode
+    fn set_sharing(&mut self, sharing: FileSharing) {
+        match self {
+            PhysicalResource::File(f) => f.set_sharing(sharing),
+            PhysicalResource::FileTemplate(t) => t.set_sharing(sharing),
+            _ => ()
+        }
+    }
+
+    /// Switches a plain file or file template to binary format.
+    /// Has no effect for other resource kinds.
+    /// 
+    /// # Arguments
+    /// * `header` - the header to write at the start of each file
+    #[cfg(feature="net")]
+    fn set_binary_header(&mut self, header: Vec<u8>) {
+        match self {
+            PhysicalResource::File(f) => f.set_binary_header(header),
+            PhysicalResource::FileTemplate(t) => t.set_binary_header(header),
+            _ => ()
+        }
+    }
+
+    /// Encrypts the content of a plain or memory mapped file or file template.
+    /// Has no effect for other resource kinds.
+    /// 
+    /// # Arguments
+    /// * `cipher` - the encryption state
+    #[cfg(feature="encryption")]
+    fn set_cipher(&mut self, cipher: Cipher) {
+        match self {
+            PhysicalResource::File(f) => f.set_cipher(cipher),
+            PhysicalResource::FileTemplate(t) => t.set_cipher(cipher),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.set_cipher(cipher),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFileTemplate(t) => t.set_cipher(cipher),
+            _ => ()
+        }
+    }
+
+    /// Indicates whether the resource is a plain file written in binary format.
+    #[cfg(feature="net")]
+    #[inline]
+    fn is_binary(&self) -> bool {
+        matches!(self, PhysicalResource::File(f) if f.is_binary())
+    }
+
+    /// Indicates whether the resource receives serialized records instead of formatted ones.
+    #[cfg(feature="net")]
+    #[inline]
+    fn writes_serialized(&self) -> bool { self.is_proxy() || self.is_binary() }
+
+    /// Indicates whether the resource sends its records over the network.
+    /// HTTP batch resources are not included, they count their failed posts themselves.
+    #[cfg(feature="net")]
+    #[inline]
+    fn is_network(&self) -> bool {
+        matches!(self, PhysicalResource::Network(_) | PhysicalResource::Syslog(_) |
+                       PhysicalResource::Webhook(_) | PhysicalResource::Gelf(_))
+    }
+
     /// Indicates whether the resource is a proxy for a resource on a remote application.
     #[cfg(feature="net")]
     #[inline]
@@ -606,7 +1818,7 @@ impl PhysicalResource {
         }
     }
 
-    /// Sends a log or trace record to a remote application.
+    /// Sends a log or trace record to a remote application or writes it to a binary file.
     /// 
     /// # Arguments
     /// * `rec` - the log or trace record
@@ -615,9 +1827,12 @@ impl PhysicalResource {
     /// Returns an error structure if the send operation fails
     #[cfg(feature="net")]
     fn send_record(&mut self, rec: &dyn RecordData) -> Result<(), Vec<CoalyException>> {
+        #[cfg(feature="faultinjection")]
+        crate::output::faultinjection::check_write().map_err(|e| vec!(e))?;
         match self {
             PhysicalResource::Network(n) => n.send_record(rec),
             PhysicalResource::Syslog(s) => s.send_record(rec),
+            PhysicalResource::File(f) => f.write_frame(rec).map_err(|e| vec!(e)),
             _ => Ok(())
         }
     }
@@ -630,7 +1845,18 @@ impl PhysicalResource {
     /// # Errors
     /// Returns an error structure if the write operation fails
     fn write_record(&mut self, s: &str) -> Result<(), Vec<CoalyException>> {
-        if let PhysicalResource::MemMappedFile(f) = self { f.write_record(s); return Ok(())  }
+        #[cfg(feature="mmfile")]
+        if let PhysicalResource::MemMappedFile(f) = self {
+            #[cfg(feature="faultinjection")]
+            crate::output::faultinjection::check_write().map_err(|e| vec!(e))?;
+            return f.write_record(s).map_err(|e| vec!(e))
+        }
+        #[cfg(feature="net")]
+        if let PhysicalResource::Webhook(w) = self {
+            return w.alert(s).map_err(|e| vec!(e))
+        }
+        #[cfg(feature="net")]
+        if let PhysicalResource::Http(h) = self { return h.add(s) }
         self.write_chunk(s.as_bytes())
     }
 
@@ -642,8 +1868,14 @@ impl PhysicalResource {
     /// # Errors
     /// Returns an error structure if the write operation fails
     fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), Vec<CoalyException>> {
+        #[cfg(feature="faultinjection")]
+        crate::output::faultinjection::check_write().map_err(|e| vec!(e))?;
         match self {
             PhysicalResource::File(f) => f.write(chunk).map_err(|e| vec!(e)),
+            #[cfg(feature="os-resources")]
+            PhysicalResource::Pipe(p) => p.write(chunk).map_err(|e| vec!(e)),
+            #[cfg(feature="os-resources")]
+            PhysicalResource::Notification(n) => { n.write(chunk); Ok(()) },
             PhysicalResource::StdOut => {
                 let stdout = io::stdout();
                 let mut handle = stdout.lock();
@@ -658,19 +1890,38 @@ impl PhysicalResource {
             },
             #[cfg(feature="net")]
             PhysicalResource::Network(n) => n.write(chunk),
+            #[cfg(feature="net")]
+            PhysicalResource::Webhook(w) => { w.add_context(chunk); Ok(()) },
+            #[cfg(feature="net")]
+            PhysicalResource::Http(h) => h.add(&String::from_utf8_lossy(chunk)),
             _ => Ok(())
         }
     }
 
     /// Closes the physical resource.
-    fn close(&mut self) {
+    /// 
+    /// # Arguments
+    /// * `deadline` - the point in time when the close operation must be completed,
+    ///   **None** if unlimited
+    fn close(&mut self, #[allow(unused_variables)] deadline: Option<Instant>) {
         match self {
             PhysicalResource::File(f) => f.close(),
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFile(f) => f.close(),
+            #[cfg(feature="os-resources")]
+            PhysicalResource::Pipe(p) => p.close(),
+            #[cfg(all(feature="os-resources", windows))]
+            PhysicalResource::EventLog(e) => e.close(),
+            #[cfg(all(feature="os-resources", target_os="linux"))]
+            PhysicalResource::Journal(j) => j.close(),
             #[cfg(feature="net")]
-            PhysicalResource::Network(n) => n.disconnect(),
+            PhysicalResource::Network(n) => n.disconnect(deadline),
             #[cfg(feature="net")]
             PhysicalResource::Syslog(s) => s.close(),
+            #[cfg(feature="net")]
+            PhysicalResource::Gelf(g) => g.close(),
+            #[cfg(feature="net")]
+            PhysicalResource::Http(h) => h.close(),
             _ => ()
         }
     }
@@ -679,8 +1930,10 @@ impl PhysicalResource {
     pub(crate) fn is_originator_specific(&self) -> bool {
         match self {
             PhysicalResource::File(f) => f.is_originator_specific(),
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFile(f) => f.is_originator_specific(),
             PhysicalResource::FileTemplate(t) => t.is_originator_specific(),
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFileTemplate(t) => t.is_originator_specific(),
             _ => false
         }
@@ -690,23 +1943,154 @@ impl PhysicalResource {
     pub(crate) fn is_thread_specific(&self) -> bool {
         match self {
             PhysicalResource::FileTemplate(t) => t.is_thread_specific(),
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFileTemplate(t) => t.is_thread_specific(),
             _ => false
         }
     }
 
     /// Performs a rollover of a file based resource if the rollover is due.
+    /// Posts the pending batches of an HTTP resource, if they are due.
     /// 
     /// # Arguments
     /// * `now` - current timestamp
     fn rollover_if_due(&mut self, now: &DateTime<Local>) -> Result<(), CoalyException> {
         match self {
             PhysicalResource::File(f) => f.rollover_if_due(now),
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFile(f) => f.rollover_if_due(now),
+            #[cfg(feature="net")]
+            PhysicalResource::Http(h) => h.post_if_due(Instant::now()),
+            _ => Ok(())
+        }
+    }
+
+    /// Creates the file of a plain or memory mapped file resource, if it doesn't exist yet.
+    /// A call to this method has no effect for other resource kinds.
+    fn create(&mut self) -> Result<(), CoalyException> {
+        match self {
+            PhysicalResource::File(f) => f.create(),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.create(),
             _ => Ok(())
         }
     }
 
+    /// Changes the output directory of a plain or memory mapped file resource, if the file
+    /// hasn't been created yet.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    fn relocate(&mut self, dir: &Path) {
+        match self {
+            PhysicalResource::File(f) => f.relocate(dir),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.relocate(dir),
+            _ => ()
+        }
+    }
+
+    /// Changes the output directory for plain or memory mapped files created from now on.
+    /// A call to this method has no effect for other resource kinds.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    fn change_output_dir(&mut self, dir: &Path) {
+        match self {
+            PhysicalResource::File(f) => f.change_output_dir(dir),
+            PhysicalResource::FileTemplate(t) => t.change_output_dir(dir),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.change_output_dir(dir),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFileTemplate(t) => t.change_output_dir(dir),
+            _ => ()
+        }
+    }
+
+    /// Returns the full path name of a plain or memory mapped file resource.
+    /// Returns an empty string for all other resource kinds.
+    fn file_path(&self) -> String {
+        match self {
+            PhysicalResource::File(f) => f.file_path(),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.file_path(),
+            _ => String::new()
+        }
+    }
+
+    /// Performs an immediate rollover of a file based resource.
+    /// A call to this method has no effect for other resource kinds.
+    fn rollover(&mut self) -> Result<(), CoalyException> {
+        match self {
+            PhysicalResource::File(f) => f.rollover_now(),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.rollover_now(),
+            _ => Ok(())
+        }
+    }
+
+    /// Opens the underlying file handle or network socket anew.
+    /// Pipes, journal and GELF sockets are closed only, they are opened again upon next write.
+    /// A call to this method has no effect for other resource kinds.
+    fn reopen(&mut self) -> Result<(), CoalyException> {
+        match self {
+            PhysicalResource::File(f) => f.reopen(),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.reopen(),
+            #[cfg(feature="os-resources")]
+            PhysicalResource::Pipe(p) => { p.close(); Ok(()) },
+            #[cfg(all(feature="os-resources", target_os="linux"))]
+            PhysicalResource::Journal(j) => { j.close(); Ok(()) },
+            #[cfg(feature="net")]
+            PhysicalResource::Network(n) => n.reconnect(),
+            #[cfg(all(feature="net", unix))]
+            PhysicalResource::Syslog(s) => s.reconnect(),
+            #[cfg(feature="net")]
+            PhysicalResource::Gelf(g) => { g.close(); Ok(()) },
+            _ => Ok(())
+        }
+    }
+
+    /// Returns the forecast for the next rollover of a file based resource.
+    /// Returns **None** for all other resource kinds.
+    fn rollover_forecast(&self) -> Option<RolloverForecast> {
+        match self {
+            PhysicalResource::File(f) => Some(f.rollover_forecast()),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => Some(f.rollover_forecast()),
+            _ => None
+        }
+    }
+
+    /// Performs the startup probe of the resource.
+    /// File based resources test their output directory, network based resources the
+    /// reachability of their peer. All other resource kinds are skipped.
+    ///
+    /// # Arguments
+    /// * `label` - the label identifying the resource
+    fn probe(&self, label: &str) -> ProbeResult {
+        match self {
+            PhysicalResource::File(f) => f.probe(label),
+            PhysicalResource::FileTemplate(t) => t.probe(label),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => f.probe(label),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFileTemplate(t) => t.probe(label),
+            #[cfg(feature="net")]
+            PhysicalResource::Network(n) => probe_peer(label, n.peer_addr()),
+            #[cfg(feature="net")]
+            PhysicalResource::Syslog(s) => probe_peer(label, s.peer_addr()),
+            #[cfg(feature="net")]
+            PhysicalResource::Webhook(w) => probe_peer(label, &w.peer_addr()),
+            #[cfg(feature="net")]
+            PhysicalResource::Gelf(g) => probe_peer(label, &g.peer_addr()),
+            #[cfg(feature="net")]
+            PhysicalResource::Http(h) => probe_peer(label, &h.peer_addr()),
+            _ => ProbeResult::skipped(label)
+        }
+    }
+
     /// Returns the name specification for this resource, optimized for the specified originator.
     /// Returns None, if the resource is not backed by a file template.
     /// 
@@ -718,12 +2102,14 @@ impl PhysicalResource {
             PhysicalResource::File(f) => {
                 Some(f.originator_optimized_name(orig_info))
             },
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFile(f) => {
                 Some(f.originator_optimized_name(orig_info))
             },
             PhysicalResource::FileTemplate(t) => {
                 Some(t.originator_optimized_name(orig_info))
             },
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFileTemplate(t) => {
                 Some(t.originator_optimized_name(orig_info))
             },
@@ -737,15 +2123,18 @@ impl PhysicalResource {
     /// # Arguments
     /// * `thread_id` - thread ID
     /// * `thread_name` - thread name
+    /// * `context` - the thread's context map
     pub(crate) fn thread_optimized_name(&self,
                                         thread_id: u64,
-                                        thread_name: &str) -> Option<FormatSpec> {
+                                        thread_name: &str,
+                                        context: Option<&ContextMap>) -> Option<FormatSpec> {
         match self {
             PhysicalResource::FileTemplate(t) => {
-                Some(t.thread_optimized_name(thread_id, thread_name))
+                Some(t.thread_optimized_name(thread_id, thread_name, context))
             },
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFileTemplate(t) => {
-                Some(t.thread_optimized_name(thread_id, thread_name))
+                Some(t.thread_optimized_name(thread_id, thread_name, context))
             },
             _ => None
         }
@@ -759,8 +2148,10 @@ impl PhysicalResource {
     pub(crate) fn use_optimized_name(&mut self, name_spec: FormatSpec) {
         match self {
             PhysicalResource::File(f) => f.update_namespec(name_spec),
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFile(f) => f.update_namespec(name_spec),
             PhysicalResource::FileTemplate(t) => t.update_namespec(name_spec),
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFileTemplate(t) => t.update_namespec(name_spec),
             _ => ()
         }
@@ -780,6 +2171,7 @@ impl PhysicalResource {
                 let r = t.instantiate(name_spec)?;
                 Ok(PhysicalResource::File(r))
             },
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFileTemplate(t) => {
                 let r = t.instantiate(name_spec)?;
                 Ok(PhysicalResource::MemMappedFile(r))
@@ -809,6 +2201,7 @@ impl PhysicalResource {
                 let r = t.instantiate(name_spec)?;
                 Ok(PhysicalResource::File(r))
             },
+            #[cfg(feature="mmfile")]
             PhysicalResource::MemMappedFileTemplate(t) => {
                 if name_spec.is_thread_specific() {
                     let opt_templ = t.for_originator(name_spec);
@@ -817,11 +2210,63 @@ impl PhysicalResource {
                 let r = t.instantiate(name_spec)?;
                 Ok(PhysicalResource::MemMappedFile(r))
             },
+            PhysicalResource::File(f) => Ok(PhysicalResource::File(f.for_originator(name_spec))),
+            #[cfg(feature="mmfile")]
+            PhysicalResource::MemMappedFile(f) => {
+                Ok(PhysicalResource::MemMappedFile(f.for_originator(name_spec)?))
+            },
             _ => Err(coalyxe!(E_INTERNAL_INV_TEMPLATE))
         }
     }
 }
 
+/// Creates the output format with the given name.
+/// If the configuration doesn't define an output format with that name, but the application
+/// registered a record formatter under the name, the formatter is used.
+///
+/// # Arguments
+/// * `config` - the system configuration
+/// * `name` - the output format name, **None** for the default format
+pub(crate) fn output_format(config: &Configuration, name: &Option<String>) -> OutputFormat {
+    let mut ofmt = OutputFormat::from_desc(config.output_format(name), config.date_time_formats(),
+                                           config.system_properties());
+    if let Some(n) = name.as_ref().filter(|n| ! config.defines_output_format(n)) {
+        if let Some(f) = registered_formatter(n) { ofmt.set_custom_formatter(f); }
+    }
+    ofmt
+}
+
+/// Returns the rules for variable values in file names.
+/// 
+/// # Arguments
+/// * `sys_props` - the system properties
+fn file_name_rules(sys_props: &SystemProperties) -> FileNameRules {
+    FileNameRules::new(sys_props.file_name_replacement_char(),
+                       sys_props.file_name_max_var_length())
+}
+
+/// Returns the encryption state for a resource.
+/// 
+/// # Arguments
+/// * `desc` - the resource descriptor
+/// * `sys_props` - the system properties
+/// 
+/// # Return values
+/// the encryption state, **None** if the resource is not encrypted
+/// 
+/// # Errors
+/// Returns an error structure if the resource is encrypted, but no key is configured or the
+/// key file could not be read
+#[cfg(feature="encryption")]
+fn resource_cipher(desc: &ResourceDesc,
+                   sys_props: &SystemProperties) -> Result<Option<Cipher>, CoalyException> {
+    if ! desc.file_data().is_some_and(|fd| fd.encrypted()) { return Ok(None) }
+    match sys_props.encryption_key() {
+        Some(spec) => Ok(Some(Cipher::new(spec)?)),
+        None => Err(coalyxe!(E_ENC_KEY_MISSING, desc.label()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::{Path, PathBuf};
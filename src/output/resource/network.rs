@@ -32,14 +32,25 @@
 
 //! Output resources of type network.
 
-use std::io::Write;
+use std::cmp::min;
+use std::io::{ErrorKind, Read, Write};
 use std::net::*;
-use crate::coalyxe;
+use std::time::{Duration, Instant};
+use crate::{coalyxe, coalyxw};
 use crate::errorhandling::*;
 use crate::net::*;
 use crate::record::originator::OriginatorInfo;
 use crate::record::recorddata::RecordData;
 use crate::net::buffer::SendBuffer;
+use crate::net::connector::{Connection, DeferredConnection, TcpConnection};
+use crate::net::fragment::{MAX_DATAGRAM_SIZE, fragments};
+#[cfg(feature="tls")]
+use crate::net::tls::TlsConnector;
+use crate::net::serializable::Serializable;
+use super::reconnect::Reconnection;
+use super::spool::Spool;
+#[cfg(feature="compression")]
+use crate::net::streamcompression::StreamCompressor;
 #[cfg(unix)]
 use std::os::unix::net::UnixStream;
 
@@ -52,31 +63,98 @@ pub struct NetworkData {
     // remote address
     remote_addr: PeerAddr,
     // TCP communication stream
-    tcp_stream: Option<TcpStream>,
+    tcp_stream: Option<TcpConnection>,
     // UDP communication socket
     udp_socket: Option<UdpSocket>,
     // Unix communication stream
     #[cfg(unix)]
-    unix_stream: Option<UnixStream>
+    unix_stream: Option<UnixStream>,
+    // TCP or UDP connection currently being established
+    pending: Option<DeferredConnection>,
+    // connector for TLS sessions, if the remote URL uses protocol tls
+    #[cfg(feature="tls")]
+    tls_connector: Option<Box<TlsConnector>>,
+    // compressor for the record stream over TCP, if stream compression is enabled
+    #[cfg(feature="compression")]
+    compressor: Option<StreamCompressor>,
+    // spool for guaranteed delivery over TCP
+    spool: Option<Spool>,
+    // data received from the server, not yet processed
+    rx_data: Vec<u8>,
+    // the optional socket address for the local network socket, needed to reconnect
+    local_addr: Option<PeerAddr>,
+    // information about process and local host, needed to reconnect
+    orig_info: Option<Box<OriginatorInfo>>,
+    // reconnect state and records held back while the connection is lost
+    reconnection: Box<Reconnection>
 }
 impl NetworkData {
     /// Creates specific structure to communicate over network.
     ///
     /// # Arguments
     /// * `peer_addr` - network protocol and address of communication partner
-    pub fn new(remote_addr: PeerAddr) -> NetworkData {
-        let send_buffer = SendBuffer::new(PROTOCOL_VERSION as u32, 1024);
+    /// * `stream_compression` - indicates whether all messages following the connect request
+    ///   shall be sent as compressed stream, effective for TCP only
+    /// * `spool` - the optional spool for guaranteed delivery, effective for TCP only
+    /// * `reconnection` - the reconnect settings and optional spill file
+    pub(crate) fn new(remote_addr: PeerAddr,
+                      stream_compression: bool,
+                      spool: Option<Spool>,
+                      reconnection: Reconnection) -> NetworkData {
+        #[cfg(feature="compression")]
+        let compressor = if stream_compression && remote_addr.is_tcp_stream() {
+            Some(StreamCompressor::new())
+        } else { None };
+        #[cfg(feature="compression")]
+        let protocol_info = if compressor.is_some() {
+            protocol_info(SCHEMA_VERSION) | PROTOCOL_FLAG_STREAM_COMPRESSION
+        } else { protocol_info(SCHEMA_VERSION) };
+        #[cfg(not(feature="compression"))]
+        let protocol_info = protocol_info(SCHEMA_VERSION);
+        // stream compression requires feature compression
+        #[cfg(not(feature="compression"))]
+        let _ = stream_compression;
+        let spool = spool.filter(|_| remote_addr.is_tcp_stream());
+        let protocol_info = if spool.is_some() { protocol_info | PROTOCOL_FLAG_ACKNOWLEDGE }
+                            else { protocol_info };
+        let send_buffer = SendBuffer::new(protocol_info, 1024);
         NetworkData {
             send_buffer,
             remote_addr,
             tcp_stream: None,
             udp_socket: None,
             #[cfg(unix)]
-            unix_stream: None
+            unix_stream: None,
+            pending: None,
+            #[cfg(feature="tls")]
+            tls_connector: None,
+            #[cfg(feature="compression")]
+            compressor,
+            spool,
+            rx_data: Vec::new(),
+            local_addr: None,
+            orig_info: None,
+            reconnection: Box::new(reconnection)
         }
     }
 
+    /// Secures the connection to the trace server by TLS.
+    /// Must be called before the resource is connected.
+    ///
+    /// # Arguments
+    /// * `connector` - the TLS connector with the client configuration
+    #[cfg(feature="tls")]
+    pub(crate) fn use_tls(&mut self, connector: TlsConnector) {
+        self.tls_connector = Some(Box::new(connector));
+    }
+
+    /// Returns the address of the communication partner.
+    #[inline]
+    pub(crate) fn peer_addr(&self) -> &PeerAddr { &self.remote_addr }
+
     /// Creates suitable communication socket and connects to a trace server.
+    /// For TCP and UDP, host name resolution and connect are done in the background, records
+    /// written in the meantime are sent as soon as the connection has been established.
     ///
     /// # Arguments
     /// * `local_addr` - the optional socket address for the local network socket
@@ -84,103 +162,175 @@ impl NetworkData {
     pub fn connect(&mut self,
                    local_addr: Option<PeerAddr>,
                    orig_info: &OriginatorInfo) -> Result<(), CoalyException> {
-        match &self.remote_addr {
-            PeerAddr::IpSocket(prot, ip_addr) => {
-                if *prot == NetworkProtocol::Tcp {
-                    if self.tcp_stream.is_some() {
-                        return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
-                    }
-                    self.tcp_stream = Some(NetworkData::connect_tcp(&ip_addr, orig_info,
-                                                                    &mut self.send_buffer)?);
-                } else {
-                    if self.udp_socket.is_some() {
-                        return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
-                    }
-                    self.udp_socket = Some(NetworkData::connect_udp(&ip_addr, local_addr,
-                                                                    orig_info,
-                                                                    &mut self.send_buffer)?);
-                }
-            },
-            #[cfg(unix)]
-            PeerAddr::UnixSocket(path) => {
-                if self.unix_stream.is_some() {
-                    return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
+        #[cfg(unix)]
+        let unix_connected = self.unix_stream.is_some();
+        #[cfg(not(unix))]
+        let unix_connected = false;
+        if unix_connected || self.tcp_stream.is_some() || self.udp_socket.is_some() ||
+           self.pending.is_some() {
+            return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
+        }
+        self.local_addr = local_addr;
+        self.orig_info = Some(Box::new(orig_info.clone()));
+        self.start_connection()
+    }
+
+    /// Starts a connection attempt to the trace server.
+    /// Unix sockets are connected immediately, TCP and UDP sockets in the background.
+    ///
+    /// # Errors
+    /// Returns an error structure if the Unix socket could not be connected
+    fn start_connection(&mut self) -> Result<(), CoalyException> {
+        self.reconnection.attempt_started();
+        let orig_info = match self.orig_info.as_ref() {
+            Some(oi) => oi,
+            None => return Ok(())
+        };
+        #[cfg(unix)]
+        if let PeerAddr::UnixSocket(path) = &self.remote_addr {
+            self.unix_stream = Some(NetworkData::connect_unix(path, orig_info,
+                                                              &mut self.send_buffer)?);
+            return Ok(())
+        }
+        // send connect request to server
+        self.send_buffer.store_client_notification(orig_info);
+        let hello = self.send_buffer.as_slice().to_vec();
+        #[cfg(feature="tls")]
+        if let Some(c) = self.tls_connector.as_ref() {
+            self.pending = Some(DeferredConnection::start_tls(&self.remote_addr,
+                                                              c.as_ref().clone(), Some(hello)));
+            return Ok(())
+        }
+        let laddr = self.local_addr.as_ref().and_then(|a| a.ip_addr()).copied();
+        self.pending = Some(DeferredConnection::start(&self.remote_addr, laddr, Some(hello)));
+        Ok(())
+    }
+
+    /// Determines the state of the connection to the trace server.
+    /// Starts a reconnect attempt, if the connection was lost and the attempt is due.
+    ///
+    /// # Errors
+    /// Returns an error structure if the connection was lost, subsequent failures of reconnect
+    /// attempts are not reported
+    fn connection_state(&mut self) -> Result<ConnectionState, Vec<CoalyException>> {
+        if self.reconnection.attempt_due(Instant::now()) {
+            if let Err(e) = self.start_connection() {
+                self.connection_lost(vec!(e))?;
+                return Ok(ConnectionState::Disconnected)
+            }
+            if self.pending.is_none() {
+                // Unix socket connected immediately
+                if let Err(e) = self.connection_established(Vec::new()) {
+                    self.connection_lost(e)?;
+                    return Ok(ConnectionState::Disconnected)
                 }
-                self.unix_stream = Some(NetworkData::connect_unix(&path, orig_info,
-                                                                  &mut self.send_buffer)?);
             }
         }
-        Ok(())
+        if self.reconnection.is_disconnected() { return Ok(ConnectionState::Disconnected) }
+        match self.complete_connection() {
+            Ok(true) => Ok(ConnectionState::Connected),
+            Ok(false) => Ok(ConnectionState::Connecting),
+            Err(e) => {
+                self.connection_lost(e)?;
+                Ok(ConnectionState::Disconnected)
+            }
+        }
     }
 
-    /// Connects the client's network resource to a trace server using TCP.
+    /// Checks whether a pending connection attempt has finished.
     ///
-    /// # Arguments
-    /// * `remote_addr` - the socket address of remote Coaly server
-    /// * `orig_info` - information about process and local host
-    /// * `send_buffer` - buffer to use for sending messages to the server
-    fn connect_tcp(remote_addr: &SocketAddr,
-                   orig_info: &OriginatorInfo,
-                   send_buffer: &mut SendBuffer) -> Result<TcpStream, CoalyException> {
-        match TcpStream::connect(remote_addr) {
-            Ok(mut s) => {
-                // send connect request to server
-                send_buffer.store_client_notification(orig_info);
-                if let Err(e) = s.write(send_buffer.as_slice()) {
-                    let local_addr = match s.local_addr() {
-                        Ok(a) => a.to_string(),
-                        _ => String::from("?")
-                    };
-                    let _ = s.shutdown(Shutdown::Both);
-                    return Err(coalyxe!(E_SOCKET_WRITE_ERR, local_addr,
-                                      remote_addr.to_string(), e.to_string()))
+    /// # Return values
+    /// **false** if the connection attempt is still in progress
+    ///
+    /// # Errors
+    /// Returns an error structure if the connection attempt failed
+    fn complete_connection(&mut self) -> Result<bool, Vec<CoalyException>> {
+        let res = match self.pending.as_mut() {
+            Some(p) => p.poll(),
+            None => return Ok(true)
+        };
+        match res {
+            None => Ok(false),
+            Some(Ok((conn, backlog))) => {
+                self.pending = None;
+                match conn {
+                    Connection::Tcp(s) => self.tcp_stream = Some(s),
+                    Connection::Udp(s) => self.udp_socket = Some(s)
                 }
-                Ok(s)
+                self.connection_established(backlog)?;
+                Ok(true)
             },
-            Err(m) =>  Err(coalyxe!(E_SOCKET_CRE_ERR, remote_addr.to_string(), m.to_string()))
+            Some(Err(e)) => {
+                self.pending = None;
+                Err(vec!(e))
+            }
         }
     }
 
-    /// Connects the client's network resource to a trace server using UDP.
+    /// Sends all records held back after the connection has been established.
+    /// These are the records written while the connection was lost, followed by those written
+    /// while the connection was being established. With spooling, these are all records in the
+    /// spool not yet acknowledged by the server, including those from previous runs of the
+    /// application.
     ///
     /// # Arguments
-    /// * `remote_addr` - the socket address of remote Coaly server
-    /// * `local_addr` - the optional socket address for the local network socket
-    /// * `orig_info` - information about process and local host
-    /// * `send_buffer` - buffer to use for sending messages to the server
-    fn connect_udp(remote_addr: &SocketAddr,
-                   local_addr: Option<PeerAddr>,
-                   orig_info: &OriginatorInfo,
-                   send_buffer: &mut SendBuffer) -> Result<UdpSocket, CoalyException> {
-        let mut laddr: Option<SocketAddr> = None;
-        if let Some(l) = local_addr {
-            if let Some(a) = l.ip_addr() { laddr = Some(*a); }
+    /// * `backlog` - the records written while the connection was being established
+    ///
+    /// # Errors
+    /// Returns an error structure if a record could not be sent
+    fn connection_established(&mut self,
+                              backlog: Vec<Vec<u8>>) -> Result<(), Vec<CoalyException>> {
+        self.reconnection.connected();
+        let held_msgs = self.reconnection.held_messages().map_err(|e| vec!(e))?;
+        for (i, (msg, end_offset)) in held_msgs.iter().enumerate() {
+            if let Err(e) = self.transmit(msg) {
+                self.reconnection.replay_aborted(&held_msgs[i..]);
+                for data in &backlog { let _ = self.reconnection.hold(data); }
+                return Err(e)
+            }
+            self.reconnection.message_replayed(*end_offset, i as u64 + 1).map_err(|e| vec!(e))?;
+        }
+        for (i, data) in backlog.iter().enumerate() {
+            if let Err(e) = self.transmit(data) {
+                for d in &backlog[i..] { let _ = self.reconnection.hold(d); }
+                return Err(e)
+            }
         }
-        if laddr.is_none() {
-            laddr = if remote_addr.is_ipv4() {
-                        Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0))
-                    } else {
-                        Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0))
-                    };
+        let spooled_msgs = match self.spool.as_mut() {
+            Some(sp) => sp.unacknowledged_messages().map_err(|e| vec!(e))?,
+            None => Vec::<(Vec<u8>, u64)>::new()
         };
-        let laddr = laddr.unwrap();
-        match UdpSocket::bind(laddr) {
-            Ok(s) => {
-                match s.connect(remote_addr) {
-                    Ok(_) => {
-                        // send connect request to server
-                        send_buffer.store_client_notification(orig_info);
-                        if let Err(e) = s.send(send_buffer.as_slice()) {
-                            return Err(coalyxe!(E_SOCKET_WRITE_ERR, laddr.to_string(),
-                                              remote_addr.to_string(), e.to_string()))
-                        }
-                        Ok(s)
-                    },
-                    Err(m) => Err(coalyxe!(E_SOCKET_CRE_ERR, remote_addr.to_string(), m.to_string()))
-                }
-            },
-            Err(m) => Err(coalyxe!(E_SOCKET_CRE_ERR, laddr.to_string(), m.to_string()))
+        for (msg, end_offset) in spooled_msgs {
+            self.transmit(&msg)?;
+            if let Some(sp) = self.spool.as_mut() { sp.message_sent(end_offset); }
         }
+        Ok(())
+    }
+
+    /// Closes the sockets after the connection to the trace server was lost and schedules the
+    /// next reconnect attempt.
+    ///
+    /// # Arguments
+    /// * `problems` - the errors causing the connection loss
+    ///
+    /// # Errors
+    /// Returns the errors causing the connection loss together with a warning about the
+    /// reconnect attempts, if the connection was established before
+    fn connection_lost(&mut self,
+                       mut problems: Vec<CoalyException>) -> Result<(), Vec<CoalyException>> {
+        self.pending = None;
+        if let Some(mut s) = self.tcp_stream.take() { s.shutdown(); }
+        self.udp_socket = None;
+        #[cfg(unix)]
+        { self.unix_stream = None; }
+        self.rx_data.clear();
+        // a new connection starts a new compressed stream
+        #[cfg(feature="compression")]
+        if self.compressor.is_some() { self.compressor = Some(StreamCompressor::new()); }
+        if ! self.reconnection.connection_lost(Instant::now()) { return Ok(()) }
+        problems.push(coalyxw!(W_RES_CONNECTION_LOST, self.remote_addr.to_string(),
+                               self.reconnection.delay().as_secs().to_string()));
+        Err(problems)
     }
 
     /// Connects the client's network resource to a trace server using Unix socket.
@@ -195,7 +345,7 @@ impl NetworkData {
             Ok(mut s) => {
                 // send connect request to server
                 send_buffer.store_client_notification(orig_info);
-                if let Err(e) = s.write(send_buffer.as_slice()) {
+                if let Err(e) = s.write_all(send_buffer.as_slice()) {
                     let _ = s.shutdown(Shutdown::Both);
                     return Err(coalyxe!(E_SOCKET_WRITE_ERR, String::from("Unix socket"),
                                       remote_addr.to_string(), e.to_string()))
@@ -215,47 +365,131 @@ impl NetworkData {
     /// Returns an error structure if the send operation fails
     pub fn send_record(&mut self, rec: &dyn RecordData) -> Result<(), Vec<CoalyException>> {
         self.send_buffer.store_record_notification(rec);
-        if let Some(s) = self.tcp_stream.as_mut() {
-            if let Err(e) = s.write(self.send_buffer.as_slice()) {
-                let local_addr = match s.local_addr() {
-                    Ok(a) => a.to_string(),
-                    _ => String::from("?")
-                };
-                return Err(vec!(coalyxe!(E_SOCKET_WRITE_ERR, local_addr.to_string(),
-                                       self.remote_addr.to_string(), e.to_string())))
+        let msg = self.send_buffer.as_slice().to_vec();
+        self.deliver(&msg)
+    }
+
+    /// Writes the given slice to the network socket.
+    ///
+    /// # Arguments
+    /// * `data` - the data to write
+    /// 
+    /// # Errors
+    /// Returns an error structure if the write operation fails
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Vec<CoalyException>> {
+        self.deliver(data)
+    }
+
+    /// Sends a message to the server.
+    /// With spooling, the message is appended to the spool before. Otherwise, messages written
+    /// while the connection is being established are deferred, those written while the
+    /// connection is lost are held back until the connection has been restored.
+    ///
+    /// # Arguments
+    /// * `msg` - the serialized message
+    /// 
+    /// # Errors
+    /// Returns an error structure if the spool could not be written or the connection was lost
+    fn deliver(&mut self, msg: &[u8]) -> Result<(), Vec<CoalyException>> {
+        let end_offset = match self.spool.as_mut() {
+            Some(sp) => Some(sp.append(msg).map_err(|e| vec!(e))?),
+            None => None
+        };
+        let state = match self.connection_state() {
+            Ok(s) => s,
+            Err(e) => {
+                if end_offset.is_none() { let _ = self.reconnection.hold(msg); }
+                return Err(e)
             }
-        }
-        if let Some(s) = self.udp_socket.as_mut() {
-            if let Err(e) = s.send(self.send_buffer.as_slice()) {
-                let local_addr = match s.local_addr() {
-                    Ok(a) => a.to_string(),
-                    _ => String::from("?")
-                };
-                return Err(vec!(coalyxe!(E_SOCKET_WRITE_ERR, local_addr.to_string(),
-                                       self.remote_addr.to_string(), e.to_string())))
+        };
+        match state {
+            ConnectionState::Connected => (),
+            // with spooling, the message is sent from the spool as soon as connected
+            _ if end_offset.is_some() => return Ok(()),
+            ConnectionState::Connecting => {
+                if let Some(p) = self.pending.as_mut() { p.defer(msg); }
+                return Ok(())
+            },
+            ConnectionState::Disconnected => {
+                return self.reconnection.hold(msg).map_err(|e| vec!(e))
             }
         }
-        #[cfg(unix)]
-        if let Some(s) = self.unix_stream.as_mut() {
-            if let Err(e) = s.write(self.send_buffer.as_slice()) {
-                return Err(vec!(coalyxe!(E_SOCKET_WRITE_ERR, String::from(""),
-                                       self.remote_addr.to_string(), e.to_string())))
+        if let Err(e) = self.transmit(msg) {
+            if end_offset.is_none() { let _ = self.reconnection.hold(msg); }
+            return self.connection_lost(e)
+        }
+        if let (Some(offs), Some(sp)) = (end_offset, self.spool.as_mut()) {
+            sp.message_sent(offs);
+            return self.receive_acknowledgements(None)
+        }
+        Ok(())
+    }
+
+    /// Reads acknowledgements from the server and removes the acknowledged messages from
+    /// the spool.
+    ///
+    /// # Arguments
+    /// * `timeout` - the maximum time to wait for outstanding acknowledgements, **None** to
+    ///   process only the acknowledgements already received
+    /// 
+    /// # Errors
+    /// Returns an error structure if the spool could not be updated
+    fn receive_acknowledgements(&mut self,
+                                timeout: Option<Duration>) -> Result<(), Vec<CoalyException>> {
+        let (s, sp) = match (self.tcp_stream.as_mut(), self.spool.as_mut()) {
+            (Some(s), Some(sp)) => (s, sp),
+            _ => return Ok(())
+        };
+        let _ = match timeout {
+            Some(t) => s.socket().set_read_timeout(Some(t)),
+            None => s.socket().set_nonblocking(true)
+        };
+        let mut buf = [0u8; 256];
+        while sp.awaits_acknowledgement() {
+            match s.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => self.rx_data.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break
+            }
+            // process all complete messages received
+            let mut pos = 0usize;
+            while self.rx_data.len() - pos >= 16 {
+                let payload_size = u32::deserialize_from(&self.rx_data[pos + 12..])
+                                   .unwrap_or(0) as usize;
+                let msg_end = pos + 16 + payload_size;
+                if msg_end > self.rx_data.len() { break }
+                if let Ok(Message::RecordAcknowledgement(count)) =
+                        Message::deserialize_from(&self.rx_data[pos + 16..msg_end]) {
+                    sp.acknowledge(count).map_err(|e| vec!(e))?;
+                }
+                pos = msg_end;
             }
+            self.rx_data.drain(..pos);
         }
+        let _ = match timeout {
+            Some(_) => s.socket().set_read_timeout(None),
+            None => s.socket().set_nonblocking(false)
+        };
         Ok(())
     }
 
-    /// Writes the given slice to the network socket.
+    /// Transmits the given data over the network socket.
     ///
     /// # Arguments
-    /// * `data` - the data to write
+    /// * `data` - the data to transmit
     /// 
     /// # Errors
     /// Returns an error structure if the write operation fails
-    pub fn write(&mut self, data: &[u8]) -> Result<(), Vec<CoalyException>> {
+    fn transmit(&mut self, data: &[u8]) -> Result<(), Vec<CoalyException>> {
         if let Some(s) = self.tcp_stream.as_mut() {
-            if let Err(m) = s.write(data) {
-                let local_addr = match s.local_addr() {
+            #[cfg(feature="compression")]
+            let data = match self.compressor.as_mut() {
+                Some(c) => c.compress(data),
+                None => data
+            };
+            if let Err(m) = s.write_all(data) {
+                let local_addr = match s.socket().local_addr() {
                     Ok(a) => a.to_string(),
                     _ => String::from("?")
                 };
@@ -264,7 +498,7 @@ impl NetworkData {
             }
         }
         if let Some(s) = self.udp_socket.as_ref() {
-            if let Err(m) = s.send(data) {
+            if let Err(m) = send_datagrams(s, data) {
                 let local_addr = match s.local_addr() {
                     Ok(a) => a.to_string(),
                     _ => String::from("?")
@@ -275,7 +509,7 @@ impl NetworkData {
         }
         #[cfg(unix)]
         if let Some(s) = self.unix_stream.as_mut() {
-            if let Err(e) = s.write(data) {
+            if let Err(e) = s.write_all(data) {
                 return Err(vec!(coalyxe!(E_SOCKET_WRITE_ERR, String::from(""),
                                        self.remote_addr.to_string(), e.to_string())))
             }
@@ -284,23 +518,69 @@ impl NetworkData {
     }    
 
     /// Disconnects the network interface from the server.
-    pub fn disconnect(&mut self) {
+    /// 
+    /// # Arguments
+    /// * `deadline` - the point in time when the disconnect must be completed,
+    ///   limits the time to wait for outstanding acknowledgements
+    pub fn disconnect(&mut self, deadline: Option<Instant>) {
+        // a connection attempt still in progress is abandoned, no further reconnect attempts
+        self.pending = None;
+        self.reconnection.attempt_started();
+        // give the server the chance to acknowledge the records sent, those not acknowledged
+        // remain in the spool and are sent again after the next connect
+        let ack_timeout = match deadline {
+            Some(d) => min(ACKNOWLEDGE_TIMEOUT, d.saturating_duration_since(Instant::now())),
+            None => ACKNOWLEDGE_TIMEOUT
+        };
+        let _ = self.receive_acknowledgements(if ack_timeout.is_zero() { None }
+                                              else { Some(ack_timeout) });
         self.send_buffer.store_disconnect_notification();
         if let Some(s) = self.tcp_stream.as_mut() {
-            let _ = s.write(self.send_buffer.as_slice());
-            let _ = s.shutdown(Shutdown::Both);
+            #[cfg(feature="compression")]
+            let data = match self.compressor.as_mut() {
+                Some(c) => c.compress(self.send_buffer.as_slice()),
+                None => self.send_buffer.as_slice()
+            };
+            #[cfg(not(feature="compression"))]
+            let data = self.send_buffer.as_slice();
+            let _ = s.write_all(data);
+            s.shutdown();
             self.tcp_stream = None;
         }
         if let Some(s) = self.udp_socket.as_mut() {
             let _ = s.send(self.send_buffer.as_slice());
             self.udp_socket = None;
         }
+        #[cfg(unix)]
         if let Some(s) = self.unix_stream.as_mut() {
-            let _ = s.write(self.send_buffer.as_slice());
+            let _ = s.write_all(self.send_buffer.as_slice());
+            let _ = s.shutdown(Shutdown::Both);
             self.unix_stream = None;
         }
     }
 
+    /// Disconnects from the trace server and connects anew, e.g. after the process changed its
+    /// user identity. The spool and spill files are opened anew as well.
+    /// Has no effect, if the resource hasn't been connected before.
+    ///
+    /// # Errors
+    /// Returns an error structure if the spool file can't be opened or the connection can't be
+    /// established
+    pub(crate) fn reconnect(&mut self) -> Result<(), CoalyException> {
+        let orig_info = match self.orig_info.clone() {
+            Some(oi) => oi,
+            None => return Ok(())
+        };
+        self.disconnect(None);
+        if let Some(sp) = self.spool.as_mut() { sp.reopen()?; }
+        self.reconnection.reopen()?;
+        // a new connection starts a new compressed stream
+        #[cfg(feature="compression")]
+        if self.compressor.is_some() { self.compressor = Some(StreamCompressor::new()); }
+        let local_addr = self.local_addr.clone();
+        self.connect(local_addr, &orig_info)
+    }
+
 //    /// Closes the network interface.
 //    pub fn close(&mut self) {
 //        self.tcp_stream = None;
@@ -308,3 +588,30 @@ impl NetworkData {
 //        self.unix_stream = None;
 //    }
 }
+
+/// Sends a serialized message over a UDP socket.
+/// Messages too large for a single datagram are split into fragments.
+///
+/// # Arguments
+/// * `socket` - the connected UDP socket
+/// * `msg` - the serialized message
+///
+/// # Errors
+/// Returns an I/O error if a datagram could not be sent
+fn send_datagrams(socket: &UdpSocket, msg: &[u8]) -> std::io::Result<()> {
+    for dg in fragments(msg, MAX_DATAGRAM_SIZE) { socket.send(&dg)?; }
+    Ok(())
+}
+
+/// State of the connection to the trace server
+enum ConnectionState {
+    // connection established
+    Connected,
+    // connection attempt in progress
+    Connecting,
+    // connection lost, waiting for the next reconnect attempt
+    Disconnected
+}
+
+// Maximum time to wait for outstanding acknowledgements upon disconnect
+const ACKNOWLEDGE_TIMEOUT: Duration = Duration::from_secs(2);
@@ -0,0 +1,170 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Output resources of type desktop notification.
+//! On Linux and BSD systems notifications are raised through the freedesktop notification
+//! service on the D-Bus session bus, using the command line tool notify-send. On macOS the
+//! notification center is used via osascript. Other platforms don't support notifications,
+//! records are discarded there.
+//! Notifications are rate limited, records arriving within the configured minimum interval
+//! after a notification are discarded.
+
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Specific data for physical resources of kind desktop notification.
+pub(crate) struct NotificationData {
+    // application name shown in the notification
+    app_name: String,
+    // minimum interval between two notifications
+    min_interval: Duration,
+    // time of last notification raised
+    last_notification: Option<Instant>
+}
+impl NotificationData {
+    /// Creates a desktop notification resource.
+    /// 
+    /// # Arguments
+    /// * `app_name` - the application name shown in the notification
+    /// * `min_interval` - the minimum interval between two notifications, in seconds
+    pub(crate) fn new(app_name: &str,
+                      min_interval: u64) -> NotificationData {
+        NotificationData {
+            app_name: app_name.to_string(),
+            min_interval: Duration::from_secs(min_interval),
+            last_notification: None
+        }
+    }
+
+    /// Raises a desktop notification with the given formatted record(s) as message text.
+    /// The data is discarded, if the last notification was raised within the minimum interval.
+    /// Failures of the notification service are ignored, since there's no reasonable way
+    /// to report them.
+    /// 
+    /// # Arguments
+    /// * `data` - the formatted record(s)
+    pub(crate) fn write(&mut self, data: &[u8]) {
+        if ! self.admit(Instant::now()) { return }
+        let text = String::from_utf8_lossy(data);
+        if let Some(mut cmd) = notification_command(&self.app_name, text.trim_end()) {
+            if let Ok(mut child) = cmd.stdin(Stdio::null())
+                                      .stdout(Stdio::null())
+                                      .stderr(Stdio::null())
+                                      .spawn() {
+                // reap the child process without blocking the worker thread
+                thread::spawn(move || { let _ = child.wait(); });
+            }
+        }
+    }
+
+    /// Checks whether a notification may be raised at the given time, and if so, registers
+    /// the time as time of the last notification.
+    /// 
+    /// # Arguments
+    /// * `now` - the current time
+    /// 
+    /// # Return values
+    /// **true** if the notification may be raised
+    fn admit(&mut self, now: Instant) -> bool {
+        if let Some(t) = self.last_notification {
+            if now.saturating_duration_since(t) < self.min_interval { return false }
+        }
+        self.last_notification = Some(now);
+        true
+    }
+}
+
+/// Returns the command to raise a desktop notification.
+/// 
+/// # Arguments
+/// * `app_name` - the application name, used as notification title
+/// * `text` - the notification text
+#[cfg(all(unix, not(target_os="macos")))]
+fn notification_command(app_name: &str, text: &str) -> Option<Command> {
+    let mut cmd = Command::new("notify-send");
+    cmd.args(["-u", "critical", "-a", app_name, app_name, text]);
+    Some(cmd)
+}
+
+/// Returns the command to raise a desktop notification.
+/// 
+/// # Arguments
+/// * `app_name` - the application name, used as notification title
+/// * `text` - the notification text
+#[cfg(target_os="macos")]
+fn notification_command(app_name: &str, text: &str) -> Option<Command> {
+    let mut cmd = Command::new("osascript");
+    cmd.args(["-e", "on run argv",
+              "-e", "display notification (item 2 of argv) with title (item 1 of argv)",
+              "-e", "end run", app_name, text]);
+    Some(cmd)
+}
+
+/// Returns the command to raise a desktop notification.
+/// Notifications are not supported on this platform.
+/// 
+/// # Arguments
+/// * `app_name` - the application name, used as notification title
+/// * `text` - the notification text
+#[cfg(not(unix))]
+fn notification_command(_app_name: &str, _text: &str) -> Option<Command> { None }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Tests rate limiting of notifications
+    fn test_admit() {
+        let mut n = NotificationData::new("test", 10);
+        let t0 = Instant::now();
+        assert!(n.admit(t0));
+        assert!(! n.admit(t0 + Duration::from_secs(5)));
+        assert!(! n.admit(t0 + Duration::from_secs(9)));
+        assert!(n.admit(t0 + Duration::from_secs(10)));
+        assert!(! n.admit(t0 + Duration::from_secs(15)));
+        let mut n = NotificationData::new("test", 0);
+        assert!(n.admit(t0));
+        assert!(n.admit(t0));
+    }
+
+    #[cfg(all(unix, not(target_os="macos")))]
+    #[test]
+    /// Tests the command line for notify-send
+    fn test_notification_command() {
+        let cmd = notification_command("myapp", "disk full").unwrap();
+        assert_eq!("notify-send", cmd.get_program());
+        let args: Vec<&std::ffi::OsStr> = cmd.get_args().collect();
+        assert_eq!(vec!("-u", "critical", "-a", "myapp", "myapp", "disk full"), args);
+    }
+}
@@ -0,0 +1,199 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Output resources of type named pipe (POSIX FIFO or Windows named pipe).
+//! The pipe is opened in non-blocking mode, records are discarded as long as no consumer
+//! process has opened the pipe for reading or the pipe is full. If the pipe can't be opened
+//! or the consumer closes its end, another attempt to open the pipe is made after a short
+//! interval.
+
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::coalyxe;
+use crate::errorhandling::*;
+
+/// Specific data for physical resources of kind named pipe.
+pub(crate) struct PipeData {
+    // full path of the pipe
+    path: PathBuf,
+    // pipe handle, if the pipe is currently open
+    pipe: Option<File>,
+    // time of last attempt to open the pipe
+    last_open_attempt: Option<Instant>
+}
+impl PipeData {
+    /// Creates a named pipe resource.
+    /// The pipe is not opened before the first record is written.
+    /// 
+    /// # Arguments
+    /// * `output_dir` - the output directory, used for relative POSIX FIFO names
+    /// * `name` - the pipe name
+    pub(crate) fn new(output_dir: &Path,
+                      name: &str) -> PipeData {
+        PipeData { path: pipe_path(output_dir, name), pipe: None, last_open_attempt: None }
+    }
+
+    /// Writes the given data to the pipe.
+    /// The data is discarded, if the pipe can't be opened or is full.
+    /// 
+    /// # Arguments
+    /// * `data` - the data to write
+    /// 
+    /// # Errors
+    /// Returns an error structure if the write operation fails for another reason than a
+    /// missing consumer or a full pipe
+    pub(crate) fn write(&mut self, data: &[u8]) -> Result<(), CoalyException> {
+        if self.pipe.is_none() && ! self.open() { return Ok(()) }
+        if let Err(e) = self.pipe.as_mut().unwrap().write_all(data) {
+            match e.kind() {
+                // pipe full, record is discarded
+                ErrorKind::WouldBlock => (),
+                // consumer closed the pipe, open anew later
+                ErrorKind::BrokenPipe => self.close(),
+                _ => {
+                    self.close();
+                    let pipe_name = self.path.to_string_lossy().to_string();
+                    return Err(coalyxe!(E_FILE_WRITE_ERR, pipe_name, e.to_string()))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes the pipe.
+    pub(crate) fn close(&mut self) { self.pipe = None; }
+
+    /// Opens the pipe for writing, unless the last attempt was made only recently.
+    /// 
+    /// # Return values
+    /// **true** if the pipe is open
+    fn open(&mut self) -> bool {
+        if let Some(t) = self.last_open_attempt {
+            if t.elapsed() < PIPE_REOPEN_INTERVAL { return false }
+        }
+        self.last_open_attempt = Some(Instant::now());
+        self.pipe = open_pipe(&self.path).ok();
+        self.pipe.is_some()
+    }
+}
+
+/// Returns the full path of a named pipe.
+/// On Windows, names not starting with a backslash are placed in the local pipe namespace.
+/// 
+/// # Arguments
+/// * `output_dir` - the output directory, used for relative POSIX FIFO names
+/// * `name` - the pipe name
+#[cfg(windows)]
+fn pipe_path(_output_dir: &Path, name: &str) -> PathBuf {
+    if name.starts_with('\\') { return PathBuf::from(name) }
+    PathBuf::from(format!("{}{}", WINDOWS_PIPE_PREFIX, name))
+}
+
+/// Returns the full path of a named pipe.
+/// Relative names are resolved against the output directory.
+/// 
+/// # Arguments
+/// * `output_dir` - the output directory, used for relative POSIX FIFO names
+/// * `name` - the pipe name
+#[cfg(not(windows))]
+fn pipe_path(output_dir: &Path, name: &str) -> PathBuf { output_dir.join(name) }
+
+/// Opens a POSIX FIFO for writing in non-blocking mode.
+/// The call fails, if no consumer has opened the FIFO for reading.
+/// 
+/// # Arguments
+/// * `path` - the full path of the FIFO
+#[cfg(not(windows))]
+fn open_pipe(path: &Path) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new().write(true).custom_flags(libc::O_NONBLOCK).open(path)
+}
+
+/// Opens a Windows named pipe for writing.
+/// The call fails, if no server process has created the pipe.
+/// 
+/// # Arguments
+/// * `path` - the full path of the pipe
+#[cfg(windows)]
+fn open_pipe(path: &Path) -> std::io::Result<File> {
+    std::fs::OpenOptions::new().write(true).open(path)
+}
+
+// minimum interval between two attempts to open a pipe
+const PIPE_REOPEN_INTERVAL: Duration = Duration::from_secs(1);
+
+// namespace for local Windows named pipes
+#[cfg(windows)]
+const WINDOWS_PIPE_PREFIX: &str = r"\\.\pipe\";
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::io::Read;
+    use std::os::unix::fs::OpenOptionsExt;
+    use crate::output::resource::tests::test_dir_path;
+
+    #[test]
+    /// Tests writing to a FIFO with and without consumer
+    fn test_fifo() {
+        let dir = test_dir_path(&["output", "resource", "pipe", "test_fifo"]);
+        let _ = std::fs::create_dir_all(&dir);
+        let fifo_path = dir.join("coaly.fifo");
+        let _ = std::fs::remove_file(&fifo_path);
+        let c_path = CString::new(fifo_path.to_string_lossy().as_bytes()).unwrap();
+        assert_eq!(0, unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) });
+        let mut pipe = PipeData::new(&dir, "coaly.fifo");
+        // without consumer records are discarded
+        assert!(pipe.write(b"lost\n").is_ok());
+        assert!(pipe.pipe.is_none());
+        // with consumer records are delivered, after reopen interval has elapsed
+        let mut consumer = std::fs::OpenOptions::new().read(true)
+                                                      .custom_flags(libc::O_NONBLOCK)
+                                                      .open(&fifo_path).unwrap();
+        assert!(pipe.write(b"early\n").is_ok());
+        assert!(pipe.pipe.is_none());
+        pipe.last_open_attempt = None;
+        assert!(pipe.write(b"delivered\n").is_ok());
+        assert!(pipe.pipe.is_some());
+        let mut buf = [0u8; 64];
+        let n = consumer.read(&mut buf).unwrap();
+        assert_eq!(b"delivered\n", &buf[..n]);
+        // consumer gone, pipe is closed
+        drop(consumer);
+        assert!(pipe.write(b"lost\n").is_ok());
+        assert!(pipe.pipe.is_none());
+        let _ = std::fs::remove_file(&fifo_path);
+    }
+}
@@ -0,0 +1,287 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+
+//! Reconnection to the logging server after the connection of a network resource was lost.
+//! Reconnect attempts are made with exponential backoff, records written while the resource is
+//! disconnected are held back, either in memory or in an optional spill file, and sent as soon
+//! as the connection has been restored.
+
+use std::time::{Duration, Instant};
+use crate::errorhandling::*;
+use super::spool::Spool;
+
+/// Reconnection state of a network resource.
+pub(crate) struct Reconnection {
+    // delay before the first reconnect attempt
+    min_delay: Duration,
+    // maximum delay between two reconnect attempts
+    max_delay: Duration,
+    // number of failed connection attempts since the connection was lost
+    failures: u32,
+    // point in time for the next reconnect attempt, **None** if connected or connecting
+    next_attempt: Option<Instant>,
+    // optional spill file for the records held back
+    spill: Option<Spool>,
+    // records held back in memory, if there is no spill file
+    held_msgs: Vec<Vec<u8>>,
+    // total size of all records held back in memory
+    held_size: usize
+}
+impl Reconnection {
+    /// Creates the reconnection state for a network resource.
+    ///
+    /// # Arguments
+    /// * `min_delay` - the delay before the first reconnect attempt
+    /// * `max_delay` - the maximum delay between two reconnect attempts
+    /// * `spill` - the optional spill file for records written while disconnected
+    pub(crate) fn new(min_delay: Duration,
+                      max_delay: Duration,
+                      spill: Option<Spool>) -> Reconnection {
+        Reconnection {
+            min_delay,
+            max_delay: Duration::max(min_delay, max_delay),
+            failures: 0,
+            next_attempt: None,
+            spill,
+            held_msgs: Vec::new(),
+            held_size: 0
+        }
+    }
+
+    /// Indicates whether the resource is disconnected and waits for the next reconnect attempt.
+    #[inline]
+    pub(crate) fn is_disconnected(&self) -> bool { self.next_attempt.is_some() }
+
+    /// Indicates whether a reconnect attempt is due.
+    ///
+    /// # Arguments
+    /// * `now` - the current timestamp
+    pub(crate) fn attempt_due(&self, now: Instant) -> bool {
+        matches!(self.next_attempt, Some(t) if now >= t)
+    }
+
+    /// Notifies the reconnection state, that a connection attempt has been started.
+    #[inline]
+    pub(crate) fn attempt_started(&mut self) { self.next_attempt = None; }
+
+    /// Notifies the reconnection state, that the connection has been established.
+    #[inline]
+    pub(crate) fn connected(&mut self) {
+        self.failures = 0;
+        self.next_attempt = None;
+    }
+
+    /// Notifies the reconnection state, that the connection was lost or a connection attempt
+    /// failed, and schedules the next reconnect attempt.
+    /// The delay doubles with every failed attempt, until the maximum delay is reached.
+    ///
+    /// # Arguments
+    /// * `now` - the current timestamp
+    ///
+    /// # Return values
+    /// **true** if the connection was established before, i.e. the loss should be reported
+    pub(crate) fn connection_lost(&mut self, now: Instant) -> bool {
+        let first_failure = self.failures == 0;
+        self.next_attempt = Some(now + self.delay());
+        self.failures = self.failures.saturating_add(1);
+        first_failure
+    }
+
+    /// Returns the delay until the next reconnect attempt, based on the number of failed
+    /// attempts so far.
+    pub(crate) fn delay(&self) -> Duration {
+        let factor = 1u32.checked_shl(self.failures).unwrap_or(u32::MAX);
+        Duration::min(self.min_delay.saturating_mul(factor), self.max_delay)
+    }
+
+    /// Holds back a message written while the resource is disconnected.
+    /// Messages held in memory are discarded, if the memory limit has been reached.
+    ///
+    /// # Arguments
+    /// * `msg` - the serialized message
+    ///
+    /// # Errors
+    /// Returns an error structure if the message could not be written to the spill file
+    pub(crate) fn hold(&mut self, msg: &[u8]) -> Result<(), CoalyException> {
+        if let Some(sp) = self.spill.as_mut() { return sp.append(msg).map(|_| ()) }
+        if self.held_size + msg.len() > MAX_HELD_SIZE { return Ok(()) }
+        self.held_size += msg.len();
+        self.held_msgs.push(msg.to_vec());
+        Ok(())
+    }
+
+    /// Returns all messages held back, oldest first.
+    /// Messages in the spill file remain there until they are reported as replayed, messages
+    /// held in memory are handed over to the caller.
+    ///
+    /// # Return values
+    /// array with the messages held back and their end offsets in the spill file
+    ///
+    /// # Errors
+    /// Returns an error structure if the spill file could not be read
+    pub(crate) fn held_messages(&mut self) -> Result<Vec<(Vec<u8>, u64)>, CoalyException> {
+        if let Some(sp) = self.spill.as_mut() { return sp.unacknowledged_messages() }
+        self.held_size = 0;
+        Ok(std::mem::take(&mut self.held_msgs).into_iter().map(|m| (m, 0)).collect())
+    }
+
+    /// Notifies the reconnection state, that a message held back has been sent.
+    ///
+    /// # Arguments
+    /// * `end_offset` - the end offset of the message in the spill file
+    /// * `count` - the number of messages held back sent so far
+    ///
+    /// # Errors
+    /// Returns an error structure if the spill file could not be updated
+    pub(crate) fn message_replayed(&mut self,
+                                   end_offset: u64,
+                                   count: u64) -> Result<(), CoalyException> {
+        match self.spill.as_mut() {
+            Some(sp) => {
+                sp.message_sent(end_offset);
+                sp.acknowledge(count)
+            },
+            None => Ok(())
+        }
+    }
+
+    /// Notifies the reconnection state, that sending the messages held back failed.
+    /// The messages not sent are held back again.
+    ///
+    /// # Arguments
+    /// * `msgs` - the messages not sent
+    pub(crate) fn replay_aborted(&mut self, msgs: &[(Vec<u8>, u64)]) {
+        // messages in the spill file haven't been removed yet
+        if self.spill.is_some() { return }
+        for (msg, _) in msgs { let _ = self.hold(msg); }
+    }
+
+    /// Opens the spill file anew, e.g. after the process changed its user identity.
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be opened
+    pub(crate) fn reopen(&mut self) -> Result<(), CoalyException> {
+        match self.spill.as_mut() {
+            Some(sp) => sp.reopen(),
+            None => Ok(())
+        }
+    }
+}
+
+// Maximum total size of the messages held back in memory while disconnected
+const MAX_HELD_SIZE: usize = 4 * 1024 * 1024;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::serializable::Serializable;
+
+    fn message(payload: &[u8]) -> Vec<u8> {
+        let mut msg = Vec::<u8>::new();
+        1u32.serialize_to(&mut msg);
+        0u64.serialize_to(&mut msg);
+        (payload.len() as u32).serialize_to(&mut msg);
+        msg.extend_from_slice(payload);
+        msg
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut rc = Reconnection::new(Duration::from_secs(1), Duration::from_secs(5), None);
+        let now = Instant::now();
+        assert!(! rc.is_disconnected());
+        assert!(rc.connection_lost(now));
+        assert!(rc.is_disconnected());
+        assert!(! rc.attempt_due(now));
+        assert!(rc.attempt_due(now + Duration::from_secs(1)));
+        rc.attempt_started();
+        assert!(! rc.is_disconnected());
+        assert_eq!(Duration::from_secs(2), rc.delay());
+        assert!(! rc.connection_lost(now));
+        assert_eq!(Duration::from_secs(4), rc.delay());
+        assert!(! rc.connection_lost(now));
+        assert_eq!(Duration::from_secs(5), rc.delay());
+        for _ in 0..40 { rc.connection_lost(now); }
+        assert_eq!(Duration::from_secs(5), rc.delay());
+        assert!(rc.attempt_due(now + Duration::from_secs(5)));
+        rc.connected();
+        assert!(! rc.is_disconnected());
+        assert_eq!(Duration::from_secs(1), rc.delay());
+        assert!(rc.connection_lost(now));
+    }
+
+    // sends all messages held back, the send operation fails at the given index
+    fn replay(rc: &mut Reconnection, fail_index: usize) -> Vec<Vec<u8>> {
+        let mut sent = Vec::<Vec<u8>>::new();
+        let msgs = rc.held_messages().unwrap();
+        for (i, (msg, end_offset)) in msgs.iter().enumerate() {
+            if i == fail_index {
+                rc.replay_aborted(&msgs[i..]);
+                break
+            }
+            sent.push(msg.to_vec());
+            rc.message_replayed(*end_offset, i as u64 + 1).unwrap();
+        }
+        sent
+    }
+
+    #[test]
+    fn test_memory_replay() {
+        let mut rc = Reconnection::new(Duration::from_secs(1), Duration::from_secs(5), None);
+        rc.hold(&message(b"first")).unwrap();
+        rc.hold(&message(b"second")).unwrap();
+        rc.hold(&message(b"third")).unwrap();
+        // a failed send retains the message and all following
+        assert_eq!(vec!(message(b"first")), replay(&mut rc, 1));
+        assert_eq!(vec!(message(b"second"), message(b"third")), replay(&mut rc, usize::MAX));
+        assert!(replay(&mut rc, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_spill_replay() {
+        let path = std::env::temp_dir().join(format!("coaly_spill_{}.spl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut rc = Reconnection::new(Duration::from_secs(1), Duration::from_secs(5),
+                                       Some(Spool::open(&path).unwrap()));
+        rc.hold(&message(b"first")).unwrap();
+        rc.hold(&message(b"second")).unwrap();
+        drop(rc);
+        // records held back must survive a restart
+        let mut rc = Reconnection::new(Duration::from_secs(1), Duration::from_secs(5),
+                                       Some(Spool::open(&path).unwrap()));
+        assert_eq!(vec!(message(b"first")), replay(&mut rc, 1));
+        assert_eq!(vec!(message(b"second")), replay(&mut rc, usize::MAX));
+        assert!(replay(&mut rc, usize::MAX).is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -88,6 +88,18 @@
 //!        If a file with the new name exists, the current output file is renamed to the name for
 //!        the first rollover file. The current output file is eventually compressed.
 //!     5. The new output file is opened.
+//! 
+//! * Checksum files
+//!     1. If requested by the rollover policy, a checksum file is written for every archived
+//!        file, named like the archive file with extension ".sha256" appended. It holds the
+//!        SHA-256 hash of the archive file in the format of the sha256sum tool, hence it can be
+//!        verified with "sha256sum -c".
+//!     2. Additionally, the checksum file holds two comment lines with the chain hash of the
+//!        previous archive file and its own chain hash, which is the SHA-256 hash of the
+//!        hexadecimal chain hash of the previous file followed by the hexadecimal hash of the
+//!        archive file. The chain starts with a hash of all zeroes, removing or modifying any
+//!        archive file or checksum file in between breaks the chain.
+//!     3. Checksum files are renamed and removed together with their archive files.
 
 #[cfg(feature="compression")]
 use zip::write::FileOptions;
@@ -97,19 +109,23 @@ use zip::ZipWriter;
 use bzip2::write::BzEncoder;
 #[cfg(feature="compression")]
 use flate2::GzBuilder;
-use regex::{Captures, Regex};
+#[cfg(feature="checksums")]
+use sha2::{Digest, Sha256};
 #[cfg(feature="compression")]
 use xz2::write::XzEncoder;
 use std::cmp::Ordering;
-#[cfg(feature="compression")]
+#[cfg(any(feature="compression", feature="checksums"))]
 use std::fs::File;
 #[cfg(feature="compression")]
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use crate::coalyxe;
+use std::time::{Duration, SystemTime};
+use crate::{coalyxe, coalyxw};
 use crate::errorhandling::*;
 use crate::output::formatspec::FormatSpec;
+use crate::output::namepattern::{FileNameMatch, FileNamePattern};
 use crate::policies::*;
+use crate::util::shared_open_options;
 
 
 
@@ -124,30 +140,41 @@ use crate::policies::*;
 /// * `name_spec` - the resource file name specification
 /// * `keep_count` - the maximum number of archive files to keep, if the limit is exceeded, the
 ///                  oldest archive files are removed
+/// * `max_total_size` - the maximum total size of all archive files in bytes, 0 if unlimited;
+///                      if the limit is exceeded, the oldest archive files are removed
 /// * `compression` - the compression algorithm to use for the archive file
+/// * `checksums` - indicates whether to write a checksum file for the archive file
+///
+/// # Return values
+/// a warning, if the archive file was created, but the checksum file couldn't be written
 ///
 /// # Errors
 /// Returns an error descriptor if any sub-operation fails
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn archive_resource(output_dir: &PathBuf,
                                active_file_name: &str,
                                new_file_name: &str,
                                name_spec: &FormatSpec,
                                keep_count: u32,
-                               compression: &CompressionAlgorithm) -> Result<(), CoalyException> {
+                               max_total_size: usize,
+                               compression: &CompressionAlgorithm,
+                               checksums: bool)
+                               -> Result<Option<CoalyException>, CoalyException> {
+    #[cfg(feature="faultinjection")]
+    crate::output::faultinjection::check_rollover(active_file_name)?;
     // determine a list of all files belonging to the output resource, newest files first
     // if we don't find any files, we assume that nothing has been logged yet
     let active_file_path = output_dir.join(active_file_name);
     let compr_ext = compression.file_extension();
     let name_dtm_dep = ! name_spec.is_datetime_independent();
     let find_pattern = name_spec.file_name_pattern(compr_ext);
-    if let Err(e) = find_pattern {
-        return Err(coalyxe!(E_ROVR_FAILED,
-                            active_file_path.to_string_lossy().to_string(), e.to_string()))
-    }
-    let find_pattern = find_pattern.unwrap();
     let res_files = find_resource_files(output_dir, &active_file_name, name_dtm_dep,
                                         &find_pattern, compr_ext)?;
-    if res_files.is_empty() { return Ok(()) }
+    if res_files.is_empty() { return Ok(None) }
+
+    // chain hash of the newest archive file must be read before the files are shifted
+    let prev_chain_hash = if checksums { newest_chain_hash(output_dir, &res_files) }
+                          else { String::new() };
 
     // Remove oldest rollover files exceeding the keep limit and eventually rename the files kept
     let res_files = remove_rollover_files(output_dir, &res_files, keep_count)?;
@@ -158,11 +185,314 @@ pub(crate) fn archive_resource(output_dir: &PathBuf,
                        else { format!("{}{}", active_file_name, compression.file_extension()) };
     let ar_file_path = output_dir.join(&ar_file_name);
     #[cfg(feature="compression")]
-    return archive_active_file(&active_file_path, &ar_file_path, compression)
-               .map_err(|e| coalyxe!(E_ROVR_FAILED, active_file_path.to_string_lossy().to_string(),
-                                     e.to_string()));
+    archive_active_file(&active_file_path, &ar_file_path, compression)
+        .map_err(|e| coalyxe!(E_ROVR_FAILED, active_file_path.to_string_lossy().to_string(),
+                              e.to_string()))?;
     #[cfg(not(feature="compression"))]
-    { let _ = std::fs::rename(active_file_path, ar_file_path); Ok(()) }
+    move_active_file(&active_file_path, &ar_file_path)
+        .map_err(|e| coalyxe!(E_ROVR_FAILED, active_file_path.to_string_lossy().to_string(),
+                              e.to_string()))?;
+    let warning = if checksums {
+        write_checksum_file(&ar_file_path, &prev_chain_hash)
+            .err()
+            .map(|e| coalyxw!(W_ROVR_CHECKSUM_FAILED, ar_file_path.to_string_lossy().to_string(),
+                              e.to_string()))
+    } else { None };
+
+    // Remove oldest archive files exceeding the total size limit, now that the size of the
+    // newest archive file is known
+    if max_total_size > 0 {
+        let ar_files = find_resource_files(output_dir, new_file_name, name_dtm_dep,
+                                           &find_pattern, compr_ext)?;
+        let ar_files: Vec<AssociatedResFile> = ar_files.into_iter()
+                                                       .filter(|f| ! f.active_flag)
+                                                       .collect();
+        let keep_count = count_within_size(output_dir, &ar_files, max_total_size);
+        remove_rollover_files(output_dir, &ar_files, keep_count as u32)?;
+    }
+    Ok(warning)
+}
+
+/// Determines the number of newest files, whose total size doesn't exceed the given limit.
+/// The newest file is always counted, even if it exceeds the limit on its own.
+///
+/// # Arguments
+/// * `dir` - the output directory for the resource
+/// * `files` - sorted list with files belonging to the resource, newest first
+/// * `max_total_size` - the maximum total size in bytes
+///
+/// # Return values
+/// the number of files within the limit
+fn count_within_size(dir: &Path,
+                     files: &[AssociatedResFile],
+                     max_total_size: usize) -> usize {
+    let mut total_size: u64 = 0;
+    for (index, f) in files.iter().enumerate() {
+        total_size += std::fs::metadata(dir.join(f.file_name())).map_or(0, |m| m.len());
+        if index > 0 && total_size > max_total_size as u64 { return index }
+    }
+    files.len()
+}
+
+/// Writes the checksum file for an archive file.
+///
+/// # Arguments
+/// * `arch_file_path` - the path of the archive file
+/// * `prev_chain_hash` - the chain hash of the previous archive file
+///
+/// # Errors
+/// Returns an error structure if an I/O error occurs
+#[cfg(feature="checksums")]
+fn write_checksum_file(arch_file_path: &Path,
+                       prev_chain_hash: &str) -> Result<(), std::io::Error> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(arch_file_path)?, &mut hasher)?;
+    let file_hash = hex_string(&hasher.finalize());
+    let chain_hash = hex_string(&Sha256::new().chain_update(prev_chain_hash)
+                                              .chain_update(&file_hash)
+                                              .finalize());
+    let file_name = arch_file_path.file_name().unwrap_or_default().to_string_lossy();
+    let contents = format!("{}{}\n{}{}\n{}  {}\n", PREV_CHAIN_PREFIX, prev_chain_hash,
+                           CHAIN_PREFIX, chain_hash, file_hash, file_name);
+    std::fs::write(checksum_file_path(arch_file_path), contents)
+}
+
+/// Checksum files can't be written without feature checksums, the configuration parser
+/// rejects rollover policies requesting them.
+#[cfg(not(feature="checksums"))]
+fn write_checksum_file(_arch_file_path: &Path,
+                       _prev_chain_hash: &str) -> Result<(), std::io::Error> {
+    Err(std::io::Error::from(std::io::ErrorKind::Unsupported))
+}
+
+/// Returns the chain hash of the newest archive file of an output resource.
+///
+/// # Arguments
+/// * `dir` - the output directory for the resource
+/// * `files` - sorted list with all existing resource files, newest first
+///
+/// # Return values
+/// the chain hash from the checksum file of the newest archive file, a hash of all zeroes if
+/// there is no such file
+fn newest_chain_hash(dir: &Path, files: &[AssociatedResFile]) -> String {
+    files.iter()
+         .find(|f| ! f.active_flag)
+         .and_then(|f| std::fs::read_to_string(checksum_file_path(&dir.join(f.file_name()))).ok())
+         .and_then(|c| c.lines()
+                        .find_map(|l| l.strip_prefix(CHAIN_PREFIX).map(|h| h.trim().to_string())))
+         .unwrap_or_else(|| "0".repeat(64))
+}
+
+/// Renames the checksum file of an archive file, if it exists.
+/// The file name within the checksum file is adjusted to the new name of the archive file.
+///
+/// # Arguments
+/// * `dir` - the output directory for the resource
+/// * `old_file_name` - the current name of the archive file
+/// * `new_file_name` - the new name of the archive file
+///
+/// # Errors
+/// Returns an error structure if an I/O error occurs
+fn shift_checksum_file(dir: &Path,
+                       old_file_name: &str,
+                       new_file_name: &str) -> Result<(), std::io::Error> {
+    let old_path = checksum_file_path(&dir.join(old_file_name));
+    let contents = match std::fs::read_to_string(&old_path) {
+        Ok(c) => c,
+        Err(_) => return Ok(())
+    };
+    let contents: String = contents.lines().map(|l| {
+        if l.starts_with('#') { return format!("{}\n", l) }
+        format!("{}  {}\n", l.split("  ").next().unwrap_or_default(), new_file_name)
+    }).collect();
+    std::fs::write(checksum_file_path(&dir.join(new_file_name)), contents)?;
+    std::fs::remove_file(old_path)
+}
+
+/// Returns the path of the checksum file for an archive file.
+///
+/// # Arguments
+/// * `arch_file_path` - the path of the archive file
+fn checksum_file_path(arch_file_path: &Path) -> PathBuf {
+    let mut path = arch_file_path.as_os_str().to_os_string();
+    path.push(CHECKSUM_FILE_EXT);
+    PathBuf::from(path)
+}
+
+/// Returns the lower case hexadecimal representation of a byte sequence.
+///
+/// # Arguments
+/// * `data` - the bytes
+#[cfg(feature="checksums")]
+fn hex_string(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Determines the files of an output resource, that would be removed by the next rollover
+/// because the rollover policy's keep count is exceeded.
+/// Dry-run counterpart of function archive_resource, no file is touched.
+///
+/// # Arguments
+/// * `output_dir` - the output directory path
+/// * `active_file_name` - the pure name of the currently active output resource file
+/// * `name_spec` - the resource file name specification
+/// * `keep_count` - the maximum number of archive files to keep
+/// * `max_total_size` - the maximum total size of all archive files in bytes, 0 if unlimited
+/// * `compression` - the compression algorithm to use for the archive file
+///
+/// # Return values
+/// the full path names of all files exceeding the keep count or the total size limit,
+/// newest first
+///
+/// # Errors
+/// Returns an error descriptor if the output directory can't be read
+pub(crate) fn retention_candidates(output_dir: &Path,
+                                   active_file_name: &str,
+                                   name_spec: &FormatSpec,
+                                   keep_count: u32,
+                                   max_total_size: usize,
+                                   compression: &CompressionAlgorithm)
+                                   -> Result<Vec<String>, CoalyException> {
+    let compr_ext = compression.file_extension();
+    let name_dtm_dep = ! name_spec.is_datetime_independent();
+    let find_pattern = name_spec.file_name_pattern(compr_ext);
+    let res_files = find_resource_files(output_dir, active_file_name, name_dtm_dep,
+                                        &find_pattern, compr_ext)?;
+    let mut keep_count = keep_count as usize;
+    if max_total_size > 0 {
+        // the active file is counted with its uncompressed size
+        keep_count = keep_count.min(count_within_size(output_dir, &res_files, max_total_size));
+    }
+    Ok(res_files.iter()
+                .skip(keep_count)
+                .map(|f| output_dir.join(f.file_name()).to_string_lossy().to_string())
+                .collect())
+}
+
+/// Removes or compresses orphaned output files left behind by earlier runs.
+/// A file is orphaned, if its name matches one of the given patterns and it hasn't been
+/// modified for the retention period. Files already compressed and checksum files are never
+/// compressed again.
+///
+/// # Arguments
+/// * `output_dir` - the output directory path
+/// * `patterns` - the regular expressions matching the names of output files with thread or
+///                process specific names
+/// * `retention` - the minimum age of an orphaned file
+/// * `compression` - the compression algorithm to use, **None** to remove orphaned files
+///
+/// # Return values
+/// the warnings for all files, that couldn't be removed or compressed
+pub(crate) fn cleanup_stale_files(output_dir: &Path,
+                                  patterns: &[FileNamePattern],
+                                  retention: Duration,
+                                  compression: CompressionAlgorithm) -> Vec<CoalyException> {
+    let mut problems = Vec::<CoalyException>::new();
+    if patterns.is_empty() { return problems }
+    let dir_list = match std::fs::read_dir(output_dir) {
+        Ok(l) => l,
+        Err(_) => return problems
+    };
+    let now = SystemTime::now();
+    for entry in dir_list.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if ! patterns.iter().any(|p| p.is_match(&file_name)) { continue }
+        let age = entry.metadata().ok()
+                       .filter(|m| m.is_file())
+                       .and_then(|m| m.modified().ok())
+                       .and_then(|m| now.duration_since(m).ok());
+        if age.is_none_or(|a| a < retention) { continue }
+        let file_path = entry.path();
+        let result = if compression == CompressionAlgorithm::None {
+            std::fs::remove_file(&file_path)
+        } else {
+            match compress_stale_file(&file_path, &file_name, compression) {
+                Some(r) => r,
+                None => continue
+            }
+        };
+        if let Err(e) = result {
+            problems.push(coalyxw!(W_CLEANUP_FAILED, file_path.to_string_lossy().to_string(),
+                                   e.to_string()));
+        }
+    }
+    problems
+}
+
+/// Compresses an orphaned output file and removes the original file.
+///
+/// # Arguments
+/// * `file_path` - the path of the orphaned file
+/// * `file_name` - the pure name of the orphaned file
+/// * `compression` - the compression algorithm to use
+///
+/// # Return values
+/// the result of the compression, **None** if the file is already compressed or a checksum file
+#[cfg(feature="compression")]
+fn compress_stale_file(file_path: &PathBuf,
+                       file_name: &str,
+                       compression: CompressionAlgorithm) -> Option<Result<(), std::io::Error>> {
+    let compr_algos = [CompressionAlgorithm::Bzip2, CompressionAlgorithm::Gzip,
+                       CompressionAlgorithm::Lzma, CompressionAlgorithm::Zip];
+    if file_name.ends_with(CHECKSUM_FILE_EXT) ||
+       compr_algos.iter().any(|a| file_name.ends_with(a.file_extension())) { return None }
+    let mut arch_path = file_path.as_os_str().to_os_string();
+    arch_path.push(compression.file_extension());
+    Some(archive_active_file(file_path, &PathBuf::from(arch_path), &compression))
+}
+
+/// Compresses an orphaned output file.
+/// Without compression support, the configuration never requests a compression algorithm.
+#[cfg(not(feature="compression"))]
+fn compress_stale_file(_file_path: &PathBuf,
+                       _file_name: &str,
+                       _compression: CompressionAlgorithm) -> Option<Result<(), std::io::Error>> {
+    None
+}
+
+/// Moves the currently active output file of a resource to the archive file.
+/// If the file can't be renamed, because another process holds an open handle to it (typically
+/// virus scanners or indexers on Windows), the file contents are copied to the archive file and
+/// the active file is truncated afterwards.
+///
+/// # Arguments
+/// * `active_file_path` - the path of the active output file
+/// * `arch_file_path` - the path for the archived active output file
+///
+/// # Errors
+/// Returns an error structure if an I/O error occurs
+fn move_active_file(active_file_path: &Path,
+                    arch_file_path: &Path) -> Result<(), std::io::Error> {
+    if std::fs::rename(active_file_path, arch_file_path).is_ok() { return Ok(()) }
+    std::fs::copy(active_file_path, arch_file_path)?;
+    truncate_active_file(active_file_path)
+}
+
+/// Removes the currently active output file of a resource after its contents have been
+/// archived.
+/// If the file can't be removed, because another process holds an open handle to it, the file is
+/// truncated instead.
+///
+/// # Arguments
+/// * `active_file_path` - the path of the active output file
+///
+/// # Errors
+/// Returns an error structure if the file can neither be removed nor truncated
+#[cfg(feature="compression")]
+fn remove_active_file(active_file_path: &Path) -> Result<(), std::io::Error> {
+    if std::fs::remove_file(active_file_path).is_ok() { return Ok(()) }
+    truncate_active_file(active_file_path)
+}
+
+/// Truncates the currently active output file of a resource.
+///
+/// # Arguments
+/// * `active_file_path` - the path of the active output file
+///
+/// # Errors
+/// Returns an error structure if the file can't be opened or truncated
+fn truncate_active_file(active_file_path: &Path) -> Result<(), std::io::Error> {
+    shared_open_options().write(true).open(active_file_path)?.set_len(0)
 }
 
 /// Archives the currently active output file of a resource.
@@ -186,7 +516,7 @@ fn archive_active_file(active_file_path: &PathBuf,
             let mut enc = BzEncoder::new(f, bzip2::Compression::fast());
             enc.write_all(&data)?;
             enc.finish()?;
-            let _ = std::fs::remove_file(active_file_path);
+            remove_active_file(active_file_path)?;
             Ok(())
         },
         CompressionAlgorithm::Zip => {
@@ -198,7 +528,7 @@ fn archive_active_file(active_file_path: &PathBuf,
             enc.start_file(fname, opts)?;
             enc.write_all(&data)?;
             enc.finish()?;
-            let _ = std::fs::remove_file(active_file_path);
+            remove_active_file(active_file_path)?;
             Ok(())
         },
         CompressionAlgorithm::Gzip => {
@@ -209,7 +539,7 @@ fn archive_active_file(active_file_path: &PathBuf,
                                          .write(f, flate2::Compression::default());
             enc.write_all(&data)?;
             enc.finish()?;
-            let _ = std::fs::remove_file(active_file_path);
+            remove_active_file(active_file_path)?;
             Ok(())
         },
         CompressionAlgorithm::Lzma => {
@@ -218,12 +548,12 @@ fn archive_active_file(active_file_path: &PathBuf,
             let mut enc = XzEncoder::new(f, 9);
             enc.write_all(&data)?;
             enc.finish()?;
-            let _ = std::fs::remove_file(active_file_path);
+            remove_active_file(active_file_path)?;
             Ok(())
         },
         CompressionAlgorithm::None => {
-            // without compression we can simply rename the file
-            std::fs::rename(active_file_path, arch_file_path)
+            // without compression we can simply move the file
+            move_active_file(active_file_path, arch_file_path)
         }
     }
 }
@@ -236,7 +566,7 @@ fn archive_active_file(active_file_path: &PathBuf,
 /// * `current_file_name` - the pure name of the current output resource file without path
 /// * `datetime_dependent` - indicates whether the file name contains date/time related variables
 ///                          (true) or not (false)
-/// * `pattern` - the regular expression to find matching files
+/// * `pattern` - the pattern to find matching files
 /// * `compr_ext` - compression specific file extension including dot, empty string for no
 ///                 compression
 ///
@@ -249,17 +579,16 @@ fn archive_active_file(active_file_path: &PathBuf,
 fn find_resource_files(dir: &Path,
                        current_file_name: &str,
                        datetime_dependent: bool,
-                       pattern: &Regex,
+                       pattern: &FileNamePattern,
                        compr_ext: &str) -> Result<Vec<AssociatedResFile>, CoalyException> {
     match std::fs::read_dir(dir) {
         Ok(dir_list) => {
             let mut files = Vec::<AssociatedResFile>::new();
             for entry in dir_list.flatten() {
                 let elem_name = entry.file_name().to_string_lossy().to_string();
-                if pattern.is_match(&elem_name) {
+                if let Some(parts) = pattern.captures(&elem_name) {
                     let act_flag = elem_name == current_file_name;
-                    let caps = pattern.captures(&elem_name).unwrap();
-                    let desc = AssociatedResFile::new(&caps, act_flag,
+                    let desc = AssociatedResFile::new(&parts, act_flag,
                                                       datetime_dependent, compr_ext);
                     files.push(desc);
                 }
@@ -301,6 +630,9 @@ fn shift_rollover_files(dir: &Path,
         if let Err(e) = std::fs::rename(old_path, &new_path) {
             return Err(coalyxe!(E_ROVR_FAILED, old_fn, e.to_string()))
         }
+        if let Err(e) = shift_checksum_file(dir, &old_fn, &f.shifted_file_name()) {
+            return Err(coalyxe!(E_ROVR_FAILED, old_fn, e.to_string()))
+        }
     }
     Ok(())
 }
@@ -325,6 +657,8 @@ fn remove_rollover_files<'a>(dir: &Path,
         if let Err(e) = std::fs::remove_file(&file_path) {
             return Err(coalyxe!(E_ROVR_FAILED, file_name, e.to_string()))
         }
+        // checksum file may not exist
+        let _ = std::fs::remove_file(checksum_file_path(&file_path));
     }
     let result_count = std::cmp::min(files.len(), keep_count as usize);
     Ok(&files[..result_count])
@@ -345,19 +679,17 @@ impl AssociatedResFile {
     /// Creates an output resource file descriptor.
     ///
     /// # Arguments
-    /// * `caps` - regular expression captures for the file name
+    /// * `parts` - the parts of the file name matching the resource's file name pattern
     /// * `active_flag` - indicates whether this is the active output file
     /// * `date_time_flag` - indicates whether the file name contains date/time related
     ///                      variables (true) or not (false)
     /// * `compr_ext` - compression specific file extension including dot, empty string for no
     ///                 compression
-    fn new(caps: &Captures, active_flag: bool, date_time_flag: bool,
+    fn new(parts: &FileNameMatch, active_flag: bool, date_time_flag: bool,
            compr_ext: &str) -> AssociatedResFile {
-        let stem = caps.get(1).unwrap().as_str().to_string();
-        let seq = if let Some(s) = caps.get(2) { s.as_str()[1..].to_string() }
-                  else { String::from("0") };
-        let seq_nr = usize::from_str_radix(&seq, 10).unwrap();
-        let ext = if let Some(e) = caps.get(3) { e.as_str().to_string() } else { String::from("") };
+        let stem = parts.stem.to_string();
+        let seq_nr = parts.seq_nr.map_or(0, |s| s.parse::<usize>().unwrap_or(usize::MAX));
+        let ext = parts.ext.unwrap_or_default().to_string();
         AssociatedResFile {
             stem,
             ext,
@@ -414,6 +746,16 @@ impl PartialEq for AssociatedResFile {
 }
 impl Eq for AssociatedResFile { }
 
+// file extension for checksum files
+const CHECKSUM_FILE_EXT: &str = ".sha256";
+
+// prefix of the line holding the chain hash of the previous archive file in checksum files
+#[cfg(feature="checksums")]
+const PREV_CHAIN_PREFIX: &str = "# previous: ";
+
+// prefix of the line holding the chain hash of the archive file in checksum files
+const CHAIN_PREFIX: &str = "# chain: ";
+
 #[cfg(test)]
 mod tests {
     use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
@@ -590,12 +932,12 @@ mod tests {
                          spec.to_file_name()
                      };
         let dtm_dep = ! spec.is_datetime_independent();
-        let pat = spec.file_name_pattern(compr_ext).unwrap();
+        let pat = spec.file_name_pattern(compr_ext);
         let rovr_files = files.iter().skip(1)
                               .map(|p| {
                                    let fname = p.file_name().unwrap().to_string_lossy();
-                                   let caps = pat.captures(&fname).unwrap();
-                                   AssociatedResFile::new(&caps, false, dtm_dep, compr_ext)
+                                   let parts = pat.captures(&fname).unwrap();
+                                   AssociatedResFile::new(&parts, false, dtm_dep, compr_ext)
                                })
                               .collect::<Vec::<AssociatedResFile>>();
         let _ = shift_rollover_files(tf_path, &new_fn, &rovr_files);
@@ -635,7 +977,7 @@ mod tests {
         let files = create_res_files(&tf_path, &spec, res_count, compr_ext);
         let cur_fn = files[0].file_name().unwrap().to_string_lossy();
         let indep = spec.is_datetime_independent();
-        let find_pattern = spec.file_name_pattern(compr_ext).unwrap();
+        let find_pattern = spec.file_name_pattern(compr_ext);
         let find_result = find_resource_files(&tf_path, &cur_fn, indep, &find_pattern, compr_ext);
         check_find_result(&tf_path, &files, &find_result);
     }
@@ -645,9 +987,9 @@ mod tests {
         let spec = FormatSpec::from_str(fn_spec).unwrap();
         let exp_name = format!("{}{}", spec.to_file_name(), fn_ext);
         let dtm_dep = ! spec.is_datetime_independent();
-        let find_pattern = spec.file_name_pattern(compr_ext).unwrap();
-        let caps = find_pattern.captures(&exp_name).unwrap();
-        let fdesc = AssociatedResFile::new(&caps, active_flag, dtm_dep, compr_ext);
+        let find_pattern = spec.file_name_pattern(compr_ext);
+        let parts = find_pattern.captures(&exp_name).unwrap();
+        let fdesc = AssociatedResFile::new(&parts, active_flag, dtm_dep, compr_ext);
         let act_name = fdesc.file_name();
         let act_shifted_name = fdesc.shifted_file_name();
         assert_eq!(active_flag, fdesc.active_flag, "{}", &act_name);
@@ -772,7 +1114,9 @@ mod tests {
                                    &new_file_name,
                                    &file_name_spec,
                                    keep_count,
-                                   compression);
+                                   0,
+                                   compression,
+                                   false);
         // archival must succeed
         assert!(res.is_ok(), "archive operation failed");
         // check archival effect
@@ -910,6 +1254,25 @@ mod tests {
         run_find_test(&tf_path, "myapp_$Time_thread_$Date_08.log", 3, ".gz");
     }
 
+    #[test]
+    /// Tests dry-run for removal of rollover files exceeding the keep count.
+    fn test_retention_candidates() {
+        let tf_path = test_dir_path(&["rollover", "test_retention_candidates"]);
+        let spec = FormatSpec::from_str(DEF_RES_NAME).unwrap();
+        let files = create_res_files(&tf_path, &spec, 4, "");
+        let cands = retention_candidates(&tf_path, DEF_RES_NAME, &spec, 3, 0,
+                                         &CompressionAlgorithm::None).unwrap();
+        let exp_cands: Vec<String> = files[3..].iter()
+                                               .map(|f| f.to_string_lossy().to_string())
+                                               .collect();
+        assert_eq!(exp_cands, cands);
+        // dry-run must not remove any file
+        assert!(files.iter().all(|f| f.exists()));
+        let cands = retention_candidates(&tf_path, DEF_RES_NAME, &spec, 5, 0,
+                                         &CompressionAlgorithm::None).unwrap();
+        assert!(cands.is_empty());
+    }
+
     #[test]
     /// Tests descriptor structure for files belonging to a resource
     fn test_associated_res_file() {
@@ -959,6 +1322,23 @@ mod tests {
         run_arch_active_file(&tf_path, "myapp.log", &CompressionAlgorithm::Lzma);
     }
 
+    #[test]
+    /// Tests move and truncation of active file
+    fn test_move_active_file() {
+        let tf_path = test_dir_path(&["rollover", "test_move_active_file"]);
+        clear_test_dir(&tf_path);
+        let _ = std::fs::create_dir_all(&tf_path);
+        let act_file_path = tf_path.join("myapp.log");
+        let arch_file_path = tf_path.join("myapp.log.1");
+        std::fs::write(&act_file_path, "Test record\n").unwrap();
+        assert!(move_active_file(&act_file_path, &arch_file_path).is_ok());
+        assert!(! act_file_path.exists());
+        assert_eq!("Test record\n", std::fs::read_to_string(&arch_file_path).unwrap());
+        std::fs::write(&act_file_path, "Test record\n").unwrap();
+        assert!(truncate_active_file(&act_file_path).is_ok());
+        assert_eq!(0, std::fs::metadata(&act_file_path).unwrap().len());
+    }
+
     #[test]
     /// Tests archival of active file
     fn test_archive_resource() {
@@ -995,4 +1375,112 @@ mod tests {
         // compression gzip, date/time dependent, archive file count at keep limit
         run_archive_resource(&tf_path, "myapp_$TimeStamp.log", true, 2, 2, &CompressionAlgorithm::Gzip);
     }
+
+    #[cfg(feature="checksums")]
+    #[test]
+    /// Tests checksum files and hash chain for archived files
+    fn test_checksum_files() {
+        let tf_path = test_dir_path(&["rollover", "test_checksum_files"]);
+        clear_test_dir(&tf_path);
+        let _ = std::fs::create_dir_all(&tf_path);
+        let spec = FormatSpec::from_str(DEF_RES_NAME).unwrap();
+        let read_sidecar = |seq_nr: usize| {
+            let file_name = res_file_name(DEF_RES_NAME, CHECKSUM_FILE_EXT, seq_nr);
+            let contents = std::fs::read_to_string(tf_path.join(file_name)).unwrap();
+            contents.lines().map(|l| l.to_string()).collect::<Vec<String>>()
+        };
+        let mut chain_hash = "0".repeat(64);
+        for data in ["DATA1", "DATA2", "DATA3"] {
+            std::fs::write(tf_path.join(DEF_RES_NAME), data).unwrap();
+            let res = archive_resource(&tf_path, DEF_RES_NAME, DEF_RES_NAME, &spec, 2, 0,
+                                       &CompressionAlgorithm::None, true);
+            assert!(matches!(res, Ok(None)));
+            let file_hash = hex_string(&Sha256::digest(data.as_bytes()));
+            let exp_chain_hash = hex_string(&Sha256::digest(format!("{}{}", chain_hash,
+                                                                    file_hash).as_bytes()));
+            let lines = read_sidecar(1);
+            assert_eq!(format!("{}{}", PREV_CHAIN_PREFIX, chain_hash), lines[0]);
+            assert_eq!(format!("{}{}", CHAIN_PREFIX, exp_chain_hash), lines[1]);
+            assert_eq!(format!("{}  {}.1", file_hash, DEF_RES_NAME), lines[2]);
+            chain_hash = exp_chain_hash;
+        }
+        // checksum file shifted together with archive file
+        let lines = read_sidecar(2);
+        assert_eq!(format!("{}.2", DEF_RES_NAME), lines[2].split("  ").nth(1).unwrap());
+        assert_eq!(read_sidecar(1)[0].replace(PREV_CHAIN_PREFIX, CHAIN_PREFIX), lines[1]);
+        // checksum file removed together with archive file
+        assert!(! res_file_path(&tf_path, DEF_RES_NAME, "", 3).exists());
+        assert!(! res_file_path(&tf_path, DEF_RES_NAME, CHECKSUM_FILE_EXT, 3).exists());
+    }
+
+    #[test]
+    /// Tests removal of rollover files exceeding the total size limit.
+    fn test_max_total_size() {
+        let tf_path = test_dir_path(&["rollover", "test_max_total_size"]);
+        clear_test_dir(&tf_path);
+        let _ = std::fs::create_dir_all(&tf_path);
+        let spec = FormatSpec::from_str(DEF_RES_NAME).unwrap();
+        for _ in 0..3 {
+            std::fs::write(tf_path.join(DEF_RES_NAME), vec![b'x'; 40000]).unwrap();
+            let res = archive_resource(&tf_path, DEF_RES_NAME, DEF_RES_NAME, &spec, 9, 100000,
+                                       &CompressionAlgorithm::None, false);
+            assert!(matches!(res, Ok(None)));
+        }
+        // keep count not reached, but third file exceeds total size
+        assert!(res_file_path(&tf_path, DEF_RES_NAME, "", 1).exists());
+        assert!(res_file_path(&tf_path, DEF_RES_NAME, "", 2).exists());
+        assert!(! res_file_path(&tf_path, DEF_RES_NAME, "", 3).exists());
+        // dry-run counts active file
+        std::fs::write(tf_path.join(DEF_RES_NAME), vec![b'x'; 40000]).unwrap();
+        let cands = retention_candidates(&tf_path, DEF_RES_NAME, &spec, 9, 100000,
+                                         &CompressionAlgorithm::None).unwrap();
+        assert_eq!(vec!(res_file_path(&tf_path, DEF_RES_NAME, "", 2).to_string_lossy()
+                                                                    .to_string()),
+                   cands);
+        // newest archive file is kept, even if it exceeds the limit on its own
+        std::fs::write(tf_path.join(DEF_RES_NAME), vec![b'x'; 120000]).unwrap();
+        let res = archive_resource(&tf_path, DEF_RES_NAME, DEF_RES_NAME, &spec, 9, 100000,
+                                   &CompressionAlgorithm::None, false);
+        assert!(matches!(res, Ok(None)));
+        assert!(res_file_path(&tf_path, DEF_RES_NAME, "", 1).exists());
+        assert!(! res_file_path(&tf_path, DEF_RES_NAME, "", 2).exists());
+    }
+
+    #[test]
+    fn test_cleanup_stale_files() {
+        let tf_path = test_dir_path(&["rollover", "test_cleanup_stale_files"]);
+        clear_test_dir(&tf_path);
+        let _ = std::fs::create_dir_all(&tf_path);
+        let spec = FormatSpec::from_str("myapp_$ProcessId.log").unwrap();
+        let patterns = vec!(spec.stale_file_pattern());
+        let stale_names = ["myapp_17.log", "myapp_18.log.1", "myapp_18.log.1.sha256"];
+        for name in stale_names.iter().chain([DEF_RES_NAME, "myapp_.log"].iter()) {
+            std::fs::write(tf_path.join(name), "x").unwrap();
+        }
+        // files younger than retention period are kept
+        let probs = cleanup_stale_files(&tf_path, &patterns, std::time::Duration::from_secs(3600),
+                                        CompressionAlgorithm::None);
+        assert!(probs.is_empty());
+        assert!(stale_names.iter().all(|n| tf_path.join(n).exists()));
+        #[cfg(feature="compression")]
+        {
+            let probs = cleanup_stale_files(&tf_path, &patterns, std::time::Duration::ZERO,
+                                            CompressionAlgorithm::Gzip);
+            assert!(probs.is_empty());
+            assert!(! tf_path.join("myapp_17.log").exists());
+            assert!(tf_path.join("myapp_17.log.gz").exists());
+            assert!(tf_path.join("myapp_18.log.1.gz").exists());
+            assert!(tf_path.join("myapp_18.log.1.sha256").exists());
+            assert!(! tf_path.join("myapp_17.log.gz.gz").exists());
+        }
+        let probs = cleanup_stale_files(&tf_path, &patterns, std::time::Duration::ZERO,
+                                        CompressionAlgorithm::None);
+        assert!(probs.is_empty());
+        let remaining: Vec<String> = std::fs::read_dir(&tf_path).unwrap().flatten()
+                                        .map(|e| e.file_name().to_string_lossy().to_string())
+                                        .collect();
+        assert_eq!(2, remaining.len());
+        assert!(tf_path.join(DEF_RES_NAME).exists());
+        assert!(tf_path.join("myapp_.log").exists());
+    }
 }
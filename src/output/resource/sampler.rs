@@ -0,0 +1,107 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Enforcement of sampling policies, writing only a statistical sample of the records issued
+//! to a resource.
+//! Records not selected are discarded silently, they are neither written nor counted as
+//! dropped.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use crate::policies::SamplingPolicy;
+
+/// Runtime state of a sampling policy for a resource.
+#[derive (Clone)]
+pub(crate) struct Sampler {
+    // the sampling policy
+    policy: SamplingPolicy,
+    // state of the pseudo random number generator
+    state: u64
+}
+impl Sampler {
+    /// Creates the runtime state for the given sampling policy.
+    ///
+    /// # Arguments
+    /// * `policy` - the sampling policy
+    pub(crate) fn new(policy: &SamplingPolicy) -> Sampler {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write(policy.name().as_bytes());
+        Sampler { policy: policy.clone(), state: hasher.finish() }
+    }
+
+    /// Checks whether a record is selected for output.
+    ///
+    /// # Arguments
+    /// * `level` - the record level ID
+    ///
+    /// # Return values
+    /// **true** if the record shall be written, **false** if it shall be discarded
+    pub(crate) fn admit(&mut self, level: u32) -> bool {
+        let rate = self.policy.rate_for(level);
+        if rate >= 1.0 { return true }
+        if rate <= 0.0 { return false }
+        self.next_value() < rate
+    }
+
+    /// Returns the next pseudo random number in the range 0.0 (inclusive) to 1.0 (exclusive).
+    /// Uses the SplitMix64 generator, which is fast and good enough for sampling purposes.
+    fn next_value(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::RecordLevelId;
+
+    #[test]
+    fn test_sampler() {
+        let debug = RecordLevelId::Debug as u32;
+        let info = RecordLevelId::Info as u32;
+        let error = RecordLevelId::Error as u32;
+        let pol = SamplingPolicy::new("s", 0.5, vec!((debug, 0.0), (error, 1.0)));
+        let mut smp = Sampler::new(&pol);
+        let mut info_count = 0;
+        for _ in 0 .. 10000 {
+            assert!(! smp.admit(debug));
+            assert!(smp.admit(error));
+            if smp.admit(info) { info_count += 1; }
+        }
+        assert!(info_count > 4500 && info_count < 5500);
+    }
+}
@@ -0,0 +1,287 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Disk based spool for network resources with guaranteed delivery.
+//! Every message is appended to the spool file before it is sent to the logging server and
+//! remains there until the server has acknowledged its receipt. Messages not acknowledged when
+//! the application terminates are sent again after the next connect, hence a message may be
+//! delivered more than once, but is never lost.
+//! The spool file starts with the offset of the first unacknowledged message, followed by the
+//! messages in the same format as sent over the network.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::coalyxe;
+use crate::errorhandling::*;
+use crate::net::serializable::Serializable;
+
+/// Spool file for messages, that must be acknowledged by the logging server.
+pub(crate) struct Spool {
+    // spool file
+    file: File,
+    // spool file name, for error messages
+    file_name: String,
+    // offset of the first message not yet acknowledged by the server
+    ack_offset: u64,
+    // end offset of the spool file
+    end_offset: u64,
+    // end offsets of all messages sent over the current connection and not yet acknowledged
+    sent_msgs: VecDeque<u64>,
+    // number of messages acknowledged by the server over the current connection
+    ack_count: u64
+}
+impl Spool {
+    /// Opens the spool file with the given path name, the file is created if it doesn't exist.
+    /// An existing file may contain messages not acknowledged in a previous run of the
+    /// application.
+    ///
+    /// # Arguments
+    /// * `path` - the spool file's path name
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be created or read
+    pub(crate) fn open(path: &Path) -> Result<Spool, CoalyException> {
+        let file_name = path.to_string_lossy().to_string();
+        let file = match OpenOptions::new().read(true).write(true).create(true)
+                                            .truncate(false).open(path) {
+            Ok(f) => f,
+            Err(e) => return Err(coalyxe!(E_FILE_CRE_ERR, file_name, e.to_string()))
+        };
+        let end_offset = match file.metadata() {
+            Ok(m) => m.len(),
+            Err(e) => return Err(coalyxe!(E_FILE_READ_ERR, file_name, e.to_string()))
+        };
+        let mut spool = Spool { file, file_name, ack_offset: SPOOL_HEADER_SIZE,
+                                end_offset: SPOOL_HEADER_SIZE, sent_msgs: VecDeque::new(),
+                                ack_count: 0 };
+        if end_offset < SPOOL_HEADER_SIZE {
+            // new spool file
+            spool.store_ack_offset()?;
+            return Ok(spool)
+        }
+        let mut hdr = [0u8; SPOOL_HEADER_SIZE as usize];
+        if let Err(e) = spool.file.read_exact(&mut hdr) {
+            return Err(coalyxe!(E_FILE_READ_ERR, spool.file_name, e.to_string()))
+        }
+        spool.end_offset = end_offset;
+        // if the offset is damaged, all messages in the spool are sent again
+        let ack_offset = u64::deserialize_from(&hdr).unwrap_or(SPOOL_HEADER_SIZE);
+        if ack_offset <= end_offset { spool.ack_offset = ack_offset; }
+        Ok(spool)
+    }
+
+    /// Opens the spool file anew, e.g. after the process changed its user identity.
+    ///
+    /// # Errors
+    /// Returns an error structure if the file can't be opened
+    pub(crate) fn reopen(&mut self) -> Result<(), CoalyException> {
+        self.file = match OpenOptions::new().read(true).write(true).open(&self.file_name) {
+            Ok(f) => f,
+            Err(e) => return Err(coalyxe!(E_FILE_CRE_ERR, self.file_name.to_string(),
+                                          e.to_string()))
+        };
+        Ok(())
+    }
+
+    /// Indicates whether all messages in the spool have been acknowledged by the server.
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool { self.ack_offset >= self.end_offset }
+
+    /// Indicates whether messages have been sent over the current connection, that haven't
+    /// been acknowledged yet.
+    #[inline]
+    pub(crate) fn awaits_acknowledgement(&self) -> bool { ! self.sent_msgs.is_empty() }
+
+    /// Appends a message to the spool.
+    ///
+    /// # Arguments
+    /// * `msg` - the serialized message including header
+    ///
+    /// # Return values
+    /// the end offset of the message in the spool file
+    ///
+    /// # Errors
+    /// Returns an error structure if the message could not be written to the spool file
+    pub(crate) fn append(&mut self, msg: &[u8]) -> Result<u64, CoalyException> {
+        if let Err(e) = self.file.seek(SeekFrom::Start(self.end_offset))
+                                 .and_then(|_| self.file.write_all(msg)) {
+            return Err(coalyxe!(E_FILE_WRITE_ERR, self.file_name.to_string(), e.to_string()))
+        }
+        self.end_offset += msg.len() as u64;
+        Ok(self.end_offset)
+    }
+
+    /// Returns all messages not yet acknowledged by the server.
+    /// Called after a connection to the server has been established, since all those messages
+    /// must be sent again. Resets the acknowledgement counter for the new connection.
+    /// A truncated message at the end of the spool, caused by an application crash, is removed.
+    ///
+    /// # Return values
+    /// array with the unacknowledged messages and their end offsets in the spool file
+    ///
+    /// # Errors
+    /// Returns an error structure if the spool file could not be read
+    pub(crate) fn unacknowledged_messages(&mut self) -> Result<Vec<(Vec<u8>, u64)>,
+                                                                 CoalyException> {
+        self.sent_msgs.clear();
+        self.ack_count = 0;
+        let mut msgs = Vec::<(Vec<u8>, u64)>::new();
+        if self.is_empty() { return Ok(msgs) }
+        let mut data = Vec::<u8>::with_capacity((self.end_offset - self.ack_offset) as usize);
+        if let Err(e) = self.file.seek(SeekFrom::Start(self.ack_offset))
+                                 .and_then(|_| self.file.read_to_end(&mut data)) {
+            return Err(coalyxe!(E_FILE_READ_ERR, self.file_name.to_string(), e.to_string()))
+        }
+        let mut pos = 0usize;
+        while data.len() - pos >= MSG_HEADER_SIZE {
+            let payload_size = u32::deserialize_from(&data[pos + 12..])? as usize;
+            let msg_end = pos + MSG_HEADER_SIZE + payload_size;
+            if msg_end > data.len() { break }
+            msgs.push((data[pos..msg_end].to_vec(), self.ack_offset + msg_end as u64));
+            pos = msg_end;
+        }
+        if pos < data.len() {
+            self.end_offset = self.ack_offset + pos as u64;
+            if let Err(e) = self.file.set_len(self.end_offset) {
+                return Err(coalyxe!(E_FILE_WRITE_ERR, self.file_name.to_string(),
+                                    e.to_string()))
+            }
+        }
+        Ok(msgs)
+    }
+
+    /// Notifies the spool, that a message has been sent over the current connection.
+    ///
+    /// # Arguments
+    /// * `end_offset` - the end offset of the message in the spool file
+    #[inline]
+    pub(crate) fn message_sent(&mut self, end_offset: u64) { self.sent_msgs.push_back(end_offset) }
+
+    /// Processes an acknowledgement from the server.
+    /// Acknowledged messages are removed from the spool, the spool file is truncated as soon as
+    /// all messages have been acknowledged.
+    ///
+    /// # Arguments
+    /// * `count` - the total number of messages acknowledged over the current connection
+    ///
+    /// # Errors
+    /// Returns an error structure if the spool file could not be updated
+    pub(crate) fn acknowledge(&mut self, count: u64) -> Result<(), CoalyException> {
+        if count <= self.ack_count { return Ok(()) }
+        let mut ack_offset = self.ack_offset;
+        while self.ack_count < count {
+            match self.sent_msgs.pop_front() {
+                Some(offs) => ack_offset = offs,
+                None => break
+            }
+            self.ack_count += 1;
+        }
+        self.ack_offset = ack_offset;
+        if self.is_empty() {
+            self.ack_offset = SPOOL_HEADER_SIZE;
+            self.end_offset = SPOOL_HEADER_SIZE;
+            if let Err(e) = self.file.set_len(SPOOL_HEADER_SIZE) {
+                return Err(coalyxe!(E_FILE_WRITE_ERR, self.file_name.to_string(),
+                                    e.to_string()))
+            }
+        }
+        self.store_ack_offset()
+    }
+
+    /// Writes the offset of the first unacknowledged message to the spool file header.
+    fn store_ack_offset(&mut self) -> Result<(), CoalyException> {
+        let mut hdr = Vec::<u8>::with_capacity(SPOOL_HEADER_SIZE as usize);
+        self.ack_offset.serialize_to(&mut hdr);
+        if let Err(e) = self.file.seek(SeekFrom::Start(0))
+                                 .and_then(|_| self.file.write_all(&hdr)) {
+            return Err(coalyxe!(E_FILE_WRITE_ERR, self.file_name.to_string(), e.to_string()))
+        }
+        Ok(())
+    }
+}
+
+// Size of the spool file header containing the offset of the first unacknowledged message
+const SPOOL_HEADER_SIZE: u64 = 8;
+
+// Size of a network message header, the payload size is stored in the last four bytes
+const MSG_HEADER_SIZE: usize = 16;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(payload: &[u8]) -> Vec<u8> {
+        let mut msg = Vec::<u8>::new();
+        1u32.serialize_to(&mut msg);
+        0u64.serialize_to(&mut msg);
+        (payload.len() as u32).serialize_to(&mut msg);
+        msg.extend_from_slice(payload);
+        msg
+    }
+
+    #[test]
+    fn test_spool() {
+        let path = std::env::temp_dir().join(format!("coaly_spool_{}.spl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut spool = Spool::open(&path).unwrap();
+        assert!(spool.is_empty());
+        let msg1 = message(b"first");
+        let msg2 = message(b"second record");
+        let end1 = spool.append(&msg1).unwrap();
+        let end2 = spool.append(&msg2).unwrap();
+        spool.message_sent(end1);
+        spool.message_sent(end2);
+        assert!(spool.awaits_acknowledgement());
+        spool.acknowledge(1).unwrap();
+        drop(spool);
+        // second message must survive a restart
+        let mut spool = Spool::open(&path).unwrap();
+        assert!(! spool.is_empty());
+        let msgs = spool.unacknowledged_messages().unwrap();
+        assert_eq!(1, msgs.len());
+        assert_eq!(msg2, msgs[0].0);
+        spool.message_sent(msgs[0].1);
+        spool.acknowledge(1).unwrap();
+        assert!(spool.is_empty());
+        assert!(! spool.awaits_acknowledgement());
+        assert_eq!(SPOOL_HEADER_SIZE, std::fs::metadata(&path).unwrap().len());
+        // truncated message at the end is discarded
+        spool.append(&msg1).unwrap();
+        spool.append(&msg2[..10]).unwrap();
+        let msgs = spool.unacknowledged_messages().unwrap();
+        assert_eq!(1, msgs.len());
+        assert_eq!(msg1, msgs[0].0);
+        let _ = std::fs::remove_file(&path);
+    }
+}
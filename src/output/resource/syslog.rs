@@ -37,6 +37,7 @@ use std::net::*;
 use crate::coalyxe;
 use crate::errorhandling::*;
 use crate::net::*;
+use crate::net::connector::{Connection, DeferredConnection, TcpConnection};
 use crate::record::originator::OriginatorInfo;
 use crate::record::recorddata::RecordData;
 #[cfg(unix)]
@@ -54,12 +55,16 @@ pub struct SyslogData {
     // remote address
     remote_addr: PeerAddr,
     // TCP communication stream
-    tcp_stream: Option<TcpStream>,
+    tcp_stream: Option<TcpConnection>,
     // UDP communication socket
     udp_socket: Option<UdpSocket>,
     // Unix communication stream
     #[cfg(unix)]
-    unix_stream: Option<UnixStream>
+    unix_stream: Option<UnixStream>,
+    // TCP or UDP connection currently being established
+    pending: Option<DeferredConnection>,
+    // the optional socket address for the local network socket, needed to reconnect
+    local_addr: Option<PeerAddr>
 }
 impl SyslogData {
     /// Creates specific structure to communicate to syslog service.
@@ -92,76 +97,74 @@ impl SyslogData {
             tcp_stream: None,
             udp_socket: None,
             #[cfg(unix)]
-            unix_stream: None
+            unix_stream: None,
+            pending: None,
+            local_addr: None
         }
     }
 
+    /// Returns the address of the communication partner.
+    #[inline]
+    pub(crate) fn peer_addr(&self) -> &PeerAddr { &self.remote_addr }
+
     /// Creates suitable communication socket and connects to syslog service.
+    /// For TCP and UDP, host name resolution and connect are done in the background, records
+    /// written in the meantime are sent as soon as the connection has been established.
     ///
     /// # Arguments
     /// * `local_addr` - the optional socket address for the local network socket
     #[cfg(unix)]
     pub fn connect(&mut self, local_addr: Option<PeerAddr>) -> Result<(), CoalyException> {
+        self.local_addr = local_addr.clone();
         match &self.remote_addr {
-            PeerAddr::IpSocket(prot, ip_addr) => {
-                if *prot == NetworkProtocol::Tcp {
-                    if self.tcp_stream.is_some() {
-                        return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
-                    }
-                    self.tcp_stream = Some(SyslogData::open_tcp(&ip_addr)?);
-                } else {
-                    if self.udp_socket.is_some() {
-                        return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
-                    }
-                    self.udp_socket = Some(SyslogData::open_udp(&ip_addr, local_addr)?);
-                }
-            },
             #[cfg(unix)]
             PeerAddr::UnixSocket(path) => {
                 if self.unix_stream.is_some() {
                     return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
                 }
                 self.unix_stream = Some(SyslogData::open_unix(&path)?);
+            },
+            _ => {
+                if self.tcp_stream.is_some() || self.udp_socket.is_some() ||
+                   self.pending.is_some() {
+                    return Err(coalyxe!(E_ALREADY_CONNECTED, self.remote_addr.to_string()))
+                }
+                let laddr = local_addr.as_ref().and_then(|a| a.ip_addr()).copied();
+                self.pending = Some(DeferredConnection::start(&self.remote_addr, laddr, None));
             }
         }
         Ok(())
     }
 
-    /// Opens a TCP socket to syslog service.
+    /// Checks whether a pending connection attempt has finished.
+    /// If the connection has been established, all records written in the meantime are sent.
     ///
-    /// # Arguments
-    /// * `remote_addr` - the the socket address of syslog service
-    fn open_tcp(remote_addr: &SocketAddr) -> Result<TcpStream, CoalyException> {
-        TcpStream::connect(remote_addr).map_err(|e| coalyxe!(E_SOCKET_CRE_ERR,
-                                                           remote_addr.to_string(),
-                                                           e.to_string()))
-    }
-
-    /// Opens a UDP socket to syslog service.
+    /// # Return values
+    /// **false** if the connection attempt is still in progress
     ///
-    /// # Arguments
-    /// * `remote_addr` - the the socket address of syslog service
-    fn open_udp(remote_addr: &SocketAddr,
-                local_addr: Option<PeerAddr>) -> Result<UdpSocket, CoalyException> {
-        let mut laddr: Option<SocketAddr> = None;
-        if let Some(l) = local_addr {
-            if let Some(a) = l.ip_addr() { laddr = Some(*a); }
-        }
-        if laddr.is_none() {
-            laddr = if remote_addr.is_ipv4() {
-                        Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0))
-                    } else {
-                        Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0))
-                    };
+    /// # Errors
+    /// Returns an error structure if the connection attempt failed
+    fn complete_connection(&mut self) -> Result<bool, Vec<CoalyException>> {
+        let res = match self.pending.as_mut() {
+            Some(p) => p.poll(),
+            None => return Ok(true)
         };
-        let laddr = laddr.unwrap();
-        let s = UdpSocket::bind(laddr).map_err(|e| coalyxe!(E_SOCKET_CRE_ERR,
-                                                          remote_addr.to_string(),
-                                                          e.to_string()))?;
-        if let Err(e) = UdpSocket::bind(laddr) {
-            return Err(coalyxe!(E_SOCKET_CRE_ERR, laddr.to_string(), e.to_string()))
+        match res {
+            None => Ok(false),
+            Some(Ok((conn, backlog))) => {
+                self.pending = None;
+                match conn {
+                    Connection::Tcp(s) => self.tcp_stream = Some(s),
+                    Connection::Udp(s) => self.udp_socket = Some(s)
+                }
+                for data in backlog { self.write_buffer(&data)?; }
+                Ok(true)
+            },
+            Some(Err(e)) => {
+                self.pending = None;
+                Err(vec!(e))
+            }
         }
-        Ok(s)
     }
 
     /// Opens a Unix socket to syslog service.
@@ -183,16 +186,34 @@ impl SyslogData {
     /// # Errors
     /// Returns an error structure if the send operation fails
     pub fn send_record(&mut self, rec: &dyn RecordData) -> Result<(), Vec<CoalyException>> {
-        let lvl = std::cmp::max(rec.level() as u32, 7);
+        let lvl = rec.level().syslog_severity();
         let pri_n_ver = format!("<{}>", self.facility + lvl);
         self.buffer.clear();
         self.buffer.extend_from_slice(pri_n_ver.as_bytes());
         self.buffer.extend_from_slice(self.fix_header.as_slice());
         let rec_msg = rec.message();
         if let Some(ref msg) = rec_msg { self.buffer.extend_from_slice(msg.as_bytes()); }
+        if ! self.complete_connection()? {
+            if let Some(p) = self.pending.as_mut() { p.defer(self.buffer.as_slice()); }
+            return Ok(())
+        }
+        let data = std::mem::take(&mut self.buffer);
+        let res = self.write_buffer(&data);
+        self.buffer = data;
+        res
+    }
+
+    /// Writes a serialized message to the syslog socket.
+    /// 
+    /// # Arguments
+    /// * `data` - the serialized message
+    /// 
+    /// # Errors
+    /// Returns an error structure if the send operation fails
+    fn write_buffer(&mut self, data: &[u8]) -> Result<(), Vec<CoalyException>> {
         if let Some(s) = self.tcp_stream.as_mut() {
-            if let Err(e) = s.write(self.buffer.as_slice()) {
-                let local_addr = match s.local_addr() {
+            if let Err(e) = s.write(data) {
+                let local_addr = match s.socket().local_addr() {
                     Ok(a) => a.to_string(),
                     _ => String::from("?")
                 };
@@ -201,7 +222,7 @@ impl SyslogData {
             }
         }
         if let Some(s) = self.udp_socket.as_mut() {
-            if let Err(e) = s.send(self.buffer.as_slice()) {
+            if let Err(e) = s.send(data) {
                 let local_addr = match s.local_addr() {
                     Ok(a) => a.to_string(),
                     _ => String::from("?")
@@ -212,7 +233,7 @@ impl SyslogData {
         }
         #[cfg(unix)]
         if let Some(s) = self.unix_stream.as_mut() {
-            if let Err(e) = s.write(self.buffer.as_slice()) {
+            if let Err(e) = s.write(data) {
                 return Err(vec!(coalyxe!(E_SOCKET_WRITE_ERR, String::from(""),
                                        self.remote_addr.to_string(), e.to_string())))
             }
@@ -222,10 +243,23 @@ impl SyslogData {
 
     /// Closes the connection to syslog service.
     pub fn close(&mut self) {
+        self.pending = None;
         self.tcp_stream = None;
         self.udp_socket = None;
         self.unix_stream = None;
     }
+
+    /// Closes the connection to syslog service and connects anew, e.g. after the process
+    /// changed its user identity.
+    ///
+    /// # Errors
+    /// Returns an error structure if the connection can't be established
+    #[cfg(unix)]
+    pub(crate) fn reconnect(&mut self) -> Result<(), CoalyException> {
+        self.close();
+        let local_addr = self.local_addr.clone();
+        self.connect(local_addr)
+    }
 }
 
 const SPACE: u8 = 32;
@@ -0,0 +1,174 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+
+//! Enforcement of throttle policies, limiting the number of records written to a resource
+//! within a time interval.
+//! Records exceeding the limit are dropped, the number of dropped records is reported in a
+//! single summary record after the interval has elapsed.
+
+use std::time::{Duration, Instant};
+use crate::coalyxw;
+use crate::errorhandling::*;
+use crate::output::outputformat::OutputFormat;
+use crate::policies::ThrottlePolicy;
+
+/// Runtime state of a throttle policy for a resource.
+#[derive (Clone)]
+pub(crate) struct Throttle {
+    // the throttle policy
+    policy: ThrottlePolicy,
+    // interval length
+    interval: Duration,
+    // start of the current interval
+    interval_start: Instant,
+    // number of records written in current interval for all levels without specific limit
+    record_count: u32,
+    // number of records written in current interval, per level specific limit
+    level_counts: Vec<u32>,
+    // number of records suppressed in current interval
+    suppressed_count: u64,
+    // thread ID, thread name and output format of the first suppressed record, used for the
+    // summary record
+    summary_origin: Option<(u64, String, OutputFormat)>
+}
+impl Throttle {
+    /// Creates the runtime state for the given throttle policy.
+    ///
+    /// # Arguments
+    /// * `policy` - the throttle policy
+    pub(crate) fn new(policy: &ThrottlePolicy) -> Throttle {
+        Throttle {
+            policy: policy.clone(),
+            interval: Duration::from_secs(policy.interval()),
+            interval_start: Instant::now(),
+            record_count: 0,
+            level_counts: vec!(0; policy.level_limits().len()),
+            suppressed_count: 0,
+            summary_origin: None
+        }
+    }
+
+    /// Checks whether a record may be written.
+    /// Counts the record, if the limit for its level has not been reached in the current
+    /// interval; otherwise counts the record as suppressed.
+    ///
+    /// # Arguments
+    /// * `level` - the record level ID
+    /// * `thread_id` - the ID of the thread that issued the record
+    /// * `thread_name` - the name of the thread that issued the record
+    /// * `output_format` - the output format used for the record
+    ///
+    /// # Return values
+    /// **true** if the record may be written, **false** if it must be suppressed
+    pub(crate) fn admit(&mut self,
+                        level: u32,
+                        thread_id: u64,
+                        thread_name: &str,
+                        output_format: &OutputFormat) -> bool {
+        let (count, limit) = match self.policy.level_limits().iter().position(|(l, _)| *l == level) {
+            Some(i) => (&mut self.level_counts[i], self.policy.level_limits()[i].1),
+            None => (&mut self.record_count, self.policy.max_records())
+        };
+        if *count < limit {
+            *count += 1;
+            return true
+        }
+        if self.summary_origin.is_none() {
+            self.summary_origin = Some((thread_id, thread_name.to_string(),
+                                        output_format.clone()));
+        }
+        self.suppressed_count += 1;
+        false
+    }
+
+    /// Starts a new interval, if the current one has elapsed.
+    ///
+    /// # Arguments
+    /// * `now` - the current time
+    ///
+    /// # Return values
+    /// summary message, thread ID, thread name and output format for the summary record, if
+    /// records have been suppressed in the elapsed interval
+    pub(crate) fn end_interval_if_due(&mut self,
+                                      now: Instant) -> Option<(String, u64, String, OutputFormat)> {
+        if now.duration_since(self.interval_start) < self.interval { return None }
+        self.interval_start = now;
+        self.record_count = 0;
+        self.level_counts.iter_mut().for_each(|c| *c = 0);
+        self.take_summary()
+    }
+
+    /// Returns the data for the summary record, if records have been suppressed in the current
+    /// interval, and resets the suppression counter.
+    ///
+    /// # Return values
+    /// summary message, thread ID, thread name and output format for the summary record
+    pub(crate) fn take_summary(&mut self) -> Option<(String, u64, String, OutputFormat)> {
+        let (tid, tname, ofmt) = self.summary_origin.take()?;
+        let msg = coalyxw!(W_THROTTLE_SUPPRESSED, self.suppressed_count.to_string(),
+                           self.policy.name().to_string()).localized_message();
+        self.suppressed_count = 0;
+        Some((msg, tid, tname, ofmt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Configuration;
+    use crate::record::RecordLevelId;
+
+    #[test]
+    fn test_throttle() {
+        let pol = ThrottlePolicy::new("t", 2, 1, vec!((RecordLevelId::Error as u32, 1)));
+        let mut thr = Throttle::new(&pol);
+        let cfg = Configuration::default();
+        let ofmt = OutputFormat::from_desc(cfg.output_format(&None), cfg.date_time_formats(),
+                                           cfg.system_properties());
+        let info = RecordLevelId::Info as u32;
+        let error = RecordLevelId::Error as u32;
+        assert!(thr.admit(info, 1, "main", &ofmt));
+        assert!(thr.admit(error, 1, "main", &ofmt));
+        assert!(thr.admit(info, 1, "main", &ofmt));
+        assert!(! thr.admit(info, 2, "worker", &ofmt));
+        assert!(! thr.admit(error, 1, "main", &ofmt));
+        let start = thr.interval_start;
+        assert!(thr.end_interval_if_due(start).is_none());
+        let (msg, tid, tname, _) = thr.end_interval_if_due(start + Duration::from_secs(1)).unwrap();
+        assert!(msg.starts_with("2 "));
+        assert_eq!(2, tid);
+        assert_eq!("worker", tname);
+        assert!(thr.admit(error, 1, "main", &ofmt));
+        assert!(thr.take_summary().is_none());
+    }
+}
@@ -0,0 +1,285 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Output resources of type webhook.
+//! Every record written to the resource raises an alert, which is sent by HTTP POST to the
+//! configured URL. Records flushed from the resource's buffer are not sent on their own, they
+//! are kept as context and sent together with the next alert.
+//! Alerts are rate limited, records arriving within the configured minimum interval after an
+//! alert are discarded.
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use crate::coalyxe;
+use crate::config::resource::WebhookPayload;
+use crate::errorhandling::*;
+use crate::net::{parse_http_url, NetworkProtocol, PeerAddr};
+use crate::util::json_escaped_str;
+
+/// Specific data for physical resources of kind webhook.
+pub(crate) struct WebhookData {
+    // URL where to post the alerts to
+    url: String,
+    // host name or address, port and path parsed from URL
+    host: String,
+    port: u16,
+    path: String,
+    // payload format
+    payload: WebhookPayload,
+    // minimum interval between two alerts
+    min_interval: Duration,
+    // time of last alert sent
+    last_alert: Option<Instant>,
+    // recent records to be sent as context with the next alert
+    context: Vec<u8>
+}
+impl WebhookData {
+    /// Creates a webhook resource.
+    /// 
+    /// # Arguments
+    /// * `url` - the HTTP URL where to post the alerts to
+    /// * `payload` - the payload format
+    /// * `min_interval` - the minimum interval between two alerts, in seconds
+    /// 
+    /// # Errors
+    /// Returns an error structure if the URL is not a valid HTTP URL
+    pub(crate) fn new(url: &str,
+                      payload: WebhookPayload,
+                      min_interval: u64) -> Result<WebhookData, CoalyException> {
+        if let Some((host, port, path)) = parse_http_url(url) {
+            return Ok(WebhookData {
+                url: url.to_string(),
+                host,
+                port,
+                path,
+                payload,
+                min_interval: Duration::from_secs(min_interval),
+                last_alert: None,
+                context: Vec::new()
+            })
+        }
+        Err(coalyxe!(E_INVALID_URL, url.to_string()))
+    }
+
+    /// Returns the address of the web server.
+    #[inline]
+    pub(crate) fn peer_addr(&self) -> PeerAddr {
+        PeerAddr::HostSocket(NetworkProtocol::Tcp, self.host.clone(), self.port)
+    }
+
+    /// Stores the given records as context for the next alert.
+    /// Only the most recent records are kept, if the context grows too large.
+    /// 
+    /// # Arguments
+    /// * `data` - the formatted records
+    pub(crate) fn add_context(&mut self, data: &[u8]) {
+        self.context.extend_from_slice(data);
+        if self.context.len() <= MAX_CONTEXT_SIZE { return }
+        let mut excess = self.context.len() - MAX_CONTEXT_SIZE;
+        // drop partial record at the beginning
+        if let Some(pos) = self.context[excess..].iter().position(|b| *b == b'\n') {
+            excess += pos + 1;
+        }
+        self.context.drain(..excess);
+    }
+
+    /// Sends an alert for the given record together with the stored context.
+    /// The alert is discarded, if the last alert was sent within the minimum interval.
+    /// 
+    /// # Arguments
+    /// * `record` - the formatted record
+    /// 
+    /// # Errors
+    /// Returns an error structure if the alert could not be delivered
+    pub(crate) fn alert(&mut self, record: &str) -> Result<(), CoalyException> {
+        let context = String::from_utf8_lossy(&self.context).to_string();
+        self.context.clear();
+        if ! self.admit(Instant::now()) { return Ok(()) }
+        let body = payload_for(self.payload, record.trim_end(), context.trim_end());
+        self.post(&body)
+    }
+
+    /// Checks whether an alert may be sent at the given time, and if so, registers
+    /// the time as time of the last alert.
+    /// 
+    /// # Arguments
+    /// * `now` - the current time
+    /// 
+    /// # Return values
+    /// **true** if the alert may be sent
+    fn admit(&mut self, now: Instant) -> bool {
+        if let Some(t) = self.last_alert {
+            if now.saturating_duration_since(t) < self.min_interval { return false }
+        }
+        self.last_alert = Some(now);
+        true
+    }
+
+    /// Posts the given JSON data to the webhook.
+    /// 
+    /// # Arguments
+    /// * `body` - the JSON data
+    /// 
+    /// # Errors
+    /// Returns an error structure if the connection fails or the webhook doesn't respond
+    /// with a success status
+    fn post(&self, body: &str) -> Result<(), CoalyException> {
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+        let addr = (host, self.port).to_socket_addrs()
+                                    .ok()
+                                    .and_then(|mut a| a.next())
+                                    .ok_or_else(|| coalyxe!(E_INVALID_URL, self.url.to_string()))?;
+        let mut stream = TcpStream::connect_timeout(&addr, WEBHOOK_TIMEOUT)
+                         .map_err(|e| coalyxe!(E_SOCKET_CRE_ERR, self.url.clone(), e.to_string()))?;
+        let _ = stream.set_read_timeout(Some(WEBHOOK_TIMEOUT));
+        let _ = stream.set_write_timeout(Some(WEBHOOK_TIMEOUT));
+        let request = format!("POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+                               Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                              self.path, self.host, body.len(), body);
+        let local_addr = stream.local_addr().map_or(String::from("-"), |a| a.to_string());
+        if let Err(e) = stream.write_all(request.as_bytes()) {
+            return Err(coalyxe!(E_SOCKET_WRITE_ERR, local_addr, self.url.clone(), e.to_string()))
+        }
+        let mut response = [0u8; 64];
+        let n = stream.read(&mut response)
+                      .map_err(|e| coalyxe!(E_SOCKET_READ_ERR, local_addr, self.url.clone(),
+                                            e.to_string()))?;
+        let status_line = String::from_utf8_lossy(&response[..n]);
+        let status_line = status_line.lines().next().unwrap_or("");
+        match status_line.split_whitespace().nth(1) {
+            Some(status) if status.starts_with('2') => Ok(()),
+            _ => Err(coalyxe!(E_WEBHOOK_REJECTED, self.url.clone(), status_line.to_string()))
+        }
+    }
+}
+
+/// Returns the JSON payload for an alert.
+/// 
+/// # Arguments
+/// * `payload` - the payload format
+/// * `record` - the formatted record
+/// * `context` - the formatted context records, may be empty
+fn payload_for(payload: WebhookPayload, record: &str, context: &str) -> String {
+    match payload {
+        WebhookPayload::Generic => {
+            format!("{{\"record\":\"{}\",\"context\":\"{}\"}}",
+                    json_escaped_str(record), json_escaped_str(context))
+        },
+        WebhookPayload::Slack => {
+            if context.is_empty() {
+                return format!("{{\"text\":\"{}\"}}", json_escaped_str(record))
+            }
+            let text = format!("{}\n```\n{}\n```", record, context);
+            format!("{{\"text\":\"{}\"}}", json_escaped_str(&text))
+        }
+    }
+}
+
+// maximum number of bytes kept as context for an alert
+const MAX_CONTEXT_SIZE: usize = 16384;
+
+// timeout for connect, send and receive operations
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Starts a web server accepting a single request and responding with the given status.
+    /// Returns the URL of the server and a handle returning the request body received.
+    fn web_server(status: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::<u8>::new();
+            let mut buf = [0u8; 1024];
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let req = String::from_utf8_lossy(&request).to_string();
+                if let Some(pos) = req.find("\r\n\r\n") {
+                    let len_line = req.lines().find(|l| l.starts_with("Content-Length:")).unwrap();
+                    let len: usize = len_line[15..].trim().parse().unwrap();
+                    if request.len() >= pos + 4 + len {
+                        stream.write_all(format!("HTTP/1.1 {}\r\n\r\n", status).as_bytes()).unwrap();
+                        return req[pos + 4..].to_string()
+                    }
+                }
+            }
+        });
+        (url, handle)
+    }
+
+    #[test]
+    /// Tests JSON payloads
+    fn test_payload_for() {
+        assert_eq!(r#"{"record":"disk \"a\" full","context":""}"#,
+                   payload_for(WebhookPayload::Generic, r#"disk "a" full"#, ""));
+        assert_eq!(r#"{"record":"disk full","context":"r1\nr2"}"#,
+                   payload_for(WebhookPayload::Generic, "disk full", "r1\nr2"));
+        assert_eq!(r#"{"text":"disk full"}"#,
+                   payload_for(WebhookPayload::Slack, "disk full", ""));
+        assert_eq!(r#"{"text":"disk full\n```\nr1\n```"}"#,
+                   payload_for(WebhookPayload::Slack, "disk full", "r1"));
+    }
+
+    #[test]
+    /// Tests limitation of alert context
+    fn test_add_context() {
+        let mut hook = WebhookData::new("http://127.0.0.1/hook", WebhookPayload::Generic, 0).unwrap();
+        let rec = format!("{}\n", "x".repeat(99));
+        for _ in 0 .. 200 { hook.add_context(rec.as_bytes()); }
+        assert!(hook.context.len() <= MAX_CONTEXT_SIZE);
+        assert_eq!(0, hook.context.len() % 100);
+        assert_eq!(b'x', hook.context[0]);
+    }
+
+    #[test]
+    /// Tests delivery and rate limiting of alerts
+    fn test_alert() {
+        let (url, server) = web_server("200 OK");
+        let mut hook = WebhookData::new(&url, WebhookPayload::Generic, 3600).unwrap();
+        hook.add_context(b"context\n");
+        assert!(hook.alert("alert 1\n").is_ok());
+        assert_eq!(r#"{"record":"alert 1","context":"context"}"#, server.join().unwrap());
+        // within minimum interval, alert is discarded without connecting to server
+        assert!(hook.alert("alert 2\n").is_ok());
+        let (url, server) = web_server("500 Internal Server Error");
+        let mut hook = WebhookData::new(&url, WebhookPayload::Slack, 0).unwrap();
+        assert!(hook.alert("alert 3\n").is_err());
+        assert_eq!(r#"{"text":"alert 3"}"#, server.join().unwrap());
+    }
+}
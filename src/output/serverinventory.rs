@@ -33,42 +33,37 @@
 //! Resource inventory for handling of all output resources for a trace server.
 
 use chrono::{DateTime, Local};
-use std::cell::RefCell;
-use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::Path;
 use std::rc::Rc;
+use std::time::Instant;
 use crate::config::Configuration;
+use crate::context::ContextMap;
 use crate::errorhandling::{CoalyException, log_problems};
 use crate::record::originator::OriginatorInfo;
 use super::Interface;
-use super::formatspec::FormatSpec;
+use super::forecast::RolloverForecast;
 use super::inventory::Inventory;
-use super::outputformat::OutputFormat;
-use super::resource::{Resource, ResourceRef};
+use super::resource::{cleanup_stale_files, close_resources, set_open_file_limit};
+use super::storage::{forwarding_storages, FileStorage, RecordStorage};
 
 
 /// Manages all output resources for a trace server.
-/// Output resources may be either "final" (then associated with a physical resource) or
-/// "generic" (file based resources where the name specification contains variables like
-/// $ThreadId or $ProcessName).
+/// Records issued by the server itself are always written to the output resources from the
+/// configuration file, records received from clients are passed to the storages selected in
+/// the server properties.
 pub(crate) struct ServerInventory {
-    // all final output resources, needed to serve timer events and system cleanup
-    all_resources: Vec<ResourceRef>,
-    // interface template containing all resources from configuration, not optimized
-    global_template: Vec<ResourceRef>,
-    // interface template containing all resources from configuration, optimized for application
-    // and originator. May hold thread-specific generic resources.
-    local_template: Vec<ResourceRef>,
-    // interface templates containing all resources for a specific originator.
-    // May hold thread-specific generic resources.
-    originator_templates: HashMap<SocketAddr, (OriginatorInfo, Vec<ResourceRef>)>,
-    // all currently allocated final originator- and/or thread-specific resources.
-    specific_resources: HashMap<FormatSpec, ResourceRef>,
+    // output resources from configuration file
+    file_storage: FileStorage,
+    // indicates whether records from clients shall be written to the file storage
+    use_file_storage: bool,
+    // storages forwarding records from clients to other servers
+    forwarders: Vec<Box<dyn RecordStorage>>,
     // originator information for local application
     local_app_data: OriginatorInfo
 }
 impl ServerInventory {
-    /// Creates the inventory for a standalone application from the specifications
+    /// Creates the inventory for a trace server from the specifications
     /// in the configuration file.
     ///
     /// # Arguments
@@ -76,55 +71,42 @@ impl ServerInventory {
     /// * `orig_info` - information about application process and local host
     pub(crate) fn new(config: &Rc<Configuration>,
                       orig_info: &OriginatorInfo) -> Box<ServerInventory> {
-        let mut problems = Vec::<CoalyException>::new();
-        let mut all_resources = Vec::<ResourceRef>::new();
-        let mut global_template = Vec::<ResourceRef>::new();
-        let mut local_template = Vec::<ResourceRef>::new();
-        let mut specific_resources = HashMap::<FormatSpec, ResourceRef>::new();
-        for rdesc in config.resources().elements() {
-            match Resource::from_config(rdesc, config, orig_info) {
-                Ok(res) => {
-                    let orig_spec_flag = res.is_originator_specific();
-                    let thread_spec_flag = res.is_thread_specific();
-                    // add unchanged resource to global template
-                    let res_ref = Rc::new(RefCell::new(res));
-                    global_template.push(res_ref.clone());
-                    if orig_spec_flag {
-                        // create originator optimized resource for local template
-                        let opt_name = res_ref.borrow().originator_optimized_name(orig_info).unwrap();
-                        let opt_res = res_ref.borrow().for_originator(opt_name.clone()).unwrap();
-                        let opt_res_ref = Rc::new(RefCell::new(opt_res));
-                        if ! thread_spec_flag {
-                            // originator-specific only
-                            specific_resources.insert(opt_name, opt_res_ref.clone());
-                            all_resources.push(opt_res_ref.clone());
-                        }
-                        local_template.push(opt_res_ref);
-                    } else {
-                        // not originator-specific
-                        if ! thread_spec_flag { all_resources.push(res_ref.clone()); }
-                        local_template.push(res_ref);
-                    }
-                },
-                Err(ex) => problems.push(ex)
-            }
-        }
+        set_open_file_limit(config.system_properties().max_open_files());
+        let mut problems = cleanup_stale_files(config);
+        let file_storage = FileStorage::new(config, orig_info, &mut problems);
+        let (forwarders, use_file_storage) = match config.server_properties() {
+            Some(srv_props) => forwarding_storages(srv_props.storages(), config),
+            None => (Vec::new(), true)
+        };
         if ! problems.is_empty() { log_problems(&problems); }
         Box::new(ServerInventory {
-                     all_resources,
-                     global_template,
-                     local_template,
-                     originator_templates: HashMap::new(),
-                     specific_resources,
+                     file_storage,
+                     use_file_storage,
+                     forwarders,
                      local_app_data: orig_info.clone()
                 })
     }
+
+    /// Returns all storages for records received from clients.
+    fn client_storages(&mut self) -> Vec<&mut dyn RecordStorage> {
+        let mut storages = Vec::<&mut dyn RecordStorage>::new();
+        if self.use_file_storage { storages.push(&mut self.file_storage); }
+        for fwd in self.forwarders.iter_mut() { storages.push(fwd.as_mut()); }
+        storages
+    }
 }
 impl Inventory for ServerInventory {
     /// Closes the inventory.
-    /// Flushes all buffer configured for flush on exit.
-    fn close(&mut self) {
-        self.all_resources.iter_mut().for_each(|x| Resource::close(&mut x.borrow_mut()));
+    /// Flushes all buffer configured for flush on exit, resources with higher flush priority
+    /// first, regardless of the storage they belong to.
+    /// 
+    /// # Arguments
+    /// * `shutdown_deadline` - the point in time when the shutdown must be completed,
+    ///   **None** if unlimited
+    fn close(&mut self, shutdown_deadline: Option<Instant>) {
+        let mut resources = self.file_storage.take_resources();
+        self.forwarders.iter_mut().for_each(|fwd| resources.append(&mut fwd.take_resources()));
+        close_resources(&resources, shutdown_deadline);
     }
 
     /// Performs a rollover for file based resources if rollover is due.
@@ -133,49 +115,61 @@ impl Inventory for ServerInventory {
     /// * `now` - current timestamp
     fn rollover_if_due(&mut self, now: &DateTime<Local>) {
         let mut problems = Vec::<CoalyException>::new();
-        for res in self.all_resources.iter_mut() {
-            if let Err(ex) = res.borrow_mut().rollover_if_due(now) {
-                problems.push(ex);
-            }
-        }
+        self.file_storage.rollover_if_due(now, &mut problems);
+        self.forwarders.iter_mut().for_each(|fwd| fwd.rollover_if_due(now, &mut problems));
+        if ! problems.is_empty() { log_problems(&problems); }
+    }
+
+    /// Handles a flush or rollover request received by signal.
+    ///
+    /// # Arguments
+    /// * `rollover` - indicates whether a file rollover was requested
+    fn handle_signal(&mut self, rollover: bool) {
+        let mut problems = Vec::<CoalyException>::new();
+        self.file_storage.handle_signal(rollover, &mut problems);
+        self.forwarders.iter_mut().for_each(|fwd| fwd.handle_signal(rollover, &mut problems));
         if ! problems.is_empty() { log_problems(&problems); }
     }
 
+    /// Returns the forecasts for the next rollover of all file based resources.
+    /// Resources forwarding records to another server are not considered.
+    fn rollover_forecast(&self) -> Vec<RolloverForecast> {
+        self.file_storage.rollover_forecast()
+    }
+
+    /// Opens the file handles and network sockets of all resources anew.
+    fn reopen_resources(&mut self) -> Vec<CoalyException> {
+        let mut problems = Vec::<CoalyException>::new();
+        self.file_storage.reopen_resources(&mut problems);
+        self.forwarders.iter_mut().for_each(|fwd| fwd.reopen_resources(&mut problems));
+        problems
+    }
+
+    /// Changes the output directory for all files created from now on.
+    /// Resources forwarding records to another server are not affected.
+    fn change_output_dir(&mut self, dir: &Path) {
+        self.file_storage.change_output_dir(dir);
+    }
+
+    /// Flushes and closes all final thread-specific resources, that are no longer used by any
+    /// thread's output interface.
+    /// Resources of the server's own threads are retained until shutdown.
+    fn release_thread_resources(&mut self) {}
+
     /// Creates and returns the output interface for a local thread.
     ///
     /// # Arguments
     /// * `thread_id` - the thread's ID
     /// * `thread_name` - the thread's name
+    /// * `context` - the thread's context map
     fn local_thread_interface(&mut self,
                               thread_id: u64,
-                              thread_name: &str) -> Interface {
+                              thread_name: &str,
+                              context: Option<&ContextMap>) -> Interface {
         let mut problems = Vec::<CoalyException>::new();
-        let mut output_resources = Vec::<(OutputFormat, ResourceRef)>::new();
-        for res in &self.local_template {
-            let ofmt = res.borrow().optimized_output_format(&self.local_app_data,
-                                                            thread_id, thread_name);
-            if res.borrow().is_thread_specific() {
-                // check whether matching resource exists
-                let res_name = res.borrow().thread_optimized_name(thread_id, thread_name).unwrap();
-                if self.specific_resources.contains_key(&res_name) {
-                    output_resources.push((ofmt, res.clone()));
-                } else {
-                    // instantiate template for the thread
-                    match res.borrow().for_thread(res_name.clone()) {
-                        Ok(spec_res) => {
-                            let spec_res = Rc::new(RefCell::new(spec_res));
-                            output_resources.push((ofmt.clone(), spec_res.clone()));
-                            self.specific_resources.insert(res_name, spec_res.clone());
-                            self.all_resources.push(spec_res);
-                        },
-                        Err(ex) => problems.push(ex)
-                    }
-                }
-            } else {
-                // process wide resources can be used unchanged
-                output_resources.push((ofmt, res.clone()));
-            }
-        }
+        let output_resources = self.file_storage.local_thread_resources(&self.local_app_data,
+                                                                        thread_id, thread_name,
+                                                                        context, &mut problems);
         if ! problems.is_empty() { log_problems(&problems); }
         Interface::new(output_resources)
     }
@@ -191,43 +185,17 @@ impl Inventory for ServerInventory {
                                thread_id: u64,
                                thread_name: &str) -> Interface {
         let mut problems = Vec::<CoalyException>::new();
-        let mut output_resources = Vec::<(OutputFormat, ResourceRef)>::new();
-        if let Some((orig_info, resources)) = self.originator_templates.get(remote_addr) {
-            for res_ref in resources {
-                let ofmt = res_ref.borrow().optimized_output_format(&orig_info,
-                                                                    thread_id, thread_name);
-                if res_ref.borrow().is_thread_specific() {
-                    // check whether matching resource exists
-                    let res_name = res_ref.borrow()
-                                          .thread_optimized_name(thread_id, thread_name)
-                                          .unwrap();
-                    if let Some(spec_res_ref) = self.specific_resources.get(&res_name) {
-                        output_resources.push((ofmt, spec_res_ref.clone()));
-                    } else {
-                        // instantiate template for the thread
-                        match res_ref.borrow().for_thread(res_name.clone()) {
-                            Ok(spec_res) => {
-                                let spec_res = Rc::new(RefCell::new(spec_res));
-                                output_resources.push((ofmt.clone(), spec_res.clone()));
-                                self.specific_resources.insert(res_name, spec_res.clone());
-                                self.all_resources.push(spec_res);
-                            },
-                            Err(ex) => problems.push(ex)
-                        }
-                    }
-                } else {
-                    // originator wide resources can be used unchanged
-                    output_resources.push((ofmt, res_ref.clone()));
-                }
-            }
+        let mut output_resources = Vec::new();
+        for storage in self.client_storages() {
+            storage.remote_thread_resources(remote_addr, thread_id, thread_name,
+                                            &mut output_resources, &mut problems);
         }
         if ! problems.is_empty() { log_problems(&problems); }
         Interface::new(output_resources)
     }
 
     /// Updates the inventory when a remote client connects.
-    /// Prepares an interface template for the remote client.
-    /// Creates thread-independent resources specific for the remote client.
+    /// Prepares the storages for records from the remote client.
     /// 
     /// # Arguments
     /// * `remote_addr` - the client's socket address
@@ -235,42 +203,20 @@ impl Inventory for ServerInventory {
     fn add_remote_client(&mut self,
                          remote_addr: &SocketAddr,
                          orig_info: OriginatorInfo) {
-        let mut orig_resources = Vec::<ResourceRef>::new();
-        for res_ref in &self.global_template {
-            let orig_spec_flag = res_ref.borrow().is_originator_specific();
-            let thread_spec_flag = res_ref.borrow().is_thread_specific();
-            if orig_spec_flag {
-                let opt_name = res_ref.borrow().originator_optimized_name(&orig_info).unwrap();
-                if let Some(res) = self.specific_resources.get(&opt_name) {
-                    // originator optimized resource already exists, use it
-                    orig_resources.push(res.clone());
-                    continue;
-                }
-                // create originator optimized resource
-                let opt_res = res_ref.borrow().for_originator(opt_name.clone()).unwrap();
-                let opt_res_ref = Rc::new(RefCell::new(opt_res));
-                if ! thread_spec_flag {
-                    self.specific_resources.insert(opt_name, opt_res_ref.clone());
-                    self.all_resources.push(opt_res_ref.clone());
-                }
-                orig_resources.push(opt_res_ref);
-            } else {
-                // not originator-specific, can be directly used and has already been stored
-                // in all resources during inventory construction
-                orig_resources.push(res_ref.clone());
-            }
+        let mut problems = Vec::<CoalyException>::new();
+        for storage in self.client_storages() {
+            storage.add_remote_client(remote_addr, &orig_info, &mut problems);
         }
-        self.originator_templates.insert(remote_addr.clone(), (orig_info, orig_resources));
+        if ! problems.is_empty() { log_problems(&problems); }
     }
 
     /// Updates the inventory when a remote client disconnects.
-    /// Removes all interface templates specific for the remote client from internal lists.
-    /// Closes all resources specific for the remote client.
+    /// Removes all data specific for the remote client from the storages.
     /// 
     /// # Arguments
     /// * `remote_addr` - the client's socket address
     fn remove_remote_client(&mut self,
                             remote_addr: &SocketAddr) {
-        self.originator_templates.remove(remote_addr);
+        for storage in self.client_storages() { storage.remove_remote_client(remote_addr); }
     }
 }
@@ -34,16 +34,24 @@
 
 use chrono::{DateTime, Local};
 use std::collections::HashMap;
+use std::path::Path;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::time::Instant;
 use crate::config::Configuration;
+use crate::config::resource::CreateErrorPolicy;
+use crate::context::ContextMap;
 use crate::errorhandling::{CoalyException, log_problems};
 use crate::record::originator::OriginatorInfo;
 use super::Interface;
+use super::forecast::RolloverForecast;
 use super::formatspec::FormatSpec;
 use super::inventory::Inventory;
 use super::outputformat::OutputFormat;
-use super::resource::{Resource, ResourceRef};
+use super::probe::{self, ProbeResult};
+use super::recordbuffer::set_buffer_memory_limit;
+use super::resource::{cleanup_stale_files, close_resources, set_open_file_limit, Resource,
+                      ResourceRef};
 
 #[cfg(feature="net")]
 use std::net::SocketAddr;
@@ -61,7 +69,10 @@ pub(crate) struct StandaloneInventory {
     // all currently allocated final thread-specific resources.
     final_thread_resources: HashMap<FormatSpec, ResourceRef>,
     // originator information for local application
-    local_app_data: OriginatorInfo
+    local_app_data: OriginatorInfo,
+    // error of the first file resource that couldn't be created upon startup and is configured
+    // to fail initialization in this case
+    startup_failure: Option<CoalyException>
 }
 impl StandaloneInventory {
     /// Creates the inventory for a standalone application from the specifications
@@ -72,9 +83,13 @@ impl StandaloneInventory {
     /// * `orig_info` - information about application process and local host
     pub(crate) fn new(config: &Rc<Configuration>,
                       orig_info: &OriginatorInfo) -> Box<StandaloneInventory> {
-        let mut problems = Vec::<CoalyException>::new();
+        set_buffer_memory_limit(config.system_properties().buffer_memory_limit());
+        set_open_file_limit(config.system_properties().max_open_files());
+        let mut problems = cleanup_stale_files(config);
         let mut all_resources = Vec::<ResourceRef>::new();
         let mut local_template = Vec::<ResourceRef>::new();
+        let mut startup_failure = None;
+        let mut probe_results = Vec::<ProbeResult>::new();
         for rdesc in config.resources().elements() {
             #[cfg(not(feature="net"))]
             let r = Resource::from_config(rdesc, config);
@@ -86,29 +101,76 @@ impl StandaloneInventory {
                         let opt_name = res.originator_optimized_name(orig_info).unwrap();
                         res.use_optimized_name(opt_name);
                     }
+                    if ! res.is_thread_specific() {
+                        match res.create_at_startup(rdesc, config.system_properties()) {
+                            Ok(Some(w)) => problems.push(w),
+                            Ok(None) => (),
+                            Err(ex) => {
+                                if rdesc.probe() {
+                                    probe_results.push(ProbeResult::failed(&rdesc.label(), None,
+                                                                           ex.clone()));
+                                }
+                                let fail_init = rdesc.file_data().is_some_and(|fd| {
+                                    fd.create_error_policy() == CreateErrorPolicy::Fail
+                                });
+                                if fail_init && startup_failure.is_none() {
+                                    startup_failure = Some(ex);
+                                } else {
+                                    problems.push(ex);
+                                }
+                                continue
+                            }
+                        }
+                    }
+                    if rdesc.probe() {
+                        let result = res.probe(&rdesc.label());
+                        if let Some(ex) = result.problem() { problems.push(ex.clone()); }
+                        probe_results.push(result);
+                    }
                     let res_ref = Rc::new(RefCell::new(res));
                     if ! res_ref.borrow().is_thread_specific() {
                         all_resources.push(res_ref.clone());
                     }
                     local_template.push(res_ref);
                 },
-                Err(ex) => problems.push(ex)
+                Err(ex) => {
+                    if rdesc.probe() {
+                        probe_results.push(ProbeResult::failed(&rdesc.label(), None, ex.clone()));
+                    }
+                    problems.push(ex)
+                }
             }
         }
+        probe::publish(probe_results);
         if ! problems.is_empty() { log_problems(&problems); }
         Box::new(StandaloneInventory {
                      all_resources,
                      local_template,
                      final_thread_resources: HashMap::new(),
-                     local_app_data: orig_info.clone()
+                     local_app_data: orig_info.clone(),
+                     startup_failure
                 })
     }
+
+    /// Returns the error of the first file resource that couldn't be created upon startup and
+    /// is configured to fail initialization in this case.
+    ///
+    /// # Return values
+    /// the error structure; **None** if initialization shall not fail
+    pub(crate) fn take_startup_failure(&mut self) -> Option<CoalyException> {
+        self.startup_failure.take()
+    }
 }
 impl Inventory for StandaloneInventory {
     /// Closes the inventory.
-    /// Flushes all buffer configured for flush on exit.
-    fn close(&mut self) {
-        self.all_resources.iter_mut().for_each(|x| Resource::close(&mut x.borrow_mut()));
+    /// Flushes all buffer configured for flush on exit, resources with higher flush priority
+    /// first.
+    /// 
+    /// # Arguments
+    /// * `shutdown_deadline` - the point in time when the shutdown must be completed,
+    ///   **None** if unlimited
+    fn close(&mut self, shutdown_deadline: Option<Instant>) {
+        close_resources(&self.all_resources, shutdown_deadline);
     }
 
     /// Performs a rollover for file based resources if rollover is due.
@@ -125,15 +187,70 @@ impl Inventory for StandaloneInventory {
         if ! problems.is_empty() { log_problems(&problems); }
     }
 
+    /// Handles a flush or rollover request received by signal.
+    ///
+    /// # Arguments
+    /// * `rollover` - indicates whether a file rollover was requested
+    fn handle_signal(&mut self, rollover: bool) {
+        let mut problems = Vec::<CoalyException>::new();
+        for res in self.all_resources.iter_mut() {
+            if let Err(mut exs) = res.borrow_mut().handle_signal(rollover) {
+                problems.append(&mut exs);
+            }
+        }
+        if ! problems.is_empty() { log_problems(&problems); }
+    }
+
+    /// Returns the forecasts for the next rollover of all file based resources.
+    fn rollover_forecast(&self) -> Vec<RolloverForecast> {
+        self.all_resources.iter().filter_map(|r| r.borrow().rollover_forecast()).collect()
+    }
+
+    /// Opens the file handles and network sockets of all resources anew.
+    fn reopen_resources(&mut self) -> Vec<CoalyException> {
+        let mut problems = Vec::<CoalyException>::new();
+        for res in self.all_resources.iter_mut() {
+            if let Err(mut exs) = res.borrow_mut().reopen() {
+                problems.append(&mut exs);
+            }
+        }
+        problems
+    }
+
+    /// Changes the output directory for all files created from now on.
+    fn change_output_dir(&mut self, dir: &Path) {
+        self.all_resources.iter().chain(self.local_template.iter())
+                          .for_each(|r| r.borrow_mut().change_output_dir(dir));
+    }
+
+    /// Flushes and closes all final thread-specific resources, that are no longer used by any
+    /// thread's output interface.
+    /// Such resources are referenced by the inventory only, i.e. from the list of all resources
+    /// and from the thread-specific resource map.
+    fn release_thread_resources(&mut self) {
+        let unused: Vec<FormatSpec> = self.final_thread_resources.iter()
+                                          .filter(|(_, r)| Rc::strong_count(r) <= 2)
+                                          .map(|(name, _)| name.clone())
+                                          .collect();
+        for name in unused {
+            if let Some(res) = self.final_thread_resources.remove(&name) {
+                self.all_resources.retain(|r| ! Rc::ptr_eq(r, &res));
+                res.borrow_mut().close(None);
+            }
+        }
+    }
+
     /// Creates and returns the output interface for a local thread.
     /// The caller must make sure that resources for the thread have not been allocated yet.
     ///
     /// # Arguments
     /// * `thread_id` - the thread's ID
     /// * `thread_name` - the thread's name
+    /// * `context` - the thread's context map
     fn local_thread_interface(&mut self,
                               thread_id: u64,
-                              thread_name: &str) -> Interface {
+                              thread_name: &str,
+                              context: Option<&ContextMap>) -> Interface {
         let mut problems = Vec::<CoalyException>::new();
         let mut output_resources = Vec::<(OutputFormat, ResourceRef)>::new();
         for res in &self.local_template {
@@ -141,7 +258,8 @@ impl Inventory for StandaloneInventory {
                                                             thread_id, thread_name);
             if res.borrow().is_thread_specific() {
                 // check whether matching resource exists
-                let res_name = res.borrow().thread_optimized_name(thread_id, thread_name).unwrap();
+                let res_name = res.borrow().thread_optimized_name(thread_id, thread_name,
+                                                                  context).unwrap();
                 if self.final_thread_resources.contains_key(&res_name) {
                     output_resources.push((ofmt, res.clone()));
                 } else {
@@ -176,7 +294,8 @@ impl Inventory for StandaloneInventory {
                                _remote_addr: &SocketAddr,
                                thread_id: u64,
                                thread_name: &str) -> Interface {
-        self.local_thread_interface(thread_id, thread_name)
+        // context maps are not transferred from remote clients
+        self.local_thread_interface(thread_id, thread_name, None)
     }
 
     /// Updates the inventory when a remote client connects.
@@ -201,3 +320,46 @@ impl Inventory for StandaloneInventory {
     fn remove_remote_client(&mut self,
                             _remote_addr: &SocketAddr) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::config;
+
+    #[test]
+    /// Tests that thread-specific resources are released, when no thread uses them anymore
+    fn test_release_thread_resources() {
+        let dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap())
+                      .join("tmp").join("output").join("standaloneinventory")
+                      .join("test_release_thread_resources");
+        std::fs::create_dir_all(&dir).unwrap();
+        let cfg_file = dir.join("release.toml");
+        std::fs::write(&cfg_file, format!("[system]\noutput_path = \"{}\"\n\
+                                           [[resources]]\nkind = \"file\"\nname = \"all.log\"\n\
+                                           levels = [ \"all\" ]\n\
+                                           [[resources]]\nkind = \"file\"\n\
+                                           name = \"thread_$ThreadId.log\"\n\
+                                           levels = [ \"all\" ]\n",
+                                          dir.to_string_lossy())).unwrap();
+        let local_info = OriginatorInfo::new(1, "app", "localhost", "127.0.0.1");
+        let cfg = config::configuration(&local_info, Some(&cfg_file.to_string_lossy()));
+        let mut inv = StandaloneInventory::new(&cfg, &local_info);
+        let intf1 = inv.local_thread_interface(1, "one", None);
+        let intf2 = inv.local_thread_interface(2, "two", None);
+        assert_eq!(3, inv.all_resources.len());
+        assert_eq!(2, inv.final_thread_resources.len());
+        inv.release_thread_resources();
+        assert_eq!(2, inv.final_thread_resources.len());
+        drop(intf1);
+        inv.release_thread_resources();
+        assert_eq!(2, inv.all_resources.len());
+        assert_eq!(1, inv.final_thread_resources.len());
+        drop(intf2);
+        inv.release_thread_resources();
+        assert_eq!(1, inv.all_resources.len());
+        assert!(inv.final_thread_resources.is_empty());
+        inv.close(None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,531 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Storages for log and trace records received by a trace server.
+//!
+//! Records received from clients may be written to the output resources from the configuration
+//! file (file storage), or forwarded to a syslog server or another Coaly log server. The latter
+//! allows a server to act as relay in front of a central log server.
+//! Every storage implements trait [`RecordStorage`], the storages to use are selected by the
+//! server properties in the configuration file.
+
+use chrono::{DateTime, Local};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::rc::Rc;
+use crate::config::Configuration;
+use crate::config::resource::{scope_contains, ResourceDesc};
+use crate::context::ContextMap;
+use crate::errorhandling::CoalyException;
+use crate::net::serverproperties::StorageDesc;
+use crate::record::originator::OriginatorInfo;
+use super::forecast::RolloverForecast;
+use super::formatspec::FormatSpec;
+use super::outputformat::OutputFormat;
+use super::resource::{Resource, ResourceRef};
+
+/// Storage for log and trace records received from remote clients.
+pub(crate) trait RecordStorage {
+    /// Removes all resources from the storage, used to close them in the order of their flush
+    /// priority.
+    /// 
+    /// # Return values
+    /// all resources of the storage
+    fn take_resources(&mut self) -> Vec<ResourceRef>;
+
+    /// Performs a rollover for file based resources if rollover is due.
+    /// 
+    /// # Arguments
+    /// * `now` - current timestamp
+    /// * `problems` - the array, where errors shall be stored
+    fn rollover_if_due(&mut self,
+                       now: &DateTime<Local>,
+                       problems: &mut Vec<CoalyException>);
+
+    /// Handles a flush or rollover request received by signal.
+    /// 
+    /// # Arguments
+    /// * `rollover` - indicates whether a file rollover was requested
+    /// * `problems` - the array, where errors shall be stored
+    fn handle_signal(&mut self,
+                     rollover: bool,
+                     problems: &mut Vec<CoalyException>);
+
+    /// Returns the forecasts for the next rollover of all file based resources.
+    fn rollover_forecast(&self) -> Vec<RolloverForecast>;
+
+    /// Opens the file handles and network sockets of all resources anew.
+    ///
+    /// # Arguments
+    /// * `problems` - the array, where errors shall be stored
+    fn reopen_resources(&mut self, problems: &mut Vec<CoalyException>);
+
+    /// Prepares the storage for records from a newly connected remote client.
+    /// 
+    /// # Arguments
+    /// * `remote_addr` - the client's socket address
+    /// * `orig_info` - information about the remote client
+    /// * `problems` - the array, where errors shall be stored
+    fn add_remote_client(&mut self,
+                         remote_addr: &SocketAddr,
+                         orig_info: &OriginatorInfo,
+                         problems: &mut Vec<CoalyException>);
+
+    /// Releases all data held by the storage for a remote client.
+    /// 
+    /// # Arguments
+    /// * `remote_addr` - the client's socket address
+    fn remove_remote_client(&mut self,
+                            remote_addr: &SocketAddr);
+
+    /// Adds the output resources for a remote thread.
+    /// 
+    /// # Arguments
+    /// * `remote_addr` - the client's socket address
+    /// * `thread_id` - the thread's ID
+    /// * `thread_name` - the thread's name
+    /// * `output_resources` - the array, where the output resources shall be added
+    /// * `problems` - the array, where errors shall be stored
+    fn remote_thread_resources(&mut self,
+                               remote_addr: &SocketAddr,
+                               thread_id: u64,
+                               thread_name: &str,
+                               output_resources: &mut Vec<(OutputFormat, ResourceRef)>,
+                               problems: &mut Vec<CoalyException>);
+}
+
+/// Creates the forwarding storages specified in the server properties.
+/// 
+/// # Arguments
+/// * `storages` - the storage descriptors from the server properties
+/// * `config` - the configuration
+/// 
+/// # Return values
+/// the forwarding storages; **true** if file storage is selected, too
+pub(crate) fn forwarding_storages(storages: &[StorageDesc],
+                                  config: &Rc<Configuration>) -> (Vec<Box<dyn RecordStorage>>,
+                                                                  bool) {
+    let mut forwarders = Vec::<Box<dyn RecordStorage>>::new();
+    let mut use_files = false;
+    for sdesc in storages {
+        match sdesc {
+            StorageDesc::File => use_files = true,
+            StorageDesc::Syslog(rdesc) | StorageDesc::Relay(rdesc) => {
+                forwarders.push(Box::new(ForwardingStorage::new(rdesc, config)));
+            }
+        }
+    }
+    (forwarders, use_files)
+}
+
+/// Storage writing records to the output resources specified in the configuration file.
+/// Also holds the resources for the records issued by the server process itself.
+pub(crate) struct FileStorage {
+    // all final output resources, needed to serve timer events and system cleanup
+    all_resources: Vec<ResourceRef>,
+    // interface template containing all resources from configuration, not optimized,
+    // together with the application IDs handled by the resource
+    global_template: Vec<(Vec<u32>, ResourceRef)>,
+    // interface template containing all resources from configuration, optimized for application
+    // and originator. May hold thread-specific generic resources.
+    local_template: Vec<ResourceRef>,
+    // interface templates containing all resources for a specific originator.
+    // May hold thread-specific generic resources.
+    originator_templates: HashMap<SocketAddr, (OriginatorInfo, Vec<ResourceRef>)>,
+    // all currently allocated final originator- and/or thread-specific resources.
+    specific_resources: HashMap<FormatSpec, ResourceRef>
+}
+impl FileStorage {
+    /// Creates the file storage from the resource specifications in the configuration file.
+    ///
+    /// # Arguments
+    /// * `config` - the configuration, either from configuration file
+    /// * `orig_info` - information about application process and local host
+    /// * `problems` - the array, where errors shall be stored
+    pub(crate) fn new(config: &Rc<Configuration>,
+                      orig_info: &OriginatorInfo,
+                      problems: &mut Vec<CoalyException>) -> FileStorage {
+        let mut all_resources = Vec::<ResourceRef>::new();
+        let mut global_template = Vec::<(Vec<u32>, ResourceRef)>::new();
+        let mut local_template = Vec::<ResourceRef>::new();
+        let mut specific_resources = HashMap::<FormatSpec, ResourceRef>::new();
+        for rdesc in config.resources().elements() {
+            match Resource::from_config(rdesc, config, orig_info) {
+                Ok(mut res) => {
+                    let orig_spec_flag = res.is_originator_specific();
+                    let thread_spec_flag = res.is_thread_specific();
+                    if ! orig_spec_flag && ! thread_spec_flag {
+                        // a server can't fail initialization, all creation errors are reported
+                        match res.create_at_startup(rdesc, config.system_properties()) {
+                            Ok(Some(ex)) | Err(ex) => problems.push(ex),
+                            Ok(None) => ()
+                        }
+                    }
+                    // add unchanged resource to global template
+                    let res_ref = Rc::new(RefCell::new(res));
+                    global_template.push((rdesc.scope().to_vec(), res_ref.clone()));
+                    if orig_spec_flag {
+                        // create originator optimized resource for local template
+                        let opt_name = res_ref.borrow().originator_optimized_name(orig_info).unwrap();
+                        let opt_res = res_ref.borrow().for_originator(opt_name.clone()).unwrap();
+                        let opt_res_ref = Rc::new(RefCell::new(opt_res));
+                        if ! thread_spec_flag {
+                            // originator-specific only
+                            specific_resources.insert(opt_name, opt_res_ref.clone());
+                            all_resources.push(opt_res_ref.clone());
+                        }
+                        local_template.push(opt_res_ref);
+                    } else {
+                        // not originator-specific
+                        if ! thread_spec_flag { all_resources.push(res_ref.clone()); }
+                        local_template.push(res_ref);
+                    }
+                },
+                Err(ex) => problems.push(ex)
+            }
+        }
+        FileStorage {
+            all_resources,
+            global_template,
+            local_template,
+            originator_templates: HashMap::new(),
+            specific_resources
+        }
+    }
+
+    /// Returns the output resources for a local thread.
+    ///
+    /// # Arguments
+    /// * `local_app_data` - information about the local application
+    /// * `thread_id` - the thread's ID
+    /// * `thread_name` - the thread's name
+    /// * `context` - the thread's context map
+    /// * `problems` - the array, where errors shall be stored
+    pub(crate) fn local_thread_resources(&mut self,
+                                         local_app_data: &OriginatorInfo,
+                                         thread_id: u64,
+                                         thread_name: &str,
+                                         context: Option<&ContextMap>,
+                                         problems: &mut Vec<CoalyException>)
+                                         -> Vec<(OutputFormat, ResourceRef)> {
+        let mut output_resources = Vec::<(OutputFormat, ResourceRef)>::new();
+        for res_ref in self.local_template.clone() {
+            let ofmt = res_ref.borrow().optimized_output_format(local_app_data,
+                                                                thread_id, thread_name);
+            if ! res_ref.borrow().is_thread_specific() {
+                // process wide resources can be used unchanged
+                output_resources.push((ofmt, res_ref));
+                continue
+            }
+            match self.thread_resource(&res_ref, thread_id, thread_name, context) {
+                Ok(spec_res_ref) => output_resources.push((ofmt, spec_res_ref)),
+                Err(ex) => problems.push(ex)
+            }
+        }
+        output_resources
+    }
+
+    /// Changes the output directory for all files created from now on, including files
+    /// created later on from templates.
+    ///
+    /// # Arguments
+    /// * `dir` - the new output directory
+    pub(crate) fn change_output_dir(&mut self, dir: &Path) {
+        let templates = self.global_template.iter().map(|(_, r)| r)
+                            .chain(self.local_template.iter())
+                            .chain(self.originator_templates.values().flat_map(|(_, t)| t.iter()));
+        self.all_resources.iter().chain(templates)
+                          .for_each(|r| r.borrow_mut().change_output_dir(dir));
+    }
+
+    /// Returns the thread specific resource for a thread specific resource template.
+    /// The resource is created, if it doesn't exist yet.
+    ///
+    /// # Arguments
+    /// * `template` - the resource template
+    /// * `thread_id` - the thread's ID
+    /// * `thread_name` - the thread's name
+    /// * `context` - the thread's context map
+    fn thread_resource(&mut self,
+                       template: &ResourceRef,
+                       thread_id: u64,
+                       thread_name: &str,
+                       context: Option<&ContextMap>) -> Result<ResourceRef, CoalyException> {
+        let res_name = template.borrow().thread_optimized_name(thread_id, thread_name,
+                                                               context).unwrap();
+        if let Some(spec_res_ref) = self.specific_resources.get(&res_name) {
+            return Ok(spec_res_ref.clone())
+        }
+        // instantiate template for the thread
+        let spec_res = Rc::new(RefCell::new(template.borrow().for_thread(res_name.clone())?));
+        self.specific_resources.insert(res_name, spec_res.clone());
+        self.all_resources.push(spec_res.clone());
+        Ok(spec_res)
+    }
+}
+impl RecordStorage for FileStorage {
+    fn take_resources(&mut self) -> Vec<ResourceRef> {
+        self.specific_resources.clear();
+        std::mem::take(&mut self.all_resources)
+    }
+
+    fn rollover_if_due(&mut self,
+                       now: &DateTime<Local>,
+                       problems: &mut Vec<CoalyException>) {
+        for res in self.all_resources.iter_mut() {
+            if let Err(ex) = res.borrow_mut().rollover_if_due(now) {
+                problems.push(ex);
+            }
+        }
+    }
+
+    fn handle_signal(&mut self,
+                     rollover: bool,
+                     problems: &mut Vec<CoalyException>) {
+        for res in self.all_resources.iter_mut() {
+            if let Err(mut exs) = res.borrow_mut().handle_signal(rollover) {
+                problems.append(&mut exs);
+            }
+        }
+    }
+
+    fn rollover_forecast(&self) -> Vec<RolloverForecast> {
+        self.all_resources.iter().filter_map(|r| r.borrow().rollover_forecast()).collect()
+    }
+
+    fn reopen_resources(&mut self, problems: &mut Vec<CoalyException>) {
+        for res in self.all_resources.iter_mut() {
+            if let Err(mut exs) = res.borrow_mut().reopen() {
+                problems.append(&mut exs);
+            }
+        }
+    }
+
+    fn add_remote_client(&mut self,
+                         remote_addr: &SocketAddr,
+                         orig_info: &OriginatorInfo,
+                         _problems: &mut Vec<CoalyException>) {
+        let mut orig_resources = Vec::<ResourceRef>::new();
+        let app_id = orig_info.application_id_value();
+        for (scope, res_ref) in &self.global_template {
+            // skip resources not handling records from the client's application
+            if ! scope_contains(scope, app_id) { continue }
+            let orig_spec_flag = res_ref.borrow().is_originator_specific();
+            let thread_spec_flag = res_ref.borrow().is_thread_specific();
+            if orig_spec_flag {
+                let opt_name = res_ref.borrow().originator_optimized_name(orig_info).unwrap();
+                if let Some(res) = self.specific_resources.get(&opt_name) {
+                    // originator optimized resource already exists, use it
+                    orig_resources.push(res.clone());
+                    continue;
+                }
+                // create originator optimized resource
+                let opt_res = res_ref.borrow().for_originator(opt_name.clone()).unwrap();
+                let opt_res_ref = Rc::new(RefCell::new(opt_res));
+                if ! thread_spec_flag {
+                    self.specific_resources.insert(opt_name, opt_res_ref.clone());
+                    self.all_resources.push(opt_res_ref.clone());
+                }
+                orig_resources.push(opt_res_ref);
+            } else {
+                // not originator-specific, can be directly used and has already been stored
+                // in all resources during storage construction
+                orig_resources.push(res_ref.clone());
+            }
+        }
+        self.originator_templates.insert(*remote_addr, (orig_info.clone(), orig_resources));
+    }
+
+    fn remove_remote_client(&mut self,
+                            remote_addr: &SocketAddr) {
+        self.originator_templates.remove(remote_addr);
+    }
+
+    fn remote_thread_resources(&mut self,
+                               remote_addr: &SocketAddr,
+                               thread_id: u64,
+                               thread_name: &str,
+                               output_resources: &mut Vec<(OutputFormat, ResourceRef)>,
+                               problems: &mut Vec<CoalyException>) {
+        let (orig_info, resources) = match self.originator_templates.get(remote_addr) {
+            Some((oi, res)) => (oi.clone(), res.clone()),
+            None => return
+        };
+        for res_ref in resources {
+            let ofmt = res_ref.borrow().optimized_output_format(&orig_info,
+                                                                thread_id, thread_name);
+            if ! res_ref.borrow().is_thread_specific() {
+                // originator wide resources can be used unchanged
+                output_resources.push((ofmt, res_ref));
+                continue
+            }
+            // context maps are not transferred from remote clients
+            match self.thread_resource(&res_ref, thread_id, thread_name, None) {
+                Ok(spec_res_ref) => output_resources.push((ofmt, spec_res_ref)),
+                Err(ex) => problems.push(ex)
+            }
+        }
+    }
+}
+
+/// Storage forwarding records to a syslog server or another Coaly log server.
+/// Every remote client gets its own connection, so that the receiver gets the originator
+/// information of the client instead of the relaying server.
+struct ForwardingStorage {
+    // descriptor of the syslog or network resource used to forward the records
+    desc: ResourceDesc,
+    // the configuration, needed to create the resources
+    config: Rc<Configuration>,
+    // forwarding resources for all connected clients
+    client_resources: HashMap<SocketAddr, (OriginatorInfo, ResourceRef)>
+}
+impl ForwardingStorage {
+    /// Creates a forwarding storage.
+    ///
+    /// # Arguments
+    /// * `desc` - the descriptor of the syslog or network resource used to forward the records
+    /// * `config` - the configuration
+    fn new(desc: &ResourceDesc,
+           config: &Rc<Configuration>) -> ForwardingStorage {
+        ForwardingStorage { desc: desc.clone(), config: config.clone(),
+                            client_resources: HashMap::new() }
+    }
+}
+impl RecordStorage for ForwardingStorage {
+    fn take_resources(&mut self) -> Vec<ResourceRef> {
+        self.client_resources.drain().map(|(_, (_, res_ref))| res_ref).collect()
+    }
+
+    fn rollover_if_due(&mut self,
+                       _now: &DateTime<Local>,
+                       _problems: &mut Vec<CoalyException>) {
+    }
+
+    fn handle_signal(&mut self,
+                     _rollover: bool,
+                     problems: &mut Vec<CoalyException>) {
+        for (_, res) in self.client_resources.values_mut() {
+            if let Err(mut exs) = res.borrow_mut().handle_signal(false) {
+                problems.append(&mut exs);
+            }
+        }
+    }
+
+    fn rollover_forecast(&self) -> Vec<RolloverForecast> { Vec::new() }
+
+    fn reopen_resources(&mut self, problems: &mut Vec<CoalyException>) {
+        for (_, res) in self.client_resources.values_mut() {
+            if let Err(mut exs) = res.borrow_mut().reopen() {
+                problems.append(&mut exs);
+            }
+        }
+    }
+
+    fn add_remote_client(&mut self,
+                         remote_addr: &SocketAddr,
+                         orig_info: &OriginatorInfo,
+                         problems: &mut Vec<CoalyException>) {
+        if ! self.desc.applies_to(orig_info.application_id_value()) { return }
+        match Resource::from_config(&self.desc, &self.config, orig_info) {
+            Ok(res) => {
+                let res_ref = Rc::new(RefCell::new(res));
+                self.client_resources.insert(*remote_addr, (orig_info.clone(), res_ref));
+            },
+            Err(ex) => problems.push(ex)
+        }
+    }
+
+    fn remove_remote_client(&mut self,
+                            remote_addr: &SocketAddr) {
+        if let Some((_, res_ref)) = self.client_resources.remove(remote_addr) {
+            Resource::close(&mut res_ref.borrow_mut(), None);
+        }
+    }
+
+    fn remote_thread_resources(&mut self,
+                               remote_addr: &SocketAddr,
+                               thread_id: u64,
+                               thread_name: &str,
+                               output_resources: &mut Vec<(OutputFormat, ResourceRef)>,
+                               _problems: &mut Vec<CoalyException>) {
+        if let Some((orig_info, res_ref)) = self.client_resources.get(remote_addr) {
+            let ofmt = res_ref.borrow().optimized_output_format(orig_info,
+                                                                thread_id, thread_name);
+            output_resources.push((ofmt, res_ref.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::config;
+
+    #[test]
+    /// Tests that remote clients only get the resources matching their application ID
+    fn test_resource_scope() {
+        let dir = Path::new(&std::env::var("COALY_TESTING_ROOT").unwrap())
+                      .join("tmp").join("output").join("storage").join("test_resource_scope");
+        std::fs::create_dir_all(&dir).unwrap();
+        let cfg_file = dir.join("scope.toml");
+        std::fs::write(&cfg_file, format!("[system]\noutput_path = \"{}\"\n\
+                                           [[resources]]\nkind = \"file\"\nname = \"all.log\"\n\
+                                           levels = [ \"all\" ]\n\
+                                           [[resources]]\nkind = \"file\"\nname = \"one.log\"\n\
+                                           levels = [ \"all\" ]\napp_ids = [ 1 ]\n\
+                                           [[resources]]\nkind = \"file\"\n\
+                                           name = \"two_$ProcessId.log\"\n\
+                                           levels = [ \"all\" ]\napp_ids = [ 2, 3 ]\n",
+                                          dir.to_string_lossy())).unwrap();
+        let local_info = OriginatorInfo::new(1, "server", "localhost", "127.0.0.1");
+        let cfg = config::configuration(&local_info, Some(&cfg_file.to_string_lossy()));
+        let mut problems = Vec::<CoalyException>::new();
+        let mut storage = FileStorage::new(&cfg, &local_info, &mut problems);
+        assert!(problems.is_empty());
+        let mut expected_counts = vec!((0, 1), (1, 2), (2, 2), (3, 2), (4, 1));
+        for (i, (app_id, expected_count)) in expected_counts.drain(..).enumerate() {
+            let addr = SocketAddr::from(([127, 0, 0, 1], 3000 + i as u16));
+            let mut client_info = OriginatorInfo::new(100 + i as u32, "client", "localhost",
+                                                      "127.0.0.1");
+            client_info.set_application_id(app_id);
+            storage.add_remote_client(&addr, &client_info, &mut problems);
+            let mut resources = Vec::new();
+            storage.remote_thread_resources(&addr, 1, "main", &mut resources, &mut problems);
+            assert_eq!(expected_count, resources.len(), "app ID {}", app_id);
+        }
+        assert!(problems.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -30,13 +30,13 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 // -----------------------------------------------------------------------------------------------
 
-//! Buffer handling and file rollover policies.
+//! Buffer handling, file rollover and throttle policies.
 
-use regex::Regex;
+use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
 use crate::coalyxw;
-use crate::datetime::{Interval, TimeSpan, TimeSpanUnit, TimeStampAnchor};
+use crate::datetime::{CronSchedule, Interval, TimeSpan, TimeSpanUnit, TimeStampAnchor};
 use crate::errorhandling::*;
 use crate::collections::MapWithDefault;
 use crate::util::parse_size_str;
@@ -48,6 +48,24 @@ pub(crate) const MIN_BUFFER_CONT_SIZE: usize = 4096; // 4 KByte
 // Maximum size for memory buffer contents
 pub(crate) const MAX_BUFFER_CONT_SIZE: usize = u32::MAX as usize; // 4 GByte
 
+// Default value and range for the time in seconds, after which an idle grown memory buffer is
+// shrunk to its initial content size; 0 means never
+pub(crate) const DEF_BUFFER_SHRINK_AFTER: usize = 0;
+pub(crate) const MIN_BUFFER_SHRINK_AFTER: usize = 0;
+pub(crate) const MAX_BUFFER_SHRINK_AFTER: usize = 86400;
+
+// Default value and range for the window in milliseconds, within which records identical to a
+// buffered one are suppressed; 0 means no suppression
+pub(crate) const DEF_BUFFER_DUPLICATE_WINDOW: usize = 0;
+pub(crate) const MIN_BUFFER_DUPLICATE_WINDOW: usize = 0;
+pub(crate) const MAX_BUFFER_DUPLICATE_WINDOW: usize = 3600000;
+
+// Default value and range for the target latency in microseconds per record written to the
+// physical resource, used to adapt the flush batch size; 0 means no adaptive flush
+pub(crate) const DEF_BUFFER_ADAPTIVE_LATENCY: usize = 0;
+pub(crate) const MIN_BUFFER_ADAPTIVE_LATENCY: usize = 0;
+pub(crate) const MAX_BUFFER_ADAPTIVE_LATENCY: usize = 10000000;
+
 // Default size for memory buffer record index
 pub(crate) const DEF_BUFFER_INDEX_SIZE: usize = 1024 * 1024; // 1 M entries
 // Minimum size for memory buffer record index
@@ -65,11 +83,45 @@ pub(crate) const DEF_FILE_SIZE: usize = 20 * 1024 * 1024;
 pub(crate) const MIN_FILE_SIZE: usize = 4096;
 pub(crate) const MAX_FILE_SIZE: usize = isize::MAX as usize;
 
+// Default value and range for minimum interval between desktop notifications, in seconds
+pub(crate) const DEF_NOTIFICATION_INTERVAL: usize = 60;
+pub(crate) const MIN_NOTIFICATION_INTERVAL: usize = 0;
+pub(crate) const MAX_NOTIFICATION_INTERVAL: usize = 86400;
+
+// Default value and range for the flush priority of a resource upon shutdown
+pub(crate) const DEF_FLUSH_PRIORITY: usize = 0;
+pub(crate) const MIN_FLUSH_PRIORITY: usize = 0;
+pub(crate) const MAX_FLUSH_PRIORITY: usize = 255;
+
+// Default value and range for the maximum flush time of a resource upon shutdown, in milliseconds;
+// 0 means unlimited
+pub(crate) const DEF_FLUSH_DEADLINE: usize = 0;
+pub(crate) const MIN_FLUSH_DEADLINE: usize = 0;
+pub(crate) const MAX_FLUSH_DEADLINE: usize = 3600000;
+
 // Default number of old files to keep before deletion
 pub(crate) const DEFAULT_KEEP_COUNT: usize = 9;
 pub(crate) const MIN_KEEP_COUNT: usize = 1;
 pub(crate) const MAX_KEEP_COUNT: usize = 255;
 
+// Default value and range for the maximum total size of all old files, 0 means unlimited
+pub(crate) const DEF_MAX_TOTAL_SIZE: usize = 0;
+pub(crate) const MIN_MAX_TOTAL_SIZE: usize = 65536;
+pub(crate) const MAX_MAX_TOTAL_SIZE: usize = isize::MAX as usize;
+
+// Default value and range for the maximum number of records per throttle interval
+pub(crate) const DEF_THROTTLE_MAX_RECORDS: usize = 1000;
+pub(crate) const MIN_THROTTLE_MAX_RECORDS: usize = 0;
+pub(crate) const MAX_THROTTLE_MAX_RECORDS: usize = u32::MAX as usize;
+
+// Default value and range for the throttle interval, in seconds
+pub(crate) const DEF_THROTTLE_INTERVAL: usize = 1;
+pub(crate) const MIN_THROTTLE_INTERVAL: usize = 1;
+pub(crate) const MAX_THROTTLE_INTERVAL: usize = 86400;
+
+// Default value for the sampling rate, all records are written
+pub(crate) const DEF_SAMPLE_RATE: f64 = 1.0;
+
 // Name for default policy
 pub(crate) const DEFAULT_POLICY_NAME: &str = "default";
 
@@ -97,7 +149,11 @@ pub(crate) enum BufferFlushCondition {
     Rollover = 0b1000,
     /// Flush if the application exits.
     /// The current contents is written to the associated physical resource.
-    Exit = 0b10000
+    Exit = 0b10000,
+    /// Flush if the operator sends signal SIGUSR1 or SIGHUP to the process (Unix only).
+    /// The current contents is written to the associated physical resource.
+    /// The bit is located beyond all record level bits.
+    Signal = 0b10000000000000000
 }
 impl Debug for BufferFlushCondition {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -107,6 +163,7 @@ impl Debug for BufferFlushCondition {
             BufferFlushCondition::Full => write!(f, "{}", FLUSH_ON_FULL),
             BufferFlushCondition::Rollover => write!(f, "{}", FLUSH_ON_ROLLOVER),
             BufferFlushCondition::Exit => write!(f, "{}", FLUSH_ON_EXIT),
+            BufferFlushCondition::Signal => write!(f, "{}", FLUSH_ON_SIGNAL),
         }
     }
 }
@@ -123,6 +180,7 @@ impl FromStr for BufferFlushCondition {
             FLUSH_ON_FULL => Ok(BufferFlushCondition::Full),
             FLUSH_ON_ROLLOVER => Ok(BufferFlushCondition::Rollover),
             FLUSH_ON_EXIT => Ok(BufferFlushCondition::Exit),
+            FLUSH_ON_SIGNAL => Ok(BufferFlushCondition::Signal),
             _ => Err(coalyxw!(W_CFG_UNKNOWN_BUF_FLUSH_CONDITION, s.to_string()))
         }
     }
@@ -133,15 +191,25 @@ impl FromStr for BufferFlushCondition {
 pub(crate) struct BufferPolicy {
     // policy name
     name: String,
-    // buffer content size in bytes
+    // initial buffer content size in bytes
     content_size: usize,
+    // maximum buffer content size in bytes, the buffer grows on demand up to this size
+    max_content_size: usize,
+    // time in seconds after which an idle grown buffer is shrunk to initial size, 0 for never
+    shrink_after: u64,
     // buffer record index size in entries
     index_size: usize,
     // bit mask with all conditions causing the buffer to be flushed
     // to associated physical resource
     flush_conditions: u32,
     // maximum length for a trace or log record, otherwise it is truncated
-    max_record_length: usize
+    max_record_length: usize,
+    // window in milliseconds, within which records with the same call site and message as a
+    // buffered one are suppressed, 0 for no suppression
+    duplicate_window: u64,
+    // target latency in microseconds per record written to the physical resource, used to
+    // adapt the flush batch size, 0 for no adaptive flush
+    adaptive_latency: u64
 }
 impl BufferPolicy {
     /// Creates a buffer policy.
@@ -149,28 +217,50 @@ impl BufferPolicy {
     ///
     /// # Arguments
     /// * `name` - the policy name
-    /// * `content_size` - the buffer content size in bytes
+    /// * `content_size` - the initial buffer content size in bytes
+    /// * `max_content_size` - the maximum buffer content size in bytes
+    /// * `shrink_after` - the idle time in seconds, after which a grown buffer is shrunk
     /// * `index_size` - the buffer record index size in entries
     /// * `flush_conditions` - the bit mask indicating all conditions causing the buffer contents
     ///                        to be flushed to associated physical resource
+    /// * `max_record_length` - the maximum record length in bytes
     #[inline]
     pub(crate) fn new(name: &str,
                       content_size: usize,
+                      max_content_size: usize,
+                      shrink_after: u64,
                       index_size: usize,
                       flush_conditions: u32,
                       max_record_length: usize) -> BufferPolicy {
         BufferPolicy {
             name: name.to_string(),
             content_size,
+            max_content_size,
+            shrink_after,
             index_size,
             flush_conditions,
-            max_record_length }
+            max_record_length,
+            duplicate_window: DEF_BUFFER_DUPLICATE_WINDOW as u64,
+            adaptive_latency: DEF_BUFFER_ADAPTIVE_LATENCY as u64 }
     }
 
-    /// Returns the buffer content size for this policy, in bytes.
+    /// Returns the name of this policy.
+    #[inline]
+    pub(crate) fn name(&self) -> &str { &self.name }
+
+    /// Returns the initial buffer content size for this policy, in bytes.
     #[inline]
     pub(crate) fn content_size(&self) -> usize { self.content_size }
 
+    /// Returns the maximum buffer content size for this policy, in bytes.
+    #[inline]
+    pub(crate) fn max_content_size(&self) -> usize { self.max_content_size }
+
+    /// Returns the idle time in seconds, after which a grown buffer is shrunk to initial size.
+    /// A value of 0 means the buffer is never shrunk.
+    #[inline]
+    pub(crate) fn shrink_after(&self) -> u64 { self.shrink_after }
+
     /// Returns the buffer record index size for this policy, in number of records.
     #[inline]
     pub(crate) fn index_size(&self) -> usize { self.index_size }
@@ -183,6 +273,32 @@ impl BufferPolicy {
     #[inline]
     pub(crate) fn max_record_length(&self) -> usize { self.max_record_length }
 
+    /// Returns the window in milliseconds, within which duplicates of a buffered record are
+    /// suppressed. A value of 0 means no suppression.
+    #[inline]
+    pub(crate) fn duplicate_window(&self) -> u64 { self.duplicate_window }
+
+    /// Sets the window for the suppression of duplicate records.
+    ///
+    /// # Arguments
+    /// * `window` - the window in milliseconds, 0 for no suppression
+    #[inline]
+    pub(crate) fn set_duplicate_window(&mut self, window: u64) { self.duplicate_window = window }
+
+    /// Returns the target latency in microseconds per record written to the physical resource.
+    /// Buffered records are written immediately while the measured latency stays below the
+    /// target, and in growing batches while it exceeds the target.
+    /// A value of 0 means no adaptive flush.
+    #[inline]
+    pub(crate) fn adaptive_latency(&self) -> u64 { self.adaptive_latency }
+
+    /// Sets the target latency for the adaptive flush.
+    ///
+    /// # Arguments
+    /// * `latency` - the target latency in microseconds per record, 0 for no adaptive flush
+    #[inline]
+    pub(crate) fn set_adaptive_latency(&mut self, latency: u64) { self.adaptive_latency = latency }
+
     /// Returns the default flush conditions for buffer policies.
     #[inline]
     pub(crate) fn default_flush_conditions() -> u32 {
@@ -194,16 +310,24 @@ impl Default for BufferPolicy {
         Self {
             name: DEFAULT_POLICY_NAME.to_string(),
             content_size: DEF_BUFFER_CONT_SIZE,
+            max_content_size: DEF_BUFFER_CONT_SIZE,
+            shrink_after: DEF_BUFFER_SHRINK_AFTER as u64,
             index_size: DEF_BUFFER_INDEX_SIZE,
             flush_conditions: BufferPolicy::default_flush_conditions(),
-            max_record_length: DEF_MAX_REC_LEN
+            max_record_length: DEF_MAX_REC_LEN,
+            duplicate_window: DEF_BUFFER_DUPLICATE_WINDOW as u64,
+            adaptive_latency: DEF_BUFFER_ADAPTIVE_LATENCY as u64
         }
     }
 }
 impl Debug for BufferPolicy {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "N:{}/CS:{}/IS:{}/C:{:b}/L:{}", self.name, self.content_size, self.index_size,
-                                           self.flush_conditions, self.max_record_length)
+        write!(f, "N:{}/CS:{}/IS:{}/C:{:b}/L:{}/MCS:{}/SA:{}", self.name, self.content_size,
+               self.index_size, self.flush_conditions, self.max_record_length,
+               self.max_content_size, self.shrink_after)?;
+        if self.duplicate_window > 0 { write!(f, "/DW:{}", self.duplicate_window)?; }
+        if self.adaptive_latency > 0 { write!(f, "/AL:{}", self.adaptive_latency)?; }
+        Ok(())
     }
 }
 
@@ -216,6 +340,8 @@ pub(crate) enum RolloverCondition {
     SizeReached(usize),
     /// New version of a file started if a specific time span has elapsed
     TimeElapsed(Interval),
+    /// New version of a file started whenever a cron-like schedule elapses
+    Scheduled(CronSchedule),
     /// No rollover, only one file
     Never
 }
@@ -227,6 +353,7 @@ impl Debug for RolloverCondition {
         match self {
             RolloverCondition::SizeReached(s) => write!(f, "SZ:{}", s),
             RolloverCondition::TimeElapsed(i) => write!(f, "INT:{:?}", i),
+            RolloverCondition::Scheduled(c) => write!(f, "CRON:{:?}", c),
             RolloverCondition::Never => write!(f, "NEVER"),
         }
     }
@@ -239,61 +366,42 @@ impl FromStr for RolloverCondition {
         if cond_str.is_empty() || cond_str.eq(ROVR_COND_NEVER) {
             return Ok(RolloverCondition::Never)
         }
-        let size_pat = Regex::new(ROVR_COND_SIZE_PATTERN).unwrap();
-        if let Some(capts) = size_pat.captures(&cond_str) {
+        if let Some(cron_spec) = cond_str.trim().strip_prefix(ROVR_COND_CRON_PREFIX) {
+            // Rollover whenever a cron-like schedule elapses
+            // cron:minute hour day_of_month month day_of_week [zone]
+            return Ok(RolloverCondition::Scheduled(CronSchedule::from_str(cron_spec)?))
+        }
+        let cond_str = cond_str.trim();
+        if let Some(size_def) = size_condition(cond_str) {
             // Rollover based on file size
             // size > n[k|m|g]
-            let size_def = capts.get(1).unwrap().as_str();
             if let Some(size_val) = parse_size_str(size_def) {
                 return Ok(RolloverCondition::SizeReached(size_val))
             }
             return Err(coalyxw!(W_CFG_INV_ROVR_FILE_SIZE, size_def.to_string()))
         }
-        let intvl_pat = Regex::new(ROVR_COND_INTVL_PATTERN).unwrap();
-        if let Some(capts) = intvl_pat.captures(&cond_str) {
-            // Periodic rollover every time an interval after application start elapses
-            // every [n] timespanunit
-            let mut ts_val: u32 = 1;
-            if let Some(valspec) = capts.get(1) {
-                let valspec = valspec.as_str().trim();
-                if let Ok(val) = u32::from_str(valspec) {
-                    ts_val = val;
-                } else {
-                    return Err(coalyxw!(W_CFG_INV_NUM_IN_INTVL, valspec.to_string()))
-                }
-            }
-            let unit_spec = capts.get(2).unwrap().as_str();
-            if let Ok(unit_val) = TimeSpanUnit::from_str(unit_spec) {
-                let ts = TimeSpan::new(unit_val, ts_val);
-                let intvl = Interval::unanchored(ts);
-                return Ok(RolloverCondition::TimeElapsed(intvl))
-            }
-            return Err(coalyxw!(W_CFG_INV_UNIT_IN_INTVL, unit_spec.to_string()))
-        }
-        let intvl_pat = Regex::new(ROVR_COND_INTVL_AT_PATTERN).unwrap();
-        if let Some(capts) = intvl_pat.captures(&cond_str) {
-            // Periodic rollover every time a specific moment of time is reached
-            // every [n] timespanunit at moment
+        if let Some((valspec, unit_spec, anchor_spec)) = interval_condition(cond_str) {
+            // Periodic rollover every time an interval elapses
+            // every [n] timespanunit [at moment]
             let mut ts_val: u32 = 1;
-            if let Some(valspec) = capts.get(1) {
-                let valspec = valspec.as_str().trim();
+            if let Some(valspec) = valspec {
                 if let Ok(val) = u32::from_str(valspec) {
                     ts_val = val;
                 } else {
                     return Err(coalyxw!(W_CFG_INV_NUM_IN_INTVL, valspec.to_string()))
                 }
             }
-            let unit_spec = capts.get(2).unwrap().as_str();
             let unit_val = TimeSpanUnit::from_str(unit_spec);
             if unit_val.is_err() {
                 return Err(coalyxw!(W_CFG_INV_UNIT_IN_INTVL, unit_spec.to_string()))
             }
             let unit_val = unit_val.unwrap();
             let ts = TimeSpan::new(unit_val, ts_val);
-            let anchor_spec = capts.get(3).unwrap().as_str();
-            let anchor_val = TimeStampAnchor::for_unit(anchor_spec, &unit_val)?;
-            let intvl = Interval::anchored(ts, anchor_val);
-            return Ok(RolloverCondition::TimeElapsed(intvl))
+            if let Some(anchor_spec) = anchor_spec {
+                let anchor_val = TimeStampAnchor::for_unit(anchor_spec, &unit_val)?;
+                return Ok(RolloverCondition::TimeElapsed(Interval::anchored(ts, anchor_val)))
+            }
+            return Ok(RolloverCondition::TimeElapsed(Interval::unanchored(ts)))
         }
         Err(coalyxw!(W_CFG_INV_ROVER_COND_PATTERN, s.to_string()))
     }
@@ -309,7 +417,11 @@ pub(crate) struct RolloverPolicy {
     // number of older files to keep before deletion
     keep_count: u32,
     // compression type for older files
-    compression: CompressionAlgorithm
+    compression: CompressionAlgorithm,
+    // indicates whether checksum files shall be written for older files
+    checksums: bool,
+    // maximum total size of all older files in bytes, 0 if unlimited
+    max_total_size: usize
 }
 impl RolloverPolicy {
     /// Creates a rollover policy.
@@ -325,7 +437,8 @@ impl RolloverPolicy {
                       condition: RolloverCondition,
                       keep_count: u32,
                       compression: CompressionAlgorithm) -> RolloverPolicy {
-        RolloverPolicy { name: name.to_string(), condition, keep_count, compression }
+        RolloverPolicy { name: name.to_string(), condition, keep_count, compression,
+                         checksums: false, max_total_size: DEF_MAX_TOTAL_SIZE }
     }
 
     /// Returns the rollover condition for this policy.
@@ -339,6 +452,32 @@ impl RolloverPolicy {
     /// Returns the compression algorithm for this policy.
     #[inline]
     pub(crate) fn compression(&self) -> CompressionAlgorithm { self.compression }
+
+    /// Indicates whether a SHA-256 checksum file shall be written for every older file.
+    #[inline]
+    pub(crate) fn checksums(&self) -> bool { self.checksums }
+
+    /// Sets whether a SHA-256 checksum file shall be written for every older file.
+    ///
+    /// # Arguments
+    /// * `checksums` - **true** to write checksum files
+    #[inline]
+    pub(crate) fn set_checksums(&mut self, checksums: bool) { self.checksums = checksums }
+
+    /// Returns the maximum total size of all older files in bytes, 0 if unlimited.
+    #[inline]
+    pub(crate) fn max_total_size(&self) -> usize { self.max_total_size }
+
+    /// Sets the maximum total size of all older files.
+    /// If the limit is exceeded, the oldest files are removed, even if the keep count is not
+    /// reached yet.
+    ///
+    /// # Arguments
+    /// * `max_total_size` - the maximum total size in bytes, 0 for no limit
+    #[inline]
+    pub(crate) fn set_max_total_size(&mut self, max_total_size: usize) {
+        self.max_total_size = max_total_size
+    }
 }
 impl Default for RolloverPolicy {
     fn default() -> Self {
@@ -346,19 +485,131 @@ impl Default for RolloverPolicy {
             name: DEFAULT_POLICY_NAME.to_string(),
             condition: RolloverCondition::default(),
             keep_count: 9,
-            compression: CompressionAlgorithm::default()
+            compression: CompressionAlgorithm::default(),
+            checksums: false,
+            max_total_size: DEF_MAX_TOTAL_SIZE
         }
     }
 }
 impl Debug for RolloverPolicy {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "N:{}/COND:{:?}/KEEP:{}/CMPR:{:?}", self.name,
-               self.condition, self.keep_count, self.compression)
+               self.condition, self.keep_count, self.compression)?;
+        if self.checksums { write!(f, "/SHA")?; }
+        if self.max_total_size > 0 { write!(f, "/MTS:{}", self.max_total_size)?; }
+        Ok(())
     }
 }
 
 pub(crate) type RolloverPolicyMap = MapWithDefault<RolloverPolicy>;
 
+/// Policy limiting the number of records written to a resource within a time interval
+#[derive (Clone)]
+pub(crate) struct ThrottlePolicy {
+    // policy name
+    name: String,
+    // maximum number of records per interval for all levels without specific limit
+    max_records: u32,
+    // interval length in seconds
+    interval: u64,
+    // level specific limits, replacing the general limit for the record level
+    level_limits: Vec<(u32, u32)>
+}
+impl ThrottlePolicy {
+    /// Creates a throttle policy.
+    /// Used for a policy defined in the policies.throttle section of the custom configuration file.
+    ///
+    /// # Arguments
+    /// * `name` - the policy name
+    /// * `max_records` - the maximum number of records per interval
+    /// * `interval` - the interval length in seconds
+    /// * `level_limits` - the level specific limits, record level ID and maximum number of
+    ///                    records per interval
+    #[inline]
+    pub(crate) fn new(name: &str,
+                      max_records: u32,
+                      interval: u64,
+                      level_limits: Vec<(u32, u32)>) -> ThrottlePolicy {
+        ThrottlePolicy { name: name.to_string(), max_records, interval, level_limits }
+    }
+
+    /// Returns the name of this policy.
+    #[inline]
+    pub(crate) fn name(&self) -> &str { &self.name }
+
+    /// Returns the maximum number of records per interval for levels without specific limit.
+    #[inline]
+    pub(crate) fn max_records(&self) -> u32 { self.max_records }
+
+    /// Returns the interval length for this policy, in seconds.
+    #[inline]
+    pub(crate) fn interval(&self) -> u64 { self.interval }
+
+    /// Returns the level specific limits for this policy.
+    #[inline]
+    pub(crate) fn level_limits(&self) -> &Vec<(u32, u32)> { &self.level_limits }
+}
+impl Debug for ThrottlePolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "N:{}/MAX:{}/INT:{}", self.name, self.max_records, self.interval)?;
+        for (lvl, max) in &self.level_limits { write!(f, "/{:b}:{}", lvl, max)?; }
+        Ok(())
+    }
+}
+
+pub(crate) type ThrottlePolicyMap = HashMap<String, ThrottlePolicy>;
+
+/// Policy writing only a statistical sample of the records issued to a resource
+#[derive (Clone)]
+pub(crate) struct SamplingPolicy {
+    // policy name
+    name: String,
+    // fraction of records written for all levels without specific rate
+    sample_rate: f64,
+    // level specific rates, replacing the general rate for the record level
+    level_rates: Vec<(u32, f64)>
+}
+impl SamplingPolicy {
+    /// Creates a sampling policy.
+    /// Used for a policy defined in the policies.sampling section of the custom configuration file.
+    ///
+    /// # Arguments
+    /// * `name` - the policy name
+    /// * `sample_rate` - the fraction of records written (0.0 - 1.0)
+    /// * `level_rates` - the level specific rates, record level ID and fraction of records
+    ///                   written
+    #[inline]
+    pub(crate) fn new(name: &str,
+                      sample_rate: f64,
+                      level_rates: Vec<(u32, f64)>) -> SamplingPolicy {
+        SamplingPolicy { name: name.to_string(), sample_rate, level_rates }
+    }
+
+    /// Returns the name of this policy.
+    #[inline]
+    pub(crate) fn name(&self) -> &str { &self.name }
+
+    /// Returns the fraction of records written for the specified record level.
+    ///
+    /// # Arguments
+    /// * `level` - the record level ID
+    pub(crate) fn rate_for(&self, level: u32) -> f64 {
+        match self.level_rates.iter().find(|(l, _)| *l == level) {
+            Some((_, rate)) => *rate,
+            None => self.sample_rate
+        }
+    }
+}
+impl Debug for SamplingPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "N:{}/SR:{}", self.name, self.sample_rate)?;
+        for (lvl, rate) in &self.level_rates { write!(f, "/{:b}:{}", lvl, rate)?; }
+        Ok(())
+    }
+}
+
+pub(crate) type SamplingPolicyMap = HashMap<String, SamplingPolicy>;
+
 /// File compression algorithms
 #[derive (Clone, Copy, Eq, PartialEq)]
 pub(crate) enum CompressionAlgorithm {
@@ -414,12 +665,73 @@ impl FromStr for CompressionAlgorithm {
     }
 }
 
+/// Checks whether the given rollover condition specifies a file size limit.
+/// 
+/// # Arguments
+/// * `cond_str` - the rollover condition, trimmed and lowercase
+/// 
+/// # Return values
+/// The size specification with optional unit, if the condition has the form
+/// *size > n[k|m|g]*; otherwise **None**
+fn size_condition(cond_str: &str) -> Option<&str> {
+    let size_def = cond_str.strip_prefix(ROVR_COND_SIZE)?.trim_start().strip_prefix('>')?.trim();
+    let num_part = size_def.strip_suffix(['k', 'm', 'g']).unwrap_or(size_def).trim_end();
+    if num_part.is_empty() || ! num_part.bytes().all(|b| b.is_ascii_digit()) { return None }
+    Some(size_def)
+}
+
+/// Checks whether the given rollover condition specifies a time interval.
+/// 
+/// # Arguments
+/// * `cond_str` - the rollover condition, trimmed and lowercase
+/// 
+/// # Return values
+/// Optional count, time span unit and optional anchor, if the condition has the form
+/// *every [n] timespanunit [at moment]*; otherwise **None**
+fn interval_condition(cond_str: &str) -> Option<(Option<&str>, &str, Option<&str>)> {
+    let intvl_spec = cond_str.strip_prefix(ROVR_COND_EVERY)?;
+    if ! intvl_spec.starts_with(char::is_whitespace) { return None }
+    let (first, rest) = next_word(intvl_spec)?;
+    let (count, unit, rest) = if first.bytes().all(|b| b.is_ascii_digit()) {
+        let (unit, rest) = next_word(rest)?;
+        (Some(first), unit, rest)
+    } else {
+        (None, first, rest)
+    };
+    if rest.is_empty() {
+        let unit_name = unit.strip_suffix('s').unwrap_or(unit);
+        if ! ROVR_INTVL_UNITS.contains(&unit_name) { return None }
+        return Some((count, unit, None))
+    }
+    if unit == ROVR_COND_AT { return Some((count, "", Some(rest))) }
+    let (keyword, anchor) = next_word(rest)?;
+    let unit_name = unit.strip_suffix('s').unwrap_or(unit);
+    if keyword != ROVR_COND_AT || anchor.is_empty() ||
+       ! ROVR_INTVL_AT_UNITS.contains(&unit_name) { return None }
+    Some((count, unit, Some(anchor)))
+}
+
+/// Splits off the first whitespace separated word from the given string.
+/// 
+/// # Return values
+/// The first word and the remainder without leading whitespace; **None** if the string
+/// contains whitespace only
+fn next_word(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    if s.is_empty() { return None }
+    match s.split_once(char::is_whitespace) {
+        Some((word, rest)) => Some((word, rest.trim_start())),
+        None => Some((s, ""))
+    }
+}
+
 // Buffer flush condition names
 const FLUSH_ON_ERROR: &str = "error";
 const FLUSH_ON_WARNING: &str = "warning";
 const FLUSH_ON_FULL: &str = "full";
 const FLUSH_ON_ROLLOVER: &str = "rollover";
 const FLUSH_ON_EXIT: &str = "exit";
+const FLUSH_ON_SIGNAL: &str = "signal";
 
 // Compression algorithm names
 const COMPR_ALGO_NONE: &str = "none";
@@ -438,10 +750,11 @@ const COMPR_EXT_LZMA: &str = ".xz";
 const COMPR_EXT_LZMA: &str = ".7z";
 const COMPR_EXT_ZIP: &str = ".zip";
 
-// Rollover condition patterns
+// Rollover condition keywords
 const ROVR_COND_NEVER: &str = "never";
-const ROVR_COND_SIZE_PATTERN: &str = r"^\s*size\s*>\s*([0-9]+\s*[kmg]{0,1})\s*$";
-const ROVR_COND_INTVL_PATTERN: &str =
-    r"^\s*every\s+([0-9]+\s+){0,1}(second[s]{0,1}|minute[s]{0,1}|hour[s]{0,1}|day[s]{0,1})\s*$";
-const ROVR_COND_INTVL_AT_PATTERN: &str =
-    r"^\s*every\s+([0-9]+\s+){0,1}(hour[s]{0,1}|day[s]{0,1}|week[s]{0,1}|month[s]{0,1}|)\s+at\s+(.*)\s*$";
+const ROVR_COND_CRON_PREFIX: &str = "cron:";
+const ROVR_COND_SIZE: &str = "size";
+const ROVR_COND_EVERY: &str = "every";
+const ROVR_COND_AT: &str = "at";
+const ROVR_INTVL_UNITS: [&str; 4] = ["second", "minute", "hour", "day"];
+const ROVR_INTVL_AT_UNITS: [&str; 4] = ["hour", "day", "week", "month"];
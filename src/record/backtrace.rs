@@ -0,0 +1,154 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Capture of short backtraces for log and trace records.
+
+use std::backtrace::Backtrace;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use super::RecordLevelId;
+
+/// Captures a short backtrace of the calling code, if backtraces are configured for the given
+/// record level.
+/// Frames within the backtrace machinery and Coaly itself are omitted, as well as frames of the
+/// Rust runtime below the application's main function.
+///
+/// # Arguments
+/// * `level` - the record level
+///
+/// # Return values
+/// the frames, innermost first, one per line; **None** if no backtrace is configured for
+/// the record level
+pub(crate) fn capture_backtrace(level: RecordLevelId) -> Option<Arc<String>> {
+    if BACKTRACE_LEVELS.load(Ordering::Relaxed) & level as u32 == 0 { return None }
+    let bt = Backtrace::force_capture().to_string();
+    Some(Arc::new(shortened(&bt, MAX_BACKTRACE_FRAMES)))
+}
+
+/// Stores the record levels, for which a backtrace is captured.
+///
+/// # Arguments
+/// * `levels` - the bit mask with the record levels
+#[inline]
+pub(crate) fn set_backtrace_levels(levels: u32) {
+    BACKTRACE_LEVELS.store(levels, Ordering::Relaxed);
+}
+
+/// Shortens a backtrace in the standard library's display format.
+/// Every frame is reduced to a single line with the function name and, if available, the
+/// source location.
+///
+/// # Arguments
+/// * `bt` - the backtrace as returned by the Display implementation of std::backtrace::Backtrace
+/// * `max_frames` - the maximum number of frames
+///
+/// # Return values
+/// the frames, innermost first, one per line
+fn shortened(bt: &str, max_frames: usize) -> String {
+    let mut frames = Vec::<String>::new();
+    let mut skipping = true;
+    let mut located = true;
+    for line in bt.lines() {
+        let line = line.trim();
+        if let Some(location) = line.strip_prefix("at ") {
+            // inlined functions may add further locations to a frame, keep the first one only
+            if let (Some(frame), false) = (frames.last_mut(), located) {
+                frame.push_str(&format!(" ({})", location));
+                located = true;
+            }
+            continue
+        }
+        let func = match line.split_once(": ") {
+            Some((nr, func)) if nr.chars().all(|c| c.is_ascii_digit()) => func,
+            _ => continue
+        };
+        if RUNTIME_FRAMES.iter().any(|f| func.contains(f)) { break }
+        if skipping && INTERNAL_FRAMES.iter().any(|f| func.starts_with(f)) { continue }
+        skipping = false;
+        if frames.len() == max_frames { break }
+        frames.push(func.to_string());
+        located = false;
+    }
+    frames.join("\n")
+}
+
+// record levels, for which a backtrace is captured
+static BACKTRACE_LEVELS: AtomicU32 = AtomicU32::new(0);
+
+// maximum number of frames in a captured backtrace
+const MAX_BACKTRACE_FRAMES: usize = 8;
+
+// prefixes of function names within the backtrace machinery and Coaly, skipped at the top
+const INTERNAL_FRAMES: &[&str] = &["std::backtrace", "<std::backtrace", "coaly::", "<coaly::"];
+
+// parts of function names of the Rust runtime and test harness, ending the backtrace
+const RUNTIME_FRAMES: &[&str] = &["std::rt::lang_start", "__rust_begin_short_backtrace"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "   0: std::backtrace_rs::backtrace::libunwind::trace
+             at /rustc/abc/library/std/src/../../backtrace/src/backtrace/libunwind.rs:116:5
+   1: std::backtrace::Backtrace::create
+             at /rustc/abc/library/std/src/backtrace.rs:331:13
+   2: coaly::record::backtrace::capture_backtrace
+             at ./src/record/backtrace.rs:52:14
+   3: coaly::agent::write
+             at ./src/agent/mod.rs:320:5
+   4: myapp::db::connect
+             at ./src/db.rs:17:9
+   5: myapp::main
+             at ./src/main.rs:8:5
+   6: std::sys::backtrace::__rust_begin_short_backtrace
+             at /rustc/abc/library/std/src/sys/backtrace.rs:152:18
+   7: std::rt::lang_start_internal
+   8: main
+   9: __libc_start_main";
+
+    #[test]
+    fn test_shortened() {
+        assert_eq!("myapp::db::connect (./src/db.rs:17:9)\nmyapp::main (./src/main.rs:8:5)",
+                   shortened(SAMPLE, MAX_BACKTRACE_FRAMES));
+        assert_eq!("myapp::db::connect (./src/db.rs:17:9)", shortened(SAMPLE, 1));
+        assert_eq!("", shortened("disabled backtrace", MAX_BACKTRACE_FRAMES));
+        assert_eq!("helper\nmain", shortened("0: helper\n1: main", MAX_BACKTRACE_FRAMES));
+    }
+
+    #[test]
+    fn test_capture_backtrace() {
+        assert!(capture_backtrace(RecordLevelId::Error).is_none());
+        let bt = shortened(&Backtrace::force_capture().to_string(), MAX_BACKTRACE_FRAMES);
+        assert!(bt.lines().count() <= MAX_BACKTRACE_FRAMES);
+        assert!(! bt.contains("std::backtrace"));
+    }
+}
@@ -38,6 +38,7 @@ use std::fmt::{Debug, Display, Formatter};
 use std::iter::Iterator;
 use std::str::FromStr;
 
+pub(crate) mod backtrace;
 pub mod originator;
 pub mod recorddata;
 
@@ -165,6 +166,112 @@ impl RecordLevelId {
             buf.push_str(&format!("{}", id));
         }
     }
+
+    /// Returns the syslog severity (RFC 5424) for this record level.
+    /// Levels emergency through info map to the severities with same name, i.e. values 0
+    /// through 6, all trace levels to debug (7). Groups map to the severity of their most
+    /// severe level. The values are part of the stable API.
+    pub fn syslog_severity(&self) -> u32 {
+        u32::min((*self as u32).trailing_zeros(), SYSLOG_SEVERITY_DEBUG)
+    }
+
+    /// Returns the record level for a syslog severity (RFC 5424).
+    /// Severity 7 (debug) maps to record level debug.
+    ///
+    /// # Arguments
+    /// * `severity` - the syslog severity, 0 through 7
+    ///
+    /// # Return values
+    /// The record level, **None** if the severity is out of range
+    pub fn from_syslog_severity(severity: u32) -> Option<RecordLevelId> {
+        if severity > SYSLOG_SEVERITY_DEBUG { return None }
+        Some(RecordLevelId::from(1 << severity))
+    }
+
+    /// Returns the OpenTelemetry severity number for this record level.
+    /// The values are part of the stable API:
+    /// emergency 24 (FATAL4), alert 23 (FATAL3), critical 21 (FATAL), error 17 (ERROR),
+    /// warning 13 (WARN), notice 10 (INFO2), info 9 (INFO), debug 5 (DEBUG),
+    /// function 1 (TRACE), module 2 (TRACE2), object 3 (TRACE3).
+    /// Groups map to the severity number of their most severe level.
+    pub fn otel_severity_number(&self) -> u32 {
+        match RecordLevelId::from(*self as u32) {
+            RecordLevelId::Emergency => 24,
+            RecordLevelId::Alert => 23,
+            RecordLevelId::Critical => 21,
+            RecordLevelId::Error => 17,
+            RecordLevelId::Warning => 13,
+            RecordLevelId::Notice => 10,
+            RecordLevelId::Info => 9,
+            RecordLevelId::Debug => 5,
+            RecordLevelId::Function => 1,
+            RecordLevelId::Module => 2,
+            _ => 3
+        }
+    }
+
+    /// Returns the record level for an OpenTelemetry severity number.
+    /// Numbers returned by function otel_severity_number map back to the same level, the
+    /// remaining numbers to the closest level within the same severity range, i.e. 4 to
+    /// object, 6 through 8 to debug, 11 and 12 to notice, 14 through 16 to warning,
+    /// 18 through 20 to error and 22 to critical.
+    ///
+    /// # Arguments
+    /// * `number` - the OpenTelemetry severity number, 1 through 24
+    ///
+    /// # Return values
+    /// The record level, **None** if the number is unspecified (0) or out of range
+    pub fn from_otel_severity_number(number: u32) -> Option<RecordLevelId> {
+        match number {
+            1 => Some(RecordLevelId::Function),
+            2 => Some(RecordLevelId::Module),
+            3 | 4 => Some(RecordLevelId::Object),
+            5 ..= 8 => Some(RecordLevelId::Debug),
+            9 => Some(RecordLevelId::Info),
+            10 ..= 12 => Some(RecordLevelId::Notice),
+            13 ..= 16 => Some(RecordLevelId::Warning),
+            17 ..= 20 => Some(RecordLevelId::Error),
+            21 | 22 => Some(RecordLevelId::Critical),
+            23 => Some(RecordLevelId::Alert),
+            24 => Some(RecordLevelId::Emergency),
+            _ => None
+        }
+    }
+
+    /// Returns the numeric value of the log crate level for this record level.
+    /// The values are part of the stable API and match the discriminants of log::Level:
+    /// emergency through error 1 (Error), warning 2 (Warn), notice and info 3 (Info),
+    /// debug 4 (Debug), function, module and object 5 (Trace).
+    /// Groups map to the value of their most severe level.
+    pub fn log_level(&self) -> u32 {
+        match RecordLevelId::from(*self as u32) {
+            RecordLevelId::Emergency | RecordLevelId::Alert |
+            RecordLevelId::Critical | RecordLevelId::Error => 1,
+            RecordLevelId::Warning => 2,
+            RecordLevelId::Notice | RecordLevelId::Info => 3,
+            RecordLevelId::Debug => 4,
+            _ => 5
+        }
+    }
+
+    /// Returns the record level for the numeric value of a log crate level.
+    /// Values 4 (Debug) and 5 (Trace) both map to record level debug, like records issued
+    /// through the log crate facade.
+    ///
+    /// # Arguments
+    /// * `value` - the numeric log crate level, 1 (Error) through 5 (Trace)
+    ///
+    /// # Return values
+    /// The record level, **None** if the value is out of range
+    pub fn from_log_level(value: u32) -> Option<RecordLevelId> {
+        match value {
+            1 => Some(RecordLevelId::Error),
+            2 => Some(RecordLevelId::Warning),
+            3 => Some(RecordLevelId::Info),
+            4 | 5 => Some(RecordLevelId::Debug),
+            _ => None
+        }
+    }
     fn dump(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             RecordLevelId::Emergency => write!(f, "{}", RECORD_LEVEL_EMERGENCY),
@@ -454,6 +561,30 @@ impl Debug for RecordLevelMap {
     }
 }
 
+/// Returns the record levels compiled into the application as bit mask.
+/// Features release_max_level_* apply to builds without debug assertions and take precedence
+/// over features max_level_*. If several features of a kind are enabled, the most restrictive
+/// one is significant.
+pub(crate) const fn static_levels() -> u32 {
+    if cfg!(not(debug_assertions)) {
+        if cfg!(feature="release_max_level_off") { return 0 }
+        if cfg!(feature="release_max_level_error") { return STATIC_LEVELS_ERROR }
+        if cfg!(feature="release_max_level_warn") { return RecordLevelId::Problems as u32 }
+        if cfg!(feature="release_max_level_info") { return RecordLevelId::Logs as u32 }
+        if cfg!(feature="release_max_level_debug") { return STATIC_LEVELS_DEBUG }
+    }
+    if cfg!(feature="max_level_off") { return 0 }
+    if cfg!(feature="max_level_error") { return STATIC_LEVELS_ERROR }
+    if cfg!(feature="max_level_warn") { return RecordLevelId::Problems as u32 }
+    if cfg!(feature="max_level_info") { return RecordLevelId::Logs as u32 }
+    if cfg!(feature="max_level_debug") { return STATIC_LEVELS_DEBUG }
+    RecordLevelId::All as u32
+}
+
+// Record levels compiled into the application for features max_level_error and max_level_debug
+const STATIC_LEVELS_ERROR: u32 = 0b000000001111;
+const STATIC_LEVELS_DEBUG: u32 = RecordLevelId::Logs as u32 | RecordLevelId::Debug as u32;
+
 // Names for all record triggers
 const RECORD_TRIGGER_ALL: &str = "all";
 const RECORD_TRIGGER_CRE: &str = "creation";
@@ -478,6 +609,16 @@ const RECORD_LEVEL_TRACES: &str = "traces";
 const RECORD_LEVEL_UNITS: &str = "units";
 const RECORD_LEVEL_ALL: &str = "all";
 
+// Names of all record levels, used for hints in configuration warnings
+pub(crate) const RECORD_LEVEL_NAMES: &[&str] = &[RECORD_LEVEL_EMERGENCY, RECORD_LEVEL_ALERT,
+                                                  RECORD_LEVEL_CRITICAL, RECORD_LEVEL_ERROR,
+                                                  RECORD_LEVEL_WARNING, RECORD_LEVEL_NOTICE,
+                                                  RECORD_LEVEL_INFO, RECORD_LEVEL_DEBUG,
+                                                  RECORD_LEVEL_FUNCTION, RECORD_LEVEL_MODULE,
+                                                  RECORD_LEVEL_OBJECT, RECORD_LEVEL_LOGS,
+                                                  RECORD_LEVEL_PROBLEMS, RECORD_LEVEL_TRACES,
+                                                  RECORD_LEVEL_UNITS, RECORD_LEVEL_ALL];
+
 // Default ID characters for all record levels.
 // The ID character replaces variable $LevelId in output formats.
 const DEFAULT_RECORD_LEVEL_ID_EMERGENCY : char = 'Y';
@@ -508,7 +649,10 @@ const DEFAULT_RECORD_LEVEL_NAME_MODULE : &str = "MOD";
 const DEFAULT_RECORD_LEVEL_NAME_OBJECT : &str = "OBJ";
 const DEFAULT_RECORD_LEVEL_NAME_GROUP : &str = "***";
 
-#[cfg(all(net, test))]
+// syslog severity for debug messages
+const SYSLOG_SEVERITY_DEBUG: u32 = 7;
+
+#[cfg(all(feature="net", test))]
 mod tests {
     use crate::net::serializable::Serializable;
     use core::fmt::Debug;
@@ -524,4 +668,47 @@ mod tests {
         assert!(clone.is_ok());
         assert_eq!(clone.unwrap(), *item);
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod level_tests {
+    use super::{RecordLevelId, STATIC_LEVELS_DEBUG, STATIC_LEVELS_ERROR};
+
+    #[test]
+    fn test_numeric_levels() {
+        assert_eq!(0, RecordLevelId::Emergency.syslog_severity());
+        assert_eq!(5, RecordLevelId::Notice.syslog_severity());
+        assert_eq!(7, RecordLevelId::Object.syslog_severity());
+        assert_eq!(0, RecordLevelId::Problems.syslog_severity());
+        assert_eq!(Some(RecordLevelId::Critical), RecordLevelId::from_syslog_severity(2));
+        assert_eq!(None, RecordLevelId::from_syslog_severity(8));
+        assert_eq!(24, RecordLevelId::Emergency.otel_severity_number());
+        assert_eq!(13, RecordLevelId::Warning.otel_severity_number());
+        assert_eq!(1, RecordLevelId::Units.otel_severity_number());
+        assert_eq!(Some(RecordLevelId::Debug), RecordLevelId::from_otel_severity_number(7));
+        assert_eq!(None, RecordLevelId::from_otel_severity_number(0));
+        assert_eq!(None, RecordLevelId::from_otel_severity_number(25));
+        assert_eq!(1, RecordLevelId::Alert.log_level());
+        assert_eq!(3, RecordLevelId::Notice.log_level());
+        assert_eq!(5, RecordLevelId::Module.log_level());
+        assert_eq!(Some(RecordLevelId::Debug), RecordLevelId::from_log_level(5));
+        assert_eq!(None, RecordLevelId::from_log_level(0));
+        for bit in 0 .. 11 {
+            let level = RecordLevelId::from(1 << bit);
+            let otel = level.otel_severity_number();
+            assert_eq!(Some(level), RecordLevelId::from_otel_severity_number(otel));
+            if bit < 8 {
+                assert_eq!(Some(level), RecordLevelId::from_syslog_severity(level.syslog_severity()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_static_levels() {
+        assert_eq!(vec!(RecordLevelId::Emergency, RecordLevelId::Alert, RecordLevelId::Critical,
+                        RecordLevelId::Error),
+                   RecordLevelId::essential_ids_in(STATIC_LEVELS_ERROR));
+        assert_eq!(Some(&RecordLevelId::Debug),
+                   RecordLevelId::essential_ids_in(STATIC_LEVELS_DEBUG).last());
+        assert_eq!(8, RecordLevelId::essential_ids_in(STATIC_LEVELS_DEBUG).len());
+    }
+}
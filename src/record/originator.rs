@@ -38,8 +38,11 @@ use std::collections::BTreeMap;
 use crate::CoalyException;
 
 #[cfg(feature="net")]
-use crate::net::serializable::{Serializable};
+use crate::net::serializable::{Serializable, deserialize_or_default};
 
+/// Name of the environment variable holding the token a process uses to authenticate at a
+/// Coaly logging server
+pub const AUTH_TOKEN_ENV_VAR: &str = "COALY_AUTH_TOKEN";
 
 /// Information about the originator of a log or trace message when sent to a remote server.
 /// Also used locally to replace variables used in record formats or file names
@@ -52,7 +55,11 @@ pub struct OriginatorInfo {
      application_name: String,
      host_name: String,
      ip_address: String,
-     env_vars: BTreeMap<String, String>
+     env_vars: BTreeMap<String, String>,
+     run_id: String,
+     parent_run_id: String,
+     trace_id: String,
+     auth_token: String
 }
 
 impl OriginatorInfo {
@@ -74,7 +81,11 @@ impl OriginatorInfo {
             application_name: String::from(""),
             host_name: host.to_string(),
             ip_address: ip.to_string(),
-            env_vars: BTreeMap::<String,String>::new()
+            env_vars: BTreeMap::<String,String>::new(),
+            run_id: String::from(""),
+            parent_run_id: String::from(""),
+            trace_id: String::from(""),
+            auth_token: String::from("")
         }
     }
 
@@ -122,11 +133,50 @@ impl OriginatorInfo {
     #[inline]
     pub fn env_var_value(&self, var_name: &str) -> Option<&String> { self.env_vars.get(var_name) }
 
+    /// Returns names and values of all environment variables
+    #[inline]
+    pub fn env_vars(&self) -> &BTreeMap<String, String> { &self.env_vars }
+
     /// Adds name and value of an environment variable
     #[inline]
     pub fn add_env_var(&mut self, name: &str, value: &str) {
         self.env_vars.insert(name.to_string(), value.to_string());
     }
+
+    /// Returns the run ID of the process
+    #[inline]
+    pub fn run_id(&self) -> &str { &self.run_id }
+
+    /// Returns the run ID of the parent process, empty if the process wasn't started by
+    /// another application using Coaly
+    #[inline]
+    pub fn parent_run_id(&self) -> &str { &self.parent_run_id }
+
+    /// Returns the trace ID shared by all processes of the process tree
+    #[inline]
+    pub fn trace_id(&self) -> &str { &self.trace_id }
+
+    /// Sets the IDs correlating the process with its parent process.
+    /// 
+    /// # Arguments
+    /// * `run_id` - the run ID of the process
+    /// * `parent_run_id` - the run ID of the parent process, empty if there is none
+    /// * `trace_id` - the trace ID shared by all processes of the process tree
+    #[inline]
+    pub fn set_run_context(&mut self, run_id: &str, parent_run_id: &str, trace_id: &str) {
+        self.run_id = run_id.to_string();
+        self.parent_run_id = parent_run_id.to_string();
+        self.trace_id = trace_id.to_string();
+    }
+
+    /// Returns the token the process uses to authenticate at a Coaly logging server,
+    /// empty if not specified
+    #[inline]
+    pub fn auth_token(&self) -> &str { &self.auth_token }
+
+    /// Sets the token the process uses to authenticate at a Coaly logging server
+    #[inline]
+    pub fn set_auth_token(&mut self, token: &str) { self.auth_token = token.to_string() }
 }
 #[cfg(feature="net")]
 impl<'a> Serializable<'a> for OriginatorInfo {
@@ -137,7 +187,11 @@ impl<'a> Serializable<'a> for OriginatorInfo {
         self.application_name.serialized_size() +
         self.host_name.serialized_size() +
         self.ip_address.serialized_size() +
-        self.env_vars.serialized_size()
+        self.env_vars.serialized_size() +
+        self.run_id.serialized_size() +
+        self.parent_run_id.serialized_size() +
+        self.trace_id.serialized_size() +
+        self.auth_token.serialized_size()
     }
     fn serialize_to(&self, buffer: &mut Vec<u8>) -> usize {
         let mut n = self.process_id.serialize_to(buffer);
@@ -147,6 +201,10 @@ impl<'a> Serializable<'a> for OriginatorInfo {
         n += self.host_name.serialize_to(buffer);
         n += self.ip_address.serialize_to(buffer);
         n += self.env_vars.serialize_to(buffer);
+        n += self.run_id.serialize_to(buffer);
+        n += self.parent_run_id.serialize_to(buffer);
+        n += self.trace_id.serialize_to(buffer);
+        n += self.auth_token.serialize_to(buffer);
         n
     }
     fn deserialize_from(buffer: &[u8]) -> Result<Self, CoalyException> {
@@ -163,14 +221,26 @@ impl<'a> Serializable<'a> for OriginatorInfo {
         let ip_address = String::deserialize_from(buf)?;
         let buf = &buf[ip_address.serialized_size()..];
         let env_vars = BTreeMap::<String, String>::deserialize_from(buf)?;
+        let buf = &buf[env_vars.serialized_size()..];
+        // run context was added in schema version 2
+        let run_id = deserialize_or_default::<String>(buf)?;
+        let buf = buf.get(run_id.serialized_size()..).unwrap_or(&[]);
+        let parent_run_id = deserialize_or_default::<String>(buf)?;
+        let buf = buf.get(parent_run_id.serialized_size()..).unwrap_or(&[]);
+        let trace_id = deserialize_or_default::<String>(buf)?;
+        let buf = buf.get(trace_id.serialized_size()..).unwrap_or(&[]);
+        // authentication token was added in schema version 3
+        let auth_token = deserialize_or_default::<String>(buf)?;
         Ok(OriginatorInfo { process_id, process_name, application_id, application_name,
-                            host_name, ip_address, env_vars } )
+                            host_name, ip_address, env_vars, run_id, parent_run_id, trace_id,
+                            auth_token } )
     }
 }
 
-#[cfg(all(test, net))]
+#[cfg(all(test, feature="net"))]
 mod tests {
     use super::OriginatorInfo;
+    use crate::net::serializable::Serializable;
     use crate::record::tests::check_serialization;
 
     #[test]
@@ -178,22 +248,38 @@ mod tests {
         let mut buffer = Vec::<u8>::with_capacity(256);
         // default app ID and name
         let oinfo_def_app = OriginatorInfo::new(1234, "testapp", "clienthost", "1.2.3.4");
-        check_serialization::<OriginatorInfo>(&oinfo_def_app, 72, &mut buffer);
+        check_serialization::<OriginatorInfo>(&oinfo_def_app, 104, &mut buffer);
         // default app ID, custom app name
         let mut oinfo_def_app_id = OriginatorInfo::new(1234, "testapp", "clienthost", "1.2.3.4");
         oinfo_def_app_id.set_application_name("superapp");
-        check_serialization::<OriginatorInfo>(&oinfo_def_app_id, 80, &mut buffer);
+        check_serialization::<OriginatorInfo>(&oinfo_def_app_id, 112, &mut buffer);
         // custom app ID and name
         let mut oinfo_cust_app = OriginatorInfo::new(1234, "testapp", "clienthost", "1.2.3.4");
         oinfo_cust_app.set_application_id(9876);
         oinfo_cust_app.set_application_name("superapp");
-        check_serialization::<OriginatorInfo>(&oinfo_cust_app, 80, &mut buffer);
+        check_serialization::<OriginatorInfo>(&oinfo_cust_app, 112, &mut buffer);
         // with environment variables
         let mut oinfo_with_enva = OriginatorInfo::new(1234, "testapp", "clienthost", "::1");
         oinfo_with_enva.set_application_id(9876);
         oinfo_with_enva.set_application_name("superapp");
         oinfo_with_enva.add_env_var("COALYROOT", "/var/log/superapp");
         oinfo_with_enva.add_env_var("LANG", "en");
-        check_serialization::<OriginatorInfo>(&oinfo_with_enva, 138, &mut buffer);
+        check_serialization::<OriginatorInfo>(&oinfo_with_enva, 172, &mut buffer);
+    }
+
+    #[test]
+    fn test_deserialize_orig_info_schema_versions() {
+        let mut oinfo = OriginatorInfo::new(1234, "testapp", "clienthost", "1.2.3.4");
+        oinfo.set_run_context("run1", "run0", "trace1");
+        let mut buffer = Vec::<u8>::with_capacity(256);
+        oinfo.serialize_to(&mut buffer);
+        // schema version 1 peer, run context and token missing
+        let v1_size = buffer.len() - 4 * 8 - 14;
+        let v1_oinfo = OriginatorInfo::deserialize_from(&buffer[..v1_size]).unwrap();
+        assert_eq!("", v1_oinfo.run_id);
+        assert_eq!("", v1_oinfo.trace_id);
+        // newer schema peer, trailing data ignored
+        buffer.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(oinfo, OriginatorInfo::deserialize_from(&buffer).unwrap());
     }
 }
\ No newline at end of file
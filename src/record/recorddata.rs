@@ -33,7 +33,12 @@
 //! Types and functionality around log or trace records.
 
 use chrono::{DateTime, Local, TimeZone};
-use crate::observer::ObserverData;
+use std::sync::Arc;
+use crate::context::ContextMap;
+use crate::datetime::{record_ticks, record_timestamp};
+use crate::errorhandling::pipetrace;
+use crate::observer::{AttrMap, ObserverData};
+use super::backtrace::capture_backtrace;
 use super::{RecordLevelId, RecordTrigger};
 
 #[cfg(feature="net")]
@@ -86,6 +91,44 @@ pub trait RecordData<'a> {
 
     /// Returns the timestamp when the record was issued as local datetime.
     fn timestamp(&self) -> DateTime<Local>;
+
+    /// Returns the names of all observers active in the issuing thread, outermost first
+    fn scope_stack(&self) -> &[String];
+
+    /// Returns the numbers of records written and suppressed by the issuing thread since its
+    /// start, as counted when the record was processed
+    fn thread_write_counts(&self) -> (u64, u64);
+
+    /// Returns the prefix, suffix and fields attached to the record by a mode change for the
+    /// innermost matching function or module, if any
+    fn decoration(&self) -> Option<&RecordDecoration>;
+
+    /// Returns the context map of the issuing thread at the time the record was created,
+    /// **None** if the map was empty
+    fn context(&self) -> Option<&ContextMap>;
+
+    /// Returns the attributes of the observers active in the issuing thread at the time the
+    /// record was created, **None** if there were no attributes
+    fn attrs(&self) -> Option<&AttrMap>;
+
+    /// Returns the shortened backtrace captured when the record was created, **None** if
+    /// backtraces are not configured for the record level
+    fn backtrace(&self) -> Option<&str>;
+
+    /// Returns the module path of the source code, where the record was issued, **None** if
+    /// the record was not issued by a log macro
+    fn module_path(&self) -> Option<&str>;
+
+    /// Returns the name of the function, where the record was issued, **None** if the record
+    /// was not issued by a log macro
+    fn function(&self) -> Option<&str>;
+
+    /// Returns the monotonic clock reading taken when the record was created, in nanoseconds
+    /// since the tick base, **None** if not available
+    fn ticks(&self) -> Option<u64>;
+
+    /// Indicates whether the filtering decisions for the record shall be traced
+    fn traced(&self) -> bool;
 }
 #[cfg(feature="net")]
 pub trait RecordData<'a> : Serializable<'a> {
@@ -127,6 +170,135 @@ pub trait RecordData<'a> : Serializable<'a> {
 
     /// Returns the timestamp when the record was issued as local datetime.
     fn timestamp(&self) -> DateTime<Local>;
+
+    /// Returns the names of all observers active in the issuing thread, outermost first
+    fn scope_stack(&self) -> &[String];
+
+    /// Returns the numbers of records written and suppressed by the issuing thread since its
+    /// start, as counted when the record was processed
+    fn thread_write_counts(&self) -> (u64, u64);
+
+    /// Returns the prefix, suffix and fields attached to the record by a mode change for the
+    /// innermost matching function or module, if any
+    fn decoration(&self) -> Option<&RecordDecoration>;
+
+    /// Returns the context map of the issuing thread at the time the record was created,
+    /// **None** if the map was empty
+    fn context(&self) -> Option<&ContextMap>;
+
+    /// Returns the attributes of the observers active in the issuing thread at the time the
+    /// record was created, **None** if there were no attributes
+    fn attrs(&self) -> Option<&AttrMap>;
+
+    /// Returns the shortened backtrace captured when the record was created, **None** if
+    /// backtraces are not configured for the record level
+    fn backtrace(&self) -> Option<&str>;
+
+    /// Returns the module path of the source code, where the record was issued, **None** if
+    /// the record was not issued by a log macro
+    fn module_path(&self) -> Option<&str>;
+
+    /// Returns the name of the function, where the record was issued, **None** if the record
+    /// was not issued by a log macro
+    fn function(&self) -> Option<&str>;
+
+    /// Returns the monotonic clock reading taken when the record was created, in nanoseconds
+    /// since the tick base, **None** if not available
+    fn ticks(&self) -> Option<u64>;
+
+    /// Indicates whether the filtering decisions for the record shall be traced
+    fn traced(&self) -> bool;
+}
+
+/// Module path and function of the source code, where a record was issued.
+/// Determined automatically by the log macros, hence plain log records can be attributed to
+/// their function without function or module observers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SourceUnit {
+    // module path as returned by std::module_path!
+    module_path: &'static str,
+    // type name of a function item nested in the issuing function
+    fn_path: &'static str
+}
+impl SourceUnit {
+    /// Creates a source unit.
+    /// Invoked by the log macros, use macro coaly_source_unit to create the source unit for
+    /// the current location.
+    /// 
+    /// # Arguments
+    /// * `module_path` - the module path, as returned by std::module_path!
+    /// * `fn_path` - the type name of a function nested in the issuing function, empty if unknown
+    #[inline]
+    pub const fn new(module_path: &'static str, fn_path: &'static str) -> SourceUnit {
+        SourceUnit { module_path, fn_path }
+    }
+
+    /// Returns the module path, empty if unknown
+    #[inline]
+    pub fn module_path(&self) -> &'static str { self.module_path }
+
+    /// Returns the name of the issuing function without module path, empty if unknown.
+    /// Records from closures and async blocks are attributed to the enclosing function.
+    pub fn function(&self) -> &'static str {
+        let mut path = self.fn_path.strip_suffix(NESTED_FN_SUFFIX).unwrap_or(self.fn_path);
+        while let Some(p) = path.strip_suffix(CLOSURE_SUFFIX) { path = p; }
+        path.rsplit(PATH_SEP).next().unwrap_or_default()
+    }
+}
+
+// Suffix of the type name of the function item nested in the issuing function
+const NESTED_FN_SUFFIX: &str = "::f";
+
+// Suffix of a type name denoting a closure or async block
+const CLOSURE_SUFFIX: &str = "::{{closure}}";
+
+// Separator for the parts of a Rust path
+const PATH_SEP: &str = "::";
+
+/// Constant prefix, suffix and fields attached to all records from functions or modules
+/// matching a mode change.
+/// Makes records from specific code areas distinguishable, e.g. from a vendored module.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RecordDecoration {
+    // text to insert before the record message
+    prefix: String,
+    // text to append to the record message
+    suffix: String,
+    // additional fields as name value pairs, sorted by name
+    fields: Vec<(String, String)>
+}
+impl RecordDecoration {
+    /// Creates a record decoration.
+    /// 
+    /// # Arguments
+    /// * `prefix` - the text to insert before the record message
+    /// * `suffix` - the text to append to the record message
+    /// * `fields` - the additional fields as name value pairs
+    pub(crate) fn new(prefix: &str,
+                      suffix: &str,
+                      fields: Vec<(String, String)>) -> RecordDecoration {
+        RecordDecoration { prefix: prefix.to_string(), suffix: suffix.to_string(), fields }
+    }
+
+    /// Returns the text to insert before the record message
+    #[inline]
+    pub fn prefix(&self) -> &str { &self.prefix }
+
+    /// Returns the text to append to the record message
+    #[inline]
+    pub fn suffix(&self) -> &str { &self.suffix }
+
+    /// Returns the additional fields as name value pairs
+    #[inline]
+    pub fn fields(&self) -> &[(String, String)] { &self.fields }
+
+    /// Returns the given message with prefix and suffix applied.
+    /// 
+    /// # Arguments
+    /// * `msg` - the record message
+    pub(crate) fn decorated(&self, msg: &str) -> String {
+        format!("{}{}{}", self.prefix, msg, self.suffix)
+    }
 }
 
 /// Log or trace record within a process.
@@ -134,6 +306,24 @@ pub trait RecordData<'a> : Serializable<'a> {
 pub struct LocalRecordData {
     common_data: CommonRecordData,
     source_fn: &'static str,
+    // names of the observers active in the issuing thread, set by the worker thread
+    scope_stack: Option<Arc<Vec<String>>>,
+    // decoration from a mode change for the innermost matching unit, set by the worker thread
+    decoration: Option<Arc<RecordDecoration>>,
+    // records written and suppressed by the issuing thread, set by the worker thread
+    write_counts: (u64, u64),
+    // context map of the issuing thread at the time the record was created
+    context: Option<Arc<ContextMap>>,
+    // observer attributes of the issuing thread at the time the record was created
+    attrs: Option<Arc<AttrMap>>,
+    // shortened backtrace of the issuing call site, if configured for the record level
+    backtrace: Option<Arc<String>>,
+    // module path and function of the issuing call site, if issued by a log macro
+    unit: SourceUnit,
+    // monotonic clock reading in nanoseconds since the tick base when the record was created
+    ticks: u64,
+    // indicates whether the record was sampled for the tracing of filtering decisions
+    traced: bool
 }
 impl LocalRecordData {
     /// Creates local record data for a plain output message to be written to output
@@ -153,7 +343,16 @@ impl LocalRecordData {
                             msg: &str) -> LocalRecordData {
         LocalRecordData {
             common_data: CommonRecordData::for_write(thread_id, thread_name, level, line_nr, msg),
-            source_fn: file_name
+            source_fn: file_name,
+            scope_stack: None,
+            decoration: None,
+            write_counts: (0, 0),
+            context: crate::context::current(),
+            attrs: crate::observer::current_attrs(),
+            backtrace: capture_backtrace(level),
+            unit: SourceUnit::default(),
+            ticks: record_ticks(),
+            traced: pipetrace::sampled()
         }
     }
 
@@ -175,7 +374,16 @@ impl LocalRecordData {
         LocalRecordData {
             common_data: CommonRecordData::for_write_obs(thread_id, thread_name,
                                                    observer_data, line_nr, msg),
-            source_fn: file_name
+            source_fn: file_name,
+            scope_stack: None,
+            decoration: None,
+            write_counts: (0, 0),
+            context: crate::context::current(),
+            attrs: crate::observer::current_attrs(),
+            backtrace: capture_backtrace(RecordLevelId::Object),
+            unit: SourceUnit::default(),
+            ticks: record_ticks(),
+            traced: pipetrace::sampled()
         }
     }
 
@@ -193,7 +401,16 @@ impl LocalRecordData {
                              line_nr: u32) -> LocalRecordData {
         LocalRecordData {
             common_data: CommonRecordData::for_create(thread_id, thread_name, observer, line_nr),
-            source_fn: observer.file_name()
+            source_fn: observer.file_name(),
+            scope_stack: None,
+            decoration: None,
+            write_counts: (0, 0),
+            context: crate::context::current(),
+            attrs: crate::observer::current_attrs(),
+            backtrace: None,
+            unit: SourceUnit::default(),
+            ticks: record_ticks(),
+            traced: pipetrace::sampled()
         }
     }
 
@@ -209,9 +426,64 @@ impl LocalRecordData {
                            observer: &ObserverData) -> LocalRecordData {
         LocalRecordData {
             common_data: CommonRecordData::for_drop(thread_id, thread_name, observer),
-            source_fn: observer.file_name()
+            source_fn: observer.file_name(),
+            scope_stack: None,
+            decoration: None,
+            write_counts: (0, 0),
+            context: crate::context::current(),
+            attrs: crate::observer::current_attrs(),
+            backtrace: None,
+            unit: SourceUnit::default(),
+            ticks: record_ticks(),
+            traced: pipetrace::sampled()
         }
     }
+
+    /// Sets the names of all observers active in the issuing thread.
+    /// 
+    /// # Arguments
+    /// * `scope_stack` - the observer names, outermost first
+    #[inline]
+    pub(crate) fn set_scope_stack(&mut self, scope_stack: Arc<Vec<String>>) {
+        self.scope_stack = Some(scope_stack);
+    }
+
+    /// Sets module path and function of the issuing call site.
+    /// 
+    /// # Arguments
+    /// * `unit` - the source unit, as determined by the log macro
+    #[inline]
+    pub(crate) fn set_source_unit(&mut self, unit: SourceUnit) {
+        self.unit = unit;
+    }
+
+    /// Sets the decoration for the innermost function or module with a matching mode change.
+    /// 
+    /// # Arguments
+    /// * `decoration` - the prefix, suffix and fields to attach
+    #[inline]
+    pub(crate) fn set_decoration(&mut self, decoration: Option<Arc<RecordDecoration>>) {
+        self.decoration = decoration;
+    }
+
+    /// Sets the numbers of records written and suppressed by the issuing thread.
+    /// 
+    /// # Arguments
+    /// * `written` - the number of records written since thread start
+    /// * `suppressed` - the number of records suppressed by the output mode since thread start
+    #[inline]
+    pub(crate) fn set_thread_write_counts(&mut self, written: u64, suppressed: u64) {
+        self.write_counts = (written, suppressed);
+    }
+
+    /// Sets the backtrace of the issuing call site.
+    /// 
+    /// # Arguments
+    /// * `backtrace` - the frames, innermost first, one per line
+    #[cfg(test)]
+    pub(crate) fn set_backtrace(&mut self, backtrace: &str) {
+        self.backtrace = Some(Arc::new(backtrace.to_string()));
+    }
 }
 impl<'a> RecordData<'a> for LocalRecordData {
     /// Returns the thread ID
@@ -265,6 +537,52 @@ impl<'a> RecordData<'a> for LocalRecordData {
     /// Returns the timestamp when the record was issued as local datetime.
     #[inline]
     fn timestamp(&self) -> DateTime<Local> { self.common_data.timestamp() }
+
+    /// Returns the names of all observers active in the issuing thread, outermost first
+    fn scope_stack(&self) -> &[String] {
+        match &self.scope_stack {
+            Some(st) => st,
+            None => &[]
+        }
+    }
+
+    /// Returns the numbers of records written and suppressed by the issuing thread
+    #[inline]
+    fn thread_write_counts(&self) -> (u64, u64) { self.write_counts }
+
+    /// Returns the decoration for the innermost function or module with a matching mode change
+    #[inline]
+    fn decoration(&self) -> Option<&RecordDecoration> { self.decoration.as_deref() }
+
+    /// Returns the context map of the issuing thread
+    #[inline]
+    fn context(&self) -> Option<&ContextMap> { self.context.as_deref() }
+
+    /// Returns the observer attributes of the issuing thread
+    #[inline]
+    fn attrs(&self) -> Option<&AttrMap> { self.attrs.as_deref() }
+
+    /// Returns the shortened backtrace captured when the record was created
+    #[inline]
+    fn backtrace(&self) -> Option<&str> { self.backtrace.as_deref().map(|b| b.as_str()) }
+
+    /// Returns the module path of the issuing call site
+    #[inline]
+    fn module_path(&self) -> Option<&str> {
+        Some(self.unit.module_path()).filter(|m| ! m.is_empty())
+    }
+
+    /// Returns the function name of the issuing call site
+    #[inline]
+    fn function(&self) -> Option<&str> { Some(self.unit.function()).filter(|f| ! f.is_empty()) }
+
+    /// Returns the monotonic clock reading taken when the record was created
+    #[inline]
+    fn ticks(&self) -> Option<u64> { Some(self.ticks) }
+
+    /// Indicates whether the filtering decisions for the record shall be traced
+    #[inline]
+    fn traced(&self) -> bool { self.traced }
 }
 #[cfg(feature="net")]
 impl<'a> Serializable<'a> for LocalRecordData {
@@ -283,7 +601,10 @@ impl<'a> Serializable<'a> for LocalRecordData {
         // with lifetimes for source file name
         // TODO mess around with source file name because needed in buffering for network resources
         let source_fn = "";
-        Ok(LocalRecordData { common_data, source_fn })
+        Ok(LocalRecordData { common_data, source_fn, scope_stack: None, decoration: None,
+                            write_counts: (0, 0), context: None, attrs: None,
+                            backtrace: None, unit: SourceUnit::default(), ticks: 0,
+                            traced: false })
     }
 }
 
@@ -345,6 +666,56 @@ impl<'a> RecordData<'a> for RemoteRecordData {
     /// Returns the timestamp when the record was issued as local datetime.
     #[inline]
     fn timestamp(&self) -> DateTime<Local> { self.common_data.timestamp() }
+
+    /// Returns the names of all observers active in the issuing thread.
+    /// Not transferred from remote clients, hence always empty.
+    #[inline]
+    fn scope_stack(&self) -> &[String] { &[] }
+
+    /// Returns the numbers of records written and suppressed by the issuing thread.
+    /// Not transferred from remote clients, hence always zero.
+    #[inline]
+    fn thread_write_counts(&self) -> (u64, u64) { (0, 0) }
+
+    /// Returns the decoration for the record.
+    /// Not transferred from remote clients, hence always **None**.
+    #[inline]
+    fn decoration(&self) -> Option<&RecordDecoration> { None }
+
+    /// Returns the context map of the issuing thread.
+    /// Not transferred from remote clients, hence always **None**.
+    #[inline]
+    fn context(&self) -> Option<&ContextMap> { None }
+
+    /// Returns the observer attributes of the issuing thread.
+    /// Not transferred from remote clients, hence always **None**.
+    #[inline]
+    fn attrs(&self) -> Option<&AttrMap> { None }
+
+    /// Returns the backtrace captured when the record was created.
+    /// Not transferred from remote clients, hence always **None**.
+    #[inline]
+    fn backtrace(&self) -> Option<&str> { None }
+
+    /// Returns the module path of the issuing call site.
+    /// Not transferred from remote clients, hence always **None**.
+    #[inline]
+    fn module_path(&self) -> Option<&str> { None }
+
+    /// Returns the function name of the issuing call site.
+    /// Not transferred from remote clients, hence always **None**.
+    #[inline]
+    fn function(&self) -> Option<&str> { None }
+
+    /// Returns the monotonic clock reading taken when the record was created.
+    /// Not comparable between processes, hence always **None**.
+    #[inline]
+    fn ticks(&self) -> Option<u64> { None }
+
+    /// Indicates whether the filtering decisions for the record shall be traced.
+    /// Remote records are never traced.
+    #[inline]
+    fn traced(&self) -> bool { false }
 }
 #[cfg(feature="net")]
 impl<'a> Serializable<'a> for RemoteRecordData {
@@ -404,12 +775,12 @@ impl CommonRecordData {
                             level: RecordLevelId,
                             line_nr: u32,
                             msg: &str) -> CommonRecordData {
-        let now = Local::now();
+        let (ts_secs, ts_nano_secs) = record_timestamp();
         CommonRecordData {
             thread_id,
             thread_name: thread_name.to_string(),
-            ts_secs: now.timestamp(),
-            ts_nano_secs: now.timestamp_subsec_nanos(),
+            ts_secs,
+            ts_nano_secs,
             level,
             trigger: RecordTrigger::Message,
             line_nr: Option::from(line_nr),
@@ -433,12 +804,12 @@ impl CommonRecordData {
                                 observer_data: &ObserverData,
                                 line_nr: u32,
                                 msg: &str) -> CommonRecordData {
-        let now = Local::now();
+        let (ts_secs, ts_nano_secs) = record_timestamp();
         CommonRecordData {
             thread_id,
             thread_name: thread_name.to_string(),
-            ts_secs: now.timestamp(),
-            ts_nano_secs: now.timestamp_subsec_nanos(),
+            ts_secs,
+            ts_nano_secs,
             level: RecordLevelId::Object,
             trigger: RecordTrigger::Message,
             line_nr: Option::from(line_nr),
@@ -461,12 +832,12 @@ impl CommonRecordData {
                              thread_name: &str,
                              observer: &ObserverData,
                              line_nr: u32) -> CommonRecordData {
-        let now = Local::now();
+        let (ts_secs, ts_nano_secs) = record_timestamp();
         CommonRecordData {
             thread_id,
             thread_name: thread_name.to_string(),
-            ts_secs: now.timestamp(),
-            ts_nano_secs: now.timestamp_subsec_nanos(),
+            ts_secs,
+            ts_nano_secs,
             level: RecordLevelId::from(*observer.kind() as u32),
             trigger: RecordTrigger::ObserverCreated,
             line_nr: Option::from(line_nr),
@@ -487,12 +858,12 @@ impl CommonRecordData {
     pub(crate) fn for_drop(thread_id: u64,
                            thread_name: &str,
                            observer: &ObserverData) -> CommonRecordData {
-        let now = Local::now();
+        let (ts_secs, ts_nano_secs) = record_timestamp();
         CommonRecordData {
             thread_id,
             thread_name: thread_name.to_string(),
-            ts_secs: now.timestamp(),
-            ts_nano_secs: now.timestamp_subsec_nanos(),
+            ts_secs,
+            ts_nano_secs,
             level: RecordLevelId::from(*observer.kind() as u32),
             trigger: RecordTrigger::ObserverDropped,
             line_nr: None,
@@ -620,9 +991,9 @@ impl<'a> Serializable<'a> for CommonRecordData {
     }
 }
 
-#[cfg(all(test, net))]
+#[cfg(all(test, feature="net"))]
 mod tests {
-    use super::{LocalRecordData, CommonRecordData, RemoteRecordData};
+    use super::{LocalRecordData, CommonRecordData, RemoteRecordData, SourceUnit};
     use crate::record::{RecordLevelId, RecordTrigger};
     use crate::record::tests::check_serialization;
 
@@ -658,6 +1029,12 @@ mod tests {
         }
     }
 
+    fn local_recdata(common_data: CommonRecordData) -> LocalRecordData {
+        LocalRecordData { common_data, source_fn: "", scope_stack: None, decoration: None,
+                          write_counts: (0, 0), context: None, attrs: None, backtrace: None,
+                          unit: SourceUnit::default(), ticks: 0, traced: false }
+    }
+
     #[test]
     fn test_serialize_record_data() {
         let mut buffer = Vec::<u8>::with_capacity(256);
@@ -670,15 +1047,9 @@ mod tests {
     #[test]
     fn test_serialize_local_record_data() {
         let mut buffer = Vec::<u8>::with_capacity(256);
-        let local_recdata_min = LocalRecordData {
-            common_data: min_recdata(),
-            source_fn: "",
-        };
+        let local_recdata_min = local_recdata(min_recdata());
         check_serialization::<LocalRecordData>(&local_recdata_min, 56, &mut buffer);
-        let local_recdata_max = LocalRecordData {
-            common_data: max_recdata(),
-            source_fn: ""
-        };
+        let local_recdata_max = local_recdata(max_recdata());
         check_serialization::<LocalRecordData>(&local_recdata_max, 111, &mut buffer);
     }
 
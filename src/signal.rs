@@ -0,0 +1,121 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+
+//! Handling of operating system signals requesting buffer flushes and file rollovers.
+//! On Unix, signal SIGUSR1 requests all memory buffers with flush condition signal to be flushed
+//! to their physical resources, signal SIGHUP additionally requests a rollover of all plain and
+//! memory mapped files.
+//! The signal handlers only record the request, it is processed by the worker thread with the
+//! next timer event.
+//! Signals are not supported on other platforms, where the functions have no effect.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+#[cfg(unix)]
+use std::sync::Once;
+
+/// Request bit for a flush of all memory buffers with flush condition signal
+pub(crate) const SIGNAL_FLUSH: u32 = 0b01;
+
+/// Request bit for a rollover of all file based resources
+pub(crate) const SIGNAL_ROLLOVER: u32 = 0b10;
+
+/// Installs the signal handlers for SIGUSR1 and SIGHUP.
+/// Handlers are installed only once per process, subsequent calls have no effect.
+#[cfg(unix)]
+pub(crate) fn install_handlers() {
+    INSTALL.call_once(|| {
+        install_handler(libc::SIGUSR1);
+        install_handler(libc::SIGHUP);
+    });
+}
+
+/// Installs the signal handlers, has no effect on platforms other than Unix.
+#[cfg(not(unix))]
+pub(crate) fn install_handlers() {}
+
+/// Returns all requests received by signal since the last call and resets them.
+///
+/// # Return values
+/// bit mask with the request bits SIGNAL_FLUSH and SIGNAL_ROLLOVER, 0 if no signal was received
+#[inline]
+pub(crate) fn take_requests() -> u32 { REQUESTS.swap(0, Ordering::AcqRel) }
+
+/// Installs the handler for the given signal.
+///
+/// # Arguments
+/// * `sig` - the signal number
+#[cfg(unix)]
+fn install_handler(sig: libc::c_int) {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(sig, &action, std::ptr::null_mut());
+    }
+}
+
+/// Signal handler, records the request associated with the signal.
+/// Only async-signal-safe operations are allowed here.
+///
+/// # Arguments
+/// * `sig` - the signal number
+#[cfg(unix)]
+extern "C" fn handle_signal(sig: libc::c_int) {
+    let request = if sig == libc::SIGHUP { SIGNAL_FLUSH | SIGNAL_ROLLOVER } else { SIGNAL_FLUSH };
+    REQUESTS.fetch_or(request, Ordering::AcqRel);
+}
+
+// requests received by signal and not yet processed by the worker thread
+static REQUESTS: AtomicU32 = AtomicU32::new(0);
+
+// guard for one-time installation of the signal handlers
+#[cfg(unix)]
+static INSTALL: Once = Once::new();
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_requests() {
+        install_handlers();
+        let _ = take_requests();
+        unsafe { libc::raise(libc::SIGUSR1); }
+        assert_eq!(SIGNAL_FLUSH, take_requests());
+        assert_eq!(0, take_requests());
+        unsafe { libc::raise(libc::SIGHUP); }
+        assert_eq!(SIGNAL_FLUSH | SIGNAL_ROLLOVER, take_requests());
+    }
+}
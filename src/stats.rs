@@ -0,0 +1,484 @@
+// -----------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// -----------------------------------------------------------------------------------------------
+
+//! Introspection counters for the logging pipeline.
+//! Output resources and the worker thread increment the counters held in process wide
+//! registries, the application takes a snapshot by calling coaly::stats(). The snapshot can be
+//! rendered in Prometheus text exposition format, to be served by the optional scrape endpoint
+//! or by an application provided HTTP server.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::output::resource::open_file_count;
+use crate::record::RecordLevelId;
+#[cfg(feature="net")]
+use std::io::{self, Read};
+#[cfg(feature="net")]
+use std::net::{SocketAddr, TcpListener, TcpStream};
+#[cfg(feature="net")]
+use std::sync::atomic::AtomicBool;
+#[cfg(feature="net")]
+use std::thread;
+#[cfg(feature="net")]
+use std::time::Duration;
+#[cfg(feature="net")]
+use crate::coalyxe;
+#[cfg(feature="net")]
+use crate::errorhandling::*;
+
+/// Counters for a single output resource
+#[derive(Default)]
+pub(crate) struct ResourceCounters {
+    written: AtomicU64,
+    dropped: AtomicU64,
+    errors: AtomicU64
+}
+impl ResourceCounters {
+    /// Counts a record successfully handed over to the resource.
+    #[inline]
+    pub(crate) fn record_written(&self) { self.written.fetch_add(1, Ordering::Relaxed); }
+
+    /// Counts a record discarded by the resource, e.g. because of throttling.
+    #[inline]
+    pub(crate) fn record_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        RECORDS_DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a failed write to the resource.
+    /// The affected record is counted as dropped, too.
+    #[inline]
+    pub(crate) fn write_failed(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        WRITE_ERRORS.fetch_add(1, Ordering::Relaxed);
+        self.record_dropped();
+    }
+}
+
+/// Statistics about a single output resource
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceStats {
+    label: String,
+    records_written: u64,
+    records_dropped: u64,
+    write_errors: u64
+}
+impl ResourceStats {
+    /// Returns the label identifying the resource.
+    /// The label consists of the resource kind followed by file name specification, pipe name
+    /// or URL, e.g. `file:app.log`.
+    #[inline]
+    pub fn label(&self) -> &str { &self.label }
+
+    /// Returns the number of records written to the resource
+    #[inline]
+    pub fn records_written(&self) -> u64 { self.records_written }
+
+    /// Returns the number of records discarded by the resource
+    #[inline]
+    pub fn records_dropped(&self) -> u64 { self.records_dropped }
+
+    /// Returns the number of failed writes to the resource
+    #[inline]
+    pub fn write_errors(&self) -> u64 { self.write_errors }
+}
+
+/// Snapshot of the logging pipeline counters.
+/// All counters are cumulative since process start.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineStats {
+    records_written: Vec<(RecordLevelId, u64)>,
+    records_dropped: u64,
+    buffer_flushes: u64,
+    rollovers: u64,
+    network_errors: u64,
+    open_files: u64,
+    files_refused: u64,
+    resources: Vec<ResourceStats>
+}
+impl PipelineStats {
+    /// Returns the number of records written for every record level
+    #[inline]
+    pub fn records_written(&self) -> &[(RecordLevelId, u64)] { &self.records_written }
+
+    /// Returns the number of records written with the given level.
+    ///
+    /// # Arguments
+    /// * `level` - the record level, group levels like Logs are not counted
+    pub fn records_written_for(&self, level: RecordLevelId) -> u64 {
+        self.records_written.iter().find(|(l, _)| *l == level).map_or(0, |(_, n)| *n)
+    }
+
+    /// Returns the number of records discarded by any output resource
+    #[inline]
+    pub fn records_dropped(&self) -> u64 { self.records_dropped }
+
+    /// Returns the number of record buffer flushes
+    #[inline]
+    pub fn buffer_flushes(&self) -> u64 { self.buffer_flushes }
+
+    /// Returns the number of output file rollovers
+    #[inline]
+    pub fn rollovers(&self) -> u64 { self.rollovers }
+
+    /// Returns the number of failed writes to network resources
+    #[inline]
+    pub fn network_errors(&self) -> u64 { self.network_errors }
+
+    /// Returns the number of plain output files currently held open
+    #[inline]
+    pub fn open_files(&self) -> u64 { self.open_files }
+
+    /// Returns the number of output files not created because the limit for open files,
+    /// given by system property max_open_files, was reached
+    #[inline]
+    pub fn files_refused(&self) -> u64 { self.files_refused }
+
+    /// Returns the statistics for all output resources, ordered by label
+    #[inline]
+    pub fn resources(&self) -> &[ResourceStats] { &self.resources }
+
+    /// Returns the statistics in Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut s = String::with_capacity(2048);
+        push_header(&mut s, "coaly_records_written_total", "Records written per level");
+        for (level, n) in &self.records_written {
+            let _ = writeln!(s, "coaly_records_written_total{{level=\"{}\"}} {}", level, n);
+        }
+        push_counter(&mut s, "coaly_records_dropped_total", "Records discarded by output resources",
+                     self.records_dropped);
+        push_counter(&mut s, "coaly_buffer_flushes_total", "Record buffer flushes",
+                     self.buffer_flushes);
+        push_counter(&mut s, "coaly_rollovers_total", "Output file rollovers", self.rollovers);
+        push_counter(&mut s, "coaly_network_errors_total", "Failed writes to network resources",
+                     self.network_errors);
+        let _ = writeln!(s, "# HELP coaly_open_files Output files currently held open");
+        let _ = writeln!(s, "# TYPE coaly_open_files gauge");
+        let _ = writeln!(s, "coaly_open_files {}", self.open_files);
+        push_counter(&mut s, "coaly_files_refused_total",
+                     "Output files refused because of the open file limit", self.files_refused);
+        let per_resource: [(&str, &str, ResourceValue); 3] = [
+            ("coaly_resource_records_written_total", "Records written per output resource",
+             |r| r.records_written),
+            ("coaly_resource_records_dropped_total", "Records discarded per output resource",
+             |r| r.records_dropped),
+            ("coaly_resource_write_errors_total", "Failed writes per output resource",
+             |r| r.write_errors)
+        ];
+        for (name, help, value) in per_resource {
+            if self.resources.is_empty() { break }
+            push_header(&mut s, name, help);
+            for r in &self.resources {
+                let _ = writeln!(s, "{}{{resource=\"{}\"}} {}", name, escaped(&r.label), value(r));
+            }
+        }
+        s
+    }
+}
+
+// accessor for a counter of resource statistics
+type ResourceValue = fn(&ResourceStats) -> u64;
+
+/// Returns a snapshot of all pipeline counters.
+pub(crate) fn snapshot() -> PipelineStats {
+    let records_written = RECORDS_WRITTEN.iter().enumerate()
+                                         .map(|(i, n)| (RecordLevelId::from(1u32 << i),
+                                                        n.load(Ordering::Relaxed)))
+                                         .collect();
+    let resources = match RESOURCES.read() {
+        Ok(res) => res.iter().map(|(label, c)| ResourceStats {
+                                 label: label.clone(),
+                                 records_written: c.written.load(Ordering::Relaxed),
+                                 records_dropped: c.dropped.load(Ordering::Relaxed),
+                                 write_errors: c.errors.load(Ordering::Relaxed)
+                             }).collect(),
+        Err(_) => Vec::new()
+    };
+    PipelineStats {
+        records_written,
+        records_dropped: RECORDS_DROPPED.load(Ordering::Relaxed),
+        buffer_flushes: BUFFER_FLUSHES.load(Ordering::Relaxed),
+        rollovers: ROLLOVERS.load(Ordering::Relaxed),
+        network_errors: NETWORK_ERRORS.load(Ordering::Relaxed),
+        open_files: open_file_count() as u64,
+        files_refused: FILES_REFUSED.load(Ordering::Relaxed),
+        resources
+    }
+}
+
+/// Counts a record processed by the worker thread.
+///
+/// # Arguments
+/// * `level` - the record level
+#[inline]
+pub(crate) fn record_written(level: RecordLevelId) {
+    let index = (level as u32).trailing_zeros() as usize;
+    if index < LEVEL_COUNT { RECORDS_WRITTEN[index].fetch_add(1, Ordering::Relaxed); }
+}
+
+/// Counts a flush of a record buffer.
+#[inline]
+pub(crate) fn buffer_flushed() { BUFFER_FLUSHES.fetch_add(1, Ordering::Relaxed); }
+
+/// Counts a rollover of an output file.
+#[inline]
+pub(crate) fn rollover_done() { ROLLOVERS.fetch_add(1, Ordering::Relaxed); }
+
+/// Counts a failed write to a network resource.
+#[cfg(feature="net")]
+#[inline]
+pub(crate) fn network_error() { NETWORK_ERRORS.fetch_add(1, Ordering::Relaxed); }
+
+/// Counts an output file not created because of the limit for open files.
+#[inline]
+pub(crate) fn file_refused() { FILES_REFUSED.fetch_add(1, Ordering::Relaxed); }
+
+/// Returns the total number of failed writes to output resources, including failures of
+/// network resources not attributed to a single record.
+#[inline]
+pub(crate) fn failure_count() -> u64 {
+    WRITE_ERRORS.load(Ordering::Relaxed) + NETWORK_ERRORS.load(Ordering::Relaxed)
+}
+
+/// Returns the total number of records processed by the worker thread.
+#[inline]
+pub(crate) fn processed_count() -> u64 {
+    RECORDS_WRITTEN.iter().map(|n| n.load(Ordering::Relaxed)).sum()
+}
+
+/// Returns the counters for the output resource with the given label.
+/// The counters are created upon first use, resources with equal labels share their counters.
+///
+/// # Arguments
+/// * `label` - the resource label
+pub(crate) fn resource_counters(label: &str) -> Arc<ResourceCounters> {
+    if let Ok(res) = RESOURCES.read() {
+        if let Some(c) = res.get(label) { return c.clone() }
+    }
+    match RESOURCES.write() {
+        Ok(mut res) => res.entry(label.to_string()).or_default().clone(),
+        Err(_) => Arc::new(ResourceCounters::default())
+    }
+}
+
+/// HTTP endpoint serving the pipeline counters in Prometheus text format.
+/// A background thread answers GET requests for paths /metrics and /, the listener is polled
+/// in non-blocking mode to notice a stop request.
+#[cfg(feature="net")]
+pub(crate) struct MetricsEndpoint {
+    // the socket address listened on
+    address: SocketAddr,
+    // stop request for the serving thread
+    stop: Arc<AtomicBool>,
+    // the serving thread
+    thread: Option<thread::JoinHandle<()>>
+}
+#[cfg(feature="net")]
+impl MetricsEndpoint {
+    /// Starts serving scrape requests on the given socket address.
+    ///
+    /// # Arguments
+    /// * `address` - the socket address to listen on
+    ///
+    /// # Errors
+    /// Returns an error structure if the socket address can't be bound
+    pub(crate) fn start(address: SocketAddr) -> Result<MetricsEndpoint, CoalyException> {
+        let listener = TcpListener::bind(address)
+                                   .and_then(|l| l.set_nonblocking(true).map(|_| l))
+                                   .map_err(|e| coalyxe!(E_METRICS_BIND_ERR, address.to_string(),
+                                                         e.to_string()))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_req = stop.clone();
+        let thread = thread::Builder::new().name(METRICS_THREAD_NAME.to_string())
+                                           .spawn(move || serve(listener, stop_req))
+                                           .map_err(|e| coalyxe!(E_METRICS_BIND_ERR,
+                                                                 address.to_string(),
+                                                                 e.to_string()))?;
+        Ok(MetricsEndpoint { address, stop, thread: Some(thread) })
+    }
+
+    /// Returns the socket address listened on
+    #[inline]
+    pub(crate) fn address(&self) -> SocketAddr { self.address }
+}
+#[cfg(feature="net")]
+impl Drop for MetricsEndpoint {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(t) = self.thread.take() { let _ = t.join(); }
+    }
+}
+
+/// Accepts and answers scrape requests until a stop is requested.
+///
+/// # Arguments
+/// * `listener` - the non-blocking listener
+/// * `stop` - the stop request
+#[cfg(feature="net")]
+fn serve(listener: TcpListener, stop: Arc<AtomicBool>) {
+    while ! stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => { let _ = answer(stream); },
+            Err(_) => thread::sleep(METRICS_POLL_INTERVAL)
+        }
+    }
+}
+
+/// Answers a single scrape request.
+///
+/// # Arguments
+/// * `stream` - the connection to the scraper
+///
+/// # Errors
+/// Returns an I/O error if the request can't be read or the response can't be written
+#[cfg(feature="net")]
+fn answer(mut stream: TcpStream) -> io::Result<()> {
+    use std::io::Write as _;
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(METRICS_IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(METRICS_IO_TIMEOUT))?;
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut request_line = request.split_whitespace();
+    let (method, path) = (request_line.next(), request_line.next());
+    let (status, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) | (Some("GET"), Some("/")) => {
+            ("200 OK", snapshot().to_prometheus())
+        },
+        _ => ("404 Not Found", String::new())
+    };
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                    Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status, body.len(), body)?;
+    stream.flush()
+}
+
+/// Appends help and type lines for a counter metric.
+fn push_header(s: &mut String, name: &str, help: &str) {
+    let _ = writeln!(s, "# HELP {} {}", name, help);
+    let _ = writeln!(s, "# TYPE {} counter", name);
+}
+
+/// Appends a counter metric without labels.
+fn push_counter(s: &mut String, name: &str, help: &str, value: u64) {
+    push_header(s, name, help);
+    let _ = writeln!(s, "{} {}", name, value);
+}
+
+/// Escapes a label value for the Prometheus text format.
+fn escaped(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// name of the thread serving scrape requests
+#[cfg(feature="net")]
+const METRICS_THREAD_NAME: &str = "coaly-metrics";
+
+// interval for polling the listener of the scrape endpoint
+#[cfg(feature="net")]
+const METRICS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// timeout for reading a scrape request and writing the response
+#[cfg(feature="net")]
+const METRICS_IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+// number of essential record levels
+const LEVEL_COUNT: usize = 11;
+
+// records processed per essential record level, indexed by level bit position
+static RECORDS_WRITTEN: [AtomicU64; LEVEL_COUNT] = [const { AtomicU64::new(0) }; LEVEL_COUNT];
+static RECORDS_DROPPED: AtomicU64 = AtomicU64::new(0);
+static WRITE_ERRORS: AtomicU64 = AtomicU64::new(0);
+static BUFFER_FLUSHES: AtomicU64 = AtomicU64::new(0);
+static ROLLOVERS: AtomicU64 = AtomicU64::new(0);
+static NETWORK_ERRORS: AtomicU64 = AtomicU64::new(0);
+static FILES_REFUSED: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    /// Counters of all output resources, keyed by resource label
+    static ref RESOURCES: RwLock<BTreeMap<String, Arc<ResourceCounters>>> =
+        RwLock::new(BTreeMap::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_stats() {
+        let before = snapshot();
+        record_written(RecordLevelId::Error);
+        record_written(RecordLevelId::Object);
+        let c = resource_counters("file:ut_\"stats\".log");
+        c.record_written();
+        c.write_failed();
+        assert!(Arc::ptr_eq(&c, &resource_counters("file:ut_\"stats\".log")));
+        let after = snapshot();
+        assert_eq!(11, after.records_written().len());
+        assert!(after.records_written_for(RecordLevelId::Error) >
+                before.records_written_for(RecordLevelId::Error));
+        assert!(after.records_written_for(RecordLevelId::Object) >
+                before.records_written_for(RecordLevelId::Object));
+        assert!(after.records_dropped() > before.records_dropped());
+        let r = after.resources().iter().find(|r| r.label().starts_with("file:ut_")).unwrap();
+        assert_eq!((1, 1, 1), (r.records_written(), r.records_dropped(), r.write_errors()));
+        let text = after.to_prometheus();
+        assert!(text.contains("# TYPE coaly_records_written_total counter\n"));
+        assert!(text.contains("coaly_records_written_total{level=\"object\"} "));
+        assert!(text.contains("# TYPE coaly_open_files gauge\n"));
+        assert!(text.contains("coaly_resource_write_errors_total{resource=\"file:ut_\\\"stats\\\".log\"} 1\n"));
+    }
+
+    #[cfg(feature="net")]
+    #[test]
+    fn test_metrics_endpoint() {
+        use std::io::Write as _;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let ep = MetricsEndpoint::start(addr).unwrap();
+        assert_eq!(addr, ep.address());
+        for (path, expected) in [("/metrics", "HTTP/1.1 200 OK"), ("/other", "HTTP/1.1 404")] {
+            let mut s = TcpStream::connect(addr).unwrap();
+            write!(s, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            s.read_to_string(&mut response).unwrap();
+            assert!(response.starts_with(expected));
+            if path == "/metrics" { assert!(response.contains("coaly_rollovers_total ")); }
+        }
+        assert!(MetricsEndpoint::start(addr).is_err());
+        drop(ep);
+    }
+}
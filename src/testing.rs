@@ -0,0 +1,238 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+
+
+//! Utilities for tests of applications using Coaly.
+//!
+//! Function deny_above returns a guard, that fails the enclosing test if a record with a level
+//! more severe than the given one was emitted while the guard was alive:
+//!
+//! ```
+//! use coaly::RecordLevelId;
+//! let _guard = coaly::testing::deny_above(RecordLevelId::Warning);
+//! // ... code under test, errors logged here cause the test to fail
+//! ```
+//!
+//! Records are checked when they are issued by the application, regardless of whether they are
+//! enabled in the configuration. By default only records issued by the thread that created the
+//! guard are checked, so that tests running in parallel don't interfere. Method all_threads
+//! extends the check to all threads of the process.
+//!
+//! If system property clock is set to mock in the configuration file, record timestamps are
+//! taken from a clock controlled by functions set_mock_time and advance_mock_time, allowing
+//! reproducible timestamps and time based rollovers in tests.
+
+use chrono::{DateTime, TimeZone};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+use crate::coalyxe;
+use crate::datetime;
+use crate::errorhandling::*;
+use crate::record::RecordLevelId;
+
+lazy_static! {
+    // checks of all guards currently alive
+    static ref DENY_CHECKS: Mutex<Vec<DenyCheck>> = Mutex::new(Vec::new());
+}
+
+// number of guards currently alive, allows to skip locking if there are none
+static ACTIVE_CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+// ID for the next guard
+static NEXT_CHECK_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Check for records above a level, associated with a guard.
+struct DenyCheck {
+    // guard ID
+    id: u64,
+    // bit mask with all denied record levels
+    denied_levels: u32,
+    // thread whose records are checked, None for all threads
+    thread_id: Option<ThreadId>,
+    // descriptions of the denied records issued so far
+    violations: Vec<String>
+}
+
+/// Guard failing the enclosing test, if records above a level have been issued during its
+/// lifetime.
+/// The test fails with a panic when the guard is dropped, unless the thread is already
+/// panicking.
+pub struct DenyGuard {
+    // guard ID
+    id: u64,
+    // the level, above which records are denied
+    level: RecordLevelId
+}
+impl DenyGuard {
+    /// Extends the check to records issued by all threads of the process.
+    /// Use with care when tests run in parallel, a record issued by another test fails this
+    /// guard's test as well.
+    pub fn all_threads(self) -> DenyGuard {
+        if let Ok(mut checks) = DENY_CHECKS.lock() {
+            if let Some(c) = checks.iter_mut().find(|c| c.id == self.id) { c.thread_id = None; }
+        }
+        self
+    }
+
+    /// Returns descriptions of all denied records issued so far, each consisting of the record
+    /// level, source file name, line number and message.
+    pub fn violations(&self) -> Vec<String> {
+        match DENY_CHECKS.lock() {
+            Ok(checks) => checks.iter().find(|c| c.id == self.id)
+                                .map(|c| c.violations.clone()).unwrap_or_default(),
+            Err(_) => Vec::new()
+        }
+    }
+}
+impl Drop for DenyGuard {
+    fn drop(&mut self) {
+        let check = match DENY_CHECKS.lock() {
+            Ok(mut checks) => checks.iter().position(|c| c.id == self.id)
+                                    .map(|pos| checks.remove(pos)),
+            Err(_) => None
+        };
+        let Some(check) = check else { return };
+        ACTIVE_CHECKS.fetch_sub(1, Ordering::SeqCst);
+        if check.violations.is_empty() || thread::panicking() { return }
+        let ex = coalyxe!(E_TEST_RECORDS_ABOVE_LEVEL, check.violations.len().to_string(),
+                          self.level.to_string(), check.violations.join("; "));
+        panic!("{}", ex.localized_message());
+    }
+}
+
+/// Creates a guard failing the enclosing test, if a record with a level more severe than the
+/// given one is issued by the current thread while the guard is alive.
+/// If a group level is specified, records more severe than its most severe member are denied.
+///
+/// # Arguments
+/// * `level` - the least severe level still allowed
+///
+/// # Return values
+/// the guard, must be bound to a variable to stay alive until the end of the test
+#[must_use]
+pub fn deny_above(level: RecordLevelId) -> DenyGuard {
+    let id = NEXT_CHECK_ID.fetch_add(1, Ordering::SeqCst);
+    let level_bits = level as u32;
+    let denied_levels = (level_bits & level_bits.wrapping_neg()) - 1;
+    if let Ok(mut checks) = DENY_CHECKS.lock() {
+        checks.push(DenyCheck { id, denied_levels, thread_id: Some(thread::current().id()),
+                                violations: Vec::new() });
+        ACTIVE_CHECKS.fetch_add(1, Ordering::SeqCst);
+    }
+    DenyGuard { id, level }
+}
+
+/// Sets the time of the mock clock used for record timestamps.
+/// Effective only if system property clock is set to mock, the clock stands still until it
+/// is set or advanced again.
+///
+/// # Arguments
+/// * `ts` - the timestamp for all subsequent records
+pub fn set_mock_time<Tz: TimeZone>(ts: &DateTime<Tz>) {
+    datetime::set_mock_nanos(ts.timestamp() * 1_000_000_000 + ts.timestamp_subsec_nanos() as i64);
+}
+
+/// Advances the mock clock used for record timestamps.
+/// Effective only if system property clock is set to mock.
+///
+/// # Arguments
+/// * `span` - the time span to add to the mock clock
+pub fn advance_mock_time(span: Duration) {
+    datetime::advance_mock_nanos(span.as_nanos() as i64);
+}
+
+/// Indicates whether at least one guard is alive.
+/// Records must be formatted in this case, even if no output resource accepts their level.
+#[inline]
+pub(crate) fn checks_active() -> bool { ACTIVE_CHECKS.load(Ordering::Relaxed) != 0 }
+
+/// Records a log or trace record issued by the application for all guards denying its level.
+/// Called for every record before it is handed over to the worker thread.
+///
+/// # Arguments
+/// * `level` - the record level
+/// * `file_name` - the name of the source code file, where the message was issued
+/// * `line_nr` - the line number in the source code file, where the message was issued
+/// * `msg` - the log or trace message
+#[inline]
+pub(crate) fn check_record(level: RecordLevelId,
+                           file_name: &str,
+                           line_nr: u32,
+                           msg: &str) {
+    if ACTIVE_CHECKS.load(Ordering::Relaxed) == 0 { return }
+    let Ok(mut checks) = DENY_CHECKS.lock() else { return };
+    let thread_id = thread::current().id();
+    for check in checks.iter_mut() {
+        if check.denied_levels & level as u32 == 0 { continue }
+        if check.thread_id.is_some_and(|t| t != thread_id) { continue }
+        check.violations.push(format!("{} {}:{} {}", level, file_name, line_nr, msg));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // serializes unit tests activating testing guards and tests relying on their absence
+    pub(crate) static CHECKS_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_deny_above() {
+        let _lock = CHECKS_LOCK.lock();
+        let guard = deny_above(RecordLevelId::Warning);
+        check_record(RecordLevelId::Warning, "a.rs", 1, "tolerated");
+        check_record(RecordLevelId::Info, "a.rs", 2, "tolerated");
+        assert!(guard.violations().is_empty());
+        // records from other threads are ignored by default
+        thread::spawn(|| check_record(RecordLevelId::Error, "b.rs", 3, "other thread"))
+              .join().unwrap();
+        assert!(guard.violations().is_empty());
+        check_record(RecordLevelId::Error, "a.rs", 4, "failure");
+        assert_eq!(vec!("error a.rs:4 failure".to_string()), guard.violations());
+        let result = std::panic::catch_unwind(move || drop(guard));
+        assert!(result.is_err());
+        // records from all threads
+        let guard = deny_above(RecordLevelId::Error).all_threads();
+        thread::spawn(|| check_record(RecordLevelId::Critical, "b.rs", 5, "other thread"))
+              .join().unwrap();
+        assert_eq!(1, guard.violations().len());
+        let result = std::panic::catch_unwind(move || drop(guard));
+        assert!(result.is_err());
+        // no violation, no panic
+        let guard = deny_above(RecordLevelId::Emergency);
+        check_record(RecordLevelId::Emergency, "a.rs", 6, "tolerated");
+        drop(guard);
+    }
+}
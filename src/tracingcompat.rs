@@ -0,0 +1,243 @@
+// ---------------------------------------------------------------------------------------------
+// Coaly - context aware logging and tracing system
+//
+// Copyright (c) 2022, Frank Sommer.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// * Redistributions of source code must retain the above copyright notice, this
+//   list of conditions and the following disclaimer.
+//
+// * Redistributions in binary form must reproduce the above copyright notice,
+//   this list of conditions and the following disclaimer in the documentation
+//   and/or other materials provided with the distribution.
+//
+// * Neither the name of the copyright holder nor the names of its
+//   contributors may be used to endorse or promote products derived from
+//   this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// ---------------------------------------------------------------------------------------------
+
+//! Integration with the tracing ecosystem.
+//! Provides a layer for subscribers of the tracing crate, that maps tracing spans to Coaly
+//! observers and tracing events to Coaly records.
+//! Spans are mapped as follows:
+//! * spans with a field named `value` - user defined object observer with the span name and the
+//!   field's value
+//! * all other spans - function observer with the span name, all span fields as arguments
+//! 
+//! Entering a span is treated like the creation of an observer, exiting the span like dropping
+//! it. Hence mode changes configured for observers are triggered by spans as well.
+//! Event levels are mapped as follows:
+//! * tracing::Level::ERROR - Coaly level error
+//! * tracing::Level::WARN - Coaly level warning
+//! * tracing::Level::INFO - Coaly level info
+//! * tracing::Level::DEBUG - Coaly level debug
+//! * tracing::Level::TRACE - Coaly level debug
+//! 
+//! Event levels may be changed per event target using adapter mappings in the configuration
+//! file, events may also be assigned to a Coaly unit that way.
+
+use std::fmt::Debug;
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+use crate::agent;
+use crate::config::adapter::*;
+use crate::observer::ObserverData;
+use crate::record::RecordLevelId;
+use crate::record::recorddata::SourceUnit;
+
+/// Layer for tracing subscribers, forwarding spans and events to the Coaly agent.
+/// Must be combined with a subscriber supporting span lookup, e.g. the registry from the
+/// tracing-subscriber crate.
+#[derive(Default)]
+pub struct CoalyLayer;
+impl CoalyLayer {
+    /// Creates a tracing layer forwarding spans and events to Coaly.
+    pub fn new() -> CoalyLayer { CoalyLayer }
+}
+impl<S> Layer<S> for CoalyLayer where S: Subscriber + for<'a> LookupSpan<'a> {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = FieldCollector::default();
+            attrs.record(&mut fields);
+            let metadata = attrs.metadata();
+            let file_name = metadata.file().unwrap_or(UNKNOWN_SOURCE);
+            let obs_data = match fields.value {
+                Some(v) => ObserverData::for_obj(metadata.name(), Some(&v), file_name),
+                None => {
+                    let args = fields.to_string();
+                    let args = if args.is_empty() { None } else { Some(args.as_str()) };
+                    ObserverData::for_fn(metadata.name(), args, file_name)
+                }
+            };
+            let line_nr = metadata.line().unwrap_or(0);
+            span.extensions_mut().insert(SpanObserver(obs_data, line_nr));
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(obs) = span.extensions().get::<SpanObserver>() {
+                agent::observer_created(&obs.0, obs.1);
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(obs) = span.extensions().get::<SpanObserver>() {
+                agent::observer_dropped(&obs.0);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = FieldCollector::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let file_name = metadata.file()
+                                .or_else(|| metadata.module_path())
+                                .unwrap_or(UNKNOWN_SOURCE);
+        let line_nr = metadata.line().unwrap_or(0);
+        let unit = SourceUnit::new(metadata.module_path().unwrap_or_default(), "");
+        agent::write_adapted(metadata.target(), adapter_level(metadata.level()),
+                             coaly_level(metadata.level()), file_name, line_nr, unit,
+                             &fields.to_string());
+    }
+}
+
+/// Observer descriptor attached to a tracing span.
+struct SpanObserver(ObserverData, u32);
+
+/// Visitor collecting the fields of a tracing span or event.
+#[derive(Default)]
+struct FieldCollector {
+    // the value of the field named message
+    message: Option<String>,
+    // the value of the field named value
+    value: Option<String>,
+    // all other fields, formatted as name=value
+    others: Vec<String>
+}
+impl FieldCollector {
+    /// Stores a field value
+    /// 
+    /// # Arguments
+    /// * `field` - the field
+    /// * `value` - the field value, formatted as string
+    fn store(&mut self, field: &Field, value: String) {
+        match field.name() {
+            FIELD_MESSAGE => self.message = Some(value),
+            FIELD_VALUE => self.value = Some(value),
+            name => self.others.push(format!("{}={}", name, value))
+        }
+    }
+}
+impl std::fmt::Display for FieldCollector {
+    /// Returns the message field followed by all other fields, value field excluded
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = self.message.clone().unwrap_or_default();
+        for field in &self.others {
+            if ! s.is_empty() { s.push(' '); }
+            s.push_str(field);
+        }
+        write!(f, "{}", s)
+    }
+}
+impl Visit for FieldCollector {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.store(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.store(field, format!("{:?}", value));
+    }
+}
+
+/// Returns the Coaly record level corresponding to a tracing level.
+/// 
+/// # Arguments
+/// * `level` - the tracing level
+fn coaly_level(level: &Level) -> RecordLevelId {
+    match *level {
+        Level::ERROR => RecordLevelId::Error,
+        Level::WARN => RecordLevelId::Warning,
+        Level::INFO => RecordLevelId::Info,
+        _ => RecordLevelId::Debug
+    }
+}
+
+/// Returns the adapter level bit value corresponding to a tracing level.
+/// 
+/// # Arguments
+/// * `level` - the tracing level
+fn adapter_level(level: &Level) -> u32 {
+    match *level {
+        Level::ERROR => ADAPTER_LEVEL_ERROR,
+        Level::WARN => ADAPTER_LEVEL_WARN,
+        Level::INFO => ADAPTER_LEVEL_INFO,
+        Level::DEBUG => ADAPTER_LEVEL_DEBUG,
+        _ => ADAPTER_LEVEL_TRACE
+    }
+}
+
+// source file name used, if the tracing metadata doesn't provide file name and module path
+const UNKNOWN_SOURCE: &str = "unknown";
+
+// names of tracing fields with special meaning
+const FIELD_MESSAGE: &str = "message";
+const FIELD_VALUE: &str = "value";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Tests level mapping
+    fn test_coaly_level() {
+        assert_eq!(RecordLevelId::Error, coaly_level(&Level::ERROR));
+        assert_eq!(RecordLevelId::Warning, coaly_level(&Level::WARN));
+        assert_eq!(RecordLevelId::Info, coaly_level(&Level::INFO));
+        assert_eq!(RecordLevelId::Debug, coaly_level(&Level::DEBUG));
+        assert_eq!(RecordLevelId::Debug, coaly_level(&Level::TRACE));
+    }
+
+    #[test]
+    /// Tests adapter level mapping
+    fn test_adapter_level() {
+        assert_eq!(ADAPTER_LEVEL_ERROR, adapter_level(&Level::ERROR));
+        assert_eq!(ADAPTER_LEVEL_WARN, adapter_level(&Level::WARN));
+        assert_eq!(ADAPTER_LEVEL_INFO, adapter_level(&Level::INFO));
+        assert_eq!(ADAPTER_LEVEL_DEBUG, adapter_level(&Level::DEBUG));
+        assert_eq!(ADAPTER_LEVEL_TRACE, adapter_level(&Level::TRACE));
+    }
+
+    #[test]
+    /// Tests formatting of collected fields
+    fn test_field_collector() {
+        let mut fields = FieldCollector::default();
+        assert_eq!("", fields.to_string());
+        fields.others.push(String::from("count=3"));
+        assert_eq!("count=3", fields.to_string());
+        fields.message = Some(String::from("started"));
+        fields.value = Some(String::from("42"));
+        fields.others.push(String::from("done=false"));
+        assert_eq!("started count=3 done=false", fields.to_string());
+    }
+}
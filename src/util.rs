@@ -38,41 +38,237 @@ extern crate libc;
 #[cfg(windows)]
 extern crate winapi;
 
-use regex::Regex;
+use std::fs::{File, Metadata, OpenOptions};
+#[cfg(windows)]
+use std::os::windows::fs::OpenOptionsExt;
 use std::path::Path;
 use std::process;
 use std::string::FromUtf8Error;
 use std::thread;
-use crate::record::originator::OriginatorInfo;
+use crate::context;
+use crate::record::originator::{AUTH_TOKEN_ENV_VAR, OriginatorInfo};
 
 #[cfg(unix)]
 pub(crate) const DIR_SEP: char = '/';
 #[cfg(windows)]
 pub(crate) const DIR_SEP: char = '\\';
 
+// Windows file share modes, allowing other processes to read, write, rename or delete open files
+#[cfg(windows)]
+const FILE_SHARE_READ: u32 = 0x00000001;
+#[cfg(windows)]
+const FILE_SHARE_WRITE: u32 = 0x00000002;
+#[cfg(windows)]
+const FILE_SHARE_DELETE: u32 = 0x00000004;
 
-/// Escapes all regular expression special characters in the specified string.
-pub(crate) fn regex_escaped_str(s: &str) -> String {
-    let mut esc_str = String::with_capacity(s.len() * 2);
+
+/// Escapes the specified string for use as JSON string value.
+/// Quotation marks, backslashes and control characters are escaped, the enclosing
+/// quotation marks are not added.
+pub(crate) fn json_escaped_str(s: &str) -> String {
+    let mut esc_str = String::with_capacity(s.len() + 8);
     for ch in s.chars() {
         match ch {
-            '.' | '[' | ']' | '{' | '}' | '(' | ')' |
-            '*' | '+' | '?' | '|' | '^' | '$' | '\\' | '-' => {
-                esc_str.push('\\');
-                esc_str.push(ch);
-            },
-            _ => { esc_str.push(ch); }
+            '"' => esc_str.push_str("\\\""),
+            '\\' => esc_str.push_str("\\\\"),
+            '\n' => esc_str.push_str("\\n"),
+            '\r' => esc_str.push_str("\\r"),
+            '\t' => esc_str.push_str("\\t"),
+            c if (c as u32) < 0x20 => esc_str.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => esc_str.push(ch)
         }
     }
     esc_str
 }
 
+/// Returns the edit distance between two strings, i.e. the minimum number of single character
+/// insertions, deletions, substitutions or transpositions of adjacent characters needed to
+/// change one string into the other.
+/// 
+/// # Arguments
+/// * `a` - the first string
+/// * `b` - the second string
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() { row[0] = i; }
+    for (j, col) in d[0].iter_mut().enumerate() { *col = j; }
+    for i in 1 ..= a.len() {
+        for j in 1 ..= b.len() {
+            let subst_cost = if a[i-1] == b[j-1] { 0 } else { 1 };
+            let mut dist = (d[i-1][j-1] + subst_cost).min(d[i-1][j] + 1).min(d[i][j-1] + 1);
+            if i > 1 && j > 1 && a[i-1] == b[j-2] && a[i-2] == b[j-1] {
+                dist = dist.min(d[i-2][j-2] + 1);
+            }
+            d[i][j] = dist;
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Returns options for opening output files.
+/// On Windows, the file is opened in a share mode allowing other processes - like virus
+/// scanners or indexers - as well as Coaly itself to read, rename or delete the file while it
+/// is open. On other platforms, plain default options are returned.
+pub(crate) fn shared_open_options() -> OpenOptions {
+    #[cfg(windows)]
+    {
+        let mut opts = OpenOptions::new();
+        opts.share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE);
+        opts
+    }
+    #[cfg(not(windows))]
+    OpenOptions::new()
+}
+
+/// Indicates whether the specified character may be used in file names.
+/// Path separators and the null character are not allowed.
+#[cfg(unix)]
+pub(crate) fn is_valid_file_name_char(ch: char) -> bool { ch != '/' && ch != '\0' }
+
+/// Indicates whether the specified character may be used in file names.
+/// Control characters and all characters reserved by Windows are not allowed.
+#[cfg(windows)]
+pub(crate) fn is_valid_file_name_char(ch: char) -> bool {
+    ! ch.is_control() && ! matches!(ch, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*')
+}
+
+/// Acquires an exclusive advisory lock on the specified file, blocking until the lock is granted.
+/// The lock is held until function unlock_file is called or the file is closed.
+///
+/// # Arguments
+/// * `f` - the open file to lock
+///
+/// # Errors
+/// Returns an I/O error if the lock can't be acquired
+#[cfg(unix)]
+pub(crate) fn lock_file(f: &File) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    loop {
+        if unsafe { libc::flock(f.as_raw_fd(), libc::LOCK_EX) } == 0 { return Ok(()) }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted { return Err(err) }
+    }
+}
+
+/// Acquires an exclusive lock on the specified file, blocking until the lock is granted.
+/// The lock is held until function unlock_file is called or the file is closed.
+///
+/// # Arguments
+/// * `f` - the open file to lock
+///
+/// # Errors
+/// Returns an I/O error if the lock can't be acquired
+#[cfg(windows)]
+pub(crate) fn lock_file(f: &File) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    let mut ovl: winapi::um::minwinbase::OVERLAPPED = unsafe { std::mem::zeroed() };
+    let rc = unsafe {
+                 winapi::um::fileapi::LockFileEx(f.as_raw_handle() as _,
+                                                 winapi::um::minwinbase::LOCKFILE_EXCLUSIVE_LOCK,
+                                                 0, u32::MAX, u32::MAX, &mut ovl)
+             };
+    if rc == 0 { return Err(std::io::Error::last_os_error()) }
+    Ok(())
+}
+
+/// Releases a lock acquired with function lock_file.
+///
+/// # Arguments
+/// * `f` - the locked file
+#[cfg(unix)]
+pub(crate) fn unlock_file(f: &File) {
+    use std::os::unix::io::AsRawFd;
+    unsafe { libc::flock(f.as_raw_fd(), libc::LOCK_UN); }
+}
+
+/// Releases a lock acquired with function lock_file.
+///
+/// # Arguments
+/// * `f` - the locked file
+#[cfg(windows)]
+pub(crate) fn unlock_file(f: &File) {
+    use std::os::windows::io::AsRawHandle;
+    let mut ovl: winapi::um::minwinbase::OVERLAPPED = unsafe { std::mem::zeroed() };
+    unsafe {
+        winapi::um::fileapi::UnlockFileEx(f.as_raw_handle() as _, 0, u32::MAX, u32::MAX,
+                                          &mut ovl);
+    }
+}
+
+/// Indicates whether the meta data of an open file and the meta data read from a path name
+/// belong to the same file.
+///
+/// # Arguments
+/// * `open_md` - the meta data of the open file
+/// * `path_md` - the meta data read from the path name
+#[cfg(unix)]
+pub(crate) fn is_same_file(open_md: &Metadata, path_md: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    open_md.dev() == path_md.dev() && open_md.ino() == path_md.ino()
+}
+
+/// Indicates whether the meta data of an open file and the meta data read from a path name
+/// belong to the same file.
+/// File IDs aren't available on this platform, a file replaced by another process is detected
+/// because it is smaller than the open file.
+///
+/// # Arguments
+/// * `open_md` - the meta data of the open file
+/// * `path_md` - the meta data read from the path name
+#[cfg(not(unix))]
+pub(crate) fn is_same_file(open_md: &Metadata, path_md: &Metadata) -> bool {
+    open_md.len() <= path_md.len()
+}
+
+/// Returns the number of bytes available to the process on the file system holding the
+/// specified path.
+///
+/// # Arguments
+/// * `path` - the path name, must exist
+///
+/// # Return values
+/// the number of free bytes, **None** if the file system can't be queried
+#[cfg(unix)]
+pub(crate) fn free_disk_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut st) } != 0 { return None }
+    #[allow(clippy::unnecessary_cast)]
+    Some(st.f_bavail as u64 * st.f_frsize as u64)
+}
+
+/// Returns the number of bytes available to the process on the disk holding the specified
+/// path.
+///
+/// # Arguments
+/// * `path` - the path name, must exist
+///
+/// # Return values
+/// the number of free bytes, **None** if the disk can't be queried
+#[cfg(windows)]
+pub(crate) fn free_disk_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    let w_path: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut avail: winapi::um::winnt::ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+    let rc = unsafe {
+                 winapi::um::fileapi::GetDiskFreeSpaceExW(w_path.as_ptr(), &mut avail,
+                                                          std::ptr::null_mut(),
+                                                          std::ptr::null_mut())
+             };
+    if rc == 0 { return None }
+    Some(unsafe { *avail.QuadPart() })
+}
+
 /// Converts a string containing a size specification to an integer value.
 /// The string must contain digits only plus an optional unit specifier character at the end.
 /// Allowed unit specifier are k, m or g for Kilobyte, Megabyte or Gigabyte.
 pub(crate) fn parse_size_str(size_str: &str) -> Option<usize> {
-    let pattern = Regex::new(SIZE_STR_PATTERN).unwrap();
-    if ! pattern.is_match(size_str) { return None }
+    let num_str = size_str.strip_suffix(|c: char| "kKmMgG".contains(c)).unwrap_or(size_str);
+    let num_str = num_str.trim_end();
+    if num_str.is_empty() || ! num_str.bytes().all(|b| b.is_ascii_digit()) { return None }
     let mut num: usize = 0;
     for ch in size_str.chars() {
         match ch {
@@ -89,6 +285,32 @@ pub(crate) fn parse_size_str(size_str: &str) -> Option<usize> {
     Some(num)
 }
 
+/// Calculates the CRC-32 checksum of the given data, using the polynomial from IEEE 802.3.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in data {
+        crc = CRC32_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Returns the lookup table for CRC-32 checksum calculation.
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
 /// Returns ID and name of the current process.
 /// If process name cannot be determined, returns PID instead.
 /// These values are used to replace the variables $ProcessId and $ProcessName inside record
@@ -137,8 +359,12 @@ pub(crate) fn host_info() -> (String, String, String) {
 pub(crate) fn originator_info() -> OriginatorInfo {
     let (pid, pname) = process_info();
     let (host, ip4, ip6) = host_info();
-    if ! ip6.is_empty() { return OriginatorInfo::new(pid, &pname, &host, &ip6) }
-    OriginatorInfo::new(pid, &pname, &host, &ip4)
+    let ip = if ip6.is_empty() { ip4 } else { ip6 };
+    let mut orig_info = OriginatorInfo::new(pid, &pname, &host, &ip);
+    let ctx = context::run_context();
+    orig_info.set_run_context(ctx.run_id(), ctx.parent_run_id(), ctx.trace_id());
+    if let Ok(token) = std::env::var(AUTH_TOKEN_ENV_VAR) { orig_info.set_auth_token(&token); }
+    orig_info
 }
 
 #[cfg(unix)]
@@ -175,14 +401,18 @@ fn process_name(pid: u32) -> String {
 /// #Return values
 /// The local IP address, or empty string, if protocol version is not active
 fn ip_address(ip_version: u32) -> String {
-    let ip_pattern = Regex::new(IP_ROUTE_PATTERN).unwrap();
     let cmd = format!("ip -{} route|grep src", ip_version);
     let ip_route_info = shell_cmd(&cmd).unwrap_or_default();
-    let mut ip_addr = String::from("");
-    if let Some(caps) = ip_pattern.captures(&ip_route_info) {
-        if let Some(m) = caps.get(1) { ip_addr = m.as_str().to_string() }
-    }
-    ip_addr
+    // use the word following the last keyword src in the first route containing one
+    ip_route_info.lines().find_map(|line| {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let trailing_space = line.ends_with(char::is_whitespace);
+        (1 .. words.len()).rev()
+                          .find(|&i| words[i] == "src" &&
+                                     (i + 2 < words.len() ||
+                                      (i + 2 == words.len() && trailing_space)))
+                          .map(|i| words[i + 1].to_string())
+    }).unwrap_or_default()
 }
 
 /// Executes the given Unix shell command and returns the standard output.
@@ -230,9 +460,7 @@ fn get_thread_id() -> usize {
     unsafe { winapi::um::processthreadsapi::GetCurrentThreadId() as usize }
 }
 
-const SIZE_STR_PATTERN: &str = "^[0-9]+\\s*[kKmMgG]{0,1}$";
-
-const IP_ROUTE_PATTERN: &str = r".*\s+src\s+(.*?)\s+.*";
+const CRC32_TABLE: [u32; 256] = crc32_table();
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -32,33 +32,60 @@
 
 //! Coaly placeholder variables, can be used in format and file name descriptors.
 
-use regex::Regex;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::collections::btree_map::Iter;
 use std::fmt::{Debug, Formatter};
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+lazy_static! {
+    /// Values of the environment variables read at record time, with the instant they were read
+    static ref RECORD_ENV_CACHE: Mutex<HashMap<String, (Instant, Option<String>)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Time in milliseconds a cached environment variable value is reused, 0 disables the cache
+static RECORD_ENV_TTL: AtomicU64 = AtomicU64::new(0);
 
 /// Names of all supported placeholder variables
 pub(crate) const VAR_NAME_APP_ID: &str = "AppId";
 pub(crate) const VAR_NAME_APP_NAME: &str = "AppName";
+pub(crate) const VAR_NAME_ATTR: &str = "Attr";
+pub(crate) const VAR_NAME_ATTRS: &str = "Attrs";
+pub(crate) const VAR_NAME_BACKTRACE: &str = "Backtrace";
+pub(crate) const VAR_NAME_CONTEXT: &str = "Context";
 pub(crate) const VAR_NAME_DATE: &str = "Date";
 pub(crate) const VAR_NAME_ENV: &str = "Env";
+pub(crate) const VAR_NAME_ENV_FIELDS: &str = "EnvFields";
+pub(crate) const VAR_NAME_FUNCTION: &str = "Function";
 pub(crate) const VAR_NAME_HOST_NAME: &str = "HostName";
 pub(crate) const VAR_NAME_IP_ADDR: &str = "IpAddress";
 pub(crate) const VAR_NAME_LEVEL: &str = "Level";
 pub(crate) const VAR_NAME_LEVEL_ID: &str = "LevelId";
 pub(crate) const VAR_NAME_MESSAGE: &str = "Message";
+pub(crate) const VAR_NAME_MODULE_PATH: &str = "ModulePath";
 pub(crate) const VAR_NAME_OBSERVER_NAME: &str = "ObserverName";
 pub(crate) const VAR_NAME_OBSERVER_VALUE: &str = "ObserverValue";
+pub(crate) const VAR_NAME_PARENT_RUN_ID: &str = "ParentRunId";
 pub(crate) const VAR_NAME_PROCESS_ID: &str = "ProcessId";
 pub(crate) const VAR_NAME_PROCESS_NAME: &str = "ProcessName";
+pub(crate) const VAR_NAME_RECORD_ENV: &str = "RecordEnv";
+pub(crate) const VAR_NAME_RUN_ID: &str = "RunId";
+pub(crate) const VAR_NAME_SCOPE_STACK: &str = "ScopeStack";
 pub(crate) const VAR_NAME_PURE_SOURCE_FILE_NAME: &str = "PureSourceFileName";
 pub(crate) const VAR_NAME_SOURCE_FILE_NAME: &str = "SourceFileName";
 pub(crate) const VAR_NAME_SOURCE_LINE_NR: &str = "SourceLineNr";
 pub(crate) const VAR_NAME_THREAD_ID: &str = "ThreadId";
 pub(crate) const VAR_NAME_THREAD_NAME: &str = "ThreadName";
+pub(crate) const VAR_NAME_THREAD_RECS_SUPPRESSED: &str = "ThreadRecordsSuppressed";
+pub(crate) const VAR_NAME_THREAD_RECS_WRITTEN: &str = "ThreadRecordsWritten";
 pub(crate) const VAR_NAME_TIME: &str = "Time";
+pub(crate) const VAR_NAME_TIME_SLICE: &str = "TimeSlice";
 pub(crate) const VAR_NAME_TIME_STAMP: &str = "TimeStamp";
+pub(crate) const VAR_NAME_TRACE_ID: &str = "TraceId";
+pub(crate) const VAR_NAME_UNIT_FIELDS: &str = "UnitFields";
 
 /// Variables that may be used in record formats and/or file names inside the configuration file.
 #[derive(Clone, Eq, Hash, PartialEq)]
@@ -67,10 +94,22 @@ pub(crate) enum Variable {
     ApplicationId,
     // user defined application name
     ApplicationName,
+    // attribute of the observers active in the issuing thread
+    Attr(String),
+    // all attributes of the observers active in the issuing thread as key=value pairs
+    Attrs,
+    // short backtrace of the call site, captured for the configured record levels only
+    Backtrace,
+    // entry of the issuing thread's context map
+    Context(String),
     // current date
     Date,
-    // environment variable
+    // environment variable, value captured upon application start
     Env(String),
+    // all environment variables configured for record enrichment as name=value pairs
+    EnvFields,
+    // name of the function that issued the log or trace, determined by the log macros
+    Function,
     // host name
     HostName,
     // host's IP address (V4 or V6)
@@ -81,16 +120,26 @@ pub(crate) enum Variable {
     LevelId,
     // log or trace message issued by the application
     Message,
+    // module path of the source code that issued the log or trace, determined by the log macros
+    ModulePath,
     // name of the observer struct that triggered the event
     ObserverName,
     // user defined value of the observer struct that triggered the event
     ObserverValue,
+    // run ID of the parent process, handed over in environment variable COALY_PARENT_CONTEXT
+    ParentRunId,
     // process ID of the application
     ProcessId,
     // process (executable) name of the application
     ProcessName,
     // name of the source file that issued the log or trace, without path
     PureSourceFileName,
+    // environment variable, value read for every record; not usable in configuration file
+    RecordEnv(String),
+    // random ID of the process run
+    RunId,
+    // names of all active observers of the issuing thread, with separator and maximum depth
+    ScopeStack(String, usize),
     // name of the source file that issued the log or trace, including path beginning under src
     SourceFileName, 
     // line number in the source file, where a log or trace message was issued
@@ -99,37 +148,78 @@ pub(crate) enum Variable {
     ThreadId,
     // user defined name of the thread that issued the log or trace message, defaults to thread ID
     ThreadName,
+    // number of records of the issuing thread suppressed by the output mode since thread start
+    ThreadRecordsSuppressed,
+    // number of records of the issuing thread written since thread start, including this one
+    ThreadRecordsWritten,
     // current time
     Time,
+    // start of the current time slice, with slice interval in minutes; file names only
+    TimeSlice(u32),
     // current date and time
-    TimeStamp
+    TimeStamp,
+    // ID shared by all processes of a process tree, the run ID of the root process
+    TraceId,
+    // fields attached by a mode change for the innermost matching unit as name=value pairs
+    UnitFields
 }
 impl Debug for Variable {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if let Variable::Env(v) = self {
             return write!(f, "{}[{}]", VAR_NAME_ENV, v)
         }
+        if let Variable::RecordEnv(v) = self {
+            return write!(f, "{}[{}]", VAR_NAME_RECORD_ENV, v)
+        }
+        if let Variable::Attr(k) = self {
+            return write!(f, "{}[{}]", VAR_NAME_ATTR, k)
+        }
+        if let Variable::Context(k) = self {
+            return write!(f, "{}[{}]", VAR_NAME_CONTEXT, k)
+        }
+        if let Variable::ScopeStack(sep, depth) = self {
+            return write!(f, "{}[{},{}]", VAR_NAME_SCOPE_STACK, sep, depth)
+        }
+        if let Variable::TimeSlice(interval) = self {
+            return write!(f, "{}[{}]", VAR_NAME_TIME_SLICE, interval)
+        }
         write!(f, "{}", match self {
             Variable::ApplicationId => VAR_NAME_APP_ID,
             Variable::ApplicationName => VAR_NAME_APP_NAME,
+            Variable::Attr(_) => "",
+            Variable::Attrs => VAR_NAME_ATTRS,
+            Variable::Backtrace => VAR_NAME_BACKTRACE,
+            Variable::Context(_) => "",
             Variable::Date => VAR_NAME_DATE,
             Variable::Env(_) => "",
+            Variable::EnvFields => VAR_NAME_ENV_FIELDS,
+            Variable::Function => VAR_NAME_FUNCTION,
             Variable::HostName => VAR_NAME_HOST_NAME,
             Variable::IpAddress => VAR_NAME_IP_ADDR,
             Variable::Level => VAR_NAME_LEVEL,
             Variable::LevelId => VAR_NAME_LEVEL_ID,
             Variable::Message => VAR_NAME_MESSAGE,
+            Variable::ModulePath => VAR_NAME_MODULE_PATH,
             Variable::ObserverName => VAR_NAME_OBSERVER_NAME,
             Variable::ObserverValue => VAR_NAME_OBSERVER_VALUE,
+            Variable::ParentRunId => VAR_NAME_PARENT_RUN_ID,
             Variable::ProcessId => VAR_NAME_PROCESS_ID,
             Variable::ProcessName => VAR_NAME_PROCESS_NAME,
             Variable::PureSourceFileName => VAR_NAME_PURE_SOURCE_FILE_NAME,
+            Variable::RecordEnv(_) => "",
+            Variable::RunId => VAR_NAME_RUN_ID,
+            Variable::ScopeStack(_, _) => "",
             Variable::SourceFileName => VAR_NAME_SOURCE_FILE_NAME, 
             Variable::SourceLineNr => VAR_NAME_SOURCE_LINE_NR,
             Variable::ThreadId => VAR_NAME_THREAD_ID,
             Variable::ThreadName => VAR_NAME_THREAD_NAME,
+            Variable::ThreadRecordsSuppressed => VAR_NAME_THREAD_RECS_SUPPRESSED,
+            Variable::ThreadRecordsWritten => VAR_NAME_THREAD_RECS_WRITTEN,
             Variable::Time => VAR_NAME_TIME,
-            Variable::TimeStamp => VAR_NAME_TIME_STAMP
+            Variable::TimeSlice(_) => "",
+            Variable::TimeStamp => VAR_NAME_TIME_STAMP,
+            Variable::TraceId => VAR_NAME_TRACE_ID,
+            Variable::UnitFields => VAR_NAME_UNIT_FIELDS
         })
     }
 }
@@ -137,29 +227,54 @@ impl FromStr for Variable {
     type Err = bool;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(grps) = Regex::new(ENV_VAR_PATTERN).unwrap().captures(s) {
-            return Ok(Variable::Env(grps.get(1).unwrap().as_str().to_string()))
+        let whole_arg = |var_name: &str| {
+            s.strip_prefix(var_name)?.strip_prefix('[')?.strip_suffix(']')
+        };
+        if let Some(name) = whole_arg(VAR_NAME_ENV) {
+            return Ok(Variable::Env(name.to_string()))
+        }
+        if let Some(key) = whole_arg(VAR_NAME_ATTR) {
+            return Ok(Variable::Attr(key.to_string()))
+        }
+        if let Some(key) = whole_arg(VAR_NAME_CONTEXT) {
+            return Ok(Variable::Context(key.to_string()))
+        }
+        if let Some((arg, _)) = bracketed_arg(s, VAR_NAME_SCOPE_STACK) {
+            return Ok(scope_stack_arg_var(arg))
         }
         match s {
             VAR_NAME_APP_ID => Ok(Variable::ApplicationId),
             VAR_NAME_APP_NAME => Ok(Variable::ApplicationName),
+            VAR_NAME_ATTRS => Ok(Variable::Attrs),
+            VAR_NAME_BACKTRACE => Ok(Variable::Backtrace),
             VAR_NAME_DATE => Ok(Variable::Date),
+            VAR_NAME_ENV_FIELDS => Ok(Variable::EnvFields),
+            VAR_NAME_FUNCTION => Ok(Variable::Function),
             VAR_NAME_HOST_NAME => Ok(Variable::HostName),
             VAR_NAME_IP_ADDR => Ok(Variable::IpAddress),
             VAR_NAME_LEVEL => Ok(Variable::Level),
             VAR_NAME_LEVEL_ID => Ok(Variable::LevelId),
             VAR_NAME_MESSAGE => Ok(Variable::Message),
+            VAR_NAME_MODULE_PATH => Ok(Variable::ModulePath),
             VAR_NAME_OBSERVER_NAME => Ok(Variable::ObserverName),
             VAR_NAME_OBSERVER_VALUE => Ok(Variable::ObserverValue),
+            VAR_NAME_PARENT_RUN_ID => Ok(Variable::ParentRunId),
             VAR_NAME_PROCESS_ID => Ok(Variable::ProcessId),
             VAR_NAME_PROCESS_NAME => Ok(Variable::ProcessName),
             VAR_NAME_PURE_SOURCE_FILE_NAME => Ok(Variable::PureSourceFileName),
+            VAR_NAME_RUN_ID => Ok(Variable::RunId),
+            VAR_NAME_SCOPE_STACK => Ok(scope_stack_var(None, None)),
             VAR_NAME_SOURCE_FILE_NAME => Ok(Variable::SourceFileName), 
             VAR_NAME_SOURCE_LINE_NR => Ok(Variable::SourceLineNr),
             VAR_NAME_THREAD_ID => Ok(Variable::ThreadId),
             VAR_NAME_THREAD_NAME => Ok(Variable::ThreadName),
+            VAR_NAME_THREAD_RECS_SUPPRESSED => Ok(Variable::ThreadRecordsSuppressed),
+            VAR_NAME_THREAD_RECS_WRITTEN => Ok(Variable::ThreadRecordsWritten),
             VAR_NAME_TIME => Ok(Variable::Time),
+            VAR_NAME_TIME_SLICE => Ok(Variable::TimeSlice(DEFAULT_TIME_SLICE_INTERVAL)),
             VAR_NAME_TIME_STAMP => Ok(Variable::TimeStamp),
+            VAR_NAME_TRACE_ID => Ok(Variable::TraceId),
+            VAR_NAME_UNIT_FIELDS => Ok(Variable::UnitFields),
             _ => Err(false)
         }
     }
@@ -185,26 +300,122 @@ impl Default for VariableMap {
         let mut m = BTreeMap::<&'static str, Variable>::new();
         m.insert(VAR_NAME_APP_ID, Variable::ApplicationId);
         m.insert(VAR_NAME_APP_NAME, Variable::ApplicationName);
+        m.insert(VAR_NAME_ATTR, Variable::Attr(String::from("")));
+        m.insert(VAR_NAME_ATTRS, Variable::Attrs);
+        m.insert(VAR_NAME_BACKTRACE, Variable::Backtrace);
+        m.insert(VAR_NAME_CONTEXT, Variable::Context(String::from("")));
         m.insert(VAR_NAME_DATE, Variable::Date);
         m.insert(VAR_NAME_ENV, Variable::Env(String::from("")));
+        m.insert(VAR_NAME_ENV_FIELDS, Variable::EnvFields);
+        m.insert(VAR_NAME_FUNCTION, Variable::Function);
         m.insert(VAR_NAME_HOST_NAME, Variable::HostName);
         m.insert(VAR_NAME_IP_ADDR, Variable::IpAddress);
         m.insert(VAR_NAME_LEVEL, Variable::Level);
         m.insert(VAR_NAME_LEVEL_ID, Variable::LevelId);
         m.insert(VAR_NAME_MESSAGE, Variable::Message);
+        m.insert(VAR_NAME_MODULE_PATH, Variable::ModulePath);
         m.insert(VAR_NAME_OBSERVER_NAME, Variable::ObserverName);
         m.insert(VAR_NAME_OBSERVER_VALUE, Variable::ObserverValue);
+        m.insert(VAR_NAME_PARENT_RUN_ID, Variable::ParentRunId);
         m.insert(VAR_NAME_PROCESS_ID, Variable::ProcessId);
         m.insert(VAR_NAME_PROCESS_NAME, Variable::ProcessName);
         m.insert(VAR_NAME_PURE_SOURCE_FILE_NAME, Variable::PureSourceFileName);
+        m.insert(VAR_NAME_RUN_ID, Variable::RunId);
+        m.insert(VAR_NAME_SCOPE_STACK, scope_stack_var(None, None));
         m.insert(VAR_NAME_SOURCE_FILE_NAME, Variable::SourceFileName);
         m.insert(VAR_NAME_SOURCE_LINE_NR, Variable::SourceLineNr);
         m.insert(VAR_NAME_THREAD_ID, Variable::ThreadId);
         m.insert(VAR_NAME_THREAD_NAME, Variable::ThreadName);
+        m.insert(VAR_NAME_THREAD_RECS_SUPPRESSED, Variable::ThreadRecordsSuppressed);
+        m.insert(VAR_NAME_THREAD_RECS_WRITTEN, Variable::ThreadRecordsWritten);
         m.insert(VAR_NAME_TIME, Variable::Time);
+        m.insert(VAR_NAME_TIME_SLICE, Variable::TimeSlice(DEFAULT_TIME_SLICE_INTERVAL));
         m.insert(VAR_NAME_TIME_STAMP, Variable::TimeStamp);
+        m.insert(VAR_NAME_TRACE_ID, Variable::TraceId);
+        m.insert(VAR_NAME_UNIT_FIELDS, Variable::UnitFields);
         Self { 0: m }
     }
 }
 
-const ENV_VAR_PATTERN: &str = r"^Env\[(.*)\]$";
+/// Returns a scope stack variable.
+/// 
+/// # Arguments
+/// * `separator` - the optional separator between observer names, defaults to `>`
+/// * `depth` - the optional maximum number of observer names, defaults to no limit
+pub(crate) fn scope_stack_var(separator: Option<&str>, depth: Option<&str>) -> Variable {
+    let sep = separator.unwrap_or(DEFAULT_SCOPE_STACK_SEPARATOR);
+    let depth = depth.and_then(|d| d.parse::<usize>().ok()).unwrap_or(0);
+    Variable::ScopeStack(sep.to_string(), depth)
+}
+
+/// Creates a scope stack variable from the argument of a placeholder variable
+/// $ScopeStack[...], holding the separator optionally followed by a comma and the maximum
+/// depth.
+///
+/// # Arguments
+/// * `arg` - the variable argument between the square brackets
+pub(crate) fn scope_stack_arg_var(arg: &str) -> Variable {
+    match arg.rsplit_once(',') {
+        Some((sep, depth)) if ! depth.is_empty() && depth.bytes().all(|b| b.is_ascii_digit()) => {
+            scope_stack_var(Some(sep), Some(depth))
+        },
+        _ => scope_stack_var(Some(arg), None)
+    }
+}
+
+/// Returns the argument of a placeholder variable with an argument in square brackets,
+/// e.g. `HOME` for `Env[HOME]`. The argument ends at the first closing bracket.
+///
+/// # Arguments
+/// * `s` - the text starting with the variable name, without leading dollar sign
+/// * `var_name` - the variable name
+///
+/// # Return values
+/// the argument and the length of the variable including the brackets; **None**, if the text
+/// doesn't start with the variable name followed by an argument in square brackets
+pub(crate) fn bracketed_arg<'a>(s: &'a str, var_name: &str) -> Option<(&'a str, usize)> {
+    let arg_and_rest = s.strip_prefix(var_name)?.strip_prefix('[')?;
+    let arg_len = arg_and_rest.find(']')?;
+    Some((&arg_and_rest[.. arg_len], var_name.len() + arg_len + 2))
+}
+
+/// Sets the time the values of environment variables read at record time are reused.
+/// All cached values are discarded.
+/// 
+/// # Arguments
+/// * `ttl` - the time to live for cached values in milliseconds, 0 to read the variables anew
+///   for every record
+pub(crate) fn set_record_env_ttl(ttl: u64) {
+    RECORD_ENV_TTL.store(ttl, Ordering::Relaxed);
+    if let Ok(mut cache) = RECORD_ENV_CACHE.lock() { cache.clear(); }
+}
+
+/// Returns the value of an environment variable read at record time.
+/// The variable is read from the process environment, if caching is disabled or the cached
+/// value is older than the configured time to live.
+/// 
+/// # Arguments
+/// * `name` - the environment variable name
+/// 
+/// # Return values
+/// the variable value, **None** if the variable is not defined
+pub(crate) fn record_env_value(name: &str) -> Option<String> {
+    let ttl = RECORD_ENV_TTL.load(Ordering::Relaxed);
+    if ttl == 0 { return std::env::var(name).ok() }
+    let mut cache = match RECORD_ENV_CACHE.lock() {
+        Ok(c) => c,
+        Err(_) => return std::env::var(name).ok()
+    };
+    let now = Instant::now();
+    if let Some((read_at, value)) = cache.get(name) {
+        if now.duration_since(*read_at).as_millis() < ttl as u128 { return value.clone() }
+    }
+    let value = std::env::var(name).ok();
+    cache.insert(name.to_string(), (now, value.clone()));
+    value
+}
+
+const DEFAULT_SCOPE_STACK_SEPARATOR: &str = ">";
+
+/// Default interval in minutes for time slices in file names
+pub(crate) const DEFAULT_TIME_SLICE_INTERVAL: u32 = 60;